@@ -1,17 +1,29 @@
-use super::user_config::UserConfig;
+use super::user_config::{ItemTableColumn, UserConfig};
+use crate::clipboard::{AppClipboard, CopyOutcome};
+use crate::debug_dump;
+use crate::history::{HistoryEntry, HistoryReader};
+use crate::library_cache::{LibraryCache, LibraryIds, VERSION as LIBRARY_CACHE_VERSION};
 use crate::network::IoEvent;
+use crate::notifications::{DoNotDisturb, Notifications, Severity};
+use crate::playback_progress::ProgressEstimate;
+use crate::startup_cache::{StartupCache, VERSION as STARTUP_CACHE_VERSION};
+use crate::ui::album_art::{select_album_art, GraphicsProtocol};
+use crate::ui::cover_accent::cover_accent;
+use crate::ui::help::{context_blocks, get_help_docs};
+use crate::ui::util::{create_artist_string, group_plays_by_day, PlayedRow};
+use crate::ui::view_text::{self, TextFormat};
 use anyhow::anyhow;
-use arboard::Clipboard;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Local, TimeDelta, Utc};
 use derivative::Derivative;
+use rand::{seq::SliceRandom, thread_rng};
 use rspotify::model::{
     album::{FullAlbum, SavedAlbum, SimplifiedAlbum},
     artist::FullArtist,
-    audio::AudioAnalysis,
+    audio::{AudioAnalysis, AudioFeatures},
     context::CurrentPlaybackContext,
-    device::DevicePayload,
-    enums::Country,
-    idtypes::{Id, PlayContextId},
+    device::{Device, DevicePayload},
+    enums::{AlbumType, Country, RepeatState, SearchType, TimeRange},
+    idtypes::{Id, PlayContextId, PlayableId, PlaylistId},
     page::{CursorBasedPage, Page},
     playing::PlayHistory,
     playlist::{PlaylistItem, SimplifiedPlaylist},
@@ -20,24 +32,95 @@ use rspotify::model::{
     user::PrivateUser,
     AlbumId, ArtistId, EpisodeId, PlayableItem, ShowId, TrackId,
 };
-use spotify_tui_util::{PlaybleItemExt, ToStatic};
+use spotify_tui_util::{PlayableIdExt, PlaybleItemExt, ToStatic};
 use std::{
-    cmp::{max, min},
-    collections::HashSet,
-    time::Instant,
+    cmp::{min, Ordering},
+    collections::{HashMap, HashSet, VecDeque},
+    hash::Hash,
+    path::PathBuf,
+    sync::Mutex,
+    time::{Duration, Instant},
 };
 use tokio::sync::mpsc::UnboundedSender;
-use tui::layout::Rect;
+use tui::{layout::Rect, style::Color};
+use unicode_width::UnicodeWidthStr;
 
-pub const LIBRARY_OPTIONS: [&str; 6] = [
+pub const LIBRARY_OPTIONS: [&str; 7] = [
     "Made For You",
     "Recently Played",
     "Liked Songs",
     "Albums",
     "Artists",
     "Podcasts",
+    "Top Items",
 ];
 
+// Right after a seek the API may still report the old progress, so the playback is only
+// refreshed after this delay
+pub const SEEK_REFRESH_DELAY: Duration = Duration::from_millis(1000);
+
+// The seek keys move the position shown straight away, and seek this long after the first press,
+// so that holding one down makes a single seek
+const SEEK_APPLY_DELAY: Duration = Duration::from_millis(500);
+
+// How often the UI ticks at most while nothing on screen moves
+pub const IDLE_TICK_RATE: Duration = Duration::from_millis(500);
+
+// The fewest results a page of a big table fetches, however short the terminal, so that paging
+// through a library doesn't take a request every few rows
+const MIN_LARGE_SEARCH_LIMIT: u32 = 20;
+const MIN_SMALL_SEARCH_LIMIT: u32 = 4;
+// The most the API returns at once
+const MAX_SEARCH_LIMIT: u32 = 50;
+
+// How many results a page fetches for the big tables and for the small ones of the search, as
+// many as show at this height of the terminal
+pub fn search_limits(height: u16) -> (u32, u32) {
+    // What is left for a table once the input, the playbar and their borders are drawn
+    let rows = u32::from(height).saturating_sub(13);
+    let large_search_limit = rows.clamp(MIN_LARGE_SEARCH_LIMIT, MAX_SEARCH_LIMIT);
+    // The search results are a third of that each, in lists with borders
+    let small_search_limit = (rows * 35 / 100)
+        .saturating_sub(2)
+        .clamp(MIN_SMALL_SEARCH_LIMIT, MAX_SEARCH_LIMIT);
+    (large_search_limit, small_search_limit)
+}
+
+// The lines the filter takes above the rows of the help
+pub const HELP_FILTER_HEIGHT: u32 = 3;
+
+// How long the saved state of an item is trusted before it is checked again, e.g. in case it
+// was liked from another client
+pub const SAVED_CHECK_TTL: Duration = Duration::from_secs(60);
+
+// How long a second press of a save/follow key has to remove an item that was already saved
+const REMOVAL_CONFIRM_WINDOW: Duration = Duration::from_secs(3);
+
+// Going back from further into a track than this restarts it instead of going to the previous one
+pub const TRACK_RESTART_MS: u128 = 3_000;
+// Going back from further into a section than this restarts it instead of going to the one
+// before, like going back a track does
+const SECTION_RESTART_MS: u32 = 1_000;
+// How often the device list is refreshed while it is open, so that devices started meanwhile show
+// up
+const DEVICE_POLL_INTERVAL: Duration = Duration::from_secs(3);
+// The most seeds, of any kind, that recommendations accept
+pub const RECOMMENDATION_SEED_LIMIT: usize = 5;
+// How close to the end of the loaded albums the selection gets before the next page is requested
+const ARTIST_ALBUMS_PREFETCH_DISTANCE: usize = 5;
+// How many history entries are read at a time, the same as Spotify's window of recent plays
+const HISTORY_PAGE_SIZE: usize = 50;
+// How many times a startup fetch is made before an error that may pass is reported anyway
+pub const STARTUP_FETCH_ATTEMPTS: u32 = 3;
+// How often the library cache is saved, besides on exit
+const LIBRARY_CACHE_SAVE_INTERVAL: Duration = Duration::from_secs(5 * 60);
+// The wait before retrying a startup fetch, doubled with each retry
+const STARTUP_RETRY_DELAY: Duration = Duration::from_secs(2);
+// How many album art images and playlist covers are kept, the least recently used going first
+const ALBUM_ART_CACHE_SIZE: usize = 32;
+// The wait before an image that couldn't be downloaded is tried again
+const ALBUM_ART_RETRY_DELAY: Duration = Duration::from_secs(60);
+
 const DEFAULT_ROUTE: Route = Route {
     id: RouteId::Home,
     active_block: ActiveBlock::Empty,
@@ -65,6 +148,46 @@ impl<T> ScrollableResultPages<T> {
         // Whenever a new page is added, set the active index to the end of the vector
         self.index = self.pages.len() - 1;
     }
+
+    // Shows the next page if it was already fetched, returning whether it was
+    pub fn next_page(&mut self) -> bool {
+        if self.index + 1 < self.pages.len() {
+            self.index += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn previous_page(&mut self) -> bool {
+        if self.index > 0 {
+            self.index -= 1;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl<T> ScrollableResultPages<Page<T>> {
+    // Where the page after the last one fetched starts, None when there are no more
+    pub fn next_offset(&self) -> Option<u32> {
+        let last = self.pages.last()?;
+        last.next.as_ref()?;
+        Some(last.offset + last.limit)
+    }
+
+    // The number of the page shown, counting from 1, and how many pages there are in total
+    pub fn page_count(&self) -> Option<(u32, u32)> {
+        let page = self.get_results(None)?;
+        let limit = page.limit.max(1);
+        Some((page.offset / limit + 1, page.total.div_ceil(limit).max(1)))
+    }
+
+    // False when nothing is loaded yet, there is no last page to be on
+    pub fn is_last_page(&self) -> bool {
+        self.page_count().is_some_and(|(page, count)| page >= count)
+    }
 }
 
 #[derive(Default)]
@@ -84,7 +207,43 @@ pub struct Library {
     pub show_episodes: ScrollableResultPages<Page<SimplifiedEpisode>>,
 }
 
-#[derive(PartialEq, Debug)]
+// What was loaded from the startup cache and hasn't been fetched again yet
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct StaleData {
+    pub user: bool,
+    pub playlists: bool,
+    pub saved_albums: bool,
+    pub saved_shows: bool,
+}
+
+// Which of the user's playlists are listed, cycled through without refetching
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub enum PlaylistFilter {
+    #[default]
+    All,
+    Owned,
+    Followed,
+}
+
+impl PlaylistFilter {
+    pub fn next(self) -> Self {
+        match self {
+            PlaylistFilter::All => PlaylistFilter::Owned,
+            PlaylistFilter::Owned => PlaylistFilter::Followed,
+            PlaylistFilter::Followed => PlaylistFilter::All,
+        }
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            PlaylistFilter::All => "Playlists",
+            PlaylistFilter::Owned => "Playlists (owned)",
+            PlaylistFilter::Followed => "Playlists (followed)",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub enum SearchResultBlock {
     AlbumSearch,
     SongSearch,
@@ -94,6 +253,46 @@ pub enum SearchResultBlock {
     Empty,
 }
 
+impl SearchResultBlock {
+    pub fn search_type(self) -> Option<SearchType> {
+        match self {
+            SearchResultBlock::AlbumSearch => Some(SearchType::Album),
+            SearchResultBlock::SongSearch => Some(SearchType::Track),
+            SearchResultBlock::ArtistSearch => Some(SearchType::Artist),
+            SearchResultBlock::PlaylistSearch => Some(SearchType::Playlist),
+            SearchResultBlock::ShowSearch => Some(SearchType::Show),
+            SearchResultBlock::Empty => None,
+        }
+    }
+}
+
+// What the artist view can search for beyond the artist's own releases
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ArtistDiscovery {
+    // Playlists with the artist's name in them, such as tour and concert setlists
+    Playlists,
+    // Albums by others the artist is on, such as compilations
+    AppearsOn,
+}
+
+impl ArtistDiscovery {
+    // The name is quoted so that it is searched as a whole rather than word by word
+    pub fn search_term(self, artist_name: &str) -> String {
+        let name = artist_name.replace('"', "");
+        match self {
+            ArtistDiscovery::Playlists => format!("\"{}\"", name),
+            ArtistDiscovery::AppearsOn => format!("artist:\"{}\"", name),
+        }
+    }
+
+    pub fn search_result_block(self) -> SearchResultBlock {
+        match self {
+            ArtistDiscovery::Playlists => SearchResultBlock::PlaylistSearch,
+            ArtistDiscovery::AppearsOn => SearchResultBlock::AlbumSearch,
+        }
+    }
+}
+
 #[derive(PartialEq, Debug, Clone)]
 pub enum ArtistBlock {
     TopTracks,
@@ -132,6 +331,10 @@ pub enum ActiveBlock {
     Artists,
     BasicView,
     Dialog(DialogContext),
+    Lyrics,
+    NotificationHistory,
+    TopArtists,
+    FinishedShows,
 }
 
 #[derive(Clone, PartialEq, Debug)]
@@ -153,6 +356,10 @@ pub enum RouteId {
     PodcastEpisodes,
     Recommendations,
     Dialog,
+    Lyrics,
+    NotificationHistory,
+    TopArtists,
+    FinishedShows,
 }
 
 #[derive(Debug)]
@@ -171,6 +378,7 @@ pub enum ItemTableContext {
     SavedTracks,
     RecommendedTracks,
     MadeForYou,
+    TopTracks,
 }
 
 // Is it possible to compose enums?
@@ -186,957 +394,5494 @@ pub enum EpisodeTableContext {
     Full,
 }
 
+// What the text typed into the input block is used for
 #[derive(Clone, PartialEq, Debug)]
-pub enum RecommendationsContext {
-    Artist,
-    Song,
+pub enum InputContext {
+    Search,
+    RenamePlaylist { playlist_id: PlaylistId<'static> },
+    EditPlaylistDescription { playlist_id: PlaylistId<'static> },
+    // Typed into the filter of the help, which stays open meanwhile
+    FilterHelp,
+    // The name of the device to transfer the playback to, typed in the device list
+    FindDevice,
 }
 
-#[derive(Derivative)]
-#[derivative(Default)]
-pub struct SearchResult {
-    pub albums: Option<Page<SimplifiedAlbum>>,
-    pub artists: Option<Page<FullArtist>>,
-    pub playlists: Option<Page<SimplifiedPlaylist>>,
-    pub tracks: Option<Page<FullTrack>>,
-    pub shows: Option<Page<SimplifiedShow>>,
-    pub episodes: Option<Page<SimplifiedEpisode>>,
-    pub selected_album_index: Option<usize>,
-    pub selected_artists_index: Option<usize>,
-    pub selected_playlists_index: Option<usize>,
-    pub selected_tracks_index: Option<usize>,
-    pub selected_shows_index: Option<usize>,
-    #[derivative(Default(value = "SearchResultBlock::SongSearch"))]
-    pub hovered_block: SearchResultBlock,
-    #[derivative(Default(value = "SearchResultBlock::Empty"))]
-    pub selected_block: SearchResultBlock,
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PlaybarControl {
+    Shuffle,
+    Repeat,
+    Like,
+    Previous,
+    PlayPause,
+    Next,
 }
 
-#[derive(Default)]
-pub struct ItemTable {
-    pub items: Vec<PlayableItem>,
-    pub selected_index: usize,
-    pub context: Option<ItemTableContext>,
+impl PlaybarControl {
+    pub const ALL: [PlaybarControl; 6] = [
+        PlaybarControl::Shuffle,
+        PlaybarControl::Repeat,
+        PlaybarControl::Like,
+        PlaybarControl::Previous,
+        PlaybarControl::PlayPause,
+        PlaybarControl::Next,
+    ];
+
+    fn index(self) -> usize {
+        Self::ALL
+            .iter()
+            .position(|control| *control == self)
+            .unwrap_or_default()
+    }
+
+    pub fn next(self) -> Self {
+        Self::ALL[(self.index() + 1) % Self::ALL.len()]
+    }
+
+    pub fn previous(self) -> Self {
+        Self::ALL[(self.index() + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
 }
 
-#[derive(Clone)]
-pub struct SelectedShow {
-    pub show: SimplifiedShow,
+// Screen areas of the interactive parts of the UI. These are recorded while drawing, which only
+// has shared access to the app, hence the lock around them in `App`.
+#[derive(Default, Debug)]
+pub struct ControlAreas {
+    pub playbar: Vec<(PlaybarControl, Rect)>,
+    pub progress: Option<Rect>,
+    pub lists: Vec<ListArea>,
 }
 
-#[derive(Clone)]
-pub struct SelectedFullShow {
-    pub show: FullShow,
+// The rows of a list or table that are on screen
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ListArea {
+    pub block: ActiveBlock,
+    pub rows: Rect,
+    // The index of the item in the first row
+    pub offset: usize,
+    pub len: usize,
 }
 
-#[derive(Clone)]
-pub struct SelectedAlbum {
-    pub album: SimplifiedAlbum,
-    pub tracks: Page<SimplifiedTrack>,
-    pub selected_index: usize,
+pub enum AlbumArt {
+    Loading,
+    Loaded(Vec<u8>),
+    // When the download failed, which it is tried again a while after
+    Failed(Instant),
 }
 
-#[derive(Clone)]
-pub struct SelectedFullAlbum {
-    pub album: FullAlbum,
-    pub selected_index: usize,
+// Downloaded album art and playlist covers, keyed by image url
+#[derive(Default)]
+pub struct AlbumArtCache {
+    // The least recently used first
+    entries: VecDeque<(String, AlbumArt)>,
 }
 
-#[derive(Clone)]
-pub struct Artist {
-    pub artist_name: String,
-    pub albums: Page<SimplifiedAlbum>,
-    pub related_artists: Vec<FullArtist>,
-    pub top_tracks: Vec<FullTrack>,
-    pub selected_album_index: usize,
-    pub selected_related_artist_index: usize,
-    pub selected_top_track_index: usize,
-    pub artist_hovered_block: ArtistBlock,
-    pub artist_selected_block: ArtistBlock,
+impl AlbumArtCache {
+    fn position(&self, url: &str) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|(entry_url, _)| entry_url == url)
+    }
+
+    pub fn get(&self, url: &str) -> Option<&AlbumArt> {
+        self.entries
+            .iter()
+            .find(|(entry_url, _)| entry_url == url)
+            .map(|(_, album_art)| album_art)
+    }
+
+    pub fn insert(&mut self, url: String, album_art: AlbumArt) {
+        if let Some(index) = self.position(&url) {
+            self.entries.remove(index);
+        }
+        self.entries.push_back((url, album_art));
+        if self.entries.len() > ALBUM_ART_CACHE_SIZE {
+            self.entries.pop_front();
+        }
+    }
+
+    // Marks the url as used, and as loading when it is due a download, which is then returned
+    pub fn start_fetch(&mut self, url: &str, now: Instant) -> bool {
+        let entry = self
+            .position(url)
+            .and_then(|index| self.entries.remove(index));
+        let is_due = match &entry {
+            None => true,
+            Some((_, AlbumArt::Failed(failed_at))) => {
+                now.saturating_duration_since(*failed_at) >= ALBUM_ART_RETRY_DELAY
+            }
+            Some((_, AlbumArt::Loading | AlbumArt::Loaded(_))) => false,
+        };
+        let album_art = match entry {
+            Some((_, album_art)) if !is_due => album_art,
+            _ => AlbumArt::Loading,
+        };
+        self.insert(url.to_string(), album_art);
+        is_due
+    }
 }
 
-#[derive(Derivative)]
-#[derivative(Default)]
-pub struct App {
-    #[derivative(Default(value = "Instant::now()"))]
-    pub instant_since_last_current_playback_poll: Instant,
-    #[derivative(Default(value = "vec![DEFAULT_ROUTE]"))]
-    navigation_stack: Vec<Route>,
-    pub audio_analysis: Option<AudioAnalysis>,
-    pub home_scroll: u16,
-    #[derivative(Default(value = "UserConfig::new()"))]
-    pub user_config: UserConfig,
-    pub artists: Vec<FullArtist>,
-    pub artist: Option<Artist>,
-    #[derivative(Default(value = "AlbumTableContext::Full"))]
-    pub album_table_context: AlbumTableContext,
-    pub saved_album_tracks_index: usize,
-    pub api_error: String,
-    pub current_playback_context: Option<CurrentPlaybackContext>,
-    pub devices: Option<DevicePayload>,
-    // Inputs:
-    // input is the string for input;
-    // input_idx is the index of the cursor in terms of character;
-    // input_cursor_position is the sum of the width of characters preceding the cursor.
-    // Reason for this complication is due to non-ASCII characters, they may
-    // take more than 1 bytes to store and more than 1 character width to display.
-    pub input: Vec<char>,
-    pub input_idx: usize,
-    pub input_cursor_position: u16,
-    pub liked_episode_ids_set: HashSet<EpisodeId<'static>>,
-    pub liked_song_ids_set: HashSet<TrackId<'static>>,
-    pub followed_artist_ids_set: HashSet<ArtistId<'static>>,
-    pub saved_album_ids_set: HashSet<AlbumId<'static>>,
-    pub saved_show_ids_set: HashSet<ShowId<'static>>,
-    #[derivative(Default(value = "20"))]
-    pub large_search_limit: u32,
-    pub library: Library,
-    pub playlist_offset: u32,
-    pub made_for_you_offset: u32,
-    pub playlist_items: Option<Page<PlaylistItem>>,
-    pub made_for_you_playlist_items: Option<Page<PlaylistItem>>,
-    pub playlists: Option<Page<SimplifiedPlaylist>>,
-    pub recently_played: SpotifyResultAndSelectedIndex<Option<CursorBasedPage<PlayHistory>>>,
-    pub recommended_tracks: Vec<FullTrack>,
-    pub recommendations_seed: String,
-    pub recommendations_context: Option<RecommendationsContext>,
-    pub search_results: SearchResult,
-    pub selected_album_simplified: Option<SelectedAlbum>,
-    pub selected_album_full: Option<SelectedFullAlbum>,
-    pub selected_device_index: Option<usize>,
-    pub selected_playlist_index: Option<usize>,
-    pub active_playlist_index: Option<usize>,
-    pub size: Rect,
-    #[allow(dead_code)]
-    #[derivative(Default(value = "4"))]
-    pub small_search_limit: u32,
-    pub song_progress_ms: u128,
-    pub seek_ms: Option<u128>,
-    pub item_table: ItemTable,
-    #[derivative(Default(value = "EpisodeTableContext::Full"))]
-    pub episode_table_context: EpisodeTableContext,
-    pub selected_show_simplified: Option<SelectedShow>,
-    pub selected_show_full: Option<SelectedFullShow>,
-    pub user: Option<PrivateUser>,
-    pub album_list_index: usize,
-    pub made_for_you_index: usize,
-    pub artists_list_index: usize,
-    #[derivative(Default(value = "Clipboard::new().ok()"))]
-    pub clipboard: Option<Clipboard>,
-    pub shows_list_index: usize,
-    pub episode_list_index: usize,
-    pub help_docs_size: u32,
-    pub help_menu_page: u32,
-    pub help_menu_max_lines: u32,
-    pub help_menu_offset: u32,
-    pub is_loading: bool,
-    io_tx: Option<UnboundedSender<IoEvent<'static>>>,
-    pub is_fetching_current_playback: bool,
-    #[derivative(Default(value = "Utc::now()"))]
-    pub spotify_token_expiry: DateTime<Utc>,
-    pub dialog: Option<String>,
-    pub confirm: bool,
+pub enum Lyrics {
+    Loading,
+    Found(String),
+    NotFound,
 }
 
-macro_rules! handle_error {
-    ($self:ident, $result:expr $(, |$err:ident| $err_expr:expr)?) => {
-        match $result {
-            Ok(ok) => ok,
-            Err(err) => {
-                let err = handle_error!(@ err $(, |$err| $err_expr)?);
-                $self.handle_error(err);
-                return;
-            },
-        }
-    };
-    (@ $err_ident:ident) => { anyhow!($err_ident) };
-    (@ $err_ident:ident |$err:ident| $err_expr:expr) => {
-        let $err = $err_ident;
-        $err_expr
-    };
+// Something that can be saved to or followed from the library
+#[derive(Clone, PartialEq, Debug)]
+pub enum LibraryItem {
+    Album(AlbumId<'static>),
+    Artist(ArtistId<'static>),
+    // Whether to follow it publicly
+    Playlist(PlaylistId<'static>, Option<bool>),
+    Show(ShowId<'static>),
 }
 
-impl App {
-    pub fn new(
-        io_tx: UnboundedSender<IoEvent<'static>>,
-        user_config: UserConfig,
-        spotify_token_expiry: DateTime<Utc>,
-    ) -> App {
-        App {
-            io_tx: Some(io_tx),
-            user_config,
-            spotify_token_expiry,
-            ..App::default()
+impl LibraryItem {
+    fn is_saved(&self, app: &App) -> bool {
+        match self {
+            LibraryItem::Album(album_id) => app.saved_album_ids_set.contains(album_id),
+            LibraryItem::Artist(artist_id) => app.followed_artist_ids_set.contains(artist_id),
+            LibraryItem::Playlist(playlist_id, _) => app.is_playlist_followed(playlist_id),
+            LibraryItem::Show(show_id) => app.saved_show_ids_set.contains(show_id),
         }
     }
 
-    // Send a network event to the network thread
-    pub fn dispatch(&mut self, event: IoEvent<'_>) {
-        // `is_loading` will be set to false again after the async action has finished in network.rs
-        self.is_loading = true;
-        if let Some(io_tx) = &self.io_tx {
-            if let Err(err) = io_tx.send(event.to_static()) {
-                self.is_loading = false;
-                println!("Error from dispatch: {err}");
-                // TODO: handle error
-            };
+    fn save_event(self) -> IoEvent<'static> {
+        match self {
+            LibraryItem::Album(album_id) => IoEvent::CurrentUserSavedAlbumAdd { album_id },
+            LibraryItem::Artist(artist_id) => IoEvent::UserFollowArtists {
+                artist_ids: vec![artist_id],
+            },
+            LibraryItem::Playlist(playlist_id, is_public) => IoEvent::UserFollowPlaylist {
+                playlist_id,
+                is_public,
+            },
+            LibraryItem::Show(show_id) => IoEvent::CurrentUserSavedShowAdd { show_id },
         }
     }
 
-    fn apply_seek(&mut self, seek_ms: u32) {
-        if let Some(CurrentPlaybackContext {
-            item: Some(item), ..
-        }) = &self.current_playback_context
-        {
-            let event = if seek_ms < item.duration().num_milliseconds() as u32 {
-                IoEvent::Seek {
-                    position_ms: seek_ms,
-                }
-            } else {
-                IoEvent::NextTrack
-            };
-
-            self.dispatch(event);
+    fn remove_event(self) -> IoEvent<'static> {
+        match self {
+            LibraryItem::Album(album_id) => IoEvent::CurrentUserSavedAlbumDelete { album_id },
+            LibraryItem::Artist(artist_id) => IoEvent::UserUnfollowArtists {
+                artist_ids: vec![artist_id],
+            },
+            LibraryItem::Playlist(playlist_id, _) => IoEvent::UserUnfollowPlaylist { playlist_id },
+            LibraryItem::Show(show_id) => IoEvent::CurrentUserSavedShowDelete { show_id },
         }
     }
+}
 
-    fn poll_current_playback(&mut self) {
-        // Poll every 5 seconds
-        let poll_interval_ms = 5_000;
+// An item that was already in the library when its save key was pressed, which is removed if the
+// key is pressed again before the deadline
+pub struct PendingRemoval {
+    pub item: LibraryItem,
+    pub deadline: Instant,
+}
 
-        let elapsed = self
-            .instant_since_last_current_playback_poll
-            .elapsed()
-            .as_millis();
+// The requests made once the UI is up. Right after a resume the network is often not back yet,
+// so these are retried instead of leaving the sidebar empty.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum StartupFetch {
+    Playlists,
+    User,
+    CurrentPlayback,
+}
 
-        if !self.is_fetching_current_playback && elapsed >= poll_interval_ms {
-            self.is_fetching_current_playback = true;
-            // Trigger the seek if the user has set a new position
-            match self.seek_ms {
-                Some(seek_ms) => self.apply_seek(seek_ms as u32),
-                None => self.dispatch(IoEvent::GetCurrentPlayback),
-            }
+impl StartupFetch {
+    const ALL: [StartupFetch; 3] = [
+        StartupFetch::Playlists,
+        StartupFetch::User,
+        StartupFetch::CurrentPlayback,
+    ];
+
+    fn event(self) -> IoEvent<'static> {
+        match self {
+            StartupFetch::Playlists => IoEvent::GetPlaylists,
+            StartupFetch::User => IoEvent::GetUser,
+            StartupFetch::CurrentPlayback => IoEvent::GetCurrentPlayback,
         }
     }
+}
 
-    pub fn update_on_tick(&mut self) {
-        self.poll_current_playback();
-        if let Some(CurrentPlaybackContext {
-            item: Some(item),
-            progress: Some(progress),
-            is_playing,
-            ..
-        }) = &self.current_playback_context
-        {
-            // Update progress even when the song is not playing,
-            // because seeking is possible while paused
-            let elapsed = if *is_playing {
-                self.instant_since_last_current_playback_poll
-                    .elapsed()
-                    .as_millis()
-            } else {
-                0u128
-            } + progress.num_milliseconds() as u128;
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum StartupFetchState {
+    // The mini player leaves some of them out
+    #[default]
+    NotStarted,
+    InFlight {
+        attempts: u32,
+    },
+    RetryAt {
+        attempts: u32,
+        at: Instant,
+    },
+    Failed,
+    Loaded,
+}
 
-            if elapsed < item.duration().num_milliseconds() as u128 {
-                self.song_progress_ms = elapsed;
-            } else {
-                self.song_progress_ms = item.duration().num_milliseconds() as u128;
-            }
-        }
-    }
+// Where the Recently Played table comes from: Spotify only keeps the last 50 plays, the local
+// history file keeps everything that was played while the app was open
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum RecentlyPlayedSource {
+    Spotify,
+    History,
+}
 
-    pub fn seek_forwards(&mut self) {
-        if let Some(CurrentPlaybackContext {
-            item: Some(item), ..
-        }) = &self.current_playback_context
-        {
-            let old_progress = match self.seek_ms {
-                Some(seek_ms) => seek_ms,
-                None => self.song_progress_ms,
-            };
+// What the Top Items view lists, the tracks in the item table or the artists in their own table
+#[derive(Clone, Copy, PartialEq, Debug, ToStatic)]
+pub enum TopItemKind {
+    Tracks,
+    Artists,
+}
 
-            let new_progress = min(
-                old_progress as u32 + self.user_config.behavior.seek_milliseconds,
-                item.duration().num_milliseconds() as u32,
-            );
+// The user's most played tracks or artists over a time range, a page at a time
+pub struct TopItems {
+    pub kind: TopItemKind,
+    pub time_range: TimeRange,
+    pub tracks: Option<Page<FullTrack>>,
+    pub artists: Option<Page<FullArtist>>,
+    pub selected_artist_index: usize,
+}
 
-            self.seek_ms = Some(new_progress as u128);
+impl Default for TopItems {
+    fn default() -> Self {
+        TopItems {
+            kind: TopItemKind::Tracks,
+            time_range: TimeRange::ShortTerm,
+            tracks: None,
+            artists: None,
+            selected_artist_index: 0,
         }
     }
+}
 
-    pub fn seek_backwards(&mut self) {
-        let old_progress = match self.seek_ms {
-            Some(seek_ms) => seek_ms,
-            None => self.song_progress_ms,
+impl TopItems {
+    // From the shortest range to the longest, and around again
+    pub fn next_time_range(time_range: TimeRange) -> TimeRange {
+        match time_range {
+            TimeRange::ShortTerm => TimeRange::MediumTerm,
+            TimeRange::MediumTerm => TimeRange::LongTerm,
+            TimeRange::LongTerm => TimeRange::ShortTerm,
+        }
+    }
+
+    pub fn title(&self) -> String {
+        let range = match self.time_range {
+            TimeRange::ShortTerm => "last 4 weeks",
+            TimeRange::MediumTerm => "last 6 months",
+            TimeRange::LongTerm => "all time",
         };
-        let new_progress = if old_progress as u32 > self.user_config.behavior.seek_milliseconds {
-            old_progress as u32 - self.user_config.behavior.seek_milliseconds
-        } else {
-            0u32
+        let kind = match self.kind {
+            TopItemKind::Tracks => "Tracks",
+            TopItemKind::Artists => "Artists",
         };
-        self.seek_ms = Some(new_progress as u128);
+        format!("Top {} ({})", kind, range)
     }
 
-    pub fn get_recommendations_for_seed(
-        &mut self,
-        seed_artist_ids: Option<Vec<ArtistId<'_>>>,
-        seed_track_ids: Option<Vec<TrackId<'_>>>,
-        first_track: Option<FullTrack>,
-    ) {
-        let country = self.get_user_country();
-        self.dispatch(IoEvent::GetRecommendationsForSeed {
-            seed_artist_ids,
-            seed_track_ids,
-            country,
-            first_track: Box::new(first_track),
-        });
+    // The offset and limit of the page shown, with the total number of items
+    fn shown_page(&self) -> Option<(u32, u32, u32)> {
+        match self.kind {
+            TopItemKind::Tracks => self
+                .tracks
+                .as_ref()
+                .map(|page| (page.offset, page.limit, page.total)),
+            TopItemKind::Artists => self
+                .artists
+                .as_ref()
+                .map(|page| (page.offset, page.limit, page.total)),
+        }
     }
 
-    pub fn get_recommendations_for_track_id(&mut self, track_id: TrackId<'_>) {
-        let country = self.get_user_country();
-        self.dispatch(IoEvent::GetRecommendationsForTrackId { track_id, country });
+    pub fn next_offset(&self) -> Option<u32> {
+        let (offset, limit, total) = self.shown_page()?;
+        (offset + limit < total).then_some(offset + limit)
     }
 
-    pub fn increase_volume(&mut self) {
-        if let Some(context) = self.current_playback_context.clone() {
-            let current_volume = context.device.volume_percent.unwrap_or_default() as u8;
-            let next_volume = min(
-                current_volume + self.user_config.behavior.volume_increment,
-                100,
-            );
+    pub fn previous_offset(&self) -> Option<u32> {
+        let (offset, limit, _) = self.shown_page()?;
+        (offset > 0).then(|| offset.saturating_sub(limit))
+    }
+}
 
-            if next_volume != current_volume {
-                self.dispatch(IoEvent::ChangeVolume {
-                    volume: next_volume,
-                });
-            }
-        }
+#[derive(Default)]
+pub struct LocalHistory {
+    reader: Option<HistoryReader>,
+    // Newest first, more are read as the selection reaches the end
+    pub entries: Vec<HistoryEntry>,
+    pub index: usize,
+}
+
+pub enum TrackAnalysis {
+    Loading,
+    Loaded(Box<AudioAnalysis>),
+    Failed,
+}
+
+// When the saved state of each id was last checked, so that the same ids aren't checked again
+// every time they show up in a table or the playback is polled
+#[derive(Derivative)]
+#[derivative(Default(bound = ""))]
+pub struct RecentChecks<T> {
+    checked_at: HashMap<T, Instant>,
+}
+
+impl<T: Clone + Eq + Hash> RecentChecks<T> {
+    fn is_recent(&self, id: &T, now: Instant) -> bool {
+        self.checked_at
+            .get(id)
+            .is_some_and(|checked_at| now - *checked_at < SAVED_CHECK_TTL)
     }
 
-    pub fn decrease_volume(&mut self) {
-        if let Some(context) = self.current_playback_context.clone() {
-            let current_volume = context.device.volume_percent.unwrap_or_default() as i8;
-            let next_volume = max(
-                current_volume - self.user_config.behavior.volume_increment as i8,
-                0,
-            );
+    // The ids that haven't been checked recently, without duplicates
+    pub fn unchecked(&self, ids: impl IntoIterator<Item = T>, now: Instant) -> Vec<T> {
+        let mut seen = HashSet::new();
+        ids.into_iter()
+            .filter(|id| !self.is_recent(id, now) && seen.insert(id.clone()))
+            .collect()
+    }
 
-            if next_volume != current_volume {
-                self.dispatch(IoEvent::ChangeVolume {
-                    volume: next_volume as u8,
-                });
-            }
-        }
+    // Whether the id was checked at all, however long ago, which its saved state is known from
+    pub fn has_checked(&self, id: &T) -> bool {
+        self.checked_at.contains_key(id)
     }
 
-    pub fn handle_error(&mut self, e: anyhow::Error) {
-        self.push_navigation_stack(RouteId::Error, ActiveBlock::Error);
-        self.api_error = e.to_string();
+    // So that the next check asks again, when the saved state may not be what was last seen
+    pub fn forget(&mut self, id: &T) {
+        self.checked_at.remove(id);
     }
 
-    pub fn is_playing(&self) -> bool {
-        let Some(CurrentPlaybackContext { is_playing, .. }) = &self.current_playback_context else {
-            return false;
-        };
-        *is_playing
+    pub fn mark_checked(&mut self, ids: impl IntoIterator<Item = T>, now: Instant) {
+        self.checked_at
+            .retain(|_, checked_at| now - *checked_at < SAVED_CHECK_TTL);
+        self.checked_at.extend(ids.into_iter().map(|id| (id, now)));
     }
+}
 
-    pub fn toggle_playback(&mut self) {
-        if let Some(CurrentPlaybackContext {
-            is_playing: true, ..
-        }) = &self.current_playback_context
-        {
-            self.dispatch(IoEvent::PausePlayback);
-        } else {
-            // When no offset or uris are passed, spotify will resume current playback
-            self.dispatch(IoEvent::ResumePlayback);
+#[derive(Clone, PartialEq, Debug)]
+pub enum RecommendationsContext {
+    Artist,
+    Song,
+    LikedSongs,
+}
+
+#[derive(Derivative)]
+#[derivative(Default)]
+pub struct SearchResult {
+    // What was searched for, which more results are fetched for
+    pub search_term: String,
+    pub albums: ScrollableResultPages<Page<SimplifiedAlbum>>,
+    pub artists: ScrollableResultPages<Page<FullArtist>>,
+    pub playlists: ScrollableResultPages<Page<SimplifiedPlaylist>>,
+    pub tracks: ScrollableResultPages<Page<FullTrack>>,
+    pub shows: ScrollableResultPages<Page<SimplifiedShow>>,
+    pub episodes: ScrollableResultPages<Page<SimplifiedEpisode>>,
+    pub selected_album_index: Option<usize>,
+    pub selected_artists_index: Option<usize>,
+    pub selected_playlists_index: Option<usize>,
+    pub selected_tracks_index: Option<usize>,
+    pub selected_shows_index: Option<usize>,
+    #[derivative(Default(value = "SearchResultBlock::SongSearch"))]
+    pub hovered_block: SearchResultBlock,
+    #[derivative(Default(value = "SearchResultBlock::Empty"))]
+    pub selected_block: SearchResultBlock,
+}
+
+impl SearchResult {
+    // New results are nothing like what was selected, so the next Enter mustn't act on whatever
+    // took its place. The hovered block is kept.
+    pub fn clear_selection(&mut self) {
+        self.selected_album_index = None;
+        self.selected_artists_index = None;
+        self.selected_playlists_index = None;
+        self.selected_tracks_index = None;
+        self.selected_shows_index = None;
+        self.selected_block = SearchResultBlock::Empty;
+    }
+
+    // Page keys act on the selected block, or the hovered one when none is
+    fn paged_block(&self) -> SearchResultBlock {
+        match self.selected_block {
+            SearchResultBlock::Empty => self.hovered_block,
+            block => block,
         }
     }
 
-    pub fn resume_playback(&mut self) {
-        if let Some(CurrentPlaybackContext {
-            is_playing: false, ..
-        }) = &self.current_playback_context
-        {
-            self.dispatch(IoEvent::ResumePlayback);
+    // Where the next page of a type of results starts, None when there are no more
+    pub fn next_offset(&self, search_type: SearchType) -> Option<u32> {
+        match search_type {
+            SearchType::Album => self.albums.next_offset(),
+            SearchType::Artist => self.artists.next_offset(),
+            SearchType::Playlist => self.playlists.next_offset(),
+            SearchType::Track => self.tracks.next_offset(),
+            SearchType::Show => self.shows.next_offset(),
+            SearchType::Episode => self.episodes.next_offset(),
         }
     }
 
-    pub fn pause_playback(&mut self) {
-        if let Some(CurrentPlaybackContext {
-            is_playing: true, ..
-        }) = &self.current_playback_context
-        {
-            self.dispatch(IoEvent::PausePlayback);
+    // Shows another page of the block's results if it was already fetched, returning whether
+    // it was
+    fn turn_page(&mut self, block: SearchResultBlock, forwards: bool) -> bool {
+        fn turn<T>(pages: &mut ScrollableResultPages<T>, forwards: bool) -> bool {
+            if forwards {
+                pages.next_page()
+            } else {
+                pages.previous_page()
+            }
+        }
+        let (is_turned, selected_index) = match block {
+            SearchResultBlock::AlbumSearch => (
+                turn(&mut self.albums, forwards),
+                &mut self.selected_album_index,
+            ),
+            SearchResultBlock::SongSearch => (
+                turn(&mut self.tracks, forwards),
+                &mut self.selected_tracks_index,
+            ),
+            SearchResultBlock::ArtistSearch => (
+                turn(&mut self.artists, forwards),
+                &mut self.selected_artists_index,
+            ),
+            SearchResultBlock::PlaylistSearch => (
+                turn(&mut self.playlists, forwards),
+                &mut self.selected_playlists_index,
+            ),
+            SearchResultBlock::ShowSearch => (
+                turn(&mut self.shows, forwards),
+                &mut self.selected_shows_index,
+            ),
+            SearchResultBlock::Empty => return false,
+        };
+        if is_turned {
+            *selected_index = Some(0);
         }
+        is_turned
     }
+}
 
-    pub fn previous_track(&mut self) {
-        if self.song_progress_ms >= 3_000 {
-            self.dispatch(IoEvent::Seek { position_ms: 0 });
-        } else {
-            self.dispatch(IoEvent::PreviousTrack);
+// Whether the artist's name is the search term, ignoring case
+pub fn is_artist_name_match(search_term: &str, artist: &FullArtist) -> bool {
+    artist.name.to_lowercase() == search_term.trim().to_lowercase()
+}
+
+// How many of the artists are named what was searched for. More than one can't be told apart by
+// name, so those are shown with how many follow them.
+pub fn artist_name_match_count(search_term: &str, artists: &[FullArtist]) -> usize {
+    artists
+        .iter()
+        .filter(|artist| is_artist_name_match(search_term, artist))
+        .count()
+}
+
+// Artists named what was searched for come first, the most followed of those first. Everything
+// else is left in the order Spotify ranked it.
+pub fn compare_artists(search_term: &str, a: &FullArtist, b: &FullArtist) -> Ordering {
+    let is_match = |artist| is_artist_name_match(search_term, artist);
+    match (is_match(a), is_match(b)) {
+        (true, true) => b.followers.total.cmp(&a.followers.total),
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+        (false, false) => Ordering::Equal,
+    }
+}
+
+// A search for a name several artists share often ranks a tribute act first, so the artists with
+// the name are ordered by followers instead
+pub fn rank_artists(search_term: &str, artists: &mut [FullArtist]) {
+    if artist_name_match_count(search_term, artists) > 1 {
+        artists.sort_by(|a, b| compare_artists(search_term, a, b));
+    }
+}
+
+// Audio features the item table can be sorted by
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TrackFeature {
+    Tempo,
+    Energy,
+}
+
+impl TrackFeature {
+    pub fn value(&self, features: &AudioFeatures) -> f32 {
+        match self {
+            TrackFeature::Tempo => features.tempo,
+            TrackFeature::Energy => features.energy,
         }
     }
 
-    // The navigation_stack actually only controls the large block to the right of `library` and
-    // `playlists`
-    pub fn push_navigation_stack(
-        &mut self,
-        next_route_id: RouteId,
-        next_active_block: ActiveBlock,
-    ) {
-        if !self
-            .navigation_stack
-            .last()
-            .map(|last_route| last_route.id == next_route_id)
-            .unwrap_or(false)
-        {
-            self.navigation_stack.push(Route {
-                id: next_route_id,
-                active_block: next_active_block,
-                hovered_block: next_active_block,
-            });
+    pub fn label(&self) -> &'static str {
+        match self {
+            TrackFeature::Tempo => "BPM",
+            TrackFeature::Energy => "Energy",
         }
     }
 
-    pub fn pop_navigation_stack(&mut self) -> Option<Route> {
-        if self.navigation_stack.len() == 1 {
-            None
-        } else {
-            self.navigation_stack.pop()
+    pub fn format(&self, features: &AudioFeatures) -> String {
+        match self {
+            TrackFeature::Tempo => format!("{:.0}", features.tempo),
+            TrackFeature::Energy => format!("{:.2}", features.energy),
         }
     }
+}
 
-    pub fn get_current_route(&self) -> &Route {
-        // if for some reason there is no route return the default
-        self.navigation_stack.last().unwrap_or(&DEFAULT_ROUTE)
+// What the item table knows about an item besides the item itself
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct ItemMetadata {
+    // Where the item was in the loaded page, which context offsets go by even once it is sorted
+    pub position: usize,
+    // Only playlists keep when their items were added
+    pub added_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ColumnSort {
+    pub column: ItemTableColumn,
+    pub ascending: bool,
+}
+
+// The sortable value of a column
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum SortKey {
+    Text(String),
+    Number(u64),
+    Time(DateTime<Utc>),
+}
+
+impl SortKey {
+    // None for items that have nothing in the column, such as the popularity of an episode
+    fn of(
+        column: ItemTableColumn,
+        item: &PlayableItem,
+        metadata: &ItemMetadata,
+    ) -> Option<SortKey> {
+        Some(match (column, item) {
+            (ItemTableColumn::Title, item) => SortKey::Text(item.name().to_lowercase()),
+            (ItemTableColumn::Artist, PlayableItem::Track(track)) => {
+                SortKey::Text(create_artist_string(&track.artists).to_lowercase())
+            }
+            (ItemTableColumn::Artist, PlayableItem::Episode(episode)) => {
+                SortKey::Text(episode.show.publisher.to_lowercase())
+            }
+            (ItemTableColumn::Album, PlayableItem::Track(track)) => {
+                SortKey::Text(track.album.name.to_lowercase())
+            }
+            (ItemTableColumn::Album, PlayableItem::Episode(episode)) => {
+                SortKey::Text(episode.show.name.to_lowercase())
+            }
+            (ItemTableColumn::Duration, item) => {
+                SortKey::Number(item.duration().num_milliseconds().max(0) as u64)
+            }
+            (ItemTableColumn::Popularity, PlayableItem::Track(track)) => {
+                SortKey::Number(track.popularity.into())
+            }
+            (ItemTableColumn::Popularity, PlayableItem::Episode(_)) => return None,
+            (ItemTableColumn::AddedAt, _) => SortKey::Time(metadata.added_at?),
+        })
     }
+}
 
-    fn get_current_route_mut(&mut self) -> &mut Route {
-        self.navigation_stack.last_mut().unwrap()
+#[derive(Default)]
+pub struct ItemTable {
+    pub items: Vec<PlayableItem>,
+    // One for each of the items, in the same order
+    metadata: Vec<ItemMetadata>,
+    pub selected_index: usize,
+    pub context: Option<ItemTableContext>,
+    // The feature the items are currently sorted by
+    pub sort: Option<TrackFeature>,
+    // The feature to sort by once the audio features being fetched arrive
+    pub pending_sort: Option<TrackFeature>,
+    // The column the items are currently sorted by
+    pub column_sort: Option<ColumnSort>,
+    // The rows marked to be queued together, by their position in the context so that a sort
+    // keeps them marked
+    pub selected_rows: HashSet<usize>,
+    // Whether the audio features of the selected row are shown over the table
+    pub is_features_popup_open: bool,
+}
+
+impl ItemTable {
+    pub fn set_items(&mut self, items: Vec<PlayableItem>) {
+        self.set_items_added_at(items.into_iter().map(|item| (item, None)).collect());
     }
 
-    pub fn set_current_route_state(
-        &mut self,
-        active_block: Option<ActiveBlock>,
-        hovered_block: Option<ActiveBlock>,
-    ) {
-        let current_route = self.get_current_route_mut();
-        if let Some(active_block) = active_block {
-            current_route.active_block = active_block;
-        }
-        if let Some(hovered_block) = hovered_block {
-            current_route.hovered_block = hovered_block;
-        }
+    // Replace the items with a newly loaded page, in the order of its context
+    pub fn set_items_added_at(&mut self, items: Vec<(PlayableItem, Option<DateTime<Utc>>)>) {
+        (self.items, self.metadata) = items
+            .into_iter()
+            .enumerate()
+            .map(|(position, (item, added_at))| (item, ItemMetadata { position, added_at }))
+            .unzip();
+        self.sort = None;
+        self.pending_sort = None;
+        self.column_sort = None;
+        self.selected_rows.clear();
+        self.is_features_popup_open = false;
     }
 
-    pub fn copy_playing_item_url(&mut self) {
-        let (
-            Some(clipboard),
-            Some(CurrentPlaybackContext {
-                item: Some(item), ..
-            }),
-        ) = (&mut self.clipboard, &self.current_playback_context)
-        else {
-            return;
-        };
-        let Some(playable_id) = item.id() else { return };
-        handle_error!(self, clipboard.set_text(playable_id.uri()));
+    pub fn metadata(&self, index: usize) -> ItemMetadata {
+        self.metadata.get(index).copied().unwrap_or(ItemMetadata {
+            position: index,
+            added_at: None,
+        })
     }
 
-    pub fn copy_playing_item_parent_url(&mut self) {
-        let (
-            Some(clipboard),
-            Some(CurrentPlaybackContext {
-                item: Some(item), ..
-            }),
-        ) = (&mut self.clipboard, &self.current_playback_context)
-        else {
-            return;
-        };
+    // Where the selected item is in its context
+    pub fn selected_position(&self) -> usize {
+        self.metadata(self.selected_index).position
+    }
 
-        let play_context_id = match item {
-            PlayableItem::Track(track) => track.album.id.clone().map(PlayContextId::from),
-            PlayableItem::Episode(episode) => Some(PlayContextId::from(episode.show.id.clone())),
-        };
-        let Some(play_context_id) = play_context_id else {
+    pub fn toggle_row_selection(&mut self) {
+        if self.selected_index >= self.items.len() {
             return;
-        };
+        }
+        let position = self.selected_position();
+        if !self.selected_rows.remove(&position) {
+            self.selected_rows.insert(position);
+        }
+    }
 
-        handle_error!(self, clipboard.set_text(play_context_id.uri()));
+    pub fn is_row_selected(&self, index: usize) -> bool {
+        self.selected_rows.contains(&self.metadata(index).position)
     }
 
-    pub fn set_saved_tracks_to_table(&mut self, saved_track_page: &Page<SavedTrack>) {
-        self.dispatch(IoEvent::SetTracksToTable {
-            tracks: saved_track_page
-                .items
-                .clone()
+    // The marked items, in the order they are shown
+    pub fn selected_items(&self) -> Vec<&PlayableItem> {
+        self.items
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| self.is_row_selected(*index))
+            .map(|(_, item)| item)
+            .collect()
+    }
+
+    // A stable sort, with the metadata carried along and the selection following the selected
+    // item. Items without a key go last.
+    fn sort_by_key<K>(
+        &mut self,
+        key: impl Fn(&PlayableItem, &ItemMetadata) -> Option<K>,
+        compare: impl Fn(&K, &K) -> Ordering,
+    ) {
+        let selected_position = self.selected_position();
+        let metadata = (0..self.items.len())
+            .map(|index| self.metadata(index))
+            .collect::<Vec<_>>();
+        let mut entries = std::mem::take(&mut self.items)
+            .into_iter()
+            .zip(metadata)
+            .map(|(item, metadata)| (key(&item, &metadata), item, metadata))
+            .collect::<Vec<_>>();
+        entries.sort_by(|(a, ..), (b, ..)| match (a, b) {
+            (Some(a), Some(b)) => compare(a, b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        });
+        (self.items, self.metadata) = entries
+            .into_iter()
+            .map(|(_, item, metadata)| (item, metadata))
+            .unzip();
+
+        self.selected_index = self
+            .metadata
+            .iter()
+            .position(|metadata| metadata.position == selected_position)
+            .unwrap_or(0);
+    }
+
+    fn apply_column_sort(&mut self, column_sort: Option<ColumnSort>) {
+        match column_sort {
+            Some(ColumnSort { column, ascending }) => self.sort_by_key(
+                |item, metadata| SortKey::of(column, item, metadata),
+                |a, b| if ascending { a.cmp(b) } else { b.cmp(a) },
+            ),
+            // Back to the order of the context
+            None => self.sort_by_key(|_, metadata| Some(metadata.position), Ord::cmp),
+        }
+        self.column_sort = column_sort;
+        self.sort = None;
+        self.pending_sort = None;
+    }
+}
+
+#[derive(Clone)]
+pub struct SelectedShow {
+    pub show: SimplifiedShow,
+}
+
+#[derive(Clone)]
+pub struct SelectedFullShow {
+    pub show: FullShow,
+}
+
+// The saved podcasts found to be finished, which are removed from the library once confirmed
+pub struct FinishedShows {
+    // Each with whether it's to be removed, which they all are to begin with
+    pub shows: Vec<(SimplifiedShow, bool)>,
+    pub selected_index: usize,
+}
+
+impl FinishedShows {
+    pub fn new(shows: Vec<SimplifiedShow>) -> FinishedShows {
+        FinishedShows {
+            shows: shows.into_iter().map(|show| (show, true)).collect(),
+            selected_index: 0,
+        }
+    }
+
+    pub fn toggle_selected(&mut self) {
+        if let Some((_, is_removed)) = self.shows.get_mut(self.selected_index) {
+            *is_removed = !*is_removed;
+        }
+    }
+
+    pub fn removed_ids(&self) -> Vec<ShowId<'static>> {
+        self.shows
+            .iter()
+            .filter(|(_, is_removed)| *is_removed)
+            .map(|(show, _)| show.id.clone())
+            .collect()
+    }
+}
+
+// The index and id of the device `query` picks: the device with that id, else the first one with
+// that name whatever the case, else the only one with it in its name. Without one, the error lists
+// the devices there are to pick from, or the ones it could be.
+pub fn find_device(devices: &DevicePayload, query: &str) -> anyhow::Result<(usize, String)> {
+    let devices = &devices.devices;
+    let query_lowercase = query.to_lowercase();
+    let names = |indices: &[usize]| {
+        indices
+            .iter()
+            .map(|index| format!("'{}'", devices[*index].name))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let exact = devices
+        .iter()
+        .position(|device| device.id.as_deref() == Some(query))
+        .or_else(|| {
+            devices
+                .iter()
+                .position(|device| device.name.to_lowercase() == query_lowercase)
+        });
+    let index = match exact {
+        Some(index) => index,
+        None => {
+            let matches = devices
+                .iter()
+                .enumerate()
+                .filter(|(_, device)| device.name.to_lowercase().contains(&query_lowercase))
+                .map(|(index, _)| index)
+                .collect::<Vec<_>>();
+            match matches[..] {
+                [index] => index,
+                [] if devices.is_empty() => {
+                    return Err(anyhow!(
+                        "no device with name '{}': no devices available",
+                        query
+                    ))
+                }
+                [] => {
+                    let all = (0..devices.len()).collect::<Vec<_>>();
+                    return Err(anyhow!(
+                        "no device with name '{}', available devices: {}",
+                        query,
+                        names(&all)
+                    ));
+                }
+                _ => {
+                    return Err(anyhow!(
+                        "more than one device matches '{}': {}",
+                        query,
+                        names(&matches)
+                    ))
+                }
+            }
+        }
+    };
+
+    let device = &devices[index];
+    let id = device.id.clone().ok_or_else(|| {
+        anyhow!(
+            "failed to use device with name '{}': no device id",
+            device.name
+        )
+    })?;
+    Ok((index, id))
+}
+
+// Whether every episode on the page has been played to the end. Episodes without a resume point
+// haven't been played, and neither has a show without episodes.
+pub fn is_fully_played(episodes: &Page<SimplifiedEpisode>) -> bool {
+    !episodes.items.is_empty()
+        && episodes.items.iter().all(|episode| {
+            episode
+                .resume_point
+                .as_ref()
+                .is_some_and(|resume_point| resume_point.fully_played)
+        })
+}
+
+#[derive(Clone)]
+pub struct SelectedAlbum {
+    pub album: SimplifiedAlbum,
+    pub tracks: Page<SimplifiedTrack>,
+    pub selected_index: usize,
+}
+
+#[derive(Clone)]
+pub struct SelectedFullAlbum {
+    pub album: FullAlbum,
+    pub selected_index: usize,
+}
+
+#[derive(Clone)]
+pub struct Artist {
+    pub artist_id: ArtistId<'static>,
+    pub artist_name: String,
+    // Loaded a page at a time as the selection nears the end, as some artists have hundreds
+    pub albums: ScrollableResultPages<Page<SimplifiedAlbum>>,
+    // The album group the albums are filtered to, or None for every group
+    pub album_type: Option<AlbumType>,
+    // The offset of the page that has been requested, so that it is only requested once
+    pub pending_albums_offset: Option<u32>,
+    pub related_artists: Vec<FullArtist>,
+    pub top_tracks: Vec<FullTrack>,
+    pub selected_album_index: usize,
+    pub selected_related_artist_index: usize,
+    pub selected_top_track_index: usize,
+    pub artist_hovered_block: ArtistBlock,
+    pub artist_selected_block: ArtistBlock,
+}
+
+impl Artist {
+    // The albums of every loaded page, in order
+    pub fn albums(&self) -> Vec<&SimplifiedAlbum> {
+        self.albums
+            .pages
+            .iter()
+            .flat_map(|page| &page.items)
+            .collect()
+    }
+
+    pub fn selected_album(&self) -> Option<&SimplifiedAlbum> {
+        self.albums().get(self.selected_album_index).copied()
+    }
+
+    // Albums appear in several groups, e.g. as an album and as a compilation, so those that have
+    // been loaded already are left out of the page
+    pub fn add_albums_page(&mut self, mut page: Page<SimplifiedAlbum>) {
+        let mut loaded_ids = self
+            .albums()
+            .into_iter()
+            .filter_map(|album| album.id.clone())
+            .collect::<HashSet<_>>();
+        page.items.retain(|album| {
+            album
+                .id
+                .as_ref()
+                .is_none_or(|id| loaded_ids.insert(id.clone()))
+        });
+        if self.pending_albums_offset == Some(page.offset) {
+            self.pending_albums_offset = None;
+        }
+        self.albums.add_pages(page);
+    }
+
+    // Where the next page starts, None once every page has been loaded
+    pub fn next_albums_offset(&self) -> Option<u32> {
+        let last_page = self.albums.pages.last()?;
+        last_page.next.as_ref()?;
+        Some(last_page.offset + last_page.limit)
+    }
+
+    // How far the page keys move the selection
+    pub fn albums_page_size(&self) -> usize {
+        self.albums
+            .pages
+            .last()
+            .map_or(0, |page| page.limit as usize)
+    }
+
+    // Every group first, then one group at a time, and around again
+    pub fn next_album_type(album_type: Option<AlbumType>) -> Option<AlbumType> {
+        match album_type {
+            None => Some(AlbumType::Album),
+            Some(AlbumType::Album) => Some(AlbumType::Single),
+            Some(AlbumType::Single) => Some(AlbumType::AppearsOn),
+            Some(AlbumType::AppearsOn) => Some(AlbumType::Compilation),
+            Some(AlbumType::Compilation) => None,
+        }
+    }
+
+    pub fn albums_title(&self) -> String {
+        let title = match self.album_type {
+            None => "Albums",
+            Some(AlbumType::Album) => "Albums: album",
+            Some(AlbumType::Single) => "Albums: single",
+            Some(AlbumType::AppearsOn) => "Albums: appears on",
+            Some(AlbumType::Compilation) => "Albums: compilation",
+        };
+        match (self.next_albums_offset(), self.albums.pages.last()) {
+            (Some(loaded), Some(last_page)) => {
+                format!(
+                    "{} ({} of {} releases loaded)",
+                    title, loaded, last_page.total
+                )
+            }
+            _ => String::from(title),
+        }
+    }
+}
+
+// Seeds for a radio, picked at random from all of the ids when there are more than allowed
+pub fn radio_seeds<T: Clone>(ids: &[T]) -> Vec<T> {
+    ids.choose_multiple(&mut thread_rng(), RECOMMENDATION_SEED_LIMIT)
+        .cloned()
+        .collect()
+}
+
+// The mode after `state` when cycling through them
+pub fn next_repeat_state(state: RepeatState) -> RepeatState {
+    match state {
+        RepeatState::Off => RepeatState::Context,
+        RepeatState::Context => RepeatState::Track,
+        RepeatState::Track => RepeatState::Off,
+    }
+}
+
+// How a repeat mode is named in the playbar and in notifications
+pub fn repeat_state_name(state: RepeatState) -> &'static str {
+    match state {
+        RepeatState::Off => "Off",
+        RepeatState::Track => "Track",
+        RepeatState::Context => "All",
+    }
+}
+
+// Whether a track or episode is in the library, or None until it's checked. Only what the library
+// holds is known without a check.
+pub fn is_item_saved(app: &App, id: &PlayableId<'static>) -> Option<bool> {
+    let (is_saved, is_checked) = match id {
+        PlayableId::Track(id) => (
+            app.liked_song_ids_set.contains(id),
+            app.checked_track_ids.has_checked(id),
+        ),
+        PlayableId::Episode(id) => (
+            app.liked_episode_ids_set.contains(id),
+            app.checked_episode_ids.has_checked(id),
+        ),
+    };
+    (is_saved || is_checked).then_some(is_saved)
+}
+
+fn set_saved<T: Eq + Hash>(ids: &mut LibraryIds<T>, id: T, is_saved: bool) {
+    if is_saved {
+        ids.insert(id);
+    } else {
+        ids.remove(&id);
+    }
+}
+
+// Whether the id is saved once flipped
+fn flip_saved<T: Eq + Hash>(ids: &mut LibraryIds<T>, id: T) -> bool {
+    let is_saved = !ids.contains(&id);
+    set_saved(ids, id, is_saved);
+    is_saved
+}
+
+pub fn owner_name(playlist: &SimplifiedPlaylist) -> &str {
+    playlist
+        .owner
+        .display_name
+        .as_deref()
+        .unwrap_or(playlist.owner.id.id())
+}
+
+// Whether an item restricted to `available_markets` can be played in the user's country. No
+// markets means no restriction, and an unknown country is given the benefit of the doubt.
+pub fn is_available_in(available_markets: &[String], country: Option<Country>) -> bool {
+    match country {
+        Some(country) if !available_markets.is_empty() => {
+            let code: &'static str = country.into();
+            available_markets.iter().any(|market| market == code)
+        }
+        _ => true,
+    }
+}
+
+#[derive(Derivative)]
+#[derivative(Default)]
+pub struct App {
+    // When the progress of the current playback was measured, which it is extrapolated from
+    #[derivative(Default(value = "Instant::now()"))]
+    pub instant_since_last_current_playback_poll: Instant,
+    // Spotify's timestamp of the current playback, older responses are out of date
+    playback_timestamp: Option<DateTime<Utc>>,
+    // When the playback was last changed locally, responses to requests sent before are out of date
+    playback_changed_at: Option<Instant>,
+    #[derivative(Default(value = "vec![DEFAULT_ROUTE]"))]
+    navigation_stack: Vec<Route>,
+    // Only the analysis of the track that was playing when it was requested is kept
+    pub audio_analysis: Option<(TrackId<'static>, TrackAnalysis)>,
+    pub audio_features: HashMap<TrackId<'static>, AudioFeatures>,
+    // Tracks whose features were asked for one at a time and haven't arrived yet
+    pub loading_track_features: HashSet<TrackId<'static>>,
+    // Only set when album art is enabled and the terminal can display images
+    pub album_art_protocol: Option<GraphicsProtocol>,
+    pub album_art: AlbumArtCache,
+    // Only set when cover accents are enabled and the terminal shows 24-bit color
+    pub is_cover_accent_enabled: bool,
+    // The accent of each playlist cover downloaded, None when it has no color to use
+    pub cover_accents: HashMap<String, Option<Color>>,
+    pub lyrics: HashMap<TrackId<'static>, Lyrics>,
+    pub lyrics_scroll: u16,
+    #[derivative(Default(value = "PlaybarControl::PlayPause"))]
+    pub playbar_control: PlaybarControl,
+    pub control_areas: Mutex<ControlAreas>,
+    pub home_scroll: u16,
+    #[derivative(Default(value = "UserConfig::new()"))]
+    pub user_config: UserConfig,
+    pub artists: Vec<FullArtist>,
+    pub artist: Option<Artist>,
+    #[derivative(Default(value = "AlbumTableContext::Full"))]
+    pub album_table_context: AlbumTableContext,
+    pub saved_album_tracks_index: usize,
+    pub api_error: String,
+    pub current_playback_context: Option<CurrentPlaybackContext>,
+    pub devices: Option<DevicePayload>,
+    // The device playback is transferred to on startup, from the client config
+    pub saved_device_id: Option<String>,
+    // When the device list was last refreshed while it was open
+    devices_polled_at: Option<Instant>,
+    // Indexed by `StartupFetch`
+    startup_fetches: [StartupFetchState; 3],
+    // Inputs:
+    // input is the string for input;
+    // input_idx is the index of the cursor in terms of character;
+    // input_cursor_position is the sum of the width of characters preceding the cursor.
+    // Reason for this complication is due to non-ASCII characters, they may
+    // take more than 1 bytes to store and more than 1 character width to display.
+    pub input: Vec<char>,
+    pub input_idx: usize,
+    pub input_cursor_position: u16,
+    #[derivative(Default(value = "InputContext::Search"))]
+    pub input_context: InputContext,
+    pub notifications: Notifications,
+    pub notification_history_index: usize,
+    // Names of playlists that have been optimistically renamed, keyed by the playlist being
+    // renamed, so that they can be restored if the request fails
+    pub pending_playlist_renames: HashMap<PlaylistId<'static>, String>,
+    // Whether the ids being liked or unliked were shown as liked once pressed, until Spotify
+    // answers, so that another press meanwhile is ignored and a wrong guess can be put right
+    pub pending_track_saves: HashMap<TrackId<'static>, bool>,
+    pub pending_episode_saves: HashMap<EpisodeId<'static>, bool>,
+    pub liked_episode_ids_set: LibraryIds<EpisodeId<'static>>,
+    pub liked_song_ids_set: LibraryIds<TrackId<'static>>,
+    pub followed_artist_ids_set: LibraryIds<ArtistId<'static>>,
+    pub saved_album_ids_set: LibraryIds<AlbumId<'static>>,
+    pub saved_show_ids_set: LibraryIds<ShowId<'static>>,
+    // Where the ids above are kept between sessions, None for the CLI which starts from scratch
+    pub library_cache_path: Option<PathBuf>,
+    library_cache_save_at: Option<Instant>,
+    // How many times the cache was saved, which exiting waits on
+    pub library_cache_saves: usize,
+    // Where the startup cache is saved on exit, only set with `behavior.enable_cache`
+    pub startup_cache_path: Option<PathBuf>,
+    pub stale: StaleData,
+    pub checked_track_ids: RecentChecks<TrackId<'static>>,
+    pub checked_episode_ids: RecentChecks<EpisodeId<'static>>,
+    pub checked_album_ids: RecentChecks<AlbumId<'static>>,
+    pub checked_show_ids: RecentChecks<ShowId<'static>>,
+    pub pending_removal: Option<PendingRemoval>,
+    #[derivative(Default(value = "20"))]
+    pub large_search_limit: u32,
+    pub library: Library,
+    pub playlist_offset: u32,
+    pub made_for_you_offset: u32,
+    pub playlist_items: Option<Page<PlaylistItem>>,
+    pub made_for_you_playlist_items: Option<Page<PlaylistItem>>,
+    pub playlists: Option<Page<SimplifiedPlaylist>>,
+    pub recently_played: SpotifyResultAndSelectedIndex<Option<CursorBasedPage<PlayHistory>>>,
+    #[derivative(Default(value = "RecentlyPlayedSource::Spotify"))]
+    pub recently_played_source: RecentlyPlayedSource,
+    pub local_history: LocalHistory,
+    pub history_path: Option<PathBuf>,
+    // Where the JSON of the selected item is dumped, only set with `--debug`
+    pub debug_dump_dir: Option<PathBuf>,
+    // The last item written to the history, so that polling the same playback records it once
+    last_recorded_uri: Option<String>,
+    pub recommended_tracks: Vec<FullTrack>,
+    pub recommendations_seed: String,
+    pub recommendations_context: Option<RecommendationsContext>,
+    pub search_results: SearchResult,
+    pub selected_album_simplified: Option<SelectedAlbum>,
+    pub selected_album_full: Option<SelectedFullAlbum>,
+    pub selected_device_index: Option<usize>,
+    // Indexes all of the playlists, whichever are listed
+    pub selected_playlist_index: Option<usize>,
+    pub active_playlist_index: Option<usize>,
+    pub playlist_filter: PlaylistFilter,
+    pub size: Rect,
+    #[allow(dead_code)]
+    #[derivative(Default(value = "4"))]
+    pub small_search_limit: u32,
+    pub song_progress_ms: u128,
+    pub progress_estimate: ProgressEstimate,
+    pub seek_ms: Option<u128>,
+    // When the position picked with the seek keys is sought to, whatever the tick rate
+    pub seek_apply_at: Option<Instant>,
+    pub item_table: ItemTable,
+    #[derivative(Default(value = "EpisodeTableContext::Full"))]
+    pub episode_table_context: EpisodeTableContext,
+    pub selected_show_simplified: Option<SelectedShow>,
+    pub selected_show_full: Option<SelectedFullShow>,
+    pub user: Option<PrivateUser>,
+    pub album_list_index: usize,
+    pub made_for_you_index: usize,
+    pub artists_list_index: usize,
+    pub top_items: TopItems,
+    pub clipboard: AppClipboard,
+    pub shows_list_index: usize,
+    // From when the saved podcasts are looked through for finished ones until they have been, or
+    // until it's cancelled
+    pub is_finding_finished_shows: bool,
+    pub finished_shows: Option<FinishedShows>,
+    pub episode_list_index: usize,
+    pub help_menu_page: u32,
+    pub help_menu_max_lines: u32,
+    pub help_menu_offset: u32,
+    // The block the help was opened from, whose keys it lists first
+    pub help_context: Option<ActiveBlock>,
+    // Only the rows of the help with this in their description or key are listed
+    pub help_filter: String,
+    pub is_loading: bool,
+    // Started with `--mini`, which only shows the playback and only fetches what it needs
+    pub is_mini_player: bool,
+    // Leaves the playbar out of the main layout, see `behavior.hide_playbar`
+    pub is_playbar_hidden: bool,
+    // Another instance of the TUI was running first, so this one polls less often
+    pub is_secondary_instance: bool,
+    io_tx: Option<UnboundedSender<IoEvent<'static>>>,
+    pub is_fetching_current_playback: bool,
+    // How many polls in a row found nothing playing, each of which makes the next one wait longer
+    pub idle_playback_polls: u32,
+    // When the playback should be refreshed ahead of the regular poll, e.g. after a seek
+    pub playback_refresh_at: Option<Instant>,
+    // The playing item that has reached its end, whose end has been refreshed at already
+    ended_item_uri: Option<String>,
+    // Shown in the playbar once the playing item has ended, until a poll has what plays next
+    pub upcoming_item: Option<PlayableItem>,
+    #[derivative(Default(value = "Utc::now()"))]
+    pub spotify_token_expiry: DateTime<Utc>,
+    // From when the token is found to have expired until it's refreshed
+    pub is_refreshing_token: bool,
+    pub dialog: Option<String>,
+    pub confirm: bool,
+}
+
+impl App {
+    pub fn new(
+        io_tx: UnboundedSender<IoEvent<'static>>,
+        user_config: UserConfig,
+        spotify_token_expiry: DateTime<Utc>,
+    ) -> App {
+        App {
+            io_tx: Some(io_tx),
+            is_playbar_hidden: user_config.behavior.hide_playbar,
+            user_config,
+            spotify_token_expiry,
+            ..App::default()
+        }
+    }
+
+    // Areas are recorded again by every draw, so that views that aren't shown anymore can't be
+    // clicked
+    pub fn clear_control_areas(&self) {
+        if let Ok(mut control_areas) = self.control_areas.lock() {
+            *control_areas = ControlAreas::default();
+        }
+    }
+
+    // The cache is loaded before anything is fetched, and saved every few minutes from then on
+    pub fn set_library_cache_path(&mut self, path: PathBuf, now: Instant) {
+        self.library_cache_path = Some(path);
+        self.library_cache_save_at = Some(now + LIBRARY_CACHE_SAVE_INTERVAL);
+        self.dispatch(IoEvent::LoadLibraryCache);
+    }
+
+    fn save_library_cache_if_due(&mut self, now: Instant) {
+        if self.library_cache_save_at.is_some_and(|at| now >= at) {
+            self.library_cache_save_at = Some(now + LIBRARY_CACHE_SAVE_INTERVAL);
+            self.dispatch(IoEvent::SaveLibraryCache);
+        }
+    }
+
+    pub fn library_cache(&self) -> LibraryCache {
+        fn uris<T: Id + Eq + std::hash::Hash>(ids: &LibraryIds<T>) -> Vec<String> {
+            let mut uris = ids.iter().map(Id::uri).collect::<Vec<_>>();
+            uris.sort();
+            uris
+        }
+        LibraryCache {
+            version: LIBRARY_CACHE_VERSION,
+            liked_songs: uris(&self.liked_song_ids_set),
+            liked_episodes: uris(&self.liked_episode_ids_set),
+            followed_artists: uris(&self.followed_artist_ids_set),
+            saved_albums: uris(&self.saved_album_ids_set),
+            saved_shows: uris(&self.saved_show_ids_set),
+        }
+    }
+
+    // Marks what the cache holds as in the library until it's checked, leaving out uris that
+    // aren't of the right type
+    pub fn load_library_cache(&mut self, cache: LibraryCache) {
+        fn load<'a, T: Eq + std::hash::Hash>(
+            ids: &mut LibraryIds<T>,
+            uris: &'a [String],
+            parse: impl Fn(&'a str) -> Option<T>,
+        ) {
+            for id in uris.iter().filter_map(|uri| parse(uri)) {
+                ids.insert_stale(id);
+            }
+        }
+        load(&mut self.liked_song_ids_set, &cache.liked_songs, |uri| {
+            TrackId::from_uri(uri).ok().map(TrackId::into_static)
+        });
+        load(
+            &mut self.liked_episode_ids_set,
+            &cache.liked_episodes,
+            |uri| EpisodeId::from_uri(uri).ok().map(EpisodeId::into_static),
+        );
+        load(
+            &mut self.followed_artist_ids_set,
+            &cache.followed_artists,
+            |uri| ArtistId::from_uri(uri).ok().map(ArtistId::into_static),
+        );
+        load(&mut self.saved_album_ids_set, &cache.saved_albums, |uri| {
+            AlbumId::from_uri(uri).ok().map(AlbumId::into_static)
+        });
+        load(&mut self.saved_show_ids_set, &cache.saved_shows, |uri| {
+            ShowId::from_uri(uri).ok().map(ShowId::into_static)
+        });
+    }
+
+    pub fn startup_cache(&self) -> StartupCache {
+        StartupCache {
+            version: STARTUP_CACHE_VERSION,
+            user: self.user.clone(),
+            playlists: self.playlists.clone(),
+            saved_albums: self.library.saved_albums.pages.first().cloned(),
+            saved_shows: self.library.saved_shows.pages.first().cloned(),
+        }
+    }
+
+    // Shows what the last session fetched until it is fetched again, which only happens before
+    // the first render
+    pub fn load_startup_cache(&mut self, cache: StartupCache) {
+        if let Some(user) = cache.user {
+            self.user = Some(user);
+            self.stale.user = true;
+        }
+        if let Some(playlists) = cache.playlists {
+            self.playlists = Some(playlists);
+            self.selected_playlist_index =
+                self.visible_playlist_indices().first().copied().or(Some(0));
+            self.stale.playlists = true;
+        }
+        if let Some(saved_albums) = cache.saved_albums {
+            self.library.saved_albums.add_pages(saved_albums);
+            self.stale.saved_albums = true;
+        }
+        if let Some(saved_shows) = cache.saved_shows {
+            self.library.saved_shows.add_pages(saved_shows);
+            self.stale.saved_shows = true;
+        }
+    }
+
+    // Switch the Recently Played table between Spotify and the local history, which is read from
+    // the end again so that it includes the latest plays
+    // The rows of the recently played table, grouped by day again each time they're drawn so that
+    // they follow what has been loaded since
+    pub fn recently_played_rows(&self) -> Vec<PlayedRow> {
+        let now = Local::now();
+        match self.recently_played_source {
+            RecentlyPlayedSource::Spotify => group_plays_by_day(
+                self.recently_played
+                    .result
+                    .iter()
+                    .flat_map(|page| &page.items)
+                    .map(|item| item.played_at),
+                &now,
+            ),
+            RecentlyPlayedSource::History => group_plays_by_day(
+                self.local_history
+                    .entries
+                    .iter()
+                    .map(|entry| entry.played_at),
+                &now,
+            ),
+        }
+    }
+
+    pub fn toggle_recently_played_source(&mut self) {
+        if self.recently_played_source == RecentlyPlayedSource::History {
+            self.recently_played_source = RecentlyPlayedSource::Spotify;
+            return;
+        }
+
+        let Some(path) = self.history_path.clone() else {
+            return;
+        };
+        if !path.exists() {
+            self.notify(
+                Severity::Info,
+                "Nothing has been recorded yet, `record_history` enables the local history",
+            );
+            return;
+        }
+        match HistoryReader::open(&path) {
+            Ok(reader) => {
+                self.local_history = LocalHistory {
+                    reader: Some(reader),
+                    ..LocalHistory::default()
+                };
+                self.recently_played_source = RecentlyPlayedSource::History;
+                self.load_more_history();
+            }
+            Err(e) => self.notify(
+                Severity::Error,
+                format!("Could not read {}: {}", path.display(), e),
+            ),
+        }
+    }
+
+    pub fn load_more_history(&mut self) {
+        let Some(reader) = self.local_history.reader.as_mut() else {
+            return;
+        };
+        let entries = match reader.next_page(HISTORY_PAGE_SIZE) {
+            Ok(entries) => entries,
+            Err(e) => {
+                self.local_history.reader = None;
+                self.notify(
+                    Severity::Error,
+                    format!("Could not read the history: {}", e),
+                );
+                return;
+            }
+        };
+        if reader.is_exhausted() {
+            self.local_history.reader = None;
+        }
+
+        let track_ids = entries
+            .iter()
+            .filter_map(|entry| TrackId::from_uri(&entry.uri).ok())
+            .map(TrackId::into_static)
+            .collect::<Vec<_>>();
+        self.local_history.entries.extend(entries);
+        if !track_ids.is_empty() {
+            self.dispatch(IoEvent::CurrentUserSavedTracksContains { track_ids });
+        }
+    }
+
+    // What to append to the history when a new item has started playing
+    pub fn take_history_entry(&mut self, played_at: DateTime<Utc>) -> Option<HistoryEntry> {
+        if !self.user_config.behavior.record_history {
+            return None;
+        }
+        let context = self
+            .current_playback_context
+            .as_ref()
+            .filter(|context| context.is_playing)?;
+        let item = context.item.as_ref()?;
+        let (uri, artist) = match item {
+            PlayableItem::Track(track) => (
+                track.id.as_ref()?.uri(),
+                create_artist_string(&track.artists),
+            ),
+            PlayableItem::Episode(episode) => (episode.id.uri(), episode.show.name.clone()),
+        };
+        if self.last_recorded_uri.as_ref() == Some(&uri) {
+            return None;
+        }
+
+        self.last_recorded_uri = Some(uri.clone());
+        Some(HistoryEntry {
+            played_at,
+            uri,
+            title: item.name().to_string(),
+            artist,
+            context_uri: context.context.as_ref().map(|context| context.uri.clone()),
+        })
+    }
+
+    pub fn start_mini_player(&mut self) {
+        self.is_mini_player = true;
+        // The only route, so that going back quits
+        self.navigation_stack = vec![Route {
+            id: RouteId::BasicView,
+            active_block: ActiveBlock::BasicView,
+            hovered_block: ActiveBlock::BasicView,
+        }];
+    }
+
+    pub fn toggle_playbar(&mut self) {
+        self.is_playbar_hidden = !self.is_playbar_hidden;
+        // Nothing is left to select when the playbar was
+        if self.is_playbar_hidden && self.get_current_route().hovered_block == ActiveBlock::PlayBar
+        {
+            self.set_current_route_state(Some(ActiveBlock::Empty), Some(ActiveBlock::MyPlaylists));
+        }
+    }
+
+    // The requests made once the UI is up. The mini player leaves out the playlists and the user,
+    // which only the full layout uses.
+    pub fn dispatch_startup_events(&mut self) {
+        if !self.is_mini_player {
+            self.make_startup_fetch(StartupFetch::Playlists, 1);
+            self.make_startup_fetch(StartupFetch::User, 1);
+        }
+        self.make_startup_fetch(StartupFetch::CurrentPlayback, 1);
+        if self.user_config.behavior.auto_select_device {
+            self.dispatch(IoEvent::GetDevices { silent: true });
+        }
+    }
+
+    fn make_startup_fetch(&mut self, fetch: StartupFetch, attempts: u32) {
+        self.startup_fetches[fetch as usize] = StartupFetchState::InFlight { attempts };
+        self.dispatch(fetch.event());
+    }
+
+    pub fn startup_fetch_state(&self, fetch: StartupFetch) -> StartupFetchState {
+        self.startup_fetches[fetch as usize]
+    }
+
+    pub fn on_startup_fetch_loaded(&mut self, fetch: StartupFetch) {
+        self.startup_fetches[fetch as usize] = StartupFetchState::Loaded;
+    }
+
+    // Schedules a retry with exponential backoff when the error may pass. Returns whether the
+    // error was taken care of, otherwise it is for the caller to report.
+    pub fn on_startup_fetch_failed(
+        &mut self,
+        fetch: StartupFetch,
+        is_transient: bool,
+        now: Instant,
+    ) -> bool {
+        let state = &mut self.startup_fetches[fetch as usize];
+        match *state {
+            StartupFetchState::InFlight { attempts }
+                if is_transient && attempts < STARTUP_FETCH_ATTEMPTS =>
+            {
+                *state = StartupFetchState::RetryAt {
+                    attempts,
+                    at: now + STARTUP_RETRY_DELAY * 2u32.pow(attempts - 1),
+                };
+                true
+            }
+            StartupFetchState::InFlight { .. } => {
+                *state = StartupFetchState::Failed;
+                false
+            }
+            // The playback is polled meanwhile, the retry is already on its way
+            StartupFetchState::RetryAt { .. } => true,
+            // Only the first load is retried, later requests fail as usual
+            StartupFetchState::NotStarted
+            | StartupFetchState::Failed
+            | StartupFetchState::Loaded => false,
+        }
+    }
+
+    fn retry_due_startup_fetches(&mut self, now: Instant) {
+        for fetch in StartupFetch::ALL {
+            if let StartupFetchState::RetryAt { attempts, at } = self.startup_fetch_state(fetch) {
+                if now >= at {
+                    self.make_startup_fetch(fetch, attempts + 1);
+                }
+            }
+        }
+    }
+
+    // Start the fetches that gave up over again, for when the network is back
+    pub fn retry_startup_fetches(&mut self) {
+        for fetch in StartupFetch::ALL {
+            if matches!(
+                self.startup_fetch_state(fetch),
+                StartupFetchState::Failed | StartupFetchState::RetryAt { .. }
+            ) {
+                self.make_startup_fetch(fetch, 1);
+            }
+        }
+    }
+
+    // Send a network event to the network thread
+    pub fn dispatch(&mut self, event: IoEvent<'_>) {
+        // `is_loading` will be set to false again after the async action has finished in network.rs
+        self.is_loading = true;
+        // Back to polling often, so that the playbar catches up with what the user just did
+        if event.is_playback_control() {
+            self.idle_playback_polls = 0;
+        }
+        if let Some(io_tx) = &self.io_tx {
+            if let Err(err) = io_tx.send(event.to_static()) {
+                self.is_loading = false;
+                println!("Error from dispatch: {err}");
+                // TODO: handle error
+            };
+        }
+    }
+
+    fn apply_seek(&mut self, seek_ms: u32) {
+        if let Some(CurrentPlaybackContext {
+            item: Some(item), ..
+        }) = &self.current_playback_context
+        {
+            let event = if seek_ms < item.duration().num_milliseconds() as u32 {
+                IoEvent::Seek {
+                    position_ms: seek_ms,
+                }
+            } else {
+                IoEvent::NextTrack
+            };
+
+            self.dispatch(event);
+        }
+    }
+
+    // Three times as long while another instance polls too, and doubled for each poll in a row
+    // that found nothing playing, up to the configured max
+    pub fn playback_poll_interval(&self) -> Duration {
+        let behavior = &self.user_config.behavior;
+        let mut base = behavior.playback_poll_interval_seconds;
+        if self.is_secondary_instance {
+            base *= 3;
+        }
+        let max = behavior.playback_poll_max_interval_seconds.max(base);
+        let back_off = 2u64.saturating_pow(self.idle_playback_polls);
+        Duration::from_secs(base.saturating_mul(back_off).min(max))
+    }
+
+    fn poll_current_playback(&mut self) {
+        let poll_interval = self.playback_poll_interval();
+        let elapsed = self.instant_since_last_current_playback_poll.elapsed();
+
+        let is_refresh_due = self
+            .playback_refresh_at
+            .is_some_and(|refresh_at| Instant::now() >= refresh_at);
+
+        if !self.is_fetching_current_playback && (elapsed >= poll_interval || is_refresh_due) {
+            self.is_fetching_current_playback = true;
+            // Trigger the seek if the user has set a new position
+            match self.seek_ms {
+                Some(seek_ms) => {
+                    self.seek_apply_at = None;
+                    self.apply_seek(seek_ms as u32);
+                }
+                None => self.dispatch(IoEvent::GetCurrentPlayback),
+            }
+        }
+    }
+
+    pub fn apply_due_seek(&mut self, now: Instant) {
+        if self.seek_apply_at.is_none_or(|apply_at| now < apply_at) {
+            return;
+        }
+        self.seek_apply_at = None;
+        // A poll may have sought to it already
+        if let Some(seek_ms) = self.seek_ms {
+            self.apply_seek(seek_ms as u32);
+        }
+    }
+
+    // The configured tick rate while something on screen moves, and a slower one otherwise, which
+    // spares redrawing a still screen. Key presses redraw straight away either way.
+    pub fn tick_rate(&self) -> Duration {
+        let configured = Duration::from_millis(self.user_config.behavior.tick_rate_milliseconds);
+        let is_animating =
+            self.is_playing() || self.get_current_route().active_block == ActiveBlock::Analysis;
+        if is_animating {
+            configured
+        } else {
+            configured.max(IDLE_TICK_RATE)
+        }
+    }
+
+    // Called once a seek has gone through. The progress is updated straight away and the playback
+    // is refreshed a little later, so that rapid seeks share a single refresh.
+    pub fn on_seek(&mut self, position_ms: u32) {
+        if let Some(context) = &mut self.current_playback_context {
+            context.progress = Some(TimeDelta::milliseconds(position_ms as i64));
+        }
+        self.instant_since_last_current_playback_poll = Instant::now();
+        self.song_progress_ms = position_ms as u128;
+        // Keep a seek that the user has made since this one was sent
+        if self.seek_ms == Some(position_ms as u128) {
+            self.seek_ms = None;
+        }
+        self.is_fetching_current_playback = false;
+        self.playback_refresh_at = Some(Instant::now() + SEEK_REFRESH_DELAY);
+    }
+
+    fn poll_devices(&mut self, now: Instant) {
+        if self.get_current_route().active_block != ActiveBlock::SelectDevice {
+            self.devices_polled_at = None;
+            return;
+        }
+        if self
+            .devices_polled_at
+            .is_some_and(|polled_at| now.duration_since(polled_at) < DEVICE_POLL_INTERVAL)
+        {
+            return;
+        }
+        // The list was fetched when it was opened
+        if self.devices_polled_at.replace(now).is_some() {
+            self.dispatch(IoEvent::GetDevices { silent: true });
+        }
+    }
+
+    // Replace the device list, keeping the selection on the same device so that the cursor
+    // doesn't jump around as devices come and go. A new selection starts on the saved device.
+    pub fn set_devices(&mut self, devices: Vec<Device>) {
+        let position = |id: &String| {
+            devices
+                .iter()
+                .position(|device| device.id.as_ref() == Some(id))
+        };
+        let selected_device_id = self
+            .selected_device_index
+            .zip(self.devices.as_ref())
+            .and_then(|(index, payload)| payload.devices.get(index)?.id.as_ref());
+
+        self.selected_device_index = selected_device_id
+            .and_then(position)
+            .or_else(|| self.saved_device_id.as_ref().and_then(position))
+            .or(if devices.is_empty() { None } else { Some(0) });
+        self.devices = Some(DevicePayload { devices });
+    }
+
+    pub fn update_on_tick(&mut self) {
+        self.poll_current_playback();
+        self.poll_devices(Instant::now());
+        self.retry_due_startup_fetches(Instant::now());
+        self.notifications.expire(Local::now());
+        self.expire_pending_removal(Instant::now());
+        self.save_library_cache_if_due(Instant::now());
+        for error in self.clipboard.command_errors() {
+            self.notify(
+                Severity::Error,
+                format!("Clipboard command failed: {}", error),
+            );
+        }
+        self.update_progress(Instant::now());
+        self.refresh_at_item_end(Instant::now());
+    }
+
+    // The playback is refreshed as soon as the playing item ends, rather than at the next poll,
+    // once for each item so that it isn't asked again on every tick until something else plays
+    fn refresh_at_item_end(&mut self, now: Instant) {
+        let Some(CurrentPlaybackContext {
+            item: Some(item),
+            is_playing: true,
+            ..
+        }) = &self.current_playback_context
+        else {
+            return;
+        };
+        let item_uri = item.id().map(|id| id.uri());
+        if self.song_progress_ms < item.duration().num_milliseconds() as u128
+            || item_uri.is_none()
+            || self.ended_item_uri == item_uri
+        {
+            return;
+        }
+
+        self.ended_item_uri = item_uri;
+        self.upcoming_item = self.next_in_item_table();
+        self.playback_refresh_at = Some(now);
+        self.poll_current_playback();
+    }
+
+    // The item after the playing one, when the item table shows the context playing in the order
+    // it plays in. Shuffle and repeat play something else next.
+    fn next_in_item_table(&self) -> Option<PlayableItem> {
+        let playback = self.current_playback_context.as_ref()?;
+        if playback.shuffle_state || playback.repeat_state != RepeatState::Off {
+            return None;
+        }
+        let context_uri = &playback.context.as_ref()?.uri;
+        let table = &self.item_table;
+        if table.sort.is_some() || table.column_sort.is_some() {
+            return None;
+        }
+        let is_table_playing = match table.context.as_ref()? {
+            ItemTableContext::SavedTracks => context_uri.ends_with(":collection"),
+            _ => self
+                .item_table_playlist()
+                .is_some_and(|playlist| playlist.id.uri() == *context_uri),
+        };
+        if !is_table_playing {
+            return None;
+        }
+
+        let playing_id = playback.item.as_ref()?.id()?;
+        let index = table
+            .items
+            .iter()
+            .position(|item| item.id().as_ref() == Some(&playing_id))?;
+        table.items.get(index + 1).cloned()
+    }
+
+    fn update_progress(&mut self, now: Instant) {
+        if let Some(CurrentPlaybackContext {
+            item: Some(item),
+            progress,
+            is_playing,
+            ..
+        }) = &self.current_playback_context
+        {
+            // Update progress even when the song is not playing,
+            // because seeking is possible while paused
+            let known_ms = progress.map(|progress| {
+                let elapsed = if *is_playing {
+                    now.saturating_duration_since(self.instant_since_last_current_playback_poll)
+                        .as_millis()
+                } else {
+                    0u128
+                };
+                elapsed + progress.num_milliseconds() as u128
+            });
+
+            self.song_progress_ms = self.progress_estimate.update(
+                item.id().map(|id| id.uri()),
+                known_ms,
+                *is_playing,
+                item.duration().num_milliseconds() as u128,
+                now,
+            );
+        }
+    }
+
+    pub fn seek_forwards(&mut self) {
+        if let Some(CurrentPlaybackContext {
+            item: Some(item), ..
+        }) = &self.current_playback_context
+        {
+            let old_progress = match self.seek_ms {
+                Some(seek_ms) => seek_ms,
+                None => self.song_progress_ms,
+            };
+
+            let new_progress = min(
+                old_progress as u32 + self.user_config.behavior.seek_milliseconds,
+                item.duration().num_milliseconds() as u32,
+            );
+
+            self.seek_ms = Some(new_progress as u128);
+            self.seek_apply_at
+                .get_or_insert(Instant::now() + SEEK_APPLY_DELAY);
+        }
+    }
+
+    pub fn seek_backwards(&mut self) {
+        let old_progress = match self.seek_ms {
+            Some(seek_ms) => seek_ms,
+            None => self.song_progress_ms,
+        };
+        let new_progress = if old_progress as u32 > self.user_config.behavior.seek_milliseconds {
+            old_progress as u32 - self.user_config.behavior.seek_milliseconds
+        } else {
+            0u32
+        };
+        self.seek_ms = Some(new_progress as u128);
+        self.seek_apply_at
+            .get_or_insert(Instant::now() + SEEK_APPLY_DELAY);
+    }
+
+    pub fn get_recommendations_for_seed(
+        &mut self,
+        seed_artist_ids: Option<Vec<ArtistId<'_>>>,
+        seed_track_ids: Option<Vec<TrackId<'_>>>,
+        first_track: Option<FullTrack>,
+    ) {
+        let country = self.get_user_country();
+        self.dispatch(IoEvent::GetRecommendationsForSeed {
+            seed_artist_ids,
+            seed_track_ids,
+            country,
+            first_track: Box::new(first_track),
+        });
+    }
+
+    // A radio from the liked songs that have been loaded, or from the most recent ones when none
+    // have been
+    pub fn start_liked_songs_radio(&mut self) {
+        self.recommendations_context = Some(RecommendationsContext::LikedSongs);
+        let track_ids = self
+            .library
+            .saved_tracks
+            .pages
+            .iter()
+            .flat_map(|page| &page.items)
+            .filter_map(|saved_track| saved_track.track.id.clone())
+            .collect::<Vec<_>>();
+
+        if track_ids.is_empty() {
+            let country = self.get_user_country();
+            self.dispatch(IoEvent::GetRecommendationsForLikedSongs { country });
+        } else {
+            self.get_recommendations_for_seed(None, Some(radio_seeds(&track_ids)), None);
+        }
+    }
+
+    pub fn get_recommendations_for_track_id(&mut self, track_id: TrackId<'_>) {
+        let country = self.get_user_country();
+        self.dispatch(IoEvent::GetRecommendationsForTrackId { track_id, country });
+    }
+
+    pub fn increase_volume(&mut self) {
+        self.change_volume(self.user_config.behavior.volume_increment as i16);
+    }
+
+    pub fn decrease_volume(&mut self) {
+        self.change_volume(-(self.user_config.behavior.volume_increment as i16));
+    }
+
+    pub fn increase_volume_big(&mut self) {
+        self.change_volume(self.user_config.behavior.volume_big_increment as i16);
+    }
+
+    pub fn decrease_volume_big(&mut self) {
+        self.change_volume(-(self.user_config.behavior.volume_big_increment as i16));
+    }
+
+    // Change the volume relative to the current device volume, clamping it to 0 - 100
+    pub fn change_volume(&mut self, delta: i16) {
+        if let Some(context) = &self.current_playback_context {
+            let current_volume = context.device.volume_percent.unwrap_or_default() as i16;
+            let next_volume = (current_volume + delta).clamp(0, 100);
+
+            if next_volume != current_volume {
+                self.dispatch(IoEvent::ChangeVolume {
+                    volume: next_volume as u8,
+                });
+            }
+        }
+    }
+
+    // Refreshes the token once it has expired, one refresh at a time
+    pub fn refresh_token_if_expired(&mut self, now: DateTime<Utc>) {
+        if now > self.spotify_token_expiry && !self.is_refreshing_token {
+            self.is_refreshing_token = true;
+            self.dispatch(IoEvent::RefreshAuthentication);
+        }
+    }
+
+    pub fn handle_error(&mut self, e: anyhow::Error) {
+        self.push_navigation_stack(RouteId::Error, ActiveBlock::Error);
+        self.api_error = e.to_string();
+    }
+
+    // The smallest image of the current playback item that is still large enough to display
+    pub fn album_art_url(&self) -> Option<String> {
+        let images = match self.current_playback_context.as_ref()?.item.as_ref()? {
+            PlayableItem::Track(track) => &track.album.images,
+            PlayableItem::Episode(episode) => &episode.images,
+        };
+        select_album_art(images).map(|image| image.url.clone())
+    }
+
+    pub fn fetch_album_art(&mut self) {
+        if self.album_art_protocol.is_none() {
+            return;
+        }
+        if let Some(url) = self.album_art_url() {
+            if self.album_art.start_fetch(&url, Instant::now()) {
+                self.dispatch(IoEvent::GetAlbumArt { url });
+            }
+        }
+    }
+
+    // The cover of the playlist in the item table, when its accent is wanted
+    fn item_table_cover_url(&self) -> Option<String> {
+        if !self.is_cover_accent_enabled {
+            return None;
+        }
+        let images = &self.item_table_playlist()?.images;
+        select_album_art(images).map(|image| image.url.clone())
+    }
+
+    // Downloads the cover of the playlist in the item table, or works out its accent once it has
+    // been downloaded
+    pub fn fetch_item_table_cover(&mut self) {
+        let Some(url) = self.item_table_cover_url() else {
+            return;
+        };
+        if self.album_art.start_fetch(&url, Instant::now()) {
+            self.dispatch(IoEvent::GetAlbumArt { url });
+        } else if let Some(AlbumArt::Loaded(bytes)) = self.album_art.get(&url) {
+            self.cover_accents
+                .entry(url)
+                .or_insert_with(|| cover_accent(bytes));
+        }
+    }
+
+    // The color the item table is drawn in for the playlist it shows, if its cover has one
+    pub fn item_table_accent(&self) -> Option<Color> {
+        let url = self.item_table_cover_url()?;
+        *self.cover_accents.get(&url)?
+    }
+
+    pub fn current_album_art(&self) -> Option<(String, &[u8])> {
+        let url = self.album_art_url()?;
+        match self.album_art.get(&url)? {
+            AlbumArt::Loaded(bytes) => Some((url, bytes)),
+            AlbumArt::Loading | AlbumArt::Failed(_) => None,
+        }
+    }
+
+    pub fn playing_track(&self) -> Option<&FullTrack> {
+        match self.current_playback_context.as_ref()?.item.as_ref()? {
+            PlayableItem::Track(track) => Some(track),
+            PlayableItem::Episode(_) => None,
+        }
+    }
+
+    pub fn fetch_lyrics(&mut self) {
+        let Some(track) = self.playing_track() else {
+            return;
+        };
+        let Some(track_id) = track.id.clone() else {
+            return;
+        };
+        if self.lyrics.contains_key(&track_id) {
+            return;
+        }
+        let title = track.name.clone();
+        let artist = track
+            .artists
+            .first()
+            .map(|artist| artist.name.clone())
+            .unwrap_or_default();
+        self.lyrics.insert(track_id.clone(), Lyrics::Loading);
+        self.dispatch(IoEvent::GetLyrics {
+            track_id,
+            artist,
+            title,
+        });
+    }
+
+    pub fn show_lyrics(&mut self) {
+        self.fetch_lyrics();
+        if self.get_current_route().id != RouteId::Lyrics {
+            self.lyrics_scroll = 0;
+            self.push_navigation_stack(RouteId::Lyrics, ActiveBlock::Lyrics);
+        }
+    }
+
+    // Nothing when an episode or a track without an id is playing, as there are no lyrics for it
+    pub fn current_lyrics(&self) -> Option<&Lyrics> {
+        let track_id = self.playing_track()?.id.as_ref()?;
+        self.lyrics.get(track_id)
+    }
+
+    pub fn notify(&mut self, severity: Severity, message: impl Into<String>) {
+        self.notifications
+            .push(severity, message.into(), Local::now());
+    }
+
+    pub fn toggle_do_not_disturb(&mut self) {
+        let do_not_disturb = if self.notifications.is_do_not_disturb() {
+            DoNotDisturb::Off
+        } else {
+            DoNotDisturb::On
+        };
+        self.notifications.set_do_not_disturb(do_not_disturb);
+    }
+
+    pub fn show_notification_history(&mut self) {
+        if self.get_current_route().id != RouteId::NotificationHistory {
+            self.notification_history_index = 0;
+            self.push_navigation_stack(
+                RouteId::NotificationHistory,
+                ActiveBlock::NotificationHistory,
+            );
+        }
+    }
+
+    pub fn copy_selected_notification(&mut self) {
+        if let Some(notification) = self
+            .notifications
+            .get_newest(self.notification_history_index)
+        {
+            let message = notification.message.clone();
+            self.copy_to_clipboard(message);
+        }
+    }
+
+    // Replace the contents of the input block, placing the cursor at the end
+    pub fn set_input(&mut self, text: &str) {
+        self.input = text.chars().collect();
+        self.input_idx = self.input.len();
+        self.input_cursor_position = UnicodeWidthStr::width(text).try_into().unwrap_or(u16::MAX);
+    }
+
+    pub fn is_playing(&self) -> bool {
+        let Some(CurrentPlaybackContext { is_playing, .. }) = &self.current_playback_context else {
+            return false;
+        };
+        *is_playing
+    }
+
+    // Take in a polled playback, unless it is out of date. Returns whether it was taken.
+    pub fn set_current_playback(
+        &mut self,
+        context: CurrentPlaybackContext,
+        requested_at: Instant,
+        received_at: Instant,
+        now: DateTime<Utc>,
+    ) -> bool {
+        if self
+            .playback_changed_at
+            .is_some_and(|changed_at| requested_at < changed_at)
+            || self
+                .playback_timestamp
+                .is_some_and(|timestamp| context.timestamp < timestamp)
+        {
+            return false;
+        }
+
+        // Spotify measured the progress at some point during the request. Its timestamp says when,
+        // but is only trusted when it falls within the request, as the clocks may differ and it is
+        // sometimes when the playback last changed instead. Otherwise it is taken to be half way.
+        let latency = received_at.saturating_duration_since(requested_at);
+        let measured_at = (now - context.timestamp)
+            .to_std()
+            .ok()
+            .filter(|age| *age <= latency)
+            .and_then(|age| received_at.checked_sub(age))
+            .unwrap_or(requested_at + latency / 2);
+
+        // Until the ended item has been replaced
+        let is_ended = context.is_playing
+            && context
+                .item
+                .as_ref()
+                .and_then(|item| item.id())
+                .map(|id| id.uri())
+                == self.ended_item_uri;
+        if !is_ended {
+            self.upcoming_item = None;
+        }
+        self.instant_since_last_current_playback_poll = measured_at;
+        self.playback_timestamp = Some(context.timestamp);
+        self.current_playback_context = Some(context);
+        self.update_progress(received_at);
+        true
+    }
+
+    // Stop the progress straight away, rather than once a poll finds the playback paused
+    fn pause_locally(&mut self, now: Instant) {
+        self.update_progress(now);
+        if let Some(context) = &mut self.current_playback_context {
+            context.is_playing = false;
+            context.progress = Some(TimeDelta::milliseconds(self.song_progress_ms as i64));
+        }
+        self.playback_changed_at = Some(now);
+    }
+
+    pub fn toggle_playback(&mut self) {
+        if let Some(CurrentPlaybackContext {
+            is_playing: true, ..
+        }) = &self.current_playback_context
+        {
+            self.pause_locally(Instant::now());
+            self.dispatch(IoEvent::PausePlayback);
+        } else {
+            // When no offset or uris are passed, spotify will resume current playback
+            self.dispatch(IoEvent::ResumePlayback);
+        }
+    }
+
+    pub fn toggle_save_playing_item(&mut self) {
+        if let Some(CurrentPlaybackContext {
+            item: Some(item), ..
+        }) = self.current_playback_context.to_owned()
+        {
+            match item {
+                PlayableItem::Track(track) => {
+                    if let Some(track_id) = track.id {
+                        self.toggle_save_track(track_id);
+                    }
+                }
+                PlayableItem::Episode(episode) => self.toggle_save_episode(episode.id),
+            };
+        };
+    }
+
+    // The liked icon changes straight away, and is put right once Spotify has answered if that
+    // turns out to be wrong. Pressing again before then does nothing, as the two requests could
+    // otherwise be answered in either order.
+    pub fn toggle_save_track(&mut self, track_id: TrackId<'static>) {
+        if self.pending_track_saves.contains_key(&track_id) {
+            return;
+        }
+        let is_saved = flip_saved(&mut self.liked_song_ids_set, track_id.clone());
+        self.pending_track_saves.insert(track_id.clone(), is_saved);
+        self.dispatch(IoEvent::ToggleSaveTrack { track_id });
+    }
+
+    pub fn toggle_save_episode(&mut self, episode_id: EpisodeId<'static>) {
+        if self.pending_episode_saves.contains_key(&episode_id) {
+            return;
+        }
+        let is_saved = flip_saved(&mut self.liked_episode_ids_set, episode_id.clone());
+        self.pending_episode_saves
+            .insert(episode_id.clone(), is_saved);
+        self.dispatch(IoEvent::ToggleSaveEpisode { episode_id });
+    }
+
+    // Spotify toggled the track, which is now saved or not. It disagrees with what was shown
+    // when the track had been liked or unliked elsewhere since it was last checked.
+    pub fn confirm_track_save(&mut self, track_id: TrackId<'_>, is_saved: bool) {
+        let track_id = track_id.into_static();
+        let shown = self.pending_track_saves.remove(&track_id);
+        set_saved(&mut self.liked_song_ids_set, track_id, is_saved);
+        match (shown, is_saved) {
+            (Some(shown), true) if shown != is_saved => self.notify(
+                Severity::Warning,
+                "The song had been removed from Liked Songs elsewhere, so it was saved again",
+            ),
+            (Some(shown), false) if shown != is_saved => self.notify(
+                Severity::Warning,
+                "The song was in Liked Songs already, so it was removed",
+            ),
+            (_, true) => self.notify(Severity::Info, "Saved to Liked Songs"),
+            (_, false) => self.notify(Severity::Info, "Removed from Liked Songs"),
+        }
+    }
+
+    // The toggle failed, so the liked icon goes back to what it was before the press
+    pub fn revert_track_save(&mut self, track_id: TrackId<'_>) {
+        let track_id = track_id.into_static();
+        if let Some(shown) = self.pending_track_saves.remove(&track_id) {
+            set_saved(&mut self.liked_song_ids_set, track_id.clone(), !shown);
+        }
+        // Known again after the next check
+        self.checked_track_ids.forget(&track_id);
+    }
+
+    pub fn revert_episode_save(&mut self, episode_id: EpisodeId<'_>) {
+        let episode_id = episode_id.into_static();
+        if let Some(shown) = self.pending_episode_saves.remove(&episode_id) {
+            set_saved(&mut self.liked_episode_ids_set, episode_id, !shown);
+        }
+    }
+
+    // Saves or removes the album of the playing track, or follows or unfollows the show of the
+    // playing episode
+    pub fn toggle_save_playing_item_parent(&mut self) {
+        let Some(item) = self
+            .current_playback_context
+            .as_ref()
+            .and_then(|context| context.item.clone())
+        else {
+            return;
+        };
+        if let Some((event, message)) = self.playing_item_parent_save(&item) {
+            self.dispatch(event);
+            self.notify(Severity::Info, message);
+        }
+    }
+
+    // What to send for the album or show of the item, and what to tell the user. When whether it
+    // is saved isn't known yet, it is only checked, so that the next press does the right thing.
+    fn playing_item_parent_save(&self, item: &PlayableItem) -> Option<(IoEvent<'static>, String)> {
+        match item {
+            PlayableItem::Track(track) => {
+                let album_id = track.album.id.clone()?.into_static();
+                let name = &track.album.name;
+                let is_saved = self.saved_album_ids_set.contains(&album_id);
+                let is_known = (is_saved && !self.saved_album_ids_set.is_stale(&album_id))
+                    || self.checked_album_ids.has_checked(&album_id);
+                Some(if !is_known {
+                    (
+                        IoEvent::CurrentUserSavedAlbumsContains {
+                            album_ids: vec![album_id],
+                        },
+                        format!(
+                            "Checking whether '{}' is in your library, press again",
+                            name
+                        ),
+                    )
+                } else if is_saved {
+                    (
+                        IoEvent::CurrentUserSavedAlbumDelete { album_id },
+                        format!("Removing the album '{}' from your library", name),
+                    )
+                } else {
+                    (
+                        IoEvent::CurrentUserSavedAlbumAdd { album_id },
+                        format!("Saving the album '{}' to your library", name),
+                    )
+                })
+            }
+            PlayableItem::Episode(episode) => {
+                let show_id = episode.show.id.clone().into_static();
+                let name = &episode.show.name;
+                let is_saved = self.saved_show_ids_set.contains(&show_id);
+                let is_known = (is_saved && !self.saved_show_ids_set.is_stale(&show_id))
+                    || self.checked_show_ids.has_checked(&show_id);
+                Some(if !is_known {
+                    (
+                        IoEvent::CurrentUserSavedShowsContains {
+                            show_ids: vec![show_id],
+                        },
+                        format!("Checking whether you follow '{}', press again", name),
+                    )
+                } else if is_saved {
+                    (
+                        IoEvent::CurrentUserSavedShowDelete { show_id },
+                        format!("Unfollowing the podcast '{}'", name),
+                    )
+                } else {
+                    (
+                        IoEvent::CurrentUserSavedShowAdd { show_id },
+                        format!("Following the podcast '{}'", name),
+                    )
+                })
+            }
+        }
+    }
+
+    // Queues the tracks of an album or playlist, up to the configured limit
+    pub fn queue_context(&mut self, play_context_id: PlayContextId<'static>) {
+        self.dispatch(IoEvent::AddContextToQueue {
+            play_context_id,
+            limit: self.user_config.behavior.queue_context_limit,
+        });
+    }
+
+    pub fn resume_playback(&mut self) {
+        if let Some(CurrentPlaybackContext {
+            is_playing: false, ..
+        }) = &self.current_playback_context
+        {
+            self.dispatch(IoEvent::ResumePlayback);
+        }
+    }
+
+    pub fn pause_playback(&mut self) {
+        if let Some(CurrentPlaybackContext {
+            is_playing: true, ..
+        }) = &self.current_playback_context
+        {
+            self.pause_locally(Instant::now());
+            self.dispatch(IoEvent::PausePlayback);
+        }
+    }
+
+    pub fn previous_track(&mut self) {
+        if self.song_progress_ms >= TRACK_RESTART_MS {
+            self.dispatch(IoEvent::Seek { position_ms: 0 });
+        } else {
+            self.dispatch(IoEvent::PreviousTrack);
+        }
+    }
+
+    // The navigation_stack actually only controls the large block to the right of `library` and
+    // `playlists`
+    pub fn push_navigation_stack(
+        &mut self,
+        next_route_id: RouteId,
+        next_active_block: ActiveBlock,
+    ) {
+        if !self
+            .navigation_stack
+            .last()
+            .map(|last_route| last_route.id == next_route_id)
+            .unwrap_or(false)
+        {
+            self.navigation_stack.push(Route {
+                id: next_route_id,
+                active_block: next_active_block,
+                hovered_block: next_active_block,
+            });
+        }
+    }
+
+    pub fn pop_navigation_stack(&mut self) -> Option<Route> {
+        if self.navigation_stack.len() == 1 {
+            None
+        } else {
+            self.navigation_stack.pop()
+        }
+    }
+
+    pub fn get_current_route(&self) -> &Route {
+        // if for some reason there is no route return the default
+        self.navigation_stack.last().unwrap_or(&DEFAULT_ROUTE)
+    }
+
+    fn get_current_route_mut(&mut self) -> &mut Route {
+        self.navigation_stack.last_mut().unwrap()
+    }
+
+    pub fn set_current_route_state(
+        &mut self,
+        active_block: Option<ActiveBlock>,
+        hovered_block: Option<ActiveBlock>,
+    ) {
+        let current_route = self.get_current_route_mut();
+        if let Some(active_block) = active_block {
+            current_route.active_block = active_block;
+        }
+        if let Some(hovered_block) = hovered_block {
+            current_route.hovered_block = hovered_block;
+        }
+    }
+
+    pub fn copy_playing_item_url(&mut self) {
+        let Some(CurrentPlaybackContext {
+            item: Some(item), ..
+        }) = &self.current_playback_context
+        else {
+            return;
+        };
+        let Some(playable_id) = item.id() else { return };
+        let uri = playable_id.uri();
+        self.copy_to_clipboard(uri);
+    }
+
+    pub fn copy_playing_item_parent_url(&mut self) {
+        let Some(CurrentPlaybackContext {
+            item: Some(item), ..
+        }) = &self.current_playback_context
+        else {
+            return;
+        };
+
+        let play_context_id = match item {
+            PlayableItem::Track(track) => track.album.id.clone().map(PlayContextId::from),
+            PlayableItem::Episode(episode) => Some(PlayContextId::from(episode.show.id.clone())),
+        };
+        let Some(play_context_id) = play_context_id else {
+            return;
+        };
+
+        let uri = play_context_id.uri();
+        self.copy_to_clipboard(uri);
+    }
+
+    // Selects the row of what is playing in the open table. When a playlist is playing from
+    // another of its pages, that page is looked for and shown instead.
+    pub fn jump_to_playing_item(&mut self) {
+        let Some(playing_id) = self
+            .current_playback_context
+            .as_ref()
+            .and_then(|context| context.item.as_ref()?.id())
+            .map(|id| id.into_static())
+        else {
+            self.notify(Severity::Info, "Nothing is playing");
+            return;
+        };
+
+        let is_found = match self.get_current_route().active_block {
+            ActiveBlock::AlbumTracks => self.select_playing_album_track(&playing_id),
+            _ => {
+                self.select_playing_table_item(&playing_id)
+                    || self.jump_to_playing_playlist_item(playing_id)
+            }
+        };
+        if !is_found {
+            self.notify(Severity::Info, "What's playing isn't in this table");
+        }
+    }
+
+    fn select_playing_album_track(&mut self, playing_id: &PlayableId) -> bool {
+        let position = |tracks: &[SimplifiedTrack]| {
+            tracks.iter().position(|track| {
+                track
+                    .id
+                    .as_ref()
+                    .is_some_and(|id| PlayableId::Track(id.as_ref()).equals(playing_id))
+            })
+        };
+        match self.album_table_context {
+            AlbumTableContext::Full => {
+                let Some(selected) = &self.selected_album_full else {
+                    return false;
+                };
+                let Some(index) = position(&selected.album.tracks.items) else {
+                    return false;
+                };
+                self.saved_album_tracks_index = index;
+            }
+            AlbumTableContext::Simplified => {
+                let Some(selected) = &mut self.selected_album_simplified else {
+                    return false;
+                };
+                let Some(index) = position(&selected.tracks.items) else {
+                    return false;
+                };
+                selected.selected_index = index;
+            }
+        }
+        true
+    }
+
+    fn select_playing_table_item(&mut self, playing_id: &PlayableId) -> bool {
+        let Some(index) = self
+            .item_table
+            .items
+            .iter()
+            .position(|item| item.id().is_some_and(|id| id.equals(playing_id)))
+        else {
+            return false;
+        };
+        self.item_table.selected_index = index;
+        true
+    }
+
+    // Looks through the other pages of the open playlist, when it is what's playing
+    fn jump_to_playing_playlist_item(&mut self, playable_id: PlayableId<'static>) -> bool {
+        if self.item_table.context != Some(ItemTableContext::MyPlaylists) {
+            return false;
+        }
+        let Some(playlist) = self.active_playlist() else {
+            return false;
+        };
+        let is_playing_playlist = self
+            .current_playback_context
+            .as_ref()
+            .and_then(|context| context.context.as_ref())
+            .is_some_and(|context| context.uri == playlist.id.uri());
+        if !is_playing_playlist || playlist.tracks.total <= self.large_search_limit {
+            return false;
+        }
+        let playlist_id = playlist.id.clone();
+        self.dispatch(IoEvent::JumpToPlaylistItem {
+            playlist_id,
+            playable_id,
+        });
+        true
+    }
+
+    pub fn copy_view_as_text(&mut self, format: TextFormat) {
+        match view_text::focused_rows(self) {
+            Some(rows) if !rows.is_empty() => {
+                let text = view_text::format_rows(&rows, format);
+                self.copy_to_clipboard(text);
+            }
+            Some(_) => self.notify(Severity::Info, "There is nothing loaded to copy"),
+            None => self.notify(Severity::Info, "Only tables of songs can be copied"),
+        }
+    }
+
+    // When neither the clipboard nor the configured clipboard command can take the text, show it
+    // instead so that it can still be copied by hand
+    // Writes what the API returned for the selected item to a file, and copies its path
+    pub fn dump_selected_item(&mut self) {
+        let Some(dir) = self.debug_dump_dir.clone() else {
+            return;
+        };
+        let Some(dump) = debug_dump::selected_item(self) else {
+            self.notify(Severity::Info, "Nothing is selected to dump");
+            return;
+        };
+        let written = dump.json.map_err(anyhow::Error::from).and_then(|json| {
+            debug_dump::write(&dir, dump.kind, &json, Local::now()).map_err(anyhow::Error::from)
+        });
+        match written {
+            Ok(path) => {
+                let path = path.display().to_string();
+                self.notify(
+                    Severity::Info,
+                    format!("Wrote the {} JSON to {}", dump.kind, path),
+                );
+                self.copy_to_clipboard(path);
+            }
+            Err(e) => self.notify(
+                Severity::Error,
+                format!("Could not dump the {}: {}", dump.kind, e),
+            ),
+        }
+    }
+
+    fn copy_to_clipboard(&mut self, text: String) {
+        let command = self.user_config.behavior.clipboard_command.clone();
+        match self.clipboard.copy(&text, command.as_deref()) {
+            CopyOutcome::Clipboard | CopyOutcome::Command => {}
+            CopyOutcome::Unavailable(reason) => self.notify(
+                Severity::Warning,
+                format!("Clipboard unavailable ({}): {}", reason, text),
+            ),
+        }
+    }
+
+    pub fn set_saved_tracks_to_table(&mut self, saved_track_page: &Page<SavedTrack>) {
+        self.dispatch(IoEvent::SetTracksToTable {
+            tracks: saved_track_page
+                .items
+                .clone()
                 .into_iter()
                 .map(|item| item.track)
                 .collect::<Vec<FullTrack>>(),
         });
     }
 
-    pub fn set_saved_artists_to_table(&mut self, saved_artists_page: &CursorBasedPage<FullArtist>) {
-        self.dispatch(IoEvent::SetArtistsToTable {
-            artists: saved_artists_page
-                .items
-                .clone()
-                .into_iter()
-                .collect::<Vec<FullArtist>>(),
-        })
+    pub fn set_saved_artists_to_table(&mut self, saved_artists_page: &CursorBasedPage<FullArtist>) {
+        self.dispatch(IoEvent::SetArtistsToTable {
+            artists: saved_artists_page
+                .items
+                .clone()
+                .into_iter()
+                .collect::<Vec<FullArtist>>(),
+        })
+    }
+
+    pub fn get_current_user_saved_artists_next(&mut self) {
+        match self
+            .library
+            .saved_artists
+            .get_results(Some(self.library.saved_artists.index + 1))
+            .cloned()
+        {
+            Some(saved_artists) => {
+                self.set_saved_artists_to_table(&saved_artists);
+                self.library.saved_artists.index += 1
+            }
+            None => {
+                if let Some(saved_artists) = &self.library.saved_artists.clone().get_results(None) {
+                    if let Some(last_artist) = saved_artists.items.last() {
+                        self.dispatch(IoEvent::GetFollowedArtists {
+                            after: Some(last_artist.id.clone()),
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn get_current_user_saved_artists_previous(&mut self) {
+        if self.library.saved_artists.index > 0 {
+            self.library.saved_artists.index -= 1;
+        }
+
+        if let Some(saved_artists) = &self.library.saved_artists.get_results(None).cloned() {
+            self.set_saved_artists_to_table(saved_artists);
+        }
+    }
+
+    pub fn get_current_user_saved_tracks_next(&mut self) {
+        // Before fetching the next tracks, check if we have already fetched them
+        match self
+            .library
+            .saved_tracks
+            .get_results(Some(self.library.saved_tracks.index + 1))
+            .cloned()
+        {
+            Some(saved_tracks) => {
+                self.set_saved_tracks_to_table(&saved_tracks);
+                self.library.saved_tracks.index += 1
+            }
+            None => {
+                if let Some(saved_tracks) = &self.library.saved_tracks.get_results(None) {
+                    let offset = Some(saved_tracks.offset + saved_tracks.limit);
+                    self.dispatch(IoEvent::GetCurrentUserSavedTracks { offset });
+                }
+            }
+        }
+    }
+
+    pub fn get_current_user_saved_tracks_previous(&mut self) {
+        if self.library.saved_tracks.index > 0 {
+            self.library.saved_tracks.index -= 1;
+        }
+
+        if let Some(saved_tracks) = &self.library.saved_tracks.get_results(None).cloned() {
+            self.set_saved_tracks_to_table(saved_tracks);
+        }
+    }
+
+    pub fn shuffle(&mut self) {
+        self.dispatch(IoEvent::ToggleShuffle);
+    }
+
+    // Nothing is fetched past the last page, which would only come back empty
+    pub fn get_current_user_saved_albums_next(&mut self) {
+        if self.library.saved_albums.is_last_page() {
+            self.notify(Severity::Info, "This is the last page of saved albums");
+            return;
+        }
+        match self
+            .library
+            .saved_albums
+            .get_results(Some(self.library.saved_albums.index + 1))
+            .cloned()
+        {
+            Some(_) => {
+                self.library.saved_albums.index += 1;
+                self.clamp_album_list_index();
+            }
+            None => {
+                if let Some(saved_albums) = &self.library.saved_albums.get_results(None) {
+                    let offset = Some(saved_albums.offset + saved_albums.limit);
+                    self.dispatch(IoEvent::GetCurrentUserSavedAlbums { offset });
+                }
+            }
+        }
+    }
+
+    pub fn search_results_next_page(&mut self) {
+        let block = self.search_results.paged_block();
+        if self.search_results.turn_page(block, true) {
+            return;
+        }
+        let Some(search_type) = block.search_type() else {
+            return;
+        };
+        if let Some(offset) = self.search_results.next_offset(search_type) {
+            self.dispatch(IoEvent::GetMoreSearchResults {
+                search_term: self.search_results.search_term.clone(),
+                search_type,
+                offset,
+                country: self.get_user_country(),
+            });
+        }
+    }
+
+    pub fn search_results_previous_page(&mut self) {
+        let block = self.search_results.paged_block();
+        self.search_results.turn_page(block, false);
+    }
+
+    pub fn get_current_user_saved_albums_previous(&mut self) {
+        if self.library.saved_albums.previous_page() {
+            self.clamp_album_list_index();
+        } else if self.library.saved_albums.page_count().is_some() {
+            self.notify(Severity::Info, "This is the first page of saved albums");
+        }
+    }
+
+    // Pages of saved albums can differ in size, the last one is usually shorter
+    pub fn clamp_album_list_index(&mut self) {
+        if let Some(albums) = self.library.saved_albums.get_results(None) {
+            self.album_list_index = self
+                .album_list_index
+                .min(albums.items.len().saturating_sub(1));
+        }
+    }
+
+    // Save an item to the library. Saving one that is already there doesn't call the API again,
+    // instead it asks for a second press to remove it.
+    pub fn save_library_item(&mut self, item: LibraryItem, name: &str, now: Instant) {
+        if !item.is_saved(self) {
+            self.pending_removal = None;
+            self.dispatch(item.save_event());
+            return;
+        }
+
+        match self.pending_removal.take() {
+            Some(pending) if pending.item == item && now < pending.deadline => {
+                self.dispatch(item.remove_event());
+            }
+            _ => {
+                self.pending_removal = Some(PendingRemoval {
+                    item,
+                    deadline: now + REMOVAL_CONFIRM_WINDOW,
+                });
+                self.notify(
+                    Severity::Info,
+                    format!(
+                        "'{}' is already in your library — press again to remove",
+                        name
+                    ),
+                );
+            }
+        }
+    }
+
+    pub fn expire_pending_removal(&mut self, now: Instant) {
+        if self
+            .pending_removal
+            .as_ref()
+            .is_some_and(|pending| now >= pending.deadline)
+        {
+            self.pending_removal = None;
+        }
+    }
+
+    pub fn current_user_saved_album_delete(&mut self, block: ActiveBlock) {
+        match block {
+            ActiveBlock::SearchResultBlock => {
+                if let Some(albums) = self.search_results.albums.get_results(None) {
+                    if let Some(selected_index) = self.search_results.selected_album_index {
+                        let selected_album = &albums.items[selected_index];
+                        if let Some(album_id) = selected_album.id.clone() {
+                            self.dispatch(IoEvent::CurrentUserSavedAlbumDelete { album_id });
+                        }
+                    }
+                }
+            }
+            ActiveBlock::AlbumList => {
+                if let Some(albums) = self.library.saved_albums.get_results(None) {
+                    if let Some(selected_album) = albums.items.get(self.album_list_index) {
+                        let album_id = selected_album.album.id.clone();
+                        self.dispatch(IoEvent::CurrentUserSavedAlbumDelete { album_id });
+                    }
+                }
+            }
+            ActiveBlock::ArtistBlock => {
+                if let Some(artist) = &self.artist {
+                    if let Some(selected_album) = artist.selected_album() {
+                        if let Some(album_id) = selected_album.id.clone() {
+                            self.dispatch(IoEvent::CurrentUserSavedAlbumDelete { album_id });
+                        }
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+
+    pub fn current_user_saved_album_add(&mut self, block: ActiveBlock) {
+        let selected_album = match block {
+            ActiveBlock::SearchResultBlock => {
+                match (
+                    self.search_results.albums.get_results(None),
+                    self.search_results.selected_album_index,
+                ) {
+                    (Some(albums), Some(selected_index)) => albums.items.get(selected_index),
+                    _ => None,
+                }
+            }
+            ActiveBlock::ArtistBlock => self
+                .artist
+                .as_ref()
+                .and_then(|artist| artist.selected_album()),
+            _ => None,
+        };
+        if let Some((album_id, name)) =
+            selected_album.and_then(|album| Some((album.id.clone()?, album.name.clone())))
+        {
+            self.save_library_item(LibraryItem::Album(album_id), &name, Instant::now());
+        }
+    }
+
+    pub fn get_current_user_saved_shows_next(&mut self) {
+        if self.library.saved_shows.is_last_page() {
+            self.notify(Severity::Info, "This is the last page of podcasts");
+            return;
+        }
+        match self
+            .library
+            .saved_shows
+            .get_results(Some(self.library.saved_shows.index + 1))
+            .cloned()
+        {
+            Some(_) => self.library.saved_shows.index += 1,
+            None => {
+                if let Some(saved_shows) = &self.library.saved_shows.get_results(None) {
+                    let offset = Some(saved_shows.offset + saved_shows.limit);
+                    self.dispatch(IoEvent::GetCurrentUserSavedShows { offset });
+                }
+            }
+        }
+    }
+
+    pub fn get_current_user_saved_shows_previous(&mut self) {
+        if !self.library.saved_shows.previous_page()
+            && self.library.saved_shows.page_count().is_some()
+        {
+            self.notify(Severity::Info, "This is the first page of podcasts");
+        }
+    }
+
+    pub fn find_finished_shows(&mut self) {
+        if self.is_finding_finished_shows {
+            self.notify(
+                Severity::Info,
+                "Already looking for finished podcasts, <Esc> cancels",
+            );
+            return;
+        }
+        self.is_finding_finished_shows = true;
+        self.notify(Severity::Info, "Looking for finished podcasts");
+        self.dispatch(IoEvent::FindFinishedShows);
+    }
+
+    // Whether there was a search to cancel
+    pub fn cancel_finding_finished_shows(&mut self) -> bool {
+        if !std::mem::take(&mut self.is_finding_finished_shows) {
+            return false;
+        }
+        self.notify(Severity::Info, "Stopped looking for finished podcasts");
+        true
+    }
+
+    // Removes the finished podcasts left checked and goes back to the saved podcasts
+    pub fn remove_finished_shows(&mut self) {
+        let Some(finished_shows) = self.finished_shows.take() else {
+            return;
+        };
+        let show_ids = finished_shows.removed_ids();
+        if !show_ids.is_empty() {
+            self.dispatch(IoEvent::RemoveSavedShows { show_ids });
+        }
+        if self.get_current_route().id == RouteId::FinishedShows {
+            self.pop_navigation_stack();
+        }
+    }
+
+    pub fn get_episode_table_next(&mut self, show_id: ShowId<'_>) {
+        match self
+            .library
+            .show_episodes
+            .get_results(Some(self.library.show_episodes.index + 1))
+            .cloned()
+        {
+            Some(_) => self.library.show_episodes.index += 1,
+            None => {
+                if let Some(show_episodes) = &self.library.show_episodes.get_results(None) {
+                    let offset = Some(show_episodes.offset + show_episodes.limit);
+                    self.dispatch(IoEvent::GetCurrentShowEpisodes { show_id, offset });
+                }
+            }
+        }
+    }
+
+    pub fn get_episode_table_previous(&mut self) {
+        if self.library.show_episodes.index > 0 {
+            self.library.show_episodes.index -= 1;
+        }
+    }
+
+    pub fn user_unfollow_artists(&mut self, block: ActiveBlock) {
+        match block {
+            ActiveBlock::SearchResultBlock => {
+                if let Some(artists) = self.search_results.artists.get_results(None) {
+                    if let Some(selected_index) = self.search_results.selected_artists_index {
+                        let selected_artist: &FullArtist = &artists.items[selected_index];
+                        let artist_id = selected_artist.id.clone();
+                        self.dispatch(IoEvent::UserUnfollowArtists {
+                            artist_ids: vec![artist_id],
+                        });
+                    }
+                }
+            }
+            ActiveBlock::AlbumList => {
+                if let Some(artists) = self.library.saved_artists.get_results(None) {
+                    if let Some(selected_artist) = artists.items.get(self.artists_list_index) {
+                        let artist_id = selected_artist.id.clone();
+                        self.dispatch(IoEvent::UserUnfollowArtists {
+                            artist_ids: vec![artist_id],
+                        });
+                    }
+                }
+            }
+            ActiveBlock::ArtistBlock => {
+                if let Some(artist) = &self.artist {
+                    let selected_artis =
+                        &artist.related_artists[artist.selected_related_artist_index];
+                    let artist_id = selected_artis.id.clone();
+                    self.dispatch(IoEvent::UserUnfollowArtists {
+                        artist_ids: vec![artist_id],
+                    });
+                }
+            }
+            ActiveBlock::TopArtists => {
+                if let Some(artist_id) = self.selected_top_artist().map(|artist| artist.id.clone())
+                {
+                    self.dispatch(IoEvent::UserUnfollowArtists {
+                        artist_ids: vec![artist_id],
+                    });
+                }
+            }
+            _ => (),
+        };
+    }
+
+    pub fn user_follow_artists(&mut self, block: ActiveBlock) {
+        let selected_artist = match block {
+            ActiveBlock::SearchResultBlock => {
+                match (
+                    self.search_results.artists.get_results(None),
+                    self.search_results.selected_artists_index,
+                ) {
+                    (Some(artists), Some(selected_index)) => artists.items.get(selected_index),
+                    _ => None,
+                }
+            }
+            ActiveBlock::ArtistBlock => self.artist.as_ref().and_then(|artist| {
+                artist
+                    .related_artists
+                    .get(artist.selected_related_artist_index)
+            }),
+            ActiveBlock::TopArtists => self.selected_top_artist(),
+            _ => None,
+        };
+        if let Some(artist) = selected_artist {
+            let (artist_id, name) = (artist.id.clone(), artist.name.clone());
+            self.save_library_item(LibraryItem::Artist(artist_id), &name, Instant::now());
+        }
+    }
+
+    // The mark next to the artist's name changes straight away, and is put back if Spotify refuses
+    pub fn toggle_follow_viewed_artist(&mut self) {
+        let Some(artist_id) = self.artist.as_ref().map(|artist| artist.artist_id.clone()) else {
+            return;
+        };
+        if self.followed_artist_ids_set.contains(&artist_id) {
+            self.followed_artist_ids_set.remove(&artist_id);
+            self.dispatch(IoEvent::UserUnfollowArtists {
+                artist_ids: vec![artist_id],
+            });
+        } else {
+            self.followed_artist_ids_set.insert(artist_id.clone());
+            self.dispatch(IoEvent::UserFollowArtists {
+                artist_ids: vec![artist_id],
+            });
+        }
+    }
+
+    // Searches only the one category, and lands on its results with the ones of the last search
+    // cleared, so the other blocks don't show results for something else
+    pub fn search_from_artist(&mut self, discovery: ArtistDiscovery) {
+        let Some(artist_name) = self
+            .artist
+            .as_ref()
+            .map(|artist| artist.artist_name.clone())
+        else {
+            return;
+        };
+        let search_term = discovery.search_term(&artist_name);
+        let block = discovery.search_result_block();
+        self.set_input(&search_term);
+        self.dispatch(IoEvent::GetSearchResults {
+            search_term,
+            types: block.search_type().map(|search_type| vec![search_type]),
+            country: self.get_user_country(),
+        });
+        self.push_search_route(block);
+    }
+
+    pub fn push_search_route(&mut self, hovered_block: SearchResultBlock) {
+        self.search_results = SearchResult {
+            hovered_block,
+            ..SearchResult::default()
+        };
+        self.push_navigation_stack(RouteId::Search, ActiveBlock::SearchResultBlock);
+    }
+
+    pub fn user_follow_playlist(&mut self) {
+        if let (Some(playlists), Some(selected_index)) = (
+            self.search_results.playlists.get_results(None),
+            self.search_results.selected_playlists_index,
+        ) {
+            let selected_playlist: &SimplifiedPlaylist = &playlists.items[selected_index];
+            let item =
+                LibraryItem::Playlist(selected_playlist.id.clone(), selected_playlist.public);
+            let name = selected_playlist.name.clone();
+            self.save_library_item(item, &name, Instant::now());
+        }
+    }
+
+    // Followed playlists are the ones in the sidebar, including the user's own
+    pub fn is_playlist_followed(&self, playlist_id: &PlaylistId) -> bool {
+        self.playlists
+            .as_ref()
+            .is_some_and(|playlists| playlists.items.iter().any(|p| p.id == *playlist_id))
+    }
+
+    // The playlist whose tracks the item table shows, if it shows one from the sidebar or a search
+    pub fn item_table_playlist(&self) -> Option<&SimplifiedPlaylist> {
+        match self.item_table.context.as_ref()? {
+            ItemTableContext::MyPlaylists => self.selected_playlist(),
+            ItemTableContext::PlaylistSearch => self
+                .search_results
+                .playlists
+                .get_results(None)?
+                .items
+                .get(self.search_results.selected_playlists_index?),
+            _ => None,
+        }
+    }
+
+    pub fn follow_item_table_playlist(&mut self) {
+        if let Some(playlist) = self.item_table_playlist() {
+            let item = LibraryItem::Playlist(playlist.id.clone(), playlist.public);
+            let name = playlist.name.clone();
+            self.save_library_item(item, &name, Instant::now());
+        }
+    }
+
+    // Asks to confirm unfollowing the playlist in the item table, which deletes it if it's the
+    // user's own
+    pub fn start_item_table_playlist_unfollow(&mut self) {
+        let Some(playlist) = self.item_table_playlist() else {
+            return;
+        };
+        if !self.is_playlist_followed(&playlist.id) {
+            return;
+        }
+        let context = match self.item_table.context {
+            Some(ItemTableContext::PlaylistSearch) => DialogContext::PlaylistSearch,
+            _ => DialogContext::PlaylistWindow,
+        };
+        self.dialog = Some(playlist.name.clone());
+        self.confirm = false;
+        self.push_navigation_stack(RouteId::Dialog, ActiveBlock::Dialog(context));
+    }
+
+    // Whether the current user owns the playlist, which isn't known until the user is fetched
+    pub fn is_owned(&self, playlist: &SimplifiedPlaylist) -> bool {
+        self.user
+            .as_ref()
+            .is_some_and(|user| user.id == playlist.owner.id)
+    }
+
+    // Indexes of the playlists that pass the filter, in order
+    pub fn visible_playlist_indices(&self) -> Vec<usize> {
+        let Some(playlists) = &self.playlists else {
+            return vec![];
+        };
+        playlists
+            .items
+            .iter()
+            .enumerate()
+            .filter(|(_, playlist)| match self.playlist_filter {
+                PlaylistFilter::All => true,
+                PlaylistFilter::Owned => self.is_owned(playlist),
+                PlaylistFilter::Followed => !self.is_owned(playlist),
+            })
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    pub fn cycle_playlist_filter(&mut self) {
+        self.set_playlist_filter(self.playlist_filter.next());
+    }
+
+    // Shows all the playlists again, false when they already were
+    pub fn clear_playlist_filter(&mut self) -> bool {
+        if self.playlist_filter == PlaylistFilter::All {
+            return false;
+        }
+        self.set_playlist_filter(PlaylistFilter::All);
+        true
+    }
+
+    fn set_playlist_filter(&mut self, filter: PlaylistFilter) {
+        self.playlist_filter = filter;
+        let visible = self.visible_playlist_indices();
+        // Keep the selection if it is still listed
+        if !self
+            .selected_playlist_index
+            .is_some_and(|index| visible.contains(&index))
+        {
+            if let Some(first) = visible.first() {
+                self.selected_playlist_index = Some(*first);
+            }
+        }
+    }
+
+    // Rejects operations on playlists that Spotify only allows their owner to make, rather
+    // than letting the request fail
+    fn check_owned(&mut self, playlist: &SimplifiedPlaylist, action: &str) -> bool {
+        if self.is_owned(playlist) {
+            return true;
+        }
+        self.handle_error(anyhow!(
+            "Only playlists you own can be {} (\"{}\" is owned by {})",
+            action,
+            playlist.name,
+            owner_name(playlist)
+        ));
+        false
+    }
+
+    // The playlist whose items are in the table
+    pub fn active_playlist(&self) -> Option<&SimplifiedPlaylist> {
+        self.playlists
+            .as_ref()?
+            .items
+            .get(self.active_playlist_index?)
+    }
+
+    // The user's playlist that is playing, marked in the playlists pane whichever one is selected
+    pub fn playing_playlist_index(&self) -> Option<usize> {
+        let context = self.current_playback_context.as_ref()?.context.as_ref()?;
+        self.playlists
+            .as_ref()?
+            .items
+            .iter()
+            .position(|playlist| playlist.id.uri() == context.uri)
+    }
+
+    // Opens the playlist in the table, which then plays from it. The ones that aren't in the
+    // sidebar have no index, so that the table doesn't play the playlist it showed before.
+    pub fn open_playlist(&mut self, playlist_id: PlaylistId<'static>) {
+        self.active_playlist_index = self
+            .playlists
+            .as_ref()
+            .and_then(|playlists| playlists.items.iter().position(|p| p.id == playlist_id));
+        self.item_table.context = Some(ItemTableContext::MyPlaylists);
+        self.playlist_offset = 0;
+        self.dispatch(IoEvent::GetPlaylistItems {
+            playlist_id,
+            offset: 0,
+        });
+    }
+
+    pub fn selected_playlist(&self) -> Option<&SimplifiedPlaylist> {
+        self.playlists
+            .as_ref()?
+            .items
+            .get(self.selected_playlist_index?)
+    }
+
+    // Asks to confirm deleting the selected playlist
+    pub fn start_playlist_delete(&mut self) {
+        let Some(playlist) = self.selected_playlist().cloned() else {
+            return;
+        };
+        if !self.check_owned(&playlist, "deleted") {
+            return;
+        }
+        self.dialog = Some(playlist.name);
+        self.confirm = false;
+        self.push_navigation_stack(
+            RouteId::Dialog,
+            ActiveBlock::Dialog(DialogContext::PlaylistWindow),
+        );
+    }
+
+    pub fn user_unfollow_playlist(&mut self) {
+        if let (Some(playlists), Some(selected_index), Some(_)) =
+            (&self.playlists, self.selected_playlist_index, &self.user)
+        {
+            let selected_playlist = &playlists.items[selected_index];
+            let playlist_id = selected_playlist.id.clone();
+            self.dispatch(IoEvent::UserUnfollowPlaylist { playlist_id })
+        }
+    }
+
+    pub fn user_unfollow_playlist_search_result(&mut self) {
+        if let (Some(playlists), Some(selected_index), Some(_)) = (
+            self.search_results.playlists.get_results(None),
+            self.search_results.selected_playlists_index,
+            &self.user,
+        ) {
+            let selected_playlist = &playlists.items[selected_index];
+            let playlist_id = selected_playlist.id.clone();
+            self.dispatch(IoEvent::UserUnfollowPlaylist { playlist_id })
+        }
+    }
+
+    pub fn start_playlist_rename(&mut self) {
+        let Some(playlist) = self.selected_playlist().cloned() else {
+            return;
+        };
+        if !self.check_owned(&playlist, "renamed") {
+            return;
+        }
+
+        let playlist_id = playlist.id;
+        let name = playlist.name;
+        self.input_context = InputContext::RenamePlaylist { playlist_id };
+        self.set_input(&name);
+        self.set_current_route_state(Some(ActiveBlock::Input), Some(ActiveBlock::Input));
+    }
+
+    // The description isn't listed with the playlists, so it's fetched before the input opens
+    pub fn start_playlist_description_edit(&mut self) {
+        let Some(playlist) = self.selected_playlist().cloned() else {
+            return;
+        };
+        if !self.check_owned(&playlist, "edited") {
+            return;
+        }
+        self.dispatch(IoEvent::GetPlaylistDescription {
+            playlist_id: playlist.id,
+        });
+    }
+
+    pub fn start_playlist_description_input(
+        &mut self,
+        playlist_id: PlaylistId<'static>,
+        description: String,
+    ) {
+        self.input_context = InputContext::EditPlaylistDescription { playlist_id };
+        self.set_input(&description);
+        self.set_current_route_state(Some(ActiveBlock::Input), Some(ActiveBlock::Input));
+    }
+
+    // An empty description clears it
+    pub fn change_playlist_description(
+        &mut self,
+        playlist_id: PlaylistId<'static>,
+        description: String,
+    ) {
+        self.dispatch(IoEvent::ChangePlaylistDetails {
+            playlist_id,
+            name: None,
+            description: Some(description.trim().to_string()),
+        });
+    }
+
+    fn playlist_mut(&mut self, playlist_id: &PlaylistId<'_>) -> Option<&mut SimplifiedPlaylist> {
+        self.playlists
+            .as_mut()?
+            .items
+            .iter_mut()
+            .find(|playlist| playlist.id == *playlist_id)
+    }
+
+    // Optimistically renames the playlist in the sidebar before asking Spotify to do the same
+    pub fn rename_playlist(&mut self, playlist_id: PlaylistId<'static>, name: String) {
+        let name = name.trim().to_string();
+        if name.is_empty() {
+            return;
+        }
+        let Some(playlist) = self.playlist_mut(&playlist_id) else {
+            return;
+        };
+        if playlist.name == name {
+            return;
+        }
+
+        let previous_name = std::mem::replace(&mut playlist.name, name.clone());
+        self.pending_playlist_renames
+            .entry(playlist_id.clone())
+            .or_insert(previous_name);
+        self.dispatch(IoEvent::ChangePlaylistDetails {
+            playlist_id,
+            name: Some(name),
+            description: None,
+        });
+    }
+
+    pub fn confirm_playlist_rename(&mut self, playlist_id: PlaylistId<'_>) {
+        self.pending_playlist_renames
+            .remove(&playlist_id.into_static());
+    }
+
+    pub fn revert_playlist_rename(&mut self, playlist_id: PlaylistId<'_>, e: anyhow::Error) {
+        let playlist_id = playlist_id.into_static();
+        if let Some(previous_name) = self.pending_playlist_renames.remove(&playlist_id) {
+            if let Some(playlist) = self.playlist_mut(&playlist_id) {
+                playlist.name = previous_name;
+            }
+        }
+        self.notify(Severity::Error, format!("Failed to rename playlist: {e}"));
+    }
+
+    pub fn user_follow_show(&mut self, block: ActiveBlock) {
+        let selected_show = match block {
+            ActiveBlock::SearchResultBlock => {
+                match (
+                    self.search_results.shows.get_results(None),
+                    self.search_results.selected_shows_index,
+                ) {
+                    (Some(shows), Some(selected_index)) => shows
+                        .items
+                        .get(selected_index)
+                        .map(|show| (show.id.clone(), show.name.clone())),
+                    _ => None,
+                }
+            }
+            ActiveBlock::EpisodeTable => match self.episode_table_context {
+                EpisodeTableContext::Full => self
+                    .selected_show_full
+                    .as_ref()
+                    .map(|selected| (selected.show.id.clone(), selected.show.name.clone())),
+                EpisodeTableContext::Simplified => self
+                    .selected_show_simplified
+                    .as_ref()
+                    .map(|selected| (selected.show.id.clone(), selected.show.name.clone())),
+            },
+            _ => None,
+        };
+        if let Some((show_id, name)) = selected_show {
+            self.save_library_item(LibraryItem::Show(show_id), &name, Instant::now());
+        }
+    }
+
+    pub fn user_unfollow_show(&mut self, block: ActiveBlock) {
+        match block {
+            ActiveBlock::Podcasts => {
+                if let Some(shows) = self.library.saved_shows.get_results(None) {
+                    if let Some(selected_show) = shows.items.get(self.shows_list_index) {
+                        let show_id = selected_show.show.id.clone();
+                        self.dispatch(IoEvent::CurrentUserSavedShowDelete { show_id });
+                    }
+                }
+            }
+            ActiveBlock::SearchResultBlock => {
+                if let Some(shows) = self.search_results.shows.get_results(None) {
+                    if let Some(selected_index) = self.search_results.selected_shows_index {
+                        let show_id = shows.items[selected_index].id.to_owned();
+                        self.dispatch(IoEvent::CurrentUserSavedShowDelete { show_id });
+                    }
+                }
+            }
+            ActiveBlock::EpisodeTable => match self.episode_table_context {
+                EpisodeTableContext::Full => {
+                    if let Some(selected_episode) = self.selected_show_full.clone() {
+                        let show_id = selected_episode.show.id;
+                        self.dispatch(IoEvent::CurrentUserSavedShowDelete { show_id });
+                    }
+                }
+                EpisodeTableContext::Simplified => {
+                    if let Some(selected_episode) = self.selected_show_simplified.clone() {
+                        let show_id = selected_episode.show.id;
+                        self.dispatch(IoEvent::CurrentUserSavedShowDelete { show_id });
+                    }
+                }
+            },
+            _ => (),
+        }
+    }
+
+    // Only lists the playlists, their items are loaded once one is opened
+    pub fn get_made_for_you(&mut self) {
+        // TODO: replace searches when relevant endpoint is added
+        const PLAYLIST_NAMES: [&str; 5] = [
+            "Discover Weekly",
+            "Release Radar",
+            "On Repeat",
+            "Repeat Rewind",
+            "Daily Drive",
+        ];
+
+        if self.library.made_for_you_playlists.pages.is_empty() {
+            for name in PLAYLIST_NAMES {
+                self.made_for_you_search_and_add(name);
+            }
+        }
+    }
+
+    // Lists the playlists again, e.g. once one that wasn't there has been made for the account
+    pub fn refresh_made_for_you(&mut self) {
+        self.library.made_for_you_playlists = ScrollableResultPages::default();
+        self.made_for_you_index = 0;
+        self.get_made_for_you();
+    }
+
+    // Opens the top items of the kind and time range that were shown last, from their first page
+    pub fn open_top_items(&mut self) {
+        self.fetch_top_items(0);
+        self.push_top_items_route();
+    }
+
+    pub fn cycle_top_items_time_range(&mut self) {
+        self.top_items.time_range = TopItems::next_time_range(self.top_items.time_range);
+        self.fetch_top_items(0);
+    }
+
+    // Shows the top artists in place of the top tracks, or the other way round
+    pub fn toggle_top_item_kind(&mut self) {
+        self.top_items.kind = match self.top_items.kind {
+            TopItemKind::Tracks => TopItemKind::Artists,
+            TopItemKind::Artists => TopItemKind::Tracks,
+        };
+        self.fetch_top_items(0);
+        self.pop_navigation_stack();
+        self.push_top_items_route();
+    }
+
+    pub fn top_items_next_page(&mut self) {
+        if let Some(offset) = self.top_items.next_offset() {
+            self.fetch_top_items(offset);
+        }
+    }
+
+    pub fn top_items_previous_page(&mut self) {
+        if let Some(offset) = self.top_items.previous_offset() {
+            self.fetch_top_items(offset);
+        }
+    }
+
+    pub fn selected_top_artist(&self) -> Option<&FullArtist> {
+        self.top_items
+            .artists
+            .as_ref()?
+            .items
+            .get(self.top_items.selected_artist_index)
+    }
+
+    fn fetch_top_items(&mut self, offset: u32) {
+        self.dispatch(IoEvent::GetUserTopItems {
+            kind: self.top_items.kind,
+            time_range: self.top_items.time_range,
+            offset,
+        });
+    }
+
+    fn push_top_items_route(&mut self) {
+        match self.top_items.kind {
+            TopItemKind::Tracks => {
+                self.push_navigation_stack(RouteId::ItemTable, ActiveBlock::ItemTable)
+            }
+            TopItemKind::Artists => {
+                self.push_navigation_stack(RouteId::TopArtists, ActiveBlock::TopArtists)
+            }
+        }
+    }
+
+    fn made_for_you_search_and_add(&mut self, search_term: &str) {
+        let country = self.get_user_country();
+        self.dispatch(IoEvent::MadeForYouSearchAndAdd {
+            search_term: search_term.to_string(),
+            country,
+        });
+    }
+
+    pub fn get_audio_analysis(&mut self) {
+        if !self.user_config.behavior.enable_audio_analysis {
+            self.notify(
+                Severity::Info,
+                "The audio analysis is disabled in the config",
+            );
+            return;
+        }
+        if self
+            .current_playback_context
+            .as_ref()
+            .is_some_and(|context| context.item.is_some())
+        {
+            self.fetch_audio_analysis();
+            if self.get_current_route().id != RouteId::Analysis {
+                self.push_navigation_stack(RouteId::Analysis, ActiveBlock::Analysis);
+            }
+        }
+    }
+
+    // Request the analysis of the playing track unless it has been already, which replaces the
+    // analysis of the previous track
+    pub fn fetch_audio_analysis(&mut self) {
+        if !self.user_config.behavior.enable_audio_analysis {
+            return;
+        }
+        let Some(track_id) = self.playing_track().and_then(|track| track.id.clone()) else {
+            return;
+        };
+        if self
+            .audio_analysis
+            .as_ref()
+            .is_some_and(|(analysed_id, _)| *analysed_id == track_id)
+        {
+            return;
+        }
+        self.audio_analysis = Some((track_id.clone(), TrackAnalysis::Loading));
+        self.fetch_track_features(track_id.clone());
+        self.dispatch(IoEvent::GetTrackAnalysis { track_id });
+    }
+
+    // Request the features of a track unless they are cached or already on their way
+    pub fn fetch_track_features(&mut self, track_id: TrackId<'static>) {
+        if self.audio_features.contains_key(&track_id)
+            || !self.loading_track_features.insert(track_id.clone())
+        {
+            return;
+        }
+        self.dispatch(IoEvent::GetTrackFeatures { track_id });
+    }
+
+    pub fn toggle_features_popup(&mut self) {
+        self.item_table.is_features_popup_open = !self.item_table.is_features_popup_open;
+        self.fetch_selected_track_features();
+    }
+
+    // The popup follows the selection, so the features of each row it moves to are fetched
+    pub fn fetch_selected_track_features(&mut self) {
+        if !self.item_table.is_features_popup_open {
+            return;
+        }
+        if let Some(PlayableItem::Track(FullTrack {
+            id: Some(track_id), ..
+        })) = self.item_table.items.get(self.item_table.selected_index)
+        {
+            self.fetch_track_features(track_id.clone());
+        }
+    }
+
+    // The analysis of the playing track, None when something else is playing
+    pub fn current_audio_analysis(&self) -> Option<&TrackAnalysis> {
+        let track_id = self.playing_track()?.id.as_ref()?;
+        match &self.audio_analysis {
+            Some((analysed_id, analysis)) if analysed_id == track_id => Some(analysis),
+            _ => None,
+        }
+    }
+
+    pub fn seek_to_section(&mut self, forwards: bool) {
+        let Some(TrackAnalysis::Loaded(analysis)) = self.current_audio_analysis() else {
+            return;
+        };
+        let progress_ms = self.seek_ms.unwrap_or(self.song_progress_ms) as u32;
+        let mut section_starts_ms = analysis
+            .sections
+            .iter()
+            .map(|section| (section.time_interval.start * 1000.0).round() as u32);
+
+        let position_ms = if forwards {
+            match section_starts_ms.find(|start_ms| *start_ms > progress_ms) {
+                Some(start_ms) => start_ms,
+                None => return,
+            }
+        } else {
+            section_starts_ms
+                .rev()
+                .find(|start_ms| start_ms + SECTION_RESTART_MS <= progress_ms)
+                .unwrap_or(0)
+        };
+
+        self.seek_to(position_ms);
+    }
+
+    // Seek straight away, showing the new position until the seek has gone through
+    pub fn seek_to(&mut self, position_ms: u32) {
+        self.seek_ms = Some(position_ms as u128);
+        self.dispatch(IoEvent::Seek { position_ms });
+    }
+
+    // Sort the loaded saved/recommended tracks by an audio feature, fetching the features that
+    // aren't cached yet first. Asking again while they are being fetched cancels the sort.
+    pub fn sort_item_table_by_feature(&mut self, feature: TrackFeature) {
+        if !matches!(
+            self.item_table.context,
+            Some(ItemTableContext::SavedTracks | ItemTableContext::RecommendedTracks)
+        ) {
+            return;
+        }
+
+        if self.item_table.pending_sort.take().is_some() {
+            return;
+        }
+
+        let track_ids = self
+            .item_table
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                PlayableItem::Track(track) => track.id.clone(),
+                PlayableItem::Episode(_) => None,
+            })
+            .filter(|track_id| !self.audio_features.contains_key(track_id))
+            .collect::<Vec<_>>();
+
+        if track_ids.is_empty() {
+            self.apply_item_table_sort(feature);
+        } else {
+            self.item_table.pending_sort = Some(feature);
+            self.dispatch(IoEvent::GetAudioFeatures { track_ids });
+        }
+    }
+
+    // Sort the item table by the cached audio features, highest first. Tracks without features
+    // go last, and the selection follows the selected track.
+    pub fn apply_item_table_sort(&mut self, feature: TrackFeature) {
+        let audio_features = &self.audio_features;
+        self.item_table.sort_by_key(
+            |item, _| match item {
+                PlayableItem::Track(track) => track
+                    .id
+                    .as_ref()
+                    .and_then(|track_id| audio_features.get(track_id))
+                    .map(|features| feature.value(features)),
+                PlayableItem::Episode(_) => None,
+            },
+            |a, b| b.total_cmp(a),
+        );
+        self.item_table.column_sort = None;
+        self.item_table.sort = Some(feature);
+    }
+
+    // Sort the item table by the next of the configured columns, going back to the order of the
+    // context after the last one
+    pub fn cycle_item_table_sort(&mut self) {
+        let columns = &self.user_config.behavior.item_table_columns;
+        let next_column = match self.item_table.column_sort {
+            None => columns.first(),
+            Some(ColumnSort { column, .. }) => columns
+                .iter()
+                .position(|c| *c == column)
+                .and_then(|index| columns.get(index + 1)),
+        };
+        let column_sort = next_column.map(|column| ColumnSort {
+            column: *column,
+            ascending: true,
+        });
+        self.item_table.apply_column_sort(column_sort);
+    }
+
+    pub fn reverse_item_table_sort(&mut self) {
+        if let Some(column_sort) = self.item_table.column_sort {
+            self.item_table.apply_column_sort(Some(ColumnSort {
+                ascending: !column_sort.ascending,
+                ..column_sort
+            }));
+        }
+    }
+
+    pub fn repeat(&mut self) {
+        if let Some(context) = &self.current_playback_context.clone() {
+            self.dispatch(IoEvent::Repeat {
+                state: next_repeat_state(context.repeat_state),
+            });
+        }
+    }
+
+    pub fn get_artist(&mut self, artist_id: ArtistId<'_>, input_artist_name: String) {
+        let country = self.get_user_country();
+        self.dispatch(IoEvent::GetArtist {
+            artist_id,
+            input_artist_name,
+            country,
+        });
+    }
+
+    // Request the next page of the artist's albums once the selection is close to the end of
+    // those loaded
+    pub fn fetch_more_artist_albums(&mut self) {
+        let Some(artist) = &self.artist else {
+            return;
+        };
+        let Some(offset) = artist.next_albums_offset() else {
+            return;
+        };
+        if artist.pending_albums_offset == Some(offset)
+            || artist.selected_album_index + ARTIST_ALBUMS_PREFETCH_DISTANCE < artist.albums().len()
+        {
+            return;
+        }
+
+        let artist_id = artist.artist_id.clone();
+        let album_type = artist.album_type;
+        let country = self.get_user_country();
+        if let Some(artist) = &mut self.artist {
+            artist.pending_albums_offset = Some(offset);
+        }
+        self.dispatch(IoEvent::GetArtistAlbums {
+            artist_id,
+            offset,
+            album_type,
+            country,
+        });
+    }
+
+    // Filter the artist's albums to the next album group, loading them again from the start
+    pub fn cycle_artist_album_type(&mut self) {
+        let country = self.get_user_country();
+        let Some(artist) = &mut self.artist else {
+            return;
+        };
+        artist.album_type = Artist::next_album_type(artist.album_type);
+        artist.albums = ScrollableResultPages::default();
+        artist.pending_albums_offset = Some(0);
+        artist.selected_album_index = 0;
+
+        let event = IoEvent::GetArtistAlbums {
+            artist_id: artist.artist_id.clone(),
+            offset: 0,
+            album_type: artist.album_type,
+            country,
+        };
+        self.dispatch(event);
+    }
+
+    // Move the selection a page further down the albums, loading more when it gets near the end
+    pub fn artist_albums_next_page(&mut self) {
+        if let Some(artist) = &mut self.artist {
+            let last_index = artist.albums().len().saturating_sub(1);
+            artist.selected_album_index =
+                (artist.selected_album_index + artist.albums_page_size()).min(last_index);
+        }
+        self.fetch_more_artist_albums();
+    }
+
+    pub fn artist_albums_previous_page(&mut self) {
+        if let Some(artist) = &mut self.artist {
+            artist.selected_album_index = artist
+                .selected_album_index
+                .saturating_sub(artist.albums_page_size());
+        }
+    }
+
+    // The market from the config beats the country of the account, which isn't known until the
+    // user is fetched and never is in the CLI
+    pub fn get_user_country(&self) -> Option<Country> {
+        self.user_config
+            .behavior
+            .market
+            .or_else(|| self.user.as_ref().and_then(|user| user.country))
+    }
+
+    pub fn is_available(&self, available_markets: &[String]) -> bool {
+        is_available_in(available_markets, self.get_user_country())
+    }
+
+    // Warns that `name` can't be played here, returning whether it can
+    pub fn check_available(&mut self, name: &str, available_markets: &[String]) -> bool {
+        if self.is_available(available_markets) {
+            return true;
+        }
+        let country = self
+            .get_user_country()
+            .map(<&'static str>::from)
+            .unwrap_or_default();
+        self.notify(
+            Severity::Warning,
+            format!("{} isn't available in your market ({})", name, country),
+        );
+        false
+    }
+
+    pub fn open_help(&mut self) {
+        let block = self.get_current_route().active_block;
+        // Opening it again from itself keeps the block it was opened from
+        if block != ActiveBlock::HelpMenu {
+            self.help_context = Some(block);
+        }
+        self.help_filter.clear();
+        self.help_menu_page = 0;
+        self.help_menu_offset = 0;
+        self.set_current_route_state(Some(ActiveBlock::HelpMenu), None);
+    }
+
+    // The rows of the help that match the filter, those about the block it was opened from first
+    pub fn help_docs(&self) -> Vec<Vec<String>> {
+        let filter = self.help_filter.to_lowercase();
+        let mut help_docs = get_help_docs(&self.user_config)
+            .into_iter()
+            .filter(|row| {
+                row[..2]
+                    .iter()
+                    .any(|cell| cell.to_lowercase().contains(&filter))
+            })
+            .collect::<Vec<_>>();
+        if let Some(block) = self.help_context {
+            // Stable, so that both groups keep the order of the help
+            help_docs.sort_by_key(|row| !context_blocks(&row[2]).contains(&block));
+        }
+        help_docs
+    }
+
+    pub fn is_filtering_help(&self) -> bool {
+        self.input_context == InputContext::FilterHelp
+    }
+
+    // The filter is shown above the rows while it is typed or applied
+    pub fn is_help_filter_shown(&self) -> bool {
+        self.is_filtering_help() || !self.help_filter.is_empty()
+    }
+
+    pub fn is_finding_device(&self) -> bool {
+        self.input_context == InputContext::FindDevice
+    }
+
+    pub fn start_finding_device(&mut self) {
+        self.input_context = InputContext::FindDevice;
+        self.set_input("");
+    }
+
+    pub fn stop_finding_device(&mut self) {
+        self.input_context = InputContext::Search;
+        self.set_input("");
+    }
+
+    // Transfers the playback to the device the name typed picks, like `spt playback --transfer`
+    pub fn transfer_to_found_device(&mut self) {
+        let Some(devices) = &self.devices else {
+            return;
+        };
+        let query = self.input.iter().collect::<String>();
+        match find_device(devices, &query) {
+            Ok((index, device_id)) => {
+                self.selected_device_index = Some(index);
+                self.stop_finding_device();
+                self.dispatch(IoEvent::TransferPlaybackToDevice { device_id });
+            }
+            Err(e) => self.notify(Severity::Warning, e.to_string()),
+        }
+    }
+
+    pub fn start_help_filter(&mut self) {
+        self.input_context = InputContext::FilterHelp;
+        let filter = self.help_filter.clone();
+        self.set_input(&filter);
+    }
+
+    // Called whenever the input changes, from the first page as the rows may have gone
+    pub fn update_help_filter(&mut self) {
+        self.help_filter = self.input.iter().collect();
+        self.help_menu_page = 0;
+        self.calculate_help_menu_offset();
     }
 
-    pub fn get_current_user_saved_artists_next(&mut self) {
-        match self
-            .library
-            .saved_artists
-            .get_results(Some(self.library.saved_artists.index + 1))
-            .cloned()
-        {
-            Some(saved_artists) => {
-                self.set_saved_artists_to_table(&saved_artists);
-                self.library.saved_artists.index += 1
-            }
-            None => {
-                if let Some(saved_artists) = &self.library.saved_artists.clone().get_results(None) {
-                    if let Some(last_artist) = saved_artists.items.last() {
-                        self.dispatch(IoEvent::GetFollowedArtists {
-                            after: Some(last_artist.id.clone()),
-                        });
-                    }
-                }
-            }
+    // Hands the input back to search, keeping the rows filtered or listing them all again
+    pub fn stop_help_filter(&mut self, keep_filter: bool) {
+        self.input_context = InputContext::Search;
+        self.set_input("");
+        if !keep_filter {
+            self.help_filter.clear();
+            self.help_menu_page = 0;
         }
+        self.calculate_help_menu_offset();
     }
 
-    pub fn get_current_user_saved_artists_previous(&mut self) {
-        if self.library.saved_artists.index > 0 {
-            self.library.saved_artists.index -= 1;
+    // Keeps the page within the rows that are listed, so that paging down stops at the last one
+    pub fn calculate_help_menu_offset(&mut self) {
+        let mut lines = self.help_menu_max_lines;
+        if self.is_help_filter_shown() {
+            lines = lines.saturating_sub(HELP_FILTER_HEIGHT);
         }
+        let lines = lines.max(1);
+        let last_page = (self.help_docs().len() as u32).saturating_sub(1) / lines;
+        self.help_menu_page = self.help_menu_page.min(last_page);
+        self.help_menu_offset = self.help_menu_page * lines;
+    }
+}
 
-        if let Some(saved_artists) = &self.library.saved_artists.get_results(None).cloned() {
-            self.set_saved_artists_to_table(saved_artists);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{episode_json, episode_page};
+    use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+
+    fn full_artist(id: &str, name: &str, followers: u32) -> FullArtist {
+        serde_json::from_value(serde_json::json!({
+            "external_urls": {},
+            "followers": { "href": null, "total": followers },
+            "genres": [],
+            "href": "",
+            "id": id,
+            "images": [],
+            "name": name,
+            "popularity": 0,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn counts_pages() {
+        let pages = |offset: u32, limit: u32, total: u32| {
+            let mut pages = ScrollableResultPages::<Page<SavedAlbum>>::default();
+            pages.add_pages(Page {
+                href: String::new(),
+                items: vec![],
+                limit,
+                next: None,
+                offset,
+                previous: None,
+                total,
+            });
+            pages
+        };
+
+        assert_eq!(pages(0, 20, 45).page_count(), Some((1, 3)));
+        assert_eq!(pages(40, 20, 45).page_count(), Some((3, 3)));
+        // A full last page
+        assert_eq!(pages(20, 20, 40).page_count(), Some((2, 2)));
+        // Nothing saved is still one page
+        assert_eq!(pages(0, 20, 0).page_count(), Some((1, 1)));
+        assert_eq!(pages(0, 0, 5).page_count(), Some((1, 5)));
+
+        assert!(!pages(0, 20, 45).is_last_page());
+        assert!(pages(40, 20, 45).is_last_page());
+        assert!(pages(0, 20, 0).is_last_page());
+        // Nothing loaded
+        let empty = ScrollableResultPages::<Page<SavedAlbum>>::default();
+        assert_eq!(empty.page_count(), None);
+        assert!(!empty.is_last_page());
+    }
+
+    #[test]
+    fn a_second_like_is_ignored_until_spotify_answers() {
+        let (io_tx, mut io_rx) = unbounded_channel();
+        let mut app = App::new(io_tx, UserConfig::new(), Utc::now());
+        let track_id = TrackId::from_id("4iV5W9uYEdYUVa79Axb7Rh").unwrap();
+
+        app.toggle_save_track(track_id.clone());
+        app.toggle_save_track(track_id.clone());
+        assert!(app.liked_song_ids_set.contains(&track_id));
+        assert!(matches!(
+            io_rx.try_recv(),
+            Ok(IoEvent::ToggleSaveTrack { .. })
+        ));
+        assert!(io_rx.try_recv().is_err());
+
+        // Spotify agrees, so it can be toggled again
+        app.confirm_track_save(track_id.clone(), true);
+        assert_eq!(
+            app.notifications.current().unwrap().message,
+            "Saved to Liked Songs"
+        );
+        app.toggle_save_track(track_id.clone());
+        assert!(!app.liked_song_ids_set.contains(&track_id));
+        assert!(io_rx.try_recv().is_ok());
+
+        // A failed toggle goes back to what was shown before the press
+        app.revert_track_save(track_id.clone());
+        assert!(app.liked_song_ids_set.contains(&track_id));
+        assert!(app.pending_track_saves.is_empty());
+    }
+
+    #[test]
+    fn a_like_spotify_disagrees_with_is_put_right() {
+        let (io_tx, _io_rx) = unbounded_channel();
+        let mut app = App::new(io_tx, UserConfig::new(), Utc::now());
+        let track_id = TrackId::from_id("4iV5W9uYEdYUVa79Axb7Rh").unwrap();
+
+        // Shown as liked, but it had been liked elsewhere, so Spotify removed it
+        app.toggle_save_track(track_id.clone());
+        app.confirm_track_save(track_id.clone(), false);
+        assert!(!app.liked_song_ids_set.contains(&track_id));
+        let notification = app.notifications.current().unwrap();
+        assert_eq!(notification.severity, Severity::Warning);
+        assert_eq!(
+            notification.message,
+            "The song was in Liked Songs already, so it was removed"
+        );
+
+        // Episodes can't be saved, so they always go back
+        let episode_id = EpisodeId::from_id("512ojhOuo1ktJprKbVcKyQ").unwrap();
+        app.toggle_save_episode(episode_id.clone());
+        assert!(app.liked_episode_ids_set.contains(&episode_id));
+        app.revert_episode_save(episode_id.clone());
+        assert!(!app.liked_episode_ids_set.contains(&episode_id));
+        assert!(app.pending_episode_saves.is_empty());
+    }
+
+    #[test]
+    fn reconciles_the_library_cache() {
+        let (io_tx, _io_rx) = unbounded_channel();
+        let mut app = App::new(io_tx, UserConfig::new(), Utc::now());
+        let liked = TrackId::from_id("4iV5W9uYEdYUVa79Axb7Rh").unwrap();
+        let unliked = TrackId::from_id("0eGsygTp906u18L0Oimnem").unwrap();
+        let unchecked = TrackId::from_id("3n3Ppam7vgaVa1iaRUc9Lp").unwrap();
+        let album = AlbumId::from_id("0sNOF9WDwhWunNAHPD3Baj").unwrap();
+
+        app.load_library_cache(LibraryCache {
+            version: LIBRARY_CACHE_VERSION,
+            liked_songs: vec![liked.uri(), unliked.uri(), unchecked.uri()],
+            // Uris of the wrong type, or no uri at all, are left out
+            saved_albums: vec![album.uri(), liked.uri(), String::from("nonsense")],
+            ..LibraryCache::default()
+        });
+        for id in [&liked, &unliked, &unchecked] {
+            assert!(app.liked_song_ids_set.is_stale(id));
         }
+        assert!(app.saved_album_ids_set.is_stale(&album));
+
+        // What the checks say
+        app.liked_song_ids_set.insert(liked.clone());
+        app.liked_song_ids_set.remove(&unliked);
+        assert!(
+            app.liked_song_ids_set.contains(&liked) && !app.liked_song_ids_set.is_stale(&liked)
+        );
+        assert!(!app.liked_song_ids_set.contains(&unliked));
+        assert!(app.liked_song_ids_set.is_stale(&unchecked));
+
+        // Everything still thought to be in the library is saved again
+        let cache = app.library_cache();
+        assert_eq!(cache.version, LIBRARY_CACHE_VERSION);
+        let mut liked_songs = vec![liked.uri(), unchecked.uri()];
+        liked_songs.sort();
+        assert_eq!(cache.liked_songs, liked_songs);
+        assert_eq!(cache.saved_albums, [album.uri()]);
+        assert!(cache.liked_episodes.is_empty());
     }
 
-    pub fn get_current_user_saved_tracks_next(&mut self) {
-        // Before fetching the next tracks, check if we have already fetched them
-        match self
-            .library
-            .saved_tracks
-            .get_results(Some(self.library.saved_tracks.index + 1))
-            .cloned()
-        {
-            Some(saved_tracks) => {
-                self.set_saved_tracks_to_table(&saved_tracks);
-                self.library.saved_tracks.index += 1
-            }
-            None => {
-                if let Some(saved_tracks) = &self.library.saved_tracks.get_results(None) {
-                    let offset = Some(saved_tracks.offset + saved_tracks.limit);
-                    self.dispatch(IoEvent::GetCurrentUserSavedTracks { offset });
-                }
-            }
+    #[test]
+    fn playback_polls_back_off_until_the_playback_is_controlled() {
+        let (io_tx, _io_rx) = unbounded_channel();
+        let mut app = App::new(io_tx, UserConfig::new(), Utc::now());
+        app.user_config.behavior.playback_poll_interval_seconds = 5;
+        app.user_config.behavior.playback_poll_max_interval_seconds = 60;
+        assert_eq!(app.playback_poll_interval(), Duration::from_secs(5));
+
+        app.idle_playback_polls = 3;
+        assert_eq!(app.playback_poll_interval(), Duration::from_secs(40));
+        app.idle_playback_polls = 100;
+        assert_eq!(app.playback_poll_interval(), Duration::from_secs(60));
+
+        // Reading doesn't count as an action
+        app.dispatch(IoEvent::GetDevices { silent: true });
+        assert_eq!(app.idle_playback_polls, 100);
+        app.dispatch(IoEvent::ResumePlayback);
+        assert_eq!(app.idle_playback_polls, 0);
+
+        app.is_secondary_instance = true;
+        assert_eq!(app.playback_poll_interval(), Duration::from_secs(15));
+    }
+
+    #[test]
+    fn loads_then_saves_the_library_cache_every_few_minutes() {
+        let (io_tx, mut io_rx) = unbounded_channel();
+        let mut app = App::new(io_tx, UserConfig::new(), Utc::now());
+        let now = Instant::now();
+
+        // Without a path, as for the CLI, nothing is saved
+        app.save_library_cache_if_due(now + LIBRARY_CACHE_SAVE_INTERVAL);
+        assert!(io_rx.try_recv().is_err());
+
+        app.set_library_cache_path(PathBuf::from("library.json"), now);
+        assert!(matches!(io_rx.try_recv(), Ok(IoEvent::LoadLibraryCache)));
+        app.save_library_cache_if_due(now + Duration::from_secs(60));
+        assert!(io_rx.try_recv().is_err());
+
+        let due = now + LIBRARY_CACHE_SAVE_INTERVAL;
+        app.save_library_cache_if_due(due);
+        app.save_library_cache_if_due(due);
+        assert!(matches!(io_rx.try_recv(), Ok(IoEvent::SaveLibraryCache)));
+        assert!(io_rx.try_recv().is_err());
+        app.save_library_cache_if_due(due + LIBRARY_CACHE_SAVE_INTERVAL);
+        assert!(matches!(io_rx.try_recv(), Ok(IoEvent::SaveLibraryCache)));
+    }
+
+    #[test]
+    fn ranks_artists_sharing_the_searched_name() {
+        let names = |artists: &[FullArtist]| {
+            artists
+                .iter()
+                .map(|artist| (artist.name.clone(), artist.followers.total))
+                .collect::<Vec<_>>()
+        };
+        let mut artists = vec![
+            full_artist("0000000000000000000001", "Genesis Tribute", 500),
+            full_artist("0000000000000000000002", "genesis", 1_000),
+            full_artist("0000000000000000000003", "Genesis", 3_000_000),
+            full_artist("0000000000000000000004", "The Genesis", 9_000_000),
+            full_artist("0000000000000000000005", "GENESIS", 1_000),
+        ];
+        rank_artists(" Genesis", &mut artists);
+        assert_eq!(
+            names(&artists),
+            [
+                (String::from("Genesis"), 3_000_000),
+                // Ties keep the order they were found in
+                (String::from("genesis"), 1_000),
+                (String::from("GENESIS"), 1_000),
+                (String::from("Genesis Tribute"), 500),
+                (String::from("The Genesis"), 9_000_000),
+            ]
+        );
+
+        // Names match whatever the case of letters outside of ascii
+        let mut artists = vec![
+            full_artist("0000000000000000000001", "sigur rós", 10),
+            full_artist("0000000000000000000002", "Sigur Rós Covers", 99),
+            full_artist("0000000000000000000003", "SIGUR RÓS", 20),
+        ];
+        assert_eq!(artist_name_match_count("Sigur Rós", &artists), 2);
+        rank_artists("Sigur Rós", &mut artists);
+        assert_eq!(
+            names(&artists),
+            [
+                (String::from("SIGUR RÓS"), 20),
+                (String::from("sigur rós"), 10),
+                (String::from("Sigur Rós Covers"), 99),
+            ]
+        );
+
+        // A name only one artist has leaves the order as it was
+        let mut artists = vec![
+            full_artist("0000000000000000000001", "Genesis Tribute", 500),
+            full_artist("0000000000000000000002", "Genesis", 3_000_000),
+        ];
+        rank_artists("Genesis", &mut artists);
+        assert_eq!(names(&artists)[0].0, "Genesis Tribute");
+    }
+
+    #[test]
+    fn rapid_seeks_share_one_refresh() {
+        let (io_tx, mut io_rx) = unbounded_channel();
+        let mut app = App::new(io_tx, UserConfig::new(), Utc::now());
+
+        for position_ms in [10_000, 20_000, 30_000] {
+            app.seek_ms = Some(position_ms as u128);
+            app.on_seek(position_ms);
         }
+        assert_eq!(app.song_progress_ms, 30_000);
+        assert!(app.seek_ms.is_none());
+
+        // Nothing is fetched before the refresh is due
+        app.poll_current_playback();
+        assert!(io_rx.try_recv().is_err());
+
+        app.playback_refresh_at = Some(Instant::now());
+        app.poll_current_playback();
+        app.poll_current_playback();
+        assert!(matches!(io_rx.try_recv(), Ok(IoEvent::GetCurrentPlayback)));
+        assert!(io_rx.try_recv().is_err());
     }
 
-    pub fn get_current_user_saved_tracks_previous(&mut self) {
-        if self.library.saved_tracks.index > 0 {
-            self.library.saved_tracks.index -= 1;
+    #[test]
+    fn search_limits_fit_the_height_of_the_terminal() {
+        for (height, limits) in [
+            (0, (20, 4)),
+            (10, (20, 4)),
+            (24, (20, 4)),
+            (40, (27, 7)),
+            (50, (37, 10)),
+            (63, (50, 15)),
+            (100, (50, 28)),
+            (200, (50, 50)),
+        ] {
+            assert_eq!(search_limits(height), limits, "{} rows", height);
         }
+    }
 
-        if let Some(saved_tracks) = &self.library.saved_tracks.get_results(None).cloned() {
-            self.set_saved_tracks_to_table(saved_tracks);
+    #[test]
+    fn refreshing_made_for_you_searches_again() {
+        let (io_tx, mut io_rx) = unbounded_channel();
+        let mut app = App::new(io_tx, UserConfig::new(), Utc::now());
+        let searches = |io_rx: &mut UnboundedReceiver<IoEvent<'static>>| {
+            std::iter::from_fn(|| io_rx.try_recv().ok())
+                .filter(|event| matches!(event, IoEvent::MadeForYouSearchAndAdd { .. }))
+                .count()
+        };
+
+        app.get_made_for_you();
+        assert_eq!(searches(&mut io_rx), 5);
+        app.library.made_for_you_playlists.add_pages(Page {
+            href: String::new(),
+            items: vec![],
+            limit: 20,
+            next: None,
+            offset: 0,
+            previous: None,
+            total: 0,
+        });
+        app.made_for_you_index = 3;
+        // Listed already
+        app.get_made_for_you();
+        assert_eq!(searches(&mut io_rx), 0);
+
+        app.refresh_made_for_you();
+        assert_eq!(searches(&mut io_rx), 5);
+        assert!(app.library.made_for_you_playlists.pages.is_empty());
+        assert_eq!(app.made_for_you_index, 0);
+    }
+
+    fn episodes(resume_points: &[serde_json::Value]) -> Page<SimplifiedEpisode> {
+        episode_page(
+            resume_points
+                .iter()
+                .map(|resume_point| episode_json("512ojhOuo1ktJprKbVcKyQ", resume_point.clone()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn shows_are_fully_played_once_every_episode_is() {
+        let played = serde_json::json!({ "fully_played": true, "resume_position_ms": 0 });
+        let started = serde_json::json!({ "fully_played": false, "resume_position_ms": 60_000 });
+
+        assert!(is_fully_played(&episodes(&[
+            played.clone(),
+            played.clone()
+        ])));
+        assert!(!is_fully_played(&episodes(&[played.clone(), started])));
+        // Without a resume point the episode hasn't been played
+        assert!(!is_fully_played(&episodes(&[
+            played,
+            serde_json::Value::Null
+        ])));
+        assert!(!is_fully_played(&episodes(&[])));
+    }
+
+    fn device(name: &str, id: Option<&str>, is_active: bool) -> Device {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "is_active": is_active,
+            "is_private_session": false,
+            "is_restricted": false,
+            "name": name,
+            "type": "Computer",
+            "volume_percent": 50,
+        }))
+        .unwrap()
+    }
+
+    fn devices(devices: &[(&str, Option<&str>)]) -> DevicePayload {
+        DevicePayload {
+            devices: devices
+                .iter()
+                .map(|(name, id)| device(name, *id, false))
+                .collect(),
         }
     }
 
-    pub fn shuffle(&mut self) {
-        self.dispatch(IoEvent::ToggleShuffle);
+    #[test]
+    fn finds_devices_by_id_name_or_part_of_the_name() {
+        let payload = devices(&[
+            ("Phone", Some("phone")),
+            ("Living Room (AVR-X2700H)", Some("avr")),
+            ("Speaker", Some("first")),
+            ("Speaker", Some("second")),
+            ("Kitchen Speaker", Some("kitchen")),
+        ]);
+        let found = |query| find_device(&payload, query).map_err(|e| e.to_string());
+
+        assert_eq!(found("second"), Ok((3, String::from("second"))));
+        // The first of those with the name, before those with it in their name
+        assert_eq!(found("speaker"), Ok((2, String::from("first"))));
+        assert_eq!(found("living room"), Ok((1, String::from("avr"))));
+        assert_eq!(found("x2700"), Ok((1, String::from("avr"))));
+        assert_eq!(
+            found("o"),
+            Err(String::from(
+                "more than one device matches 'o': 'Phone', 'Living Room (AVR-X2700H)'"
+            ))
+        );
+        assert_eq!(
+            found("web"),
+            Err(String::from(
+                "no device with name 'web', available devices: 'Phone', \
+'Living Room (AVR-X2700H)', 'Speaker', 'Speaker', 'Kitchen Speaker'"
+            ))
+        );
     }
 
-    pub fn get_current_user_saved_albums_next(&mut self) {
-        match self
-            .library
-            .saved_albums
-            .get_results(Some(self.library.saved_albums.index + 1))
-            .cloned()
-        {
-            Some(_) => self.library.saved_albums.index += 1,
-            None => {
-                if let Some(saved_albums) = &self.library.saved_albums.get_results(None) {
-                    let offset = Some(saved_albums.offset + saved_albums.limit);
-                    self.dispatch(IoEvent::GetCurrentUserSavedAlbums { offset });
-                }
+    #[test]
+    fn finds_the_first_device_with_the_name() {
+        let payload = devices(&[
+            ("Phone", Some("phone")),
+            ("Speaker", Some("first")),
+            ("Speaker", Some("second")),
+        ]);
+        assert_eq!(
+            find_device(&payload, "Speaker").unwrap(),
+            (1, String::from("first"))
+        );
+
+        assert_eq!(
+            find_device(&payload, "Laptop").unwrap_err().to_string(),
+            "no device with name 'Laptop', available devices: 'Phone', 'Speaker', 'Speaker'"
+        );
+        assert_eq!(
+            find_device(&devices(&[]), "Laptop")
+                .unwrap_err()
+                .to_string(),
+            "no device with name 'Laptop': no devices available"
+        );
+        assert_eq!(
+            find_device(&devices(&[("Web Player", None)]), "Web Player")
+                .unwrap_err()
+                .to_string(),
+            "failed to use device with name 'Web Player': no device id"
+        );
+    }
+
+    #[test]
+    fn transfers_to_the_device_typed() {
+        let (io_tx, mut io_rx) = unbounded_channel();
+        let mut app = App::new(io_tx, UserConfig::new(), Utc::now());
+        app.devices = Some(devices(&[
+            ("Phone", Some("phone")),
+            ("Living Room (AVR-X2700H)", Some("avr")),
+        ]));
+
+        app.start_finding_device();
+        app.set_input("o");
+        app.transfer_to_found_device();
+        // Still typing, told which devices it could be
+        assert!(app.is_finding_device());
+        assert!(io_rx.try_recv().is_err());
+        assert!(app
+            .notifications
+            .current()
+            .unwrap()
+            .message
+            .starts_with("more than one device"));
+
+        app.set_input("living");
+        app.transfer_to_found_device();
+        assert!(!app.is_finding_device());
+        assert_eq!(app.selected_device_index, Some(1));
+        assert!(matches!(
+            io_rx.try_recv(),
+            Ok(IoEvent::TransferPlaybackToDevice { device_id }) if device_id == "avr"
+        ));
+    }
+
+    #[test]
+    fn only_the_checked_finished_shows_are_removed() {
+        let (io_tx, mut io_rx) = unbounded_channel();
+        let mut app = App::new(io_tx, UserConfig::new(), Utc::now());
+        let show = |id: &str| -> SimplifiedShow {
+            serde_json::from_value(serde_json::json!({
+                "available_markets": [],
+                "copyrights": [],
+                "description": "",
+                "explicit": false,
+                "external_urls": {},
+                "href": "",
+                "id": id,
+                "images": [],
+                "languages": ["en"],
+                "media_type": "audio",
+                "name": "Show",
+                "publisher": "Publisher",
+            }))
+            .unwrap()
+        };
+
+        app.find_finished_shows();
+        assert!(matches!(io_rx.try_recv(), Ok(IoEvent::FindFinishedShows)));
+        // Once at a time
+        app.find_finished_shows();
+        assert!(io_rx.try_recv().is_err());
+        assert!(app.cancel_finding_finished_shows());
+        assert!(!app.is_finding_finished_shows);
+        assert!(!app.cancel_finding_finished_shows());
+
+        app.push_navigation_stack(RouteId::FinishedShows, ActiveBlock::FinishedShows);
+        let mut finished_shows = FinishedShows::new(vec![
+            show("4rOoJ6Egrf8K2IrywzwOMk"),
+            show("5CfCWKI5pZ28U0uOzXkDHe"),
+        ]);
+        finished_shows.selected_index = 1;
+        finished_shows.toggle_selected();
+        app.finished_shows = Some(finished_shows);
+
+        app.remove_finished_shows();
+        match io_rx.try_recv() {
+            Ok(IoEvent::RemoveSavedShows { show_ids }) => {
+                assert_eq!(show_ids, vec![show("4rOoJ6Egrf8K2IrywzwOMk").id]);
             }
+            event => panic!("unexpected {:?}", event),
         }
+        assert!(app.finished_shows.is_none());
+        assert_eq!(app.get_current_route().id, RouteId::Home);
     }
 
-    pub fn get_current_user_saved_albums_previous(&mut self) {
-        if self.library.saved_albums.index > 0 {
-            self.library.saved_albums.index -= 1;
+    #[test]
+    fn held_seek_keys_seek_once_when_due() {
+        let (io_tx, mut io_rx) = unbounded_channel();
+        let mut app = App::new(io_tx, UserConfig::new(), Utc::now());
+        app.current_playback_context = Some(playback(60_000, Utc::now()));
+        app.song_progress_ms = 60_000;
+        app.user_config.behavior.seek_milliseconds = 5_000;
+
+        app.seek_forwards();
+        let apply_at = app.seek_apply_at.unwrap();
+        app.seek_forwards();
+        app.seek_forwards();
+        // Counted from the first press
+        assert_eq!(app.seek_apply_at, Some(apply_at));
+
+        app.apply_due_seek(apply_at - Duration::from_millis(1));
+        assert!(io_rx.try_recv().is_err());
+        app.apply_due_seek(apply_at);
+        assert!(matches!(
+            io_rx.try_recv(),
+            Ok(IoEvent::Seek {
+                position_ms: 75_000
+            })
+        ));
+        assert!(app.seek_apply_at.is_none());
+    }
+
+    #[test]
+    fn ticks_slow_down_while_nothing_moves() {
+        let (io_tx, _io_rx) = unbounded_channel();
+        let mut app = App::new(io_tx, UserConfig::new(), Utc::now());
+        app.user_config.behavior.tick_rate_milliseconds = 16;
+        assert_eq!(app.tick_rate(), IDLE_TICK_RATE);
+
+        app.current_playback_context = Some(playback(0, Utc::now()));
+        assert_eq!(app.tick_rate(), Duration::from_millis(16));
+        app.current_playback_context.as_mut().unwrap().is_playing = false;
+        assert_eq!(app.tick_rate(), IDLE_TICK_RATE);
+
+        app.push_navigation_stack(RouteId::Analysis, ActiveBlock::Analysis);
+        assert_eq!(app.tick_rate(), Duration::from_millis(16));
+        app.pop_navigation_stack();
+
+        // Already slower than idle
+        app.user_config.behavior.tick_rate_milliseconds = 900;
+        assert_eq!(app.tick_rate(), Duration::from_millis(900));
+    }
+
+    fn playback(progress_ms: i64, timestamp: DateTime<Utc>) -> CurrentPlaybackContext {
+        use rspotify::model::{
+            context::Actions,
+            enums::{CurrentlyPlayingType, DeviceType, RepeatState},
+        };
+        CurrentPlaybackContext {
+            device: Device {
+                id: None,
+                is_active: true,
+                is_private_session: false,
+                is_restricted: false,
+                name: String::from("Speaker"),
+                _type: DeviceType::Speaker,
+                volume_percent: Some(50),
+            },
+            repeat_state: RepeatState::Off,
+            shuffle_state: false,
+            context: None,
+            timestamp,
+            progress: Some(TimeDelta::milliseconds(progress_ms)),
+            is_playing: true,
+            item: Some(PlayableItem::Track(FullTrack {
+                album: SimplifiedAlbum::default(),
+                artists: vec![],
+                available_markets: vec![],
+                disc_number: 1,
+                duration: TimeDelta::seconds(180),
+                explicit: false,
+                external_ids: HashMap::new(),
+                external_urls: HashMap::new(),
+                href: None,
+                id: Some(TrackId::from_id("4iV5W9uYEdYUVa79Axb7Rh").unwrap()),
+                is_local: false,
+                is_playable: None,
+                linked_from: None,
+                restrictions: None,
+                name: String::from("Song"),
+                popularity: 0,
+                preview_url: None,
+                track_number: 1,
+            })),
+            currently_playing_type: CurrentlyPlayingType::Track,
+            actions: Actions::default(),
         }
     }
 
-    pub fn current_user_saved_album_delete(&mut self, block: ActiveBlock) {
-        match block {
-            ActiveBlock::SearchResultBlock => {
-                if let Some(albums) = &self.search_results.albums {
-                    if let Some(selected_index) = self.search_results.selected_album_index {
-                        let selected_album = &albums.items[selected_index];
-                        if let Some(album_id) = selected_album.id.clone() {
-                            self.dispatch(IoEvent::CurrentUserSavedAlbumDelete { album_id });
-                        }
-                    }
-                }
-            }
-            ActiveBlock::AlbumList => {
-                if let Some(albums) = self.library.saved_albums.get_results(None) {
-                    if let Some(selected_album) = albums.items.get(self.album_list_index) {
-                        let album_id = selected_album.album.id.clone();
-                        self.dispatch(IoEvent::CurrentUserSavedAlbumDelete { album_id });
-                    }
-                }
-            }
-            ActiveBlock::ArtistBlock => {
-                if let Some(artist) = &self.artist {
-                    if let Some(selected_album) =
-                        artist.albums.items.get(artist.selected_album_index)
-                    {
-                        if let Some(album_id) = selected_album.id.clone() {
-                            self.dispatch(IoEvent::CurrentUserSavedAlbumDelete { album_id });
-                        }
-                    }
-                }
-            }
-            _ => (),
+    // Within a millisecond of the expected progress
+    fn assert_progress(app: &App, expected_ms: u128) {
+        assert!(
+            app.song_progress_ms.abs_diff(expected_ms) <= 1,
+            "{} is not {}",
+            app.song_progress_ms,
+            expected_ms
+        );
+    }
+
+    #[test]
+    fn progress_accounts_for_the_poll_latency() {
+        let mut app = App::default();
+        let requested_at = Instant::now();
+        let latency = Duration::from_millis(400);
+        let received_at = requested_at + latency;
+        let now = Utc::now();
+
+        // The timestamp is when playback last changed, long before the request
+        let context = playback(60_000, now - TimeDelta::minutes(10));
+        assert!(app.set_current_playback(context, requested_at, received_at, now));
+        // Measured half way through the request
+        assert_progress(&app, 60_200);
+        app.update_progress(received_at + Duration::from_secs(2));
+        assert_progress(&app, 62_200);
+
+        // The timestamp is when the progress was measured, 100ms before the response arrived
+        let requested_at = received_at + Duration::from_secs(5);
+        let received_at = requested_at + latency;
+        let now = now + TimeDelta::seconds(6);
+        let context = playback(66_000, now - TimeDelta::milliseconds(100));
+        assert!(app.set_current_playback(context, requested_at, received_at, now));
+        assert_progress(&app, 66_100);
+        app.update_progress(received_at + Duration::from_secs(1));
+        assert_progress(&app, 67_100);
+    }
+
+    #[test]
+    fn shows_what_plays_next_as_soon_as_the_track_ends() {
+        let (io_tx, mut io_rx) = unbounded_channel();
+        let mut app = App::new(io_tx, UserConfig::new(), Utc::now());
+        let now = Utc::now();
+        let received_at = Instant::now();
+        let mut ended = playback(180_000, now);
+        ended.context = Some(
+            serde_json::from_value(serde_json::json!({
+                "uri": "spotify:user:listener:collection",
+                "href": "",
+                "external_urls": {},
+                "type": "playlist",
+            }))
+            .unwrap(),
+        );
+        let playing = ended.item.clone().unwrap();
+        let mut next = playing.clone();
+        if let PlayableItem::Track(track) = &mut next {
+            track.id = Some(TrackId::from_id("0eGsygTp906u18L0Oimnem").unwrap());
         }
+        app.item_table.context = Some(ItemTableContext::SavedTracks);
+        app.item_table.set_items(vec![playing, next.clone()]);
+        app.set_current_playback(ended.clone(), received_at, received_at, now);
+
+        app.refresh_at_item_end(received_at);
+        assert!(matches!(io_rx.try_recv(), Ok(IoEvent::GetCurrentPlayback)));
+        assert_eq!(
+            app.upcoming_item.as_ref().and_then(|item| item.id()),
+            next.id()
+        );
+
+        // Refreshed once, even when the poll still has the track that ended
+        app.is_fetching_current_playback = false;
+        ended.timestamp = now + TimeDelta::seconds(1);
+        let received_at = received_at + Duration::from_secs(1);
+        app.set_current_playback(ended.clone(), received_at, received_at, ended.timestamp);
+        app.refresh_at_item_end(received_at);
+        assert!(io_rx.try_recv().is_err());
+        assert!(app.upcoming_item.is_some());
+
+        // Until a poll has what plays next
+        let mut started = ended;
+        started.item = Some(next);
+        started.progress = Some(TimeDelta::seconds(1));
+        started.timestamp = now + TimeDelta::seconds(2);
+        let received_at = received_at + Duration::from_secs(1);
+        app.set_current_playback(started.clone(), received_at, received_at, started.timestamp);
+        assert!(app.upcoming_item.is_none());
+
+        // Repeat plays something else next
+        let context = app.current_playback_context.as_mut().unwrap();
+        context.item = app.item_table.items.first().cloned();
+        assert!(app.next_in_item_table().is_some());
+        app.current_playback_context.as_mut().unwrap().repeat_state = RepeatState::Context;
+        assert!(app.next_in_item_table().is_none());
     }
 
-    pub fn current_user_saved_album_add(&mut self, block: ActiveBlock) {
-        match block {
-            ActiveBlock::SearchResultBlock => {
-                if let Some(albums) = &self.search_results.albums {
-                    if let Some(selected_index) = self.search_results.selected_album_index {
-                        let selected_album = &albums.items[selected_index];
-                        if let Some(album_id) = selected_album.id.clone() {
-                            self.dispatch(IoEvent::CurrentUserSavedAlbumAdd { album_id });
-                        }
-                    }
-                }
-            }
-            ActiveBlock::ArtistBlock => {
-                if let Some(artist) = &self.artist {
-                    if let Some(selected_album) =
-                        artist.albums.items.get(artist.selected_album_index)
-                    {
-                        if let Some(album_id) = selected_album.id.clone() {
-                            self.dispatch(IoEvent::CurrentUserSavedAlbumAdd { album_id });
-                        }
-                    }
-                }
-            }
-            _ => (),
+    #[test]
+    fn polls_without_a_progress_carry_on_from_the_last_one() {
+        let (io_tx, _io_rx) = unbounded_channel();
+        let mut app = App::new(io_tx, UserConfig::new(), Utc::now());
+        let now = Utc::now();
+        let received_at = Instant::now();
+        app.set_current_playback(playback(30_000, now), received_at, received_at, now);
+        assert!(!app.progress_estimate.is_approximate());
+
+        let mut context = playback(0, now + TimeDelta::seconds(5));
+        context.progress = None;
+        let received_at = received_at + Duration::from_secs(5);
+        app.set_current_playback(
+            context,
+            received_at,
+            received_at,
+            now + TimeDelta::seconds(5),
+        );
+        assert_progress(&app, 35_000);
+        assert!(app.progress_estimate.is_approximate());
+        app.update_progress(received_at + Duration::from_secs(1));
+        assert_progress(&app, 36_000);
+
+        let received_at = received_at + Duration::from_secs(5);
+        let now = now + TimeDelta::seconds(10);
+        app.set_current_playback(playback(38_000, now), received_at, received_at, now);
+        assert_progress(&app, 38_000);
+        assert!(!app.progress_estimate.is_approximate());
+    }
+
+    #[test]
+    fn saves_the_album_or_show_of_what_is_playing() {
+        let (io_tx, mut io_rx) = unbounded_channel();
+        let mut app = App::new(io_tx, UserConfig::new(), Utc::now());
+        let episode: rspotify::model::show::FullEpisode =
+            serde_json::from_value(serde_json::json!({
+                "audio_preview_url": null,
+                "description": "",
+                "duration_ms": 1_800_000,
+                "explicit": false,
+                "external_urls": {},
+                "href": "",
+                "id": "512ojhOuo1ktJprKbVcKyQ",
+                "images": [],
+                "is_externally_hosted": false,
+                "is_playable": true,
+                "language": "en",
+                "languages": [],
+                "name": "Episode",
+                "release_date": "2020-01-01",
+                "release_date_precision": "day",
+                "resume_point": null,
+                "show": {
+                    "available_markets": [],
+                    "copyrights": [],
+                    "description": "",
+                    "explicit": false,
+                    "external_urls": {},
+                    "href": "",
+                    "id": "4rOoJ6Egrf8K2IrywzwOMk",
+                    "images": [],
+                    "languages": [],
+                    "media_type": "audio",
+                    "name": "Podcast",
+                    "publisher": "",
+                },
+            }))
+            .unwrap();
+        let show_id = episode.show.id.clone();
+        let mut context = playback(0, Utc::now());
+        context.item = Some(PlayableItem::Episode(episode));
+        app.current_playback_context = Some(context);
+
+        // Not known to be followed or not, so that is checked first
+        app.toggle_save_playing_item_parent();
+        assert!(matches!(
+            io_rx.try_recv(),
+            Ok(IoEvent::CurrentUserSavedShowsContains { show_ids }) if show_ids == [show_id.clone()]
+        ));
+
+        app.checked_show_ids
+            .mark_checked([show_id.clone()], Instant::now());
+        app.toggle_save_playing_item_parent();
+        assert!(matches!(
+            io_rx.try_recv(),
+            Ok(IoEvent::CurrentUserSavedShowAdd { .. })
+        ));
+        assert_eq!(
+            app.notifications.current().unwrap().message,
+            "Following the podcast 'Podcast'"
+        );
+
+        app.saved_show_ids_set.insert(show_id);
+        app.toggle_save_playing_item_parent();
+        assert!(matches!(
+            io_rx.try_recv(),
+            Ok(IoEvent::CurrentUserSavedShowDelete { .. })
+        ));
+        assert_eq!(
+            app.notifications.current().unwrap().message,
+            "Unfollowing the podcast 'Podcast'"
+        );
+
+        // The album of a track, which is known to be saved once seen in the library
+        let album_id = AlbumId::from_id("4aawyAB9vmqN3uQ7FjRGTy").unwrap();
+        let mut context = playback(0, Utc::now());
+        if let Some(PlayableItem::Track(track)) = &mut context.item {
+            track.album.id = Some(album_id.clone());
+            track.album.name = String::from("Album");
+        }
+        app.current_playback_context = Some(context);
+        app.saved_album_ids_set.insert(album_id);
+        app.toggle_save_playing_item_parent();
+        assert!(matches!(
+            io_rx.try_recv(),
+            Ok(IoEvent::CurrentUserSavedAlbumDelete { .. })
+        ));
+        assert_eq!(
+            app.notifications.current().unwrap().message,
+            "Removing the album 'Album' from your library"
+        );
+
+        // Without an album id there is nothing to save
+        app.current_playback_context = Some(playback(0, Utc::now()));
+        app.toggle_save_playing_item_parent();
+        assert!(io_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn pausing_freezes_the_progress() {
+        let (io_tx, mut io_rx) = unbounded_channel();
+        let mut app = App::new(io_tx, UserConfig::new(), Utc::now());
+        let now = Utc::now();
+        let requested_at = Instant::now();
+        let received_at = requested_at + Duration::from_millis(200);
+        app.set_current_playback(playback(30_000, now), requested_at, received_at, now);
+
+        app.toggle_playback();
+        assert!(matches!(io_rx.try_recv(), Ok(IoEvent::PausePlayback)));
+        assert!(!app.is_playing());
+        let paused_at_ms = app.song_progress_ms;
+        app.update_progress(Instant::now() + Duration::from_secs(3));
+        assert_eq!(app.song_progress_ms, paused_at_ms);
+
+        // A poll sent before the pause still finds it playing, and is out of date
+        assert!(!app.set_current_playback(
+            playback(31_000, now + TimeDelta::seconds(1)),
+            requested_at,
+            Instant::now(),
+            now + TimeDelta::seconds(1),
+        ));
+        assert!(!app.is_playing());
+
+        // As is one measured before the playback already taken in
+        let requested_at = Instant::now();
+        assert!(!app.set_current_playback(
+            playback(29_000, now - TimeDelta::seconds(1)),
+            requested_at,
+            requested_at,
+            now,
+        ));
+        assert!(!app.is_playing());
+    }
+
+    #[test]
+    fn later_seeks_are_kept() {
+        let mut app = App {
+            seek_ms: Some(20_000),
+            ..App::default()
+        };
+
+        app.on_seek(10_000);
+        assert_eq!(app.seek_ms, Some(20_000));
+    }
+
+    #[test]
+    fn recent_checks_are_skipped() {
+        let mut checks = RecentChecks::default();
+        let now = Instant::now();
+
+        assert_eq!(checks.unchecked([1, 2, 2, 3], now), vec![1, 2, 3]);
+
+        checks.mark_checked([1, 2], now);
+        assert_eq!(
+            checks.unchecked([1, 2, 3], now + SAVED_CHECK_TTL / 2),
+            vec![3]
+        );
+        assert_eq!(
+            checks.unchecked([1, 2, 3], now + SAVED_CHECK_TTL),
+            vec![1, 2, 3]
+        );
+
+        // Expired checks are dropped as new ones come in
+        checks.mark_checked([3], now + SAVED_CHECK_TTL);
+        assert_eq!(checks.checked_at.len(), 1);
+    }
+
+    fn saved_album_app() -> (App, UnboundedReceiver<IoEvent<'static>>, LibraryItem) {
+        let (io_tx, io_rx) = unbounded_channel();
+        let mut app = App::new(io_tx, UserConfig::new(), Utc::now());
+        let album_id = AlbumId::from_id("4aawyAB9vmqN3uQ7FjRGTy").unwrap();
+        app.saved_album_ids_set.insert(album_id.clone());
+        (app, io_rx, LibraryItem::Album(album_id))
+    }
+
+    #[test]
+    fn saving_a_saved_item_twice_removes_it() {
+        let (mut app, mut io_rx, album) = saved_album_app();
+        let now = Instant::now();
+
+        app.save_library_item(album.clone(), "Global Warming", now);
+        assert!(io_rx.try_recv().is_err());
+        assert_eq!(
+            app.notifications.current().unwrap().message,
+            "'Global Warming' is already in your library — press again to remove"
+        );
+
+        app.save_library_item(album, "Global Warming", now + Duration::from_secs(1));
+        assert!(matches!(
+            io_rx.try_recv(),
+            Ok(IoEvent::CurrentUserSavedAlbumDelete { .. })
+        ));
+        assert!(app.pending_removal.is_none());
+    }
+
+    #[test]
+    fn removal_expires() {
+        let (mut app, mut io_rx, album) = saved_album_app();
+        let now = Instant::now();
+
+        app.save_library_item(album.clone(), "Global Warming", now);
+        app.expire_pending_removal(now + REMOVAL_CONFIRM_WINDOW);
+        assert!(app.pending_removal.is_none());
+
+        // The second press only arms the removal again
+        app.save_library_item(album, "Global Warming", now + REMOVAL_CONFIRM_WINDOW);
+        assert!(io_rx.try_recv().is_err());
+        assert!(app.pending_removal.is_some());
+    }
+
+    #[test]
+    fn removal_is_only_for_the_same_item() {
+        let (mut app, mut io_rx, album) = saved_album_app();
+        let now = Instant::now();
+        let other_album = AlbumId::from_id("6akEvsycLGftJxYudPjmqK").unwrap();
+
+        app.save_library_item(album.clone(), "Global Warming", now);
+        // Not saved yet, so it is saved straight away and disarms the removal
+        app.save_library_item(LibraryItem::Album(other_album), "Planet Pit", now);
+        assert!(matches!(
+            io_rx.try_recv(),
+            Ok(IoEvent::CurrentUserSavedAlbumAdd { .. })
+        ));
+
+        app.save_library_item(album, "Global Warming", now);
+        assert!(io_rx.try_recv().is_err());
+        assert!(app.pending_removal.is_some());
+    }
+
+    #[test]
+    fn album_art_cache_keeps_the_recently_used_images() {
+        let mut cache = AlbumArtCache::default();
+        let now = Instant::now();
+        for index in 0..ALBUM_ART_CACHE_SIZE {
+            assert!(cache.start_fetch(&index.to_string(), now));
+            cache.insert(index.to_string(), AlbumArt::Loaded(vec![]));
         }
+        // Using the oldest one keeps it over the next oldest
+        assert!(!cache.start_fetch("0", now));
+        assert!(cache.start_fetch("new", now));
+        assert!(matches!(cache.get("0"), Some(AlbumArt::Loaded(_))));
+        assert!(cache.get("1").is_none());
+        assert!(matches!(cache.get("new"), Some(AlbumArt::Loading)));
+        assert_eq!(cache.entries.len(), ALBUM_ART_CACHE_SIZE);
+    }
+
+    #[test]
+    fn album_art_that_failed_is_tried_again_later() {
+        let mut cache = AlbumArtCache::default();
+        let now = Instant::now();
+        assert!(cache.start_fetch("cover", now));
+        // Still loading
+        assert!(!cache.start_fetch("cover", now));
+
+        cache.insert(String::from("cover"), AlbumArt::Failed(now));
+        assert!(!cache.start_fetch("cover", now + ALBUM_ART_RETRY_DELAY / 2));
+        assert!(matches!(cache.get("cover"), Some(AlbumArt::Failed(_))));
+        assert!(cache.start_fetch("cover", now + ALBUM_ART_RETRY_DELAY));
+        assert!(matches!(cache.get("cover"), Some(AlbumArt::Loading)));
+    }
+
+    #[test]
+    fn accents_the_item_table_with_the_playlist_cover() {
+        let (io_tx, mut io_rx) = unbounded_channel();
+        let mut app = App::new(io_tx, UserConfig::new(), Utc::now());
+        let playlist = serde_json::json!({
+            "collaborative": false,
+            "external_urls": {},
+            "href": "",
+            "id": "37i9dQZF1DXcBWIGoYBM5M",
+            "images": [{"url": "cover", "width": 300, "height": 300}],
+            "name": "Today's Top Hits",
+            "owner": {"external_urls": {}, "href": "", "id": "spotify"},
+            "public": true,
+            "snapshot_id": "",
+            "tracks": {"href": "", "total": 50},
+        });
+        app.playlists = Some(
+            serde_json::from_value(serde_json::json!({
+                "href": "",
+                "items": [playlist],
+                "limit": 20,
+                "next": null,
+                "offset": 0,
+                "previous": null,
+                "total": 1,
+            }))
+            .unwrap(),
+        );
+        app.selected_playlist_index = Some(0);
+        app.item_table.context = Some(ItemTableContext::MyPlaylists);
+
+        // Off unless enabled
+        app.fetch_item_table_cover();
+        assert!(io_rx.try_recv().is_err());
+
+        app.is_cover_accent_enabled = true;
+        app.fetch_item_table_cover();
+        app.fetch_item_table_cover();
+        assert!(matches!(
+            io_rx.try_recv(),
+            Ok(IoEvent::GetAlbumArt { url }) if url == "cover"
+        ));
+        assert!(io_rx.try_recv().is_err());
+        assert_eq!(app.item_table_accent(), None);
+
+        let mut bytes = std::io::Cursor::new(vec![]);
+        image::DynamicImage::ImageRgb8(image::RgbImage::from_pixel(
+            8,
+            8,
+            image::Rgb([30, 185, 84]),
+        ))
+        .write_to(&mut bytes, image::ImageOutputFormat::Png)
+        .unwrap();
+        app.album_art
+            .insert(String::from("cover"), AlbumArt::Loaded(bytes.into_inner()));
+        app.fetch_item_table_cover();
+        assert_eq!(app.item_table_accent(), Some(Color::Rgb(30, 185, 84)));
+
+        // A cover that can't be used leaves the theme's colors
+        app.cover_accents.clear();
+        app.album_art
+            .insert(String::from("cover"), AlbumArt::Loaded(b"broken".to_vec()));
+        app.fetch_item_table_cover();
+        assert_eq!(app.item_table_accent(), None);
+        assert!(io_rx.try_recv().is_err());
     }
 
-    pub fn get_current_user_saved_shows_next(&mut self) {
-        match self
-            .library
-            .saved_shows
-            .get_results(Some(self.library.saved_shows.index + 1))
-            .cloned()
-        {
-            Some(_) => self.library.saved_shows.index += 1,
-            None => {
-                if let Some(saved_shows) = &self.library.saved_shows.get_results(None) {
-                    let offset = Some(saved_shows.offset + saved_shows.limit);
-                    self.dispatch(IoEvent::GetCurrentUserSavedShows { offset });
-                }
-            }
-        }
+    fn album_page(ids: &[&str], offset: u32, total: u32) -> Page<SimplifiedAlbum> {
+        let limit = 3;
+        let items = ids
+            .iter()
+            .map(|id| {
+                serde_json::json!({
+                    "artists": [],
+                    "available_markets": [],
+                    "external_urls": {},
+                    "href": null,
+                    "id": format!("{:0>22}", id),
+                    "images": [],
+                    "name": id,
+                    "release_date": null,
+                    "release_date_precision": null,
+                })
+            })
+            .collect::<Vec<_>>();
+        serde_json::from_value(serde_json::json!({
+            "href": "",
+            "items": items,
+            "limit": limit,
+            "next": (offset + limit < total).then_some("https://api.spotify.com/next"),
+            "offset": offset,
+            "previous": null,
+            "total": total,
+        }))
+        .unwrap()
     }
 
-    pub fn get_current_user_saved_shows_previous(&mut self) {
-        if self.library.saved_shows.index > 0 {
-            self.library.saved_shows.index -= 1;
+    fn artist_with_albums(pages: Vec<Page<SimplifiedAlbum>>) -> Artist {
+        let mut artist = Artist {
+            artist_id: ArtistId::from_id("0OdUWJ0sBjDrqHygGUXeCF").unwrap(),
+            artist_name: String::from("Band of Horses"),
+            albums: ScrollableResultPages::default(),
+            album_type: None,
+            pending_albums_offset: None,
+            related_artists: vec![],
+            top_tracks: vec![],
+            selected_album_index: 0,
+            selected_related_artist_index: 0,
+            selected_top_track_index: 0,
+            artist_hovered_block: ArtistBlock::Albums,
+            artist_selected_block: ArtistBlock::Albums,
+        };
+        for page in pages {
+            artist.add_albums_page(page);
         }
+        artist
     }
 
-    pub fn get_episode_table_next(&mut self, show_id: ShowId<'_>) {
-        match self
-            .library
-            .show_episodes
-            .get_results(Some(self.library.show_episodes.index + 1))
-            .cloned()
-        {
-            Some(_) => self.library.show_episodes.index += 1,
-            None => {
-                if let Some(show_episodes) = &self.library.show_episodes.get_results(None) {
-                    let offset = Some(show_episodes.offset + show_episodes.limit);
-                    self.dispatch(IoEvent::GetCurrentShowEpisodes { show_id, offset });
-                }
-            }
-        }
+    #[test]
+    fn artist_discovery_searches_for_the_whole_name() {
+        assert_eq!(
+            ArtistDiscovery::Playlists.search_term("Band of Horses"),
+            "\"Band of Horses\""
+        );
+        assert_eq!(
+            ArtistDiscovery::AppearsOn.search_term("Band of Horses"),
+            "artist:\"Band of Horses\""
+        );
+        // Quotes in the name would end the quoted search early
+        assert_eq!(
+            ArtistDiscovery::AppearsOn.search_term("\"Weird Al\" Yankovic"),
+            "artist:\"Weird Al Yankovic\""
+        );
     }
 
-    pub fn get_episode_table_previous(&mut self) {
-        if self.library.show_episodes.index > 0 {
-            self.library.show_episodes.index -= 1;
-        }
+    #[test]
+    fn artist_discovery_lands_on_its_category() {
+        let (io_tx, mut io_rx) = unbounded_channel();
+        let mut app = App::new(io_tx, UserConfig::new(), Utc::now());
+        app.artist = Some(artist_with_albums(vec![]));
+        app.search_results.search_term = String::from("an earlier search");
+        app.search_results.selected_block = SearchResultBlock::SongSearch;
+
+        app.search_from_artist(ArtistDiscovery::Playlists);
+
+        let Ok(IoEvent::GetSearchResults {
+            search_term, types, ..
+        }) = io_rx.try_recv()
+        else {
+            panic!("a search is made");
+        };
+        assert_eq!(search_term, "\"Band of Horses\"");
+        assert_eq!(types, Some(vec![SearchType::Playlist]));
+        assert_eq!(app.get_current_route().id, RouteId::Search);
+        assert_eq!(
+            app.get_current_route().active_block,
+            ActiveBlock::SearchResultBlock
+        );
+        assert_eq!(
+            app.search_results.hovered_block,
+            SearchResultBlock::PlaylistSearch
+        );
+        assert_eq!(app.search_results.selected_block, SearchResultBlock::Empty);
+        assert!(app.search_results.search_term.is_empty());
+        assert_eq!(app.input.iter().collect::<String>(), search_term);
+
+        app.search_from_artist(ArtistDiscovery::AppearsOn);
+        assert!(matches!(
+            io_rx.try_recv(),
+            Ok(IoEvent::GetSearchResults { types: Some(types), .. }) if types == [SearchType::Album]
+        ));
+        assert_eq!(
+            app.search_results.hovered_block,
+            SearchResultBlock::AlbumSearch
+        );
     }
 
-    pub fn user_unfollow_artists(&mut self, block: ActiveBlock) {
-        match block {
-            ActiveBlock::SearchResultBlock => {
-                if let Some(artists) = &self.search_results.artists {
-                    if let Some(selected_index) = self.search_results.selected_artists_index {
-                        let selected_artist: &FullArtist = &artists.items[selected_index];
-                        let artist_id = selected_artist.id.clone();
-                        self.dispatch(IoEvent::UserUnfollowArtists {
-                            artist_ids: vec![artist_id],
-                        });
-                    }
-                }
-            }
-            ActiveBlock::AlbumList => {
-                if let Some(artists) = self.library.saved_artists.get_results(None) {
-                    if let Some(selected_artist) = artists.items.get(self.artists_list_index) {
-                        let artist_id = selected_artist.id.clone();
-                        self.dispatch(IoEvent::UserUnfollowArtists {
-                            artist_ids: vec![artist_id],
-                        });
-                    }
-                }
-            }
-            ActiveBlock::ArtistBlock => {
-                if let Some(artist) = &self.artist {
-                    let selected_artis =
-                        &artist.related_artists[artist.selected_related_artist_index];
-                    let artist_id = selected_artis.id.clone();
-                    self.dispatch(IoEvent::UserUnfollowArtists {
-                        artist_ids: vec![artist_id],
-                    });
-                }
-            }
-            _ => (),
+    #[test]
+    fn artist_albums_across_pages() {
+        let mut artist = artist_with_albums(vec![
+            album_page(&["a", "b", "c"], 0, 8),
+            // Also released as part of another group
+            album_page(&["c", "d", "e"], 3, 8),
+        ]);
+
+        let names = |artist: &Artist| {
+            artist
+                .albums()
+                .iter()
+                .map(|album| album.name.clone())
+                .collect::<Vec<_>>()
         };
+        assert_eq!(names(&artist), ["a", "b", "c", "d", "e"]);
+        artist.selected_album_index = 3;
+        assert_eq!(artist.selected_album().unwrap().name, "d");
+        assert_eq!(artist.next_albums_offset(), Some(6));
+        assert_eq!(artist.albums_title(), "Albums (6 of 8 releases loaded)");
+
+        artist.add_albums_page(album_page(&["f", "a"], 6, 8));
+        assert_eq!(names(&artist), ["a", "b", "c", "d", "e", "f"]);
+        artist.selected_album_index = 5;
+        assert_eq!(artist.selected_album().unwrap().name, "f");
+        assert_eq!(artist.next_albums_offset(), None);
+        assert_eq!(artist.albums_title(), "Albums");
+
+        artist.selected_album_index = 6;
+        assert!(artist.selected_album().is_none());
     }
 
-    pub fn user_follow_artists(&mut self, block: ActiveBlock) {
-        match block {
-            ActiveBlock::SearchResultBlock => {
-                if let Some(artists) = &self.search_results.artists {
-                    if let Some(selected_index) = self.search_results.selected_artists_index {
-                        let selected_artist: &FullArtist = &artists.items[selected_index];
-                        let artist_id = selected_artist.id.clone();
-                        self.dispatch(IoEvent::UserFollowArtists {
-                            artist_ids: vec![artist_id],
-                        });
-                    }
-                }
-            }
-            ActiveBlock::ArtistBlock => {
-                if let Some(artist) = &self.artist {
-                    let selected_artis =
-                        &artist.related_artists[artist.selected_related_artist_index];
-                    let artist_id = selected_artis.id.clone();
-                    self.dispatch(IoEvent::UserFollowArtists {
-                        artist_ids: vec![artist_id],
-                    });
-                }
-            }
-            _ => (),
-        }
+    #[test]
+    fn cycles_the_top_items_time_range() {
+        let (io_tx, mut io_rx) = unbounded_channel();
+        let mut app = App::new(io_tx, UserConfig::new(), Utc::now());
+        let mut fetched = || match io_rx.try_recv() {
+            Ok(IoEvent::GetUserTopItems {
+                kind,
+                time_range,
+                offset,
+            }) => Some((kind, time_range, offset)),
+            _ => None,
+        };
+
+        app.open_top_items();
+        assert_eq!(app.get_current_route().id, RouteId::ItemTable);
+        assert_eq!(app.top_items.title(), "Top Tracks (last 4 weeks)");
+        assert_eq!(
+            fetched(),
+            Some((TopItemKind::Tracks, TimeRange::ShortTerm, 0))
+        );
+
+        app.cycle_top_items_time_range();
+        assert_eq!(app.top_items.title(), "Top Tracks (last 6 months)");
+        assert_eq!(
+            fetched(),
+            Some((TopItemKind::Tracks, TimeRange::MediumTerm, 0))
+        );
+        app.cycle_top_items_time_range();
+        assert_eq!(app.top_items.title(), "Top Tracks (all time)");
+        app.cycle_top_items_time_range();
+        assert_eq!(app.top_items.time_range, TimeRange::ShortTerm);
+
+        // The artists replace the tracks, and the time range stays
+        app.toggle_top_item_kind();
+        let route = app.get_current_route();
+        assert_eq!(
+            (route.id.clone(), route.active_block),
+            (RouteId::TopArtists, ActiveBlock::TopArtists)
+        );
+        assert_eq!(app.navigation_stack.len(), 2);
+        assert_eq!(app.top_items.title(), "Top Artists (last 4 weeks)");
+        fetched();
+        fetched();
+        assert_eq!(
+            fetched(),
+            Some((TopItemKind::Artists, TimeRange::ShortTerm, 0))
+        );
     }
 
-    pub fn user_follow_playlist(&mut self) {
-        if let SearchResult {
-            playlists: Some(ref playlists),
-            selected_playlists_index: Some(selected_index),
-            ..
-        } = self.search_results
-        {
-            let selected_playlist: &SimplifiedPlaylist = &playlists.items[selected_index];
-            let playlist_id = selected_playlist.id.clone();
-            let is_public = selected_playlist.public;
-            self.dispatch(IoEvent::UserFollowPlaylist {
-                playlist_id,
-                is_public,
-            });
-        }
+    #[test]
+    fn pages_through_top_items() {
+        let (io_tx, mut io_rx) = unbounded_channel();
+        let mut app = App::new(io_tx, UserConfig::new(), Utc::now());
+        let page = |offset, total| Page::<FullTrack> {
+            href: String::new(),
+            items: vec![],
+            limit: 20,
+            next: None,
+            offset,
+            previous: None,
+            total,
+        };
+
+        // Nothing is shown yet
+        app.top_items_next_page();
+        assert!(io_rx.try_recv().is_err());
+
+        app.top_items.tracks = Some(page(0, 45));
+        app.top_items_previous_page();
+        assert!(io_rx.try_recv().is_err());
+        app.top_items_next_page();
+        assert!(matches!(
+            io_rx.try_recv(),
+            Ok(IoEvent::GetUserTopItems { offset: 20, .. })
+        ));
+
+        app.top_items.tracks = Some(page(40, 45));
+        app.top_items_next_page();
+        assert!(io_rx.try_recv().is_err());
+        app.top_items_previous_page();
+        assert!(matches!(
+            io_rx.try_recv(),
+            Ok(IoEvent::GetUserTopItems { offset: 20, .. })
+        ));
     }
 
-    pub fn user_unfollow_playlist(&mut self) {
-        if let (Some(playlists), Some(selected_index), Some(_)) =
-            (&self.playlists, self.selected_playlist_index, &self.user)
-        {
-            let selected_playlist = &playlists.items[selected_index];
-            let playlist_id = selected_playlist.id.clone();
-            self.dispatch(IoEvent::UserUnfollowPlaylist { playlist_id })
-        }
+    #[test]
+    fn follows_the_artist_shown() {
+        let (io_tx, mut io_rx) = unbounded_channel();
+        let mut app = App::new(io_tx, UserConfig::new(), Utc::now());
+        app.toggle_follow_viewed_artist();
+        assert!(io_rx.try_recv().is_err());
+
+        app.artist = Some(artist_with_albums(vec![]));
+        let artist_id = ArtistId::from_id("0OdUWJ0sBjDrqHygGUXeCF").unwrap();
+        app.toggle_follow_viewed_artist();
+        assert!(app.followed_artist_ids_set.contains(&artist_id));
+        assert!(matches!(
+            io_rx.try_recv(),
+            Ok(IoEvent::UserFollowArtists { artist_ids }) if artist_ids == [artist_id.clone()]
+        ));
+
+        app.toggle_follow_viewed_artist();
+        assert!(!app.followed_artist_ids_set.contains(&artist_id));
+        assert!(matches!(
+            io_rx.try_recv(),
+            Ok(IoEvent::UserUnfollowArtists { artist_ids }) if artist_ids == [artist_id]
+        ));
     }
 
-    pub fn user_unfollow_playlist_search_result(&mut self) {
-        if let (Some(playlists), Some(selected_index), Some(_)) = (
-            &self.search_results.playlists,
-            self.search_results.selected_playlists_index,
-            &self.user,
-        ) {
-            let selected_playlist = &playlists.items[selected_index];
-            let playlist_id = selected_playlist.id.clone();
-            self.dispatch(IoEvent::UserUnfollowPlaylist { playlist_id })
-        }
+    #[test]
+    fn more_artist_albums_are_fetched_near_the_end() {
+        let (io_tx, mut io_rx) = unbounded_channel();
+        let mut app = App::new(io_tx, UserConfig::new(), Utc::now());
+        let mut artist = artist_with_albums(vec![
+            album_page(&["a", "b", "c"], 0, 20),
+            album_page(&["d", "e", "f"], 3, 20),
+            album_page(&["g", "h", "i"], 6, 20),
+        ]);
+        app.artist = Some(artist.clone());
+
+        app.fetch_more_artist_albums();
+        assert!(io_rx.try_recv().is_err());
+
+        artist.selected_album_index = 4;
+        app.artist = Some(artist);
+        app.fetch_more_artist_albums();
+        assert!(matches!(
+            io_rx.try_recv(),
+            Ok(IoEvent::GetArtistAlbums { offset: 9, .. })
+        ));
+        // Only requested once
+        app.fetch_more_artist_albums();
+        assert!(io_rx.try_recv().is_err());
+
+        let artist = app.artist.as_mut().unwrap();
+        artist.add_albums_page(album_page(&["j", "k", "l"], 9, 20));
+        assert_eq!(artist.pending_albums_offset, None);
+        assert_eq!(artist.selected_album().unwrap().name, "e");
     }
 
-    pub fn user_follow_show(&mut self, block: ActiveBlock) {
-        match block {
-            ActiveBlock::SearchResultBlock => {
-                if let Some(shows) = &self.search_results.shows {
-                    if let Some(selected_index) = self.search_results.selected_shows_index {
-                        if let Some(show_id) =
-                            shows.items.get(selected_index).map(|item| item.id.clone())
-                        {
-                            self.dispatch(IoEvent::CurrentUserSavedShowAdd { show_id });
-                        }
-                    }
-                }
-            }
-            ActiveBlock::EpisodeTable => match self.episode_table_context {
-                EpisodeTableContext::Full => {
-                    if let Some(selected_episode) = self.selected_show_full.clone() {
-                        let show_id = selected_episode.show.id;
-                        self.dispatch(IoEvent::CurrentUserSavedShowAdd { show_id });
-                    }
-                }
-                EpisodeTableContext::Simplified => {
-                    if let Some(selected_episode) = self.selected_show_simplified.clone() {
-                        let show_id = selected_episode.show.id;
-                        self.dispatch(IoEvent::CurrentUserSavedShowAdd { show_id });
-                    }
-                }
-            },
-            _ => (),
+    #[test]
+    fn filters_artist_albums_by_group() {
+        let (io_tx, mut io_rx) = unbounded_channel();
+        let mut app = App::new(io_tx, UserConfig::new(), Utc::now());
+        let mut artist = artist_with_albums(vec![
+            album_page(&["a", "b", "c"], 0, 8),
+            album_page(&["d", "e", "f"], 3, 8),
+        ]);
+        artist.selected_album_index = 4;
+        app.artist = Some(artist);
+
+        app.cycle_artist_album_type();
+        let artist = app.artist.as_ref().unwrap();
+        assert_eq!(artist.album_type, Some(AlbumType::Album));
+        assert!(artist.albums().is_empty());
+        assert_eq!(artist.selected_album_index, 0);
+        assert_eq!(artist.albums_title(), "Albums: album");
+        assert!(matches!(
+            io_rx.try_recv(),
+            Ok(IoEvent::GetArtistAlbums {
+                offset: 0,
+                album_type: Some(AlbumType::Album),
+                ..
+            })
+        ));
+
+        // Nothing more until the first page of the group is in
+        app.fetch_more_artist_albums();
+        assert!(io_rx.try_recv().is_err());
+
+        for album_type in [
+            Some(AlbumType::Single),
+            Some(AlbumType::AppearsOn),
+            Some(AlbumType::Compilation),
+            None,
+        ] {
+            app.cycle_artist_album_type();
+            assert_eq!(app.artist.as_ref().unwrap().album_type, album_type);
         }
     }
 
-    pub fn user_unfollow_show(&mut self, block: ActiveBlock) {
-        match block {
-            ActiveBlock::Podcasts => {
-                if let Some(shows) = self.library.saved_shows.get_results(None) {
-                    if let Some(selected_show) = shows.items.get(self.shows_list_index) {
-                        let show_id = selected_show.show.id.clone();
-                        self.dispatch(IoEvent::CurrentUserSavedShowDelete { show_id });
-                    }
-                }
-            }
-            ActiveBlock::SearchResultBlock => {
-                if let Some(shows) = &self.search_results.shows {
-                    if let Some(selected_index) = self.search_results.selected_shows_index {
-                        let show_id = shows.items[selected_index].id.to_owned();
-                        self.dispatch(IoEvent::CurrentUserSavedShowDelete { show_id });
-                    }
-                }
-            }
-            ActiveBlock::EpisodeTable => match self.episode_table_context {
-                EpisodeTableContext::Full => {
-                    if let Some(selected_episode) = self.selected_show_full.clone() {
-                        let show_id = selected_episode.show.id;
-                        self.dispatch(IoEvent::CurrentUserSavedShowDelete { show_id });
-                    }
-                }
-                EpisodeTableContext::Simplified => {
-                    if let Some(selected_episode) = self.selected_show_simplified.clone() {
-                        let show_id = selected_episode.show.id;
-                        self.dispatch(IoEvent::CurrentUserSavedShowDelete { show_id });
-                    }
-                }
-            },
-            _ => (),
-        }
+    #[test]
+    fn pages_through_artist_albums() {
+        let (io_tx, mut io_rx) = unbounded_channel();
+        let mut app = App::new(io_tx, UserConfig::new(), Utc::now());
+        app.artist = Some(artist_with_albums(vec![
+            album_page(&["a", "b", "c"], 0, 20),
+            album_page(&["d", "e", "f"], 3, 20),
+        ]));
+
+        app.artist_albums_next_page();
+        assert_eq!(app.artist.as_ref().unwrap().selected_album_index, 3);
+        // Close enough to the end to load the next page
+        assert!(matches!(
+            io_rx.try_recv(),
+            Ok(IoEvent::GetArtistAlbums { offset: 6, .. })
+        ));
+        app.artist_albums_next_page();
+        assert_eq!(app.artist.as_ref().unwrap().selected_album_index, 5);
+
+        app.artist_albums_previous_page();
+        assert_eq!(app.artist.as_ref().unwrap().selected_album_index, 2);
+        app.artist_albums_previous_page();
+        assert_eq!(app.artist.as_ref().unwrap().selected_album_index, 0);
     }
 
-    pub fn get_made_for_you(&mut self) {
-        // TODO: replace searches when relevant endpoint is added
-        const DISCOVER_WEEKLY: &str = "Discover Weekly";
-        const RELEASE_RADAR: &str = "Release Radar";
-        const ON_REPEAT: &str = "On Repeat";
-        const REPEAT_REWIND: &str = "Repeat Rewind";
-        const DAILY_DRIVE: &str = "Daily Drive";
+    #[test]
+    fn radio_seeds_respect_the_limit() {
+        let ids = (0..20).collect::<Vec<_>>();
+        let seeds = radio_seeds(&ids);
+        assert_eq!(seeds.len(), RECOMMENDATION_SEED_LIMIT);
+        assert_eq!(seeds.iter().collect::<HashSet<_>>().len(), seeds.len());
 
-        if self.library.made_for_you_playlists.pages.is_empty() {
-            // We shouldn't be fetching all the results immediately - only load the data when the
-            // user selects the playlist
-            self.made_for_you_search_and_add(DISCOVER_WEEKLY);
-            self.made_for_you_search_and_add(RELEASE_RADAR);
-            self.made_for_you_search_and_add(ON_REPEAT);
-            self.made_for_you_search_and_add(REPEAT_REWIND);
-            self.made_for_you_search_and_add(DAILY_DRIVE);
-        }
+        assert_eq!(radio_seeds(&[1, 2]).len(), 2);
+        assert!(radio_seeds::<u8>(&[]).is_empty());
     }
 
-    fn made_for_you_search_and_add(&mut self, search_term: &str) {
-        let country = self.get_user_country();
-        self.dispatch(IoEvent::MadeForYouSearchAndAdd {
-            search_term: search_term.to_string(),
-            country,
-        });
+    #[test]
+    fn liked_songs_radio_fetches_liked_songs_when_none_are_loaded() {
+        let (io_tx, mut io_rx) = unbounded_channel();
+        let mut app = App::new(io_tx, UserConfig::new(), Utc::now());
+
+        app.start_liked_songs_radio();
+        assert!(matches!(
+            io_rx.try_recv(),
+            Ok(IoEvent::GetRecommendationsForLikedSongs { .. })
+        ));
+        assert!(matches!(
+            app.recommendations_context,
+            Some(RecommendationsContext::LikedSongs)
+        ));
     }
 
-    pub fn get_audio_analysis(&mut self) {
-        match &self.current_playback_context {
-            Some(CurrentPlaybackContext {
-                item: Some(item), ..
-            }) => {
-                match item {
-                    PlayableItem::Episode(_) => {}
-                    PlayableItem::Track(track) => match track.id.clone() {
-                        Some(track_id) => {
-                            if self.get_current_route().id != RouteId::Analysis {
-                                self.dispatch(IoEvent::GetTrackAnalysis { track_id });
-                            }
-                        }
-                        None => {}
-                    },
-                };
-                self.push_navigation_stack(RouteId::Analysis, ActiveBlock::Analysis);
-            }
-            _ => {}
-        }
+    #[test]
+    fn device_selection_follows_the_device() {
+        let mut app = App {
+            saved_device_id: Some(String::from("laptop")),
+            ..App::default()
+        };
+
+        app.set_devices(vec![
+            device("desktop", Some("desktop"), false),
+            device("laptop", Some("laptop"), true),
+        ]);
+        assert_eq!(app.selected_device_index, Some(1));
+
+        app.selected_device_index = Some(0);
+        app.set_devices(vec![
+            device("phone", Some("phone"), true),
+            device("laptop", Some("laptop"), false),
+            device("desktop", Some("desktop"), false),
+        ]);
+        assert_eq!(app.selected_device_index, Some(2));
+
+        // The selected device went away, so the selection falls back to the saved device
+        app.set_devices(vec![
+            device("phone", Some("phone"), true),
+            device("laptop", Some("laptop"), false),
+        ]);
+        assert_eq!(app.selected_device_index, Some(1));
+
+        app.set_devices(vec![]);
+        assert_eq!(app.selected_device_index, None);
+        app.set_devices(vec![device("phone", Some("phone"), true)]);
+        assert_eq!(app.selected_device_index, Some(0));
     }
 
-    pub fn repeat(&mut self) {
-        if let Some(context) = &self.current_playback_context.clone() {
-            self.dispatch(IoEvent::Repeat {
-                state: context.repeat_state,
-            });
+    #[test]
+    fn devices_are_polled_while_the_list_is_open() {
+        let (io_tx, mut io_rx) = unbounded_channel();
+        let mut app = App::new(io_tx, UserConfig::new(), Utc::now());
+        let now = Instant::now();
+
+        app.poll_devices(now);
+        assert!(io_rx.try_recv().is_err());
+
+        app.push_navigation_stack(RouteId::SelectedDevice, ActiveBlock::SelectDevice);
+        // Opening the list fetched it already
+        app.poll_devices(now);
+        assert!(io_rx.try_recv().is_err());
+        app.poll_devices(now + DEVICE_POLL_INTERVAL / 2);
+        assert!(io_rx.try_recv().is_err());
+        app.poll_devices(now + DEVICE_POLL_INTERVAL);
+        assert!(matches!(
+            io_rx.try_recv(),
+            Ok(IoEvent::GetDevices { silent: true })
+        ));
+        app.poll_devices(now + DEVICE_POLL_INTERVAL * 3 / 2);
+        assert!(io_rx.try_recv().is_err());
+
+        app.pop_navigation_stack();
+        app.poll_devices(now + DEVICE_POLL_INTERVAL * 3);
+        assert!(io_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn startup_fetches_are_retried_with_backoff() {
+        let (io_tx, mut io_rx) = unbounded_channel();
+        let mut app = App::new(io_tx, UserConfig::new(), Utc::now());
+        let now = Instant::now();
+        app.dispatch_startup_events();
+        while io_rx.try_recv().is_ok() {}
+
+        // Fails twice, then succeeds
+        assert!(app.on_startup_fetch_failed(StartupFetch::Playlists, true, now));
+        app.retry_due_startup_fetches(now + STARTUP_RETRY_DELAY / 2);
+        assert!(io_rx.try_recv().is_err());
+        app.retry_due_startup_fetches(now + STARTUP_RETRY_DELAY);
+        assert!(matches!(io_rx.try_recv(), Ok(IoEvent::GetPlaylists)));
+
+        let now = now + STARTUP_RETRY_DELAY;
+        assert!(app.on_startup_fetch_failed(StartupFetch::Playlists, true, now));
+        app.retry_due_startup_fetches(now + STARTUP_RETRY_DELAY);
+        assert!(io_rx.try_recv().is_err());
+        app.retry_due_startup_fetches(now + STARTUP_RETRY_DELAY * 2);
+        assert!(matches!(io_rx.try_recv(), Ok(IoEvent::GetPlaylists)));
+        assert_eq!(
+            app.startup_fetch_state(StartupFetch::Playlists),
+            StartupFetchState::InFlight { attempts: 3 }
+        );
+
+        app.on_startup_fetch_loaded(StartupFetch::Playlists);
+        assert_eq!(
+            app.startup_fetch_state(StartupFetch::Playlists),
+            StartupFetchState::Loaded
+        );
+        // Later failures are reported as usual
+        assert!(!app.on_startup_fetch_failed(StartupFetch::Playlists, true, now));
+        app.retry_due_startup_fetches(now + STARTUP_RETRY_DELAY * 10);
+        assert!(io_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn startup_fetches_give_up() {
+        let (io_tx, mut io_rx) = unbounded_channel();
+        let mut app = App::new(io_tx, UserConfig::new(), Utc::now());
+        let now = Instant::now();
+        app.dispatch_startup_events();
+        while io_rx.try_recv().is_ok() {}
+
+        for _ in 1..STARTUP_FETCH_ATTEMPTS {
+            assert!(app.on_startup_fetch_failed(StartupFetch::User, true, now));
+            app.retry_due_startup_fetches(now + STARTUP_RETRY_DELAY * 100);
+            assert!(matches!(io_rx.try_recv(), Ok(IoEvent::GetUser)));
         }
+        assert!(!app.on_startup_fetch_failed(StartupFetch::User, true, now));
+        assert_eq!(
+            app.startup_fetch_state(StartupFetch::User),
+            StartupFetchState::Failed
+        );
+
+        // Errors that won't pass aren't retried at all
+        assert!(!app.on_startup_fetch_failed(StartupFetch::Playlists, false, now));
+        app.retry_due_startup_fetches(now + STARTUP_RETRY_DELAY * 100);
+        assert!(io_rx.try_recv().is_err());
+
+        // Until asked to, with all attempts available again
+        app.on_startup_fetch_loaded(StartupFetch::CurrentPlayback);
+        app.retry_startup_fetches();
+        let events = std::iter::from_fn(|| io_rx.try_recv().ok()).collect::<Vec<_>>();
+        assert!(matches!(
+            events[..],
+            [IoEvent::GetPlaylists, IoEvent::GetUser]
+        ));
+        assert!(app.on_startup_fetch_failed(StartupFetch::User, true, now));
     }
 
-    pub fn get_artist(&mut self, artist_id: ArtistId<'_>, input_artist_name: String) {
-        let country = self.get_user_country();
-        self.dispatch(IoEvent::GetArtist {
-            artist_id,
-            input_artist_name,
-            country,
-        });
+    #[test]
+    fn availability_in_the_users_market() {
+        let markets = |codes: &[&str]| codes.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+
+        // No markets means no restriction
+        assert!(is_available_in(&[], Some(Country::Germany)));
+        assert!(is_available_in(
+            &markets(&["DE", "US"]),
+            Some(Country::Germany)
+        ));
+        assert!(!is_available_in(
+            &markets(&["GB", "US"]),
+            Some(Country::Germany)
+        ));
+        // Nothing to go on without the user's country
+        assert!(is_available_in(&markets(&["GB", "US"]), None));
     }
 
-    pub fn get_user_country(&self) -> Option<Country> {
-        self.user.to_owned().and_then(|user| user.country)
+    #[test]
+    fn mini_player_skips_the_library() {
+        let startup_events = |is_mini_player: bool| {
+            let (io_tx, mut io_rx) = unbounded_channel();
+            let mut app = App::new(io_tx, UserConfig::new(), Utc::now());
+            if is_mini_player {
+                app.start_mini_player();
+            }
+            app.dispatch_startup_events();
+            std::iter::from_fn(|| io_rx.try_recv().ok()).collect::<Vec<_>>()
+        };
+
+        let events = startup_events(true);
+        assert!(matches!(
+            events[..],
+            [
+                IoEvent::GetCurrentPlayback,
+                IoEvent::GetDevices { silent: true }
+            ]
+        ));
+
+        let events = startup_events(false);
+        assert!(matches!(
+            events[..2],
+            [IoEvent::GetPlaylists, IoEvent::GetUser]
+        ));
     }
 
-    pub fn calculate_help_menu_offset(&mut self) {
-        let old_offset = self.help_menu_offset;
+    #[test]
+    fn refreshes_an_expired_token_once() {
+        let (io_tx, mut io_rx) = unbounded_channel();
+        let now = Utc::now();
+        let mut app = App::new(io_tx, UserConfig::new(), now);
 
-        if self.help_menu_max_lines < self.help_docs_size {
-            self.help_menu_offset = self.help_menu_page * self.help_menu_max_lines;
-        }
-        if self.help_menu_offset > self.help_docs_size {
-            self.help_menu_offset = old_offset;
-            self.help_menu_page -= 1;
-        }
+        app.refresh_token_if_expired(now);
+        assert!(io_rx.try_recv().is_err());
+
+        let later = now + chrono::Duration::seconds(1);
+        app.refresh_token_if_expired(later);
+        app.refresh_token_if_expired(later);
+        assert!(matches!(
+            io_rx.try_recv(),
+            Ok(IoEvent::RefreshAuthentication)
+        ));
+        assert!(io_rx.try_recv().is_err());
+        assert!(app.is_refreshing_token);
     }
 }
@@ -0,0 +1,55 @@
+use anyhow::Result;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{fs, path::Path};
+
+// None when there is no file yet, or it can't be read
+pub fn load_json<T: DeserializeOwned>(path: &Path) -> Option<T> {
+    let contents = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+pub fn save_json<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    // Written next to the file and moved over it, so that a crash never leaves half a file
+    let partial_path = path.with_extension("partial");
+    fs::write(&partial_path, serde_json::to_string(value)?)?;
+    fs::rename(&partial_path, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Cache {
+        ids: Vec<String>,
+    }
+
+    #[test]
+    fn saves_and_loads_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache.json");
+        assert_eq!(load_json::<Cache>(&path), None);
+
+        let cache = |ids: &[&str]| Cache {
+            ids: ids.iter().map(|id| id.to_string()).collect(),
+        };
+        save_json(&path, &cache(&["first"])).unwrap();
+        assert_eq!(load_json(&path), Some(cache(&["first"])));
+        // Written over
+        save_json(&path, &cache(&["second", "third"])).unwrap();
+        assert_eq!(load_json(&path), Some(cache(&["second", "third"])));
+        assert!(!path.with_extension("partial").exists());
+    }
+
+    #[test]
+    fn ignores_files_it_cant_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache.json");
+        for contents in ["", "{\"ids\": [", "[1, 2, 3]", "{\"ids\": [1]}"] {
+            fs::write(&path, contents).unwrap();
+            assert_eq!(load_json::<Cache>(&path), None, "{}", contents);
+        }
+    }
+}
@@ -1,4 +1,5 @@
-use clap::{builder::ArgPredicate, Arg, ArgGroup, Command};
+use crate::control::COMMANDS;
+use clap::{builder::ArgPredicate, Arg, ArgAction, ArgGroup, Command};
 
 fn device_arg() -> Arg {
     Arg::new("device")
@@ -16,7 +17,11 @@ fn format_arg() -> Arg {
     .help("Specifies the output format")
     .long_help(
       "There are multiple format specifiers you can use: %a: artist, %b: album, %p: playlist, \
-%t: track, %h: show, %f: flags (shuffle, repeat, like), %s: playback status, %v: volume, %d: current device. \
+%t: track, %h: show, %f: flags (shuffle, repeat, like), %s: playback status, %v: volume, %d: current device, \
+%r: progress, %c: the playlist, album, artist or show playing from. \
+For scripts, %R is the repeat mode (off, context or track), %S whether shuffle is on (true or false) and \
+%P the percentage played (0 to 100). Listing devices, %i is the id of the device, which `--transfer` takes \
+too, and %A a * for the active one. \
 Example: spt pb -s -f 'playing on %d at %v%'",
     )
 }
@@ -37,8 +42,12 @@ Here's a list:
 
 * `--next` and `--previous` cannot be used with other options
 * `--status`, `--toggle`, `--transfer`, `--volume`, `--like`, `--repeat` and `--shuffle` \
-can be used together
-* `--share-track` and `--share-album` cannot be used with other options",
+(or their older explicit forms `--repeat-mode`, `--shuffle-on` and `--shuffle-off`) can be used together
+* `--shuffle` and `--repeat` alone toggle and cycle. Given a state, like `--shuffle off` or \
+`--repeat track`, they only change anything when it isn't already set, which is what scripts want.
+* `--share-track` and `--share-album` cannot be used with other options
+* `--dnd` cannot be used with other options, it talks to the running TUI
+* `--follow` keeps running after any actions, printing the status again whenever the playback changes. With `--events-json` it prints one JSON object per change instead, for scripts.",
     )
     .visible_alias("pb")
     .arg(device_arg())
@@ -63,6 +72,42 @@ can be used together
         .long("status")
         .help("Prints out the current status of a device (default)"),
     )
+    .arg(
+      Arg::new("follow")
+        .long("follow")
+        .visible_alias("wait")
+        .action(ArgAction::SetTrue)
+        .help("Keeps printing the status whenever the playing item, playback status or device changes"),
+    )
+    .arg(
+      Arg::new("interval")
+        .long("interval")
+        .value_name("SECONDS")
+        .value_parser(clap::value_parser!(u64).range(1..))
+        .default_value("2")
+        .requires("follow")
+        .help("How often `--follow` checks the playback"),
+    )
+    .arg(
+      Arg::new("fail-fast")
+        .long("fail-fast")
+        .action(ArgAction::SetTrue)
+        .requires("follow")
+        .help("Stops `--follow` at the first network error, which are otherwise printed to stderr"),
+    )
+    .arg(
+      Arg::new("events-json")
+        .long("events-json")
+        .action(ArgAction::SetTrue)
+        .requires("follow")
+        .help("Prints each change as a line of JSON, with `--follow`")
+        .long_help(
+          "Each line is an object with the `event` (track_changed, paused, resumed, seeked, \
+volume_changed or device_changed) and the whole `status`: uri, title, artist, album, duration_ms, \
+progress_ms, is_playing, shuffle, repeat, device_id, device_name and volume_percent. The first \
+line is a track_changed event with the status when following started.",
+        ),
+    )
     .arg(
       Arg::new("share-track")
         .long("share-track")
@@ -77,7 +122,7 @@ can be used together
       Arg::new("transfer")
         .long("transfer")
         .value_name("DEVICE")
-        .help("Transfers the playback to new DEVICE"),
+        .help("Transfers the playback to new DEVICE, by id, name or part of the name"),
     )
     .arg(
       Arg::new("like")
@@ -92,17 +137,43 @@ can be used together
     .arg(
       Arg::new("shuffle")
         .long("shuffle")
-        .help("Toggles shuffle mode"),
+        .value_name("STATE")
+        .num_args(0..=1)
+        .value_parser(["on", "off"])
+        .help("Toggles shuffle mode, or turns it on or off if it isn't already"),
+    )
+    .arg(
+      Arg::new("shuffle-on")
+        .long("shuffle-on")
+        .action(ArgAction::SetTrue)
+        .help("Turns shuffle mode on, if it isn't already"),
+    )
+    .arg(
+      Arg::new("shuffle-off")
+        .long("shuffle-off")
+        .action(ArgAction::SetTrue)
+        .help("Turns shuffle mode off, if it isn't already"),
     )
     .arg(
       Arg::new("repeat")
         .long("repeat")
-        .help("Switches between repeat modes"),
+        .value_name("MODE")
+        .num_args(0..=1)
+        .value_parser(["off", "context", "track"])
+        .help("Switches between repeat modes, or sets one if it isn't already"),
+    )
+    .arg(
+      Arg::new("repeat-mode")
+        .long("repeat-mode")
+        .value_name("MODE")
+        .value_parser(["off", "context", "track"])
+        .help("Sets the repeat mode, if it isn't already"),
     )
     .arg(
       Arg::new("next")
         .short('n')
         .long("next")
+        .action(ArgAction::Count)
         .help("Jumps to the next song")
         .long_help(
           "This jumps to the next song if specied once. If you want to jump, let's say 3 songs \
@@ -113,13 +184,23 @@ forward, you can use `--next` 3 times: `spt pb -nnn`.",
       Arg::new("previous")
         .short('p')
         .long("previous")
+        .action(ArgAction::Count)
         .help("Jumps to the previous song")
         .long_help(
-          "This jumps to the beginning of the current song if specied once. You probably want to \
-jump to the previous song though, so you can use the previous flag twice: `spt pb -pp`. To jump \
-two songs back, you can use `spt pb -ppp` and so on.",
+          "Like in the TUI, this jumps to the beginning of the current song if it has been playing \
+for at least three seconds, and to the previous song otherwise. Each further `--previous` jumps \
+one song further back: `spt pb -pp` goes to the previous song from anywhere in the current one. \
+Use `--force` to skip back every time.",
         ),
     )
+    .arg(
+      Arg::new("force")
+        .long("force")
+        .action(ArgAction::SetTrue)
+        .requires("previous")
+        .conflicts_with("next")
+        .help("Makes `--previous` always jump to the previous song, rather than restart the current one"),
+    )
     .arg(
       Arg::new("seek")
         .long("seek")
@@ -135,8 +216,25 @@ seconds backwards and `spt pb --seek 10` to the tenth second of the track.",
       Arg::new("volume")
         .short('v')
         .long("volume")
-        .value_name("VOLUME")
-        .help("Sets the volume of a device to VOLUME (1 - 100)"),
+        .value_name("±VOLUME")
+        .allow_hyphen_values(true)
+        .help("Sets the volume of a device to VOLUME (0 - 100), or changes it by VOLUME (+/-)")
+        .long_help(
+          "For example: `spt pb --volume +10` turns the volume up by ten, `spt pb --volume -10` \
+down by ten and `spt pb --volume 10` sets it to ten. The result is clamped to 0 - 100.",
+        ),
+    )
+    .arg(
+      Arg::new("dnd")
+        .long("dnd")
+        .value_name("on|off|DURATION")
+        .conflicts_with_all(["single", "flags", "actions", "jumps", "follow"])
+        .help("Turns do not disturb on or off in the running TUI, or on for a DURATION like 60m")
+        .long_help(
+          "While do not disturb is on, the TUI only shows errors, everything else still goes to \
+the notification history. With a DURATION (90s, 60m or 2h) it turns itself off again afterwards. \
+This needs the TUI to be running with `control_socket: true`, like `spt ctl dnd`.",
+        ),
     )
     .group(
       ArgGroup::new("jumps")
@@ -149,9 +247,27 @@ seconds backwards and `spt pb --seek 10` to the tenth second of the track.",
         .args(&["like", "dislike"])
         .multiple(false),
     )
+    .group(
+      ArgGroup::new("shuffles")
+        .args(["shuffle", "shuffle-on", "shuffle-off"])
+        .multiple(false),
+    )
+    .group(
+      ArgGroup::new("repeats")
+        .args(["repeat", "repeat-mode"])
+        .multiple(false),
+    )
     .group(
       ArgGroup::new("flags")
-        .args(&["like", "dislike", "shuffle", "repeat"])
+        .args(&[
+          "like",
+          "dislike",
+          "shuffle",
+          "shuffle-on",
+          "shuffle-off",
+          "repeat",
+          "repeat-mode",
+        ])
         .multiple(true)
         .conflicts_with_all(&["single", "jumps"]),
     )
@@ -165,7 +281,7 @@ seconds backwards and `spt pb --seek 10` to the tenth second of the track.",
       ArgGroup::new("single")
         .args(&["share-track", "share-album"])
         .multiple(false)
-        .conflicts_with_all(&["actions", "flags", "jumps"]),
+        .conflicts_with_all(["actions", "flags", "jumps", "follow"]),
     )
 }
 
@@ -179,8 +295,11 @@ pub fn play_subcommand() -> Command {
 name, you have to specify the type: `--track`, `--album`, `--artist`, `--playlist` \
 or `--show`. The first item which was found will be played without confirmation. \
 To add a track to the queue, use `--queue`. To play a random song from a playlist, \
-use `--random`. Again, with `--format` you can specify how the output will look. \
-The same function as found in `playback` will be called.",
+use `--random`. To play recommendations based on an artist instead, like a radio, use \
+`--radio` with `--artist`. To choose one of your playlists from a numbered list instead, \
+use `--pick-playlist`, narrowed down with `--filter` and `--limit`. Again, with `--format` you can \
+specify how the output will look. The same function as found in `playback` will be called, once \
+the playback shows what was started. To print nothing instead, use `--quiet`.",
         )
         .visible_alias("p")
         .arg(device_arg())
@@ -190,7 +309,12 @@ The same function as found in `playback` will be called.",
                 .short('u')
                 .long("uri")
                 .value_name("URI")
-                .help("Plays the URI"),
+                .action(ArgAction::Append)
+                .value_delimiter(',')
+                .help(
+                    "Plays the URI, or the open.spotify.com link. Tracks and episodes can be given \
+                     more than once, or separated by commas, to play them in order",
+                ),
         )
         .arg(
             Arg::new("name")
@@ -204,48 +328,95 @@ The same function as found in `playback` will be called.",
             Arg::new("queue")
                 .short('q')
                 .long("queue")
+                .action(ArgAction::SetTrue)
                 // Only works with tracks
-                .conflicts_with_all(&["album", "artist", "playlist", "show"])
+                .conflicts_with_all(&["album", "artist", "playlist", "show", "pick-playlist"])
                 .help("Adds track to queue instead of playing it directly"),
         )
         .arg(
             Arg::new("random")
                 .short('r')
                 .long("random")
+                .action(ArgAction::SetTrue)
                 // Only works with playlists
                 .conflicts_with_all(&["track", "album", "artist", "show"])
                 .help("Plays a random track (only works with playlists)"),
         )
+        .arg(
+            Arg::new("radio")
+                .long("radio")
+                .action(ArgAction::SetTrue)
+                .requires("name")
+                .requires("artist")
+                // Only works with artists
+                .conflicts_with_all(["queue", "random", "track", "album", "playlist", "show"])
+                .help(
+                    "Plays recommendations based on the artist instead (only works with artists)",
+                ),
+        )
+        .arg(
+            Arg::new("quiet")
+                .long("quiet")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("format")
+                .help("Doesn't print the status after playing"),
+        )
         .arg(
             Arg::new("album")
                 .short('b')
                 .long("album")
+                .action(ArgAction::SetTrue)
                 .help("Looks for an album"),
         )
         .arg(
             Arg::new("artist")
                 .short('a')
                 .long("artist")
+                .action(ArgAction::SetTrue)
                 .help("Looks for an artist"),
         )
         .arg(
             Arg::new("track")
                 .short('t')
                 .long("track")
+                .action(ArgAction::SetTrue)
                 .help("Looks for a track"),
         )
         .arg(
             Arg::new("show")
                 .short('w')
                 .long("show")
+                .action(ArgAction::SetTrue)
                 .help("Looks for a show"),
         )
         .arg(
             Arg::new("playlist")
                 .short('p')
                 .long("playlist")
+                .action(ArgAction::SetTrue)
                 .help("Looks for a playlist"),
         )
+        .arg(
+            Arg::new("pick-playlist")
+                .long("pick-playlist")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["contexts", "radio"])
+                .help("Lists your playlists and plays the one whose number you enter"),
+        )
+        .arg(
+            Arg::new("filter")
+                .long("filter")
+                .value_name("TEXT")
+                // Leaves `--pick-playlist`, as one of the actions is required
+                .conflicts_with_all(["uri", "name"])
+                .help("Only lists the playlists with TEXT in their name"),
+        )
+        .arg(
+            Arg::new("limit")
+                .long("limit")
+                .conflicts_with_all(["uri", "name"])
+                .help("Specifies the maximum number of playlists fetched (1 - 50)"),
+        )
         .group(
             ArgGroup::new("contexts")
                 .args(&["track", "artist", "playlist", "album", "show"])
@@ -253,7 +424,7 @@ The same function as found in `playback` will be called.",
         )
         .group(
             ArgGroup::new("actions")
-                .args(&["uri", "name"])
+                .args(&["uri", "name", "pick-playlist"])
                 .multiple(false)
                 .required(true),
         )
@@ -263,40 +434,62 @@ pub fn list_subcommand() -> Command {
     Command::new("list")
         .version(env!("CARGO_PKG_VERSION"))
         .author(env!("CARGO_PKG_AUTHORS"))
-        .about("Lists devices, liked songs and playlists")
+        .about("Lists devices, liked songs, playlists and podcasts")
         .long_about(
-            "This will list devices, liked songs or playlists. With the `--limit` flag you are \
-able to specify the amount of results (between 1 and 50). Here, the `--format` is \
-even more awesome, get your output exactly the way you want. The format option will \
-be applied to every item found.",
+            "This will list devices, liked songs, playlists or podcasts. With the `--limit` flag you are \
+able to specify the amount of results (between 1 and 50). Liked songs are fetched page \
+by page, so their limit can be any number, and `--all` lists every liked song. Here, \
+the `--format` is even more awesome, get your output exactly the way you want. The \
+format option will be applied to every item found.",
         )
         .visible_alias("l")
         .arg(format_arg().default_value_ifs([
-            ("devices", ArgPredicate::IsPresent, "%v% %d"),
-            ("liked", ArgPredicate::IsPresent, "%t - %a (%u)"),
-            ("playlists", ArgPredicate::IsPresent, "%p (%u)"),
+            ("devices", ArgPredicate::Equals("true".into()), "%A %v% %d"),
+            ("liked", ArgPredicate::Equals("true".into()), "%t - %a (%u)"),
+            ("playlists", ArgPredicate::Equals("true".into()), "%p (%u)"),
+            ("podcasts", ArgPredicate::Equals("true".into()), "%h - %a (%u)"),
         ]))
         .arg(
             Arg::new("devices")
                 .short('d')
                 .long("devices")
+                .action(ArgAction::SetTrue)
                 .help("Lists devices"),
         )
         .arg(
             Arg::new("playlists")
                 .short('p')
                 .long("playlists")
+                .action(ArgAction::SetTrue)
                 .help("Lists playlists"),
         )
-        .arg(Arg::new("liked").long("liked").help("Lists liked songs"))
         .arg(
-            Arg::new("limit")
-                .long("limit")
-                .help("Specifies the maximum number of results (1 - 50)"),
+            Arg::new("liked")
+                .long("liked")
+                .action(ArgAction::SetTrue)
+                .help("Lists liked songs"),
+        )
+        .arg(
+            Arg::new("podcasts")
+                .long("podcasts")
+                .action(ArgAction::SetTrue)
+                .help("Lists followed podcasts"),
+        )
+        .arg(
+            Arg::new("limit").long("limit").help(
+                "Specifies the maximum number of results (1 - 50, any number for liked songs)",
+            ),
+        )
+        .arg(
+            Arg::new("all")
+                .long("all")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["devices", "playlists", "podcasts", "limit"])
+                .help("Lists every liked song instead of a single page"),
         )
         .group(
             ArgGroup::new("listable")
-                .args(&["devices", "playlists", "liked"])
+                .args(&["devices", "playlists", "liked", "podcasts"])
                 .required(true)
                 .multiple(false),
         )
@@ -369,3 +562,71 @@ specify it.",
                 .multiple(false),
         )
 }
+
+fn show_arg() -> Arg {
+    Arg::new("show")
+        .short('w')
+        .long("show")
+        .value_name("URI")
+        .required(true)
+        .help("Specifies the podcast by its URI, URL or id")
+}
+
+pub fn follow_subcommand() -> Command {
+    Command::new("follow")
+        .version(env!("CARGO_PKG_VERSION"))
+        .author(env!("CARGO_PKG_AUTHORS"))
+        .about("Follows podcasts")
+        .long_about(
+            "This will follow a podcast, adding it to your library. Podcasts are specified \
+with `--show` and a Spotify URI, share URL or id.",
+        )
+        .arg(show_arg())
+}
+
+pub fn unfollow_subcommand() -> Command {
+    Command::new("unfollow")
+        .version(env!("CARGO_PKG_VERSION"))
+        .author(env!("CARGO_PKG_AUTHORS"))
+        .about("Unfollows podcasts")
+        .long_about(
+            "This will unfollow a podcast, removing it from your library. Podcasts are \
+specified with `--show` and a Spotify URI, share URL or id.",
+        )
+        .arg(show_arg())
+}
+
+pub fn ctl_subcommand() -> Command {
+    Command::new("ctl")
+        .version(env!("CARGO_PKG_VERSION"))
+        .author(env!("CARGO_PKG_AUTHORS"))
+        .about("Controls the running TUI")
+        .long_about(
+            "Sends a command to the running TUI through its control socket (enabled with \
+`control_socket: true` in config.yml), which avoids authenticating again. When the TUI isn't \
+running, the command is sent to the Spotify API instead.",
+        )
+        .arg(
+            Arg::new("command")
+                .value_name("COMMAND")
+                .required(true)
+                .value_parser(COMMANDS)
+                .help("The command to run"),
+        )
+        .arg(
+            Arg::new("value")
+                .value_name("VALUE")
+                .allow_hyphen_values(true)
+                .help("The volume or seek amount (±N), the URI to open, or on, off or a duration for dnd")
+                .long_help(
+                    "For example: `spt ctl volume +10` turns the volume up by ten, \
+`spt ctl seek -10` jumps ten seconds backwards, `spt ctl open spotify:album:...` plays an album \
+and `spt ctl dnd 60m` only lets errors through the notifications for an hour.",
+                ),
+        )
+        .arg(
+            format_arg()
+                .default_value("%f %s %t - %a")
+                .help("Specifies the output format when the TUI isn't running"),
+        )
+}
@@ -1,17 +1,45 @@
-use super::util::{Flag, Format, FormatType, JumpDirection, Type};
-use crate::network::{IoEvent, Network};
+use super::dry_run;
+use super::events::{classify, event_line, PlaybackEvent, StatusSnapshot};
+use super::pick::{filter_playlists, parse_selection, Prompt};
+use super::util::{repeat_event, shuffle_event, Flag, Format, FormatType, JumpDirection, Type};
+use crate::app::{find_device, TRACK_RESTART_MS};
+use crate::control::ControlCommand;
+use crate::network::{IoEvent, Network, PlaybackOffset};
 use crate::user_config::UserConfig;
 use anyhow::{anyhow, Result};
 use chrono::Duration;
 use rand::{thread_rng, Rng};
 use rspotify::clients::BaseClient;
 use rspotify::model::idtypes::*;
-use rspotify::model::{context::CurrentPlaybackContext, PlayableItem};
+use rspotify::model::{context::CurrentPlaybackContext, PlayableItem, SearchType};
 use spotify_tui_util::ParseFromUri;
+use std::io::{self, Write};
+use std::time::Instant;
+
+// How long a change is waited on to show in the playback before the status is printed anyway, and
+// how often the playback is checked in between
+const CONVERGENCE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(3);
+const CONVERGENCE_INTERVAL: std::time::Duration = std::time::Duration::from_millis(250);
+// How far the progress may be from where a seek went, once it shows
+const SEEK_CONVERGENCE_TOLERANCE_MS: i64 = 1_000;
+
+pub struct FollowOptions {
+    // How long to wait between checks of the playback
+    pub interval: std::time::Duration,
+    // Print every change as a line of JSON, rather than the status when what is playing changes
+    pub events_json: bool,
+    // Stop at the first network error
+    pub fail_fast: bool,
+}
 
 pub struct CliApp {
     pub net: Network,
     pub config: UserConfig,
+    // Describe mutating events instead of sending them
+    pub dry_run: bool,
+    pub convergence_timeout: std::time::Duration,
+    // Printed after the status, which they are about
+    pub warnings: Vec<String>,
 }
 
 macro_rules! handle_error {
@@ -36,8 +64,70 @@ macro_rules! handle_error {
 // I just .await all processes and directly interact
 // by calling network.handle_network_event
 impl CliApp {
-    pub fn new(net: Network, config: UserConfig) -> Self {
-        Self { net, config }
+    pub fn new(net: Network, config: UserConfig, dry_run: bool) -> Self {
+        Self {
+            net,
+            config,
+            dry_run,
+            convergence_timeout: CONVERGENCE_TIMEOUT,
+            warnings: vec![],
+        }
+    }
+
+    // The playback as last fetched, None when nothing is playing anywhere
+    pub async fn snapshot(&self) -> Option<StatusSnapshot> {
+        self.net
+            .app
+            .read()
+            .await
+            .current_playback_context
+            .as_ref()
+            .map(StatusSnapshot::new)
+    }
+
+    // Spotify takes a moment to show a change in the playback, so it is polled until
+    // `is_converged` holds for it. Otherwise the status printed afterwards may be out of date,
+    // which a warning says once the timeout has passed.
+    async fn await_playback(
+        &mut self,
+        change: &str,
+        is_converged: impl Fn(&CurrentPlaybackContext) -> bool,
+    ) {
+        let deadline = Instant::now() + self.convergence_timeout;
+        loop {
+            self.dispatch(IoEvent::GetCurrentPlayback).await;
+            let app = self.net.app.read().await;
+            if app
+                .current_playback_context
+                .as_ref()
+                .is_some_and(&is_converged)
+            {
+                return;
+            }
+            drop(app);
+
+            let now = Instant::now();
+            if now >= deadline {
+                self.warnings.push(format!(
+                    "{} hasn't shown in the playback yet, the status may be out of date",
+                    change
+                ));
+                return;
+            }
+            tokio::time::sleep(CONVERGENCE_INTERVAL.min(deadline - now)).await;
+        }
+    }
+
+    // Send an event, unless it is a mutating one in a dry run, in which case what it would do is
+    // printed instead. Returns whether the event was sent.
+    async fn dispatch(&mut self, event: IoEvent<'_>) -> bool {
+        if self.dry_run && event.is_mutating() {
+            println!("{}", dry_run::describe(&*self.net.app.read().await, &event));
+            false
+        } else {
+            self.net.handle_network_event(event).await;
+            true
+        }
     }
 
     async fn is_a_saved_item(&mut self, playable_id: PlayableId<'_>) -> bool {
@@ -46,11 +136,10 @@ impl CliApp {
             PlayableId::Episode(_) => false,
             // Update the liked_song_ids_set
             PlayableId::Track(track_id) => {
-                self.net
-                    .handle_network_event(IoEvent::CurrentUserSavedTracksContains {
-                        track_ids: vec![track_id.clone()],
-                    })
-                    .await;
+                self.dispatch(IoEvent::CurrentUserSavedTracksContains {
+                    track_ids: vec![track_id.clone()],
+                })
+                .await;
                 self.net
                     .app
                     .read()
@@ -66,7 +155,10 @@ impl CliApp {
             format = format.replace(val.get_placeholder(), &val.inner(self.config.clone()));
         }
         // Replace unsupported flags with 'None'
-        for p in &["%a", "%b", "%t", "%p", "%h", "%u", "%d", "%v", "%f", "%s"] {
+        for p in &[
+            "%a", "%b", "%t", "%p", "%h", "%u", "%d", "%i", "%A", "%v", "%f", "%R", "%S", "%s",
+            "%P", "%c",
+        ] {
             format = format.replace(p, "None");
         }
         format.trim().to_string()
@@ -77,11 +169,11 @@ impl CliApp {
         let context = self.net.app.read().await.current_playback_context.clone();
         if let Some(c) = context {
             if c.is_playing {
-                self.net.handle_network_event(IoEvent::PausePlayback).await;
+                self.dispatch(IoEvent::PausePlayback).await;
                 return;
             }
         }
-        self.net.handle_network_event(IoEvent::ResumePlayback).await;
+        self.dispatch(IoEvent::ResumePlayback).await;
     }
 
     // spt pb --share-track (share the current playing song)
@@ -121,25 +213,17 @@ impl CliApp {
     pub async fn set_device(&mut self, name: String) -> Result<()> {
         // Change the device if specified by user
         let mut app = self.net.app.write().await;
-        let mut device_index = 0;
-        if let Some(dp) = &app.devices {
-            for (i, d) in dp.devices.iter().enumerate() {
-                if d.name == name {
-                    let id = d.id.clone().ok_or_else(|| {
-                        anyhow!("failed to use device with name '{name}': no device id")
-                    })?;
-                    device_index = i;
-                    // Save the id of the device
-                    self.net
-                        .client_config
-                        .set_device_id(id)
-                        .map_err(|_e| anyhow!("failed to use device with name '{name}'"))?;
-                }
-            }
-        } else {
-            // Error out if no device is available
-            return Err(anyhow!("no device available"));
-        }
+        // Error out if no device is available
+        let devices = app
+            .devices
+            .as_ref()
+            .ok_or_else(|| anyhow!("no device available"))?;
+        let (device_index, id) = find_device(devices, &name)?;
+        // Save the id of the device
+        self.net
+            .client_config
+            .set_device_id(id)
+            .map_err(|_e| anyhow!("failed to use device with name '{}'", name))?;
         app.selected_device_index = Some(device_index);
         Ok(())
     }
@@ -155,36 +239,69 @@ impl CliApp {
             return Err(anyhow!("limit must be between 1 and 50"));
         };
 
-        self.net
-            .handle_network_event(IoEvent::UpdateSearchLimits {
-                large_search_limit: num,
-                small_search_limit: num,
-            })
-            .await;
+        self.dispatch(IoEvent::UpdateSearchLimits {
+            large_search_limit: num,
+            small_search_limit: num,
+        })
+        .await;
         Ok(())
     }
 
     pub async fn volume(&mut self, vol: String) -> Result<()> {
         let num = vol
-            .parse::<u32>()
-            .map_err(|_e| anyhow!("volume must be between 0 and 100"))?;
+            .parse::<i32>()
+            .map_err(|_e| anyhow!("volume must be a number, optionally prefixed with + or -"))?;
 
-        // Check if it's in range
-        if num > 100 {
-            return Err(anyhow!("volume must be between 0 and 100"));
+        // `+N` and `-N` are relative to the current device volume, `N` is absolute
+        let volume = if vol.starts_with('+') || vol.starts_with('-') {
+            self.dispatch(IoEvent::GetCurrentPlayback).await;
+            let app = self.net.app.read().await;
+            match &app.current_playback_context {
+                Some(context) => context.device.volume_percent.unwrap_or_default() as i32 + num,
+                None => return Err(anyhow!("no context available")),
+            }
+        } else {
+            num
         };
 
-        self.net
-            .handle_network_event(IoEvent::ChangeVolume { volume: num as u8 })
+        let volume = volume.clamp(0, 100) as u8;
+        if self.dispatch(IoEvent::ChangeVolume { volume }).await {
+            self.await_playback("The volume change", |context| {
+                context.device.volume_percent == Some(volume.into())
+            })
             .await;
+        }
         Ok(())
     }
 
     // spt playback --next / --previous
-    pub async fn jump(&mut self, d: &JumpDirection) {
+    // spt playback --next / --previous, `amount` times
+    pub async fn jump(&mut self, d: &JumpDirection, amount: u64, force: bool) {
         match d {
-            JumpDirection::Next => self.net.handle_network_event(IoEvent::NextTrack).await,
-            JumpDirection::Previous => self.net.handle_network_event(IoEvent::PreviousTrack).await,
+            JumpDirection::Next => {
+                for _ in 0..amount {
+                    self.dispatch(IoEvent::NextTrack).await;
+                }
+            }
+            JumpDirection::Previous => {
+                let progress_ms = if force {
+                    None
+                } else {
+                    self.dispatch(IoEvent::GetCurrentPlayback).await;
+                    let app = self.net.app.read().await;
+                    app.current_playback_context
+                        .as_ref()
+                        .and_then(|context| context.progress)
+                        .map(|progress| progress.num_milliseconds().max(0) as u128)
+                };
+                let (restart, skips) = previous_jumps(progress_ms, amount);
+                if restart {
+                    self.dispatch(IoEvent::Seek { position_ms: 0 }).await;
+                }
+                for _ in 0..skips {
+                    self.dispatch(IoEvent::PreviousTrack).await;
+                }
+            }
         }
     }
 
@@ -201,6 +318,8 @@ impl CliApp {
                                 format.to_string(),
                                 vec![
                                     Some(Format::Device(d.name.clone())),
+                                    d.id.clone().map(Format::DeviceId),
+                                    Some(Format::Active(d.is_active)),
                                     d.volume_percent.map(Format::Volume),
                                 ]
                                 .into_iter()
@@ -215,7 +334,7 @@ impl CliApp {
                 }
             }
             Type::Playlist => {
-                self.net.handle_network_event(IoEvent::GetPlaylists).await;
+                self.dispatch(IoEvent::GetPlaylists).await;
                 if let Some(playlists) = &self.net.app.read().await.playlists {
                     playlists
                         .items
@@ -232,31 +351,31 @@ impl CliApp {
                     "No playlists found".to_string()
                 }
             }
-            Type::Liked => {
-                self.net
-                    .handle_network_event(IoEvent::GetCurrentUserSavedTracks { offset: None })
+            Type::Show => {
+                self.dispatch(IoEvent::GetCurrentUserSavedShows { offset: None })
                     .await;
-                let liked_songs = self
+                if let Some(saved_shows) = self
                     .net
                     .app
                     .read()
                     .await
-                    .item_table
-                    .items
-                    .iter()
-                    .filter_map(|playable_item| match playable_item {
-                        PlayableItem::Episode(_) => None,
-                        PlayableItem::Track(full_track) => Some(self.format_output(
-                            format.to_string(),
-                            Format::from_type(FormatType::Track(Box::new(full_track.clone()))),
-                        )),
-                    })
-                    .collect::<Vec<String>>();
-                // Check if there are any liked songs
-                if liked_songs.is_empty() {
-                    "No liked songs found".to_string()
+                    .library
+                    .saved_shows
+                    .get_results(None)
+                {
+                    saved_shows
+                        .items
+                        .iter()
+                        .map(|s| {
+                            self.format_output(
+                                format.to_string(),
+                                Format::from_type(FormatType::Show(Box::new(s.show.clone()))),
+                            )
+                        })
+                        .collect::<Vec<String>>()
+                        .join("\n")
                 } else {
-                    liked_songs.join("\n")
+                    "No podcasts found".to_string()
                 }
             }
             // Enforced by clap
@@ -264,32 +383,65 @@ impl CliApp {
         }
     }
 
-    // spt playback --transfer DEVICE
-    pub async fn transfer_playback(&mut self, device: &str) -> Result<()> {
-        // Get the device id by name
-        let mut device_id = String::new();
-        if let Some(devices) = &self.net.app.read().await.devices {
-            for d in &devices.devices {
-                if d.name == device {
-                    let id = d.id.clone().ok_or_else(|| {
-                        anyhow!("failed to use device with name '{}': no device id", d.name)
-                    })?;
-                    device_id.push_str(&id);
-                    break;
-                }
-            }
+    // spt follow --show URI / spt unfollow --show URI
+    pub async fn follow_show(&mut self, show: &str, follow: bool) -> Result<String> {
+        let show_id = parse_show_id(show)?;
+        let uri = show_id.uri();
+        let (event, action) = if follow {
+            (IoEvent::CurrentUserSavedShowAdd { show_id }, "Followed")
+        } else {
+            (
+                IoEvent::CurrentUserSavedShowDelete { show_id },
+                "Unfollowed",
+            )
         };
+        if self.dispatch(event).await {
+            Ok(format!("{} {}", action, uri))
+        } else {
+            Ok(String::from("Nothing was changed (dry run)"))
+        }
+    }
 
-        if device_id.is_empty() {
-            Err(anyhow!("no device with name '{}'", device))
+    // spt list --liked [--limit LIMIT | --all]
+    pub async fn list_liked(&mut self, format: &str, limit: Option<u32>) -> String {
+        self.dispatch(IoEvent::GetAllCurrentUserSavedTracks { limit })
+            .await;
+        let liked_songs = self
+            .net
+            .app
+            .read()
+            .await
+            .item_table
+            .items
+            .iter()
+            .filter_map(|playable_item| match playable_item {
+                PlayableItem::Episode(_) => None,
+                PlayableItem::Track(full_track) => Some(self.format_output(
+                    format.to_string(),
+                    Format::from_type(FormatType::Track(Box::new(full_track.clone()))),
+                )),
+            })
+            .collect::<Vec<String>>();
+        // Check if there are any liked songs
+        if liked_songs.is_empty() {
+            "No liked songs found".to_string()
         } else {
-            self.net
-                .handle_network_event(IoEvent::TransferPlaybackToDevice { device_id })
-                .await;
-            Ok(())
+            liked_songs.join("\n")
         }
     }
 
+    // spt playback --transfer DEVICE
+    pub async fn transfer_playback(&mut self, device: &str) -> Result<()> {
+        // Get the device id by name
+        let device_id = match &self.net.app.read().await.devices {
+            Some(devices) => find_device(devices, device)?.1,
+            None => return Err(anyhow!("no device with name '{}'", device)),
+        };
+        self.dispatch(IoEvent::TransferPlaybackToDevice { device_id })
+            .await;
+        Ok(())
+    }
+
     pub async fn seek(&mut self, seconds_str: String) -> Result<()> {
         let seconds = Duration::seconds(match seconds_str.parse::<i32>() {
             Ok(s) => s.abs() as i64,
@@ -297,9 +449,7 @@ impl CliApp {
         });
 
         let (current_pos, duration) = {
-            self.net
-                .handle_network_event(IoEvent::GetCurrentPlayback)
-                .await;
+            self.dispatch(IoEvent::GetCurrentPlayback).await;
             let app = self.net.app.read().await;
             if let Some(CurrentPlaybackContext {
                 progress: Some(progress),
@@ -336,18 +486,61 @@ impl CliApp {
 
         // Check if position_to_seek is greater than duration (next track)
         if position_to_seek > duration {
-            self.jump(&JumpDirection::Next).await;
+            self.jump(&JumpDirection::Next, 1, false).await;
         } else {
             let position_ms = position_to_seek.num_milliseconds() as u32;
             // This seeks to a position in the current song
-            self.net
-                .handle_network_event(IoEvent::Seek { position_ms })
+            let sought_at = Instant::now();
+            if self.dispatch(IoEvent::Seek { position_ms }).await {
+                self.await_playback("The seek", |context| {
+                    // The progress moves on from where the seek went while waiting
+                    let elapsed_ms = sought_at.elapsed().as_millis() as i64;
+                    context.progress.is_some_and(|progress| {
+                        let moved = progress.num_milliseconds() - i64::from(position_ms);
+                        (-SEEK_CONVERGENCE_TOLERANCE_MS
+                            ..=elapsed_ms + SEEK_CONVERGENCE_TOLERANCE_MS)
+                            .contains(&moved)
+                    })
+                })
                 .await;
+            }
         }
 
         Ok(())
     }
 
+    // spt ctl COMMAND, when the TUI isn't running
+    pub async fn control(&mut self, command: ControlCommand) -> Result<()> {
+        match command {
+            ControlCommand::Toggle => self.toggle_playback().await,
+            ControlCommand::Next => self.jump(&JumpDirection::Next, 1, false).await,
+            ControlCommand::Previous => self.jump(&JumpDirection::Previous, 1, false).await,
+            ControlCommand::Volume { value } => self.volume(value).await?,
+            ControlCommand::Seek { value } => self.seek(value).await?,
+            ControlCommand::Like => {
+                let playable_id = {
+                    let app = self.net.app.read().await;
+                    app.current_playback_context
+                        .as_ref()
+                        .and_then(|c| c.item.as_ref())
+                        .and_then(|item| item.id())
+                        .map(PlayableId::into_static)
+                        .ok_or_else(|| anyhow!("no item playing"))?
+                };
+                let is_liked = self.is_a_saved_item(playable_id).await;
+                self.mark(Flag::Like(!is_liked)).await?;
+            }
+            ControlCommand::Open { uri } => {
+                self.play_uri(uri, false, false).await?;
+            }
+            // Only the TUI shows notifications
+            ControlCommand::Dnd { .. } => {
+                return Err(anyhow!("do not disturb needs the TUI to be running"))
+            }
+        }
+        Ok(())
+    }
+
     // spt playback --like / --dislike / --shuffle / --repeat
     pub async fn mark(&mut self, flag: Flag) -> Result<()> {
         let c = {
@@ -374,29 +567,40 @@ impl CliApp {
                     // Want to like and is not liked yet -> like
                     true => {
                         if !self.is_a_saved_item(playable_id).await {
-                            self.net
-                                .handle_network_event(IoEvent::ToggleSaveTrack { track_id })
-                                .await;
+                            self.dispatch(IoEvent::ToggleSaveTrack { track_id }).await;
                         }
                     }
                     // Want to dislike but is already disliked -> do nothing
                     // Want to dislike and is liked currently -> remove like
                     false => {
                         if self.is_a_saved_item(playable_id).await {
-                            self.net
-                                .handle_network_event(IoEvent::ToggleSaveTrack { track_id })
-                                .await;
+                            self.dispatch(IoEvent::ToggleSaveTrack { track_id }).await;
                         }
                     }
                 }
             }
-            Flag::Shuffle => self.net.handle_network_event(IoEvent::ToggleShuffle).await,
-            Flag::Repeat => {
-                self.net
-                    .handle_network_event(IoEvent::Repeat {
-                        state: c.repeat_state,
-                    })
-                    .await;
+            Flag::Shuffle(wanted) => {
+                if let Some(event) = shuffle_event(c.shuffle_state, wanted) {
+                    if self.dispatch(event).await {
+                        let shuffle_state = !c.shuffle_state;
+                        self.await_playback("The shuffle change", |context| {
+                            context.shuffle_state == shuffle_state
+                        })
+                        .await;
+                    }
+                }
+            }
+            Flag::Repeat(wanted) => {
+                if let Some(event @ IoEvent::Repeat { state }) =
+                    repeat_event(c.repeat_state, wanted)
+                {
+                    if self.dispatch(event).await {
+                        self.await_playback("The repeat change", |context| {
+                            context.repeat_state == state
+                        })
+                        .await;
+                    }
+                }
             }
         }
 
@@ -406,11 +610,35 @@ impl CliApp {
     // spt playback -s
     pub async fn get_status(&mut self, format: String) -> Result<String> {
         // Update info on current playback
-        self.net
-            .handle_network_event(IoEvent::GetCurrentPlayback)
-            .await;
-        self.net
-            .handle_network_event(IoEvent::GetCurrentUserSavedTracks { offset: None })
+        self.dispatch(IoEvent::GetCurrentPlayback).await;
+        self.status(format).await
+    }
+
+    // The status after `spt play`, once the playback shows what was started rather than what
+    // played before it. Nothing is printed when starting it failed, so that the error that
+    // follows isn't mixed up with the status.
+    pub async fn status_after_play(
+        &mut self,
+        started: Option<Started>,
+        format: String,
+    ) -> Result<String> {
+        if !self.net.app.read().await.api_error.is_empty() {
+            return Ok(String::new());
+        }
+        // Queued, or a dry run, which leave the playback as it was
+        let Some(started) = started else {
+            return self.get_status(format).await;
+        };
+        self.await_playback("The playback started", |context| {
+            started.is_playing(context)
+        })
+        .await;
+        self.status(format).await
+    }
+
+    // The status of the playback as last fetched
+    async fn status(&mut self, format: String) -> Result<String> {
+        self.dispatch(IoEvent::GetCurrentUserSavedTracks { offset: None })
             .await;
 
         let context = self
@@ -432,35 +660,47 @@ impl CliApp {
                     .ok_or_else(|| anyhow!("no track id found"))?;
                 let mut hs = Format::from_type(FormatType::Track(Box::new(track.clone())));
                 if let Some(progress) = &context.progress {
-                    hs.push(Format::Position((
+                    hs.extend(Format::progress(
                         progress.num_milliseconds() as u32,
                         track.duration.num_milliseconds() as u32,
-                    )))
+                    ))
                 }
                 hs.push(Format::Flags((
                     context.repeat_state,
                     context.shuffle_state,
                     self.is_a_saved_item(PlayableId::Track(track_id)).await,
                 )));
+                hs.push(Format::RepeatMode(context.repeat_state));
+                hs.push(Format::Shuffle(context.shuffle_state));
                 hs
             }
             PlayableItem::Episode(episode) => {
                 let mut hs = Format::from_type(FormatType::Episode(Box::new(episode.clone())));
                 if let Some(progress) = &context.progress {
-                    hs.push(Format::Position((
+                    hs.extend(Format::progress(
                         progress.num_milliseconds() as u32,
                         episode.duration.num_milliseconds() as u32,
-                    )))
+                    ))
                 }
                 hs.push(Format::Flags((
                     context.repeat_state,
                     context.shuffle_state,
                     false,
                 )));
+                hs.push(Format::RepeatMode(context.repeat_state));
+                hs.push(Format::Shuffle(context.shuffle_state));
                 hs
             }
         };
 
+        // Only looked up when asked for, as it takes another request
+        if format.contains("%c") {
+            if let Some(playing_context) = &context.context {
+                if let Ok(name) = self.net.get_context_name(&playing_context.uri).await {
+                    hs.push(Format::Context(name));
+                }
+            }
+        }
         hs.push(Format::Device(context.device.name));
         hs.push(Format::Playing(context.is_playing));
         context
@@ -471,62 +711,185 @@ impl CliApp {
         Ok(self.format_output(format, hs))
     }
 
+    // spt pb --follow (or --wait), which prints the status again whenever what is playing changes,
+    // or every change as JSON, until interrupted
+    pub async fn follow_status(&mut self, format: String, options: FollowOptions) -> Result<()> {
+        let mut previous = None;
+        let interrupted = tokio::signal::ctrl_c();
+        tokio::pin!(interrupted);
+        loop {
+            tokio::select! {
+                result = &mut interrupted => return Ok(result?),
+                result = self.print_changes(&format, &options, &mut previous) => result?,
+            }
+            tokio::select! {
+                result = &mut interrupted => return Ok(result?),
+                _ = tokio::time::sleep(options.interval) => {}
+            }
+        }
+    }
+
+    // One poll of `follow_status`
+    async fn print_changes(
+        &mut self,
+        format: &str,
+        options: &FollowOptions,
+        previous: &mut Option<(StatusSnapshot, Instant)>,
+    ) -> Result<()> {
+        let mut stdout = io::stdout();
+        self.dispatch(IoEvent::GetCurrentPlayback).await;
+        let polled_at = Instant::now();
+        self.report_error(options).await?;
+
+        // Nothing to report while no device is playing
+        let Some(snapshot) = self
+            .net
+            .app
+            .read()
+            .await
+            .current_playback_context
+            .as_ref()
+            .map(StatusSnapshot::new)
+        else {
+            return Ok(());
+        };
+        let events = classify(
+            previous.as_ref().map(|(snapshot, _)| snapshot),
+            &snapshot,
+            previous
+                .as_ref()
+                .map(|(_, at)| polled_at - *at)
+                .unwrap_or_default(),
+        );
+
+        // Flushed for every line, so that consumers see changes as they happen even when stdout is
+        // a pipe
+        if options.events_json {
+            for event in events {
+                writeln!(stdout, "{}", event_line(event, &snapshot)?)?;
+                stdout.flush()?;
+            }
+        } else if events.iter().any(PlaybackEvent::changes_what_is_playing) {
+            match self.get_status(format.to_string()).await {
+                Ok(status) => {
+                    writeln!(stdout, "{}", status)?;
+                    stdout.flush()?;
+                }
+                Err(err) => eprintln!("{}", err),
+            }
+            self.report_error(options).await?;
+        }
+        *previous = Some((snapshot, polled_at));
+        Ok(())
+    }
+
+    // Network errors are only printed while following, so that a dropped connection doesn't end
+    // it, unless `--fail-fast` was given
+    async fn report_error(&mut self, options: &FollowOptions) -> Result<()> {
+        let error = std::mem::take(&mut self.net.app.write().await.api_error);
+        if error.is_empty() {
+            Ok(())
+        } else if options.fail_fast {
+            Err(anyhow!(error))
+        } else {
+            eprintln!("{}", error);
+            Ok(())
+        }
+    }
+
     // spt play -u URI
-    pub async fn play_uri(&mut self, uri: String, queue: bool, random: bool) {
+    pub async fn play_uri(
+        &mut self,
+        uri: String,
+        queue: bool,
+        random: bool,
+    ) -> Result<Option<Started>> {
+        self.play_uris(&[uri], queue, random).await
+    }
+
+    // spt play --uri URI... (tracks and episodes play in order, or a single album, artist,
+    // playlist or show)
+    pub async fn play_uris(
+        &mut self,
+        uris: &[String],
+        queue: bool,
+        random: bool,
+    ) -> Result<Option<Started>> {
+        let play_context_id = match classify_uris(uris)? {
+            PlayUris::Playables(playable_ids) => {
+                if queue {
+                    for playable_id in playable_ids {
+                        self.dispatch(IoEvent::AddItemToQueue { playable_id }).await;
+                    }
+                    return Ok(None);
+                }
+                let index = if random {
+                    thread_rng().gen_range(0..playable_ids.len())
+                } else {
+                    0
+                };
+                let started = Started::Item(playable_ids[index].clone());
+                let event = IoEvent::StartPlayablesPlayback {
+                    playable_ids,
+                    offset: Some(PlaybackOffset::Index(index as u32)),
+                };
+                return Ok(self.start(event, started).await);
+            }
+            PlayUris::Context(play_context_id) => play_context_id,
+        };
+
         let offset = if random {
-            let play_context_id = handle_error!(self, PlayContextId::from_uri(&uri));
-            match play_context_id {
+            let num = match &play_context_id {
                 PlayContextId::Album(id) => {
-                    let album = handle_error!(self, self.net.spotify.album(id, None).await);
-                    let num = album.tracks.total;
-                    Some(thread_rng().gen_range(0..num) as usize)
+                    let album = handle_error!(
+                        self,
+                        self.net.spotify.album(id.clone(), None).await,
+                        Ok(None)
+                    );
+                    album.tracks.total
                 }
                 PlayContextId::Artist(id) => {
-                    let tracks =
-                        handle_error!(self, self.net.spotify.artist_top_tracks(id, None).await);
-                    let num = tracks.len();
-                    Some(thread_rng().gen_range(0..num) as usize)
+                    let tracks = handle_error!(
+                        self,
+                        self.net.spotify.artist_top_tracks(id.clone(), None).await,
+                        Ok(None)
+                    );
+                    tracks.len() as u32
                 }
                 PlayContextId::Playlist(id) => {
-                    let playlist =
-                        handle_error!(self, self.net.spotify.playlist(id, None, None).await);
-                    let num = playlist.tracks.total;
-                    Some(thread_rng().gen_range(0..num) as usize)
+                    let playlist = handle_error!(
+                        self,
+                        self.net.spotify.playlist(id.clone(), None, None).await,
+                        Ok(None)
+                    );
+                    playlist.tracks.total
                 }
                 PlayContextId::Show(id) => {
-                    let show = handle_error!(self, self.net.spotify.get_a_show(id, None).await);
-                    let num = show.episodes.total;
-                    Some(thread_rng().gen_range(0..num) as usize)
+                    let show = handle_error!(
+                        self,
+                        self.net.spotify.get_a_show(id.clone(), None).await,
+                        Ok(None)
+                    );
+                    show.episodes.total
                 }
-            }
+            };
+            Some(PlaybackOffset::Index(thread_rng().gen_range(0..num)))
         } else {
             None
         };
 
-        if uri.contains("spotify:track:") {
-            let playable_id = handle_error!(self, PlayableId::from_uri(&uri));
+        let started = Started::Context(play_context_id.clone());
+        let event = IoEvent::StartContextPlayback {
+            play_context_id,
+            offset,
+        };
+        Ok(self.start(event, started).await)
+    }
 
-            if queue {
-                self.net
-                    .handle_network_event(IoEvent::AddItemToQueue { playable_id })
-                    .await;
-            } else {
-                self.net
-                    .handle_network_event(IoEvent::StartPlayablesPlayback {
-                        playable_ids: vec![playable_id],
-                        offset: Some(0),
-                    })
-                    .await;
-            }
-        } else {
-            let play_context_id = handle_error!(self, PlayContextId::from_uri(&uri));
-            self.net
-                .handle_network_event(IoEvent::StartContextPlayback {
-                    play_context_id,
-                    offset: offset.map(|o| o as u32),
-                })
-                .await;
-        }
+    // Sends the event that starts the playback, returning what it started once that went through
+    async fn start(&mut self, event: IoEvent<'_>, started: Started) -> Option<Started> {
+        let is_sent = self.dispatch(event).await;
+        (is_sent && self.net.app.read().await.api_error.is_empty()).then_some(started)
     }
 
     // spt play -n NAME ...
@@ -536,13 +899,37 @@ impl CliApp {
         item: Type,
         queue: bool,
         random: bool,
-    ) -> Result<()> {
-        self.net
-            .handle_network_event(IoEvent::GetSearchResults {
-                search_term: name.clone(),
-                country: None,
-            })
-            .await;
+        radio: bool,
+    ) -> Result<Option<Started>> {
+        // The radio starts from the artist, whatever type is asked for
+        let search_type = if radio {
+            Some(SearchType::Artist)
+        } else {
+            item.search_type()
+        };
+        let country = self.net.app.read().await.get_user_country();
+        self.dispatch(IoEvent::GetSearchResults {
+            search_term: name.clone(),
+            types: Some(search_type.into_iter().collect()),
+            country,
+        })
+        .await;
+
+        if radio {
+            let artist_id = self
+                .net
+                .app
+                .read()
+                .await
+                .search_results
+                .artists
+                .get_results(None)
+                .and_then(|r| r.items.first())
+                .ok_or_else(|| anyhow!("no artists with name '{}'", name))?
+                .id
+                .clone();
+            return self.play_artist_radio(artist_id).await;
+        }
         // Get the uri of the first found
         // item + the offset or return an error message
         let uri = {
@@ -550,53 +937,53 @@ impl CliApp {
             match item {
                 Type::Album => results
                     .albums
-                    .as_ref()
+                    .get_results(None)
                     .map(|r| r.items.iter().find(|item| item.id.is_some()))
                     .flatten()
-                    .ok_or_else(|| anyhow!("no albums with name '{name}'"))?
+                    .ok_or_else(|| anyhow!("no albums with name '{}'", name))?
                     .id
                     .as_ref()
                     .unwrap()
                     .uri(),
                 Type::Artist => results
                     .artists
-                    .as_ref()
+                    .get_results(None)
                     .map(|r| r.items.first())
                     .flatten()
-                    .ok_or_else(|| anyhow!("no artists with name '{name}'"))?
+                    .ok_or_else(|| anyhow!("no artists with name '{}'", name))?
                     .id
                     .uri(),
                 // Type::Episode => results
                 //     .episodes
-                //     .as_ref()
+                //     .get_results(None)
                 //     .map(|r| r.items.first())
                 //     .flatten()
-                //     .ok_or_else(|| anyhow!("no episodes with name '{name}'"))?
+                //     .ok_or_else(|| anyhow!("no episodes with name '{}'", name))?
                 //     .id
                 //     .as_ref()
                 //     .uri(),
                 Type::Playlist => results
                     .playlists
-                    .as_ref()
+                    .get_results(None)
                     .map(|r| r.items.first())
                     .flatten()
-                    .ok_or_else(|| anyhow!("no playlists with name '{name}'"))?
+                    .ok_or_else(|| anyhow!("no playlists with name '{}'", name))?
                     .id
                     .uri(),
                 Type::Show => results
                     .shows
-                    .as_ref()
+                    .get_results(None)
                     .map(|r| r.items.first())
                     .flatten()
-                    .ok_or_else(|| anyhow!("no shows with name '{name}'"))?
+                    .ok_or_else(|| anyhow!("no shows with name '{}'", name))?
                     .id
                     .uri(),
                 Type::Track => results
                     .tracks
-                    .as_ref()
+                    .get_results(None)
                     .map(|r| r.items.iter().find(|item| item.id.is_some()))
                     .flatten()
-                    .ok_or_else(|| anyhow!("no tracks with name '{name}'"))?
+                    .ok_or_else(|| anyhow!("no tracks with name '{}'", name))?
                     .id
                     .as_ref()
                     .unwrap()
@@ -606,24 +993,106 @@ impl CliApp {
         };
 
         // Play or queue the uri
-        self.play_uri(uri, queue, random).await;
+        self.play_uri(uri, queue, random).await
+    }
 
-        Ok(())
+    // spt play --pick-playlist: lists the playlists, and returns the uri of the one picked
+    pub async fn pick_playlist(
+        &mut self,
+        filter: Option<&str>,
+        prompt: &mut impl Prompt,
+    ) -> Result<String> {
+        if !prompt.is_interactive() {
+            return Err(anyhow!(
+                "--pick-playlist reads the number from a terminal, use --uri or --name with \
+--playlist to play a playlist from a script"
+            ));
+        }
+
+        self.dispatch(IoEvent::GetPlaylists).await;
+        let app = self.net.app.read().await;
+        let playlists = app
+            .playlists
+            .as_ref()
+            .map(|playlists| filter_playlists(&playlists.items, filter))
+            .unwrap_or_default();
+        if playlists.is_empty() {
+            return Err(match filter {
+                Some(filter) => anyhow!("no playlists with '{}' in their name", filter),
+                None => anyhow!("no playlists found"),
+            });
+        }
+
+        let list = playlists
+            .iter()
+            .enumerate()
+            .map(|(index, playlist)| {
+                let name = self.format_output(
+                    String::from("%p"),
+                    Format::from_type(FormatType::Playlist(Box::new((*playlist).clone()))),
+                );
+                format!("{:>3}. {}\n", index + 1, name)
+            })
+            .collect::<String>();
+        prompt.show(&list);
+        loop {
+            prompt.show("Playlist number: ");
+            let Some(input) = prompt.read_line()? else {
+                return Err(anyhow!("no playlist was picked"));
+            };
+            match parse_selection(&input, playlists.len()) {
+                Ok(index) => return Ok(playlists[index].id.uri()),
+                Err(message) => prompt.show(&format!("{}\n", message)),
+            }
+        }
+    }
+
+    // Plays the recommendations based on the artist, the way the TUI starts them
+    async fn play_artist_radio(&mut self, artist_id: ArtistId<'static>) -> Result<Option<Started>> {
+        let country = self.net.app.read().await.get_user_country();
+        self.dispatch(IoEvent::GetRecommendationsForSeed {
+            seed_artist_ids: Some(vec![artist_id]),
+            seed_track_ids: None,
+            first_track: Box::new(None),
+            country,
+        })
+        .await;
+
+        let playable_ids = self
+            .net
+            .app
+            .read()
+            .await
+            .recommended_tracks
+            .iter()
+            .filter_map(|track| track.id.clone())
+            .map(PlayableId::Track)
+            .collect::<Vec<_>>();
+        if playable_ids.is_empty() {
+            return Err(anyhow!("no recommendations found for the artist"));
+        }
+        let started = Started::Item(playable_ids[0].clone());
+        let event = IoEvent::StartPlayablesPlayback {
+            playable_ids,
+            offset: Some(PlaybackOffset::Index(0)),
+        };
+        Ok(self.start(event, started).await)
     }
 
     // spt query -s SEARCH ...
     pub async fn query(&mut self, search: String, format: String, item: Type) -> String {
-        self.net
-            .handle_network_event(IoEvent::GetSearchResults {
-                search_term: search.clone(),
-                country: None,
-            })
-            .await;
+        let country = self.net.app.read().await.get_user_country();
+        self.dispatch(IoEvent::GetSearchResults {
+            search_term: search.clone(),
+            types: Some(item.search_type().into_iter().collect()),
+            country,
+        })
+        .await;
 
         let app = self.net.app.read().await;
         match item {
             Type::Album => {
-                if let Some(results) = &app.search_results.albums {
+                if let Some(results) = app.search_results.albums.get_results(None) {
                     results
                         .items
                         .iter()
@@ -640,7 +1109,7 @@ impl CliApp {
                 }
             }
             Type::Artist => {
-                if let Some(results) = &app.search_results.artists {
+                if let Some(results) = app.search_results.artists.get_results(None) {
                     results
                         .items
                         .iter()
@@ -657,7 +1126,7 @@ impl CliApp {
                 }
             }
             // Type::Episode => {
-            //     if let Some(results) = &app.search_results.episodes {
+            //     if let Some(results) = app.search_results.episodes.get_results(None) {
             //         results
             //             .items
             //             .iter()
@@ -676,7 +1145,7 @@ impl CliApp {
             //     }
             // }
             Type::Playlist => {
-                if let Some(results) = &app.search_results.playlists {
+                if let Some(results) = app.search_results.playlists.get_results(None) {
                     results
                         .items
                         .iter()
@@ -693,7 +1162,7 @@ impl CliApp {
                 }
             }
             Type::Show => {
-                if let Some(results) = &app.search_results.shows {
+                if let Some(results) = app.search_results.shows.get_results(None) {
                     results
                         .items
                         .iter()
@@ -710,7 +1179,7 @@ impl CliApp {
                 }
             }
             Type::Track => {
-                if let Some(results) = &app.search_results.tracks {
+                if let Some(results) = app.search_results.tracks.get_results(None) {
                     results
                         .items
                         .iter()
@@ -731,3 +1200,532 @@ impl CliApp {
         }
     }
 }
+
+// What `spt play` started, which the status printed after it waits for
+#[derive(Debug, PartialEq)]
+pub enum Started {
+    Item(PlayableId<'static>),
+    Context(PlayContextId<'static>),
+}
+
+impl Started {
+    fn is_playing(&self, context: &CurrentPlaybackContext) -> bool {
+        match self {
+            Started::Item(playable_id) => context
+                .item
+                .as_ref()
+                .and_then(|item| item.id())
+                .is_some_and(|id| id.uri() == playable_id.uri()),
+            Started::Context(play_context_id) => context
+                .context
+                .as_ref()
+                .is_some_and(|context| context.uri == play_context_id.uri()),
+        }
+    }
+}
+
+// What a list of URIs, or open.spotify.com links, plays: either tracks and episodes, in order, or
+// one context
+#[derive(Debug, PartialEq)]
+enum PlayUris {
+    Playables(Vec<PlayableId<'static>>),
+    Context(PlayContextId<'static>),
+}
+
+fn classify_uris(uris: &[String]) -> Result<PlayUris> {
+    let mut playable_ids = vec![];
+    let mut play_context_ids = vec![];
+    for uri in uris {
+        if let Ok(playable_id) = PlayableId::from_uri_or_url(uri) {
+            reject_empty_id(uri, playable_id.id())?;
+            playable_ids.push(playable_id.into_static());
+        } else {
+            let play_context_id = PlayContextId::from_uri_or_url(uri)
+                .map_err(|e| anyhow!("failed to play '{}': {}", uri, e))?;
+            reject_empty_id(uri, play_context_id.id())?;
+            play_context_ids.push(play_context_id.into_static());
+        }
+    }
+
+    match (play_context_ids.pop(), play_context_ids.is_empty()) {
+        (None, _) if !playable_ids.is_empty() => Ok(PlayUris::Playables(playable_ids)),
+        (Some(play_context_id), true) if playable_ids.is_empty() => {
+            Ok(PlayUris::Context(play_context_id))
+        }
+        (None, _) => Err(anyhow!("no URI to play")),
+        (Some(_), true) => Err(anyhow!(
+            "an album, artist, playlist or show can't be played along with tracks or episodes"
+        )),
+        (Some(_), false) => Err(anyhow!(
+            "only one album, artist, playlist or show can be played at a time"
+        )),
+    }
+}
+
+// The ids parse without one, e.g. `spotify:track:`, but Spotify rejects them
+fn reject_empty_id(uri: &str, id: &str) -> Result<()> {
+    if id.is_empty() {
+        return Err(anyhow!("failed to play '{}': the id is missing", uri));
+    }
+    Ok(())
+}
+
+// A podcast by its uri, open.spotify.com link or bare id
+fn parse_show_id(show: &str) -> Result<ShowId<'static>> {
+    match PlayContextId::from_id_or_uri(rspotify::model::Type::Show, show) {
+        Ok(PlayContextId::Show(show_id)) if !show_id.id().is_empty() => Ok(show_id.into_static()),
+        _ => Err(anyhow!("'{}' is not a valid podcast URI", show)),
+    }
+}
+
+// Whether going back `amount` times from `progress_ms` into the song restarts it first, the way
+// the TUI does, and how many songs it then skips back. Without a progress every jump is a skip.
+fn previous_jumps(progress_ms: Option<u128>, amount: u64) -> (bool, u64) {
+    match progress_ms {
+        Some(progress_ms) if progress_ms >= TRACK_RESTART_MS && amount > 0 => (true, amount - 1),
+        _ => (false, amount),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::clap::{play_subcommand, playback_subcommand};
+    use crate::network::mock_api;
+
+    #[test]
+    fn going_back_restarts_the_song_first() {
+        assert_eq!(previous_jumps(Some(45_000), 1), (true, 0));
+        assert_eq!(previous_jumps(Some(45_000), 2), (true, 1));
+        assert_eq!(previous_jumps(Some(3_000), 3), (true, 2));
+        assert_eq!(previous_jumps(Some(2_999), 1), (false, 1));
+        assert_eq!(previous_jumps(Some(0), 2), (false, 2));
+        // Forced, or with nothing playing
+        assert_eq!(previous_jumps(None, 2), (false, 2));
+
+        let parse = |args: &[&str]| {
+            playback_subcommand()
+                .try_get_matches_from(std::iter::once("playback").chain(args.iter().copied()))
+        };
+        let matches = parse(&["-pp", "--force"]).unwrap();
+        assert!(matches.get_flag("force"));
+        assert!(matches!(
+            JumpDirection::from_matches(&matches),
+            Some((JumpDirection::Previous, 2))
+        ));
+        let matches = parse(&["-p"]).unwrap();
+        assert!(!matches.get_flag("force"));
+        assert!(matches!(
+            JumpDirection::from_matches(&matches),
+            Some((JumpDirection::Previous, 1))
+        ));
+        assert!(matches!(
+            JumpDirection::from_matches(&parse(&["--next", "--next", "--next"]).unwrap()),
+            Some((JumpDirection::Next, 3))
+        ));
+        assert!(JumpDirection::from_matches(&parse(&["--volume", "10"]).unwrap()).is_none());
+        for args in [
+            &["--force"][..],
+            &["-n", "--force"],
+            &["-n", "-p"],
+            &["-p", "--volume", "10"],
+        ] {
+            assert!(parse(args).is_err(), "{:?}", args);
+        }
+    }
+
+    // A CLI on a Spotify that shows the shuffle on from the `turned_on_at`th look at the playback
+    async fn shuffle_cli(turned_on_at: usize) -> (CliApp, mock_api::Requests) {
+        let polls = std::sync::atomic::AtomicUsize::new(0);
+        let (mut net, requests) = mock_api::network_with(move |path| {
+            if !path.starts_with("/me/player?") {
+                return None;
+            }
+            let poll = polls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            let mut playback = mock_api::playback();
+            playback["shuffle_state"] = (poll >= turned_on_at).into();
+            Some(playback.to_string())
+        })
+        .await;
+        net.client_config.device_id = Some(String::from("active"));
+        let mut cli = CliApp::new(net, UserConfig::new(), false);
+        cli.convergence_timeout = std::time::Duration::from_millis(600);
+        cli.dispatch(IoEvent::GetCurrentPlayback).await;
+        (cli, requests)
+    }
+
+    fn playback_polls(requests: &mock_api::Requests) -> usize {
+        requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|path| path.starts_with("/me/player?"))
+            .count()
+    }
+
+    #[tokio::test]
+    async fn waits_for_the_shuffle_to_show() {
+        // The first poll after the toggle still has the shuffle off
+        let (mut cli, requests) = shuffle_cli(2).await;
+        cli.mark(Flag::Shuffle(None)).await.unwrap();
+        assert_eq!(playback_polls(&requests), 3);
+        assert!(cli.warnings.is_empty());
+        let app = cli.net.app.read().await;
+        assert!(app.current_playback_context.as_ref().unwrap().shuffle_state);
+    }
+
+    #[tokio::test]
+    async fn warns_when_the_shuffle_never_shows() {
+        let (mut cli, requests) = shuffle_cli(usize::MAX).await;
+        let started_at = Instant::now();
+        cli.mark(Flag::Shuffle(Some(true))).await.unwrap();
+        assert!(started_at.elapsed() >= cli.convergence_timeout);
+        assert!(playback_polls(&requests) > 2);
+        assert_eq!(
+            cli.warnings,
+            ["The shuffle change hasn't shown in the playback yet, the status may be out of date"]
+        );
+
+        // A dry run changes nothing, so there's nothing to wait for
+        let (mut cli, requests) = shuffle_cli(usize::MAX).await;
+        cli.dry_run = true;
+        cli.mark(Flag::Shuffle(None)).await.unwrap();
+        assert_eq!(playback_polls(&requests), 1);
+        assert!(cli.warnings.is_empty());
+    }
+
+    // Answers with the lines it was given, in order, and keeps what it was shown
+    struct ScriptedPrompt {
+        is_interactive: bool,
+        lines: std::collections::VecDeque<&'static str>,
+        shown: String,
+    }
+
+    impl Prompt for ScriptedPrompt {
+        fn is_interactive(&self) -> bool {
+            self.is_interactive
+        }
+
+        fn show(&mut self, text: &str) {
+            self.shown.push_str(text);
+        }
+
+        fn read_line(&mut self) -> Result<Option<String>> {
+            Ok(self.lines.pop_front().map(String::from))
+        }
+    }
+
+    fn answers(lines: &[&'static str]) -> ScriptedPrompt {
+        ScriptedPrompt {
+            is_interactive: true,
+            lines: lines.iter().copied().collect(),
+            shown: String::new(),
+        }
+    }
+
+    async fn playlists_cli() -> (CliApp, mock_api::Requests) {
+        let playlist = |id: &str, name: &str| {
+            serde_json::json!({
+                "collaborative": false,
+                "external_urls": {},
+                "href": "",
+                "id": id,
+                "images": [],
+                "name": name,
+                "owner": { "external_urls": {}, "href": "", "id": "listener" },
+                "snapshot_id": "snapshot",
+                "tracks": { "href": "", "total": 10 }
+            })
+        };
+        let (net, requests) = mock_api::network_with(move |path| {
+            path.starts_with("/me/playlists").then(|| {
+                serde_json::json!({
+                    "href": "",
+                    "items": [
+                        playlist("37i9dQZF1DXcBWIGoYBM5M", "Morning Jazz"),
+                        playlist("37i9dQZF1DX0XUsuxWHRQd", "Workout"),
+                        playlist("37i9dQZF1DX4sWSpwq3LiO", "Jazz Classics"),
+                    ],
+                    "limit": 20,
+                    "next": null,
+                    "offset": 0,
+                    "previous": null,
+                    "total": 3
+                })
+                .to_string()
+            })
+        })
+        .await;
+        (CliApp::new(net, UserConfig::new(), false), requests)
+    }
+
+    #[tokio::test]
+    async fn picks_a_playlist_from_the_list() {
+        let (mut cli, _) = playlists_cli().await;
+        let mut prompt = answers(&["3\n", "jazz\n", "2\n"]);
+        let uri = cli.pick_playlist(Some("jazz"), &mut prompt).await.unwrap();
+        assert_eq!(uri, "spotify:playlist:37i9dQZF1DX4sWSpwq3LiO");
+        assert_eq!(
+            prompt.shown,
+            "  1. Morning Jazz\n  2. Jazz Classics\n\
+Playlist number: Pick a number from 1 to 2\n\
+Playlist number: 'jazz' isn't a number\n\
+Playlist number: "
+        );
+
+        // The input ends before anything is picked
+        let (mut cli, _) = playlists_cli().await;
+        let mut prompt = answers(&["0\n"]);
+        assert!(cli.pick_playlist(None, &mut prompt).await.is_err());
+        assert!(prompt.shown.contains("  3. Jazz Classics\n"));
+
+        let (mut cli, _) = playlists_cli().await;
+        let error = cli
+            .pick_playlist(Some("podcast"), &mut answers(&["1\n"]))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "no playlists with 'podcast' in their name"
+        );
+    }
+
+    #[tokio::test]
+    async fn searches_in_the_configured_market() {
+        let (net, requests) = mock_api::network_with(|_| None).await;
+        let mut cli = CliApp::new(net, UserConfig::new(), false);
+        cli.query(String::from("jazz"), String::from("%t"), Type::Track)
+            .await;
+        cli.net.app.write().await.user_config.behavior.market =
+            Some(rspotify::model::Country::UnitedKingdom);
+        cli.query(String::from("jazz"), String::from("%t"), Type::Track)
+            .await;
+
+        let requests = requests.lock().unwrap();
+        assert!(!requests[0].contains("market="), "{}", requests[0]);
+        assert!(requests[1].contains("market=GB"), "{}", requests[1]);
+    }
+
+    #[tokio::test]
+    async fn picking_needs_a_terminal() {
+        let (mut cli, requests) = playlists_cli().await;
+        let mut prompt = ScriptedPrompt {
+            is_interactive: false,
+            ..answers(&["1\n"])
+        };
+        assert!(cli.pick_playlist(None, &mut prompt).await.is_err());
+        // Nothing was fetched or read
+        assert!(requests.lock().unwrap().is_empty());
+        assert_eq!(prompt.lines.len(), 1);
+
+        let parse = |args: &[&str]| {
+            play_subcommand()
+                .try_get_matches_from(std::iter::once("play").chain(args.iter().copied()))
+        };
+        assert!(parse(&["--queue", "-u", "spotify:track:4iV5W9uYEdYUVa79Axb7Rh"]).is_ok());
+        let matches = parse(&["--pick-playlist", "--filter", "jazz", "--random"]).unwrap();
+        assert!(matches.get_flag("pick-playlist"));
+        assert_eq!(
+            matches.get_one::<String>("filter").map(String::as_str),
+            Some("jazz")
+        );
+        for args in [
+            &["--filter", "jazz"][..],
+            &["--limit", "5", "-n", "jazz", "-p"],
+            &[
+                "--filter",
+                "jazz",
+                "-u",
+                "spotify:playlist:37i9dQZF1DXcBWIGoYBM5M",
+            ],
+            &["--pick-playlist", "--queue"],
+            &["--pick-playlist", "-n", "jazz", "-p"],
+            &["--pick-playlist", "--track"],
+        ] {
+            assert!(parse(args).is_err(), "{:?}", args);
+        }
+    }
+
+    #[tokio::test]
+    async fn searches_only_the_type_it_reads() {
+        let queries = [
+            (Type::Album, "album"),
+            (Type::Artist, "artist"),
+            (Type::Playlist, "playlist"),
+            (Type::Show, "show"),
+            (Type::Track, "track"),
+        ];
+        for (item, search_type) in queries {
+            let (net, requests) = mock_api::network().await;
+            let mut cli = CliApp::new(net, UserConfig::new(), false);
+            cli.query(String::from("song"), String::from("%t"), item)
+                .await;
+            assert_eq!(mock_api::searches(&requests), [search_type]);
+        }
+
+        let plays = [
+            (Type::Track, false, "track"),
+            (Type::Album, false, "album"),
+            // The radio starts from an artist
+            (Type::Track, true, "artist"),
+        ];
+        for (item, radio, search_type) in plays {
+            let (net, requests) = mock_api::network().await;
+            let mut cli = CliApp::new(net, UserConfig::new(), false);
+            // Nothing is found, so nothing is played either
+            assert!(cli
+                .play(String::from("song"), item, false, false, radio)
+                .await
+                .is_err());
+            assert_eq!(mock_api::searches(&requests), [search_type]);
+        }
+    }
+
+    #[test]
+    fn classifies_uris() {
+        let uris = |uris: &[&str]| uris.iter().map(|uri| uri.to_string()).collect::<Vec<_>>();
+        let track = "spotify:track:4iV5W9uYEdYUVa79Axb7Rh";
+        let episode = "spotify:episode:512ojhOuo1ktJprKbVcKyQ";
+        let album = "spotify:album:0sNOF9WDwhWunNAHPD3Baj";
+
+        assert_eq!(
+            classify_uris(&uris(&[episode, track])).unwrap(),
+            PlayUris::Playables(vec![
+                PlayableId::Episode(EpisodeId::from_id("512ojhOuo1ktJprKbVcKyQ").unwrap()),
+                PlayableId::Track(TrackId::from_id("4iV5W9uYEdYUVa79Axb7Rh").unwrap()),
+            ])
+        );
+        assert_eq!(
+            classify_uris(&uris(&[album])).unwrap(),
+            PlayUris::Context(PlayContextId::Album(
+                AlbumId::from_id("0sNOF9WDwhWunNAHPD3Baj").unwrap()
+            ))
+        );
+        // Links as copied from the share menu
+        assert_eq!(
+            classify_uris(&uris(&[
+                "https://open.spotify.com/album/0sNOF9WDwhWunNAHPD3Baj?si=0123456789abcdef"
+            ]))
+            .unwrap(),
+            classify_uris(&uris(&[album])).unwrap()
+        );
+
+        assert_eq!(
+            classify_uris(&uris(&[track, album]))
+                .unwrap_err()
+                .to_string(),
+            "an album, artist, playlist or show can't be played along with tracks or episodes"
+        );
+        assert!(classify_uris(&uris(&[album, album])).is_err());
+        assert_eq!(
+            classify_uris(&uris(&["spotify:track:"]))
+                .unwrap_err()
+                .to_string(),
+            "failed to play 'spotify:track:': the id is missing"
+        );
+        assert_eq!(
+            classify_uris(&uris(&["spotify:user:me"]))
+                .unwrap_err()
+                .to_string(),
+            "failed to play 'spotify:user:me': InvalidType"
+        );
+        assert!(classify_uris(&[]).is_err());
+
+        // Repeated or separated by commas
+        let matches = play_subcommand()
+            .try_get_matches_from(["play", "-u", &format!("{track},{episode}"), "-u", track])
+            .unwrap();
+        assert_eq!(
+            matches
+                .get_many::<String>("uri")
+                .unwrap()
+                .collect::<Vec<_>>(),
+            [track, episode, track]
+        );
+    }
+
+    #[tokio::test]
+    async fn lists_devices_with_their_ids_and_the_active_one_marked() {
+        let (net, _) = mock_api::network().await;
+        net.app.write().await.devices = serde_json::from_value(serde_json::json!({
+            "devices": [
+                {
+                    "id": "avr", "is_active": true, "is_private_session": false,
+                    "is_restricted": false, "name": "Living Room", "type": "AVR",
+                    "volume_percent": 40,
+                },
+                {
+                    "id": null, "is_active": false, "is_private_session": false,
+                    "is_restricted": false, "name": "Web Player", "type": "Computer",
+                    "volume_percent": null,
+                },
+            ],
+        }))
+        .unwrap();
+        let mut cli = CliApp::new(net, UserConfig::new(), false);
+
+        assert_eq!(
+            cli.list(Type::Device, "%A %v% %d").await,
+            "* 40% Living Room\nNone% Web Player"
+        );
+        assert_eq!(
+            cli.list(Type::Device, "%d (%i)").await,
+            "Living Room (avr)\nWeb Player (None)"
+        );
+    }
+
+    #[tokio::test]
+    async fn queues_each_uri_in_order() {
+        let (net, requests) = mock_api::network().await;
+        let mut cli = CliApp::new(net, UserConfig::new(), false);
+        let uris = [
+            String::from("spotify:track:4iV5W9uYEdYUVa79Axb7Rh"),
+            String::from("spotify:episode:512ojhOuo1ktJprKbVcKyQ"),
+        ];
+        cli.play_uris(&uris, true, false).await.unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].contains("4iV5W9uYEdYUVa79Axb7Rh"));
+        assert!(requests[1].contains("512ojhOuo1ktJprKbVcKyQ"));
+    }
+
+    #[tokio::test]
+    async fn names_what_it_found_nothing_for() {
+        let (net, _) = mock_api::network().await;
+        let mut cli = CliApp::new(net, UserConfig::new(), false);
+        let error = cli
+            .play(
+                String::from("A cool song"),
+                Type::Track,
+                false,
+                false,
+                false,
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(error.to_string(), "no tracks with name 'A cool song'");
+    }
+
+    #[test]
+    fn parses_podcasts_by_uri_link_or_id() {
+        let id = "4rOoJ6Egrf8K2IrywzwOMk";
+        for show in [
+            format!("spotify:show:{id}"),
+            format!("https://open.spotify.com/show/{id}?si=abc"),
+            String::from(id),
+        ] {
+            assert_eq!(parse_show_id(&show).unwrap().id(), id, "{}", show);
+        }
+        for show in [
+            "spotify:album:4aawyAB9vmqN3uQ7FjRGTy",
+            "https://open.spotify.com/episode/512ojhOuo1ktJprKbVcKyQ",
+            "spotify:show:",
+        ] {
+            assert_eq!(
+                parse_show_id(show).unwrap_err().to_string(),
+                format!("'{}' is not a valid podcast URI", show)
+            );
+        }
+    }
+}
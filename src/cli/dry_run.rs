@@ -0,0 +1,295 @@
+use crate::app::App;
+use crate::network::IoEvent;
+use crate::ui::util::millis_to_minutes;
+use rspotify::model::{Id, PlayableItem};
+
+// The name of an item the app already knows about, so that descriptions aren't only URIs
+fn name_of(app: &App, uri: &str) -> Option<String> {
+    if let Some(item) = app
+        .current_playback_context
+        .as_ref()
+        .and_then(|context| context.item.as_ref())
+    {
+        match item {
+            PlayableItem::Track(track) if track.id.as_ref().is_some_and(|id| id.uri() == uri) => {
+                return Some(track.name.clone());
+            }
+            PlayableItem::Episode(episode) if episode.id.uri() == uri => {
+                return Some(episode.name.clone());
+            }
+            _ => {}
+        }
+    }
+
+    let search = &app.search_results;
+    let tracks = search.tracks.pages.iter().flat_map(|page| &page.items);
+    let albums = search.albums.pages.iter().flat_map(|page| &page.items);
+    let artists = search.artists.pages.iter().flat_map(|page| &page.items);
+    let playlists = search
+        .playlists
+        .pages
+        .iter()
+        .chain(app.playlists.iter())
+        .flat_map(|page| &page.items);
+    let shows = search.shows.pages.iter().flat_map(|page| &page.items);
+
+    tracks
+        .filter_map(|track| Some((track.id.as_ref()?.uri(), &track.name)))
+        .chain(albums.filter_map(|album| Some((album.id.as_ref()?.uri(), &album.name))))
+        .chain(artists.map(|artist| (artist.id.uri(), &artist.name)))
+        .chain(playlists.map(|playlist| (playlist.id.uri(), &playlist.name)))
+        .chain(shows.map(|show| (show.id.uri(), &show.name)))
+        .find(|(item_uri, _)| item_uri == uri)
+        .map(|(_, name)| name.clone())
+}
+
+fn item(app: &App, uri: String) -> String {
+    match name_of(app, &uri) {
+        Some(name) => format!("{} — '{}'", uri, name),
+        None => uri,
+    }
+}
+
+fn items<'a>(app: &App, ids: impl IntoIterator<Item = &'a (impl Id + 'a)>) -> String {
+    ids.into_iter()
+        .map(|id| item(app, id.uri()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// What a mutating event would do, for `--dry-run`
+pub fn describe(app: &App, event: &IoEvent) -> String {
+    let description = match event {
+        IoEvent::AddItemToQueue { playable_id } => {
+            format!("add {} to the queue", item(app, playable_id.uri()))
+        }
+        IoEvent::ChangePlaylistDetails {
+            playlist_id,
+            name,
+            description,
+        } => {
+            let changes = [
+                name.as_ref().map(|name| format!("rename it to '{}'", name)),
+                description
+                    .as_ref()
+                    .map(|description| format!("set its description to '{}'", description)),
+            ];
+            format!(
+                "change playlist {}: {}",
+                item(app, playlist_id.uri()),
+                changes
+                    .into_iter()
+                    .flatten()
+                    .collect::<Vec<_>>()
+                    .join(" and ")
+            )
+        }
+        IoEvent::ChangeVolume { volume } => format!("set the volume to {}%", volume),
+        IoEvent::CurrentUserSavedAlbumAdd { album_id } => {
+            format!("save album {}", item(app, album_id.uri()))
+        }
+        IoEvent::CurrentUserSavedAlbumDelete { album_id } => {
+            format!(
+                "remove album {} from your library",
+                item(app, album_id.uri())
+            )
+        }
+        IoEvent::CurrentUserSavedShowAdd { show_id } => {
+            format!("follow podcast {}", item(app, show_id.uri()))
+        }
+        IoEvent::CurrentUserSavedShowDelete { show_id } => {
+            format!("unfollow podcast {}", item(app, show_id.uri()))
+        }
+        IoEvent::RemoveSavedShows { show_ids } => {
+            format!("unfollow podcast {}", items(app, show_ids))
+        }
+        IoEvent::NextTrack => String::from("skip to the next track"),
+        IoEvent::PausePlayback => String::from("pause playback"),
+        IoEvent::PreviousTrack => String::from("go back to the previous track"),
+        IoEvent::Repeat { state } => {
+            format!("set repeat to {}", format!("{:?}", state).to_lowercase())
+        }
+        IoEvent::ResumeEpisodePlayback {
+            playable_ids,
+            position_ms,
+            ..
+        } => format!(
+            "play {} from {}",
+            items(app, playable_ids),
+            millis_to_minutes(*position_ms as u128)
+        ),
+        IoEvent::ResumePlayback => String::from("resume playback"),
+        IoEvent::Seek { position_ms } => {
+            format!("seek to {}", millis_to_minutes(*position_ms as u128))
+        }
+        IoEvent::StartContextPlayback {
+            play_context_id, ..
+        } => format!("play {}", item(app, play_context_id.uri())),
+        IoEvent::StartPlayablesPlayback { playable_ids, .. } => {
+            format!("play {}", items(app, playable_ids))
+        }
+        IoEvent::ToggleSaveEpisode { episode_id } => {
+            let uri = item(app, episode_id.uri());
+            if app.liked_episode_ids_set.contains(episode_id) {
+                format!("remove episode {} from your episodes", uri)
+            } else {
+                format!("save episode {}", uri)
+            }
+        }
+        IoEvent::ToggleSaveTrack { track_id } => {
+            let uri = item(app, track_id.uri());
+            if app.liked_song_ids_set.contains(track_id) {
+                format!("remove track {} from your liked songs", uri)
+            } else {
+                format!("save track {}", uri)
+            }
+        }
+        IoEvent::ToggleShuffle => {
+            let is_shuffled = app
+                .current_playback_context
+                .as_ref()
+                .is_some_and(|context| context.shuffle_state);
+            format!("turn shuffle {}", if is_shuffled { "off" } else { "on" })
+        }
+        IoEvent::TransferPlaybackToDevice { device_id } => {
+            let name = app
+                .devices
+                .iter()
+                .flat_map(|payload| &payload.devices)
+                .find(|device| device.id.as_ref() == Some(device_id))
+                .map(|device| format!("{} — '{}'", device_id, device.name));
+            format!(
+                "transfer playback to device {}",
+                name.unwrap_or_else(|| device_id.clone())
+            )
+        }
+        IoEvent::UserUnfollowArtists { artist_ids } => {
+            format!("unfollow artist {}", items(app, artist_ids))
+        }
+        IoEvent::UserFollowArtists { artist_ids } => {
+            format!("follow artist {}", items(app, artist_ids))
+        }
+        IoEvent::UserFollowPlaylist { playlist_id, .. } => {
+            format!("follow playlist {}", item(app, playlist_id.uri()))
+        }
+        IoEvent::UserUnfollowPlaylist { playlist_id } => {
+            format!("unfollow playlist {}", item(app, playlist_id.uri()))
+        }
+        event => format!("send {:?}", event),
+    };
+    format!("would {}", description)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::PlaybackOffset;
+    use rspotify::model::{EpisodeId, PlayContextId, PlayableId, RepeatState, TrackId};
+
+    #[test]
+    fn describes_events() {
+        let mut app = App::default();
+        let track_id = TrackId::from_id("4iV5W9uYEdYUVa79Axb7Rh").unwrap();
+
+        assert_eq!(
+            describe(
+                &app,
+                &IoEvent::ToggleSaveTrack {
+                    track_id: track_id.clone()
+                }
+            ),
+            "would save track spotify:track:4iV5W9uYEdYUVa79Axb7Rh"
+        );
+
+        app.liked_song_ids_set.insert(track_id.clone());
+        assert_eq!(
+            describe(
+                &app,
+                &IoEvent::ToggleSaveTrack {
+                    track_id: track_id.clone()
+                }
+            ),
+            "would remove track spotify:track:4iV5W9uYEdYUVa79Axb7Rh from your liked songs"
+        );
+
+        assert_eq!(
+            describe(
+                &app,
+                &IoEvent::StartPlayablesPlayback {
+                    playable_ids: vec![PlayableId::Track(track_id)],
+                    offset: None,
+                }
+            ),
+            "would play spotify:track:4iV5W9uYEdYUVa79Axb7Rh"
+        );
+        assert_eq!(
+            describe(
+                &app,
+                &IoEvent::Seek {
+                    position_ms: 90_000
+                }
+            ),
+            "would seek to 1:30"
+        );
+        let episode_id = EpisodeId::from_id("512ojhOuo1ktJprKbVcKyQ").unwrap();
+        assert_eq!(
+            describe(
+                &app,
+                &IoEvent::ResumeEpisodePlayback {
+                    playable_ids: vec![PlayableId::Episode(episode_id)],
+                    offset: PlaybackOffset::Index(0),
+                    position_ms: 600_000,
+                }
+            ),
+            "would play spotify:episode:512ojhOuo1ktJprKbVcKyQ from 10:00"
+        );
+        assert_eq!(
+            describe(
+                &app,
+                &IoEvent::Repeat {
+                    state: RepeatState::Context
+                }
+            ),
+            "would set repeat to context"
+        );
+    }
+
+    #[test]
+    fn uses_names_from_search_results() {
+        let mut app = App::default();
+        app.search_results.albums.add_pages(
+            serde_json::from_value(serde_json::json!({
+                "href": "",
+                "items": [{
+                    "artists": [],
+                    "available_markets": [],
+                    "external_urls": {},
+                    "href": null,
+                    "id": "4aawyAB9vmqN3uQ7FjRGTy",
+                    "images": [],
+                    "name": "Global Warming",
+                    "release_date": null,
+                    "release_date_precision": null,
+                }],
+                "limit": 1,
+                "next": null,
+                "offset": 0,
+                "previous": null,
+                "total": 1,
+            }))
+            .unwrap(),
+        );
+
+        let album_id = rspotify::model::AlbumId::from_id("4aawyAB9vmqN3uQ7FjRGTy").unwrap();
+        assert_eq!(
+            describe(
+                &app,
+                &IoEvent::StartContextPlayback {
+                    play_context_id: PlayContextId::Album(album_id),
+                    offset: None,
+                }
+            ),
+            "would play spotify:album:4aawyAB9vmqN3uQ7FjRGTy — 'Global Warming'"
+        );
+    }
+}
@@ -0,0 +1,432 @@
+use crate::ui::util::{create_artist_string, millis_to_minutes};
+use anyhow::Result;
+use rspotify::model::{context::CurrentPlaybackContext, Id, PlayableItem};
+use serde::Serialize;
+use std::time::Duration;
+
+// How far the progress may drift from where it should be, between polls that take their own time,
+// before it counts as a seek
+const SEEK_TOLERANCE: Duration = Duration::from_secs(2);
+
+// The state of the playback at one poll of `--follow`, which is also the payload of every event
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct StatusSnapshot {
+    pub uri: Option<String>,
+    pub title: Option<String>,
+    // The artists of a track, or the show of an episode
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub duration_ms: Option<u64>,
+    pub progress_ms: Option<u64>,
+    pub is_playing: bool,
+    pub shuffle: bool,
+    pub repeat: &'static str,
+    pub device_id: Option<String>,
+    pub device_name: String,
+    pub volume_percent: Option<u32>,
+}
+
+impl StatusSnapshot {
+    pub fn new(context: &CurrentPlaybackContext) -> Self {
+        let (uri, title, artist, album, duration) = match &context.item {
+            Some(PlayableItem::Track(track)) => (
+                track.id.as_ref().map(|id| id.uri()),
+                Some(track.name.clone()),
+                Some(create_artist_string(&track.artists)),
+                Some(track.album.name.clone()),
+                Some(track.duration),
+            ),
+            Some(PlayableItem::Episode(episode)) => (
+                Some(episode.id.uri()),
+                Some(episode.name.clone()),
+                Some(episode.show.name.clone()),
+                None,
+                Some(episode.duration),
+            ),
+            None => (None, None, None, None, None),
+        };
+        let to_ms = |duration: chrono::Duration| duration.num_milliseconds().max(0) as u64;
+
+        StatusSnapshot {
+            uri,
+            title,
+            artist,
+            album,
+            duration_ms: duration.map(to_ms),
+            progress_ms: context.progress.map(to_ms),
+            is_playing: context.is_playing,
+            shuffle: context.shuffle_state,
+            repeat: context.repeat_state.into(),
+            device_id: context.device.id.clone(),
+            device_name: context.device.name.clone(),
+            volume_percent: context.device.volume_percent,
+        }
+    }
+}
+
+impl StatusSnapshot {
+    // One line for people rather than scripts, which `--verbose` and errors print, e.g.
+    // "paused on Speaker at 0:30/3:00 of Song by Artist, shuffle off, repeat off"
+    pub fn summary(&self) -> String {
+        let state = if self.is_playing { "playing" } else { "paused" };
+        let mut summary = format!("{} on {}", state, self.device_name);
+        if let (Some(progress), Some(duration)) = (self.progress_ms, self.duration_ms) {
+            summary += &format!(
+                " at {}/{}",
+                millis_to_minutes(progress.into()),
+                millis_to_minutes(duration.into())
+            );
+        }
+        if let Some(title) = &self.title {
+            summary += &format!(" of {}", title);
+            if let Some(artist) = &self.artist {
+                summary += &format!(" by {}", artist);
+            }
+        }
+        let shuffle = if self.shuffle { "on" } else { "off" };
+        summary + &format!(", shuffle {}, repeat {}", shuffle, self.repeat)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlaybackEvent {
+    DeviceChanged,
+    TrackChanged,
+    Paused,
+    Resumed,
+    Seeked,
+    VolumeChanged,
+}
+
+impl PlaybackEvent {
+    // What `--follow` prints the status again for, leaving out seeks and volume changes
+    pub fn changes_what_is_playing(&self) -> bool {
+        !matches!(self, PlaybackEvent::Seeked | PlaybackEvent::VolumeChanged)
+    }
+}
+
+// What changed between two polls, `elapsed` apart. The first poll has nothing to compare with and
+// reports the track, so that consumers start out knowing what is playing.
+pub fn classify(
+    previous: Option<&StatusSnapshot>,
+    current: &StatusSnapshot,
+    elapsed: Duration,
+) -> Vec<PlaybackEvent> {
+    let Some(previous) = previous else {
+        return vec![PlaybackEvent::TrackChanged];
+    };
+
+    let mut events = vec![];
+    if previous.device_id != current.device_id || previous.device_name != current.device_name {
+        events.push(PlaybackEvent::DeviceChanged);
+    }
+    let is_same_item = previous.uri == current.uri;
+    if !is_same_item {
+        events.push(PlaybackEvent::TrackChanged);
+    }
+    match (previous.is_playing, current.is_playing) {
+        (true, false) => events.push(PlaybackEvent::Paused),
+        (false, true) => events.push(PlaybackEvent::Resumed),
+        _ => {}
+    }
+    if let (true, Some(before), Some(now)) =
+        (is_same_item, previous.progress_ms, current.progress_ms)
+    {
+        // Playing for any part of the time between the polls moves the progress by up to the
+        // time elapsed
+        let is_moving = previous.is_playing || current.is_playing;
+        let moved = if is_moving { elapsed.as_millis() } else { 0 };
+        let latest = before + moved as u64;
+        let tolerance = SEEK_TOLERANCE.as_millis() as u64;
+        if now + tolerance < before || now > latest + tolerance {
+            events.push(PlaybackEvent::Seeked);
+        }
+    }
+    if previous.volume_percent != current.volume_percent {
+        events.push(PlaybackEvent::VolumeChanged);
+    }
+    events
+}
+
+#[derive(Serialize)]
+struct EventLine<'a> {
+    event: PlaybackEvent,
+    status: &'a StatusSnapshot,
+}
+
+// One line of `--events-json`
+pub fn event_line(event: PlaybackEvent, status: &StatusSnapshot) -> Result<String> {
+    Ok(serde_json::to_string(&EventLine { event, status })?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::clap::playback_subcommand;
+
+    const SECOND: Duration = Duration::from_secs(1);
+
+    fn snapshot() -> StatusSnapshot {
+        StatusSnapshot {
+            uri: Some(String::from("spotify:track:4iV5W9uYEdYUVa79Axb7Rh")),
+            title: Some(String::from("Song")),
+            artist: Some(String::from("Artist")),
+            album: Some(String::from("Album")),
+            duration_ms: Some(180_000),
+            progress_ms: Some(30_000),
+            is_playing: true,
+            shuffle: false,
+            repeat: "off",
+            device_id: Some(String::from("speaker")),
+            device_name: String::from("Speaker"),
+            volume_percent: Some(50),
+        }
+    }
+
+    // Polled a second later, with the progress moved on by that second
+    fn a_second_later(snapshot: &StatusSnapshot) -> StatusSnapshot {
+        StatusSnapshot {
+            progress_ms: snapshot.progress_ms.map(|progress| progress + 1000),
+            ..snapshot.clone()
+        }
+    }
+
+    #[test]
+    fn the_first_poll_reports_the_track() {
+        assert_eq!(
+            classify(None, &snapshot(), Duration::ZERO),
+            [PlaybackEvent::TrackChanged]
+        );
+    }
+
+    #[test]
+    fn nothing_changed() {
+        let previous = snapshot();
+        assert!(classify(Some(&previous), &a_second_later(&previous), SECOND).is_empty());
+
+        // Paused, so the progress stays put
+        let previous = StatusSnapshot {
+            is_playing: false,
+            ..snapshot()
+        };
+        assert!(classify(Some(&previous), &previous, SECOND).is_empty());
+
+        // Polls that are a little late or early aren't seeks
+        let mut current = a_second_later(&snapshot());
+        current.progress_ms = Some(32_500);
+        assert!(classify(Some(&snapshot()), &current, SECOND).is_empty());
+        current.progress_ms = Some(29_000);
+        assert!(classify(Some(&snapshot()), &current, SECOND).is_empty());
+    }
+
+    #[test]
+    fn track_changed() {
+        let current = StatusSnapshot {
+            uri: Some(String::from("spotify:track:0eGsygTp906u18L0Oimnem")),
+            title: Some(String::from("Another song")),
+            progress_ms: Some(0),
+            ..snapshot()
+        };
+        // Starting the next track from the beginning isn't a seek
+        assert_eq!(
+            classify(Some(&snapshot()), &current, SECOND),
+            [PlaybackEvent::TrackChanged]
+        );
+
+        // Nothing playing anymore is a change of track too
+        let current = StatusSnapshot {
+            uri: None,
+            progress_ms: None,
+            ..snapshot()
+        };
+        assert_eq!(
+            classify(Some(&snapshot()), &current, SECOND),
+            [PlaybackEvent::TrackChanged]
+        );
+    }
+
+    #[test]
+    fn paused_and_resumed() {
+        let paused = StatusSnapshot {
+            is_playing: false,
+            progress_ms: Some(30_400),
+            ..snapshot()
+        };
+        assert_eq!(
+            classify(Some(&snapshot()), &paused, SECOND),
+            [PlaybackEvent::Paused]
+        );
+
+        let resumed = StatusSnapshot {
+            is_playing: true,
+            progress_ms: Some(31_000),
+            ..paused.clone()
+        };
+        assert_eq!(
+            classify(Some(&paused), &resumed, SECOND),
+            [PlaybackEvent::Resumed]
+        );
+    }
+
+    #[test]
+    fn seeked() {
+        for progress_ms in [90_000, 0] {
+            let current = StatusSnapshot {
+                progress_ms: Some(progress_ms),
+                ..snapshot()
+            };
+            assert_eq!(
+                classify(Some(&snapshot()), &current, SECOND),
+                [PlaybackEvent::Seeked],
+                "{}",
+                progress_ms
+            );
+        }
+
+        // While paused the progress doesn't move at all
+        let paused = StatusSnapshot {
+            is_playing: false,
+            ..snapshot()
+        };
+        let current = StatusSnapshot {
+            progress_ms: Some(40_000),
+            ..paused.clone()
+        };
+        assert_eq!(
+            classify(Some(&paused), &current, SECOND),
+            [PlaybackEvent::Seeked]
+        );
+        // But it does over a long gap between polls while playing
+        let current = StatusSnapshot {
+            progress_ms: Some(40_000),
+            ..snapshot()
+        };
+        assert!(classify(Some(&snapshot()), &current, 10 * SECOND).is_empty());
+    }
+
+    #[test]
+    fn volume_and_device_changed() {
+        let current = StatusSnapshot {
+            volume_percent: Some(70),
+            ..a_second_later(&snapshot())
+        };
+        assert_eq!(
+            classify(Some(&snapshot()), &current, SECOND),
+            [PlaybackEvent::VolumeChanged]
+        );
+
+        let current = StatusSnapshot {
+            device_id: Some(String::from("phone")),
+            device_name: String::from("Phone"),
+            volume_percent: None,
+            ..a_second_later(&snapshot())
+        };
+        assert_eq!(
+            classify(Some(&snapshot()), &current, SECOND),
+            [PlaybackEvent::DeviceChanged, PlaybackEvent::VolumeChanged]
+        );
+    }
+
+    #[test]
+    fn several_changes_at_once() {
+        let current = StatusSnapshot {
+            uri: None,
+            is_playing: false,
+            device_id: None,
+            device_name: String::from("Laptop"),
+            volume_percent: Some(10),
+            ..snapshot()
+        };
+        assert_eq!(
+            classify(Some(&snapshot()), &current, SECOND),
+            [
+                PlaybackEvent::DeviceChanged,
+                PlaybackEvent::TrackChanged,
+                PlaybackEvent::Paused,
+                PlaybackEvent::VolumeChanged,
+            ]
+        );
+    }
+
+    #[test]
+    fn only_some_changes_print_the_status_again() {
+        let printed = [
+            PlaybackEvent::DeviceChanged,
+            PlaybackEvent::TrackChanged,
+            PlaybackEvent::Paused,
+            PlaybackEvent::Resumed,
+        ];
+        for event in printed {
+            assert!(event.changes_what_is_playing(), "{:?}", event);
+        }
+        for event in [PlaybackEvent::Seeked, PlaybackEvent::VolumeChanged] {
+            assert!(!event.changes_what_is_playing(), "{:?}", event);
+        }
+    }
+
+    #[test]
+    fn formats_event_lines() {
+        let line = event_line(PlaybackEvent::TrackChanged, &snapshot()).unwrap();
+        assert!(!line.contains('\n'));
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value["event"], "track_changed");
+        assert_eq!(value["status"]["title"], "Song");
+        assert_eq!(value["status"]["progress_ms"], 30_000);
+        assert_eq!(value["status"]["repeat"], "off");
+        assert_eq!(value["status"]["volume_percent"], 50);
+    }
+
+    #[test]
+    fn summarizes_the_playback() {
+        assert_eq!(
+            snapshot().summary(),
+            "playing on Speaker at 0:30/3:00 of Song by Artist, shuffle off, repeat off"
+        );
+        let paused = StatusSnapshot {
+            uri: None,
+            title: None,
+            artist: None,
+            album: None,
+            duration_ms: None,
+            progress_ms: None,
+            is_playing: false,
+            shuffle: true,
+            repeat: "context",
+            ..snapshot()
+        };
+        assert_eq!(
+            paused.summary(),
+            "paused on Speaker, shuffle on, repeat context"
+        );
+    }
+
+    #[test]
+    fn follow_options() {
+        let parse = |args: &[&str]| {
+            playback_subcommand()
+                .try_get_matches_from(std::iter::once("playback").chain(args.iter().copied()))
+        };
+        let matches = parse(&["--follow", "--events-json"]).unwrap();
+        assert!(matches.get_flag("follow"));
+        assert!(matches.get_flag("events-json"));
+        assert!(!parse(&["--follow"]).unwrap().get_flag("events-json"));
+        assert!(parse(&["--events-json"]).is_err());
+
+        let matches = parse(&["--wait", "--interval", "5", "--fail-fast"]).unwrap();
+        assert!(matches.get_flag("follow"));
+        assert!(matches.get_flag("fail-fast"));
+        assert_eq!(matches.get_one::<u64>("interval"), Some(&5));
+        assert_eq!(
+            parse(&["--follow"]).unwrap().get_one::<u64>("interval"),
+            Some(&2)
+        );
+        for args in [
+            &["--interval", "5"][..],
+            &["--fail-fast"],
+            &["--follow", "--interval", "0"],
+        ] {
+            assert!(parse(args).is_err(), "{:?}", args);
+        }
+    }
+}
@@ -1,11 +1,39 @@
 use super::{
+    events::StatusSnapshot,
+    pick::StdinPrompt,
     util::{Flag, JumpDirection, Type},
-    CliApp,
+    CliApp, FollowOptions,
 };
+use crate::control::ControlCommand;
 use crate::network::{IoEvent, Network};
 use crate::user_config::UserConfig;
 use anyhow::{anyhow, Result};
 use clap::ArgMatches;
+use std::time::Duration;
+
+// Number of liked songs listed when neither `--limit` nor `--all` is given
+const DEFAULT_LIKED_LIMIT: u32 = 20;
+
+fn parse_liked_limit(max: &str) -> Result<u32> {
+    match max.parse::<u32>() {
+        Ok(num) if num > 0 => Ok(num),
+        _ => Err(anyhow!("limit must be a positive number")),
+    }
+}
+
+// Errors say what the playback was before the command, which often explains them
+fn with_playback<T>(result: Result<T>, before: Option<&StatusSnapshot>) -> Result<T> {
+    result.map_err(|error| match before {
+        Some(snapshot) => anyhow!("{}; playback was {}", error, snapshot.summary()),
+        None => anyhow!("{}; nothing was playing", error),
+    })
+}
+
+// A line of `--verbose`, on stderr so that it never mixes with the output
+fn verbose_line(when: &str, snapshot: Option<&StatusSnapshot>) -> String {
+    let summary = snapshot.map_or_else(|| String::from("nothing playing"), |s| s.summary());
+    format!("playback {}: {}", when, summary)
+}
 
 // Handle the different subcommands
 pub async fn handle_matches(
@@ -14,13 +42,31 @@ pub async fn handle_matches(
     net: Network,
     config: UserConfig,
 ) -> Result<String> {
-    let mut cli = CliApp::new(net, config);
+    let mut cli = CliApp::new(net, config, matches.get_flag("dry-run"));
+    let verbose = matches.get_flag("verbose");
 
-    cli.net.handle_network_event(IoEvent::GetDevices).await;
+    cli.net
+        .handle_network_event(IoEvent::GetDevices { silent: false })
+        .await;
     cli.net
         .handle_network_event(IoEvent::GetCurrentPlayback)
         .await;
 
+    let before = cli.snapshot().await;
+    if verbose {
+        eprintln!("{}", verbose_line("before", before.as_ref()));
+    }
+    let result = run_command(&mut cli, matches, cmd).await;
+    if verbose {
+        cli.net
+            .handle_network_event(IoEvent::GetCurrentPlayback)
+            .await;
+        eprintln!("{}", verbose_line("after", cli.snapshot().await.as_ref()));
+    }
+    with_playback(result, before.as_ref())
+}
+
+async fn run_command(cli: &mut CliApp, matches: &ArgMatches, cmd: String) -> Result<String> {
     let devices_list = match &cli.net.app.read().await.devices {
         Some(p) => p
             .devices
@@ -70,11 +116,9 @@ pub async fn handle_matches(
                     cli.mark(f).await?;
                 }
             }
-            if matches.get_raw_occurrences("jumps").is_some() {
-                let (direction, amount) = JumpDirection::from_matches(matches);
-                for _ in 0..amount {
-                    cli.jump(&direction).await;
-                }
+            if let Some((direction, amount)) = JumpDirection::from_matches(matches) {
+                cli.jump(&direction, amount, matches.get_flag("force"))
+                    .await;
             }
             if let Ok(Some(vol)) = matches.try_get_one::<String>("volume") {
                 cli.volume(vol.to_string()).await?;
@@ -83,22 +127,48 @@ pub async fn handle_matches(
                 cli.seek(secs.to_string()).await?;
             }
 
+            // Goes on until interrupted, and there's no last status to print after that
+            if matches.get_flag("follow") {
+                let options = FollowOptions {
+                    interval: Duration::from_secs(*matches.get_one::<u64>("interval").unwrap()),
+                    events_json: matches.get_flag("events-json"),
+                    fail_fast: matches.get_flag("fail-fast"),
+                };
+                cli.follow_status(format.to_string(), options).await?;
+                return Ok(String::new());
+            }
+
             // Print out the status if no errors were found
             cli.get_status(format.to_string()).await
         }
         "play" => {
-            let queue = matches.get_raw_occurrences("queue").is_some();
-            let random = matches.get_raw_occurrences("random").is_some();
+            let queue = matches.get_flag("queue");
+            let random = matches.get_flag("random");
+            let radio = matches.get_flag("radio");
             let format = matches.try_get_one::<String>("format").unwrap().unwrap();
 
-            if let Ok(Some(uri)) = matches.try_get_one::<String>("uri") {
-                cli.play_uri(uri.to_string(), queue, random).await;
+            let started = if let Ok(Some(uris)) = matches.try_get_many::<String>("uri") {
+                let uris = uris.cloned().collect::<Vec<_>>();
+                cli.play_uris(&uris, queue, random).await?
+            } else if matches.get_flag("pick-playlist") {
+                if let Some(max) = matches.get_one::<String>("limit") {
+                    cli.update_query_limits(max.to_string()).await?;
+                }
+                let filter = matches.get_one::<String>("filter").map(String::as_str);
+                let uri = cli.pick_playlist(filter, &mut StdinPrompt).await?;
+                cli.play_uri(uri, false, random).await?
             } else if let Ok(Some(name)) = matches.try_get_one::<String>("name") {
                 let category = Type::play_from_matches(matches);
-                cli.play(name.to_string(), category, queue, random).await?;
-            }
+                cli.play(name.to_string(), category, queue, random, radio)
+                    .await?
+            } else {
+                None
+            };
 
-            cli.get_status(format.to_string()).await
+            if matches.get_flag("quiet") {
+                return Ok(String::new());
+            }
+            cli.status_after_play(started, format.to_string()).await
         }
         "list" => {
             let format = matches
@@ -107,6 +177,21 @@ pub async fn handle_matches(
                 .unwrap()
                 .to_string();
 
+            let category = Type::list_from_matches(matches);
+
+            // Liked songs are fetched page by page, so their limit isn't bound to the page size
+            if let Type::Liked = category {
+                let limit = if matches.get_flag("all") {
+                    None
+                } else {
+                    match matches.try_get_one::<String>("limit") {
+                        Ok(Some(max)) => Some(parse_liked_limit(max)?),
+                        _ => Some(DEFAULT_LIKED_LIMIT),
+                    }
+                };
+                return Ok(cli.list_liked(&format, limit).await);
+            }
+
             // Update the limits for the list and search functions
             // I think the small and big search limits are very confusing
             // so I just set them both to max, is this okay?
@@ -114,7 +199,6 @@ pub async fn handle_matches(
                 cli.update_query_limits(max.to_string()).await?;
             }
 
-            let category = Type::list_from_matches(matches);
             Ok(cli.list(category, &format).await)
         }
         "search" => {
@@ -144,15 +228,192 @@ pub async fn handle_matches(
                 )
                 .await)
         }
+        "follow" => {
+            let show = matches.try_get_one::<String>("show").unwrap().unwrap();
+            cli.follow_show(show, true).await
+        }
+        "unfollow" => {
+            let show = matches.try_get_one::<String>("show").unwrap().unwrap();
+            cli.follow_show(show, false).await
+        }
+        "ctl" => {
+            let format = matches.try_get_one::<String>("format").unwrap().unwrap();
+            let command = ControlCommand::from_args(
+                matches.get_one::<String>("command").unwrap(),
+                matches.get_one::<String>("value").map(String::as_str),
+            )?;
+            cli.control(command).await?;
+            cli.get_status(format.to_string()).await
+        }
         // Clap enforces that one of the things above is specified
         _ => unreachable!(),
     };
 
     // Check if there was an error
     let api_error = cli.net.app.read().await.api_error.clone();
-    if api_error.is_empty() {
-        output
-    } else {
-        Err(anyhow!("{}", api_error))
+    if !api_error.is_empty() {
+        return Err(anyhow!("{}", api_error));
+    }
+
+    // Warnings are about the status, so they follow it
+    let output = output?;
+    if cli.warnings.is_empty() {
+        return Ok(output);
+    }
+    if !output.is_empty() {
+        println!("{}", output);
+    }
+    for warning in &cli.warnings {
+        eprintln!("warning: {}", warning);
+    }
+    Ok(String::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::clap::play_subcommand;
+    use crate::network::mock_api;
+
+    fn snapshot() -> StatusSnapshot {
+        StatusSnapshot {
+            uri: None,
+            title: None,
+            artist: None,
+            album: None,
+            duration_ms: None,
+            progress_ms: None,
+            is_playing: false,
+            shuffle: false,
+            repeat: "off",
+            device_id: Some(String::from("speaker")),
+            device_name: String::from("Speaker"),
+            volume_percent: Some(50),
+        }
+    }
+
+    #[test]
+    fn errors_carry_the_playback() {
+        let failed = || Err::<(), _>(anyhow!("failed to seek"));
+        assert_eq!(
+            with_playback(failed(), Some(&snapshot()))
+                .unwrap_err()
+                .to_string(),
+            "failed to seek; playback was paused on Speaker, shuffle off, repeat off"
+        );
+        assert_eq!(
+            with_playback(failed(), None).unwrap_err().to_string(),
+            "failed to seek; nothing was playing"
+        );
+        assert!(with_playback(Ok(()), Some(&snapshot())).is_ok());
+    }
+
+    #[test]
+    fn verbose_lines() {
+        assert_eq!(
+            verbose_line("before", Some(&snapshot())),
+            "playback before: paused on Speaker, shuffle off, repeat off"
+        );
+        assert_eq!(
+            verbose_line("after", None),
+            "playback after: nothing playing"
+        );
+    }
+
+    const TRACK: &str = "spotify:track:4iV5W9uYEdYUVa79Axb7Rh";
+
+    // A CLI on a Spotify that has the track playing, and answers playing it with `play_status`
+    async fn play_cli(play_status: u16) -> (CliApp, mock_api::Requests) {
+        let (mut net, requests) = mock_api::network_with_status(move |path| {
+            if path.starts_with("/me/player/play") {
+                let error = serde_json::json!({
+                    "error": {
+                        "status": play_status,
+                        "message": "Player command failed: Restriction violated",
+                        "reason": "UNKNOWN",
+                    }
+                });
+                return Some((play_status, error.to_string()));
+            }
+            if path.starts_with("/me/tracks/contains") {
+                return Some((200, String::from("[false]")));
+            }
+            if path.starts_with("/me/tracks?") {
+                let page = serde_json::json!({
+                    "href": "", "items": [], "limit": 20, "next": null, "offset": 0,
+                    "previous": null, "total": 0,
+                });
+                return Some((200, page.to_string()));
+            }
+            if !path.starts_with("/me/player?") {
+                return None;
+            }
+            let mut playback = mock_api::playback();
+            playback["item"] = serde_json::json!({
+                "album": { "artists": [], "external_urls": {}, "images": [], "name": "Album" },
+                "artists": [{ "external_urls": {}, "name": "Artist" }],
+                "disc_number": 1,
+                "duration_ms": 180_000,
+                "explicit": false,
+                "external_ids": {},
+                "external_urls": {},
+                "id": "4iV5W9uYEdYUVa79Axb7Rh",
+                "is_local": false,
+                "name": "Track",
+                "popularity": 0,
+                "track_number": 1,
+                "type": "track",
+            });
+            Some((200, playback.to_string()))
+        })
+        .await;
+        net.client_config.device_id = Some(String::from("active"));
+        let mut cli = CliApp::new(net, UserConfig::new(), false);
+        cli.convergence_timeout = Duration::from_millis(600);
+        (cli, requests)
+    }
+
+    fn playback_polls(requests: &mock_api::Requests) -> usize {
+        requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|path| path.starts_with("/me/player?"))
+            .count()
+    }
+
+    async fn play(cli: &mut CliApp, args: &[&str]) -> Result<String> {
+        let matches = play_subcommand()
+            .try_get_matches_from(["play", "-u", TRACK].iter().chain(args).copied())
+            .unwrap();
+        run_command(cli, &matches, String::from("play")).await
+    }
+
+    #[tokio::test]
+    async fn prints_the_status_once_the_track_has_started() {
+        let (mut cli, requests) = play_cli(200).await;
+        let status = play(&mut cli, &["--format", "%t - %a"]).await.unwrap();
+        assert_eq!(status, "Track - Artist");
+        // The first look at the playback already has the track
+        assert_eq!(playback_polls(&requests), 1);
+        assert!(cli.warnings.is_empty());
+    }
+
+    #[tokio::test]
+    async fn quiet_plays_without_looking_at_the_playback() {
+        let (mut cli, requests) = play_cli(200).await;
+        assert_eq!(play(&mut cli, &["--quiet"]).await.unwrap(), "");
+        assert_eq!(playback_polls(&requests), 0);
+        assert!(play_subcommand()
+            .try_get_matches_from(["play", "-u", TRACK, "--quiet", "--format", "%t"])
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn a_failed_play_is_reported_instead_of_the_status() {
+        let (mut cli, requests) = play_cli(403).await;
+        let error = play(&mut cli, &[]).await.unwrap_err();
+        assert!(error.to_string().contains("403"), "{}", error);
+        assert_eq!(playback_polls(&requests), 0);
     }
 }
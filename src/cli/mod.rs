@@ -1,8 +1,14 @@
 mod clap;
 mod cli_app;
+mod dry_run;
+mod events;
 mod handle;
+mod pick;
 mod util;
 
-pub use self::clap::{list_subcommand, play_subcommand, playback_subcommand, search_subcommand};
-use cli_app::CliApp;
+pub use self::clap::{
+    ctl_subcommand, follow_subcommand, list_subcommand, play_subcommand, playback_subcommand,
+    search_subcommand, unfollow_subcommand,
+};
+use cli_app::{CliApp, FollowOptions};
 pub use handle::handle_matches;
@@ -0,0 +1,125 @@
+use anyhow::Result;
+use rspotify::model::playlist::SimplifiedPlaylist;
+use std::io::{self, BufRead, IsTerminal, Write};
+
+// Where a choice is read from, so that tests can stand in for the terminal
+pub trait Prompt {
+    // Whether someone is there to answer, otherwise reading would wait on nothing
+    fn is_interactive(&self) -> bool;
+    fn show(&mut self, text: &str);
+    // None once the input has ended
+    fn read_line(&mut self) -> Result<Option<String>>;
+}
+
+// Lists and asks on stderr, which leaves stdout to the status printed after playing
+pub struct StdinPrompt;
+
+impl Prompt for StdinPrompt {
+    fn is_interactive(&self) -> bool {
+        io::stdin().is_terminal()
+    }
+
+    fn show(&mut self, text: &str) {
+        eprint!("{}", text);
+        let _ = io::stderr().flush();
+    }
+
+    fn read_line(&mut self) -> Result<Option<String>> {
+        let mut line = String::new();
+        if io::stdin().lock().read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        Ok(Some(line))
+    }
+}
+
+// The playlists with `filter` in their name, ignoring case
+pub fn filter_playlists<'a>(
+    playlists: &'a [SimplifiedPlaylist],
+    filter: Option<&str>,
+) -> Vec<&'a SimplifiedPlaylist> {
+    let filter = filter.map(str::to_lowercase);
+    playlists
+        .iter()
+        .filter(|playlist| {
+            filter
+                .as_ref()
+                .is_none_or(|filter| playlist.name.to_lowercase().contains(filter))
+        })
+        .collect()
+}
+
+// The index of the item picked out of `count`, numbered from 1, or why the input isn't one
+pub fn parse_selection(input: &str, count: usize) -> Result<usize, String> {
+    let input = input.trim();
+    match input.parse::<usize>() {
+        Ok(number) if (1..=count).contains(&number) => Ok(number - 1),
+        Ok(_) => Err(format!("Pick a number from 1 to {}", count)),
+        Err(_) => Err(format!("'{}' isn't a number", input)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn playlist(name: &str) -> SimplifiedPlaylist {
+        serde_json::from_value(json!({
+            "collaborative": false,
+            "external_urls": {},
+            "href": "https://api.spotify.com/v1/playlists/37i9dQZF1DXcBWIGoYBM5M",
+            "id": "37i9dQZF1DXcBWIGoYBM5M",
+            "images": [],
+            "name": name,
+            "owner": {
+                "external_urls": {},
+                "href": "https://api.spotify.com/v1/users/listener",
+                "id": "listener"
+            },
+            "snapshot_id": "snapshot",
+            "tracks": { "href": "", "total": 10 }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn filters_playlists_by_name() {
+        let playlists = [
+            playlist("Morning Jazz"),
+            playlist("Workout"),
+            playlist("jazz classics"),
+        ];
+        let names = |filter| {
+            filter_playlists(&playlists, filter)
+                .iter()
+                .map(|playlist| playlist.name.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(None).len(), 3);
+        assert_eq!(names(Some("JAZZ")), ["Morning Jazz", "jazz classics"]);
+        assert!(names(Some("podcast")).is_empty());
+    }
+
+    #[test]
+    fn parses_selections() {
+        assert_eq!(parse_selection("1\n", 3), Ok(0));
+        assert_eq!(parse_selection("  3 ", 3), Ok(2));
+        assert_eq!(
+            parse_selection("0", 3),
+            Err(String::from("Pick a number from 1 to 3"))
+        );
+        assert_eq!(
+            parse_selection("4", 3),
+            Err(String::from("Pick a number from 1 to 3"))
+        );
+        assert_eq!(
+            parse_selection("two", 3),
+            Err(String::from("'two' isn't a number"))
+        );
+        assert_eq!(
+            parse_selection("-1", 3),
+            Err(String::from("'-1' isn't a number"))
+        );
+    }
+}
@@ -1,10 +1,12 @@
 use clap::ArgMatches;
 use rspotify::model::{
     album::SimplifiedAlbum, artist::FullArtist, artist::SimplifiedArtist, enums::RepeatState,
-    idtypes::Id, playlist::SimplifiedPlaylist, show::FullEpisode, show::SimplifiedShow,
-    track::FullTrack,
+    enums::SearchType, idtypes::Id, playlist::SimplifiedPlaylist, show::FullEpisode,
+    show::SimplifiedShow, track::FullTrack,
 };
 
+use crate::app::next_repeat_state;
+use crate::network::IoEvent;
 use crate::user_config::UserConfig;
 
 // Possible types to list or search
@@ -21,15 +23,15 @@ pub enum Type {
 
 impl Type {
     pub fn play_from_matches(m: &ArgMatches) -> Self {
-        if m.contains_id("playlist") {
+        if m.get_flag("playlist") {
             Self::Playlist
-        } else if m.contains_id("track") {
+        } else if m.get_flag("track") {
             Self::Track
-        } else if m.contains_id("artist") {
+        } else if m.get_flag("artist") {
             Self::Artist
-        } else if m.contains_id("album") {
+        } else if m.get_flag("album") {
             Self::Album
-        } else if m.contains_id("show") {
+        } else if m.get_flag("show") {
             Self::Show
         }
         // Enforced by clap
@@ -56,13 +58,27 @@ impl Type {
         }
     }
 
+    // The type of search results to look through for this type, if there is one
+    pub fn search_type(&self) -> Option<SearchType> {
+        match self {
+            Self::Album => Some(SearchType::Album),
+            Self::Artist => Some(SearchType::Artist),
+            Self::Playlist => Some(SearchType::Playlist),
+            Self::Show => Some(SearchType::Show),
+            Self::Track => Some(SearchType::Track),
+            Self::Device | Self::Liked => None,
+        }
+    }
+
     pub fn list_from_matches(m: &ArgMatches) -> Self {
-        if m.contains_id("playlists") {
+        if m.get_flag("playlists") {
             Self::Playlist
-        } else if m.contains_id("devices") {
+        } else if m.get_flag("devices") {
             Self::Device
-        } else if m.contains_id("liked") {
+        } else if m.get_flag("liked") {
             Self::Liked
+        } else if m.get_flag("podcasts") {
+            Self::Show
         }
         // Enforced by clap
         else {
@@ -80,8 +96,10 @@ pub enum Flag {
     // * User chooses like -> Flag::Like(true)
     // * User chooses dislike -> Flag::Like(false)
     Like(bool),
-    Shuffle,
-    Repeat,
+    // Toggles when None, otherwise switches to the given state only if it isn't already
+    Shuffle(Option<bool>),
+    // Cycles when None, otherwise switches to the given mode only if it isn't already
+    Repeat(Option<RepeatState>),
 }
 
 impl Flag {
@@ -96,16 +114,54 @@ impl Flag {
             flags.push(Self::Like(false));
         }
 
+        // Only one of each, enforced by clap. `--shuffle` and `--repeat` without a value toggle
+        // and cycle.
         if m.contains_id("shuffle") {
-            flags.push(Self::Shuffle);
+            let state = m.get_one::<String>("shuffle");
+            flags.push(Self::Shuffle(state.map(|state| state == "on")));
+        } else if m.get_flag("shuffle-on") {
+            flags.push(Self::Shuffle(Some(true)));
+        } else if m.get_flag("shuffle-off") {
+            flags.push(Self::Shuffle(Some(false)));
         }
         if m.contains_id("repeat") {
-            flags.push(Self::Repeat);
+            let mode = m.get_one::<String>("repeat");
+            flags.push(Self::Repeat(mode.map(|mode| parse_repeat_mode(mode))));
+        } else if let Some(mode) = m.get_one::<String>("repeat-mode") {
+            flags.push(Self::Repeat(Some(parse_repeat_mode(mode))));
         }
         flags
     }
 }
 
+// One of the values accepted by `--repeat` and `--repeat-mode`
+fn parse_repeat_mode(mode: &str) -> RepeatState {
+    match mode {
+        "off" => RepeatState::Off,
+        "context" => RepeatState::Context,
+        "track" => RepeatState::Track,
+        // Enforced by clap
+        _ => unreachable!(),
+    }
+}
+
+// What to send to get shuffle from `current` to `wanted`, nothing when it is already there
+pub fn shuffle_event(current: bool, wanted: Option<bool>) -> Option<IoEvent<'static>> {
+    match wanted {
+        Some(wanted) if wanted == current => None,
+        _ => Some(IoEvent::ToggleShuffle),
+    }
+}
+
+// What to send to get repeat from `current` to `wanted`, nothing when it is already there
+pub fn repeat_event(current: RepeatState, wanted: Option<RepeatState>) -> Option<IoEvent<'static>> {
+    let state = wanted.unwrap_or_else(|| next_repeat_state(current));
+    if state == current {
+        return None;
+    }
+    Some(IoEvent::Repeat { state })
+}
+
 // Possible directions to jump to
 pub enum JumpDirection {
     Next,
@@ -113,20 +169,13 @@ pub enum JumpDirection {
 }
 
 impl JumpDirection {
-    pub fn from_matches(m: &ArgMatches) -> (Self, u64) {
-        if m.contains_id("next") {
-            (
-                Self::Next,
-                m.get_raw_occurrences("next").unwrap().count() as u64,
-            )
-        } else if m.contains_id("previous") {
-            (
-                Self::Previous,
-                m.get_raw_occurrences("previous").unwrap().count() as u64,
-            )
-        // Enforced by clap
-        } else {
-            unreachable!()
+    // The direction and how many times to jump, None without --next or --previous
+    pub fn from_matches(m: &ArgMatches) -> Option<(Self, u64)> {
+        match (m.get_count("next"), m.get_count("previous")) {
+            (0, 0) => None,
+            (next, 0) => Some((Self::Next, next as u64)),
+            // Only one of them, enforced by clap
+            (_, previous) => Some((Self::Previous, previous as u64)),
         }
     }
 }
@@ -156,11 +205,22 @@ pub enum Format {
     Show(String),
     Uri(String),
     Device(String),
+    // For scripts, which `--transfer` takes as well as the name
+    DeviceId(String),
+    // Whether the device is the one playing
+    Active(bool),
     Volume(u32),
     // Current position, duration
     Position((u32, u32)),
+    // How much of the item has been played, from 0 to 100
+    Percent(u32),
+    // The name of the playlist, album, artist or show playing from
+    Context(String),
     // This is a bit long, should it be splitted up?
     Flags((RepeatState, bool, bool)),
+    // Words rather than icons, for scripts
+    RepeatMode(RepeatState),
+    Shuffle(bool),
     Playing(bool),
 }
 
@@ -172,6 +232,19 @@ pub fn join_artists(a: Vec<SimplifiedArtist>) -> String {
 }
 
 impl Format {
+    // Where playback is in an item, as the position and the percentage played
+    pub fn progress(progress_ms: u32, duration_ms: u32) -> Vec<Self> {
+        let percent = if duration_ms == 0 {
+            0
+        } else {
+            crate::ui::util::get_track_progress_percentage(progress_ms as u128, duration_ms)
+        };
+        vec![
+            Self::Position((progress_ms, duration_ms)),
+            Self::Percent(percent.into()),
+        ]
+    }
+
     fn try_append_uri(id: Option<impl Id>, mut items: Vec<Self>) -> Vec<Self> {
         if let Some(id) = id {
             items.push(Self::Uri(id.uri()));
@@ -231,9 +304,13 @@ impl Format {
             Self::Show(s) => s.clone(),
             Self::Uri(s) => s.clone(),
             Self::Device(s) => s.clone(),
+            Self::DeviceId(s) => s.clone(),
+            Self::Context(s) => s.clone(),
+            Self::Active(a) => String::from(if *a { "*" } else { "" }),
             // Because this match statements
             // needs to return a &String, I have to do it this way
             Self::Volume(s) => s.to_string(),
+            Self::Percent(p) => p.to_string(),
             Self::Position((curr, duration)) => {
                 crate::ui::util::display_track_progress(*curr as u128, *duration)
             }
@@ -263,6 +340,8 @@ impl Format {
                     .collect::<Vec<String>>()
                     .join(" ")
             }
+            Self::RepeatMode(r) => <&str>::from(*r).to_string(),
+            Self::Shuffle(s) => s.to_string(),
             Self::Playing(s) => {
                 if *s {
                     conf.behavior.playing_icon
@@ -282,10 +361,144 @@ impl Format {
             Self::Show(_) => "%h",
             Self::Uri(_) => "%u",
             Self::Device(_) => "%d",
+            Self::DeviceId(_) => "%i",
+            Self::Active(_) => "%A",
             Self::Volume(_) => "%v",
             Self::Position(_) => "%r",
+            Self::Percent(_) => "%P",
+            Self::Context(_) => "%c",
             Self::Flags(_) => "%f",
+            Self::RepeatMode(_) => "%R",
+            Self::Shuffle(_) => "%S",
             Self::Playing(_) => "%s",
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::clap::playback_subcommand;
+
+    fn flags(args: &[&str]) -> Vec<String> {
+        let matches = playback_subcommand()
+            .try_get_matches_from(std::iter::once("playback").chain(args.iter().copied()))
+            .unwrap();
+        Flag::from_matches(&matches)
+            .iter()
+            .map(|flag| match flag {
+                Flag::Like(like) => format!("like {}", like),
+                Flag::Shuffle(shuffle) => format!("shuffle {:?}", shuffle),
+                Flag::Repeat(repeat) => format!("repeat {:?}", repeat),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parses_flags() {
+        assert_eq!(
+            flags(&["--shuffle", "--repeat"]),
+            ["shuffle None", "repeat None"]
+        );
+        assert_eq!(
+            flags(&["--shuffle-off", "--repeat-mode", "track"]),
+            ["shuffle Some(false)", "repeat Some(Track)"]
+        );
+        assert_eq!(flags(&["--shuffle-on"]), ["shuffle Some(true)"]);
+        assert_eq!(
+            flags(&["--shuffle", "on", "--repeat", "context"]),
+            ["shuffle Some(true)", "repeat Some(Context)"]
+        );
+        assert_eq!(
+            flags(&["--repeat=off", "--shuffle=off"]),
+            ["shuffle Some(false)", "repeat Some(Off)"]
+        );
+        // A bare flag before another option still toggles
+        assert_eq!(flags(&["--repeat", "--volume", "10"]), ["repeat None"]);
+        assert!(flags(&[]).is_empty());
+
+        for args in [
+            &["--shuffle", "--shuffle-on"][..],
+            &["--repeat", "--repeat-mode", "off"],
+            &["--repeat-mode", "sometimes"],
+            &["--shuffle", "sometimes"],
+            &["--repeat", "always"],
+            &["--shuffle", "on", "--shuffle-off"],
+            &["--repeat", "track", "--repeat-mode", "off"],
+        ] {
+            assert!(playback_subcommand()
+                .try_get_matches_from(std::iter::once("playback").chain(args.iter().copied()))
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn explicit_states_that_already_match_are_no_ops() {
+        assert!(shuffle_event(true, Some(true)).is_none());
+        assert!(shuffle_event(false, Some(false)).is_none());
+        assert!(matches!(
+            shuffle_event(false, Some(true)),
+            Some(IoEvent::ToggleShuffle)
+        ));
+        assert!(matches!(
+            shuffle_event(true, None),
+            Some(IoEvent::ToggleShuffle)
+        ));
+
+        assert!(repeat_event(RepeatState::Track, Some(RepeatState::Track)).is_none());
+        assert!(matches!(
+            repeat_event(RepeatState::Off, Some(RepeatState::Track)),
+            Some(IoEvent::Repeat {
+                state: RepeatState::Track
+            })
+        ));
+        // Cycling always changes the mode
+        assert!(matches!(
+            repeat_event(RepeatState::Track, None),
+            Some(IoEvent::Repeat {
+                state: RepeatState::Off
+            })
+        ));
+    }
+
+    #[test]
+    fn formats_flags_as_words() {
+        let conf = UserConfig::new();
+        assert_eq!(
+            Format::RepeatMode(RepeatState::Context).inner(conf.clone()),
+            "context"
+        );
+        assert_eq!(
+            Format::RepeatMode(RepeatState::Off).inner(conf.clone()),
+            "off"
+        );
+        assert_eq!(Format::Shuffle(true).inner(conf), "true");
+    }
+
+    #[test]
+    fn formats_progress() {
+        let conf = UserConfig::new();
+        let progress = |progress_ms, duration_ms| {
+            Format::progress(progress_ms, duration_ms)
+                .iter()
+                .map(|format| {
+                    (
+                        format.get_placeholder().to_string(),
+                        format.inner(conf.clone()),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            progress(45_000, 180_000),
+            [
+                (String::from("%r"), String::from("0:45/3:00 (-2:15)")),
+                (String::from("%P"), String::from("25")),
+            ]
+        );
+        assert_eq!(progress(200_000, 180_000)[1].1, "100");
+        // Nothing to divide by
+        assert_eq!(progress(0, 0)[1].1, "0");
+    }
+}
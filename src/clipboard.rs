@@ -0,0 +1,279 @@
+use anyhow::{anyhow, Result};
+use arboard::Clipboard;
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+    sync::{
+        mpsc::{channel, Receiver, Sender},
+        Mutex,
+    },
+    thread,
+};
+
+pub trait ClipboardProvider: Send + Sync {
+    fn set_text(&mut self, text: &str) -> Result<()>;
+}
+
+impl ClipboardProvider for Clipboard {
+    fn set_text(&mut self, text: &str) -> Result<()> {
+        Clipboard::set_text(self, text).map_err(|e| anyhow!(e))
+    }
+}
+
+type ClipboardFactory = fn() -> Result<Box<dyn ClipboardProvider>>;
+
+fn system_clipboard() -> Result<Box<dyn ClipboardProvider>> {
+    Ok(Box::new(Clipboard::new()?))
+}
+
+// Where copied text ended up
+#[derive(Debug, PartialEq)]
+pub enum CopyOutcome {
+    Clipboard,
+    // Handed to the configured clipboard command, which reports failures later on
+    Command,
+    // Nothing could take the text, with the reason why
+    Unavailable(String),
+}
+
+// The system clipboard is not always available when the app starts (e.g. on Wayland sessions
+// where the clipboard provider starts late), so it is created lazily and re-created after it
+// fails, falling back to an external command such as `wl-copy` when one is configured.
+pub struct AppClipboard {
+    clipboard: Option<Box<dyn ClipboardProvider>>,
+    factory: ClipboardFactory,
+    // Clipboard commands finish in the background and send their result back here
+    command_sender: Sender<Result<()>>,
+    command_receiver: Mutex<Receiver<Result<()>>>,
+    pending_commands: usize,
+}
+
+impl Default for AppClipboard {
+    fn default() -> Self {
+        AppClipboard::new(system_clipboard)
+    }
+}
+
+impl AppClipboard {
+    pub fn new(factory: ClipboardFactory) -> Self {
+        let (command_sender, command_receiver) = channel();
+        AppClipboard {
+            clipboard: factory().ok(),
+            factory,
+            command_sender,
+            command_receiver: Mutex::new(command_receiver),
+            pending_commands: 0,
+        }
+    }
+
+    pub fn copy(&mut self, text: &str, command: Option<&str>) -> CopyOutcome {
+        if self.clipboard.is_none() {
+            self.clipboard = (self.factory)().ok();
+        }
+
+        let mut reason = String::from("no clipboard available");
+        if let Some(clipboard) = &mut self.clipboard {
+            match clipboard.set_text(text) {
+                Ok(()) => return CopyOutcome::Clipboard,
+                Err(e) => {
+                    // Try again with a fresh clipboard next time
+                    self.clipboard = None;
+                    reason = e.to_string();
+                }
+            }
+        }
+
+        match command {
+            Some(command) => match spawn_copy_command(command, text, self.command_sender.clone()) {
+                Ok(()) => {
+                    self.pending_commands += 1;
+                    CopyOutcome::Command
+                }
+                Err(e) => CopyOutcome::Unavailable(e.to_string()),
+            },
+            None => CopyOutcome::Unavailable(reason),
+        }
+    }
+
+    // Collect the errors of clipboard commands that have finished since the last call
+    pub fn command_errors(&mut self) -> Vec<anyhow::Error> {
+        let mut errors = vec![];
+        if self.pending_commands == 0 {
+            return errors;
+        }
+        if let Ok(receiver) = self.command_receiver.lock() {
+            for result in receiver.try_iter() {
+                self.pending_commands -= 1;
+                if let Err(e) = result {
+                    errors.push(e);
+                }
+            }
+        }
+        errors
+    }
+}
+
+// Spawn the command with the text on its stdin without waiting for it to exit, so that a slow
+// or hanging command can't block the UI
+fn spawn_copy_command(command: &str, text: &str, sender: Sender<Result<()>>) -> Result<()> {
+    let mut parts = command.split_whitespace();
+    let program = parts
+        .next()
+        .ok_or_else(|| anyhow!("clipboard_command is empty"))?;
+
+    let mut child = Command::new(program)
+        .args(parts)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| anyhow!("could not run \"{}\": {}", command, e))?;
+
+    let mut stdin = child.stdin.take();
+    let text = text.to_string();
+    let command = command.to_string();
+    thread::spawn(move || {
+        let result = (|| {
+            if let Some(mut stdin) = stdin.take() {
+                stdin.write_all(text.as_bytes())?;
+            }
+            let output = child.wait_with_output()?;
+            if output.status.success() {
+                Ok(())
+            } else {
+                Err(anyhow!(
+                    "\"{}\" failed ({}): {}",
+                    command,
+                    output.status,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ))
+            }
+        })();
+        // The receiver is gone if the app has already quit
+        let _ = sender.send(result);
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::{Duration, Instant};
+
+    struct WorkingClipboard;
+
+    impl ClipboardProvider for WorkingClipboard {
+        fn set_text(&mut self, _text: &str) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    struct BrokenClipboard;
+
+    impl ClipboardProvider for BrokenClipboard {
+        fn set_text(&mut self, _text: &str) -> Result<()> {
+            Err(anyhow!("clipboard went away"))
+        }
+    }
+
+    fn unavailable() -> Result<Box<dyn ClipboardProvider>> {
+        Err(anyhow!("no display"))
+    }
+
+    fn broken() -> Result<Box<dyn ClipboardProvider>> {
+        Ok(Box::new(BrokenClipboard))
+    }
+
+    fn wait_for_command_errors(clipboard: &mut AppClipboard) -> Vec<anyhow::Error> {
+        let started = Instant::now();
+        while clipboard.pending_commands > 0 && started.elapsed() < Duration::from_secs(5) {
+            thread::sleep(Duration::from_millis(10));
+            let errors = clipboard.command_errors();
+            if !errors.is_empty() {
+                return errors;
+            }
+        }
+        vec![]
+    }
+
+    #[test]
+    fn copies_to_the_clipboard_when_available() {
+        fn working() -> Result<Box<dyn ClipboardProvider>> {
+            Ok(Box::new(WorkingClipboard))
+        }
+        let mut clipboard = AppClipboard::new(working);
+
+        assert_eq!(clipboard.copy("uri", None), CopyOutcome::Clipboard);
+    }
+
+    #[test]
+    fn retries_creating_the_clipboard_when_copying() {
+        static ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
+        // Fails on startup, but is available by the time something is copied
+        fn late() -> Result<Box<dyn ClipboardProvider>> {
+            if ATTEMPTS.fetch_add(1, Ordering::SeqCst) == 0 {
+                Err(anyhow!("not started yet"))
+            } else {
+                Ok(Box::new(WorkingClipboard))
+            }
+        }
+        let mut clipboard = AppClipboard::new(late);
+        assert!(clipboard.clipboard.is_none());
+
+        assert_eq!(clipboard.copy("uri", None), CopyOutcome::Clipboard);
+        assert_eq!(ATTEMPTS.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn unavailable_without_a_command() {
+        let mut clipboard = AppClipboard::new(unavailable);
+
+        assert!(matches!(
+            clipboard.copy("uri", None),
+            CopyOutcome::Unavailable(_)
+        ));
+    }
+
+    #[test]
+    fn failing_clipboard_is_dropped_and_falls_back() {
+        let mut clipboard = AppClipboard::new(broken);
+
+        assert_eq!(
+            clipboard.copy("uri", None),
+            CopyOutcome::Unavailable(String::from("clipboard went away"))
+        );
+        assert!(clipboard.clipboard.is_none());
+    }
+
+    #[test]
+    fn missing_command_is_reported_immediately() {
+        let mut clipboard = AppClipboard::new(unavailable);
+
+        assert!(matches!(
+            clipboard.copy("uri", Some("spotify-tui-no-such-command")),
+            CopyOutcome::Unavailable(_)
+        ));
+        assert!(clipboard.copy("uri", Some("  ")) != CopyOutcome::Command);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn falls_back_to_the_command() {
+        let mut clipboard = AppClipboard::new(unavailable);
+
+        assert_eq!(clipboard.copy("uri", Some("cat")), CopyOutcome::Command);
+        assert!(wait_for_command_errors(&mut clipboard).is_empty());
+        assert_eq!(clipboard.pending_commands, 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn reports_failing_commands_later() {
+        let mut clipboard = AppClipboard::new(unavailable);
+
+        assert_eq!(clipboard.copy("uri", Some("false")), CopyOutcome::Command);
+        assert_eq!(wait_for_command_errors(&mut clipboard).len(), 1);
+    }
+}
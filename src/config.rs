@@ -1,17 +1,17 @@
 use super::banner::BANNER;
-use anyhow::{anyhow, Error, Result};
+use crate::paths::{
+    self, CLIENT_CONFIG_FILE, DEBUG_DUMP_DIR, HISTORY_FILE, LIBRARY_CACHE_FILE, STARTUP_CACHE_FILE,
+    TOKEN_CACHE_FILE, UPDATE_CHECK_FILE,
+};
+use anyhow::{Error, Result};
 use serde::{Deserialize, Serialize};
 use std::{
     fs,
     io::{stdin, Write},
-    path::{Path, PathBuf},
+    path::PathBuf,
 };
 
 const DEFAULT_PORT: u16 = 8888;
-const FILE_NAME: &str = "client.yml";
-const CONFIG_DIR: &str = ".config";
-const APP_CONFIG_DIR: &str = "spotify-tui";
-const TOKEN_CACHE_FILE: &str = ".spotify_token_cache.json";
 
 #[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct ClientConfig {
@@ -25,6 +25,11 @@ pub struct ClientConfig {
 pub struct ConfigPaths {
     pub config_file_path: PathBuf,
     pub token_cache_path: PathBuf,
+    pub history_path: PathBuf,
+    pub library_cache_path: PathBuf,
+    pub startup_cache_path: PathBuf,
+    pub update_check_path: PathBuf,
+    pub debug_dump_dir: PathBuf,
 }
 
 impl ClientConfig {
@@ -46,32 +51,16 @@ impl ClientConfig {
     }
 
     pub fn get_or_build_paths(&self) -> Result<ConfigPaths> {
-        match dirs::home_dir() {
-            Some(home) => {
-                let path = Path::new(&home);
-                let home_config_dir = path.join(CONFIG_DIR);
-                let app_config_dir = home_config_dir.join(APP_CONFIG_DIR);
-
-                if !home_config_dir.exists() {
-                    fs::create_dir(&home_config_dir)?;
-                }
-
-                if !app_config_dir.exists() {
-                    fs::create_dir(&app_config_dir)?;
-                }
-
-                let config_file_path = &app_config_dir.join(FILE_NAME);
-                let token_cache_path = &app_config_dir.join(TOKEN_CACHE_FILE);
-
-                let paths = ConfigPaths {
-                    config_file_path: config_file_path.to_path_buf(),
-                    token_cache_path: token_cache_path.to_path_buf(),
-                };
-
-                Ok(paths)
-            }
-            None => Err(anyhow!("No $HOME directory found for client config")),
-        }
+        let app_dirs = paths::app_dirs()?;
+        Ok(ConfigPaths {
+            config_file_path: app_dirs.config_dir.join(CLIENT_CONFIG_FILE),
+            token_cache_path: app_dirs.cache_dir.join(TOKEN_CACHE_FILE),
+            history_path: app_dirs.cache_dir.join(HISTORY_FILE),
+            library_cache_path: app_dirs.cache_dir.join(LIBRARY_CACHE_FILE),
+            startup_cache_path: app_dirs.cache_dir.join(STARTUP_CACHE_FILE),
+            update_check_path: app_dirs.cache_dir.join(UPDATE_CHECK_FILE),
+            debug_dump_dir: app_dirs.cache_dir.join(DEBUG_DUMP_DIR),
+        })
     }
 
     pub fn set_device_id(&mut self, device_id: String) -> Result<()> {
@@ -0,0 +1,406 @@
+use crate::app::App;
+use crate::network::{IoEvent, PlaybackOffset};
+use crate::notifications::DoNotDisturb;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local, TimeDelta};
+use rspotify::model::{context::CurrentPlaybackContext, PlayContextId, PlayableId};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use spotify_tui_util::{ParseFromUri, PlaybleItemExt};
+
+// The commands accepted over the control socket, one JSON object per line, e.g.
+// `{"command": "volume", "value": "+10"}`. Anything else is answered with an error.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "lowercase", deny_unknown_fields)]
+pub enum ControlCommand {
+    Toggle,
+    Next,
+    Previous,
+    // An absolute volume, or a change of it when prefixed with + or -
+    Volume { value: String },
+    // A position in seconds, or a jump forwards or backwards when prefixed with + or -
+    Seek { value: String },
+    Like,
+    Open { uri: String },
+    // on, off, or on for a while like 60m, 2h or 90s
+    Dnd { value: String },
+}
+
+pub const COMMANDS: [&str; 8] = [
+    "toggle", "next", "previous", "volume", "seek", "like", "open", "dnd",
+];
+
+impl ControlCommand {
+    pub fn from_args(command: &str, value: Option<&str>) -> Result<ControlCommand> {
+        let value = || {
+            value
+                .map(String::from)
+                .ok_or_else(|| anyhow!("{} needs a value", command))
+        };
+        Ok(match command {
+            "toggle" => ControlCommand::Toggle,
+            "next" => ControlCommand::Next,
+            "previous" => ControlCommand::Previous,
+            "volume" => ControlCommand::Volume { value: value()? },
+            "seek" => ControlCommand::Seek { value: value()? },
+            "like" => ControlCommand::Like,
+            "open" => ControlCommand::Open { uri: value()? },
+            "dnd" => ControlCommand::Dnd { value: value()? },
+            _ => return Err(anyhow!("unknown command {}", command)),
+        })
+    }
+}
+
+enum Amount {
+    Absolute(i64),
+    Relative(i64),
+}
+
+fn parse_amount(value: &str) -> Result<Amount> {
+    let amount = value
+        .parse::<i64>()
+        .map_err(|_| anyhow!("\"{}\" is not a number", value))?;
+    if value.starts_with('+') || value.starts_with('-') {
+        Ok(Amount::Relative(amount))
+    } else {
+        Ok(Amount::Absolute(amount))
+    }
+}
+
+pub fn parse_do_not_disturb(value: &str, now: DateTime<Local>) -> Result<DoNotDisturb> {
+    match value {
+        "on" => return Ok(DoNotDisturb::On),
+        "off" => return Ok(DoNotDisturb::Off),
+        _ => {}
+    }
+    let invalid = || anyhow!("\"{}\" is not on, off or a duration like 60m", value);
+    let split = value.len().checked_sub(1).ok_or_else(invalid)?;
+    let (amount, unit) = value.split_at(split);
+    let amount = amount.parse::<u32>().map_err(|_| invalid())?;
+    let duration = match unit {
+        "s" => TimeDelta::seconds(amount.into()),
+        "m" => TimeDelta::minutes(amount.into()),
+        "h" => TimeDelta::hours(amount.into()),
+        _ => return Err(invalid()),
+    };
+    if duration.is_zero() {
+        return Err(invalid());
+    }
+    Ok(DoNotDisturb::Until(now + duration))
+}
+
+// Apply a command to the app, dispatching the same events as the equivalent keys
+pub fn apply(app: &mut App, command: ControlCommand) -> Result<()> {
+    match command {
+        ControlCommand::Toggle => app.toggle_playback(),
+        ControlCommand::Next => app.dispatch(IoEvent::NextTrack),
+        ControlCommand::Previous => app.previous_track(),
+        ControlCommand::Volume { value } => match parse_amount(&value)? {
+            Amount::Relative(delta) => app.change_volume(delta.clamp(-100, 100) as i16),
+            Amount::Absolute(volume) => app.dispatch(IoEvent::ChangeVolume {
+                volume: volume.clamp(0, 100) as u8,
+            }),
+        },
+        ControlCommand::Seek { value } => {
+            let Some(CurrentPlaybackContext {
+                item: Some(item), ..
+            }) = &app.current_playback_context
+            else {
+                return Err(anyhow!("nothing is playing"));
+            };
+            let duration_ms = item.duration().num_milliseconds();
+            let position_ms = match parse_amount(&value)? {
+                Amount::Relative(seconds) => app.song_progress_ms as i64 + seconds * 1000,
+                Amount::Absolute(seconds) => seconds * 1000,
+            };
+            app.dispatch(IoEvent::Seek {
+                position_ms: position_ms.clamp(0, duration_ms) as u32,
+            });
+        }
+        ControlCommand::Like => app.toggle_save_playing_item(),
+        ControlCommand::Open { uri } => {
+            if let Ok(playable_id) = PlayableId::from_uri(&uri) {
+                app.dispatch(IoEvent::StartPlayablesPlayback {
+                    playable_ids: vec![playable_id.into_static()],
+                    offset: Some(PlaybackOffset::Index(0)),
+                });
+            } else {
+                let play_context_id = PlayContextId::from_uri(&uri)
+                    .map_err(|_| anyhow!("\"{}\" is not a Spotify URI", uri))?;
+                app.dispatch(IoEvent::StartContextPlayback {
+                    play_context_id: play_context_id.into_static(),
+                    offset: None,
+                });
+            }
+        }
+        ControlCommand::Dnd { value } => app
+            .notifications
+            .set_do_not_disturb(parse_do_not_disturb(&value, Local::now())?),
+    }
+    Ok(())
+}
+
+pub fn status(app: &App) -> Value {
+    match &app.current_playback_context {
+        Some(context) => json!({
+            "is_playing": context.is_playing,
+            "item": context.item.as_ref().map(|item| item.name()),
+            "device": context.device.name,
+            "volume": context.device.volume_percent,
+            "progress_ms": app.song_progress_ms as u64,
+        }),
+        None => Value::Null,
+    }
+}
+
+// Handle one line of input, always answering with a single line of JSON
+pub fn handle_line(app: &mut App, line: &str) -> String {
+    let response = serde_json::from_str::<ControlCommand>(line)
+        .map_err(|e| anyhow!("invalid command: {}", e))
+        .and_then(|command| apply(app, command));
+    match response {
+        Ok(()) => json!({ "ok": true, "status": status(app) }),
+        Err(e) => json!({ "ok": false, "error": e.to_string() }),
+    }
+    .to_string()
+}
+
+#[cfg(unix)]
+pub use self::socket::{listen, send_command, serve};
+
+#[cfg(unix)]
+mod socket {
+    use super::{handle_line, ControlCommand};
+    use crate::app::App;
+    use anyhow::Result;
+    use std::{fs, io::ErrorKind, os::unix::fs::PermissionsExt, path::Path, sync::Arc};
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+        net::{UnixListener, UnixStream},
+        sync::RwLock,
+    };
+
+    // Bind the socket, replacing a stale one left behind by an instance that didn't shut down
+    // cleanly. Fails when another instance is still listening on it. Only the user can connect,
+    // as anyone who can controls their playback.
+    pub async fn listen(path: &Path) -> Result<UnixListener> {
+        if path.exists() {
+            if UnixStream::connect(path).await.is_ok() {
+                return Err(anyhow::anyhow!(
+                    "{} is in use by another instance",
+                    path.display()
+                ));
+            }
+            fs::remove_file(path)?;
+        }
+        let listener = UnixListener::bind(path)?;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+        Ok(listener)
+    }
+
+    pub async fn serve(listener: UnixListener, app: Arc<RwLock<App>>) {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(handle_connection(stream, app.clone()));
+        }
+    }
+
+    async fn handle_connection(stream: UnixStream, app: Arc<RwLock<App>>) -> Result<()> {
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        while let Some(line) = lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let response = handle_line(&mut *app.write().await, &line);
+            writer.write_all(response.as_bytes()).await?;
+            writer.write_all(b"\n").await?;
+        }
+        Ok(())
+    }
+
+    // Send a command to a running instance. Returns None when there is no instance to talk to.
+    pub async fn send_command(path: &Path, command: &ControlCommand) -> Result<Option<String>> {
+        let stream = match UnixStream::connect(path).await {
+            Ok(stream) => stream,
+            Err(e) if matches!(e.kind(), ErrorKind::NotFound | ErrorKind::ConnectionRefused) => {
+                return Ok(None)
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        let (reader, mut writer) = stream.into_split();
+        let mut request = serde_json::to_string(command)?;
+        request.push('\n');
+        writer.write_all(request.as_bytes()).await?;
+
+        let response = BufReader::new(reader).lines().next_line().await?;
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_commands() {
+        assert_eq!(
+            serde_json::from_str::<ControlCommand>(r#"{"command": "volume", "value": "+10"}"#)
+                .unwrap(),
+            ControlCommand::Volume {
+                value: String::from("+10")
+            }
+        );
+        assert_eq!(
+            ControlCommand::from_args("seek", Some("-5")).unwrap(),
+            ControlCommand::Seek {
+                value: String::from("-5")
+            }
+        );
+        assert!(ControlCommand::from_args("open", None).is_err());
+        assert!(ControlCommand::from_args("quit", None).is_err());
+        for command in COMMANDS {
+            assert!(ControlCommand::from_args(command, Some("1")).is_ok());
+        }
+    }
+
+    #[test]
+    fn rejects_commands_outside_the_whitelist() {
+        let mut app = App::default();
+
+        for line in [
+            r#"{"command": "quit"}"#,
+            r#"{"command": "volume"}"#,
+            r#"{"command": "volume", "value": "+10", "extra": 1}"#,
+            "not json",
+        ] {
+            let response: Value = serde_json::from_str(&handle_line(&mut app, line)).unwrap();
+            assert_eq!(response["ok"], false, "{}", line);
+            assert!(response["error"].is_string());
+        }
+        assert!(!app.is_loading);
+    }
+
+    #[test]
+    fn parses_do_not_disturb() {
+        let now = Local::now();
+        assert_eq!(parse_do_not_disturb("on", now).unwrap(), DoNotDisturb::On);
+        assert_eq!(parse_do_not_disturb("off", now).unwrap(), DoNotDisturb::Off);
+        assert_eq!(
+            parse_do_not_disturb("60m", now).unwrap(),
+            DoNotDisturb::Until(now + TimeDelta::hours(1))
+        );
+        assert_eq!(
+            parse_do_not_disturb("2h", now).unwrap(),
+            DoNotDisturb::Until(now + TimeDelta::hours(2))
+        );
+        for value in ["", "m", "0m", "-5m", "5", "5d", "soon"] {
+            assert!(parse_do_not_disturb(value, now).is_err(), "{}", value);
+        }
+    }
+
+    #[test]
+    fn applies_commands() {
+        let mut app = App::default();
+
+        let response: Value =
+            serde_json::from_str(&handle_line(&mut app, r#"{"command": "next"}"#)).unwrap();
+        assert_eq!(response, json!({ "ok": true, "status": null }));
+        assert!(app.is_loading);
+    }
+
+    #[test]
+    fn rejects_invalid_values() {
+        let mut app = App::default();
+
+        let response: Value = serde_json::from_str(&handle_line(
+            &mut app,
+            r#"{"command": "open", "uri": "spotify:nonsense:1"}"#,
+        ))
+        .unwrap();
+        assert_eq!(response["ok"], false);
+
+        let response: Value = serde_json::from_str(&handle_line(
+            &mut app,
+            r#"{"command": "volume", "value": "loud"}"#,
+        ))
+        .unwrap();
+        assert_eq!(response["ok"], false);
+
+        // Seeking needs something to seek in
+        let response: Value = serde_json::from_str(&handle_line(
+            &mut app,
+            r#"{"command": "seek", "value": "+10"}"#,
+        ))
+        .unwrap();
+        assert_eq!(response["ok"], false);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn serves_commands_over_a_socket() {
+        use std::{os::unix::fs::PermissionsExt, sync::Arc};
+        use tokio::{
+            io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+            net::UnixStream,
+            sync::RwLock,
+        };
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("spotify-tui.sock");
+        let app = Arc::new(RwLock::new(App::default()));
+        let listener = listen(&path).await.unwrap();
+        tokio::spawn(serve(listener, app.clone()));
+
+        // A second instance can't take over the socket, and other users can't connect to it
+        assert!(listen(&path).await.is_err());
+        assert_eq!(
+            std::fs::metadata(&path).unwrap().permissions().mode() & 0o777,
+            0o600
+        );
+
+        let response = send_command(&path, &ControlCommand::Toggle)
+            .await
+            .unwrap()
+            .unwrap();
+        let response: Value = serde_json::from_str(&response).unwrap();
+        assert_eq!(response["ok"], true);
+        assert!(app.read().await.is_loading);
+
+        // Malformed input is answered without dropping the connection
+        let (reader, mut writer) = UnixStream::connect(&path).await.unwrap().into_split();
+        let mut lines = BufReader::new(reader).lines();
+        writer.write_all(b"{\"command\": \"rm\"}\n").await.unwrap();
+        let response: Value =
+            serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+        assert_eq!(response["ok"], false);
+
+        writer
+            .write_all(b"{\"command\": \"like\"}\n")
+            .await
+            .unwrap();
+        let response: Value =
+            serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+        assert_eq!(response["ok"], true);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn no_instance_to_send_to() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("spotify-tui.sock");
+
+        assert_eq!(
+            send_command(&path, &ControlCommand::Next).await.unwrap(),
+            None
+        );
+
+        // A stale socket left behind by a crashed instance is replaced
+        drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+        assert_eq!(
+            send_command(&path, &ControlCommand::Next).await.unwrap(),
+            None
+        );
+        assert!(listen(&path).await.is_ok());
+    }
+}
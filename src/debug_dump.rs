@@ -0,0 +1,324 @@
+use crate::app::{ActiveBlock, AlbumTableContext, App, ArtistBlock, SearchResultBlock};
+use chrono::{DateTime, Local};
+use rspotify::model::PlayableItem;
+use serde::Serialize;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+// The JSON of an item as the API returned it, for bug reports about its metadata
+#[derive(Debug)]
+pub struct Dump {
+    // What the item is, e.g. "track", which names the file
+    pub kind: &'static str,
+    pub json: serde_json::Result<String>,
+}
+
+fn dump(kind: &'static str, value: &impl Serialize) -> Dump {
+    Dump {
+        kind,
+        json: serde_json::to_string_pretty(value),
+    }
+}
+
+fn dump_playable(item: &PlayableItem) -> Dump {
+    match item {
+        PlayableItem::Track(track) => dump("track", track),
+        PlayableItem::Episode(episode) => dump("episode", episode),
+    }
+}
+
+// The item selected in the focused block, None when nothing is or the block has no items
+pub fn selected_item(app: &App) -> Option<Dump> {
+    match app.get_current_route().active_block {
+        ActiveBlock::ItemTable => app
+            .item_table
+            .items
+            .get(app.item_table.selected_index)
+            .map(dump_playable),
+        ActiveBlock::AlbumTracks => match app.album_table_context {
+            AlbumTableContext::Simplified => {
+                let album = app.selected_album_simplified.as_ref()?;
+                album
+                    .tracks
+                    .items
+                    .get(album.selected_index)
+                    .map(|track| dump("track", track))
+            }
+            AlbumTableContext::Full => {
+                let album = app.selected_album_full.as_ref()?;
+                album
+                    .album
+                    .tracks
+                    .items
+                    .get(album.selected_index)
+                    .map(|track| dump("track", track))
+            }
+        },
+        ActiveBlock::AlbumList => app
+            .library
+            .saved_albums
+            .get_results(None)?
+            .items
+            .get(app.album_list_index)
+            .map(|saved| dump("album", &saved.album)),
+        ActiveBlock::MyPlaylists => app
+            .selected_playlist()
+            .map(|playlist| dump("playlist", playlist)),
+        ActiveBlock::EpisodeTable => app
+            .library
+            .show_episodes
+            .get_results(None)?
+            .items
+            .get(app.episode_list_index)
+            .map(|episode| dump("episode", episode)),
+        ActiveBlock::ArtistBlock => {
+            let artist = app.artist.as_ref()?;
+            match artist.artist_selected_block {
+                ArtistBlock::TopTracks => artist
+                    .top_tracks
+                    .get(artist.selected_top_track_index)
+                    .map(|track| dump("track", track)),
+                ArtistBlock::Albums => artist
+                    .albums()
+                    .get(artist.selected_album_index)
+                    .map(|album| dump("album", *album)),
+                ArtistBlock::RelatedArtists => artist
+                    .related_artists
+                    .get(artist.selected_related_artist_index)
+                    .map(|artist| dump("artist", artist)),
+                ArtistBlock::Empty => None,
+            }
+        }
+        ActiveBlock::SearchResultBlock => {
+            let results = &app.search_results;
+            match results.selected_block {
+                SearchResultBlock::SongSearch => results
+                    .tracks
+                    .get_results(None)?
+                    .items
+                    .get(results.selected_tracks_index?)
+                    .map(|track| dump("track", track)),
+                SearchResultBlock::AlbumSearch => results
+                    .albums
+                    .get_results(None)?
+                    .items
+                    .get(results.selected_album_index?)
+                    .map(|album| dump("album", album)),
+                SearchResultBlock::ArtistSearch => results
+                    .artists
+                    .get_results(None)?
+                    .items
+                    .get(results.selected_artists_index?)
+                    .map(|artist| dump("artist", artist)),
+                SearchResultBlock::PlaylistSearch => results
+                    .playlists
+                    .get_results(None)?
+                    .items
+                    .get(results.selected_playlists_index?)
+                    .map(|playlist| dump("playlist", playlist)),
+                SearchResultBlock::ShowSearch => results
+                    .shows
+                    .get_results(None)?
+                    .items
+                    .get(results.selected_shows_index?)
+                    .map(|show| dump("show", show)),
+                SearchResultBlock::Empty => None,
+            }
+        }
+        ActiveBlock::PlayBar | ActiveBlock::BasicView => app
+            .current_playback_context
+            .as_ref()?
+            .item
+            .as_ref()
+            .map(dump_playable),
+        _ => None,
+    }
+}
+
+// Each dump gets a file of its own, e.g. `track-20240102-150405.json`
+pub fn write(dir: &Path, kind: &str, json: &str, now: DateTime<Local>) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!("{}-{}.json", kind, now.format("%Y%m%d-%H%M%S")));
+    fs::write(&path, json)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{Artist, RouteId};
+    use crate::user_config::UserConfig;
+    use chrono::{TimeZone, Utc};
+    use rspotify::model::{ArtistId, Page};
+    use serde_json::json;
+
+    fn page<T: serde::de::DeserializeOwned>(item: serde_json::Value) -> Page<T> {
+        serde_json::from_value(json!({
+            "href": "",
+            "items": [item],
+            "limit": 20,
+            "next": null,
+            "offset": 0,
+            "previous": null,
+            "total": 1,
+        }))
+        .unwrap()
+    }
+
+    fn track_json() -> serde_json::Value {
+        json!({
+            "album": { "artists": [], "external_urls": {}, "images": [], "name": "Album" },
+            "artists": [],
+            "disc_number": 1,
+            "duration_ms": 180_000,
+            "explicit": false,
+            "external_ids": {},
+            "external_urls": {},
+            "id": "4iV5W9uYEdYUVa79Axb7Rh",
+            "is_local": false,
+            "name": "Track",
+            "popularity": 0,
+            "track_number": 1,
+            "type": "track",
+        })
+    }
+
+    fn album_json() -> serde_json::Value {
+        json!({
+            "artists": [],
+            "external_urls": {},
+            "id": "4aawyAB9vmqN3uQ7FjRGTy",
+            "images": [],
+            "name": "Album",
+        })
+    }
+
+    fn artist_json() -> serde_json::Value {
+        json!({
+            "external_urls": {},
+            "followers": { "href": null, "total": 0 },
+            "genres": [],
+            "href": "",
+            "id": "0OdUWJ0sBjDrqHygGUXeCF",
+            "images": [],
+            "name": "Artist",
+            "popularity": 0,
+        })
+    }
+
+    fn playlist_json() -> serde_json::Value {
+        json!({
+            "collaborative": false,
+            "external_urls": {},
+            "href": "",
+            "id": "37i9dQZF1DXcBWIGoYBM5M",
+            "images": [],
+            "name": "Mix",
+            "owner": { "external_urls": {}, "href": "", "id": "owner" },
+            "snapshot_id": "",
+            "tracks": { "href": "", "total": 1 },
+        })
+    }
+
+    fn episode_json() -> serde_json::Value {
+        json!({
+            "audio_preview_url": null,
+            "description": "",
+            "duration_ms": 1_800_000,
+            "explicit": false,
+            "external_urls": {},
+            "href": "",
+            "id": "512ojhOuo1ktJprKbVcKyQ",
+            "images": [],
+            "is_externally_hosted": false,
+            "is_playable": true,
+            "language": "en",
+            "languages": [],
+            "name": "Episode",
+            "release_date": "2020-01-01",
+            "release_date_precision": "day",
+            "resume_point": null,
+        })
+    }
+
+    // The dump of the focused block, parsed back to check that it is valid JSON
+    fn dumped(app: &App) -> (&'static str, serde_json::Value) {
+        let dump = selected_item(app).unwrap();
+        let json = serde_json::from_str(&dump.json.unwrap()).unwrap();
+        (dump.kind, json)
+    }
+
+    #[test]
+    fn dumps_the_item_selected_in_each_block() {
+        let mut app = App::new(
+            tokio::sync::mpsc::unbounded_channel().0,
+            UserConfig::new(),
+            Utc::now(),
+        );
+
+        app.push_navigation_stack(RouteId::Search, ActiveBlock::SearchResultBlock);
+        // Nothing selected yet
+        assert!(selected_item(&app).is_none());
+        let results = &mut app.search_results;
+        results.tracks.add_pages(page(track_json()));
+        results.albums.add_pages(page(album_json()));
+        results.artists.add_pages(page(artist_json()));
+        results.playlists.add_pages(page(playlist_json()));
+        results.selected_tracks_index = Some(0);
+        results.selected_album_index = Some(0);
+        results.selected_artists_index = Some(0);
+        results.selected_playlists_index = Some(0);
+        for (block, kind) in [
+            (SearchResultBlock::SongSearch, "track"),
+            (SearchResultBlock::AlbumSearch, "album"),
+            (SearchResultBlock::ArtistSearch, "artist"),
+            (SearchResultBlock::PlaylistSearch, "playlist"),
+        ] {
+            app.search_results.selected_block = block;
+            let (dumped_kind, json) = dumped(&app);
+            assert_eq!(dumped_kind, kind);
+            assert!(json["id"].is_string());
+        }
+
+        app.push_navigation_stack(RouteId::Podcasts, ActiveBlock::EpisodeTable);
+        app.library.show_episodes.add_pages(page(episode_json()));
+        let (kind, json) = dumped(&app);
+        assert_eq!(kind, "episode");
+        assert_eq!(json["id"], "512ojhOuo1ktJprKbVcKyQ");
+
+        app.push_navigation_stack(RouteId::Artist, ActiveBlock::ArtistBlock);
+        app.artist = Some(Artist {
+            artist_id: ArtistId::from_id("0OdUWJ0sBjDrqHygGUXeCF").unwrap(),
+            artist_name: String::from("Artist"),
+            albums: Default::default(),
+            album_type: None,
+            pending_albums_offset: None,
+            related_artists: vec![serde_json::from_value(artist_json()).unwrap()],
+            top_tracks: vec![serde_json::from_value(track_json()).unwrap()],
+            selected_album_index: 0,
+            selected_related_artist_index: 0,
+            selected_top_track_index: 0,
+            artist_hovered_block: ArtistBlock::TopTracks,
+            artist_selected_block: ArtistBlock::TopTracks,
+        });
+        let (kind, json) = dumped(&app);
+        assert_eq!(kind, "track");
+        assert_eq!(json["name"], "Track");
+        app.artist.as_mut().unwrap().artist_selected_block = ArtistBlock::RelatedArtists;
+        assert_eq!(dumped(&app).0, "artist");
+        // No albums loaded
+        app.artist.as_mut().unwrap().artist_selected_block = ArtistBlock::Albums;
+        assert!(selected_item(&app).is_none());
+    }
+
+    #[test]
+    fn writes_each_dump_to_a_file_of_its_own() {
+        let dir = tempfile::tempdir().unwrap();
+        let now = Local.with_ymd_and_hms(2024, 1, 2, 15, 4, 5).unwrap();
+        let path = write(&dir.path().join("debug"), "track", "{}", now).unwrap();
+        assert_eq!(path.file_name().unwrap(), "track-20240102-150405.json");
+        assert_eq!(fs::read_to_string(path).unwrap(), "{}");
+    }
+}
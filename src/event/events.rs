@@ -1,9 +1,12 @@
 use crate::event::Key;
 use anyhow::Error;
-use crossterm::event::{self, EventStream};
+use crossterm::event::{self, EventStream, MouseEvent};
 use futures_util::{FutureExt, StreamExt};
 use std::time::Duration;
-use tokio::{sync::mpsc, task::JoinHandle};
+use tokio::{
+    sync::{mpsc, watch},
+    task::JoinHandle,
+};
 
 #[derive(Clone, Copy, Debug)]
 /// Configuration for event handling.
@@ -24,6 +27,8 @@ impl Default for EventConfig {
 pub enum Event<I> {
     /// An input event occurred.
     Input(I),
+    /// A mouse event occurred.
+    Mouse(MouseEvent),
     /// An tick event occurred.
     Tick,
 }
@@ -35,6 +40,7 @@ pub struct Events {
     // Need to be kept around to prevent disposing the sender side.
     #[allow(dead_code)]
     tx: mpsc::UnboundedSender<Event<Key>>,
+    tick_rate_tx: watch::Sender<Duration>,
 }
 
 impl Events {
@@ -49,16 +55,24 @@ impl Events {
     /// Constructs an new instance of `Events` from given config.
     pub fn with_config(config: EventConfig) -> (Events, JoinHandle<()>) {
         let (tx, rx) = mpsc::unbounded_channel();
+        let (tick_rate_tx, mut tick_rate_rx) = watch::channel(config.tick_rate);
 
         let event_tx = tx.clone();
         let handle = tokio::spawn(async move {
             let mut reader = EventStream::new();
             loop {
+                let tick_rate = *tick_rate_rx.borrow_and_update();
                 let result = tokio::select! {
-                    _ = tokio::time::sleep(config.tick_rate).fuse() => event_tx.send(Event::Tick).map_err(Error::msg),
+                    _ = tokio::time::sleep(tick_rate).fuse() => event_tx.send(Event::Tick).map_err(Error::msg),
+                    // Starts the wait over at the new rate
+                    Ok(()) = tick_rate_rx.changed() => Ok(()),
                     event = reader.next().fuse() => {
                         match event {
-                            Some(Ok(event::Event::Key(key))) => event_tx.send(Event::Input(Key::from(key))).map_err(Error::msg),
+                            Some(Ok(event::Event::Key(key))) => match Key::pressed(key) {
+                                Some(key) => event_tx.send(Event::Input(key)).map_err(Error::msg),
+                                None => Ok(()),
+                            },
+                            Some(Ok(event::Event::Mouse(mouse))) => event_tx.send(Event::Mouse(mouse)).map_err(Error::msg),
                             Some(res) => res.map(|_| ()).map_err(Error::msg),
                             None => break,
                         }
@@ -70,7 +84,23 @@ impl Events {
             }
         });
 
-        (Events { rx, tx }, handle)
+        (
+            Events {
+                rx,
+                tx,
+                tick_rate_tx,
+            },
+            handle,
+        )
+    }
+
+    /// Changes the tick rate, e.g. to tick less often while nothing on screen moves.
+    pub fn set_tick_rate(&self, tick_rate: Duration) {
+        self.tick_rate_tx.send_if_modified(|current| {
+            let is_changed = *current != tick_rate;
+            *current = tick_rate;
+            is_changed
+        });
     }
 
     /// Attempts to read an event.
@@ -8,6 +8,8 @@ pub enum Key {
     Enter,
     /// Tabulation key
     Tab,
+    /// Shift+Tab
+    BackTab,
     /// Backspace key
     Backspace,
     /// Escape key
@@ -35,6 +37,23 @@ pub enum Key {
     /// Page Down key
     PageDown,
 
+    /// Ctrl+Left arrow
+    CtrlLeft,
+    /// Ctrl+Right arrow
+    CtrlRight,
+    /// Ctrl+Up arrow
+    CtrlUp,
+    /// Ctrl+Down arrow
+    CtrlDown,
+    /// Ctrl+Home key
+    CtrlHome,
+    /// Ctrl+End key
+    CtrlEnd,
+    /// Ctrl+Page Up key
+    CtrlPageUp,
+    /// Ctrl+Page Down key
+    CtrlPageDown,
+
     /// F0 key
     F0,
     /// F1 key
@@ -93,6 +112,36 @@ impl Key {
             _ => panic!("unknown function key: F{}", n),
         }
     }
+
+    /// Returns the key with Ctrl held, if there is such a key
+    pub fn with_ctrl(self) -> Option<Key> {
+        match self {
+            Key::Char(c) => Some(Key::Ctrl(c)),
+            Key::Left => Some(Key::CtrlLeft),
+            Key::Right => Some(Key::CtrlRight),
+            Key::Up => Some(Key::CtrlUp),
+            Key::Down => Some(Key::CtrlDown),
+            Key::Home => Some(Key::CtrlHome),
+            Key::End => Some(Key::CtrlEnd),
+            Key::PageUp => Some(Key::CtrlPageUp),
+            Key::PageDown => Some(Key::CtrlPageDown),
+            _ => None,
+        }
+    }
+
+    /// Returns the key with Alt held, if there is such a key
+    pub fn with_alt(self) -> Option<Key> {
+        match self {
+            Key::Char(c) => Some(Key::Alt(c)),
+            _ => None,
+        }
+    }
+
+    /// Returns the key of a key press. Terminals speaking the kitty keyboard protocol also report
+    /// releases and repeats, which would otherwise act a second time.
+    pub fn pressed(key_event: event::KeyEvent) -> Option<Key> {
+        (key_event.kind == event::KeyEventKind::Press).then(|| Key::from(key_event))
+    }
 }
 
 impl fmt::Display for Key {
@@ -105,6 +154,15 @@ impl fmt::Display for Key {
             Key::Ctrl(c) => write!(f, "<Ctrl+{}>", c),
             Key::Char(c) => write!(f, "{}", c),
             Key::Left | Key::Right | Key::Up | Key::Down => write!(f, "<{:?} Arrow Key>", self),
+            Key::BackTab => write!(f, "<Shift+Tab>"),
+            Key::CtrlLeft => write!(f, "<Ctrl+Left Arrow Key>"),
+            Key::CtrlRight => write!(f, "<Ctrl+Right Arrow Key>"),
+            Key::CtrlUp => write!(f, "<Ctrl+Up Arrow Key>"),
+            Key::CtrlDown => write!(f, "<Ctrl+Down Arrow Key>"),
+            Key::CtrlHome => write!(f, "<Ctrl+Home>"),
+            Key::CtrlEnd => write!(f, "<Ctrl+End>"),
+            Key::CtrlPageUp => write!(f, "<Ctrl+PageUp>"),
+            Key::CtrlPageDown => write!(f, "<Ctrl+PageDown>"),
             Key::Enter
             | Key::Tab
             | Key::Backspace
@@ -122,86 +180,130 @@ impl fmt::Display for Key {
 
 impl From<event::KeyEvent> for Key {
     fn from(key_event: event::KeyEvent) -> Self {
-        match key_event {
-            event::KeyEvent {
-                code: event::KeyCode::Esc,
-                ..
-            } => Key::Esc,
-            event::KeyEvent {
-                code: event::KeyCode::Backspace,
-                ..
-            } => Key::Backspace,
-            event::KeyEvent {
-                code: event::KeyCode::Left,
-                ..
-            } => Key::Left,
-            event::KeyEvent {
-                code: event::KeyCode::Right,
-                ..
-            } => Key::Right,
-            event::KeyEvent {
-                code: event::KeyCode::Up,
-                ..
-            } => Key::Up,
-            event::KeyEvent {
-                code: event::KeyCode::Down,
-                ..
-            } => Key::Down,
-            event::KeyEvent {
-                code: event::KeyCode::Home,
-                ..
-            } => Key::Home,
-            event::KeyEvent {
-                code: event::KeyCode::End,
-                ..
-            } => Key::End,
-            event::KeyEvent {
-                code: event::KeyCode::PageUp,
-                ..
-            } => Key::PageUp,
-            event::KeyEvent {
-                code: event::KeyCode::PageDown,
-                ..
-            } => Key::PageDown,
-            event::KeyEvent {
-                code: event::KeyCode::Delete,
-                ..
-            } => Key::Delete,
-            event::KeyEvent {
-                code: event::KeyCode::Insert,
-                ..
-            } => Key::Ins,
-            event::KeyEvent {
-                code: event::KeyCode::F(n),
-                ..
-            } => Key::from_f(n),
-            event::KeyEvent {
-                code: event::KeyCode::Enter,
-                ..
-            } => Key::Enter,
-            event::KeyEvent {
-                code: event::KeyCode::Tab,
-                ..
-            } => Key::Tab,
-
-            // First check for char + modifier
-            event::KeyEvent {
-                code: event::KeyCode::Char(c),
-                modifiers: event::KeyModifiers::ALT,
-                ..
-            } => Key::Alt(c),
-            event::KeyEvent {
-                code: event::KeyCode::Char(c),
-                modifiers: event::KeyModifiers::CONTROL,
-                ..
-            } => Key::Ctrl(c),
-
-            event::KeyEvent {
-                code: event::KeyCode::Char(c),
-                ..
-            } => Key::Char(c),
-
-            _ => Key::Unknown,
+        use event::{KeyCode, KeyModifiers};
+
+        let key = match key_event.code {
+            KeyCode::Esc => Key::Esc,
+            KeyCode::Backspace => Key::Backspace,
+            KeyCode::Left => Key::Left,
+            KeyCode::Right => Key::Right,
+            KeyCode::Up => Key::Up,
+            KeyCode::Down => Key::Down,
+            KeyCode::Home => Key::Home,
+            KeyCode::End => Key::End,
+            KeyCode::PageUp => Key::PageUp,
+            KeyCode::PageDown => Key::PageDown,
+            KeyCode::Delete => Key::Delete,
+            KeyCode::Insert => Key::Ins,
+            // Some keyboards have keys up to F24, which aren't supported
+            KeyCode::F(n) if n <= 12 => Key::from_f(n),
+            KeyCode::Enter => Key::Enter,
+            // Most terminals send Shift+Tab as a key of its own, those with the kitty keyboard
+            // protocol as Tab with Shift held
+            KeyCode::BackTab => Key::BackTab,
+            KeyCode::Tab if key_event.modifiers.contains(KeyModifiers::SHIFT) => Key::BackTab,
+            KeyCode::Tab => Key::Tab,
+            KeyCode::Char(c) => Key::Char(c),
+            _ => return Key::Unknown,
+        };
+
+        // Shift is already in the character typed. Keys without a Ctrl or Alt form, and Ctrl
+        // with Alt (which is AltGr for some layouts), are the key itself.
+        let modifiers = key_event.modifiers - KeyModifiers::SHIFT;
+        let modified = if modifiers == KeyModifiers::CONTROL {
+            key.with_ctrl()
+        } else if modifiers == KeyModifiers::ALT {
+            key.with_alt()
+        } else {
+            None
+        };
+        modified.unwrap_or(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> Key {
+        Key::from(KeyEvent::new(code, modifiers))
+    }
+
+    #[test]
+    fn converts_crossterm_keys() {
+        let none = KeyModifiers::NONE;
+        let ctrl = KeyModifiers::CONTROL;
+        let alt = KeyModifiers::ALT;
+        let shift = KeyModifiers::SHIFT;
+        let cases = [
+            (KeyCode::Char('a'), none, Key::Char('a')),
+            (KeyCode::Char('A'), shift, Key::Char('A')),
+            (KeyCode::Char(' '), none, Key::Char(' ')),
+            (KeyCode::Char('d'), ctrl, Key::Ctrl('d')),
+            (KeyCode::Char('D'), ctrl | shift, Key::Ctrl('D')),
+            (KeyCode::Char('x'), alt, Key::Alt('x')),
+            (KeyCode::Char('X'), alt | shift, Key::Alt('X')),
+            // AltGr on some layouts
+            (KeyCode::Char('@'), ctrl | alt, Key::Char('@')),
+            (KeyCode::Tab, none, Key::Tab),
+            (KeyCode::BackTab, shift, Key::BackTab),
+            (KeyCode::Tab, shift, Key::BackTab),
+            (KeyCode::Enter, none, Key::Enter),
+            (KeyCode::Enter, ctrl, Key::Enter),
+            (KeyCode::Esc, none, Key::Esc),
+            (KeyCode::Backspace, none, Key::Backspace),
+            (KeyCode::Delete, none, Key::Delete),
+            (KeyCode::Insert, none, Key::Ins),
+            (KeyCode::Left, none, Key::Left),
+            (KeyCode::Right, none, Key::Right),
+            (KeyCode::Up, none, Key::Up),
+            (KeyCode::Down, none, Key::Down),
+            (KeyCode::Left, ctrl, Key::CtrlLeft),
+            (KeyCode::Right, ctrl, Key::CtrlRight),
+            (KeyCode::Up, ctrl, Key::CtrlUp),
+            (KeyCode::Down, ctrl, Key::CtrlDown),
+            (KeyCode::Right, shift, Key::Right),
+            (KeyCode::Right, alt, Key::Right),
+            (KeyCode::Home, none, Key::Home),
+            (KeyCode::End, none, Key::End),
+            (KeyCode::PageUp, none, Key::PageUp),
+            (KeyCode::PageDown, none, Key::PageDown),
+            (KeyCode::Home, ctrl, Key::CtrlHome),
+            (KeyCode::End, ctrl, Key::CtrlEnd),
+            (KeyCode::PageUp, ctrl, Key::CtrlPageUp),
+            (KeyCode::PageDown, ctrl, Key::CtrlPageDown),
+            (KeyCode::PageDown, ctrl | shift, Key::CtrlPageDown),
+            (KeyCode::F(1), none, Key::F1),
+            (KeyCode::F(5), none, Key::F5),
+            (KeyCode::F(12), none, Key::F12),
+            (KeyCode::F(5), ctrl, Key::F5),
+            (KeyCode::F(13), none, Key::Unknown),
+            (KeyCode::CapsLock, none, Key::Unknown),
+        ];
+        for (code, modifiers, expected) in cases {
+            assert_eq!(key(code, modifiers), expected, "{:?} {:?}", code, modifiers);
         }
     }
+
+    #[test]
+    fn only_presses_act() {
+        let event = |kind| KeyEvent::new_with_kind(KeyCode::Char('j'), KeyModifiers::NONE, kind);
+        assert_eq!(
+            Key::pressed(event(KeyEventKind::Press)),
+            Some(Key::Char('j'))
+        );
+        assert_eq!(Key::pressed(event(KeyEventKind::Repeat)), None);
+        assert_eq!(Key::pressed(event(KeyEventKind::Release)), None);
+    }
+
+    #[test]
+    fn displays_keys() {
+        assert_eq!(Key::Char(' ').to_string(), "<Space>");
+        assert_eq!(Key::Ctrl('d').to_string(), "<Ctrl+d>");
+        assert_eq!(Key::BackTab.to_string(), "<Shift+Tab>");
+        assert_eq!(Key::CtrlRight.to_string(), "<Ctrl+Right Arrow Key>");
+        assert_eq!(Key::CtrlPageUp.to_string(), "<Ctrl+PageUp>");
+        assert_eq!(Key::F5.to_string(), "F5");
+    }
 }
@@ -1,7 +1,10 @@
+use rspotify::prelude::PlayContextId;
+
 use super::common_key_events;
 use crate::{
     app::{ActiveBlock, AlbumTableContext, App, RouteId, SelectedFullAlbum},
     event::Key,
+    network::IoEvent,
 };
 
 pub fn handler(key: Key, app: &mut App) {
@@ -60,6 +63,31 @@ pub fn handler(key: Key, app: &mut App) {
             app.get_current_user_saved_albums_previous()
         }
         Key::Char('D') => app.current_user_saved_album_delete(ActiveBlock::AlbumList),
+        _ if key == app.user_config.keys.add_item_to_queue => {
+            if let Some(selected_album) = app
+                .library
+                .saved_albums
+                .get_results(None)
+                .and_then(|albums| albums.items.get(app.album_list_index))
+            {
+                let play_context_id = PlayContextId::Album(selected_album.album.id.clone());
+                app.queue_context(play_context_id);
+            }
+        }
+        Key::Char('e') => {
+            if let Some(selected_album) = app
+                .library
+                .saved_albums
+                .get_results(None)
+                .and_then(|albums| albums.items.get(app.album_list_index))
+            {
+                let play_context_id = PlayContextId::Album(selected_album.album.id.clone());
+                app.dispatch(IoEvent::StartContextPlayback {
+                    play_context_id,
+                    offset: None,
+                });
+            }
+        }
         _ => {}
     };
 }
@@ -67,6 +95,13 @@ pub fn handler(key: Key, app: &mut App) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::user_config::UserConfig;
+    use chrono::Utc;
+    use rspotify::{
+        model::{Page, SavedAlbum},
+        prelude::Id,
+    };
+    use tokio::sync::mpsc::unbounded_channel;
 
     #[test]
     fn on_left_press() {
@@ -91,4 +126,122 @@ mod tests {
         let current_route = app.get_current_route();
         assert_eq!(current_route.active_block, ActiveBlock::Empty);
     }
+
+    fn saved_album(id: &str) -> SavedAlbum {
+        serde_json::from_value(serde_json::json!({
+            "added_at": "2024-01-01T00:00:00Z",
+            "album": {
+                "artists": [],
+                "album_type": "album",
+                "copyrights": [],
+                "external_ids": {},
+                "external_urls": {},
+                "genres": [],
+                "href": "",
+                "id": id,
+                "images": [],
+                "name": "Kind of Blue",
+                "popularity": 0,
+                "release_date": "1959-08-17",
+                "release_date_precision": "day",
+                "tracks": {
+                    "href": "",
+                    "items": [],
+                    "limit": 50,
+                    "next": null,
+                    "offset": 0,
+                    "previous": null,
+                    "total": 0
+                }
+            }
+        }))
+        .unwrap()
+    }
+
+    fn saved_albums_page(offset: u32, total: u32) -> Page<SavedAlbum> {
+        let count = total.saturating_sub(offset).min(20) as usize;
+        Page {
+            href: String::new(),
+            items: vec![saved_album("1weenld61qoidwYuZ1GESA"); count],
+            limit: 20,
+            next: None,
+            offset,
+            previous: None,
+            total,
+        }
+    }
+
+    #[test]
+    fn pages_stop_at_either_end() {
+        let (io_tx, mut io_rx) = unbounded_channel();
+        let mut app = App::new(io_tx, UserConfig::new(), Utc::now());
+        app.library.saved_albums.add_pages(saved_albums_page(0, 30));
+
+        let previous_page = app.user_config.keys.previous_page;
+        handler(previous_page, &mut app);
+        assert_eq!(
+            app.notifications.current().unwrap().message,
+            "This is the first page of saved albums"
+        );
+
+        let next_page = app.user_config.keys.next_page;
+        handler(next_page, &mut app);
+        assert!(matches!(
+            io_rx.try_recv(),
+            Ok(IoEvent::GetCurrentUserSavedAlbums { offset: Some(20) })
+        ));
+
+        // No request for a page past the last one
+        app.library
+            .saved_albums
+            .add_pages(saved_albums_page(20, 30));
+        handler(next_page, &mut app);
+        assert!(io_rx.try_recv().is_err());
+        assert_eq!(
+            app.notifications.current().unwrap().message,
+            "This is the last page of saved albums"
+        );
+
+        handler(previous_page, &mut app);
+        assert_eq!(app.library.saved_albums.page_count(), Some((1, 2)));
+        handler(next_page, &mut app);
+        assert_eq!(app.library.saved_albums.page_count(), Some((2, 2)));
+        assert!(io_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn plays_the_selected_album() {
+        let (io_tx, mut io_rx) = unbounded_channel();
+        let mut app = App::new(io_tx, UserConfig::new(), Utc::now());
+        handler(Key::Char('e'), &mut app);
+        assert!(io_rx.try_recv().is_err());
+
+        app.library.saved_albums.add_pages(saved_albums_page(0, 2));
+        app.album_list_index = 1;
+        handler(Key::Char('e'), &mut app);
+        match io_rx.try_recv() {
+            Ok(IoEvent::StartContextPlayback {
+                play_context_id: PlayContextId::Album(album_id),
+                offset: None,
+            }) => assert_eq!(album_id.id(), "1weenld61qoidwYuZ1GESA"),
+            _ => panic!("expected the album to play"),
+        }
+    }
+
+    #[test]
+    fn selection_fits_a_shorter_page() {
+        let (io_tx, _io_rx) = unbounded_channel();
+        let mut app = App::new(io_tx, UserConfig::new(), Utc::now());
+        app.library.saved_albums.add_pages(saved_albums_page(0, 25));
+        app.library
+            .saved_albums
+            .add_pages(saved_albums_page(20, 25));
+        app.library.saved_albums.previous_page();
+        app.album_list_index = 15;
+
+        handler(app.user_config.keys.next_page, &mut app);
+        assert_eq!(app.album_list_index, 4);
+        handler(app.user_config.keys.previous_page, &mut app);
+        assert_eq!(app.album_list_index, 4);
+    }
 }
@@ -1,9 +1,11 @@
 use super::common_key_events;
 use crate::{
-    app::{AlbumTableContext, App, RecommendationsContext},
+    app::{AlbumTableContext, App, LibraryItem, RecommendationsContext},
     event::Key,
-    network::IoEvent,
+    network::{IoEvent, PlaybackOffset},
 };
+use rspotify::model::AlbumId;
+use std::time::Instant;
 
 pub fn handler(key: Key, app: &mut App) {
     match key {
@@ -53,12 +55,18 @@ pub fn handler(key: Key, app: &mut App) {
         k if common_key_events::low_event(k) => handle_low_event(app),
         Key::Char('s') => handle_save_event(app),
         Key::Char('w') => handle_save_album_event(app),
+        k if k == app.user_config.keys.jump_to_playing => app.jump_to_playing_item(),
+        Key::Char('D') => {
+            if let Some((album_id, _)) = selected_album(app) {
+                app.dispatch(IoEvent::CurrentUserSavedAlbumDelete { album_id });
+            }
+        }
         Key::Enter => match app.album_table_context {
             AlbumTableContext::Full => {
                 if let Some(selected_album) = app.selected_album_full.clone() {
                     app.dispatch(IoEvent::StartContextPlayback {
                         play_context_id: selected_album.album.id.into(),
-                        offset: Some(app.saved_album_tracks_index as u32),
+                        offset: Some(PlaybackOffset::Index(app.saved_album_tracks_index as u32)),
                     });
                 };
             }
@@ -67,7 +75,9 @@ pub fn handler(key: Key, app: &mut App) {
                     if let Some(album_id) = selected_album_simplified.album.id.clone() {
                         app.dispatch(IoEvent::StartContextPlayback {
                             play_context_id: album_id.into(),
-                            offset: Some(selected_album_simplified.selected_index as u32),
+                            offset: Some(PlaybackOffset::Index(
+                                selected_album_simplified.selected_index as u32,
+                            )),
                         });
                     }
                 };
@@ -216,9 +226,7 @@ fn handle_save_event(app: &mut App) {
                     .get(app.saved_album_tracks_index)
                 {
                     if let Some(track_id) = &selected_track.id {
-                        app.dispatch(IoEvent::ToggleSaveTrack {
-                            track_id: track_id.clone(),
-                        });
+                        app.toggle_save_track(track_id.clone());
                     };
                 };
             };
@@ -231,9 +239,7 @@ fn handle_save_event(app: &mut App) {
                     .get(selected_album_simplified.selected_index)
                 {
                     if let Some(track_id) = &selected_track.id {
-                        app.dispatch(IoEvent::ToggleSaveTrack {
-                            track_id: track_id.clone(),
-                        });
+                        app.toggle_save_track(track_id.clone());
                     };
                 };
             };
@@ -241,28 +247,33 @@ fn handle_save_event(app: &mut App) {
     }
 }
 
-fn handle_save_album_event(app: &mut App) {
+// The album shown, with its name
+fn selected_album(app: &App) -> Option<(AlbumId<'static>, String)> {
     match app.album_table_context {
-        AlbumTableContext::Full => {
-            if let Some(selected_album) = app.selected_album_full.clone() {
-                let album_id = selected_album.album.id.clone();
-                app.dispatch(IoEvent::CurrentUserSavedAlbumAdd { album_id });
-            };
-        }
-        AlbumTableContext::Simplified => {
-            if let Some(selected_album_simplified) = app.selected_album_simplified.clone() {
-                if let Some(album_id) = selected_album_simplified.album.id {
-                    app.dispatch(IoEvent::CurrentUserSavedAlbumAdd { album_id });
-                };
-            };
-        }
+        AlbumTableContext::Full => app
+            .selected_album_full
+            .as_ref()
+            .map(|selected| (selected.album.id.clone(), selected.album.name.clone())),
+        AlbumTableContext::Simplified => app
+            .selected_album_simplified
+            .as_ref()
+            .and_then(|selected| Some((selected.album.id.clone()?, selected.album.name.clone()))),
+    }
+}
+
+fn handle_save_album_event(app: &mut App) {
+    if let Some((album_id, name)) = selected_album(app) {
+        app.save_library_item(LibraryItem::Album(album_id), &name, Instant::now());
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::app::ActiveBlock;
+    use crate::app::{ActiveBlock, SelectedAlbum};
+    use crate::user_config::UserConfig;
+    use chrono::Utc;
+    use rspotify::model::Page;
 
     #[test]
     fn on_left_press() {
@@ -287,4 +298,52 @@ mod tests {
         let current_route = app.get_current_route();
         assert_eq!(current_route.active_block, ActiveBlock::Empty);
     }
+
+    #[test]
+    fn saves_and_removes_the_album_shown() {
+        let (io_tx, mut io_rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut app = App::new(io_tx, UserConfig::new(), Utc::now());
+        app.album_table_context = AlbumTableContext::Simplified;
+        app.selected_album_simplified = Some(SelectedAlbum {
+            album: serde_json::from_value(serde_json::json!({
+                "artists": [],
+                "available_markets": [],
+                "external_urls": {},
+                "href": null,
+                "id": "4aawyAB9vmqN3uQ7FjRGTy",
+                "images": [],
+                "name": "Global Warming",
+                "release_date": null,
+                "release_date_precision": null,
+            }))
+            .unwrap(),
+            tracks: Page {
+                href: String::new(),
+                items: vec![],
+                limit: 20,
+                next: None,
+                offset: 0,
+                previous: None,
+                total: 0,
+            },
+            selected_index: 0,
+        });
+        let album_id = AlbumId::from_id("4aawyAB9vmqN3uQ7FjRGTy").unwrap();
+
+        handler(Key::Char('w'), &mut app);
+        match io_rx.try_recv() {
+            Ok(IoEvent::CurrentUserSavedAlbumAdd { album_id: saved }) => {
+                assert_eq!(saved, album_id)
+            }
+            event => panic!("unexpected {:?}", event),
+        }
+
+        handler(Key::Char('D'), &mut app);
+        match io_rx.try_recv() {
+            Ok(IoEvent::CurrentUserSavedAlbumDelete { album_id: removed }) => {
+                assert_eq!(removed, album_id)
+            }
+            event => panic!("unexpected {:?}", event),
+        }
+    }
 }
@@ -1,3 +1,10 @@
+use super::common_key_events;
 use crate::{app::App, event::Key};
 
-pub fn handler(_key: Key, _app: &mut App) {}
+pub fn handler(key: Key, app: &mut App) {
+    match key {
+        k if common_key_events::left_event(k) => app.seek_to_section(false),
+        k if common_key_events::right_event(k) => app.seek_to_section(true),
+        _ => {}
+    }
+}
@@ -1,9 +1,11 @@
-use rspotify::prelude::PlayableId;
+use rspotify::prelude::{PlayContextId, PlayableId};
 
 use super::common_key_events;
-use crate::app::{ActiveBlock, App, ArtistBlock, ItemTableContext, RecommendationsContext};
+use crate::app::{
+    ActiveBlock, App, ArtistBlock, ArtistDiscovery, ItemTableContext, RecommendationsContext,
+};
 use crate::event::Key;
-use crate::network::IoEvent;
+use crate::network::{IoEvent, PlaybackOffset};
 
 fn handle_down_press_on_selected_block(app: &mut App) {
     if let Some(artist) = &mut app.artist {
@@ -17,10 +19,11 @@ fn handle_down_press_on_selected_block(app: &mut App) {
             }
             ArtistBlock::Albums => {
                 let next_index = common_key_events::on_down_press_handler(
-                    &artist.albums.items,
+                    &artist.albums(),
                     Some(artist.selected_album_index),
                 );
                 artist.selected_album_index = next_index;
+                app.fetch_more_artist_albums();
             }
             ArtistBlock::RelatedArtists => {
                 let next_index = common_key_events::on_down_press_handler(
@@ -63,7 +66,7 @@ fn handle_up_press_on_selected_block(app: &mut App) {
             }
             ArtistBlock::Albums => {
                 let next_index = common_key_events::on_up_press_handler(
-                    &artist.albums.items,
+                    &artist.albums(),
                     Some(artist.selected_album_index),
                 );
                 artist.selected_album_index = next_index;
@@ -125,7 +128,7 @@ fn handle_middle_press_on_selected_block(app: &mut App) {
                 artist.selected_top_track_index = next_index;
             }
             ArtistBlock::Albums => {
-                let next_index = common_key_events::on_middle_press_handler(&artist.albums.items);
+                let next_index = common_key_events::on_middle_press_handler(&artist.albums());
                 artist.selected_album_index = next_index;
             }
             ArtistBlock::RelatedArtists => {
@@ -146,8 +149,9 @@ fn handle_low_press_on_selected_block(app: &mut App) {
                 artist.selected_top_track_index = next_index;
             }
             ArtistBlock::Albums => {
-                let next_index = common_key_events::on_low_press_handler(&artist.albums.items);
+                let next_index = common_key_events::on_low_press_handler(&artist.albums());
                 artist.selected_album_index = next_index;
+                app.fetch_more_artist_albums();
             }
             ArtistBlock::RelatedArtists => {
                 let next_index = common_key_events::on_low_press_handler(&artist.related_artists);
@@ -198,16 +202,11 @@ fn handle_enter_event_on_selected_block(app: &mut App) {
                     .collect();
                 app.dispatch(IoEvent::StartPlayablesPlayback {
                     playable_ids,
-                    offset: Some(selected_index as u32),
+                    offset: Some(PlaybackOffset::Index(selected_index as u32)),
                 });
             }
             ArtistBlock::Albums => {
-                if let Some(selected_album) = artist
-                    .albums
-                    .items
-                    .get(artist.selected_album_index)
-                    .cloned()
-                {
+                if let Some(selected_album) = artist.selected_album().cloned() {
                     app.item_table.context = Some(ItemTableContext::AlbumSearch);
                     app.dispatch(IoEvent::GetAlbumTracks {
                         album: Box::new(selected_album),
@@ -310,6 +309,26 @@ pub fn handler(key: Key, app: &mut App) {
             ArtistBlock::RelatedArtists => app.user_follow_artists(ActiveBlock::ArtistBlock),
             _ => (),
         },
+        Key::Char('F') => app.toggle_follow_viewed_artist(),
+        k if k == app.user_config.keys.search_artist_playlists => {
+            app.search_from_artist(ArtistDiscovery::Playlists)
+        }
+        k if k == app.user_config.keys.search_artist_appears_on => {
+            app.search_from_artist(ArtistDiscovery::AppearsOn)
+        }
+        Key::Char('t') if artist.artist_selected_block == ArtistBlock::Albums => {
+            app.cycle_artist_album_type()
+        }
+        k if k == app.user_config.keys.next_page
+            && artist.artist_selected_block == ArtistBlock::Albums =>
+        {
+            app.artist_albums_next_page()
+        }
+        k if k == app.user_config.keys.previous_page
+            && artist.artist_selected_block == ArtistBlock::Albums =>
+        {
+            app.artist_albums_previous_page()
+        }
         Key::Char('D') => match artist.artist_selected_block {
             ArtistBlock::Albums => app.current_user_saved_album_delete(ActiveBlock::ArtistBlock),
             ArtistBlock::RelatedArtists => app.user_unfollow_artists(ActiveBlock::ArtistBlock),
@@ -326,6 +345,15 @@ pub fn handler(key: Key, app: &mut App) {
                     app.dispatch(IoEvent::AddItemToQueue { playable_id });
                 }
             }
+            ArtistBlock::Albums => {
+                if let Some(album_id) = artist
+                    .albums()
+                    .get(artist.selected_album_index)
+                    .and_then(|album| album.id.clone())
+                {
+                    app.queue_context(PlayContextId::Album(album_id));
+                }
+            }
             _ => (),
         },
         _ => {}
@@ -66,98 +66,109 @@ pub fn on_high_press_handler() -> usize {
     0
 }
 
+// Both stay at the top of an empty list, which a view can be opened with
 pub fn on_middle_press_handler<T>(selection_data: &[T]) -> usize {
-    let mut index = selection_data.len() / 2;
+    let index = selection_data.len() / 2;
     if selection_data.len() % 2 == 0 {
-        index -= 1;
+        index.saturating_sub(1)
+    } else {
+        index
     }
-    index
 }
 
 pub fn on_low_press_handler<T>(selection_data: &[T]) -> usize {
-    selection_data.len() - 1
+    selection_data.len().saturating_sub(1)
+}
+
+// The block each route focuses by default: where moving right from the sidebar lands, and where
+// Esc puts the focus back to. The last few take up the whole screen, so they are never reached
+// from the sidebar.
+const DEFAULT_BLOCKS: [(RouteId, ActiveBlock); 20] = [
+    (RouteId::AlbumTracks, ActiveBlock::AlbumTracks),
+    (RouteId::ItemTable, ActiveBlock::ItemTable),
+    (RouteId::Podcasts, ActiveBlock::Podcasts),
+    (RouteId::Recommendations, ActiveBlock::ItemTable),
+    (RouteId::AlbumList, ActiveBlock::AlbumList),
+    (RouteId::PodcastEpisodes, ActiveBlock::EpisodeTable),
+    (RouteId::MadeForYou, ActiveBlock::MadeForYou),
+    (RouteId::Artists, ActiveBlock::Artists),
+    (RouteId::RecentlyPlayed, ActiveBlock::RecentlyPlayed),
+    (RouteId::Search, ActiveBlock::SearchResultBlock),
+    (RouteId::Artist, ActiveBlock::ArtistBlock),
+    (RouteId::Home, ActiveBlock::Home),
+    (RouteId::Lyrics, ActiveBlock::Lyrics),
+    (
+        RouteId::NotificationHistory,
+        ActiveBlock::NotificationHistory,
+    ),
+    (RouteId::TopArtists, ActiveBlock::TopArtists),
+    (RouteId::FinishedShows, ActiveBlock::FinishedShows),
+    (RouteId::Analysis, ActiveBlock::Analysis),
+    (RouteId::BasicView, ActiveBlock::BasicView),
+    (RouteId::SelectedDevice, ActiveBlock::SelectDevice),
+    (RouteId::Error, ActiveBlock::Error),
+];
+
+// The blocks of the sidebar in the order moving left tries them
+const SIDEBAR_BLOCKS: [ActiveBlock; 2] = [ActiveBlock::Library, ActiveBlock::MyPlaylists];
+
+// Which blocks of the sidebar have anything to show
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SidebarContent {
+    pub library: bool,
+    pub playlists: bool,
+}
+
+impl SidebarContent {
+    pub fn new(app: &App) -> Self {
+        SidebarContent {
+            // The library always lists its options
+            library: true,
+            playlists: !app.visible_playlist_indices().is_empty(),
+        }
+    }
+
+    fn has(&self, block: ActiveBlock) -> bool {
+        match block {
+            ActiveBlock::Library => self.library,
+            ActiveBlock::MyPlaylists => self.playlists,
+            _ => false,
+        }
+    }
+}
+
+pub fn default_block(route: &RouteId) -> Option<ActiveBlock> {
+    DEFAULT_BLOCKS
+        .iter()
+        .find(|(id, _)| id == route)
+        .map(|(_, block)| *block)
+}
+
+// Where moving left lands. A block with nothing in it would seem to swallow keys, so it's skipped
+// for the nearest one with content. With neither, it's the library, which says it's empty.
+pub fn sidebar_block(content: SidebarContent) -> ActiveBlock {
+    SIDEBAR_BLOCKS
+        .into_iter()
+        .find(|block| content.has(*block))
+        .unwrap_or(ActiveBlock::Library)
 }
 
 pub fn handle_right_event(app: &mut App) {
-    match app.get_current_route().hovered_block {
-        ActiveBlock::MyPlaylists | ActiveBlock::Library => match app.get_current_route().id {
-            RouteId::AlbumTracks => {
-                app.set_current_route_state(
-                    Some(ActiveBlock::AlbumTracks),
-                    Some(ActiveBlock::AlbumTracks),
-                );
-            }
-            RouteId::ItemTable => {
-                app.set_current_route_state(
-                    Some(ActiveBlock::ItemTable),
-                    Some(ActiveBlock::ItemTable),
-                );
-            }
-            RouteId::Podcasts => {
-                app.set_current_route_state(
-                    Some(ActiveBlock::Podcasts),
-                    Some(ActiveBlock::Podcasts),
-                );
-            }
-            RouteId::Recommendations => {
-                app.set_current_route_state(
-                    Some(ActiveBlock::ItemTable),
-                    Some(ActiveBlock::ItemTable),
-                );
-            }
-            RouteId::AlbumList => {
-                app.set_current_route_state(
-                    Some(ActiveBlock::AlbumList),
-                    Some(ActiveBlock::AlbumList),
-                );
-            }
-            RouteId::PodcastEpisodes => {
-                app.set_current_route_state(
-                    Some(ActiveBlock::EpisodeTable),
-                    Some(ActiveBlock::EpisodeTable),
-                );
-            }
-            RouteId::MadeForYou => {
-                app.set_current_route_state(
-                    Some(ActiveBlock::MadeForYou),
-                    Some(ActiveBlock::MadeForYou),
-                );
-            }
-            RouteId::Artists => {
-                app.set_current_route_state(Some(ActiveBlock::Artists), Some(ActiveBlock::Artists));
-            }
-            RouteId::RecentlyPlayed => {
-                app.set_current_route_state(
-                    Some(ActiveBlock::RecentlyPlayed),
-                    Some(ActiveBlock::RecentlyPlayed),
-                );
-            }
-            RouteId::Search => {
-                app.set_current_route_state(
-                    Some(ActiveBlock::SearchResultBlock),
-                    Some(ActiveBlock::SearchResultBlock),
-                );
-            }
-            RouteId::Artist => app.set_current_route_state(
-                Some(ActiveBlock::ArtistBlock),
-                Some(ActiveBlock::ArtistBlock),
-            ),
-            RouteId::Home => {
-                app.set_current_route_state(Some(ActiveBlock::Home), Some(ActiveBlock::Home));
-            }
-            RouteId::SelectedDevice => {}
-            RouteId::Error => {}
-            RouteId::Analysis => {}
-            RouteId::BasicView => {}
-            RouteId::Dialog => {}
-        },
-        _ => {}
-    };
+    if !matches!(
+        app.get_current_route().hovered_block,
+        ActiveBlock::MyPlaylists | ActiveBlock::Library
+    ) {
+        return;
+    }
+    if let Some(block) = default_block(&app.get_current_route().id) {
+        app.set_current_route_state(Some(block), Some(block));
+    }
 }
 
 pub fn handle_left_event(app: &mut App) {
     // TODO: This should send you back to either library or playlist based on last selection
-    app.set_current_route_state(Some(ActiveBlock::Empty), Some(ActiveBlock::Library));
+    let block = sidebar_block(SidebarContent::new(app));
+    app.set_current_route_state(Some(ActiveBlock::Empty), Some(block));
 }
 
 #[cfg(test)]
@@ -193,4 +204,57 @@ mod tests {
         let next_index = on_up_press_handler(&data, Some(index));
         assert_eq!(next_index, data.len() - 1);
     }
+
+    #[test]
+    fn middle_and_low_in_an_empty_list() {
+        let data: Vec<&str> = vec![];
+        assert_eq!(on_middle_press_handler(&data), 0);
+        assert_eq!(on_low_press_handler(&data), 0);
+
+        assert_eq!(on_middle_press_handler(&["Choice 1", "Choice 2"]), 0);
+        assert_eq!(
+            on_middle_press_handler(&["Choice 1", "Choice 2", "Choice 3"]),
+            1
+        );
+        assert_eq!(on_low_press_handler(&["Choice 1", "Choice 2"]), 1);
+    }
+
+    #[test]
+    fn moving_left_skips_empty_blocks() {
+        let cases = [
+            (true, true, ActiveBlock::Library),
+            (true, false, ActiveBlock::Library),
+            (false, true, ActiveBlock::MyPlaylists),
+            (false, false, ActiveBlock::Library),
+        ];
+        for (library, playlists, expected) in cases {
+            let content = SidebarContent { library, playlists };
+            assert_eq!(sidebar_block(content), expected, "{:?}", content);
+        }
+    }
+
+    #[test]
+    fn moving_right_focuses_the_main_view() {
+        assert_eq!(
+            default_block(&RouteId::Recommendations),
+            Some(ActiveBlock::ItemTable)
+        );
+        assert_eq!(
+            default_block(&RouteId::PodcastEpisodes),
+            Some(ActiveBlock::EpisodeTable)
+        );
+        // A dialog's block holds what it is about, so it has no default
+        assert_eq!(default_block(&RouteId::Dialog), None);
+
+        // Only from the sidebar
+        let mut app = App::default();
+        app.push_navigation_stack(RouteId::Home, ActiveBlock::Empty);
+        app.set_current_route_state(None, Some(ActiveBlock::PlayBar));
+        handle_right_event(&mut app);
+        assert_eq!(app.get_current_route().hovered_block, ActiveBlock::PlayBar);
+        app.set_current_route_state(None, Some(ActiveBlock::MyPlaylists));
+        handle_right_event(&mut app);
+        assert_eq!(app.get_current_route().active_block, ActiveBlock::Home);
+        assert_eq!(app.get_current_route().hovered_block, ActiveBlock::Home);
+    }
 }
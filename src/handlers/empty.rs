@@ -22,10 +22,15 @@ pub fn handler(key: Key, app: &mut App) {
             | ActiveBlock::Podcasts
             | ActiveBlock::EpisodeTable
             | ActiveBlock::Home
+            | ActiveBlock::Lyrics
             | ActiveBlock::MadeForYou
+            | ActiveBlock::NotificationHistory
+            | ActiveBlock::FinishedShows
             | ActiveBlock::MyPlaylists
             | ActiveBlock::RecentlyPlayed
-            | ActiveBlock::ItemTable => {
+            | ActiveBlock::ItemTable
+                if !app.is_playbar_hidden =>
+            {
                 app.set_current_route_state(None, Some(ActiveBlock::PlayBar));
             }
             _ => {}
@@ -47,10 +52,15 @@ pub fn handler(key: Key, app: &mut App) {
             | ActiveBlock::Podcasts
             | ActiveBlock::EpisodeTable
             | ActiveBlock::Home
+            | ActiveBlock::Lyrics
             | ActiveBlock::MadeForYou
+            | ActiveBlock::NotificationHistory
+            | ActiveBlock::FinishedShows
             | ActiveBlock::RecentlyPlayed
             | ActiveBlock::ItemTable => {
-                app.set_current_route_state(None, Some(ActiveBlock::Library));
+                let block =
+                    common_key_events::sidebar_block(common_key_events::SidebarContent::new(app));
+                app.set_current_route_state(None, Some(block));
             }
             _ => {}
         },
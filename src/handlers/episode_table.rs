@@ -1,3 +1,4 @@
+use rspotify::model::show::ResumePoint;
 use rspotify::prelude::PlayableId;
 
 use super::{
@@ -6,7 +7,7 @@ use super::{
 };
 use crate::app::ActiveBlock;
 use crate::event::Key;
-use crate::network::IoEvent;
+use crate::network::{IoEvent, PlaybackOffset};
 
 pub fn handler(key: Key, app: &mut App) {
     match key {
@@ -78,13 +79,33 @@ fn on_enter(app: &mut App) {
             .map(|episode| episode.id.clone())
             .map(PlayableId::Episode)
             .collect::<Vec<_>>();
-        app.dispatch(IoEvent::StartPlayablesPlayback {
-            playable_ids,
-            offset: Some(app.episode_list_index as u32),
-        });
+        let offset = PlaybackOffset::Index(app.episode_list_index as u32);
+        let resume_point = episodes
+            .items
+            .get(app.episode_list_index)
+            .and_then(|episode| episode.resume_point.as_ref());
+        match resume_position_ms(resume_point) {
+            Some(position_ms) => app.dispatch(IoEvent::ResumeEpisodePlayback {
+                playable_ids,
+                offset,
+                position_ms,
+            }),
+            None => app.dispatch(IoEvent::StartPlayablesPlayback {
+                playable_ids,
+                offset: Some(offset),
+            }),
+        }
     }
 }
 
+// Where to start an episode that was listened to part of the way. Fully played episodes start
+// over from the beginning.
+fn resume_position_ms(resume_point: Option<&ResumePoint>) -> Option<u32> {
+    let resume_point = resume_point.filter(|resume_point| !resume_point.fully_played)?;
+    let position_ms = resume_point.resume_position.num_milliseconds();
+    (position_ms > 0).then_some(position_ms as u32)
+}
+
 fn handle_prev_event(app: &mut App) {
     app.get_episode_table_previous();
 }
@@ -140,3 +161,61 @@ fn toggle_sort_by_date(app: &mut App) {
         app.episode_list_index = 0;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{episode_json, episode_page};
+    use crate::user_config::UserConfig;
+    use chrono::Utc;
+    use rspotify::model::{show::SimplifiedEpisode, Page};
+
+    fn episodes(resume_point: serde_json::Value) -> Page<SimplifiedEpisode> {
+        episode_page(vec![
+            episode_json("512ojhOuo1ktJprKbVcKyQ", resume_point.clone()),
+            episode_json("4rOoJ6Egrf8K2IrywzwOMk", resume_point),
+        ])
+    }
+
+    #[test]
+    fn resumes_episodes_where_they_were_left() {
+        let (io_tx, mut io_rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut app = App::new(io_tx, UserConfig::new(), Utc::now());
+        app.episode_list_index = 1;
+
+        app.library
+            .show_episodes
+            .add_pages(episodes(serde_json::json!({
+                "fully_played": false,
+                "resume_position_ms": 600_000,
+            })));
+        handler(Key::Enter, &mut app);
+        assert!(matches!(
+            io_rx.try_recv(),
+            Ok(IoEvent::ResumeEpisodePlayback {
+                playable_ids,
+                offset: PlaybackOffset::Index(1),
+                position_ms: 600_000,
+            }) if playable_ids.len() == 2
+        ));
+
+        // Played to the end, or not started, so it starts from the beginning
+        let resume_points = [
+            serde_json::json!({"fully_played": true, "resume_position_ms": 1_800_000}),
+            serde_json::json!({"fully_played": false, "resume_position_ms": 0}),
+            serde_json::Value::Null,
+        ];
+        for resume_point in resume_points {
+            app.library.show_episodes = Default::default();
+            app.library.show_episodes.add_pages(episodes(resume_point));
+            handler(Key::Enter, &mut app);
+            assert!(matches!(
+                io_rx.try_recv(),
+                Ok(IoEvent::StartPlayablesPlayback {
+                    offset: Some(PlaybackOffset::Index(1)),
+                    ..
+                })
+            ));
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use super::common_key_events;
+use crate::{app::App, event::Key};
+
+pub fn handler(key: Key, app: &mut App) {
+    let Some(finished_shows) = &mut app.finished_shows else {
+        if common_key_events::left_event(key) {
+            common_key_events::handle_left_event(app);
+        }
+        return;
+    };
+    let shows = &finished_shows.shows;
+
+    match key {
+        k if common_key_events::left_event(k) => common_key_events::handle_left_event(app),
+        k if common_key_events::down_event(k) => {
+            finished_shows.selected_index = common_key_events::on_down_press_handler(
+                shows,
+                Some(finished_shows.selected_index),
+            );
+        }
+        k if common_key_events::up_event(k) => {
+            finished_shows.selected_index =
+                common_key_events::on_up_press_handler(shows, Some(finished_shows.selected_index));
+        }
+        k if common_key_events::high_event(k) => {
+            finished_shows.selected_index = common_key_events::on_high_press_handler();
+        }
+        k if common_key_events::middle_event(k) => {
+            finished_shows.selected_index = common_key_events::on_middle_press_handler(shows);
+        }
+        k if common_key_events::low_event(k) => {
+            finished_shows.selected_index = common_key_events::on_low_press_handler(shows);
+        }
+        Key::Char('V') => finished_shows.toggle_selected(),
+        Key::Enter => app.remove_finished_shows(),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{ActiveBlock, FinishedShows, RouteId};
+    use crate::network::IoEvent;
+    use crate::user_config::UserConfig;
+    use chrono::Utc;
+    use rspotify::model::SimplifiedShow;
+    use tokio::sync::mpsc::unbounded_channel;
+
+    fn show(id: &str) -> SimplifiedShow {
+        serde_json::from_value(serde_json::json!({
+            "available_markets": [], "copyrights": [], "description": "", "explicit": false,
+            "external_urls": {}, "href": "", "id": id, "images": [], "languages": [],
+            "media_type": "audio", "name": id, "publisher": "",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn removes_the_shows_left_checked() {
+        let (io_tx, mut io_rx) = unbounded_channel();
+        let mut app = App::new(io_tx, UserConfig::new(), Utc::now());
+        app.push_navigation_stack(RouteId::Podcasts, ActiveBlock::Podcasts);
+        app.push_navigation_stack(RouteId::FinishedShows, ActiveBlock::FinishedShows);
+        app.finished_shows = Some(FinishedShows::new(vec![
+            show("4rOoJ6Egrf8K2IrywzwOMk"),
+            show("5CfCWKI5pZ28U0uOzXkDHe"),
+            show("6ShFMYxeDNMo15COLObDvC"),
+        ]));
+
+        // Uncheck the second, then check it and uncheck the last one
+        handler(Key::Down, &mut app);
+        handler(Key::Char('V'), &mut app);
+        handler(Key::Char('V'), &mut app);
+        handler(Key::Char('L'), &mut app);
+        handler(Key::Char('V'), &mut app);
+        handler(Key::Enter, &mut app);
+
+        match io_rx.try_recv() {
+            Ok(IoEvent::RemoveSavedShows { show_ids }) => {
+                assert_eq!(
+                    show_ids,
+                    [
+                        show("4rOoJ6Egrf8K2IrywzwOMk").id,
+                        show("5CfCWKI5pZ28U0uOzXkDHe").id
+                    ]
+                );
+            }
+            event => panic!("unexpected {:?}", event),
+        }
+        assert_eq!(app.get_current_route().id, RouteId::Podcasts);
+    }
+
+    #[test]
+    fn nothing_is_removed_when_every_show_is_unchecked() {
+        let (io_tx, mut io_rx) = unbounded_channel();
+        let mut app = App::new(io_tx, UserConfig::new(), Utc::now());
+        app.push_navigation_stack(RouteId::FinishedShows, ActiveBlock::FinishedShows);
+        app.finished_shows = Some(FinishedShows::new(vec![show("4rOoJ6Egrf8K2IrywzwOMk")]));
+
+        handler(Key::Char('V'), &mut app);
+        handler(Key::Enter, &mut app);
+        assert!(io_rx.try_recv().is_err());
+        assert!(app.finished_shows.is_none());
+    }
+}
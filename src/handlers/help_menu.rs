@@ -1,4 +1,4 @@
-use super::common_key_events;
+use super::{common_key_events, input};
 use crate::{app::App, event::Key};
 
 #[derive(PartialEq)]
@@ -8,6 +8,21 @@ enum Direction {
 }
 
 pub fn handler(key: Key, app: &mut App) {
+    if app.is_filtering_help() {
+        match key {
+            Key::Enter => app.stop_help_filter(true),
+            Key::Esc => app.stop_help_filter(false),
+            // Letters are typed, so only the arrows page while typing
+            Key::Down => move_page(Direction::Down, app),
+            Key::Up => move_page(Direction::Up, app),
+            _ => {
+                input::handler(key, app);
+                app.update_help_filter();
+            }
+        }
+        return;
+    }
+
     match key {
         k if common_key_events::down_event(k) => {
             move_page(Direction::Down, app);
@@ -35,3 +50,75 @@ fn move_page(direction: Direction, app: &mut App) {
     }
     app.calculate_help_menu_offset();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{ActiveBlock, InputContext};
+    use crate::handlers::handle_app;
+    use crate::user_config::UserConfig;
+    use chrono::Utc;
+
+    fn help_app() -> App {
+        let mut app = App::new(
+            tokio::sync::mpsc::unbounded_channel().0,
+            UserConfig::new(),
+            Utc::now(),
+        );
+        app.help_menu_max_lines = 10;
+        app.set_current_route_state(Some(ActiveBlock::MadeForYou), None);
+        app.open_help();
+        app
+    }
+
+    #[test]
+    fn typing_filters_the_rows() {
+        let mut app = help_app();
+        let all = app.help_docs().len();
+
+        handle_app(Key::Char('/'), &mut app);
+        for c in "volume".chars() {
+            handler(Key::Char(c), &mut app);
+        }
+        assert_eq!(app.help_filter, "volume");
+        let rows = app.help_docs();
+        assert!(rows.len() < all);
+        assert!(rows
+            .iter()
+            .all(|row| row[0].to_lowercase().contains("volume")));
+
+        // Kept once typed, with the input handed back to search
+        handler(Key::Enter, &mut app);
+        assert_eq!(app.input_context, InputContext::Search);
+        assert!(app.input.is_empty());
+        assert_eq!(app.help_filter, "volume");
+
+        handle_app(Key::Char('/'), &mut app);
+        assert_eq!(app.input.iter().collect::<String>(), "volume");
+        handler(Key::Esc, &mut app);
+        assert!(app.help_filter.is_empty());
+        assert_eq!(app.help_docs().len(), all);
+    }
+
+    #[test]
+    fn pages_stop_at_the_last_filtered_row() {
+        let mut app = help_app();
+        app.help_filter = String::from("playlist");
+        let rows = app.help_docs().len() as u32;
+        // The filter takes a few of the lines
+        let lines = 10 - crate::app::HELP_FILTER_HEIGHT;
+
+        for _ in 0..20 {
+            handler(Key::Down, &mut app);
+        }
+        assert_eq!(app.help_menu_page, (rows - 1) / lines);
+        assert!(app.help_menu_offset < rows);
+    }
+
+    #[test]
+    fn lists_the_keys_of_the_block_it_was_opened_from_first() {
+        let app = help_app();
+        assert_eq!(app.help_context, Some(ActiveBlock::MadeForYou));
+        assert_eq!(app.help_docs()[0][2], "Made For You");
+    }
+}
@@ -1,6 +1,6 @@
 extern crate unicode_width;
 
-use super::super::app::{ActiveBlock, App, RouteId};
+use super::super::app::{ActiveBlock, App, InputContext, RouteId};
 use crate::event::Key;
 use crate::network::IoEvent;
 use rspotify::model::idtypes::*;
@@ -66,13 +66,31 @@ pub fn handler(key: Key, app: &mut App) {
                 app.input_cursor_position += compute_character_width(next_c);
             }
         }
-        Key::Esc => {
-            app.set_current_route_state(Some(ActiveBlock::Empty), Some(ActiveBlock::Library));
-        }
+        Key::Esc => match app.input_context {
+            InputContext::Search => {
+                app.set_current_route_state(Some(ActiveBlock::Empty), Some(ActiveBlock::Library));
+            }
+            InputContext::RenamePlaylist { .. } | InputContext::EditPlaylistDescription { .. } => {
+                leave_playlist_edit(app)
+            }
+            // The help menu and the device list take Esc and Enter themselves
+            InputContext::FilterHelp | InputContext::FindDevice => {}
+        },
         Key::Enter => {
             let input_str: String = app.input.iter().collect();
 
-            process_input(app, input_str);
+            match app.input_context.clone() {
+                InputContext::Search => process_input(app, input_str),
+                InputContext::RenamePlaylist { playlist_id } => {
+                    app.rename_playlist(playlist_id, input_str);
+                    leave_playlist_edit(app);
+                }
+                InputContext::EditPlaylistDescription { playlist_id } => {
+                    app.change_playlist_description(playlist_id, input_str);
+                    leave_playlist_edit(app);
+                }
+                InputContext::FilterHelp | InputContext::FindDevice => {}
+            }
         }
         Key::Char(c) => {
             app.input.insert(app.input_idx, c);
@@ -95,6 +113,16 @@ pub fn handler(key: Key, app: &mut App) {
     }
 }
 
+// Hand the input block back to search and return to the playlist sidebar
+fn leave_playlist_edit(app: &mut App) {
+    app.input_context = InputContext::Search;
+    app.set_input("");
+    app.set_current_route_state(
+        Some(ActiveBlock::MyPlaylists),
+        Some(ActiveBlock::MyPlaylists),
+    );
+}
+
 fn process_input(app: &mut App, input: String) {
     // Don't do anything if there is no input
     if input.is_empty() {
@@ -113,6 +141,7 @@ fn process_input(app: &mut App, input: String) {
     // Default fallback behavior: treat the input as a raw search phrase.
     app.dispatch(IoEvent::GetSearchResults {
         search_term: input,
+        types: None,
         country: app.get_user_country(),
     });
     app.push_navigation_stack(RouteId::Search, ActiveBlock::SearchResultBlock);
@@ -157,10 +186,7 @@ fn attempt_process_uri(app: &mut App, input: &str, base: &str, sep: &str) -> boo
     let (playlist_id, matched) = spotify_resource_id(base, input, sep, "playlist");
     if matched {
         let playlist_id = PlaylistId::from_id(&playlist_id).unwrap();
-        app.dispatch(IoEvent::GetPlaylistItems {
-            playlist_id,
-            offset: 0,
-        });
+        app.open_playlist(playlist_id.into_static());
         return true;
     }
 
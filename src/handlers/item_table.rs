@@ -1,9 +1,10 @@
 use super::{
-    super::app::{App, ItemTable, ItemTableContext, RecommendationsContext},
+    super::app::{App, ItemTable, ItemTableContext, RecommendationsContext, TrackFeature},
     common_key_events,
 };
 use crate::event::Key;
-use crate::network::IoEvent;
+use crate::network::{IoEvent, PlaybackOffset};
+use crate::notifications::Severity;
 use rand::{thread_rng, Rng};
 use rspotify::model::{idtypes::*, PlayableItem};
 use spotify_tui_util::ToStatic;
@@ -70,6 +71,7 @@ pub fn handler(key: Key, app: &mut App) {
                     ItemTableContext::SavedTracks => {
                         app.get_current_user_saved_tracks_next();
                     }
+                    ItemTableContext::TopTracks => app.top_items_next_page(),
                     ItemTableContext::AlbumSearch => {}
                     ItemTableContext::PlaylistSearch => {}
                     ItemTableContext::MadeForYou => {
@@ -126,6 +128,7 @@ pub fn handler(key: Key, app: &mut App) {
                     ItemTableContext::SavedTracks => {
                         app.get_current_user_saved_tracks_previous();
                     }
+                    ItemTableContext::TopTracks => app.top_items_previous_page(),
                     ItemTableContext::AlbumSearch => {}
                     ItemTableContext::PlaylistSearch => {}
                     ItemTableContext::MadeForYou => {
@@ -154,9 +157,24 @@ pub fn handler(key: Key, app: &mut App) {
             };
         }
         Key::Char('s') => handle_save_track_event(app),
+        Key::Char('w') => app.follow_item_table_playlist(),
+        Key::Char('D') => app.start_item_table_playlist_unfollow(),
         Key::Char('S') => play_random_song(app),
+        Key::Char('V') => app.item_table.toggle_row_selection(),
+        Key::Char('i') => app.toggle_features_popup(),
+        Key::Char('T') => app.sort_item_table_by_feature(TrackFeature::Tempo),
+        Key::Char('E') => app.sort_item_table_by_feature(TrackFeature::Energy),
+        Key::Char('O') => app.cycle_item_table_sort(),
+        Key::Char('t') if app.item_table.context == Some(ItemTableContext::TopTracks) => {
+            app.cycle_top_items_time_range()
+        }
+        Key::Tab if app.item_table.context == Some(ItemTableContext::TopTracks) => {
+            app.toggle_top_item_kind()
+        }
+        Key::Char('R') => app.reverse_item_table_sort(),
         k if k == app.user_config.keys.jump_to_end => jump_to_end(app),
         k if k == app.user_config.keys.jump_to_start => jump_to_start(app),
+        k if k == app.user_config.keys.jump_to_playing => app.jump_to_playing_item(),
         //recommended song radio
         Key::Char('r') => {
             handle_recommended_tracks(app);
@@ -164,6 +182,7 @@ pub fn handler(key: Key, app: &mut App) {
         _ if key == app.user_config.keys.add_item_to_queue => on_queue(app),
         _ => {}
     }
+    app.fetch_selected_track_features();
 }
 
 fn play_random_song(app: &mut App) {
@@ -179,9 +198,9 @@ fn play_random_song(app: &mut App) {
                         let play_context_id = PlayContextId::Playlist(selected_playlist.id.clone());
                         app.dispatch(IoEvent::StartContextPlayback {
                             play_context_id,
-                            offset: Some(
-                                thread_rng().gen_range(0..selected_playlist.tracks.total) as u32
-                            ),
+                            offset: Some(PlaybackOffset::Index(
+                                thread_rng().gen_range(0..selected_playlist.tracks.total) as u32,
+                            )),
                         });
                     }
                 }
@@ -198,7 +217,23 @@ fn play_random_song(app: &mut App) {
                     let rand_idx = thread_rng().gen_range(0..playable_ids.len());
                     app.dispatch(IoEvent::StartPlayablesPlayback {
                         playable_ids,
-                        offset: Some(rand_idx as u32),
+                        offset: Some(PlaybackOffset::Index(rand_idx as u32)),
+                    })
+                }
+            }
+            ItemTableContext::TopTracks => {
+                let playable_ids = app
+                    .item_table
+                    .items
+                    .iter()
+                    .filter_map(|item| item.id())
+                    .map(|id| id.into_static())
+                    .collect::<Vec<_>>();
+                if !playable_ids.is_empty() {
+                    let rand_idx = thread_rng().gen_range(0..playable_ids.len());
+                    app.dispatch(IoEvent::StartPlayablesPlayback {
+                        playable_ids,
+                        offset: Some(PlaybackOffset::Index(rand_idx as u32)),
                     })
                 }
             }
@@ -206,7 +241,7 @@ fn play_random_song(app: &mut App) {
             ItemTableContext::PlaylistSearch => {
                 if let (Some(selected_playlist_index), Some(playlist_result)) = (
                     &app.search_results.selected_playlists_index,
-                    &app.search_results.playlists,
+                    app.search_results.playlists.get_results(None),
                 ) {
                     if let Some(selected_playlist) = playlist_result
                         .items
@@ -215,9 +250,9 @@ fn play_random_song(app: &mut App) {
                         let play_context_id = PlayContextId::Playlist(selected_playlist.id.clone());
                         app.dispatch(IoEvent::StartContextPlayback {
                             play_context_id,
-                            offset: Some(
-                                thread_rng().gen_range(0..selected_playlist.tracks.total) as u32
-                            ),
+                            offset: Some(PlaybackOffset::Index(
+                                thread_rng().gen_range(0..selected_playlist.tracks.total) as u32,
+                            )),
                         });
                     }
                 };
@@ -232,7 +267,9 @@ fn play_random_song(app: &mut App) {
                     let play_context_id = PlayContextId::Playlist(playlist.id.clone());
                     app.dispatch(IoEvent::StartContextPlayback {
                         play_context_id,
-                        offset: Some(thread_rng().gen_range(0..playlist.tracks.total)),
+                        offset: Some(PlaybackOffset::Index(
+                            thread_rng().gen_range(0..playlist.tracks.total),
+                        )),
                     })
                 };
             }
@@ -251,7 +288,7 @@ fn handle_save_track_event(app: &mut App) {
         };
         track_id.into_static()
     };
-    app.dispatch(IoEvent::ToggleSaveTrack { track_id });
+    app.toggle_save_track(track_id);
 }
 
 fn handle_recommended_tracks(app: &mut App) {
@@ -301,6 +338,7 @@ fn jump_to_end(app: &mut App) {
             ItemTableContext::AlbumSearch => {}
             ItemTableContext::PlaylistSearch => {}
             ItemTableContext::MadeForYou => {}
+            ItemTableContext::TopTracks => {}
         },
         None => {}
     }
@@ -311,6 +349,7 @@ fn on_enter(app: &mut App) {
         context,
         selected_index,
         items,
+        ..
     } = &app.item_table;
     match &context {
         Some(context) => match context {
@@ -328,39 +367,28 @@ fn on_enter(app: &mut App) {
                     if let Some(play_context_id) = play_context_id {
                         app.dispatch(IoEvent::StartContextPlayback {
                             play_context_id,
-                            offset: Some(
-                                app.item_table.selected_index as u32 + app.playlist_offset,
-                            ),
+                            offset: Some(PlaybackOffset::Index(
+                                app.item_table.selected_position() as u32 + app.playlist_offset,
+                            )),
                         });
                     }
                 };
             }
-            ItemTableContext::RecommendedTracks => {
+            // Play the tracks in the order they are shown, which may have been sorted locally
+            ItemTableContext::RecommendedTracks
+            | ItemTableContext::SavedTracks
+            | ItemTableContext::TopTracks => {
+                let playable_ids = items
+                    .iter()
+                    .filter_map(|item| item.id())
+                    .map(|id| id.into_static())
+                    .collect::<Vec<_>>();
+
                 app.dispatch(IoEvent::StartPlayablesPlayback {
-                    playable_ids: app
-                        .recommended_tracks
-                        .iter()
-                        .filter_map(|x| x.id.clone())
-                        .map(PlayableId::Track)
-                        .collect::<Vec<_>>(),
-                    offset: Some(app.item_table.selected_index as u32),
+                    playable_ids,
+                    offset: Some(PlaybackOffset::Index(app.item_table.selected_index as u32)),
                 });
             }
-            ItemTableContext::SavedTracks => {
-                if let Some(saved_tracks) = &app.library.saved_tracks.get_results(None) {
-                    let playable_ids = saved_tracks
-                        .items
-                        .iter()
-                        .filter_map(|item| item.track.id.clone())
-                        .map(PlayableId::Track)
-                        .collect::<Vec<_>>();
-
-                    app.dispatch(IoEvent::StartPlayablesPlayback {
-                        playable_ids,
-                        offset: Some(app.item_table.selected_index as u32),
-                    });
-                };
-            }
             ItemTableContext::AlbumSearch => {}
             ItemTableContext::PlaylistSearch => {
                 let ItemTable {
@@ -371,7 +399,7 @@ fn on_enter(app: &mut App) {
                 if let Some(_track) = items.get(*selected_index) {
                     let play_context_id = match (
                         &app.search_results.selected_playlists_index,
-                        &app.search_results.playlists,
+                        app.search_results.playlists.get_results(None),
                     ) {
                         (Some(selected_playlist_index), Some(playlist_result)) => playlist_result
                             .items
@@ -384,7 +412,9 @@ fn on_enter(app: &mut App) {
                     if let Some(play_context_id) = play_context_id {
                         app.dispatch(IoEvent::StartContextPlayback {
                             play_context_id,
-                            offset: Some(app.item_table.selected_index as u32),
+                            offset: Some(PlaybackOffset::Index(
+                                app.item_table.selected_position() as u32
+                            )),
                         });
                     }
                 };
@@ -405,9 +435,9 @@ fn on_enter(app: &mut App) {
 
                     app.dispatch(IoEvent::StartContextPlayback {
                         play_context_id,
-                        offset: Some(
-                            app.item_table.selected_index as u32 + app.made_for_you_offset,
-                        ),
+                        offset: Some(PlaybackOffset::Index(
+                            app.item_table.selected_position() as u32 + app.made_for_you_offset,
+                        )),
                     });
                 }
             }
@@ -417,10 +447,15 @@ fn on_enter(app: &mut App) {
 }
 
 fn on_queue(app: &mut App) {
+    if !app.item_table.selected_rows.is_empty() {
+        queue_selected_rows(app);
+        return;
+    }
     let ItemTable {
         context,
         selected_index,
         items,
+        ..
     } = &app.item_table;
     match &context {
         Some(context) => match context {
@@ -435,7 +470,7 @@ fn on_queue(app: &mut App) {
             ItemTableContext::RecommendedTracks => {
                 if let Some(playable_id) = app
                     .recommended_tracks
-                    .get(app.item_table.selected_index)
+                    .get(app.item_table.selected_position())
                     .and_then(|track| track.id.clone().map(PlayableId::Track))
                 {
                     app.dispatch(IoEvent::AddItemToQueue { playable_id });
@@ -445,7 +480,7 @@ fn on_queue(app: &mut App) {
                 if let Some(page) = app.library.saved_tracks.get_results(None) {
                     if let Some(playable_id) = page
                         .items
-                        .get(app.item_table.selected_index)
+                        .get(app.item_table.selected_position())
                         .and_then(|saved_track| saved_track.track.id.clone().map(PlayableId::Track))
                     {
                         app.dispatch(IoEvent::AddItemToQueue { playable_id });
@@ -466,7 +501,7 @@ fn on_queue(app: &mut App) {
                     app.dispatch(IoEvent::AddItemToQueue { playable_id });
                 };
             }
-            ItemTableContext::MadeForYou => {
+            ItemTableContext::MadeForYou | ItemTableContext::TopTracks => {
                 if let Some(playable_id) = items
                     .get(*selected_index)
                     .and_then(|playable_item| playable_item.id().to_static())
@@ -479,6 +514,41 @@ fn on_queue(app: &mut App) {
     };
 }
 
+// Local files can't be queued, as they have no id
+fn queue_selected_rows(app: &mut App) {
+    let selected_items = app.item_table.selected_items();
+    let playable_ids = selected_items
+        .iter()
+        .filter_map(|item| item.id().to_static())
+        .collect::<Vec<_>>();
+    let skipped = selected_items.len() - playable_ids.len();
+    let queued = playable_ids.len();
+
+    for playable_id in playable_ids {
+        app.dispatch(IoEvent::AddItemToQueue { playable_id });
+    }
+    app.item_table.selected_rows.clear();
+
+    let items = |count: usize| if count == 1 { "item" } else { "items" };
+    if skipped == 0 {
+        app.notify(
+            Severity::Info,
+            format!("Queueing {} {}", queued, items(queued)),
+        );
+    } else {
+        app.notify(
+            Severity::Warning,
+            format!(
+                "Queueing {} {}, skipped {} local {}",
+                queued,
+                items(queued),
+                skipped,
+                if skipped == 1 { "file" } else { "files" }
+            ),
+        );
+    }
+}
+
 fn jump_to_start(app: &mut App) {
     match &app.item_table.context {
         Some(context) => match context {
@@ -503,7 +573,519 @@ fn jump_to_start(app: &mut App) {
             ItemTableContext::AlbumSearch => {}
             ItemTableContext::PlaylistSearch => {}
             ItemTableContext::MadeForYou => {}
+            ItemTableContext::TopTracks => {}
         },
         None => {}
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{ActiveBlock, ColumnSort, DialogContext, RouteId};
+    use crate::network::mock_api;
+    use crate::test_fixtures::full_track;
+    use crate::user_config::{ItemTableColumn, UserConfig};
+    use chrono::{DateTime, Duration, Utc};
+    use rspotify::model::{
+        audio::AudioFeatures, context::CurrentPlaybackContext, playlist::SimplifiedPlaylist,
+        track::FullTrack, Modality, Page,
+    };
+    use spotify_tui_util::PlaybleItemExt;
+
+    fn track(id: &str) -> PlayableItem {
+        PlayableItem::Track(full_track(id))
+    }
+
+    fn features(id: &str, tempo: f32) -> AudioFeatures {
+        AudioFeatures {
+            acousticness: 0.0,
+            analysis_url: String::new(),
+            danceability: 0.0,
+            duration: Duration::seconds(180),
+            energy: tempo / 200.0,
+            id: TrackId::from_id(id).unwrap().into_static(),
+            instrumentalness: 0.0,
+            key: 0,
+            liveness: 0.0,
+            loudness: 0.0,
+            mode: Modality::Major,
+            speechiness: 0.0,
+            tempo,
+            time_signature: 4,
+            track_href: String::new(),
+            valence: 0.0,
+        }
+    }
+
+    fn popular_track(id: &str, popularity: u32) -> PlayableItem {
+        PlayableItem::Track(FullTrack {
+            popularity,
+            ..full_track(id)
+        })
+    }
+
+    fn episode() -> PlayableItem {
+        PlayableItem::Episode(
+            serde_json::from_value(serde_json::json!({
+                "audio_preview_url": null,
+                "description": "",
+                "duration_ms": 1_800_000,
+                "explicit": false,
+                "external_urls": {},
+                "href": "",
+                "id": "512ojhOuo1ktJprKbVcKyQ",
+                "images": [],
+                "is_externally_hosted": false,
+                "is_playable": true,
+                "language": "en",
+                "languages": [],
+                "name": "Episode",
+                "release_date": "2020-01-01",
+                "release_date_precision": "day",
+                "resume_point": null,
+                "show": {
+                    "available_markets": [],
+                    "copyrights": [],
+                    "description": "",
+                    "explicit": false,
+                    "external_urls": {},
+                    "href": "",
+                    "id": "4rOoJ6Egrf8K2IrywzwOMk",
+                    "images": [],
+                    "languages": [],
+                    "media_type": "audio",
+                    "name": "Podcast",
+                    "publisher": "",
+                },
+            }))
+            .unwrap(),
+        )
+    }
+
+    fn added(days: i64) -> Option<DateTime<Utc>> {
+        DateTime::from_timestamp(1_700_000_000 + days * 86_400, 0)
+    }
+
+    fn item_names(app: &App) -> Vec<&str> {
+        app.item_table
+            .items
+            .iter()
+            .map(|item| item.name())
+            .collect()
+    }
+
+    #[test]
+    fn sort_by_columns() {
+        let mut app = App::default();
+        app.user_config.behavior.item_table_columns = vec![
+            ItemTableColumn::Title,
+            ItemTableColumn::Popularity,
+            ItemTableColumn::AddedAt,
+        ];
+        app.item_table.context = Some(ItemTableContext::MyPlaylists);
+        app.item_table.set_items_added_at(vec![
+            (popular_track("b", 50), added(2)),
+            (episode(), added(0)),
+            (popular_track("a", 90), None),
+            (popular_track("c", 50), added(1)),
+        ]);
+        app.item_table.selected_index = 3;
+
+        handler(Key::Char('O'), &mut app);
+        assert_eq!(item_names(&app), ["a", "b", "c", "Episode"]);
+        // The selection follows the selected item
+        assert_eq!(app.item_table.selected_index, 2);
+        assert_eq!(app.item_table.selected_position(), 3);
+
+        // Ties keep their order, and episodes have no popularity either way
+        handler(Key::Char('O'), &mut app);
+        assert_eq!(item_names(&app), ["b", "c", "a", "Episode"]);
+        handler(Key::Char('R'), &mut app);
+        assert_eq!(item_names(&app), ["a", "b", "c", "Episode"]);
+        assert_eq!(
+            app.item_table.column_sort,
+            Some(ColumnSort {
+                column: ItemTableColumn::Popularity,
+                ascending: false
+            })
+        );
+
+        handler(Key::Char('O'), &mut app);
+        assert_eq!(item_names(&app), ["Episode", "c", "b", "a"]);
+        assert_eq!(app.item_table.metadata(0).added_at, added(0));
+
+        // Back to the order of the playlist
+        handler(Key::Char('O'), &mut app);
+        assert_eq!(item_names(&app), ["b", "Episode", "a", "c"]);
+        assert_eq!(app.item_table.column_sort, None);
+        handler(Key::Char('R'), &mut app);
+        assert_eq!(item_names(&app), ["b", "Episode", "a", "c"]);
+
+        // Loading another page starts over unsorted
+        handler(Key::Char('O'), &mut app);
+        app.item_table
+            .set_items(vec![popular_track("z", 0), popular_track("y", 0)]);
+        assert_eq!(item_names(&app), ["z", "y"]);
+        assert_eq!(app.item_table.column_sort, None);
+    }
+
+    // The user's only playlist, with `total` items
+    fn mix(total: u32) -> Page<SimplifiedPlaylist> {
+        serde_json::from_value(serde_json::json!({
+            "href": "",
+            "items": [{
+                "collaborative": false,
+                "external_urls": {},
+                "href": "",
+                "id": "37i9dQZF1DXcBWIGoYBM5M",
+                "images": [],
+                "name": "Mix",
+                "owner": {
+                    "display_name": null,
+                    "external_urls": {},
+                    "href": "",
+                    "id": "owner",
+                },
+                "public": null,
+                "snapshot_id": "",
+                "tracks": { "href": "", "total": total },
+            }],
+            "limit": 20,
+            "next": null,
+            "offset": 0,
+            "previous": null,
+            "total": 1,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn queues_the_marked_rows_together() {
+        let (io_tx, mut io_rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut app = App::new(io_tx, UserConfig::new(), Utc::now());
+        app.push_navigation_stack(RouteId::ItemTable, ActiveBlock::ItemTable);
+        let mut local_file = track("local");
+        if let PlayableItem::Track(track) = &mut local_file {
+            track.id = None;
+            track.is_local = true;
+        }
+        app.item_table.context = Some(ItemTableContext::MyPlaylists);
+        app.item_table
+            .set_items(vec![track("c"), local_file, track("a"), track("b")]);
+        let queue = app.user_config.keys.add_item_to_queue;
+
+        for index in [3, 1, 0, 2] {
+            app.item_table.selected_index = index;
+            handler(Key::Char('V'), &mut app);
+        }
+        // Unmarked again
+        handler(Key::Char('V'), &mut app);
+        // Marks follow their items when sorted
+        handler(Key::Char('O'), &mut app);
+        let names = |app: &App| {
+            app.item_table
+                .selected_items()
+                .iter()
+                .map(|item| item.name().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(&app), ["b", "c", "local"]);
+
+        handler(queue, &mut app);
+        for name in ["b", "c"] {
+            assert!(matches!(
+                io_rx.try_recv(),
+                Ok(IoEvent::AddItemToQueue { playable_id }) if playable_id.uri().ends_with(name)
+            ));
+        }
+        assert!(io_rx.try_recv().is_err());
+        assert!(app.item_table.selected_rows.is_empty());
+        assert_eq!(
+            app.notifications.current().unwrap().message,
+            "Queueing 2 items, skipped 1 local file"
+        );
+
+        // Without any marked, just the selected row
+        app.item_table.selected_index = 0;
+        handler(queue, &mut app);
+        assert!(matches!(
+            io_rx.try_recv(),
+            Ok(IoEvent::AddItemToQueue { .. })
+        ));
+        assert!(io_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn marks_are_cleared_by_escape_and_new_items() {
+        let mut app = App::default();
+        app.push_navigation_stack(RouteId::ItemTable, ActiveBlock::ItemTable);
+        app.item_table.set_items(vec![track("a"), track("b")]);
+
+        handler(Key::Char('V'), &mut app);
+        crate::handlers::handle_app(Key::Esc, &mut app);
+        assert!(app.item_table.selected_rows.is_empty());
+        // Still in the table, with the marks gone first
+        assert_eq!(app.get_current_route().active_block, ActiveBlock::ItemTable);
+
+        handler(Key::Char('V'), &mut app);
+        app.item_table.set_items(vec![track("c")]);
+        assert!(app.item_table.selected_rows.is_empty());
+    }
+
+    #[test]
+    fn sorted_playlist_plays_from_the_item_position() {
+        let (io_tx, mut io_rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut app = App::new(io_tx, UserConfig::new(), Utc::now());
+        app.playlists = Some(mix(23));
+        app.active_playlist_index = Some(0);
+        app.playlist_offset = 20;
+        app.item_table.context = Some(ItemTableContext::MyPlaylists);
+        app.item_table
+            .set_items(vec![track("c"), track("b"), track("a")]);
+
+        handler(Key::Char('O'), &mut app);
+        app.item_table.selected_index = 0;
+        handler(Key::Enter, &mut app);
+
+        assert!(matches!(
+            io_rx.try_recv(),
+            Ok(IoEvent::StartContextPlayback {
+                offset: Some(PlaybackOffset::Index(22)),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn plays_the_open_playlist_rather_than_the_selected_one() {
+        let (io_tx, mut io_rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut app = App::new(io_tx, UserConfig::new(), Utc::now());
+        let mut playlists = mix(3);
+        let mut other = playlists.items[0].clone();
+        other.id = PlaylistId::from_id("1h0CEZCm6IbFTbxThn6Xcs").unwrap();
+        playlists.items.push(other);
+        app.playlists = Some(playlists);
+        let plays = |io_rx: &mut tokio::sync::mpsc::UnboundedReceiver<IoEvent>| {
+            while let Ok(event) = io_rx.try_recv() {
+                if let IoEvent::StartContextPlayback {
+                    play_context_id, ..
+                } = event
+                {
+                    return Some(play_context_id.uri());
+                }
+            }
+            None
+        };
+
+        // Opened from the playlists pane, then another one hovered
+        app.selected_playlist_index = Some(0);
+        crate::handlers::playlist::handler(Key::Enter, &mut app);
+        app.selected_playlist_index = Some(1);
+        app.item_table.set_items(vec![track("a")]);
+        handler(Key::Enter, &mut app);
+        assert_eq!(
+            plays(&mut io_rx).as_deref(),
+            Some("spotify:playlist:37i9dQZF1DXcBWIGoYBM5M")
+        );
+
+        // Opened by jumping to the playing playlist
+        let mut playback: CurrentPlaybackContext =
+            serde_json::from_value(mock_api::playback()).unwrap();
+        playback.context = Some(
+            serde_json::from_value(serde_json::json!({
+                "type": "playlist",
+                "href": "",
+                "external_urls": {},
+                "uri": "spotify:playlist:1h0CEZCm6IbFTbxThn6Xcs",
+            }))
+            .unwrap(),
+        );
+        app.current_playback_context = Some(playback.clone());
+        assert_eq!(app.playing_playlist_index(), Some(1));
+        crate::handlers::handle_app(app.user_config.keys.jump_to_context, &mut app);
+        handler(Key::Enter, &mut app);
+        assert_eq!(
+            plays(&mut io_rx).as_deref(),
+            Some("spotify:playlist:1h0CEZCm6IbFTbxThn6Xcs")
+        );
+
+        // A playlist that isn't the user's isn't played in place of the last one opened
+        playback.context.as_mut().unwrap().uri =
+            String::from("spotify:playlist:0vvXsWCC9xrXsKd4FyS8kM");
+        app.current_playback_context = Some(playback);
+        assert_eq!(app.playing_playlist_index(), None);
+        crate::handlers::handle_app(app.user_config.keys.jump_to_context, &mut app);
+        handler(Key::Enter, &mut app);
+        assert_eq!(plays(&mut io_rx), None);
+    }
+
+    #[test]
+    fn jumps_to_the_playing_item() {
+        let (io_tx, mut io_rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut app = App::new(io_tx, UserConfig::new(), Utc::now());
+        app.playlists = Some(mix(45));
+        app.active_playlist_index = Some(0);
+        app.item_table.context = Some(ItemTableContext::MyPlaylists);
+        app.item_table
+            .set_items(vec![track("a"), track("b"), track("c")]);
+        let mut playback: CurrentPlaybackContext =
+            serde_json::from_value(mock_api::playback()).unwrap();
+        playback.context = Some(
+            serde_json::from_value(serde_json::json!({
+                "type": "playlist",
+                "href": "",
+                "external_urls": {},
+                "uri": "spotify:playlist:37i9dQZF1DXcBWIGoYBM5M",
+            }))
+            .unwrap(),
+        );
+        let jump = app.user_config.keys.jump_to_playing;
+
+        // Nothing is playing
+        handler(jump, &mut app);
+        assert_eq!(app.item_table.selected_index, 0);
+        assert_eq!(
+            app.notifications.current().unwrap().message,
+            "Nothing is playing"
+        );
+
+        playback.item = Some(track("c"));
+        app.current_playback_context = Some(playback.clone());
+        handler(jump, &mut app);
+        assert_eq!(app.item_table.selected_index, 2);
+        assert!(io_rx.try_recv().is_err());
+
+        // On another page of the playlist, which is looked for
+        playback.item = Some(track("z"));
+        app.current_playback_context = Some(playback.clone());
+        handler(jump, &mut app);
+        assert!(matches!(
+            io_rx.try_recv(),
+            Ok(IoEvent::JumpToPlaylistItem { playable_id, .. })
+                if playable_id.uri() == "spotify:track:z"
+        ));
+
+        // Playing from somewhere else, so not in this playlist
+        playback.context = None;
+        app.current_playback_context = Some(playback);
+        handler(jump, &mut app);
+        assert!(io_rx.try_recv().is_err());
+        assert_eq!(
+            app.notifications.current().unwrap().message,
+            "What's playing isn't in this table"
+        );
+    }
+
+    #[test]
+    fn sort_by_tempo_puts_tracks_without_features_last() {
+        let mut app = App::default();
+        app.item_table.context = Some(ItemTableContext::SavedTracks);
+        app.item_table.items = vec![
+            track("slow"),
+            track("unknown"),
+            track("fast"),
+            track("medium"),
+        ];
+        for (id, tempo) in [("slow", 80.0), ("fast", 170.0), ("medium", 120.0)] {
+            let features = features(id, tempo);
+            app.audio_features.insert(features.id.clone(), features);
+        }
+        app.item_table.selected_index = 3;
+
+        // "unknown" has no cached features so they are requested first
+        handler(Key::Char('T'), &mut app);
+        assert_eq!(app.item_table.pending_sort, Some(TrackFeature::Tempo));
+
+        // Spotify has no features for "unknown" either
+        app.item_table.pending_sort = None;
+        app.apply_item_table_sort(TrackFeature::Tempo);
+
+        assert_eq!(item_names(&app), vec!["fast", "medium", "slow", "unknown"]);
+        assert_eq!(app.item_table.sort, Some(TrackFeature::Tempo));
+        // The selection follows the selected track
+        assert_eq!(app.item_table.selected_index, 1);
+    }
+
+    #[test]
+    fn sort_waits_for_missing_features_and_can_be_cancelled() {
+        let mut app = App::default();
+        app.item_table.context = Some(ItemTableContext::RecommendedTracks);
+        app.item_table.items = vec![track("slow"), track("fast")];
+
+        handler(Key::Char('E'), &mut app);
+        assert_eq!(app.item_table.pending_sort, Some(TrackFeature::Energy));
+        assert_eq!(item_names(&app), vec!["slow", "fast"]);
+
+        handler(Key::Char('E'), &mut app);
+        assert_eq!(app.item_table.pending_sort, None);
+        assert_eq!(app.item_table.sort, None);
+    }
+
+    #[test]
+    fn sort_is_ignored_outside_of_saved_and_recommended_tracks() {
+        let mut app = App::default();
+        app.item_table.context = Some(ItemTableContext::MyPlaylists);
+        app.item_table.items = vec![track("slow"), track("fast")];
+
+        handler(Key::Char('T'), &mut app);
+
+        assert_eq!(app.item_table.pending_sort, None);
+        assert_eq!(app.item_table.sort, None);
+    }
+
+    #[test]
+    fn follows_and_unfollows_the_playlist_shown() {
+        let (io_tx, mut io_rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut app = App::new(io_tx, UserConfig::new(), Utc::now());
+        let playlist = serde_json::json!({
+            "collaborative": false,
+            "external_urls": {},
+            "href": "",
+            "id": "37i9dQZF1DXcBWIGoYBM5M",
+            "images": [],
+            "name": "Today's Top Hits",
+            "owner": {"external_urls": {}, "href": "", "id": "spotify"},
+            "public": true,
+            "snapshot_id": "",
+            "tracks": {"href": "", "total": 50},
+        });
+        let page = |items| {
+            serde_json::from_value(serde_json::json!({
+                "href": "",
+                "items": items,
+                "limit": 20,
+                "next": null,
+                "offset": 0,
+                "previous": null,
+                "total": 1,
+            }))
+            .unwrap()
+        };
+        app.search_results
+            .playlists
+            .add_pages(page(serde_json::json!([playlist])));
+        app.search_results.selected_playlists_index = Some(0);
+        app.item_table.context = Some(ItemTableContext::PlaylistSearch);
+
+        // Not followed yet, so there's nothing to unfollow
+        handler(Key::Char('D'), &mut app);
+        assert_eq!(app.get_current_route().id, RouteId::Home);
+        handler(Key::Char('w'), &mut app);
+        assert!(matches!(
+            io_rx.try_recv(),
+            Ok(IoEvent::UserFollowPlaylist {
+                is_public: Some(true),
+                ..
+            })
+        ));
+
+        app.playlists = Some(page(serde_json::json!([playlist])));
+        handler(Key::Char('D'), &mut app);
+        assert_eq!(
+            app.get_current_route().active_block,
+            ActiveBlock::Dialog(DialogContext::PlaylistSearch)
+        );
+        assert_eq!(app.dialog.as_deref(), Some("Today's Top Hits"));
+    }
+}
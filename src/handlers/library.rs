@@ -34,6 +34,8 @@ pub fn handler(key: Key, app: &mut App) {
             let next_index = common_key_events::on_low_press_handler(&LIBRARY_OPTIONS);
             app.library.selected_index = next_index
         }
+        // Liked Songs radio
+        Key::Char('r') if app.library.selected_index == 2 => app.start_liked_songs_radio(),
         // `library` should probably be an array of structs with enums rather than just using indexes
         // like this
         Key::Enter => match app.library.selected_index {
@@ -67,6 +69,8 @@ pub fn handler(key: Key, app: &mut App) {
                 app.dispatch(IoEvent::GetCurrentUserSavedShows { offset: None });
                 app.push_navigation_stack(RouteId::Podcasts, ActiveBlock::Podcasts);
             }
+            // Top Items,
+            6 => app.open_top_items(),
             // This is required because Rust can't tell if this pattern in exhaustive
             _ => {}
         },
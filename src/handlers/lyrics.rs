@@ -0,0 +1,47 @@
+use super::{super::app::App, common_key_events};
+use crate::event::Key;
+
+const LARGE_SCROLL: u16 = 10;
+const SMALL_SCROLL: u16 = 1;
+
+pub fn handler(key: Key, app: &mut App) {
+    match key {
+        k if common_key_events::left_event(k) => common_key_events::handle_left_event(app),
+        k if common_key_events::down_event(k) => {
+            app.lyrics_scroll += SMALL_SCROLL;
+        }
+        k if common_key_events::up_event(k) => {
+            app.lyrics_scroll = app.lyrics_scroll.saturating_sub(SMALL_SCROLL);
+        }
+        k if k == app.user_config.keys.next_page => {
+            app.lyrics_scroll += LARGE_SCROLL;
+        }
+        k if k == app.user_config.keys.previous_page => {
+            app.lyrics_scroll = app.lyrics_scroll.saturating_sub(LARGE_SCROLL);
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scrolls_down_and_up() {
+        let mut app = App::default();
+
+        handler(Key::Down, &mut app);
+        handler(Key::Ctrl('d'), &mut app);
+        assert_eq!(app.lyrics_scroll, SMALL_SCROLL + LARGE_SCROLL);
+
+        handler(Key::Up, &mut app);
+        assert_eq!(app.lyrics_scroll, LARGE_SCROLL);
+
+        // Scrolling past the top stops there
+        handler(Key::Ctrl('u'), &mut app);
+        handler(Key::Ctrl('u'), &mut app);
+        handler(Key::Up, &mut app);
+        assert_eq!(app.lyrics_scroll, 0);
+    }
+}
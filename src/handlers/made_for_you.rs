@@ -44,6 +44,7 @@ pub fn handler(key: Key, app: &mut App) {
                 app.made_for_you_index = next_index;
             }
         }
+        Key::Char('R') => app.refresh_made_for_you(),
         Key::Enter => {
             if let (Some(playlists), selected_playlist_index) = (
                 &app.library.made_for_you_playlists.get_results(Some(0)),
@@ -9,27 +9,54 @@ mod dialog;
 mod empty;
 mod episode_table;
 mod error_screen;
+mod finished_shows;
 mod help_menu;
 mod home;
 mod input;
 mod item_table;
 mod library;
+mod lyrics;
 mod made_for_you;
+mod mouse;
+mod notification_history;
 mod playbar;
 mod playlist;
 mod podcasts;
 mod recently_played;
 mod search_results;
 mod select_device;
+mod top_artists;
 
 use super::app::{ActiveBlock, App, ArtistBlock, RouteId, SearchResultBlock};
 use crate::event::Key;
 use crate::network::IoEvent;
-use rspotify::model::{context::CurrentPlaybackContext, PlayableItem};
+use crate::ui::view_text::TextFormat;
+use crate::user_config::KeyBindings;
+use rspotify::model::{
+    context::{Context, CurrentPlaybackContext},
+    enums::Type,
+    PlayableItem, PlaylistId,
+};
+use std::mem;
 
+pub use help_menu::handler as help_menu_handler;
 pub use input::handler as input_handler;
+pub use mouse::handler as handle_mouse;
+pub use select_device::handler as select_device_handler;
 
 pub fn handle_app(key: Key, app: &mut App) {
+    if app.is_mini_player {
+        if app.get_current_route().active_block != ActiveBlock::BasicView {
+            // e.g. picking a device or reading an error, without the global keys navigating
+            // away to views that have nothing loaded
+            handle_block_events(key, app);
+            return;
+        }
+        if !is_mini_player_key(key, &app.user_config.keys) {
+            return;
+        }
+    }
+
     // First handle any global event and then move to block event
     match key {
         Key::Esc => {
@@ -45,7 +72,7 @@ pub fn handle_app(key: Key, app: &mut App) {
             handle_jump_to_context(app);
         }
         _ if key == app.user_config.keys.manage_devices => {
-            app.dispatch(IoEvent::GetDevices);
+            app.dispatch(IoEvent::GetDevices { silent: false });
         }
         _ if key == app.user_config.keys.decrease_volume => {
             app.decrease_volume();
@@ -53,6 +80,12 @@ pub fn handle_app(key: Key, app: &mut App) {
         _ if key == app.user_config.keys.increase_volume => {
             app.increase_volume();
         }
+        _ if key == app.user_config.keys.decrease_volume_big => {
+            app.decrease_volume_big();
+        }
+        _ if key == app.user_config.keys.increase_volume_big => {
+            app.increase_volume_big();
+        }
         // Press space to toggle playback
         _ if key == app.user_config.keys.toggle_playback => {
             app.toggle_playback();
@@ -70,7 +103,7 @@ pub fn handle_app(key: Key, app: &mut App) {
             app.previous_track();
         }
         _ if key == app.user_config.keys.help => {
-            app.set_current_route_state(Some(ActiveBlock::HelpMenu), None);
+            app.open_help();
         }
 
         _ if key == app.user_config.keys.shuffle => {
@@ -79,6 +112,18 @@ pub fn handle_app(key: Key, app: &mut App) {
         _ if key == app.user_config.keys.repeat => {
             app.repeat();
         }
+        // Filters the help rather than leaving it for the search
+        _ if key == app.user_config.keys.search
+            && app.get_current_route().active_block == ActiveBlock::HelpMenu =>
+        {
+            app.start_help_filter();
+        }
+        // Picks a device by name rather than leaving it for the search
+        _ if key == app.user_config.keys.search
+            && app.get_current_route().active_block == ActiveBlock::SelectDevice =>
+        {
+            app.start_finding_device();
+        }
         _ if key == app.user_config.keys.search => {
             app.set_current_route_state(Some(ActiveBlock::Input), Some(ActiveBlock::Input));
         }
@@ -94,10 +139,58 @@ pub fn handle_app(key: Key, app: &mut App) {
         _ if key == app.user_config.keys.basic_view => {
             app.push_navigation_stack(RouteId::BasicView, ActiveBlock::BasicView);
         }
+        _ if key == app.user_config.keys.lyrics => {
+            app.show_lyrics();
+        }
+        _ if key == app.user_config.keys.notification_history => {
+            app.show_notification_history();
+        }
+        _ if key == app.user_config.keys.toggle_playbar => {
+            app.toggle_playbar();
+        }
+        _ if key == app.user_config.keys.like_current => {
+            app.toggle_save_playing_item();
+        }
+        _ if key == app.user_config.keys.save_playing_item_parent => {
+            app.toggle_save_playing_item_parent();
+        }
+        _ if key == app.user_config.keys.dump_selected_item && app.debug_dump_dir.is_some() => {
+            app.dump_selected_item();
+        }
+        _ if key == app.user_config.keys.do_not_disturb => {
+            app.toggle_do_not_disturb();
+        }
+        _ if key == app.user_config.keys.copy_view => {
+            app.copy_view_as_text(TextFormat::Plain);
+        }
+        _ if key == app.user_config.keys.copy_view_markdown => {
+            app.copy_view_as_text(TextFormat::Markdown);
+        }
         _ => handle_block_events(key, app),
     }
 }
 
+// The playback controls, volume, seeking and liking (`s` in the basic view), which is all the mini
+// player has
+fn is_mini_player_key(key: Key, keys: &KeyBindings) -> bool {
+    key == Key::Char('s')
+        || [
+            keys.toggle_playback,
+            keys.next_track,
+            keys.previous_track,
+            keys.seek_forwards,
+            keys.seek_backwards,
+            keys.decrease_volume,
+            keys.increase_volume,
+            keys.decrease_volume_big,
+            keys.increase_volume_big,
+            keys.shuffle,
+            keys.repeat,
+            keys.like_current,
+        ]
+        .contains(&key)
+}
+
 // Handle event for the current active block
 fn handle_block_events(key: Key, app: &mut App) {
     let current_route = app.get_current_route();
@@ -168,49 +261,139 @@ fn handle_block_events(key: Key, app: &mut App) {
         ActiveBlock::Dialog(_) => {
             dialog::handler(key, app);
         }
+        ActiveBlock::Lyrics => {
+            lyrics::handler(key, app);
+        }
+        ActiveBlock::NotificationHistory => {
+            notification_history::handler(key, app);
+        }
+        ActiveBlock::TopArtists => {
+            top_artists::handler(key, app);
+        }
+        ActiveBlock::FinishedShows => {
+            finished_shows::handler(key, app);
+        }
     }
 }
 
+// Esc first clears what is selected inside the active block, then puts the focus back on the
+// route's default block, where the arrow keys work straight away. Errors and dialogs are closed
+// instead.
 fn handle_escape(app: &mut App) {
+    if matches!(
+        app.get_current_route().active_block,
+        ActiveBlock::Error | ActiveBlock::Dialog(_)
+    ) {
+        app.pop_navigation_stack();
+        return;
+    }
+    if clear_inner_selection(app) {
+        return;
+    }
+    if let Some(block) = common_key_events::default_block(&app.get_current_route().id) {
+        app.set_current_route_state(Some(block), Some(block));
+    }
+}
+
+// Whether there was anything to clear
+fn clear_inner_selection(app: &mut App) -> bool {
     match app.get_current_route().active_block {
         ActiveBlock::SearchResultBlock => {
-            app.search_results.selected_block = SearchResultBlock::Empty;
-        }
-        ActiveBlock::ArtistBlock => {
-            if let Some(artist) = &mut app.artist {
-                artist.artist_selected_block = ArtistBlock::Empty;
-            }
-        }
-        ActiveBlock::Error => {
-            app.pop_navigation_stack();
-        }
-        ActiveBlock::Dialog(_) => {
-            app.pop_navigation_stack();
-        }
-        // These are global views that have no active/inactive distinction so do nothing
-        ActiveBlock::SelectDevice | ActiveBlock::Analysis => {}
-        _ => {
-            app.set_current_route_state(Some(ActiveBlock::Empty), None);
-        }
+            mem::replace(
+                &mut app.search_results.selected_block,
+                SearchResultBlock::Empty,
+            ) != SearchResultBlock::Empty
+        }
+        ActiveBlock::ArtistBlock => app.artist.as_mut().is_some_and(|artist| {
+            mem::replace(&mut artist.artist_selected_block, ArtistBlock::Empty)
+                != ArtistBlock::Empty
+        }),
+        // Close the features popup, unmark the rows to queue, then cancel any audio feature sort
+        // that is still waiting on its features
+        ActiveBlock::ItemTable if app.item_table.is_features_popup_open => {
+            app.item_table.is_features_popup_open = false;
+            true
+        }
+        ActiveBlock::ItemTable if !app.item_table.selected_rows.is_empty() => {
+            app.item_table.selected_rows.clear();
+            true
+        }
+        ActiveBlock::ItemTable => app.item_table.pending_sort.take().is_some(),
+        ActiveBlock::MyPlaylists => app.clear_playlist_filter(),
+        ActiveBlock::Podcasts => app.cancel_finding_finished_shows(),
+        _ => false,
+    }
+}
+
+// What the playback was started from, as far as jumping to it goes
+#[derive(Debug, PartialEq)]
+enum PlayingContext {
+    Album,
+    Artist,
+    Playlist(PlaylistId<'static>),
+    Show,
+    LikedSongs,
+}
+
+// Liked Songs has a context of the user's collection, or none at all with some clients, in which
+// case the track being liked is the best guess there is
+fn playing_context(context: Option<&Context>, is_liked_track: bool) -> Option<PlayingContext> {
+    let Some(context) = context else {
+        return is_liked_track.then_some(PlayingContext::LikedSongs);
+    };
+    if context.uri.ends_with(":collection") {
+        return Some(PlayingContext::LikedSongs);
+    }
+    match context._type {
+        Type::Album => Some(PlayingContext::Album),
+        Type::Artist => Some(PlayingContext::Artist),
+        Type::Playlist => PlaylistId::from_uri(&context.uri)
+            .ok()
+            .map(|playlist_id| PlayingContext::Playlist(playlist_id.into_static())),
+        Type::Show => Some(PlayingContext::Show),
+        Type::Collection => Some(PlayingContext::LikedSongs),
+        _ => None,
     }
 }
 
 fn handle_jump_to_context(app: &mut App) {
-    if let Some(current_playback_context) = &app.current_playback_context {
-        if let Some(play_context) = current_playback_context.context.clone() {
-            match play_context._type {
-                rspotify::model::enums::Type::Album => handle_jump_to_album(app),
-                rspotify::model::enums::Type::Artist => handle_jump_to_artist_album(app),
-                rspotify::model::enums::Type::Playlist => app.dispatch(IoEvent::GetPlaylistItems {
-                    playlist_id: rspotify::model::PlaylistId::from_uri(&play_context.uri).unwrap(),
-                    offset: 0,
-                }),
-                _ => {}
-            }
-        }
+    let Some(current_playback_context) = &app.current_playback_context else {
+        return;
+    };
+    let is_liked_track = app
+        .playing_track()
+        .and_then(|track| track.id.as_ref())
+        .is_some_and(|track_id| app.liked_song_ids_set.contains(track_id));
+    match playing_context(current_playback_context.context.as_ref(), is_liked_track) {
+        // The show of the episode is opened like the album of a track
+        Some(PlayingContext::Album | PlayingContext::Show) => handle_jump_to_album(app),
+        Some(PlayingContext::Artist) => handle_jump_to_artist_album(app),
+        Some(PlayingContext::Playlist(playlist_id)) => app.open_playlist(playlist_id),
+        Some(PlayingContext::LikedSongs) => handle_jump_to_liked_songs(app),
+        None => {}
     }
 }
 
+// Opens the page of Liked Songs with the playing track on it, when it's on a page that was
+// already loaded, and the first page otherwise
+fn handle_jump_to_liked_songs(app: &mut App) {
+    let track_id = app.playing_track().and_then(|track| track.id.clone());
+    let found = app.library.saved_tracks.pages.iter().find_map(|page| {
+        let row = page
+            .items
+            .iter()
+            .position(|item| item.track.id.is_some() && item.track.id == track_id)?;
+        Some((page.offset, row))
+    });
+    let (offset, row) = match found {
+        Some((offset, row)) => (Some(offset), row),
+        None => (None, 0),
+    };
+    app.item_table.selected_index = row;
+    app.dispatch(IoEvent::GetCurrentUserSavedTracks { offset });
+    app.push_navigation_stack(RouteId::ItemTable, ActiveBlock::ItemTable);
+}
+
 fn handle_jump_to_album(app: &mut App) {
     if let Some(CurrentPlaybackContext {
         item: Some(item), ..
@@ -252,3 +435,346 @@ fn handle_jump_to_artist_album(app: &mut App) {
         }
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::full_track as track;
+    use crate::user_config::UserConfig;
+    use chrono::{Duration, Utc};
+    use rspotify::model::{
+        context::Actions,
+        device::Device,
+        enums::{CurrentlyPlayingType, DeviceType, RepeatState},
+        page::Page,
+        track::{FullTrack, SavedTrack},
+        TrackId,
+    };
+
+    fn playing(track: FullTrack, context: Option<Context>) -> CurrentPlaybackContext {
+        CurrentPlaybackContext {
+            device: Device {
+                id: None,
+                is_active: true,
+                is_private_session: false,
+                is_restricted: false,
+                name: String::from("Speaker"),
+                _type: DeviceType::Speaker,
+                volume_percent: Some(50),
+            },
+            repeat_state: RepeatState::Off,
+            shuffle_state: false,
+            context,
+            timestamp: Utc::now(),
+            progress: Some(Duration::seconds(30)),
+            is_playing: true,
+            item: Some(PlayableItem::Track(track)),
+            currently_playing_type: CurrentlyPlayingType::Track,
+            actions: Actions::default(),
+        }
+    }
+
+    fn context(_type: Type, uri: &str) -> Context {
+        Context {
+            uri: uri.to_string(),
+            href: String::new(),
+            external_urls: Default::default(),
+            _type,
+        }
+    }
+
+    #[test]
+    fn classifies_playing_contexts() {
+        let playlist_id = PlaylistId::from_id("37i9dQZF1DXcBWIGoYBM5M").unwrap();
+        let cases = [
+            (
+                context(Type::Playlist, "spotify:playlist:37i9dQZF1DXcBWIGoYBM5M"),
+                Some(PlayingContext::Playlist(playlist_id)),
+            ),
+            (
+                context(Type::Album, "spotify:album:4aawyAB9vmqN3uQ7FjRGTy"),
+                Some(PlayingContext::Album),
+            ),
+            (
+                context(Type::Artist, "spotify:artist:0OdUWJ0sBjDrqHygGUXeCF"),
+                Some(PlayingContext::Artist),
+            ),
+            (
+                context(Type::Show, "spotify:show:4rOoJ6Egrf8K2IrywzwOMk"),
+                Some(PlayingContext::Show),
+            ),
+            (
+                context(Type::Collection, "spotify:user:someone:collection"),
+                Some(PlayingContext::LikedSongs),
+            ),
+            // Typed as the user by some clients
+            (
+                context(Type::User, "spotify:user:someone:collection"),
+                Some(PlayingContext::LikedSongs),
+            ),
+            (context(Type::Playlist, "nonsense"), None),
+            (context(Type::User, "spotify:user:someone"), None),
+        ];
+        for (context, expected) in cases {
+            for is_liked_track in [false, true] {
+                assert_eq!(
+                    playing_context(Some(&context), is_liked_track),
+                    expected,
+                    "{}",
+                    context.uri
+                );
+            }
+        }
+
+        // Without a context, only a liked track is taken to be playing from Liked Songs
+        assert_eq!(
+            playing_context(None, true),
+            Some(PlayingContext::LikedSongs)
+        );
+        assert_eq!(playing_context(None, false), None);
+    }
+
+    #[test]
+    fn likes_what_is_playing_from_any_view() {
+        let (io_tx, mut io_rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut app = App::new(io_tx, UserConfig::new(), Utc::now());
+        let like = app.user_config.keys.like_current;
+        let track_id = TrackId::from_id("4iV5W9uYEdYUVa79Axb7Rh").unwrap();
+        app.push_navigation_stack(RouteId::Artist, ActiveBlock::ArtistBlock);
+
+        // Nothing playing
+        handle_app(like, &mut app);
+        assert!(io_rx.try_recv().is_err());
+
+        app.current_playback_context = Some(playing(track("4iV5W9uYEdYUVa79Axb7Rh"), None));
+        handle_app(like, &mut app);
+        // Shown as liked before Spotify has answered
+        assert!(app.liked_song_ids_set.contains(&track_id));
+        assert!(matches!(
+            io_rx.try_recv(),
+            Ok(IoEvent::ToggleSaveTrack { track_id: id }) if id == track_id
+        ));
+
+        // Ignored until Spotify has answered
+        handle_app(like, &mut app);
+        assert!(app.liked_song_ids_set.contains(&track_id));
+        assert!(io_rx.try_recv().is_err());
+
+        app.confirm_track_save(track_id.clone(), true);
+        handle_app(like, &mut app);
+        assert!(!app.liked_song_ids_set.contains(&track_id));
+        assert!(matches!(
+            io_rx.try_recv(),
+            Ok(IoEvent::ToggleSaveTrack { .. })
+        ));
+        assert_eq!(app.get_current_route().id, RouteId::Artist);
+    }
+
+    #[test]
+    fn jumps_to_the_liked_songs_playing() {
+        let (io_tx, mut io_rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut app = App::new(io_tx, UserConfig::new(), Utc::now());
+        let playing_id = "4iV5W9uYEdYUVa79Axb7Rh";
+        let jump = app.user_config.keys.jump_to_context;
+
+        // Not liked and without a context, so there's nowhere to go
+        app.current_playback_context = Some(playing(track(playing_id), None));
+        handle_app(jump, &mut app);
+        assert!(io_rx.try_recv().is_err());
+
+        // The page it's on isn't known, so the first one is opened
+        app.current_playback_context = Some(playing(
+            track(playing_id),
+            Some(context(Type::Collection, "spotify:user:someone:collection")),
+        ));
+        handle_app(jump, &mut app);
+        assert!(matches!(
+            io_rx.try_recv(),
+            Ok(IoEvent::GetCurrentUserSavedTracks { offset: None })
+        ));
+        assert_eq!(app.get_current_route().id, RouteId::ItemTable);
+        assert_eq!(app.item_table.selected_index, 0);
+
+        // Liked, on the second page loaded
+        let saved = |id| SavedTrack {
+            added_at: Utc::now(),
+            track: track(id),
+        };
+        let page = |offset, items| Page {
+            href: String::new(),
+            items,
+            limit: 20,
+            next: None,
+            offset,
+            previous: None,
+            total: 22,
+        };
+        app.library
+            .saved_tracks
+            .add_pages(page(0, vec![saved("0eGsygTp906u18L0Oimnem")]));
+        app.library.saved_tracks.add_pages(page(
+            20,
+            vec![saved("0eGsygTp906u18L0Oimnem"), saved(playing_id)],
+        ));
+        app.liked_song_ids_set
+            .insert(TrackId::from_id(playing_id).unwrap());
+        app.current_playback_context = Some(playing(track(playing_id), None));
+        handle_app(jump, &mut app);
+        assert!(matches!(
+            io_rx.try_recv(),
+            Ok(IoEvent::GetCurrentUserSavedTracks { offset: Some(20) })
+        ));
+        assert_eq!(app.item_table.selected_index, 1);
+    }
+
+    #[test]
+    fn mini_player_only_controls_the_playback() {
+        let mut app = App::default();
+        app.start_mini_player();
+
+        for key in [
+            app.user_config.keys.search,
+            app.user_config.keys.help,
+            app.user_config.keys.lyrics,
+            app.user_config.keys.audio_analysis,
+            Key::Enter,
+            Key::Down,
+            Key::Esc,
+        ] {
+            handle_app(key, &mut app);
+            let route = app.get_current_route();
+            assert_eq!(route.id, RouteId::BasicView, "{:?}", key);
+            assert_eq!(route.active_block, ActiveBlock::BasicView, "{:?}", key);
+        }
+        assert!(!app.is_loading);
+
+        handle_app(app.user_config.keys.next_track, &mut app);
+        assert!(app.is_loading);
+    }
+
+    #[test]
+    fn mini_player_can_leave_other_screens() {
+        let mut app = App::default();
+        app.start_mini_player();
+        app.handle_error(anyhow::anyhow!("Device not found"));
+
+        // The error screen's own handling still applies, the global keys don't
+        handle_app(app.user_config.keys.search, &mut app);
+        assert_eq!(app.get_current_route().active_block, ActiveBlock::Error);
+    }
+
+    // Walks Esc through a route, checking where the focus is after each press
+    fn escape(app: &mut App, expected: &[(ActiveBlock, ActiveBlock)]) {
+        for (press, &(active, hovered)) in expected.iter().enumerate() {
+            handle_app(Key::Esc, app);
+            let route = app.get_current_route();
+            assert_eq!(
+                (route.active_block, route.hovered_block),
+                (active, hovered),
+                "press {}",
+                press + 1
+            );
+            assert!(
+                route.active_block != ActiveBlock::Empty
+                    || route.hovered_block != ActiveBlock::Empty
+            );
+        }
+    }
+
+    #[test]
+    fn escape_deselects_then_focuses_the_search_results() {
+        let mut app = App::default();
+        app.push_navigation_stack(RouteId::Search, ActiveBlock::SearchResultBlock);
+        app.search_results.selected_block = SearchResultBlock::AlbumSearch;
+        let results = (
+            ActiveBlock::SearchResultBlock,
+            ActiveBlock::SearchResultBlock,
+        );
+        escape(&mut app, &[results]);
+        assert_eq!(app.search_results.selected_block, SearchResultBlock::Empty);
+        escape(&mut app, &[results, results]);
+        assert_eq!(app.get_current_route().id, RouteId::Search);
+
+        // From the sidebar back to the results
+        app.set_current_route_state(Some(ActiveBlock::Empty), Some(ActiveBlock::Library));
+        escape(&mut app, &[results]);
+    }
+
+    #[test]
+    fn escape_deselects_then_focuses_the_artist() {
+        let mut app = App::default();
+        app.push_navigation_stack(RouteId::Artist, ActiveBlock::ArtistBlock);
+        app.artist = Some(crate::app::Artist {
+            artist_id: rspotify::model::ArtistId::from_id("0OdUWJ0sBjDrqHygGUXeCF").unwrap(),
+            artist_name: String::from("Band of Horses"),
+            albums: Default::default(),
+            album_type: None,
+            pending_albums_offset: None,
+            related_artists: vec![],
+            top_tracks: vec![],
+            selected_album_index: 0,
+            selected_related_artist_index: 0,
+            selected_top_track_index: 0,
+            artist_hovered_block: ArtistBlock::Albums,
+            artist_selected_block: ArtistBlock::Albums,
+        });
+        let artist = (ActiveBlock::ArtistBlock, ActiveBlock::ArtistBlock);
+        escape(&mut app, &[artist, artist]);
+        let artist_view = app.artist.as_ref().unwrap();
+        assert_eq!(artist_view.artist_selected_block, ArtistBlock::Empty);
+        assert_eq!(artist_view.artist_hovered_block, ArtistBlock::Albums);
+    }
+
+    #[test]
+    fn escape_cancels_a_sort_then_focuses_the_item_table() {
+        let mut app = App::default();
+        app.push_navigation_stack(RouteId::ItemTable, ActiveBlock::ItemTable);
+        app.set_current_route_state(None, Some(ActiveBlock::MyPlaylists));
+        app.item_table.pending_sort = Some(crate::app::TrackFeature::Tempo);
+        let table = (ActiveBlock::ItemTable, ActiveBlock::ItemTable);
+        escape(
+            &mut app,
+            &[(ActiveBlock::ItemTable, ActiveBlock::MyPlaylists), table],
+        );
+        assert_eq!(app.item_table.pending_sort, None);
+
+        // Help is closed the same way
+        handle_app(app.user_config.keys.help, &mut app);
+        escape(&mut app, &[table]);
+    }
+
+    #[test]
+    fn escape_clears_the_playlist_filter_then_focuses_the_route() {
+        let mut app = App::default();
+        app.set_current_route_state(
+            Some(ActiveBlock::MyPlaylists),
+            Some(ActiveBlock::MyPlaylists),
+        );
+        app.cycle_playlist_filter();
+        escape(
+            &mut app,
+            &[
+                (ActiveBlock::MyPlaylists, ActiveBlock::MyPlaylists),
+                (ActiveBlock::Home, ActiveBlock::Home),
+            ],
+        );
+        assert_eq!(app.playlist_filter, crate::app::PlaylistFilter::All);
+    }
+
+    #[test]
+    fn escape_closes_dialogs_and_errors() {
+        let mut app = App::default();
+        app.push_navigation_stack(RouteId::ItemTable, ActiveBlock::ItemTable);
+        app.push_navigation_stack(
+            RouteId::Dialog,
+            ActiveBlock::Dialog(crate::app::DialogContext::PlaylistWindow),
+        );
+        let table = (ActiveBlock::ItemTable, ActiveBlock::ItemTable);
+        escape(&mut app, &[table]);
+        assert_eq!(app.get_current_route().id, RouteId::ItemTable);
+
+        app.handle_error(anyhow::anyhow!("Device not found"));
+        assert_eq!(app.get_current_route().active_block, ActiveBlock::Error);
+        escape(&mut app, &[table, table]);
+    }
+}
@@ -0,0 +1,125 @@
+use super::{handle_block_events, playbar};
+use crate::{
+    app::{ActiveBlock, AlbumTableContext, App, ListArea, RecentlyPlayedSource},
+    event::Key,
+    ui::util::PlayedRow,
+};
+use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+use spotify_tui_util::PlaybleItemExt;
+use tui::layout::Rect;
+
+fn contains(area: Rect, column: u16, row: u16) -> bool {
+    column >= area.x && column < area.x + area.width && row >= area.y && row < area.y + area.height
+}
+
+pub fn handler(event: MouseEvent, app: &mut App) {
+    if !app.user_config.behavior.enable_mouse {
+        return;
+    }
+
+    let (column, row) = (event.column, event.row);
+    let (progress, control, list) = match app.control_areas.lock() {
+        Ok(control_areas) => (
+            control_areas.progress,
+            control_areas
+                .playbar
+                .iter()
+                .find(|(_, area)| contains(*area, column, row))
+                .map(|(control, _)| *control),
+            control_areas
+                .lists
+                .iter()
+                .find(|list| contains(list.rows, column, row))
+                .copied(),
+        ),
+        Err(_) => return,
+    };
+
+    match event.kind {
+        MouseEventKind::Down(MouseButton::Left) => {
+            if let Some(progress) = progress.filter(|area| contains(*area, column, row)) {
+                seek_to_column(app, progress, column);
+            } else if let Some(control) = control {
+                playbar::activate_control(app, control);
+            } else if let Some(list) = list {
+                let index = list.offset + (row - list.rows.y) as usize;
+                if index < list.len {
+                    focus(app, list);
+                    select_index(app, list.block, index);
+                }
+            }
+        }
+        MouseEventKind::ScrollDown | MouseEventKind::ScrollUp => {
+            if let Some(list) = list {
+                focus(app, list);
+                let key = if event.kind == MouseEventKind::ScrollDown {
+                    Key::Down
+                } else {
+                    Key::Up
+                };
+                handle_block_events(key, app);
+            }
+        }
+        _ => {}
+    }
+}
+
+// Seek to the position the column is at along the progress bar
+fn seek_to_column(app: &mut App, progress: Rect, column: u16) {
+    let Some(item) = app
+        .current_playback_context
+        .as_ref()
+        .and_then(|context| context.item.as_ref())
+    else {
+        return;
+    };
+    let duration_ms = item.duration().num_milliseconds().max(0) as u64;
+    let position_ms = (column - progress.x) as u64 * duration_ms / progress.width.max(1) as u64;
+    app.seek_to(position_ms as u32);
+}
+
+fn focus(app: &mut App, list: ListArea) {
+    app.set_current_route_state(Some(list.block), Some(list.block));
+}
+
+fn select_index(app: &mut App, block: ActiveBlock, index: usize) {
+    match block {
+        ActiveBlock::Library => app.library.selected_index = index,
+        ActiveBlock::MyPlaylists => {
+            // Rows are only the listed playlists
+            if let Some(index) = app.visible_playlist_indices().get(index) {
+                app.selected_playlist_index = Some(*index);
+            }
+        }
+        ActiveBlock::ItemTable => app.item_table.selected_index = index,
+        ActiveBlock::AlbumTracks => match app.album_table_context {
+            AlbumTableContext::Full => app.saved_album_tracks_index = index,
+            AlbumTableContext::Simplified => {
+                if let Some(selected_album) = &mut app.selected_album_simplified {
+                    selected_album.selected_index = index;
+                }
+            }
+        },
+        ActiveBlock::AlbumList => app.album_list_index = index,
+        ActiveBlock::Artists => app.artists_list_index = index,
+        ActiveBlock::Podcasts => app.shows_list_index = index,
+        ActiveBlock::RecentlyPlayed => {
+            // Rows are the plays and a header for each day, which can't be selected
+            if let Some(PlayedRow::Play(index)) = app.recently_played_rows().get(index) {
+                match app.recently_played_source {
+                    RecentlyPlayedSource::Spotify => app.recently_played.index = *index,
+                    RecentlyPlayedSource::History => app.local_history.index = *index,
+                }
+            }
+        }
+        ActiveBlock::MadeForYou => app.made_for_you_index = index,
+        ActiveBlock::EpisodeTable => app.episode_list_index = index,
+        ActiveBlock::NotificationHistory => app.notification_history_index = index,
+        ActiveBlock::FinishedShows => {
+            if let Some(finished_shows) = &mut app.finished_shows {
+                finished_shows.selected_index = index;
+            }
+        }
+        _ => {}
+    }
+}
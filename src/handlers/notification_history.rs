@@ -0,0 +1,78 @@
+use super::common_key_events;
+use crate::{app::App, event::Key};
+
+pub fn handler(key: Key, app: &mut App) {
+    let history = app.notifications.history();
+    if history.is_empty() {
+        if common_key_events::left_event(key) {
+            common_key_events::handle_left_event(app);
+        }
+        return;
+    }
+
+    match key {
+        k if common_key_events::left_event(k) => common_key_events::handle_left_event(app),
+        k if common_key_events::down_event(k) => {
+            app.notification_history_index = common_key_events::on_down_press_handler(
+                history,
+                Some(app.notification_history_index),
+            );
+        }
+        k if common_key_events::up_event(k) => {
+            app.notification_history_index = common_key_events::on_up_press_handler(
+                history,
+                Some(app.notification_history_index),
+            );
+        }
+        k if common_key_events::high_event(k) => {
+            app.notification_history_index = common_key_events::on_high_press_handler();
+        }
+        k if common_key_events::middle_event(k) => {
+            app.notification_history_index = common_key_events::on_middle_press_handler(history);
+        }
+        k if common_key_events::low_event(k) => {
+            app.notification_history_index = common_key_events::on_low_press_handler(history);
+        }
+        Key::Enter => app.copy_selected_notification(),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notifications::Severity;
+
+    #[test]
+    fn navigates_the_history() {
+        let mut app = App::default();
+
+        // Nothing to select
+        handler(Key::Down, &mut app);
+        handler(Key::Char('L'), &mut app);
+        assert_eq!(app.notification_history_index, 0);
+
+        app.notify(Severity::Info, "first");
+        app.notify(Severity::Error, "second");
+        app.notify(Severity::Warning, "third");
+
+        handler(Key::Down, &mut app);
+        assert_eq!(app.notification_history_index, 1);
+        assert_eq!(
+            app.notifications
+                .get_newest(app.notification_history_index)
+                .unwrap()
+                .message,
+            "second"
+        );
+
+        handler(Key::Char('L'), &mut app);
+        assert_eq!(app.notification_history_index, 2);
+
+        // Wraps around
+        handler(Key::Down, &mut app);
+        assert_eq!(app.notification_history_index, 0);
+        handler(Key::Up, &mut app);
+        assert_eq!(app.notification_history_index, 2);
+    }
+}
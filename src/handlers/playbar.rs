@@ -1,38 +1,42 @@
 use super::{
-    super::app::{ActiveBlock, App},
+    super::app::{ActiveBlock, App, PlaybarControl},
     common_key_events,
 };
 use crate::event::Key;
 use crate::network::IoEvent;
-use anyhow::anyhow;
-use rspotify::model::{context::CurrentPlaybackContext, PlayableItem};
 
 pub fn handler(key: Key, app: &mut App) {
     match key {
         k if common_key_events::up_event(k) => {
             app.set_current_route_state(Some(ActiveBlock::Empty), Some(ActiveBlock::MyPlaylists));
         }
+        k if common_key_events::left_event(k) => {
+            app.playbar_control = app.playbar_control.previous();
+        }
+        k if common_key_events::right_event(k) => {
+            app.playbar_control = app.playbar_control.next();
+        }
+        Key::Enter => {
+            activate_control(app, app.playbar_control);
+        }
         Key::Char('s') => {
-            if let Some(CurrentPlaybackContext {
-                item: Some(item), ..
-            }) = app.current_playback_context.to_owned()
-            {
-                match item {
-                    PlayableItem::Track(track) => {
-                        if let Some(track_id) = track.id {
-                            app.dispatch(IoEvent::ToggleSaveTrack { track_id });
-                        }
-                    }
-                    PlayableItem::Episode(_) => {
-                        app.handle_error(anyhow!("cannot save episodes right now"));
-                    }
-                };
-            };
+            app.toggle_save_playing_item();
         }
         _ => {}
     };
 }
 
+pub fn activate_control(app: &mut App, control: PlaybarControl) {
+    match control {
+        PlaybarControl::Shuffle => app.shuffle(),
+        PlaybarControl::Repeat => app.repeat(),
+        PlaybarControl::Like => app.toggle_save_playing_item(),
+        PlaybarControl::Previous => app.previous_track(),
+        PlaybarControl::PlayPause => app.toggle_playback(),
+        PlaybarControl::Next => app.dispatch(IoEvent::NextTrack),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -47,4 +51,33 @@ mod tests {
         assert_eq!(current_route.active_block, ActiveBlock::Empty);
         assert_eq!(current_route.hovered_block, ActiveBlock::MyPlaylists);
     }
+
+    #[test]
+    fn cycles_through_controls() {
+        let mut app = App::default();
+        app.set_current_route_state(Some(ActiveBlock::PlayBar), Some(ActiveBlock::PlayBar));
+        assert_eq!(app.playbar_control, PlaybarControl::PlayPause);
+
+        handler(Key::Right, &mut app);
+        assert_eq!(app.playbar_control, PlaybarControl::Next);
+
+        // Wraps around at either end
+        handler(Key::Right, &mut app);
+        assert_eq!(app.playbar_control, PlaybarControl::Shuffle);
+
+        handler(Key::Left, &mut app);
+        assert_eq!(app.playbar_control, PlaybarControl::Next);
+
+        handler(Key::Char('h'), &mut app);
+        assert_eq!(app.playbar_control, PlaybarControl::PlayPause);
+    }
+
+    #[test]
+    fn activates_focused_control() {
+        let mut app = App::default();
+        app.playbar_control = PlaybarControl::Next;
+
+        handler(Key::Enter, &mut app);
+        assert!(app.is_loading);
+    }
 }
@@ -1,105 +1,324 @@
-use super::{
-    super::app::{App, DialogContext, ItemTableContext},
-    common_key_events,
-};
-use crate::app::{ActiveBlock, RouteId};
+use super::{super::app::App, common_key_events};
 use crate::event::Key;
-use crate::network::IoEvent;
+use rspotify::prelude::PlayContextId;
+
+// Moves the selection within the listed playlists, which may be only some of them
+fn select_listed(app: &mut App, next_position: impl FnOnce(&[usize], Option<usize>) -> usize) {
+    let listed = app.visible_playlist_indices();
+    if listed.is_empty() {
+        return;
+    }
+    let position = app
+        .selected_playlist_index
+        .and_then(|index| listed.iter().position(|listed| *listed == index));
+    app.selected_playlist_index = listed.get(next_position(&listed, position)).copied();
+}
 
 pub fn handler(key: Key, app: &mut App) {
     match key {
         k if common_key_events::right_event(k) => common_key_events::handle_right_event(app),
-        k if common_key_events::down_event(k) => {
-            match &app.playlists {
-                Some(p) => {
-                    if let Some(selected_playlist_index) = app.selected_playlist_index {
-                        let next_index = common_key_events::on_down_press_handler(
-                            &p.items,
-                            Some(selected_playlist_index),
-                        );
-                        app.selected_playlist_index = Some(next_index);
-                    }
-                }
-                None => {}
-            };
+        k if common_key_events::down_event(k) && app.selected_playlist_index.is_some() => {
+            select_listed(app, common_key_events::on_down_press_handler)
         }
         k if common_key_events::up_event(k) => {
-            match &app.playlists {
-                Some(p) => {
-                    let next_index = common_key_events::on_up_press_handler(
-                        &p.items,
-                        app.selected_playlist_index,
-                    );
-                    app.selected_playlist_index = Some(next_index);
-                }
-                None => {}
-            };
+            select_listed(app, common_key_events::on_up_press_handler)
         }
         k if common_key_events::high_event(k) => {
-            match &app.playlists {
-                Some(_p) => {
-                    let next_index = common_key_events::on_high_press_handler();
-                    app.selected_playlist_index = Some(next_index);
-                }
-                None => {}
-            };
-        }
-        k if common_key_events::middle_event(k) => {
-            match &app.playlists {
-                Some(p) => {
-                    let next_index = common_key_events::on_middle_press_handler(&p.items);
-                    app.selected_playlist_index = Some(next_index);
-                }
-                None => {}
-            };
-        }
-        k if common_key_events::low_event(k) => {
-            match &app.playlists {
-                Some(p) => {
-                    let next_index = common_key_events::on_low_press_handler(&p.items);
-                    app.selected_playlist_index = Some(next_index);
-                }
-                None => {}
-            };
+            select_listed(app, |_, _| common_key_events::on_high_press_handler())
         }
+        k if common_key_events::middle_event(k) => select_listed(app, |listed, _| {
+            common_key_events::on_middle_press_handler(listed)
+        }),
+        k if common_key_events::low_event(k) => select_listed(app, |listed, _| {
+            common_key_events::on_low_press_handler(listed)
+        }),
         Key::Enter => {
-            if let (Some(playlists), Some(selected_playlist_index)) =
-                (&app.playlists, &app.selected_playlist_index)
-            {
-                app.active_playlist_index = Some(selected_playlist_index.to_owned());
-                app.item_table.context = Some(ItemTableContext::MyPlaylists);
-                app.playlist_offset = 0;
-                if let Some(selected_playlist) =
-                    playlists.items.get(selected_playlist_index.to_owned())
-                {
-                    let playlist_id = selected_playlist.id.to_owned();
-                    app.dispatch(IoEvent::GetPlaylistItems {
-                        playlist_id,
-                        offset: app.playlist_offset,
-                    });
-                }
-            };
+            if let Some(playlist_id) = app.selected_playlist().map(|p| p.id.clone()) {
+                app.open_playlist(playlist_id);
+            }
         }
-        Key::Char('D') => {
-            if let (Some(playlists), Some(selected_index)) =
-                (&app.playlists, app.selected_playlist_index)
-            {
-                let selected_playlist = &playlists.items[selected_index].name;
-                app.dialog = Some(selected_playlist.clone());
-                app.confirm = false;
-
-                app.push_navigation_stack(
-                    RouteId::Dialog,
-                    ActiveBlock::Dialog(DialogContext::PlaylistWindow),
-                );
+        _ if key == app.user_config.keys.add_item_to_queue => {
+            if let Some(playlist_id) = app.selected_playlist().map(|p| p.id.clone()) {
+                app.queue_context(PlayContextId::Playlist(playlist_id));
             }
         }
+        // The same key retries loading the playlists when there are none to rename
+        Key::Char('R') if app.playlists.is_none() => app.retry_startup_fetches(),
+        Key::Char('R') => app.start_playlist_rename(),
+        Key::Char('D') => app.start_playlist_delete(),
+        Key::Char('E') => app.start_playlist_description_edit(),
+        Key::Char('F') => app.cycle_playlist_filter(),
         _ => {}
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use crate::app::{ActiveBlock, DialogContext, InputContext, PlaylistFilter, StartupFetch};
+    use crate::handlers::input;
+    use crate::network::IoEvent;
+    use crate::user_config::UserConfig;
+    use rspotify::model::{
+        page::Page,
+        playlist::{PlaylistTracksRef, SimplifiedPlaylist},
+        user::{PrivateUser, PublicUser},
+        PlaylistId, UserId,
+    };
+    use std::{collections::HashMap, time::Instant};
+
+    const OWNER_ID: &str = "owner";
+    const PLAYLIST_ID: &str = "37i9dQZF1DXcBWIGoYBM5M";
+
+    fn playlist(owner_id: &str) -> SimplifiedPlaylist {
+        SimplifiedPlaylist {
+            collaborative: false,
+            external_urls: HashMap::new(),
+            href: String::new(),
+            id: PlaylistId::from_id(PLAYLIST_ID).unwrap(),
+            images: vec![],
+            name: String::from("Old name"),
+            owner: PublicUser {
+                display_name: None,
+                external_urls: HashMap::new(),
+                followers: None,
+                href: String::new(),
+                id: UserId::from_id(owner_id).unwrap().into_static(),
+                images: vec![],
+            },
+            public: None,
+            snapshot_id: String::new(),
+            tracks: PlaylistTracksRef {
+                href: String::new(),
+                total: 0,
+            },
+        }
+    }
+
+    fn app_with_playlist(owner_id: &str) -> App {
+        with_playlist(App::default(), owner_id)
+    }
+
+    fn with_playlist(mut app: App, owner_id: &str) -> App {
+        app.user = Some(PrivateUser {
+            country: None,
+            display_name: None,
+            email: None,
+            external_urls: HashMap::new(),
+            explicit_content: None,
+            followers: None,
+            href: String::new(),
+            id: UserId::from_id(OWNER_ID).unwrap(),
+            images: None,
+            product: None,
+        });
+        app.playlists = Some(Page {
+            href: String::new(),
+            items: vec![playlist(owner_id)],
+            limit: 20,
+            next: None,
+            offset: 0,
+            previous: None,
+            total: 1,
+        });
+        app.selected_playlist_index = Some(0);
+        app.set_current_route_state(
+            Some(ActiveBlock::MyPlaylists),
+            Some(ActiveBlock::MyPlaylists),
+        );
+        app
+    }
+
+    fn playlist_name(app: &App) -> &str {
+        &app.playlists.as_ref().unwrap().items[0].name
+    }
+
     #[test]
     fn test() {}
+
+    #[test]
+    fn rename_prefills_input_for_owned_playlist() {
+        let mut app = app_with_playlist(OWNER_ID);
+
+        handler(Key::Char('R'), &mut app);
+
+        assert_eq!(app.input.iter().collect::<String>(), "Old name");
+        assert_eq!(app.input_idx, 8);
+        assert_eq!(app.input_cursor_position, 8);
+        assert_eq!(
+            app.input_context,
+            InputContext::RenamePlaylist {
+                playlist_id: PlaylistId::from_id(PLAYLIST_ID).unwrap()
+            }
+        );
+        assert_eq!(app.get_current_route().active_block, ActiveBlock::Input);
+    }
+
+    #[test]
+    fn rename_refused_for_playlist_owned_by_someone_else() {
+        let mut app = app_with_playlist("someone-else");
+
+        handler(Key::Char('R'), &mut app);
+
+        assert_eq!(app.input_context, InputContext::Search);
+        assert!(app.input.is_empty());
+        assert_eq!(app.get_current_route().active_block, ActiveBlock::Error);
+        assert_eq!(
+            app.api_error,
+            "Only playlists you own can be renamed (\"Old name\" is owned by someone-else)"
+        );
+    }
+
+    #[test]
+    fn edits_the_description_of_owned_playlists() {
+        let (io_tx, mut io_rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut app = with_playlist(
+            App::new(io_tx, UserConfig::new(), chrono::Utc::now()),
+            OWNER_ID,
+        );
+        let playlist_id = PlaylistId::from_id(PLAYLIST_ID).unwrap();
+
+        handler(Key::Char('E'), &mut app);
+        match io_rx.try_recv() {
+            Ok(IoEvent::GetPlaylistDescription { playlist_id: id }) => assert_eq!(id, playlist_id),
+            _ => panic!("expected the description to be fetched"),
+        }
+        // Once fetched
+        app.start_playlist_description_input(playlist_id.clone(), String::from("Old"));
+        assert_eq!(
+            app.input_context,
+            InputContext::EditPlaylistDescription {
+                playlist_id: playlist_id.clone()
+            }
+        );
+        input::handler(Key::Char('!'), &mut app);
+        input::handler(Key::Enter, &mut app);
+        match io_rx.try_recv() {
+            Ok(IoEvent::ChangePlaylistDetails {
+                playlist_id: id,
+                name: None,
+                description: Some(description),
+            }) => {
+                assert_eq!(id, playlist_id);
+                assert_eq!(description, "Old!");
+            }
+            _ => panic!("expected the description to change"),
+        }
+        assert_eq!(app.input_context, InputContext::Search);
+        assert_eq!(
+            app.get_current_route().active_block,
+            ActiveBlock::MyPlaylists
+        );
+
+        let mut app = app_with_playlist("someone-else");
+        handler(Key::Char('E'), &mut app);
+        assert_eq!(app.get_current_route().active_block, ActiveBlock::Error);
+        assert_eq!(
+            app.api_error,
+            "Only playlists you own can be edited (\"Old name\" is owned by someone-else)"
+        );
+    }
+
+    #[test]
+    fn delete_refused_for_playlist_owned_by_someone_else() {
+        let mut app = app_with_playlist("someone-else");
+
+        handler(Key::Char('D'), &mut app);
+
+        assert!(app.dialog.is_none());
+        assert_eq!(app.get_current_route().active_block, ActiveBlock::Error);
+
+        let mut app = app_with_playlist(OWNER_ID);
+        handler(Key::Char('D'), &mut app);
+        assert_eq!(app.dialog.as_deref(), Some("Old name"));
+        assert_eq!(
+            app.get_current_route().active_block,
+            ActiveBlock::Dialog(DialogContext::PlaylistWindow)
+        );
+    }
+
+    #[test]
+    fn filters_owned_and_followed_playlists() {
+        let mut app = app_with_playlist(OWNER_ID);
+        let playlists = app.playlists.as_mut().unwrap();
+        playlists.items.push(playlist("someone-else"));
+        playlists.items.push(playlist(OWNER_ID));
+        playlists.items.push(playlist("someone-else"));
+        assert_eq!(app.visible_playlist_indices(), [0, 1, 2, 3]);
+
+        handler(Key::Char('F'), &mut app);
+        assert_eq!(app.playlist_filter, PlaylistFilter::Owned);
+        assert_eq!(app.visible_playlist_indices(), [0, 2]);
+        // Moving skips the playlists that aren't listed
+        handler(Key::Down, &mut app);
+        assert_eq!(app.selected_playlist_index, Some(2));
+        handler(Key::Down, &mut app);
+        assert_eq!(app.selected_playlist_index, Some(0));
+
+        // The selection moves to the first listed playlist when it is filtered out
+        handler(Key::Char('F'), &mut app);
+        assert_eq!(app.playlist_filter, PlaylistFilter::Followed);
+        assert_eq!(app.selected_playlist_index, Some(1));
+        handler(Key::Up, &mut app);
+        assert_eq!(app.selected_playlist_index, Some(3));
+
+        handler(Key::Char('F'), &mut app);
+        assert_eq!(app.playlist_filter, PlaylistFilter::All);
+        assert_eq!(app.selected_playlist_index, Some(3));
+        // Nothing was fetched again
+        assert!(!app.is_loading);
+    }
+
+    #[test]
+    fn retries_loading_the_playlists() {
+        let (io_tx, mut io_rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut app = App::new(io_tx, UserConfig::new(), chrono::Utc::now());
+        app.dispatch_startup_events();
+        while io_rx.try_recv().is_ok() {}
+        app.on_startup_fetch_failed(StartupFetch::Playlists, false, Instant::now());
+
+        handler(Key::Char('R'), &mut app);
+
+        assert!(matches!(io_rx.try_recv(), Ok(IoEvent::GetPlaylists)));
+        assert_eq!(app.get_current_route().active_block, ActiveBlock::Empty);
+    }
+
+    #[test]
+    fn rename_is_applied_optimistically_and_rolled_back_on_failure() {
+        let mut app = app_with_playlist(OWNER_ID);
+        let playlist_id = PlaylistId::from_id(PLAYLIST_ID).unwrap();
+
+        app.rename_playlist(playlist_id.clone(), String::from("New name"));
+        assert_eq!(playlist_name(&app), "New name");
+
+        app.revert_playlist_rename(playlist_id, anyhow::anyhow!("forbidden"));
+        assert_eq!(playlist_name(&app), "Old name");
+        assert!(app.pending_playlist_renames.is_empty());
+        assert!(app.notifications.current().is_some());
+    }
+
+    #[test]
+    fn rename_is_kept_on_success() {
+        let mut app = app_with_playlist(OWNER_ID);
+        let playlist_id = PlaylistId::from_id(PLAYLIST_ID).unwrap();
+
+        app.rename_playlist(playlist_id.clone(), String::from("New name"));
+        app.confirm_playlist_rename(playlist_id);
+
+        assert_eq!(playlist_name(&app), "New name");
+        assert!(app.pending_playlist_renames.is_empty());
+    }
+
+    #[test]
+    fn rename_to_empty_or_same_name_is_a_no_op() {
+        let mut app = app_with_playlist(OWNER_ID);
+        let playlist_id = PlaylistId::from_id(PLAYLIST_ID).unwrap();
+
+        app.rename_playlist(playlist_id.clone(), String::from("   "));
+        app.rename_playlist(playlist_id, String::from("Old name"));
+
+        assert_eq!(playlist_name(&app), "Old name");
+        assert!(app.pending_playlist_renames.is_empty());
+        assert!(!app.is_loading);
+    }
 }
@@ -56,6 +56,38 @@ pub fn handler(key: Key, app: &mut App) {
         k if k == app.user_config.keys.next_page => app.get_current_user_saved_shows_next(),
         k if k == app.user_config.keys.previous_page => app.get_current_user_saved_shows_previous(),
         Key::Char('D') => app.user_unfollow_show(ActiveBlock::Podcasts),
+        Key::Char('F') => app.find_finished_shows(),
         _ => {}
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::user_config::UserConfig;
+    use chrono::Utc;
+    use rspotify::model::Page;
+    use tokio::sync::mpsc::unbounded_channel;
+
+    #[test]
+    fn no_request_past_the_last_page() {
+        let (io_tx, mut io_rx) = unbounded_channel();
+        let mut app = App::new(io_tx, UserConfig::new(), Utc::now());
+        app.library.saved_shows.add_pages(Page {
+            href: String::new(),
+            items: vec![],
+            limit: 20,
+            next: None,
+            offset: 0,
+            previous: None,
+            total: 3,
+        });
+
+        handler(app.user_config.keys.next_page, &mut app);
+        assert!(io_rx.try_recv().is_err());
+        assert_eq!(
+            app.notifications.current().unwrap().message,
+            "This is the last page of podcasts"
+        );
+    }
+}
@@ -1,11 +1,24 @@
-use rspotify::prelude::PlayableId;
+use rspotify::{
+    model::TrackId,
+    prelude::{PlayContextId, PlayableId},
+};
+use spotify_tui_util::ParseFromUri;
 
 use super::{super::app::App, common_key_events};
-use crate::{app::RecommendationsContext, event::Key, network::IoEvent};
+use crate::{
+    app::{RecentlyPlayedSource, RecommendationsContext},
+    event::Key,
+    history::HistoryEntry,
+    network::{IoEvent, PlaybackOffset},
+};
 
 pub fn handler(key: Key, app: &mut App) {
     match key {
         k if common_key_events::left_event(k) => common_key_events::handle_left_event(app),
+        Key::Char('t') => app.toggle_recently_played_source(),
+        _ if app.recently_played_source == RecentlyPlayedSource::History => {
+            history_handler(key, app)
+        }
         k if common_key_events::down_event(k) => {
             if let Some(recently_played_result) = &app.recently_played.result {
                 let next_index = common_key_events::on_down_press_handler(
@@ -50,15 +63,36 @@ pub fn handler(key: Key, app: &mut App) {
                     recently_played_result.items.get(app.recently_played.index)
                 {
                     if let Some(track_id) = selected_track.track.id.clone() {
-                        app.dispatch(IoEvent::ToggleSaveTrack { track_id });
+                        app.toggle_save_track(track_id);
                     };
                 };
             };
         }
         Key::Enter => {
             if let Some(recently_played_result) = &app.recently_played.result.clone() {
-                let playable_ids = recently_played_result
-                    .items
+                // From where it was played, starting at it
+                let selected = recently_played_result.items.get(app.recently_played.index);
+                if let Some((play_context_id, track_id)) = selected.and_then(|item| {
+                    let uri = &item.context.as_ref()?.uri;
+                    let play_context_id = PlayContextId::from_uri(uri).ok()?.into_static();
+                    Some((play_context_id, item.track.id.clone()?))
+                }) {
+                    app.dispatch(IoEvent::StartContextPlayback {
+                        play_context_id,
+                        offset: Some(PlaybackOffset::Item(PlayableId::Track(track_id))),
+                    });
+                    return;
+                }
+
+                // Otherwise the list as shown, where local files can't be played and don't count
+                // towards the offset
+                let index = app.recently_played.index;
+                let items = &recently_played_result.items;
+                let offset = items[..index.min(items.len())]
+                    .iter()
+                    .filter(|item| item.track.id.is_some())
+                    .count();
+                let playable_ids = items
                     .iter()
                     .filter_map(|item| item.track.id.clone())
                     .map(PlayableId::Track)
@@ -66,7 +100,7 @@ pub fn handler(key: Key, app: &mut App) {
 
                 app.dispatch(IoEvent::StartPlayablesPlayback {
                     playable_ids,
-                    offset: Some(app.recently_played.index as u32),
+                    offset: Some(PlaybackOffset::Index(offset as u32)),
                 });
             };
         }
@@ -101,9 +135,97 @@ pub fn handler(key: Key, app: &mut App) {
     };
 }
 
+fn playable_id(entry: &HistoryEntry) -> Option<PlayableId<'static>> {
+    PlayableId::from_uri(&entry.uri)
+        .ok()
+        .map(PlayableId::into_static)
+}
+
+fn selected_entry(app: &App) -> Option<&HistoryEntry> {
+    app.local_history.entries.get(app.local_history.index)
+}
+
+// The same keys as for Spotify's recent plays, over the entries read from the history file
+fn history_handler(key: Key, app: &mut App) {
+    match key {
+        k if common_key_events::down_event(k) => {
+            // Read further back before wrapping around to the top
+            if app.local_history.index + 1 >= app.local_history.entries.len() {
+                app.load_more_history();
+            }
+            app.local_history.index = common_key_events::on_down_press_handler(
+                &app.local_history.entries,
+                Some(app.local_history.index),
+            );
+        }
+        k if common_key_events::up_event(k) => {
+            app.local_history.index = common_key_events::on_up_press_handler(
+                &app.local_history.entries,
+                Some(app.local_history.index),
+            );
+        }
+        k if common_key_events::high_event(k) => {
+            app.local_history.index = common_key_events::on_high_press_handler();
+        }
+        k if common_key_events::middle_event(k) => {
+            app.local_history.index =
+                common_key_events::on_middle_press_handler(&app.local_history.entries);
+        }
+        k if common_key_events::low_event(k) => {
+            app.local_history.index =
+                common_key_events::on_low_press_handler(&app.local_history.entries);
+        }
+        Key::Char('s') => {
+            if let Some(track_id) = selected_entry(app)
+                .and_then(|entry| TrackId::from_uri(&entry.uri).ok())
+                .map(TrackId::into_static)
+            {
+                app.toggle_save_track(track_id);
+            }
+        }
+        Key::Enter => {
+            let index = app.local_history.index;
+            let playable_ids = app
+                .local_history
+                .entries
+                .iter()
+                .map(playable_id)
+                .collect::<Vec<_>>();
+            if playable_ids.get(index).is_some_and(Option::is_some) {
+                // Entries that can't be played are left out, so they don't count towards the offset
+                let offset = playable_ids[..index].iter().flatten().count();
+                app.dispatch(IoEvent::StartPlayablesPlayback {
+                    playable_ids: playable_ids.into_iter().flatten().collect(),
+                    offset: Some(PlaybackOffset::Index(offset as u32)),
+                });
+            }
+        }
+        Key::Char('r') => {
+            if let Some((id, title)) = selected_entry(app).and_then(|entry| {
+                let id = TrackId::from_uri(&entry.uri).ok()?.into_static();
+                Some((id, entry.title.clone()))
+            }) {
+                app.recommendations_context = Some(RecommendationsContext::Song);
+                app.recommendations_seed = title;
+                app.get_recommendations_for_track_id(id);
+            }
+        }
+        _ if key == app.user_config.keys.add_item_to_queue => {
+            if let Some(playable_id) = selected_entry(app).and_then(playable_id) {
+                app.dispatch(IoEvent::AddItemToQueue { playable_id });
+            }
+        }
+        _ => {}
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::{super::super::app::ActiveBlock, *};
+    use rspotify::{
+        model::{CursorBasedPage, PlayHistory},
+        prelude::Id,
+    };
 
     #[test]
     fn on_left_press() {
@@ -128,4 +250,145 @@ mod tests {
         let current_route = app.get_current_route();
         assert_eq!(current_route.active_block, ActiveBlock::Empty);
     }
+
+    #[test]
+    fn plays_from_the_local_history() {
+        use crate::{history, user_config::UserConfig};
+        use chrono::Utc;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("history.jsonl");
+        for uri in [
+            "spotify:track:4iV5W9uYEdYUVa79Axb7Rh",
+            "local:nonsense",
+            "spotify:episode:512ojhOuo1ktJprKbVcKyQ",
+        ] {
+            history::append(
+                &path,
+                &HistoryEntry {
+                    played_at: Utc::now(),
+                    uri: String::from(uri),
+                    title: String::from("Title"),
+                    artist: String::from("Artist"),
+                    context_uri: None,
+                },
+            )
+            .unwrap();
+        }
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut app = App::new(tx, UserConfig::new(), Utc::now());
+        app.history_path = Some(path);
+
+        handler(Key::Char('t'), &mut app);
+        assert_eq!(app.recently_played_source, RecentlyPlayedSource::History);
+        assert_eq!(app.local_history.entries.len(), 3);
+        // The liked state of the tracks is checked
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(IoEvent::CurrentUserSavedTracksContains { .. })
+        ));
+
+        // Newest first, so the track is last, after the entry that can't be played
+        handler(Key::Down, &mut app);
+        handler(Key::Down, &mut app);
+        handler(Key::Enter, &mut app);
+        match rx.try_recv() {
+            Ok(IoEvent::StartPlayablesPlayback {
+                playable_ids,
+                offset,
+            }) => {
+                assert_eq!(playable_ids.len(), 2);
+                assert_eq!(offset, Some(PlaybackOffset::Index(1)));
+            }
+            _ => panic!("expected playback to start"),
+        }
+
+        handler(Key::Char('t'), &mut app);
+        assert_eq!(app.recently_played_source, RecentlyPlayedSource::Spotify);
+    }
+
+    fn recently_played(items: &[(Option<&str>, Option<&str>)]) -> CursorBasedPage<PlayHistory> {
+        let items = items
+            .iter()
+            .map(|(id, context_uri)| {
+                serde_json::json!({
+                    "track": {
+                        "album": {
+                            "artists": [], "external_urls": {}, "images": [], "name": "Album"
+                        },
+                        "artists": [],
+                        "disc_number": 1,
+                        "duration_ms": 180_000,
+                        "explicit": false,
+                        "external_ids": {},
+                        "external_urls": {},
+                        "id": id,
+                        "is_local": id.is_none(),
+                        "name": "Track",
+                        "popularity": 0,
+                        "track_number": 1,
+                    },
+                    "played_at": "2024-01-01T00:00:00Z",
+                    "context": context_uri.map(|uri| serde_json::json!({
+                        "uri": uri, "href": "", "external_urls": {}, "type": "playlist"
+                    })),
+                })
+            })
+            .collect::<Vec<_>>();
+        serde_json::from_value(serde_json::json!({
+            "href": "", "items": items, "limit": 50, "next": null, "cursors": null, "total": null
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn plays_from_where_it_was_played() {
+        use crate::user_config::UserConfig;
+        use chrono::Utc;
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut app = App::new(tx, UserConfig::new(), Utc::now());
+        app.recently_played.result = Some(recently_played(&[
+            (None, None),
+            (
+                Some("4iV5W9uYEdYUVa79Axb7Rh"),
+                Some("spotify:playlist:37i9dQZF1DXcBWIGoYBM5M"),
+            ),
+            (
+                Some("0eGsygTp906u18L0Oimnem"),
+                Some("spotify:user:listener:collection"),
+            ),
+        ]));
+
+        app.recently_played.index = 1;
+        handler(Key::Enter, &mut app);
+        match rx.try_recv() {
+            Ok(IoEvent::StartContextPlayback {
+                play_context_id,
+                offset: Some(PlaybackOffset::Item(playable_id)),
+            }) => {
+                assert_eq!(
+                    play_context_id.uri(),
+                    "spotify:playlist:37i9dQZF1DXcBWIGoYBM5M"
+                );
+                assert_eq!(playable_id.uri(), "spotify:track:4iV5W9uYEdYUVa79Axb7Rh");
+            }
+            _ => panic!("expected the playlist to play"),
+        }
+
+        // Liked songs can't be played as a context, so the list is played instead
+        app.recently_played.index = 2;
+        handler(Key::Enter, &mut app);
+        match rx.try_recv() {
+            Ok(IoEvent::StartPlayablesPlayback {
+                playable_ids,
+                offset,
+            }) => {
+                assert_eq!(playable_ids.len(), 2);
+                assert_eq!(offset, Some(PlaybackOffset::Index(1)));
+            }
+            _ => panic!("expected the list to play"),
+        }
+    }
 }
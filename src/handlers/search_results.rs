@@ -6,14 +6,14 @@ use super::{
     common_key_events,
 };
 use crate::event::Key;
-use crate::network::IoEvent;
+use crate::network::{IoEvent, PlaybackOffset};
 use rspotify::model::idtypes::*;
 
 fn handle_down_press_on_selected_block(app: &mut App) {
     // Start selecting within the selected block
     match app.search_results.selected_block {
         SearchResultBlock::AlbumSearch => {
-            if let Some(result) = &app.search_results.albums {
+            if let Some(result) = app.search_results.albums.get_results(None) {
                 let next_index = common_key_events::on_down_press_handler(
                     &result.items,
                     app.search_results.selected_album_index,
@@ -22,7 +22,7 @@ fn handle_down_press_on_selected_block(app: &mut App) {
             }
         }
         SearchResultBlock::SongSearch => {
-            if let Some(result) = &app.search_results.tracks {
+            if let Some(result) = app.search_results.tracks.get_results(None) {
                 let next_index = common_key_events::on_down_press_handler(
                     &result.items,
                     app.search_results.selected_tracks_index,
@@ -31,7 +31,7 @@ fn handle_down_press_on_selected_block(app: &mut App) {
             }
         }
         SearchResultBlock::ArtistSearch => {
-            if let Some(result) = &app.search_results.artists {
+            if let Some(result) = app.search_results.artists.get_results(None) {
                 let next_index = common_key_events::on_down_press_handler(
                     &result.items,
                     app.search_results.selected_artists_index,
@@ -40,7 +40,7 @@ fn handle_down_press_on_selected_block(app: &mut App) {
             }
         }
         SearchResultBlock::PlaylistSearch => {
-            if let Some(result) = &app.search_results.playlists {
+            if let Some(result) = app.search_results.playlists.get_results(None) {
                 let next_index = common_key_events::on_down_press_handler(
                     &result.items,
                     app.search_results.selected_playlists_index,
@@ -49,7 +49,7 @@ fn handle_down_press_on_selected_block(app: &mut App) {
             }
         }
         SearchResultBlock::ShowSearch => {
-            if let Some(result) = &app.search_results.shows {
+            if let Some(result) = app.search_results.shows.get_results(None) {
                 let next_index = common_key_events::on_down_press_handler(
                     &result.items,
                     app.search_results.selected_shows_index,
@@ -86,7 +86,7 @@ fn handle_up_press_on_selected_block(app: &mut App) {
     // Start selecting within the selected block
     match app.search_results.selected_block {
         SearchResultBlock::AlbumSearch => {
-            if let Some(result) = &app.search_results.albums {
+            if let Some(result) = app.search_results.albums.get_results(None) {
                 let next_index = common_key_events::on_up_press_handler(
                     &result.items,
                     app.search_results.selected_album_index,
@@ -95,7 +95,7 @@ fn handle_up_press_on_selected_block(app: &mut App) {
             }
         }
         SearchResultBlock::SongSearch => {
-            if let Some(result) = &app.search_results.tracks {
+            if let Some(result) = app.search_results.tracks.get_results(None) {
                 let next_index = common_key_events::on_up_press_handler(
                     &result.items,
                     app.search_results.selected_tracks_index,
@@ -104,7 +104,7 @@ fn handle_up_press_on_selected_block(app: &mut App) {
             }
         }
         SearchResultBlock::ArtistSearch => {
-            if let Some(result) = &app.search_results.artists {
+            if let Some(result) = app.search_results.artists.get_results(None) {
                 let next_index = common_key_events::on_up_press_handler(
                     &result.items,
                     app.search_results.selected_artists_index,
@@ -113,7 +113,7 @@ fn handle_up_press_on_selected_block(app: &mut App) {
             }
         }
         SearchResultBlock::PlaylistSearch => {
-            if let Some(result) = &app.search_results.playlists {
+            if let Some(result) = app.search_results.playlists.get_results(None) {
                 let next_index = common_key_events::on_up_press_handler(
                     &result.items,
                     app.search_results.selected_playlists_index,
@@ -122,7 +122,7 @@ fn handle_up_press_on_selected_block(app: &mut App) {
             }
         }
         SearchResultBlock::ShowSearch => {
-            if let Some(result) = &app.search_results.shows {
+            if let Some(result) = app.search_results.shows.get_results(None) {
                 let next_index = common_key_events::on_up_press_handler(
                     &result.items,
                     app.search_results.selected_shows_index,
@@ -158,31 +158,31 @@ fn handle_up_press_on_hovered_block(app: &mut App) {
 fn handle_high_press_on_selected_block(app: &mut App) {
     match app.search_results.selected_block {
         SearchResultBlock::AlbumSearch => {
-            if let Some(_result) = &app.search_results.albums {
+            if let Some(_result) = app.search_results.albums.get_results(None) {
                 let next_index = common_key_events::on_high_press_handler();
                 app.search_results.selected_album_index = Some(next_index);
             }
         }
         SearchResultBlock::SongSearch => {
-            if let Some(_result) = &app.search_results.tracks {
+            if let Some(_result) = app.search_results.tracks.get_results(None) {
                 let next_index = common_key_events::on_high_press_handler();
                 app.search_results.selected_tracks_index = Some(next_index);
             }
         }
         SearchResultBlock::ArtistSearch => {
-            if let Some(_result) = &app.search_results.artists {
+            if let Some(_result) = app.search_results.artists.get_results(None) {
                 let next_index = common_key_events::on_high_press_handler();
                 app.search_results.selected_artists_index = Some(next_index);
             }
         }
         SearchResultBlock::PlaylistSearch => {
-            if let Some(_result) = &app.search_results.playlists {
+            if let Some(_result) = app.search_results.playlists.get_results(None) {
                 let next_index = common_key_events::on_high_press_handler();
                 app.search_results.selected_playlists_index = Some(next_index);
             }
         }
         SearchResultBlock::ShowSearch => {
-            if let Some(_result) = &app.search_results.shows {
+            if let Some(_result) = app.search_results.shows.get_results(None) {
                 let next_index = common_key_events::on_high_press_handler();
                 app.search_results.selected_shows_index = Some(next_index);
             }
@@ -194,31 +194,31 @@ fn handle_high_press_on_selected_block(app: &mut App) {
 fn handle_middle_press_on_selected_block(app: &mut App) {
     match app.search_results.selected_block {
         SearchResultBlock::AlbumSearch => {
-            if let Some(result) = &app.search_results.albums {
+            if let Some(result) = app.search_results.albums.get_results(None) {
                 let next_index = common_key_events::on_middle_press_handler(&result.items);
                 app.search_results.selected_album_index = Some(next_index);
             }
         }
         SearchResultBlock::SongSearch => {
-            if let Some(result) = &app.search_results.tracks {
+            if let Some(result) = app.search_results.tracks.get_results(None) {
                 let next_index = common_key_events::on_middle_press_handler(&result.items);
                 app.search_results.selected_tracks_index = Some(next_index);
             }
         }
         SearchResultBlock::ArtistSearch => {
-            if let Some(result) = &app.search_results.artists {
+            if let Some(result) = app.search_results.artists.get_results(None) {
                 let next_index = common_key_events::on_middle_press_handler(&result.items);
                 app.search_results.selected_artists_index = Some(next_index);
             }
         }
         SearchResultBlock::PlaylistSearch => {
-            if let Some(result) = &app.search_results.playlists {
+            if let Some(result) = app.search_results.playlists.get_results(None) {
                 let next_index = common_key_events::on_middle_press_handler(&result.items);
                 app.search_results.selected_playlists_index = Some(next_index);
             }
         }
         SearchResultBlock::ShowSearch => {
-            if let Some(result) = &app.search_results.shows {
+            if let Some(result) = app.search_results.shows.get_results(None) {
                 let next_index = common_key_events::on_middle_press_handler(&result.items);
                 app.search_results.selected_shows_index = Some(next_index);
             }
@@ -230,31 +230,31 @@ fn handle_middle_press_on_selected_block(app: &mut App) {
 fn handle_low_press_on_selected_block(app: &mut App) {
     match app.search_results.selected_block {
         SearchResultBlock::AlbumSearch => {
-            if let Some(result) = &app.search_results.albums {
+            if let Some(result) = app.search_results.albums.get_results(None) {
                 let next_index = common_key_events::on_low_press_handler(&result.items);
                 app.search_results.selected_album_index = Some(next_index);
             }
         }
         SearchResultBlock::SongSearch => {
-            if let Some(result) = &app.search_results.tracks {
+            if let Some(result) = app.search_results.tracks.get_results(None) {
                 let next_index = common_key_events::on_low_press_handler(&result.items);
                 app.search_results.selected_tracks_index = Some(next_index);
             }
         }
         SearchResultBlock::ArtistSearch => {
-            if let Some(result) = &app.search_results.artists {
+            if let Some(result) = app.search_results.artists.get_results(None) {
                 let next_index = common_key_events::on_low_press_handler(&result.items);
                 app.search_results.selected_artists_index = Some(next_index);
             }
         }
         SearchResultBlock::PlaylistSearch => {
-            if let Some(result) = &app.search_results.playlists {
+            if let Some(result) = app.search_results.playlists.get_results(None) {
                 let next_index = common_key_events::on_low_press_handler(&result.items);
                 app.search_results.selected_playlists_index = Some(next_index);
             }
         }
         SearchResultBlock::ShowSearch => {
-            if let Some(result) = &app.search_results.shows {
+            if let Some(result) = app.search_results.shows.get_results(None) {
                 let next_index = common_key_events::on_low_press_handler(&result.items);
                 app.search_results.selected_shows_index = Some(next_index);
             }
@@ -268,7 +268,7 @@ fn handle_add_item_to_queue(app: &mut App) {
         SearchResultBlock::SongSearch => {
             if let (Some(index), Some(tracks)) = (
                 app.search_results.selected_tracks_index,
-                &app.search_results.tracks,
+                app.search_results.tracks.get_results(None),
             ) {
                 if let Some(track_id) = tracks.items.get(index).and_then(|track| track.id.clone()) {
                     app.dispatch(IoEvent::AddItemToQueue {
@@ -277,9 +277,29 @@ fn handle_add_item_to_queue(app: &mut App) {
                 }
             }
         }
+        SearchResultBlock::PlaylistSearch => {
+            if let Some(playlist_id) = app
+                .search_results
+                .selected_playlists_index
+                .zip(app.search_results.playlists.get_results(None))
+                .and_then(|(index, playlists)| playlists.items.get(index))
+                .map(|playlist| playlist.id.clone())
+            {
+                app.queue_context(PlayContextId::Playlist(playlist_id));
+            }
+        }
+        SearchResultBlock::AlbumSearch => {
+            if let Some(album_id) = app
+                .search_results
+                .selected_album_index
+                .zip(app.search_results.albums.get_results(None))
+                .and_then(|(index, albums)| albums.items.get(index))
+                .and_then(|album| album.id.clone())
+            {
+                app.queue_context(PlayContextId::Album(album_id));
+            }
+        }
         SearchResultBlock::ArtistSearch => {}
-        SearchResultBlock::PlaylistSearch => {}
-        SearchResultBlock::AlbumSearch => {}
         SearchResultBlock::ShowSearch => {}
         SearchResultBlock::Empty => {}
     };
@@ -290,7 +310,7 @@ fn handle_enter_event_on_selected_block(app: &mut App) {
         SearchResultBlock::AlbumSearch => {
             if let (Some(index), Some(albums_result)) = (
                 &app.search_results.selected_album_index,
-                &app.search_results.albums,
+                app.search_results.albums.get_results(None),
             ) {
                 if let Some(album) = albums_result.items.get(index.to_owned()).cloned() {
                     app.item_table.context = Some(ItemTableContext::AlbumSearch);
@@ -301,8 +321,11 @@ fn handle_enter_event_on_selected_block(app: &mut App) {
             }
         }
         SearchResultBlock::SongSearch => {
-            let offset = app.search_results.selected_tracks_index.map(|x| x as u32);
-            let tracks = app.search_results.tracks.clone();
+            let offset = app
+                .search_results
+                .selected_tracks_index
+                .map(|x| PlaybackOffset::Index(x as u32));
+            let tracks = app.search_results.tracks.get_results(None).cloned();
             let playable_ids = tracks
                 .map(|x| x.items)
                 .unwrap_or_default()
@@ -318,7 +341,7 @@ fn handle_enter_event_on_selected_block(app: &mut App) {
         }
         SearchResultBlock::ArtistSearch => {
             if let Some(index) = &app.search_results.selected_artists_index {
-                if let Some(result) = app.search_results.artists.clone() {
+                if let Some(result) = app.search_results.artists.get_results(None).cloned() {
                     if let Some(artist) = result.items.get(index.to_owned()) {
                         app.get_artist(artist.id.clone(), artist.name.clone());
                         app.push_navigation_stack(RouteId::Artist, ActiveBlock::ArtistBlock);
@@ -329,7 +352,7 @@ fn handle_enter_event_on_selected_block(app: &mut App) {
         SearchResultBlock::PlaylistSearch => {
             if let (Some(index), Some(playlists_result)) = (
                 app.search_results.selected_playlists_index,
-                &app.search_results.playlists,
+                app.search_results.playlists.get_results(None),
             ) {
                 if let Some(playlist) = playlists_result.items.get(index) {
                     // Go to playlist tracks table
@@ -345,9 +368,12 @@ fn handle_enter_event_on_selected_block(app: &mut App) {
         SearchResultBlock::ShowSearch => {
             if let (Some(index), Some(shows_result)) = (
                 app.search_results.selected_shows_index,
-                &app.search_results.shows,
+                app.search_results.shows.get_results(None),
             ) {
                 if let Some(show) = shows_result.items.get(index).cloned() {
+                    if !app.check_available(&show.name, &show.available_markets) {
+                        return;
+                    }
                     // Go to show tracks table
                     app.dispatch(IoEvent::GetShowEpisodes {
                         show: Box::new(show),
@@ -400,7 +426,7 @@ fn handle_recommended_tracks(app: &mut App) {
         SearchResultBlock::AlbumSearch => {}
         SearchResultBlock::SongSearch => {
             if let Some(index) = &app.search_results.selected_tracks_index {
-                if let Some(result) = app.search_results.tracks.clone() {
+                if let Some(result) = app.search_results.tracks.get_results(None).cloned() {
                     if let Some(track) = result.items.get(index.to_owned()) {
                         let track_ids = track.id.clone().map(|id| vec![id]);
 
@@ -413,7 +439,7 @@ fn handle_recommended_tracks(app: &mut App) {
         }
         SearchResultBlock::ArtistSearch => {
             if let Some(index) = &app.search_results.selected_artists_index {
-                if let Some(result) = app.search_results.artists.clone() {
+                if let Some(result) = app.search_results.artists.get_results(None).cloned() {
                     if let Some(artist) = result.items.get(index.to_owned()) {
                         let artist_ids = Some(vec![artist.id.clone()]);
                         app.recommendations_context = Some(RecommendationsContext::Artist);
@@ -536,7 +562,7 @@ pub fn handler(key: Key, app: &mut App) {
             }
             SearchResultBlock::PlaylistSearch => {
                 if let (Some(playlists), Some(selected_index)) = (
-                    &app.search_results.playlists,
+                    app.search_results.playlists.get_results(None),
                     app.search_results.selected_playlists_index,
                 ) {
                     let selected_playlist = &playlists.items[selected_index].name;
@@ -553,8 +579,141 @@ pub fn handler(key: Key, app: &mut App) {
             SearchResultBlock::Empty => {}
         },
         Key::Char('r') => handle_recommended_tracks(app),
+        k if k == app.user_config.keys.next_page => app.search_results_next_page(),
+        k if k == app.user_config.keys.previous_page => app.search_results_previous_page(),
         _ if key == app.user_config.keys.add_item_to_queue => handle_add_item_to_queue(app),
         // Add `s` to "see more" on each option
         _ => {}
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::user_config::UserConfig;
+    use chrono::Utc;
+    use rspotify::model::{user::PrivateUser, Country, Page, SearchType, SimplifiedShow};
+    use serde_json::{json, Value};
+    use tokio::sync::mpsc::unbounded_channel;
+
+    fn show(id: &str, name: &str, available_markets: &[&str]) -> Value {
+        json!({
+            "available_markets": available_markets,
+            "copyrights": [],
+            "description": "",
+            "explicit": false,
+            "external_urls": {},
+            "href": "",
+            "id": id,
+            "images": [],
+            "languages": ["en"],
+            "media_type": "audio",
+            "name": name,
+            "publisher": "Publisher",
+        })
+    }
+
+    #[test]
+    fn shows_unavailable_in_the_users_market_are_blocked() {
+        let (io_tx, mut io_rx) = unbounded_channel();
+        let mut app = App::new(io_tx, UserConfig::new(), Utc::now());
+        app.user = serde_json::from_value::<PrivateUser>(json!({
+            "country": "DE",
+            "external_urls": {},
+            "href": "",
+            "id": "listener",
+        }))
+        .ok();
+        assert_eq!(app.get_user_country(), Some(Country::Germany));
+        // The market in the config comes first
+        app.user_config.behavior.market = Some(Country::UnitedKingdom);
+        assert_eq!(app.get_user_country(), Some(Country::UnitedKingdom));
+        app.user_config.behavior.market = None;
+        app.search_results.shows.add_pages(
+            serde_json::from_value(json!({
+                "href": "",
+                "items": [
+                    show("5CfCWKI5pZ28U0uOzXkDHe", "Only in the UK", &["GB"]),
+                    show("4rOoJ6Egrf8K2IrywzwOMk", "Everywhere", &[]),
+                ],
+                "limit": 2,
+                "next": null,
+                "offset": 0,
+                "previous": null,
+                "total": 2,
+            }))
+            .unwrap(),
+        );
+        app.search_results.selected_block = SearchResultBlock::ShowSearch;
+        app.search_results.selected_shows_index = Some(0);
+
+        handler(Key::Enter, &mut app);
+        assert!(io_rx.try_recv().is_err());
+        assert_eq!(
+            app.notifications.current().unwrap().message,
+            "Only in the UK isn't available in your market (DE)"
+        );
+
+        app.search_results.selected_shows_index = Some(1);
+        handler(Key::Enter, &mut app);
+        assert!(matches!(
+            io_rx.try_recv(),
+            Ok(IoEvent::GetShowEpisodes { .. })
+        ));
+    }
+
+    fn show_page(offset: u32, next: bool) -> Page<SimplifiedShow> {
+        serde_json::from_value(json!({
+            "href": "",
+            "items": [show("4rOoJ6Egrf8K2IrywzwOMk", &format!("Show {}", offset), &[])],
+            "limit": 1,
+            "next": if next { Some("next") } else { None },
+            "offset": offset,
+            "previous": null,
+            "total": 2,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn pages_through_a_block_of_results() {
+        let (io_tx, mut io_rx) = unbounded_channel();
+        let mut app = App::new(io_tx, UserConfig::new(), Utc::now());
+        app.search_results.search_term = String::from("news");
+        app.search_results.shows.add_pages(show_page(0, true));
+        app.search_results.hovered_block = SearchResultBlock::ShowSearch;
+        assert_eq!(app.search_results.shows.page_count(), Some((1, 2)));
+
+        // The next page is fetched for the hovered block
+        handler(Key::Ctrl('d'), &mut app);
+        match io_rx.try_recv() {
+            Ok(IoEvent::GetMoreSearchResults {
+                search_term,
+                search_type,
+                offset,
+                ..
+            }) => {
+                assert_eq!(search_term, "news");
+                assert_eq!(search_type, SearchType::Show);
+                assert_eq!(offset, 1);
+            }
+            event => panic!("unexpected {:?}", event),
+        }
+
+        app.search_results.shows.add_pages(show_page(1, false));
+        app.search_results.selected_shows_index = Some(0);
+        assert_eq!(app.search_results.shows.page_count(), Some((2, 2)));
+        // Nothing more to fetch after the last page
+        handler(Key::Ctrl('d'), &mut app);
+        assert!(io_rx.try_recv().is_err());
+
+        // Pages already fetched are shown again without another request
+        handler(Key::Ctrl('u'), &mut app);
+        assert!(io_rx.try_recv().is_err());
+        assert_eq!(app.search_results.shows.page_count(), Some((1, 2)));
+        assert_eq!(app.search_results.selected_shows_index, Some(0));
+        handler(Key::Ctrl('d'), &mut app);
+        assert!(io_rx.try_recv().is_err());
+        assert_eq!(app.search_results.shows.page_count(), Some((2, 2)));
+    }
+}
@@ -1,11 +1,20 @@
 use super::{
     super::app::{ActiveBlock, App},
-    common_key_events,
+    common_key_events, input,
 };
 use crate::event::Key;
 use crate::network::IoEvent;
 
 pub fn handler(key: Key, app: &mut App) {
+    if app.is_finding_device() {
+        match key {
+            Key::Enter => app.transfer_to_found_device(),
+            Key::Esc => app.stop_finding_device(),
+            _ => input::handler(key, app),
+        }
+        return;
+    }
+
     match key {
         Key::Esc => {
             app.set_current_route_state(Some(ActiveBlock::Library), None);
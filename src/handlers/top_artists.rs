@@ -0,0 +1,84 @@
+use rspotify::prelude::PlayContextId;
+
+use super::common_key_events;
+use crate::{
+    app::{ActiveBlock, App, RecommendationsContext, RouteId},
+    event::Key,
+    network::IoEvent,
+};
+
+pub fn handler(key: Key, app: &mut App) {
+    let top_items = &mut app.top_items;
+    match key {
+        k if common_key_events::left_event(k) => common_key_events::handle_left_event(app),
+        k if common_key_events::down_event(k) => {
+            if let Some(artists) = &top_items.artists {
+                top_items.selected_artist_index = common_key_events::on_down_press_handler(
+                    &artists.items,
+                    Some(top_items.selected_artist_index),
+                );
+            }
+        }
+        k if common_key_events::up_event(k) => {
+            if let Some(artists) = &top_items.artists {
+                top_items.selected_artist_index = common_key_events::on_up_press_handler(
+                    &artists.items,
+                    Some(top_items.selected_artist_index),
+                );
+            }
+        }
+        k if common_key_events::high_event(k) => {
+            top_items.selected_artist_index = common_key_events::on_high_press_handler();
+        }
+        // Both count back from the end, which an empty page doesn't have
+        k if common_key_events::middle_event(k) => {
+            if let Some(artists) = top_items
+                .artists
+                .as_ref()
+                .filter(|page| !page.items.is_empty())
+            {
+                top_items.selected_artist_index =
+                    common_key_events::on_middle_press_handler(&artists.items);
+            }
+        }
+        k if common_key_events::low_event(k) => {
+            if let Some(artists) = top_items
+                .artists
+                .as_ref()
+                .filter(|page| !page.items.is_empty())
+            {
+                top_items.selected_artist_index =
+                    common_key_events::on_low_press_handler(&artists.items);
+            }
+        }
+        Key::Enter => {
+            if let Some(artist) = app.selected_top_artist().cloned() {
+                app.get_artist(artist.id, artist.name);
+                app.push_navigation_stack(RouteId::Artist, ActiveBlock::ArtistBlock);
+            }
+        }
+        Key::Char('w') => app.user_follow_artists(ActiveBlock::TopArtists),
+        Key::Char('D') => app.user_unfollow_artists(ActiveBlock::TopArtists),
+        Key::Char('e') => {
+            if let Some(artist) = app.selected_top_artist() {
+                let play_context_id = PlayContextId::Artist(artist.id.clone());
+                app.dispatch(IoEvent::StartContextPlayback {
+                    play_context_id,
+                    offset: None,
+                });
+            }
+        }
+        Key::Char('r') => {
+            if let Some(artist) = app.selected_top_artist().cloned() {
+                app.recommendations_context = Some(RecommendationsContext::Artist);
+                app.recommendations_seed = artist.name;
+                app.get_recommendations_for_seed(Some(vec![artist.id]), None, None);
+            }
+        }
+        Key::Char('t') => app.cycle_top_items_time_range(),
+        Key::Tab => app.toggle_top_item_kind(),
+        k if k == app.user_config.keys.next_page => app.top_items_next_page(),
+        k if k == app.user_config.keys.previous_page => app.top_items_previous_page(),
+        _ => {}
+    }
+}
@@ -0,0 +1,233 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{File, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+// How much of the file is read at a time, working backwards from the end
+const CHUNK_SIZE: u64 = 64 * 1024;
+// No entry comes close, anything longer is corrupted and is dropped rather than held in memory
+const MAX_LINE_LENGTH: usize = 1024 * 1024;
+
+// One play in the local history file, which holds one JSON object per line, oldest first
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub played_at: DateTime<Utc>,
+    pub uri: String,
+    pub title: String,
+    pub artist: String,
+    pub context_uri: Option<String>,
+}
+
+pub fn append(path: &Path, entry: &HistoryEntry) -> Result<()> {
+    let mut line = serde_json::to_string(entry)?;
+    line.push('\n');
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    // A single write, so that a crash leaves at most one partial line at the end
+    file.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+// Reads the history newest first, a chunk at a time from the end of the file, so that even a
+// history of several gigabytes is never loaded at once
+pub struct HistoryReader {
+    file: File,
+    // Everything before this offset is still to be read
+    position: u64,
+    // Read but not yet returned, starting somewhere in a line when position isn't 0
+    buffer: Vec<u8>,
+}
+
+impl HistoryReader {
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = File::open(path)?;
+        let position = file.metadata()?.len();
+        let mut reader = HistoryReader {
+            file,
+            position,
+            buffer: vec![],
+        };
+        // Whatever follows the last newline is a line still being written, or left behind by a
+        // crash. It is empty when the file ends cleanly.
+        reader.next_line()?;
+        Ok(reader)
+    }
+
+    fn next_line(&mut self) -> Result<Option<Vec<u8>>> {
+        loop {
+            // A line is complete once the newline before it has been read, or the start of the
+            // file has been reached
+            if let Some(newline) = self.buffer.iter().rposition(|byte| *byte == b'\n') {
+                let line = self.buffer.split_off(newline + 1);
+                self.buffer.truncate(newline);
+                return Ok(Some(line));
+            }
+            if self.position == 0 {
+                if self.buffer.is_empty() {
+                    return Ok(None);
+                }
+                return Ok(Some(std::mem::take(&mut self.buffer)));
+            }
+            if self.buffer.len() > MAX_LINE_LENGTH {
+                self.buffer.clear();
+            }
+
+            let start = self.position.saturating_sub(CHUNK_SIZE);
+            let mut chunk = vec![0; (self.position - start) as usize];
+            self.file.seek(SeekFrom::Start(start))?;
+            self.file.read_exact(&mut chunk)?;
+            chunk.append(&mut self.buffer);
+            self.buffer = chunk;
+            self.position = start;
+        }
+    }
+
+    // Up to `count` more entries, skipping blank and corrupted lines. Fewer means the start of
+    // the file was reached.
+    pub fn next_page(&mut self, count: usize) -> Result<Vec<HistoryEntry>> {
+        let mut entries = Vec::with_capacity(count);
+        while entries.len() < count {
+            let Some(line) = self.next_line()? else {
+                break;
+            };
+            if let Ok(entry) = serde_json::from_slice(&line) {
+                entries.push(entry);
+            }
+        }
+        Ok(entries)
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.position == 0 && self.buffer.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn entry(i: usize) -> HistoryEntry {
+        HistoryEntry {
+            played_at: DateTime::from_timestamp(1_700_000_000 + i as i64 * 180, 0).unwrap(),
+            uri: format!("spotify:track:{:022}", i),
+            title: format!("Track {}", i),
+            artist: String::from("Artist"),
+            context_uri: None,
+        }
+    }
+
+    fn titles(entries: &[HistoryEntry]) -> Vec<String> {
+        entries.iter().map(|entry| entry.title.clone()).collect()
+    }
+
+    #[test]
+    fn reads_newest_first_in_pages() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("history.jsonl");
+        for i in 0..5 {
+            append(&path, &entry(i)).unwrap();
+        }
+
+        let mut reader = HistoryReader::open(&path).unwrap();
+        assert_eq!(
+            titles(&reader.next_page(2).unwrap()),
+            ["Track 4", "Track 3"]
+        );
+        assert!(!reader.is_exhausted());
+        assert_eq!(
+            titles(&reader.next_page(10).unwrap()),
+            ["Track 2", "Track 1", "Track 0"]
+        );
+        assert!(reader.is_exhausted());
+        assert!(reader.next_page(10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn skips_partial_and_corrupted_lines() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("history.jsonl");
+        append(&path, &entry(0)).unwrap();
+        let mut contents = fs::read_to_string(&path).unwrap();
+        contents.push_str("not json\n\n");
+        fs::write(&path, contents).unwrap();
+        append(&path, &entry(1)).unwrap();
+        // Cut off half way through writing the last line
+        let mut contents = fs::read(&path).unwrap();
+        contents.extend_from_slice(&serde_json::to_vec(&entry(2)).unwrap()[..30]);
+        fs::write(&path, contents).unwrap();
+
+        let mut reader = HistoryReader::open(&path).unwrap();
+        assert_eq!(
+            titles(&reader.next_page(10).unwrap()),
+            ["Track 1", "Track 0"]
+        );
+
+        // A file holding only a partial line has nothing to show
+        fs::write(&path, "{\"played_at\":").unwrap();
+        let mut reader = HistoryReader::open(&path).unwrap();
+        assert!(reader.next_page(10).unwrap().is_empty());
+
+        fs::write(&path, "").unwrap();
+        let mut reader = HistoryReader::open(&path).unwrap();
+        assert!(reader.next_page(10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn lines_spanning_chunks() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("history.jsonl");
+        // Lines longer than a chunk, and enough of them that chunk boundaries fall everywhere
+        let count = 40;
+        for i in 0..count {
+            let mut entry = entry(i);
+            entry.artist = "a".repeat(CHUNK_SIZE as usize / 16 * (i % 20 + 1));
+            append(&path, &entry).unwrap();
+        }
+        assert!(fs::metadata(&path).unwrap().len() > CHUNK_SIZE * 10);
+
+        let mut reader = HistoryReader::open(&path).unwrap();
+        let mut entries = vec![];
+        loop {
+            let page = reader.next_page(3).unwrap();
+            if page.is_empty() {
+                break;
+            }
+            // Only what a page needs is read
+            assert!(reader.buffer.len() as u64 <= CHUNK_SIZE * 3);
+            entries.extend(page);
+        }
+
+        assert_eq!(entries.len(), count);
+        for (i, entry) in entries.iter().enumerate() {
+            assert_eq!(entry.title, format!("Track {}", count - 1 - i));
+        }
+    }
+
+    #[test]
+    fn reads_only_the_end_of_a_large_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("history.jsonl");
+        // A sparse file, so that it is huge without taking up space or time to write, standing
+        // in for older entries
+        let size = 4 * 1024 * 1024 * 1024;
+        let mut file = File::create(&path).unwrap();
+        file.set_len(size - 1).unwrap();
+        file.seek(SeekFrom::End(0)).unwrap();
+        file.write_all(b"\n").unwrap();
+        drop(file);
+        append(&path, &entry(1)).unwrap();
+        append(&path, &entry(2)).unwrap();
+
+        let mut reader = HistoryReader::open(&path).unwrap();
+        assert_eq!(
+            titles(&reader.next_page(2).unwrap()),
+            ["Track 2", "Track 1"]
+        );
+        assert!(reader.position > size - CHUNK_SIZE);
+    }
+}
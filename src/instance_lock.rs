@@ -0,0 +1,219 @@
+use anyhow::{anyhow, Result};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    process,
+};
+
+// Held by the first TUI to start for as long as it runs, so that those started after it leave the
+// token to it rather than refreshing it too, which would revoke the token it is using. The file
+// holds the id of the process holding the lock.
+pub struct InstanceLock {
+    path: PathBuf,
+}
+
+// How many times a lock left behind is cleared before giving up, should other instances keep
+// taking it first
+const TAKE_OVER_TRIES: usize = 3;
+
+pub enum Instance {
+    // The only TUI running, or the one that took over with --force. The lock is only held for
+    // its drop, which releases it when the TUI ends.
+    Primary { _lock: InstanceLock },
+    // Another TUI, with this process id, holds the lock
+    Secondary { pid: u32 },
+}
+
+pub fn acquire(path: &Path, force: bool) -> Result<Instance> {
+    acquire_after(path, force, || {})
+}
+
+// Like `acquire`, with `before_create` run just before each try at creating the lock, which is
+// where another instance starting at the same time may create it first
+fn acquire_after(path: &Path, force: bool, mut before_create: impl FnMut()) -> Result<Instance> {
+    let pid = process::id().to_string();
+    for _ in 0..TAKE_OVER_TRIES {
+        before_create();
+        match create(path, &pid) {
+            Ok(()) => return Ok(primary(path)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        // Released in the meantime
+        let Ok(contents) = fs::read_to_string(path) else {
+            continue;
+        };
+        if force {
+            fs::write(path, &pid)?;
+            return Ok(primary(path));
+        }
+        match contents.trim().parse::<u32>() {
+            Ok(holder) if holder == process::id() => return Ok(primary(path)),
+            Ok(holder) if is_running(holder) => return Ok(Instance::Secondary { pid: holder }),
+            // Left behind, removed unless another instance has just taken it over
+            _ => {
+                if fs::read_to_string(path).is_ok_and(|now| now == contents) {
+                    let _ = fs::remove_file(path);
+                }
+            }
+        }
+    }
+    Err(anyhow!(
+        "couldn't take the lock at {}, other instances kept taking it",
+        path.display()
+    ))
+}
+
+// Written to a file of its own and linked into place, which fails when the lock exists already.
+// Another instance never sees the lock without the process id in it.
+fn create(path: &Path, pid: &str) -> io::Result<()> {
+    let partial_path = path.with_extension(format!("{}.partial", pid));
+    fs::write(&partial_path, pid)?;
+    let linked = fs::hard_link(&partial_path, path);
+    let _ = fs::remove_file(&partial_path);
+    linked
+}
+
+fn primary(path: &Path) -> Instance {
+    Instance::Primary {
+        _lock: InstanceLock {
+            path: path.to_path_buf(),
+        },
+    }
+}
+
+// The process holding the lock, None when there is no lock or when it was left behind by a
+// process that has ended without removing it, e.g. by crashing
+pub fn holder(path: &Path) -> Option<u32> {
+    let pid = fs::read_to_string(path).ok()?.trim().parse::<u32>().ok()?;
+    is_running(pid).then_some(pid)
+}
+
+impl Drop for InstanceLock {
+    // Once another instance has taken over with --force the lock is its to remove
+    fn drop(&mut self) {
+        if fs::read_to_string(&self.path).is_ok_and(|pid| pid.trim() == process::id().to_string()) {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn is_running(pid: u32) -> bool {
+    Path::new("/proc").join(pid.to_string()).exists()
+}
+
+// Signal 0 only checks that the process exists
+#[cfg(all(unix, not(target_os = "linux")))]
+fn is_running(pid: u32) -> bool {
+    process::Command::new("kill")
+        .args(["-0", &pid.to_string()])
+        .stderr(process::Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+#[cfg(windows)]
+fn is_running(pid: u32) -> bool {
+    process::Command::new("tasklist")
+        .args(["/NH", "/FI", &format!("PID eq {}", pid)])
+        .output()
+        .is_ok_and(|output| {
+            String::from_utf8_lossy(&output.stdout)
+                .split_whitespace()
+                .any(|word| word == pid.to_string())
+        })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    // The id of a process that has ended
+    fn ended_pid() -> u32 {
+        let mut child = process::Command::new("true").spawn().unwrap();
+        child.wait().unwrap();
+        child.id()
+    }
+
+    #[test]
+    fn the_first_instance_holds_the_lock() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("spotify-tui.lock");
+
+        let Instance::Primary { _lock: lock } = acquire(&path, false).unwrap() else {
+            panic!("the lock was free");
+        };
+        assert_eq!(holder(&path), Some(process::id()));
+        drop(lock);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn later_instances_are_secondary_unless_forced() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("spotify-tui.lock");
+        // Running for as long as the tests are
+        let other = std::os::unix::process::parent_id();
+        fs::write(&path, other.to_string()).unwrap();
+
+        assert!(matches!(
+            acquire(&path, false).unwrap(),
+            Instance::Secondary { pid } if pid == other
+        ));
+
+        let Instance::Primary { _lock: lock } = acquire(&path, true).unwrap() else {
+            panic!("--force takes over");
+        };
+        assert_eq!(holder(&path), Some(process::id()));
+        // The instance taken over from leaves the lock alone when it ends
+        fs::write(&path, other.to_string()).unwrap();
+        drop(lock);
+        assert_eq!(holder(&path), Some(other));
+    }
+
+    #[test]
+    fn stale_locks_are_taken() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("spotify-tui.lock");
+
+        fs::write(&path, ended_pid().to_string()).unwrap();
+        assert_eq!(holder(&path), None);
+        assert!(matches!(
+            acquire(&path, false).unwrap(),
+            Instance::Primary { .. }
+        ));
+
+        fs::write(&path, "not a pid").unwrap();
+        assert_eq!(holder(&path), None);
+    }
+
+    #[test]
+    fn an_instance_that_creates_the_lock_first_holds_it() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("spotify-tui.lock");
+        let other = std::os::unix::process::parent_id();
+
+        // Created by another instance after this one found no lock
+        assert!(matches!(
+            acquire_after(&path, false, || fs::write(&path, other.to_string()).unwrap()).unwrap(),
+            Instance::Secondary { pid } if pid == other
+        ));
+        assert_eq!(holder(&path), Some(other));
+
+        // One left behind in the meantime is taken
+        fs::remove_file(&path).unwrap();
+        let mut tries = 0;
+        let instance = acquire_after(&path, false, || {
+            if tries == 0 {
+                fs::write(&path, ended_pid().to_string()).unwrap();
+            }
+            tries += 1;
+        })
+        .unwrap();
+        assert!(matches!(instance, Instance::Primary { .. }));
+        assert_eq!(tries, 2);
+        assert_eq!(holder(&path), Some(process::id()));
+    }
+}
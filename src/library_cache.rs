@@ -0,0 +1,162 @@
+use crate::cache_file;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::{borrow::Borrow, collections::HashMap, hash::Hash, path::Path};
+
+// Bumped when the file changes in a way this version couldn't read. A file of another version is
+// ignored like a corrupted one, and the cache fills up again as the library loads.
+pub const VERSION: u32 = 1;
+
+// Whether an id was seen in the library this session, or is only remembered from the last one
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mark {
+    Stale,
+    Confirmed,
+}
+
+// The ids of one kind of item in the library. Those loaded from the cache are stale until a
+// check or a fetch of the library confirms them, or shows they are gone.
+#[derive(Clone, Debug)]
+pub struct LibraryIds<T> {
+    ids: HashMap<T, Mark>,
+}
+
+impl<T> Default for LibraryIds<T> {
+    fn default() -> Self {
+        LibraryIds {
+            ids: HashMap::new(),
+        }
+    }
+}
+
+impl<T: Eq + Hash> LibraryIds<T> {
+    pub fn contains<Q>(&self, id: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.ids.contains_key(id)
+    }
+
+    pub fn is_stale<Q>(&self, id: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.ids.get(id) == Some(&Mark::Stale)
+    }
+
+    // Seen in the library
+    pub fn insert(&mut self, id: T) {
+        self.ids.insert(id, Mark::Confirmed);
+    }
+
+    // Shown not to be in the library
+    pub fn remove<Q>(&mut self, id: &Q)
+    where
+        T: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.ids.remove(id);
+    }
+
+    // Remembered from the last session, which never overrides what this one has seen in the
+    // library. The cache is loaded before anything is checked, so nothing removed this session
+    // comes back.
+    pub fn insert_stale(&mut self, id: T) {
+        self.ids.entry(id).or_insert(Mark::Stale);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.ids.keys()
+    }
+}
+
+// The cache file, a JSON object with the uris of each kind of item in the library. Fields a later
+// version adds are ignored, and fields it drops are empty.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct LibraryCache {
+    pub version: u32,
+    #[serde(default)]
+    pub liked_songs: Vec<String>,
+    #[serde(default)]
+    pub liked_episodes: Vec<String>,
+    #[serde(default)]
+    pub followed_artists: Vec<String>,
+    #[serde(default)]
+    pub saved_albums: Vec<String>,
+    #[serde(default)]
+    pub saved_shows: Vec<String>,
+}
+
+// None when there is no cache yet, it can't be read, or it is of another version
+pub fn load(path: &Path) -> Option<LibraryCache> {
+    cache_file::load_json::<LibraryCache>(path).filter(|cache| cache.version == VERSION)
+}
+
+pub fn save(path: &Path, cache: &LibraryCache) -> Result<()> {
+    cache_file::save_json(path, cache)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn marks_follow_the_library() {
+        let mut ids = LibraryIds::default();
+        ids.insert_stale(String::from("stale"));
+        ids.insert_stale(String::from("gone"));
+        ids.insert(String::from("seen"));
+        assert!(ids.contains("stale") && ids.is_stale("stale"));
+        assert!(ids.contains("seen") && !ids.is_stale("seen"));
+        assert!(!ids.contains("other") && !ids.is_stale("other"));
+
+        // Confirmed by a check or a fetch
+        ids.insert(String::from("stale"));
+        assert!(ids.contains("stale") && !ids.is_stale("stale"));
+        // Shown to be gone
+        ids.remove("gone");
+        assert!(!ids.contains("gone") && !ids.is_stale("gone"));
+
+        // What was seen this session stays confirmed
+        ids.insert_stale(String::from("seen"));
+        assert!(!ids.is_stale("seen"));
+
+        let mut all = ids.iter().cloned().collect::<Vec<_>>();
+        all.sort();
+        assert_eq!(all, ["seen", "stale"]);
+    }
+
+    #[test]
+    fn saves_and_loads_the_library_of_this_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("library.json");
+        let cache = LibraryCache {
+            version: VERSION,
+            liked_songs: vec![String::from("spotify:track:4iV5W9uYEdYUVa79Axb7Rh")],
+            saved_albums: vec![String::from("spotify:album:0sNOF9WDwhWunNAHPD3Baj")],
+            ..LibraryCache::default()
+        };
+        save(&path, &cache).unwrap();
+        assert_eq!(load(&path), Some(cache));
+
+        for contents in [
+            "{\"version\": 2, \"liked_songs\": []}",
+            "{\"liked_songs\": []}",
+        ] {
+            std::fs::write(&path, contents).unwrap();
+            assert_eq!(load(&path), None, "{}", contents);
+        }
+
+        // Fields from a later version of the same format are left out
+        std::fs::write(
+            &path,
+            "{\"version\": 1, \"liked_songs\": [\"spotify:track:4iV5W9uYEdYUVa79Axb7Rh\"], \"playlists\": []}",
+        )
+        .unwrap();
+        let cache = load(&path).unwrap();
+        assert_eq!(cache.liked_songs.len(), 1);
+        assert!(cache.saved_shows.is_empty());
+    }
+}
@@ -0,0 +1,126 @@
+use anyhow::{anyhow, Result};
+use reqwest::{Client, RequestBuilder, StatusCode};
+use serde::Deserialize;
+
+pub const DEFAULT_LYRICS_API_URL: &str = "https://lrclib.net/api";
+
+// Spotify's API has no lyrics, so they come from an external service. A provider only describes
+// the request and how to read its response, the network thread does the actual fetching.
+pub trait LyricsProvider: Send + Sync {
+    fn request(&self, client: &Client, artist: &str, title: &str) -> RequestBuilder;
+
+    // The lyrics in a response, None when the service has none for the track
+    fn parse(&self, status: StatusCode, body: &str) -> Result<Option<String>>;
+}
+
+// https://lrclib.net, which needs no API key
+pub struct LrcLib {
+    api_url: String,
+}
+
+impl LrcLib {
+    pub fn new(api_url: impl Into<String>) -> Self {
+        LrcLib {
+            api_url: api_url.into(),
+        }
+    }
+}
+
+impl Default for LrcLib {
+    fn default() -> Self {
+        LrcLib::new(DEFAULT_LYRICS_API_URL)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LrcLibLyrics {
+    plain_lyrics: Option<String>,
+}
+
+impl LyricsProvider for LrcLib {
+    fn request(&self, client: &Client, artist: &str, title: &str) -> RequestBuilder {
+        client
+            .get(format!("{}/get", self.api_url.trim_end_matches('/')))
+            .query(&[("artist_name", artist), ("track_name", title)])
+    }
+
+    fn parse(&self, status: StatusCode, body: &str) -> Result<Option<String>> {
+        if status == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            return Err(anyhow!("Lyrics request failed ({})", status));
+        }
+        let lyrics: LrcLibLyrics = serde_json::from_str(body)?;
+        // Instrumentals are found, but have no lyrics
+        Ok(lyrics
+            .plain_lyrics
+            .filter(|lyrics| !lyrics.trim().is_empty()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_the_request() {
+        let provider = LrcLib::new("http://localhost:3000/api/");
+        let request = provider
+            .request(&Client::new(), "Queen", "Don't Stop Me Now")
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            request.url().as_str(),
+            "http://localhost:3000/api/get?artist_name=Queen&track_name=Don%27t+Stop+Me+Now"
+        );
+    }
+
+    #[test]
+    fn parses_lyrics() {
+        let provider = LrcLib::default();
+
+        assert_eq!(
+            provider
+                .parse(
+                    StatusCode::OK,
+                    r#"{"id": 1, "plainLyrics": "Tonight I'm gonna have myself", "instrumental": false}"#
+                )
+                .unwrap(),
+            Some(String::from("Tonight I'm gonna have myself"))
+        );
+    }
+
+    #[test]
+    fn nothing_found() {
+        let provider = LrcLib::default();
+
+        assert_eq!(
+            provider
+                .parse(StatusCode::NOT_FOUND, r#"{"message": "Failed"}"#)
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            provider
+                .parse(
+                    StatusCode::OK,
+                    r#"{"plainLyrics": null, "instrumental": true}"#
+                )
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn failed_requests_are_errors() {
+        let provider = LrcLib::default();
+
+        assert!(provider
+            .parse(StatusCode::INTERNAL_SERVER_ERROR, "")
+            .is_err());
+        assert!(provider.parse(StatusCode::OK, "<html>").is_err());
+    }
+}
@@ -1,11 +1,26 @@
 mod app;
 mod banner;
+mod cache_file;
 mod cli;
+mod clipboard;
 mod config;
+mod control;
+mod debug_dump;
 mod event;
 mod handlers;
+mod history;
+mod instance_lock;
+mod library_cache;
+mod lyrics;
 mod network;
+mod notifications;
+mod paths;
+mod playback_progress;
+mod startup_cache;
+#[cfg(test)]
+mod test_fixtures;
 mod ui;
+mod update_check;
 mod user_config;
 
 use crate::app::RouteId;
@@ -15,7 +30,7 @@ use app::{ActiveBlock, App};
 use backtrace::Backtrace;
 use banner::BANNER;
 use chrono::Utc;
-use clap::{builder::PossibleValue, Arg, Command};
+use clap::{builder::PossibleValue, Arg, ArgAction, Command};
 use clap_complete::Shell;
 use config::ClientConfig;
 use crossterm::{
@@ -28,26 +43,34 @@ use crossterm::{
     },
     ExecutableCommand,
 };
-use network::{IoEvent, Network};
+use lyrics::LrcLib;
+use network::{IoEvent, Network, TerminalHandover};
+use notifications::Severity;
 use rspotify::{clients::OAuthClient, AuthCodePkceSpotify, Config, Credentials, OAuth, Token};
 use souvlaki::{MediaControlEvent, MediaControls, MediaPlayback, PlatformConfig};
-use std::cmp::{max, min};
-use std::io::{self, stdout};
+use std::cmp::min;
+use std::io::{self, stdout, Stdout};
 use std::panic::{self, PanicHookInfo};
 use std::path::PathBuf;
 use std::sync::{mpsc::Receiver, Arc};
-use tokio::sync::RwLock;
+use std::time::{Duration, Instant};
+use tokio::sync::{oneshot, RwLock};
 use tui::{
     backend::{Backend, CrosstermBackend},
     Terminal,
 };
-use user_config::{UserConfig, UserConfigPaths};
+use ui::album_art::AlbumArtRenderer;
+use user_config::{
+    UserConfig, UserConfigPaths, MAX_TICK_RATE_MILLISECONDS, MIN_TICK_RATE_MILLISECONDS,
+};
 use winit::event::{DeviceEvent, DeviceId, WindowEvent};
 use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop};
 use winit::window::{Window, WindowId};
 use winit::{application::ApplicationHandler, error::EventLoopError};
 
-const SCOPES: [&str; 14] = [
+// A token cached with fewer of these is ignored when reading the cache, which signs in again to
+// grant the ones added since
+const SCOPES: [&str; 15] = [
     "playlist-read-collaborative",
     "playlist-read-private",
     "playlist-modify-private",
@@ -62,8 +85,11 @@ const SCOPES: [&str; 14] = [
     "user-read-playback-position",
     "user-read-private",
     "user-read-recently-played",
+    "user-top-read",
 ];
 
+const MINI_PLAYER_TICK_RATE_MILLISECONDS: u64 = 100;
+
 /// get token automatically with local webserver
 pub async fn get_token_auto(spotify: &mut AuthCodePkceSpotify) -> Option<Token> {
     let token = match spotify.token.lock().await {
@@ -89,6 +115,85 @@ fn close_application() -> Result<()> {
     Ok(())
 }
 
+// Waits for the deadline, or for ever without one
+async fn sleep_until(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline.into()).await,
+        None => std::future::pending().await,
+    }
+}
+
+// Takes the terminal back after leaving it with close_application
+fn restore_terminal(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    enable_mouse: bool,
+) -> Result<()> {
+    execute!(terminal.backend_mut(), EnterAlternateScreen)?;
+    if enable_mouse {
+        execute!(terminal.backend_mut(), EnableMouseCapture)?;
+    }
+    enable_raw_mode()?;
+    terminal.clear()?;
+    Ok(())
+}
+
+// Takes the terminal back after the process was stopped, redrawing everything at the size it
+// may have been changed to meanwhile
+async fn resume_terminal(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    album_art_renderer: &mut Option<AlbumArtRenderer>,
+    app: &RwLock<App>,
+    enable_mouse: bool,
+) -> Result<()> {
+    restore_terminal(terminal, enable_mouse)?;
+    if let Some(renderer) = album_art_renderer {
+        renderer.invalidate();
+    }
+    // Picked up by the next loop, like a resize
+    app.write().await.size = tui::layout::Rect::default();
+    Ok(())
+}
+
+// Stops the process like Ctrl-z does outside of raw mode, returning once the shell resumes it
+#[cfg(unix)]
+fn suspend() -> Result<()> {
+    // SAFETY: raise only sends the signal, whose default action stops the process
+    if unsafe { libc::raise(libc::SIGTSTP) } != 0 {
+        return Err(io::Error::last_os_error().into());
+    }
+    Ok(())
+}
+
+// SIGCONT, sent whenever the process is resumed, whether it stopped itself or something else
+// stopped it. Never arrives on platforms without signals.
+#[cfg(unix)]
+struct Continued(tokio::signal::unix::Signal);
+#[cfg(not(unix))]
+struct Continued;
+
+impl Continued {
+    #[cfg(unix)]
+    fn listen() -> Result<Self> {
+        use tokio::signal::unix::{signal, SignalKind};
+        Ok(Self(signal(SignalKind::from_raw(libc::SIGCONT))?))
+    }
+
+    #[cfg(not(unix))]
+    fn listen() -> Result<Self> {
+        Ok(Self)
+    }
+
+    #[cfg(unix)]
+    async fn recv(&mut self) -> Option<()> {
+        self.0.recv().await
+    }
+
+    #[cfg(not(unix))]
+    async fn recv(&mut self) -> Option<()> {
+        std::future::pending().await
+    }
+}
+
 fn panic_hook(info: &PanicHookInfo<'_>) {
     if cfg!(debug_assertions) {
         let location = info.location().unwrap();
@@ -129,14 +234,15 @@ async fn main() -> Result<()> {
     .about(env!("CARGO_PKG_DESCRIPTION"))
     .override_usage("Press `?` while running the app to see keybindings")
     .before_help(BANNER)
-    .after_help(
-      "Your spotify Client ID and Client Secret are stored in $HOME/.config/spotify-tui/client.yml",
-    )
+    .after_help(paths::client_config_help())
     .arg(
       Arg::new("tick-rate")
         .short('t')
         .long("tick-rate")
         .help("Set the tick rate (milliseconds): the lower the number the higher the FPS.")
+        .value_parser(
+          clap::value_parser!(u64).range(MIN_TICK_RATE_MILLISECONDS..MAX_TICK_RATE_MILLISECONDS),
+        )
         .long_help(
           "Specify the tick rate in milliseconds: the lower the number the \
 higher the FPS. It can be nicer to have a lower value when you want to use the audio analysis view \
@@ -151,6 +257,52 @@ of the app. Beware that this comes at a CPU cost!",
         .help("Specify configuration file path.")
         .num_args(1),
     )
+    .arg(
+      Arg::new("dry-run")
+        .long("dry-run")
+        .help("Print what commands would change instead of changing it")
+        .long_help(
+          "Print what the command would change on Spotify (playback, library, follows) instead \
+of changing it. Lookups still go through, so that the output has real names.",
+        )
+        .action(ArgAction::SetTrue)
+        .global(true),
+    )
+    .arg(
+      Arg::new("verbose")
+        .long("verbose")
+        .help("Print the playback before and after the command to stderr")
+        .action(ArgAction::SetTrue)
+        .global(true),
+    )
+    .arg(
+      Arg::new("mini")
+        .long("mini")
+        .help("Start as a mini player that only shows and controls the playback")
+        .long_help(
+          "Start as a mini player that only shows the playback, e.g. for a small terminal kept \
+on top of other windows. Only the playback controls, volume, seeking and liking are available, \
+and the library isn't loaded.",
+        )
+        .action(ArgAction::SetTrue),
+    )
+    .arg(
+      Arg::new("force")
+        .long("force")
+        .help("Take over from another running instance of the TUI")
+        .long_help(
+          "Take over from another running instance of the TUI. Without it, an instance started \
+while another one runs leaves refreshing the sign-in to that one and checks the playback less \
+often.",
+        )
+        .action(ArgAction::SetTrue),
+    )
+    .arg(
+      Arg::new("debug")
+        .long("debug")
+        .help("Enable the key that dumps the JSON of the selected item, for bug reports")
+        .action(ArgAction::SetTrue),
+    )
     .arg(
       Arg::new("completions")
         .long("completions")
@@ -169,7 +321,10 @@ of the app. Beware that this comes at a CPU cost!",
     .subcommand(cli::playback_subcommand())
     .subcommand(cli::play_subcommand())
     .subcommand(cli::list_subcommand())
-    .subcommand(cli::search_subcommand());
+    .subcommand(cli::search_subcommand())
+    .subcommand(cli::follow_subcommand())
+    .subcommand(cli::unfollow_subcommand())
+    .subcommand(cli::ctl_subcommand());
 
     let matches = clap_app.clone().get_matches();
 
@@ -188,6 +343,44 @@ of the app. Beware that this comes at a CPU cost!",
         return Ok(());
     }
 
+    // Do not disturb only exists in a running instance, there is nothing to fall back to
+    if let Some(value) = matches
+        .subcommand_matches("playback")
+        .and_then(|m| m.get_one::<String>("dnd"))
+    {
+        let command = control::ControlCommand::Dnd {
+            value: value.clone(),
+        };
+        #[cfg(unix)]
+        if let Some(response) =
+            control::send_command(&paths::control_socket_path()?, &command).await?
+        {
+            println!("{}", response);
+            return Ok(());
+        }
+        return Err(anyhow!(
+            "--dnd needs the TUI to be running with control_socket: true"
+        ));
+    }
+
+    // Drive a running instance through its control socket, without authenticating again
+    #[cfg(unix)]
+    if let Some(m) = matches
+        .subcommand_matches("ctl")
+        .filter(|m| !m.get_flag("dry-run"))
+    {
+        let command = control::ControlCommand::from_args(
+            m.get_one::<String>("command").unwrap(),
+            m.get_one::<String>("value").map(String::as_str),
+        )?;
+        if let Some(response) =
+            control::send_command(&paths::control_socket_path()?, &command).await?
+        {
+            println!("{}", response);
+            return Ok(());
+        }
+    }
+
     let mut user_config = UserConfig::new();
     if let Some(config_file_path) = matches.get_one::<String>("config") {
         let config_file_path = PathBuf::from(config_file_path);
@@ -197,11 +390,16 @@ of the app. Beware that this comes at a CPU cost!",
     user_config.load_config()?;
 
     if let Some(tick_rate) = matches.get_one::<u64>("tick-rate") {
-        if *tick_rate >= 1000 {
-            panic!("Tick rate must be below 1000");
-        } else {
-            user_config.behavior.tick_rate_milliseconds = *tick_rate;
-        }
+        user_config.behavior.tick_rate_milliseconds = *tick_rate;
+    }
+
+    let is_mini_player = matches.get_flag("mini");
+    // Smoother progress, which is all there is to look at
+    if is_mini_player && !matches.contains_id("tick-rate") {
+        user_config.behavior.tick_rate_milliseconds = min(
+            user_config.behavior.tick_rate_milliseconds,
+            MINI_PLAYER_TICK_RATE_MILLISECONDS,
+        );
     }
 
     let mut client_config = ClientConfig::new();
@@ -209,6 +407,21 @@ of the app. Beware that this comes at a CPU cost!",
 
     let config_paths = client_config.get_or_build_paths()?;
 
+    // Only the TUI runs for long enough to need refreshing the token, and polls the playback
+    let instance_lock_path = paths::instance_lock_path()?;
+    let instance = if matches.subcommand_name().is_none() {
+        Some(instance_lock::acquire(
+            &instance_lock_path,
+            matches.get_flag("force"),
+        )?)
+    } else {
+        None
+    };
+    let secondary_of = match &instance {
+        Some(instance_lock::Instance::Secondary { pid }) => Some(*pid),
+        _ => None,
+    };
+
     // Start authorization with spotify
     let oauth = OAuth {
         redirect_uri: client_config.get_redirect_uri(),
@@ -221,7 +434,8 @@ of the app. Beware that this comes at a CPU cost!",
         Config {
             cache_path: config_paths.token_cache_path,
             token_cached: true,
-            token_refreshing: true,
+            // Refreshing the token revokes the one the other instance uses
+            token_refreshing: secondary_of.is_none(),
             ..Default::default()
         },
     );
@@ -245,32 +459,111 @@ of the app. Beware that this comes at a CPU cost!",
         // Save, because we checked if the subcommand is present at runtime
         let m = matches.subcommand_matches(cmd).unwrap();
         let network = Network::new(spotify, client_config, app);
-        println!(
-            "{}",
-            cli::handle_matches(m, cmd.to_string(), network, user_config).await?
-        );
+        let output = cli::handle_matches(m, cmd.to_string(), network, user_config).await?;
+        // e.g. following the playback, which printed as it went
+        if !output.is_empty() {
+            println!("{}", output);
+        }
         return Ok(());
     }
 
+    app.write().await.history_path = Some(config_paths.history_path);
+    if matches.get_flag("debug") {
+        app.write().await.debug_dump_dir = Some(config_paths.debug_dump_dir);
+    }
+    app.write()
+        .await
+        .set_library_cache_path(config_paths.library_cache_path, Instant::now());
+    if user_config.behavior.enable_cache {
+        let mut app = app.write().await;
+        if let Some(cache) = startup_cache::load(&config_paths.startup_cache_path) {
+            app.load_startup_cache(cache);
+        }
+        app.startup_cache_path = Some(config_paths.startup_cache_path);
+    }
+    if is_mini_player {
+        app.write().await.start_mini_player();
+    }
+    if let Some(pid) = secondary_of {
+        let mut app = app.write().await;
+        app.is_secondary_instance = true;
+        app.notify(
+            Severity::Warning,
+            format!(
+                "spotify-tui is already running (process {}), so this one shares its sign-in and checks the playback less often. Start with --force to take over",
+                pid
+            ),
+        );
+    }
+    if user_config.behavior.check_for_updates {
+        tokio::spawn(notify_newer_release(
+            app.clone(),
+            config_paths.update_check_path,
+        ));
+    }
+
     // close main thread
     let (main_tx, main_rx) = tokio::sync::mpsc::channel(1);
+    let (first_draw_tx, first_draw_rx) = oneshot::channel();
+    let (terminal_tx, terminal_rx) = tokio::sync::mpsc::unbounded_channel();
+
+    let lyrics_provider = LrcLib::new(user_config.behavior.lyrics_api_url.clone());
 
     // Launch the UI (async)
     let ui_app = app.clone();
-    tokio::task::spawn(start_ui(user_config, ui_app, main_tx.clone()));
+    tokio::task::spawn(start_ui(
+        user_config,
+        ui_app,
+        main_tx.clone(),
+        first_draw_tx,
+        terminal_rx,
+    ));
 
     // Launch the io event handler
     let io_app = app.clone();
     tokio::task::spawn(async move {
-        let mut network = Network::new(spotify, client_config, io_app);
+        let mut network = Network::new(spotify, client_config, io_app)
+            .with_lyrics_provider(Arc::new(lyrics_provider))
+            .with_terminal_handover(terminal_tx);
+        if secondary_of.is_some() {
+            network = network.with_shared_token(instance_lock_path);
+        }
         handle_io_events(rx, &mut network).await
     });
 
-    MetadataManager::start(app, main_rx)?;
+    // The media controls open a session with the system and an event loop, which the first frame
+    // doesn't need to wait for
+    if let Some(result) =
+        after_first_draw(first_draw_rx, || MetadataManager::start(app, main_rx)).await
+    {
+        result?;
+    }
 
     Ok(())
 }
 
+// Runs `start` once the UI has drawn its first frame, or not at all when the UI ends before that
+async fn after_first_draw<T>(
+    first_draw: oneshot::Receiver<()>,
+    start: impl FnOnce() -> T,
+) -> Option<T> {
+    first_draw.await.ok()?;
+    Some(start())
+}
+
+async fn notify_newer_release(app: Arc<RwLock<App>>, cache_path: PathBuf) {
+    if let Some(latest) = update_check::newer_release(&cache_path, Utc::now()).await {
+        app.write().await.notify(
+            Severity::Info,
+            format!(
+                "spotify-tui {} is available (you have {}). Set behavior.check_for_updates to false to stop checking",
+                latest,
+                update_check::CURRENT_VERSION
+            ),
+        );
+    }
+}
+
 async fn handle_io_events(
     mut rx: tokio::sync::mpsc::UnboundedReceiver<IoEvent<'static>>,
     network: &mut Network,
@@ -284,22 +577,63 @@ async fn start_ui(
     user_config: UserConfig,
     app: Arc<RwLock<App>>,
     main_tx: tokio::sync::mpsc::Sender<()>,
+    first_draw_tx: oneshot::Sender<()>,
+    mut terminal_rx: tokio::sync::mpsc::UnboundedReceiver<TerminalHandover>,
 ) -> Result<()> {
+    let mut first_draw_tx = Some(first_draw_tx);
     // Terminal initialization
     let mut stdout = stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    execute!(stdout, EnterAlternateScreen)?;
+    if user_config.behavior.enable_mouse {
+        execute!(stdout, EnableMouseCapture)?;
+    }
     enable_raw_mode()?;
 
-    let mut backend = CrosstermBackend::new(stdout);
+    let backend = CrosstermBackend::new(stdout);
 
-    if user_config.behavior.set_window_title {
-        backend.execute(SetTitle("Spotify"))?;
-    }
+    // Set by the first draw, and again whenever it changes
+    let mut window_title = String::new();
 
     let mut terminal = Terminal::new(backend)?;
     terminal.hide_cursor()?;
 
-    let (mut events, events_handle) =
+    // Only set when this instance bound the socket, so that it never removes another's
+    #[cfg(unix)]
+    let mut control_socket_path = None;
+    #[cfg(unix)]
+    if user_config.behavior.control_socket {
+        let listener = match paths::control_socket_path() {
+            Ok(path) => control::listen(&path)
+                .await
+                .map(|listener| (path, listener)),
+            Err(e) => Err(e),
+        };
+        match listener {
+            Ok((path, listener)) => {
+                tokio::spawn(control::serve(listener, app.clone()));
+                control_socket_path = Some(path);
+            }
+            Err(e) => app.write().await.notify(
+                Severity::Warning,
+                format!("Control socket unavailable: {}", e),
+            ),
+        }
+    }
+
+    if user_config.behavior.playlist_cover_accent {
+        app.write().await.is_cover_accent_enabled = ui::cover_accent::supports_truecolor();
+    }
+
+    let mut album_art_renderer = if user_config.behavior.show_album_art {
+        let protocol = ui::album_art::detect_protocol();
+        app.write().await.album_art_protocol = protocol;
+        protocol.map(AlbumArtRenderer::new)
+    } else {
+        None
+    };
+
+    let mut continued = Continued::listen()?;
+    let (mut events, mut events_handle) =
         event::Events::new(user_config.behavior.tick_rate_milliseconds);
 
     // play music on, if not send them to the device selection view
@@ -317,13 +651,17 @@ async fn start_ui(
                 app.help_menu_page = 0;
 
                 app.size = size;
+                // The terminal gets cleared on resize, taking any album art with it
+                if let Some(renderer) = &mut album_art_renderer {
+                    renderer.invalidate();
+                }
 
-                // Based on the size of the terminal, adjust the search limit.
-                let potential_limit = max((app.size.height as i32) - 13, 0) as u32;
-                let max_limit = min(potential_limit, 50);
-                let large_search_limit = min((f32::from(size.height) / 1.4) as u32, max_limit);
-                let small_search_limit = min((f32::from(size.height) / 2.85) as u32, max_limit / 2);
-
+                // Based on the size of the terminal, adjust the search limit, unless the config
+                // sets it
+                let (large_search_limit, small_search_limit) = app::search_limits(size.height);
+                let behavior = &app.user_config.behavior;
+                let large_search_limit = behavior.large_search_limit.unwrap_or(large_search_limit);
+                let small_search_limit = behavior.small_search_limit.unwrap_or(small_search_limit);
                 app.dispatch(IoEvent::UpdateSearchLimits {
                     large_search_limit,
                     small_search_limit,
@@ -342,6 +680,8 @@ async fn start_ui(
         let should_reauthenticate = {
             let app = app.read().await;
             let current_route = app.get_current_route();
+            let mut album_art_area = None;
+            app.clear_control_areas();
             terminal.draw(|mut f| match current_route.active_block {
                 ActiveBlock::HelpMenu => {
                     ui::draw_help_menu(&mut f, &app);
@@ -356,51 +696,141 @@ async fn start_ui(
                     ui::audio_analysis::draw(&mut f, &app);
                 }
                 ActiveBlock::BasicView => {
-                    ui::draw_basic_view(&mut f, &app);
+                    album_art_area = ui::draw_basic_view(&mut f, &app);
                 }
                 _ => {
-                    ui::draw_main_layout(&mut f, &app);
+                    album_art_area = ui::draw_main_layout(&mut f, &app);
                 }
             })?;
 
-            if current_route.active_block == ActiveBlock::Input {
+            if let Some(renderer) = &mut album_art_renderer {
+                let album_art = app.current_album_art();
+                let album_art = album_art
+                    .as_ref()
+                    .zip(album_art_area)
+                    .map(|((url, bytes), area)| (url.as_str(), *bytes, area));
+                if renderer.render(terminal.backend_mut(), album_art)? {
+                    terminal.clear()?;
+                }
+            }
+
+            if current_route.active_block == ActiveBlock::Input || app.is_filtering_help() {
                 terminal.show_cursor()?;
             } else {
                 terminal.hide_cursor()?;
             }
 
-            let cursor_offset = if app.size.height > ui::util::SMALL_TERMINAL_HEIGHT {
-                2
-            } else {
-                1
-            };
-
             // Put the cursor back inside the input box
-            terminal.backend_mut().execute(MoveTo(
-                cursor_offset + app.input_cursor_position,
-                cursor_offset,
-            ))?;
+            let (cursor_x, cursor_y) = ui::util::get_input_cursor_position(&app);
+            terminal.backend_mut().execute(MoveTo(cursor_x, cursor_y))?;
+
+            if user_config.behavior.set_window_title {
+                let title = ui::util::get_window_title(&app);
+                if title != window_title {
+                    terminal.backend_mut().execute(SetTitle(&title))?;
+                    window_title = title;
+                }
+            }
 
             // Handle authentication refresh
-            Utc::now() > app.spotify_token_expiry
+            Utc::now() > app.spotify_token_expiry && !app.is_refreshing_token
         };
 
         if should_reauthenticate {
-            app.write().await.dispatch(IoEvent::RefreshAuthentication);
+            app.write().await.refresh_token_if_expired(Utc::now());
         }
 
-        match events.next().await {
+        let seek_apply_at = {
+            let app = app.read().await;
+            events.set_tick_rate(app.tick_rate());
+            app.seek_apply_at
+        };
+
+        let event = tokio::select! {
+            event = events.next() => event,
+            _ = sleep_until(seek_apply_at) => {
+                app.write().await.apply_due_seek(Instant::now());
+                continue;
+            }
+            Some(handover) = terminal_rx.recv() => {
+                // Signing in again prompts in the terminal, which has to be left as it was before
+                // the UI, with nothing else reading the keyboard
+                events_handle.abort();
+                let _ = (&mut events_handle).await;
+                close_application()?;
+                let _ = handover.released.send(());
+                let _ = handover.restore.await;
+
+                restore_terminal(&mut terminal, user_config.behavior.enable_mouse)?;
+                if let Some(renderer) = &mut album_art_renderer {
+                    renderer.invalidate();
+                }
+                (events, events_handle) =
+                    event::Events::new(user_config.behavior.tick_rate_milliseconds);
+                continue;
+            }
+            Some(()) = continued.recv() => {
+                // Stopped by something else, the shell took the terminal back meanwhile
+                events_handle.abort();
+                let _ = (&mut events_handle).await;
+                resume_terminal(
+                    &mut terminal,
+                    &mut album_art_renderer,
+                    &app,
+                    user_config.behavior.enable_mouse,
+                )
+                .await?;
+                (events, events_handle) =
+                    event::Events::new(user_config.behavior.tick_rate_milliseconds);
+                continue;
+            }
+        };
+
+        match event {
             Some(event::Event::Input(key)) => {
                 if key == Key::Ctrl('c') {
                     break;
                 }
 
+                // Raw mode keeps the terminal from stopping the process itself, so the screen is
+                // left as it was before the UI first, and taken back once the shell resumes it
+                #[cfg(unix)]
+                if key == Key::Ctrl('z') {
+                    events_handle.abort();
+                    let _ = (&mut events_handle).await;
+                    terminal.show_cursor()?;
+                    close_application()?;
+                    suspend()?;
+
+                    // Nothing is drawn until the shell has resumed the process
+                    continued.recv().await;
+                    resume_terminal(
+                        &mut terminal,
+                        &mut album_art_renderer,
+                        &app,
+                        user_config.behavior.enable_mouse,
+                    )
+                    .await?;
+                    (events, events_handle) =
+                        event::Events::new(user_config.behavior.tick_rate_milliseconds);
+                    continue;
+                }
+
                 let current_active_block = app.read().await.get_current_route().active_block;
 
                 // To avoid swallowing the global key presses `q` and `-` make a special
                 // case for the input handler
                 if current_active_block == ActiveBlock::Input {
                     handlers::input_handler(key, &mut *app.write().await);
+                } else if current_active_block == ActiveBlock::HelpMenu
+                    && app.read().await.is_filtering_help()
+                {
+                    // Typed into the filter rather than taken for the global keys
+                    handlers::help_menu_handler(key, &mut *app.write().await);
+                } else if current_active_block == ActiveBlock::SelectDevice
+                    && app.read().await.is_finding_device()
+                {
+                    handlers::select_device_handler(key, &mut *app.write().await);
                 } else if key == app.read().await.user_config.keys.back {
                     if app.read().await.get_current_route().active_block != ActiveBlock::Input {
                         // Go back through navigation stack when not in search input mode and exit the app if there are no more places to back to
@@ -420,6 +850,9 @@ async fn start_ui(
                     handlers::handle_app(key, &mut *app.write().await);
                 }
             }
+            Some(event::Event::Mouse(mouse)) => {
+                handlers::handle_mouse(mouse, &mut *app.write().await);
+            }
             Some(event::Event::Tick) => {
                 app.write().await.update_on_tick();
             }
@@ -430,10 +863,10 @@ async fn start_ui(
         // startup speed
         if is_first_render {
             let mut app = app.write().await;
-            app.dispatch(IoEvent::GetPlaylists);
-            app.dispatch(IoEvent::GetUser);
-            app.dispatch(IoEvent::GetCurrentPlayback);
-            app.help_docs_size = ui::help::get_help_docs(&app.user_config.keys).len() as u32;
+            app.dispatch_startup_events();
+            if let Some(first_draw_tx) = first_draw_tx.take() {
+                let _ = first_draw_tx.send(());
+            }
 
             is_first_render = false;
         }
@@ -441,13 +874,43 @@ async fn start_ui(
 
     events_handle.abort();
 
+    let library_cache_saves = {
+        let mut app = app.write().await;
+        app.dispatch(IoEvent::SaveLibraryCache);
+        // Small enough to write before leaving, and not worth keeping the terminal over
+        if let Some(path) = &app.startup_cache_path {
+            let _ = startup_cache::save(path, &app.startup_cache());
+        }
+        app.library_cache_saves
+    };
+
+    #[cfg(unix)]
+    if let Some(path) = control_socket_path {
+        let _ = std::fs::remove_file(path);
+    }
+
     terminal.show_cursor()?;
     close_application()?;
+    wait_for_library_cache(&app, library_cache_saves).await;
     main_tx.send(()).await?;
 
     Ok(())
 }
 
+// The network task is stopped with the process, so it's given a moment to finish the last save of
+// the library cache. Saves made every few minutes cover the rest when it is busy for longer.
+async fn wait_for_library_cache(app: &RwLock<App>, saves: usize) {
+    if app.read().await.library_cache_path.is_none() {
+        return;
+    }
+    for _ in 0..20 {
+        if app.read().await.library_cache_saves > saves {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+}
+
 struct MetadataManager {
     app: Arc<RwLock<App>>,
     controls: MediaControls,
@@ -483,7 +946,12 @@ impl MetadataManager {
         let mut controls = MediaControls::new(config).unwrap();
         let (tx, rx) = std::sync::mpsc::sync_channel(32);
 
-        controls.attach(move |e| tx.send(e).unwrap()).unwrap();
+        // Events that come faster than the loop takes them, or after it stopped, are dropped
+        controls
+            .attach(move |e| {
+                let _ = tx.try_send(e);
+            })
+            .unwrap();
 
         let mut this = Self {
             app: app.clone(),
@@ -574,3 +1042,45 @@ impl ApplicationHandler for MetadataManager {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    #[tokio::test]
+    async fn media_controls_wait_for_the_first_draw() {
+        let started = Arc::new(AtomicBool::new(false));
+        let (first_draw_tx, first_draw_rx) = oneshot::channel();
+        let handle = tokio::spawn({
+            let started = started.clone();
+            after_first_draw(first_draw_rx, move || started.store(true, Ordering::SeqCst))
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!started.load(Ordering::SeqCst));
+
+        first_draw_tx.send(()).unwrap();
+        assert_eq!(handle.await.unwrap(), Some(()));
+        assert!(started.load(Ordering::SeqCst));
+
+        // A UI that ends before drawing anything never starts them
+        let (first_draw_tx, first_draw_rx) = oneshot::channel::<()>();
+        drop(first_draw_tx);
+        assert_eq!(
+            after_first_draw(first_draw_rx, || unreachable!()).await,
+            None::<()>
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn hears_when_the_process_is_continued() {
+        let mut continued = Continued::listen().unwrap();
+        // Harmless to a process that isn't stopped
+        assert_eq!(unsafe { libc::raise(libc::SIGCONT) }, 0);
+        tokio::time::timeout(Duration::from_secs(5), continued.recv())
+            .await
+            .unwrap()
+            .unwrap();
+    }
+}
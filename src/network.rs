@@ -1,17 +1,23 @@
 use crate::app::{
-    ActiveBlock, AlbumTableContext, App, Artist, ArtistBlock, EpisodeTableContext,
-    ItemTableContext, RouteId, ScrollableResultPages, SelectedAlbum, SelectedFullAlbum,
-    SelectedFullShow, SelectedShow,
+    is_fully_played, radio_seeds, rank_artists, repeat_state_name, ActiveBlock, AlbumArt,
+    AlbumTableContext, App, Artist, ArtistBlock, EpisodeTableContext, FinishedShows,
+    ItemTableContext, Lyrics, RouteId, ScrollableResultPages, SelectedAlbum, SelectedFullAlbum,
+    SelectedFullShow, SelectedShow, StartupFetch, TopItemKind, TrackAnalysis,
 };
 use crate::config::ClientConfig;
+use crate::history;
+use crate::instance_lock;
+use crate::library_cache;
+use crate::lyrics::{LrcLib, LyricsProvider};
+use crate::notifications::Severity;
 use anyhow::anyhow;
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
 use derivative::Derivative;
-use futures_util::{future::try_join_all, try_join};
+use futures_util::{future::try_join_all, stream, try_join, StreamExt};
 use rspotify::model::{
     album::SimplifiedAlbum,
     artist::FullArtist,
-    enums::{AdditionalType, Country, RepeatState, SearchType},
+    enums::{AdditionalType, AlbumType, Country, RepeatState, SearchType, TimeRange},
     idtypes::*,
     page::Page,
     playlist::{PlaylistItem, SimplifiedPlaylist},
@@ -19,13 +25,32 @@ use rspotify::model::{
     search::SearchResult,
     show::SimplifiedShow,
     track::FullTrack,
-    DevicePayload, Market, Offset, PlayableItem,
+    Market, Offset, PlayableItem,
 };
-use rspotify::{clients::*, AuthCodePkceSpotify};
+use rspotify::{clients::*, http::HttpError, AuthCodePkceSpotify, ClientError, Token};
 use serde::Deserialize;
 use spotify_tui_util::*;
-use std::{sync::Arc, time::Instant};
-use tokio::sync::RwLock;
+use std::{cmp::min, collections::HashSet, path::PathBuf, sync::Arc, time::Instant};
+use tokio::sync::{mpsc::UnboundedSender, oneshot, RwLock};
+
+// Where playback of a context or of a list of items starts
+#[derive(Clone, Debug, PartialEq, ToStatic)]
+pub enum PlaybackOffset {
+    // The position in the context or list, counting from 0
+    Index(u32),
+    Item(PlayableId<'static>),
+}
+
+impl PlaybackOffset {
+    // The one place an rspotify Offset is made. rspotify encodes an index as the milliseconds
+    // of a Duration, which is easy to get wrong anywhere else.
+    pub fn into_offset(self) -> Offset {
+        match self {
+            PlaybackOffset::Index(index) => Offset::Position(Duration::milliseconds(index as i64)),
+            PlaybackOffset::Item(playable_id) => Offset::Uri(playable_id.uri()),
+        }
+    }
+}
 
 #[derive(Derivative, ToStatic)]
 #[derivative(Debug)]
@@ -34,6 +59,19 @@ pub enum IoEvent<'a> {
         #[derivative(Debug(format_with = "fmt_id"))]
         playable_id: PlayableId<'a>,
     },
+    // Queues the tracks of an album or the items of a playlist in order, at most `limit` of them
+    AddContextToQueue {
+        #[derivative(Debug(format_with = "fmt_id"))]
+        play_context_id: PlayContextId<'a>,
+        limit: u32,
+    },
+    // Leaves out what is None
+    ChangePlaylistDetails {
+        #[derivative(Debug(format_with = "fmt_id"))]
+        playlist_id: PlaylistId<'a>,
+        name: Option<String>,
+        description: Option<String>,
+    },
     ChangeVolume {
         volume: u8,
     },
@@ -69,6 +107,8 @@ pub enum IoEvent<'a> {
         #[derivative(Debug(format_with = "fmt_ids"))]
         track_ids: Vec<TrackId<'a>>,
     },
+    // Looks through the saved podcasts for those whose every episode has been played
+    FindFinishedShows,
     GetAlbum {
         #[derivative(Debug(format_with = "fmt_id"))]
         album_id: AlbumId<'a>,
@@ -86,10 +126,22 @@ pub enum IoEvent<'a> {
         input_artist_name: String,
         country: Option<Country>,
     },
+    GetArtistAlbums {
+        #[derivative(Debug(format_with = "fmt_id"))]
+        artist_id: ArtistId<'a>,
+        offset: u32,
+        album_type: Option<AlbumType>,
+        country: Option<Country>,
+    },
     GetTrackAnalysis {
         #[derivative(Debug(format_with = "fmt_id"))]
         track_id: TrackId<'a>,
     },
+    // The features of one track, as opposed to GetAudioFeatures which only feeds a sort
+    GetTrackFeatures {
+        #[derivative(Debug(format_with = "fmt_id"))]
+        track_id: TrackId<'a>,
+    },
     GetCurrentPlayback,
     GetCurrentShowEpisodes {
         #[derivative(Debug(format_with = "fmt_id"))]
@@ -105,16 +157,40 @@ pub enum IoEvent<'a> {
     GetCurrentUserSavedTracks {
         offset: Option<u32>,
     },
-    GetDevices,
+    GetAlbumArt {
+        url: String,
+    },
+    GetAudioFeatures {
+        #[derivative(Debug(format_with = "fmt_ids"))]
+        track_ids: Vec<TrackId<'a>>,
+    },
+    GetAllCurrentUserSavedTracks {
+        limit: Option<u32>,
+    },
+    GetDevices {
+        // Only show the device selection when the saved device isn't available
+        silent: bool,
+    },
     GetFollowedArtists {
         after: Option<ArtistId<'a>>,
     },
+    GetLyrics {
+        #[derivative(Debug(format_with = "fmt_id"))]
+        track_id: TrackId<'a>,
+        artist: String,
+        title: String,
+    },
     GetMadeForYouPlaylistItems {
         #[derivative(Debug(format_with = "fmt_id"))]
         playlist_id: PlaylistId<'a>,
         offset: u32,
     },
     GetPlaylists,
+    // Opens the input to edit it
+    GetPlaylistDescription {
+        #[derivative(Debug(format_with = "fmt_id"))]
+        playlist_id: PlaylistId<'a>,
+    },
     GetPlaylistItems {
         #[derivative(Debug(format_with = "fmt_id"))]
         playlist_id: PlaylistId<'a>,
@@ -129,6 +205,10 @@ pub enum IoEvent<'a> {
         first_track: Box<Option<FullTrack>>,
         country: Option<Country>,
     },
+    // Recommendations seeded by random liked songs, for when none have been loaded yet
+    GetRecommendationsForLikedSongs {
+        country: Option<Country>,
+    },
     GetRecommendationsForTrackId {
         #[derivative(Debug(format_with = "fmt_id"))]
         track_id: TrackId<'a>,
@@ -136,6 +216,15 @@ pub enum IoEvent<'a> {
     },
     GetSearchResults {
         search_term: String,
+        // The types of results to search for, all of them when None
+        types: Option<Vec<SearchType>>,
+        country: Option<Country>,
+    },
+    // The next page of one type of results for the current search
+    GetMoreSearchResults {
+        search_term: String,
+        search_type: SearchType,
+        offset: u32,
         country: Option<Country>,
     },
     GetShow {
@@ -146,6 +235,21 @@ pub enum IoEvent<'a> {
         show: Box<SimplifiedShow>,
     },
     GetUser,
+    GetUserTopItems {
+        kind: TopItemKind,
+        time_range: TimeRange,
+        offset: u32,
+    },
+    // Looks through the pages of a playlist for an item, and shows the page it is on with its row
+    // selected
+    JumpToPlaylistItem {
+        #[derivative(Debug(format_with = "fmt_id"))]
+        playlist_id: PlaylistId<'a>,
+        #[derivative(Debug(format_with = "fmt_id"))]
+        playable_id: PlayableId<'a>,
+    },
+    // Reads the library cache, marking what it holds as in the library until checked
+    LoadLibraryCache,
     MadeForYouSearchAndAdd {
         search_term: String,
         country: Option<Country>,
@@ -154,10 +258,23 @@ pub enum IoEvent<'a> {
     PausePlayback,
     PreviousTrack,
     RefreshAuthentication,
+    // Unfollows the podcasts, however many there are
+    RemoveSavedShows {
+        #[derivative(Debug(format_with = "fmt_ids"))]
+        show_ids: Vec<ShowId<'a>>,
+    },
     Repeat {
         state: RepeatState,
     },
+    // Plays the episodes from the one at `offset`, starting at `position_ms` into it
+    ResumeEpisodePlayback {
+        #[derivative(Debug(format_with = "fmt_ids"))]
+        playable_ids: Vec<PlayableId<'a>>,
+        offset: PlaybackOffset,
+        position_ms: u32,
+    },
     ResumePlayback,
+    SaveLibraryCache,
     Seek {
         position_ms: u32,
     },
@@ -170,12 +287,12 @@ pub enum IoEvent<'a> {
     StartContextPlayback {
         #[derivative(Debug(format_with = "fmt_id"))]
         play_context_id: PlayContextId<'a>,
-        offset: Option<u32>,
+        offset: Option<PlaybackOffset>,
     },
     StartPlayablesPlayback {
         #[derivative(Debug(format_with = "fmt_ids"))]
         playable_ids: Vec<PlayableId<'a>>,
-        offset: Option<u32>,
+        offset: Option<PlaybackOffset>,
     },
     ToggleSaveEpisode {
         #[derivative(Debug(format_with = "fmt_id"))]
@@ -216,11 +333,166 @@ pub enum IoEvent<'a> {
     },
 }
 
+impl IoEvent<'_> {
+    // Whether the event is the user changing the playback, after which it is polled for often
+    // again however long nothing was playing
+    pub fn is_playback_control(&self) -> bool {
+        matches!(
+            self,
+            IoEvent::NextTrack
+                | IoEvent::PausePlayback
+                | IoEvent::PreviousTrack
+                | IoEvent::Repeat { .. }
+                | IoEvent::ResumeEpisodePlayback { .. }
+                | IoEvent::ResumePlayback
+                | IoEvent::Seek { .. }
+                | IoEvent::StartContextPlayback { .. }
+                | IoEvent::StartPlayablesPlayback { .. }
+                | IoEvent::ToggleShuffle
+                | IoEvent::TransferPlaybackToDevice { .. }
+        )
+    }
+
+    // Whether the event changes anything on Spotify (playback, library, follows), as opposed to
+    // only reading from it. Every variant is listed so that new events have to be classified.
+    pub fn is_mutating(&self) -> bool {
+        match self {
+            IoEvent::AddContextToQueue { .. }
+            | IoEvent::AddItemToQueue { .. }
+            | IoEvent::ChangePlaylistDetails { .. }
+            | IoEvent::ChangeVolume { .. }
+            | IoEvent::CurrentUserSavedAlbumAdd { .. }
+            | IoEvent::CurrentUserSavedAlbumDelete { .. }
+            | IoEvent::CurrentUserSavedShowAdd { .. }
+            | IoEvent::CurrentUserSavedShowDelete { .. }
+            | IoEvent::NextTrack
+            | IoEvent::PausePlayback
+            | IoEvent::PreviousTrack
+            | IoEvent::RemoveSavedShows { .. }
+            | IoEvent::Repeat { .. }
+            | IoEvent::ResumeEpisodePlayback { .. }
+            | IoEvent::ResumePlayback
+            | IoEvent::Seek { .. }
+            | IoEvent::StartContextPlayback { .. }
+            | IoEvent::StartPlayablesPlayback { .. }
+            | IoEvent::ToggleSaveEpisode { .. }
+            | IoEvent::ToggleSaveTrack { .. }
+            | IoEvent::ToggleShuffle
+            | IoEvent::TransferPlaybackToDevice { .. }
+            | IoEvent::UserUnfollowArtists { .. }
+            | IoEvent::UserFollowArtists { .. }
+            | IoEvent::UserFollowPlaylist { .. }
+            | IoEvent::UserUnfollowPlaylist { .. } => true,
+            IoEvent::CurrentUserSavedAlbumsContains { .. }
+            | IoEvent::CurrentUserSavedEpisodesContains { .. }
+            | IoEvent::CurrentUserSavedShowsContains { .. }
+            | IoEvent::CurrentUserSavedTracksContains { .. }
+            | IoEvent::FindFinishedShows
+            | IoEvent::GetAlbum { .. }
+            | IoEvent::GetAlbumForTrack { .. }
+            | IoEvent::GetAlbumTracks { .. }
+            | IoEvent::GetArtist { .. }
+            | IoEvent::GetArtistAlbums { .. }
+            | IoEvent::GetTrackAnalysis { .. }
+            | IoEvent::GetTrackFeatures { .. }
+            | IoEvent::GetCurrentPlayback
+            | IoEvent::GetCurrentShowEpisodes { .. }
+            | IoEvent::GetCurrentUserSavedAlbums { .. }
+            | IoEvent::GetCurrentUserSavedShows { .. }
+            | IoEvent::GetCurrentUserSavedTracks { .. }
+            | IoEvent::GetAlbumArt { .. }
+            | IoEvent::GetAudioFeatures { .. }
+            | IoEvent::GetAllCurrentUserSavedTracks { .. }
+            | IoEvent::GetDevices { .. }
+            | IoEvent::GetFollowedArtists { .. }
+            | IoEvent::GetLyrics { .. }
+            | IoEvent::GetMadeForYouPlaylistItems { .. }
+            | IoEvent::GetPlaylists
+            | IoEvent::GetPlaylistDescription { .. }
+            | IoEvent::GetPlaylistItems { .. }
+            | IoEvent::GetRecentlyPlayed
+            | IoEvent::GetRecommendationsForSeed { .. }
+            | IoEvent::GetRecommendationsForLikedSongs { .. }
+            | IoEvent::GetRecommendationsForTrackId { .. }
+            | IoEvent::GetSearchResults { .. }
+            | IoEvent::GetMoreSearchResults { .. }
+            | IoEvent::GetShow { .. }
+            | IoEvent::GetShowEpisodes { .. }
+            | IoEvent::GetUser
+            | IoEvent::GetUserTopItems { .. }
+            | IoEvent::JumpToPlaylistItem { .. }
+            | IoEvent::LoadLibraryCache
+            | IoEvent::MadeForYouSearchAndAdd { .. }
+            | IoEvent::RefreshAuthentication
+            | IoEvent::SaveLibraryCache
+            | IoEvent::SetArtistsToTable { .. }
+            | IoEvent::SetTracksToTable { .. }
+            | IoEvent::UpdateSearchLimits { .. }
+            | IoEvent::UserArtistFollowCheck { .. } => false,
+        }
+    }
+}
+
+// The most tracks the several-tracks audio features endpoint accepts at once
+const AUDIO_FEATURES_CHUNK_SIZE: usize = 100;
+
+// The largest page Spotify returns for saved tracks
+const SAVED_TRACKS_PAGE_LIMIT: u32 = 50;
+
+// The most ids each of the saved-state "contains" endpoints accepts at once
+const SAVED_TRACKS_CONTAINS_LIMIT: usize = 50;
+const SAVED_ALBUMS_CONTAINS_LIMIT: usize = 20;
+const SAVED_SHOWS_CONTAINS_LIMIT: usize = 50;
+
+// The most podcasts unfollowing them accepts at once
+const SAVED_SHOWS_REMOVE_LIMIT: usize = 50;
+
+// The largest page Spotify returns for saved podcasts and for the episodes of a podcast
+const SHOWS_PAGE_LIMIT: u32 = 50;
+
+// How many podcasts have their episodes fetched at once when looking for finished ones, and how
+// many are looked through between each progress notification
+const FINISHED_SHOWS_CONCURRENCY: usize = 4;
+const FINISHED_SHOWS_PROGRESS_EVERY: usize = 10;
+
+// How long a secondary instance waits to read the token from the cache again, when the other
+// instance hasn't refreshed it yet
+const SHARED_TOKEN_RETRY: Duration = Duration::seconds(10);
+
+// How long refreshing the token waits to be tried again after failing to reach Spotify
+const TOKEN_REFRESH_RETRY: Duration = Duration::seconds(5);
+
+// What a search looks for unless it asks for only some of these
+const ALL_SEARCH_TYPES: [SearchType; 6] = [
+    SearchType::Track,
+    SearchType::Artist,
+    SearchType::Album,
+    SearchType::Playlist,
+    SearchType::Show,
+    SearchType::Episode,
+];
+
+// Asks the UI to hand the terminal over while signing in again, which prompts in the terminal
+// like at startup
+pub struct TerminalHandover {
+    // Sent once the terminal is out of raw mode and nothing else reads the keyboard
+    pub released: oneshot::Sender<()>,
+    // Sent once signing in is over, and the UI can take the terminal back
+    pub restore: oneshot::Receiver<()>,
+}
+
 #[derive(Clone)]
 pub struct Network {
     pub spotify: AuthCodePkceSpotify,
     pub client_config: ClientConfig,
     pub app: Arc<RwLock<App>>,
+    http_client: reqwest::Client,
+    lyrics_provider: Arc<dyn LyricsProvider>,
+    // None without a UI, which leaves the terminal as it is
+    terminal_tx: Option<UnboundedSender<TerminalHandover>>,
+    // The lock of the instance that refreshes the token while this one reads it from the cache,
+    // None when this is the only instance
+    shared_token_lock: Option<PathBuf>,
     large_search_limit: u32,
     small_search_limit: u32,
 }
@@ -237,6 +509,109 @@ macro_rules! handle_error {
     };
 }
 
+// Like `handle_error!`, but for the startup fetches, which are retried instead when the error may
+// pass
+macro_rules! handle_startup_error {
+    ($self:ident, $fetch:expr, $res:expr) => {
+        match $res {
+            Ok(ok) => ok,
+            Err(err) => {
+                $self.handle_startup_error($fetch, err).await;
+                return;
+            }
+        }
+    };
+}
+
+// Whether a status is worth trying again later: Spotify rate limiting or having trouble of its own
+fn is_transient_status(status: reqwest::StatusCode) -> bool {
+    status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+// Whether a failed request may succeed as is when repeated, as opposed to errors such as a
+// revoked token or a missing item that will keep failing
+pub fn is_transient(err: &ClientError) -> bool {
+    match err {
+        ClientError::Http(err) => match err.as_ref() {
+            HttpError::Client(err) => err.is_connect() || err.is_timeout() || err.is_request(),
+            HttpError::StatusCode(response) => is_transient_status(response.status()),
+        },
+        ClientError::Io(_) => true,
+        _ => false,
+    }
+}
+
+// A token that is no longer valid, e.g. because another instance refreshed it
+fn is_unauthorized(err: &ClientError) -> bool {
+    matches!(
+        err,
+        ClientError::Http(err) if matches!(
+            err.as_ref(),
+            HttpError::StatusCode(response) if response.status() == reqwest::StatusCode::UNAUTHORIZED
+        )
+    )
+}
+
+// What went wrong refreshing the token, None when Spotify turned the refresh token down because
+// it was revoked or has expired
+async fn refresh_error_message(err: ClientError) -> Option<String> {
+    let ClientError::Http(http_error) = err else {
+        return Some(err.to_string());
+    };
+    match *http_error {
+        HttpError::StatusCode(response)
+            if response.status() == reqwest::StatusCode::BAD_REQUEST =>
+        {
+            let body = response.text().await.unwrap_or_default();
+            (!body.contains("invalid_grant")).then(|| format!("400 Bad Request: {}", body))
+        }
+        http_error => Some(ClientError::Http(Box::new(http_error)).to_string()),
+    }
+}
+
+// What to tell the user about an error that may pass by itself, which isn't worth leaving what
+// they were doing for. None for any other error.
+fn transient_error_message(err: &ClientError) -> Option<String> {
+    if !is_transient(err) {
+        return None;
+    }
+    let message = match err {
+        ClientError::Http(err) => match err.as_ref() {
+            HttpError::StatusCode(response)
+                if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS =>
+            {
+                let retry_after = response
+                    .headers()
+                    .get(reqwest::header::RETRY_AFTER)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| value.parse::<u64>().ok());
+                match retry_after {
+                    Some(seconds) => format!(
+                        "Spotify is limiting requests, try again in {} seconds",
+                        seconds
+                    ),
+                    None => String::from("Spotify is limiting requests, try again in a moment"),
+                }
+            }
+            HttpError::StatusCode(response) => format!(
+                "Spotify is having trouble ({}), try again in a moment",
+                response.status().as_u16()
+            ),
+            HttpError::Client(_) => {
+                String::from("Couldn't reach Spotify, check the connection and try again")
+            }
+        },
+        _ => String::from("Couldn't reach Spotify, check the connection and try again"),
+    };
+    Some(message)
+}
+
+fn audio_features_chunks<'a, 'b>(
+    track_ids: &'b [TrackId<'a>],
+) -> impl Iterator<Item = &'b [TrackId<'a>]> {
+    track_ids.chunks(AUDIO_FEATURES_CHUNK_SIZE)
+}
+
 #[inline]
 fn join_ids<'a, T: Id + 'a>(ids: impl IntoIterator<Item = T>) -> String {
     let ids = ids.into_iter().collect::<Vec<_>>();
@@ -260,13 +635,63 @@ impl Network {
             small_search_limit: 4,
             client_config,
             app,
+            http_client: reqwest::Client::new(),
+            lyrics_provider: Arc::new(LrcLib::default()),
+            terminal_tx: None,
+            shared_token_lock: None,
+        }
+    }
+
+    pub fn with_lyrics_provider(mut self, lyrics_provider: Arc<dyn LyricsProvider>) -> Self {
+        self.lyrics_provider = lyrics_provider;
+        self
+    }
+
+    pub fn with_terminal_handover(
+        mut self,
+        terminal_tx: UnboundedSender<TerminalHandover>,
+    ) -> Self {
+        self.terminal_tx = Some(terminal_tx);
+        self
+    }
+
+    pub fn with_shared_token(mut self, instance_lock_path: PathBuf) -> Self {
+        self.shared_token_lock = Some(instance_lock_path);
+        self
+    }
+
+    // The name of what is playing from a context, which the playback only gives the URI of. Used
+    // by the CLI, which waits for it rather than dispatching an event.
+    pub async fn get_context_name(&self, context_uri: &str) -> anyhow::Result<String> {
+        if context_uri.ends_with(":collection") {
+            return Ok(String::from("Liked Songs"));
         }
+        Ok(match PlayContextId::from_uri(context_uri)? {
+            PlayContextId::Album(album_id) => self.spotify.album(album_id, None).await?.name,
+            PlayContextId::Artist(artist_id) => self.spotify.artist(artist_id).await?.name,
+            PlayContextId::Playlist(playlist_id) => {
+                self.spotify.playlist(playlist_id, None, None).await?.name
+            }
+            PlayContextId::Show(show_id) => self.spotify.get_a_show(show_id, None).await?.name,
+        })
     }
 
     #[allow(clippy::cognitive_complexity)]
     pub async fn handle_network_event(&mut self, event: IoEvent<'_>) {
         match event {
+            IoEvent::AddContextToQueue {
+                play_context_id,
+                limit,
+            } => self.add_context_to_queue(play_context_id, limit).await,
             IoEvent::AddItemToQueue { playable_id } => self.add_item_to_queue(playable_id).await,
+            IoEvent::ChangePlaylistDetails {
+                playlist_id,
+                name,
+                description,
+            } => {
+                self.change_playlist_details(playlist_id, name, description)
+                    .await
+            }
             IoEvent::ChangeVolume { volume } => self.change_volume(volume).await,
             IoEvent::CurrentUserSavedAlbumAdd { album_id } => {
                 self.current_user_saved_album_add(album_id).await
@@ -292,6 +717,7 @@ impl Network {
             IoEvent::CurrentUserSavedTracksContains { track_ids } => {
                 self.current_user_saved_tracks_contains(track_ids).await
             }
+            IoEvent::FindFinishedShows => self.find_finished_shows().await,
             IoEvent::GetAlbum { album_id } => self.get_album(album_id).await,
             IoEvent::GetAlbumForTrack { track_id } => self.get_album_for_track(track_id).await,
             IoEvent::GetAlbumTracks { album } => self.get_album_tracks(album).await,
@@ -300,7 +726,17 @@ impl Network {
                 input_artist_name,
                 country,
             } => self.get_artist(artist_id, input_artist_name, country).await,
+            IoEvent::GetArtistAlbums {
+                artist_id,
+                offset,
+                album_type,
+                country,
+            } => {
+                self.get_artist_albums(artist_id, offset, album_type, country)
+                    .await
+            }
             IoEvent::GetTrackAnalysis { track_id } => self.get_track_analysis(track_id).await,
+            IoEvent::GetTrackFeatures { track_id } => self.get_track_features(track_id).await,
             IoEvent::GetCurrentPlayback => self.get_current_playback().await,
             IoEvent::GetCurrentShowEpisodes { show_id, offset } => {
                 self.get_current_show_episodes(show_id, offset).await
@@ -314,8 +750,18 @@ impl Network {
             IoEvent::GetCurrentUserSavedTracks { offset } => {
                 self.get_current_user_saved_tracks(offset).await
             }
-            IoEvent::GetDevices => self.get_devices().await,
+            IoEvent::GetAlbumArt { url } => self.get_album_art(url).await,
+            IoEvent::GetAudioFeatures { track_ids } => self.get_audio_features(track_ids).await,
+            IoEvent::GetAllCurrentUserSavedTracks { limit } => {
+                self.get_all_current_user_saved_tracks(limit).await
+            }
+            IoEvent::GetDevices { silent } => self.get_devices(silent).await,
             IoEvent::GetFollowedArtists { after } => self.get_followed_artists(after).await,
+            IoEvent::GetLyrics {
+                track_id,
+                artist,
+                title,
+            } => self.get_lyrics(track_id, artist, title).await,
             IoEvent::GetMadeForYouPlaylistItems {
                 playlist_id,
                 offset,
@@ -324,10 +770,17 @@ impl Network {
                     .await
             }
             IoEvent::GetPlaylists => self.get_current_user_playlists().await,
+            IoEvent::GetPlaylistDescription { playlist_id } => {
+                self.get_playlist_description(playlist_id).await
+            }
             IoEvent::GetPlaylistItems {
                 playlist_id,
                 offset,
             } => self.get_playlist_items(playlist_id, offset).await,
+            IoEvent::JumpToPlaylistItem {
+                playlist_id,
+                playable_id,
+            } => self.jump_to_playlist_item(playlist_id, playable_id).await,
             IoEvent::GetRecentlyPlayed => self.get_recently_played().await,
             IoEvent::GetRecommendationsForSeed {
                 seed_artist_ids,
@@ -343,17 +796,35 @@ impl Network {
                 )
                 .await
             }
+            IoEvent::GetRecommendationsForLikedSongs { country } => {
+                self.get_recommendations_for_liked_songs(country).await
+            }
             IoEvent::GetRecommendationsForTrackId { track_id, country } => {
                 self.get_recommendations_for_track_id(track_id, country)
                     .await
             }
             IoEvent::GetSearchResults {
                 search_term,
+                types,
+                country,
+            } => self.get_search_results(search_term, types, country).await,
+            IoEvent::GetMoreSearchResults {
+                search_term,
+                search_type,
+                offset,
                 country,
-            } => self.get_search_results(search_term, country).await,
+            } => {
+                self.get_more_search_results(search_term, search_type, offset, country)
+                    .await
+            }
             IoEvent::GetShow { show_id } => self.get_show(show_id).await,
             IoEvent::GetShowEpisodes { show } => self.get_show_episodes(show).await,
             IoEvent::GetUser => self.get_user().await,
+            IoEvent::GetUserTopItems {
+                kind,
+                time_range,
+                offset,
+            } => self.get_user_top_items(kind, time_range, offset).await,
             IoEvent::MadeForYouSearchAndAdd {
                 search_term,
                 country,
@@ -362,7 +833,18 @@ impl Network {
             IoEvent::PausePlayback => self.pause_playback().await,
             IoEvent::PreviousTrack => self.previous_track().await,
             IoEvent::RefreshAuthentication => self.refresh_authentication().await,
+            IoEvent::RemoveSavedShows { show_ids } => self.remove_saved_shows(show_ids).await,
+            IoEvent::LoadLibraryCache => self.load_library_cache().await,
+            IoEvent::SaveLibraryCache => self.save_library_cache().await,
             IoEvent::Repeat { state } => self.repeat(state).await,
+            IoEvent::ResumeEpisodePlayback {
+                playable_ids,
+                offset,
+                position_ms,
+            } => {
+                self.resume_episode_playback(playable_ids, offset, position_ms)
+                    .await
+            }
             IoEvent::ResumePlayback => self.resume_playback().await,
             IoEvent::Seek { position_ms } => self.seek(position_ms).await,
             IoEvent::SetArtistsToTable { artists } => self.set_artists_to_table(artists).await,
@@ -390,6 +872,10 @@ impl Network {
             } => {
                 self.large_search_limit = large_search_limit;
                 self.small_search_limit = small_search_limit;
+                // The paging in the UI steps by the same limit as the pages fetched
+                let mut app = self.app.write().await;
+                app.large_search_limit = large_search_limit;
+                app.small_search_limit = small_search_limit;
             }
             IoEvent::UserUnfollowArtists { artist_ids } => {
                 self.user_unfollow_artists(artist_ids).await
@@ -411,9 +897,27 @@ impl Network {
         app.is_loading = false;
     }
 
+    // Errors that may pass by themselves are only notified of, without leaving the current view.
+    // Anything else, such as a revoked token or a missing permission, gets the error screen.
     async fn handle_error(&mut self, e: anyhow::Error) {
+        let client_error = e.downcast_ref::<ClientError>();
+        if self.shared_token_lock.is_some() && client_error.is_some_and(is_unauthorized) {
+            if let Some(token) = self.reload_shared_token().await {
+                let mut app = self.app.write().await;
+                app.spotify_token_expiry = token.expires_at.unwrap_or(Utc::now());
+                app.notify(
+                    Severity::Warning,
+                    "Took the session refreshed by the other spotify-tui, try again",
+                );
+                return;
+            }
+        }
+
         let mut app = self.app.write().await;
-        app.handle_error(e);
+        match client_error.and_then(transient_error_message) {
+            Some(message) => app.notify(Severity::Warning, message),
+            None => app.handle_error(e),
+        }
     }
 
     async fn add_item_to_queue(&mut self, playable_id: PlayableId<'_>) {
@@ -423,28 +927,138 @@ impl Network {
                 .add_item_to_queue(playable_id, self.client_config.device_id.as_deref())
                 .await
         );
+        let mut app = self.app.write().await;
+        app.notify(Severity::Info, "Added to the queue");
+    }
+
+    // Each item is queued on its own, as that is all the API can do. A failure partway through says
+    // how many made it.
+    async fn add_context_to_queue(&mut self, play_context_id: PlayContextId<'_>, limit: u32) {
+        let playable_ids = handle_error!(
+            self,
+            self.context_playable_ids(&play_context_id, limit).await
+        );
+        let total = playable_ids.len();
+        let items = |count: usize| if count == 1 { "item" } else { "items" };
+        for (queued, playable_id) in playable_ids.into_iter().enumerate() {
+            let result = self
+                .spotify
+                .add_item_to_queue(playable_id, self.client_config.device_id.as_deref())
+                .await;
+            if let Err(e) = result {
+                let mut app = self.app.write().await;
+                app.notify(
+                    Severity::Error,
+                    format!(
+                        "Queued {} of {} {} before failing: {}",
+                        queued,
+                        total,
+                        items(total),
+                        e
+                    ),
+                );
+                return;
+            }
+        }
+        let mut app = self.app.write().await;
+        app.notify(
+            Severity::Info,
+            format!("Added {} {} to the queue", total, items(total)),
+        );
+    }
+
+    // The tracks of the album or the items of the playlist, in order and leaving out local files
+    async fn context_playable_ids(
+        &self,
+        play_context_id: &PlayContextId<'_>,
+        limit: u32,
+    ) -> anyhow::Result<Vec<PlayableId<'static>>> {
+        let mut playable_ids = Vec::new();
+        let mut offset = 0;
+        while (playable_ids.len() as u32) < limit {
+            let page_limit = Some(self.large_search_limit);
+            let (ids, page_len, has_next): (Vec<PlayableId<'static>>, _, _) = match play_context_id
+            {
+                PlayContextId::Album(album_id) => {
+                    let page = self
+                        .spotify
+                        .album_track_manual(album_id.as_ref(), None, page_limit, Some(offset))
+                        .await?;
+                    let ids = page
+                        .items
+                        .iter()
+                        .filter_map(|track| track.id.clone().map(PlayableId::Track))
+                        .collect();
+                    (ids, page.items.len() as u32, page.next.is_some())
+                }
+                PlayContextId::Playlist(playlist_id) => {
+                    let page = self
+                        .spotify
+                        .playlist_items_manual(
+                            playlist_id.as_ref(),
+                            None,
+                            None,
+                            page_limit,
+                            Some(offset),
+                        )
+                        .await?;
+                    let ids = page
+                        .items
+                        .iter()
+                        .filter_map(|item| item.track.as_ref()?.id().to_static())
+                        .collect();
+                    (ids, page.items.len() as u32, page.next.is_some())
+                }
+                _ => return Err(anyhow!("Only albums and playlists can be queued whole")),
+            };
+            playable_ids.extend(ids);
+            offset += page_len;
+            if !has_next || page_len == 0 {
+                break;
+            }
+        }
+        playable_ids.truncate(limit as usize);
+        Ok(playable_ids)
+    }
+
+    async fn handle_startup_error(&mut self, fetch: StartupFetch, e: ClientError) {
+        let mut app = self.app.write().await;
+        if !app.on_startup_fetch_failed(fetch, is_transient(&e), Instant::now()) {
+            app.handle_error(anyhow!(e));
+        }
     }
 
     async fn get_user(&mut self) {
-        let user = handle_error!(self, self.spotify.current_user().await);
+        let user =
+            handle_startup_error!(self, StartupFetch::User, self.spotify.current_user().await);
         let mut app = self.app.write().await;
         app.user = Some(user);
+        app.stale.user = false;
+        app.on_startup_fetch_loaded(StartupFetch::User);
     }
 
-    async fn get_devices(&mut self) {
+    async fn get_devices(&mut self, silent: bool) {
         let devices = handle_error!(self, self.spotify.device().await);
         let mut app = self.app.write().await;
-        app.push_navigation_stack(RouteId::SelectedDevice, ActiveBlock::SelectDevice);
-        if !devices.is_empty() {
-            app.devices = Some(DevicePayload { devices });
-            // Select the first device in the list
-            app.selected_device_index = Some(0);
+
+        app.saved_device_id = self.client_config.device_id.clone();
+        let is_saved_device_available = app.saved_device_id.as_ref().is_some_and(|device_id| {
+            devices
+                .iter()
+                .any(|device| device.id.as_ref() == Some(device_id))
+        });
+        if !silent || !is_saved_device_available {
+            app.push_navigation_stack(RouteId::SelectedDevice, ActiveBlock::SelectDevice);
         }
+
+        app.set_devices(devices);
     }
 
     async fn get_current_playback(&mut self) {
-        let context = handle_error!(
+        let requested_at = Instant::now();
+        let context = handle_startup_error!(
             self,
+            StartupFetch::CurrentPlayback,
             self.spotify
                 .current_playback(
                     None,
@@ -453,11 +1067,40 @@ impl Network {
                 .await
         );
 
+        let received_at = Instant::now();
+
         let mut app = self.app.write().await;
-        app.instant_since_last_current_playback_poll = Instant::now();
+        app.on_startup_fetch_loaded(StartupFetch::CurrentPlayback);
 
-        if let Some(context) = context {
-            app.current_playback_context = Some(context.clone());
+        let is_current = match &context {
+            Some(context) => {
+                app.idle_playback_polls = 0;
+                app.set_current_playback(context.clone(), requested_at, received_at, Utc::now())
+            }
+            None => {
+                app.idle_playback_polls = app.idle_playback_polls.saturating_add(1);
+                app.instant_since_last_current_playback_poll = received_at;
+                false
+            }
+        };
+        // A response that is out of date is dropped
+        if let Some(context) = context.filter(|_| is_current) {
+            app.fetch_album_art();
+            if let Some(entry) = app.take_history_entry(Utc::now()) {
+                if let Some(path) = &app.history_path {
+                    if let Err(e) = history::append(path, &entry) {
+                        app.notify(
+                            Severity::Warning,
+                            format!("Could not record the play in the history: {}", e),
+                        );
+                    }
+                }
+            }
+            match app.get_current_route().id {
+                RouteId::Lyrics => app.fetch_lyrics(),
+                RouteId::Analysis => app.fetch_audio_analysis(),
+                _ => {}
+            }
             if let Some(item) = context.item {
                 match item {
                     PlayableItem::Track(track) => {
@@ -478,29 +1121,98 @@ impl Network {
 
         app.seek_ms.take();
         app.is_fetching_current_playback = false;
+        app.playback_refresh_at = None;
     }
 
-    async fn current_user_saved_tracks_contains(&mut self, track_ids: Vec<TrackId<'_>>) {
-        let is_saved_vec = handle_error!(
-            self,
-            self.spotify
-                .current_user_saved_tracks_contains(track_ids.clone())
+    // Album art and playlist covers are only decoration, so failing to download them is not worth
+    // an error screen
+    async fn get_album_art(&mut self, url: String) {
+        let bytes = async {
+            self.http_client
+                .get(&url)
+                .send()
+                .await?
+                .error_for_status()?
+                .bytes()
                 .await
-        );
+        }
+        .await;
 
         let mut app = self.app.write().await;
-        for (i, track_id) in track_ids.into_iter().map(TrackId::into_static).enumerate() {
-            if let Some(is_liked) = is_saved_vec.get(i) {
-                if *is_liked {
-                    app.liked_song_ids_set.insert(track_id);
-                } else {
-                    // The song is not liked, so check if it should be removed
-                    if app.liked_song_ids_set.contains(&track_id) {
-                        app.liked_song_ids_set.remove(&track_id);
-                    }
-                }
-            };
+        let album_art = match bytes {
+            Ok(bytes) => AlbumArt::Loaded(bytes.to_vec()),
+            Err(_) => AlbumArt::Failed(Instant::now()),
+        };
+        app.album_art.insert(url, album_art);
+        app.fetch_item_table_cover();
+    }
+
+    async fn get_lyrics(&mut self, track_id: TrackId<'_>, artist: String, title: String) {
+        let track_id = track_id.into_static();
+        let lyrics = async {
+            let response = self
+                .lyrics_provider
+                .request(&self.http_client, &artist, &title)
+                .send()
+                .await?;
+            let status = response.status();
+            let body = response.text().await?;
+            self.lyrics_provider.parse(status, &body)
+        }
+        .await;
+
+        let mut app = self.app.write().await;
+        match lyrics {
+            Ok(Some(lyrics)) => {
+                app.lyrics.insert(track_id, Lyrics::Found(lyrics));
+            }
+            Ok(None) => {
+                app.lyrics.insert(track_id, Lyrics::NotFound);
+            }
+            Err(e) => {
+                // Not cached, so that opening the lyrics again retries
+                app.lyrics.remove(&track_id);
+                app.handle_error(e);
+            }
+        }
+    }
+
+    async fn current_user_saved_tracks_contains(&mut self, track_ids: Vec<TrackId<'_>>) {
+        let now = Instant::now();
+        let track_ids = self
+            .app
+            .read()
+            .await
+            .checked_track_ids
+            .unchecked(track_ids.into_iter().map(TrackId::into_static), now);
+        if track_ids.is_empty() {
+            return;
         }
+
+        let mut is_saved_vec = Vec::with_capacity(track_ids.len());
+        for chunk in track_ids.chunks(SAVED_TRACKS_CONTAINS_LIMIT) {
+            is_saved_vec.extend(handle_error!(
+                self,
+                self.spotify
+                    .current_user_saved_tracks_contains(chunk.to_vec())
+                    .await
+            ));
+        }
+
+        let mut app = self.app.write().await;
+        for (track_id, is_liked) in track_ids.iter().zip(is_saved_vec) {
+            // Set once the toggle has been answered
+            if app.pending_track_saves.contains_key(track_id) {
+                continue;
+            }
+            if is_liked {
+                app.liked_song_ids_set.insert(track_id.clone());
+            } else {
+                // The song is not liked, so check if it should be removed
+                app.liked_song_ids_set.remove(track_id);
+            }
+        }
+        app.checked_track_ids.mark_checked(track_ids, now);
     }
 
     async fn get_playlist_items(&mut self, playlist_id: PlaylistId<'_>, offset: u32) {
@@ -522,37 +1234,90 @@ impl Network {
         let mut app = self.app.write().await;
         app.playlist_items = Some(playlist_items);
         app.push_navigation_stack(RouteId::ItemTable, ActiveBlock::ItemTable);
+        app.fetch_item_table_cover();
+    }
+
+    async fn jump_to_playlist_item(
+        &mut self,
+        playlist_id: PlaylistId<'_>,
+        playable_id: PlayableId<'_>,
+    ) {
+        let mut offset = 0;
+        loop {
+            let playlist_items = handle_error!(
+                self,
+                self.spotify
+                    .playlist_items_manual(
+                        playlist_id.as_ref(),
+                        None,
+                        None,
+                        Some(self.large_search_limit),
+                        Some(offset),
+                    )
+                    .await
+            );
+            // Counted the way the table lists them, which leaves out items without a track
+            let index = playlist_items
+                .items
+                .iter()
+                .filter_map(|item| item.track.as_ref())
+                .position(|item| item.id().is_some_and(|id| id.equals(&playable_id)));
+
+            let mut app = self.app.write().await;
+            // Only while the playlist is still open
+            let is_open = app.item_table.context == Some(ItemTableContext::MyPlaylists)
+                && app.active_playlist().map(|playlist| &playlist.id) == Some(&playlist_id);
+            if !is_open {
+                return;
+            }
+            if let Some(index) = index {
+                drop(app);
+                self.set_playlist_items_to_table(&playlist_items).await;
+                let mut app = self.app.write().await;
+                app.playlist_offset = offset;
+                app.playlist_items = Some(playlist_items);
+                app.item_table.selected_index = index;
+                return;
+            }
+            offset += playlist_items.limit;
+            if playlist_items.next.is_none() || offset >= playlist_items.total {
+                app.notify(Severity::Info, "What's playing isn't in this playlist");
+                return;
+            }
+        }
     }
 
     async fn set_playlist_items_to_table(&mut self, playlist_item_page: &Page<PlaylistItem>) {
-        self.set_items_to_table(
+        self.set_items_added_at_to_table(
             playlist_item_page
                 .items
                 .clone()
                 .into_iter()
-                .filter_map(|item| item.track)
+                .filter_map(|item| Some((item.track?, item.added_at)))
                 .collect(),
         )
         .await;
     }
 
     async fn set_items_to_table(&mut self, tracks: Vec<PlayableItem>) {
+        self.set_items_added_at_to_table(tracks.into_iter().map(|item| (item, None)).collect())
+            .await;
+    }
+
+    async fn set_items_added_at_to_table(
+        &mut self,
+        items: Vec<(PlayableItem, Option<DateTime<Utc>>)>,
+    ) {
         let mut app = self.app.write().await;
 
-        // Send this event round (don't block here)
-        app.dispatch(IoEvent::CurrentUserSavedTracksContains {
-            track_ids: tracks
-                .iter()
-                .filter_map(|item| item.id())
-                .filter_map(|id| match id {
-                    PlayableId::Track(track_id) => Some(track_id),
-                    PlayableId::Episode(_) => None,
-                })
-                .map(|id| id.into_static())
-                .collect(),
-        });
+        // Send these events round (don't block here)
+        let (track_ids, episode_ids) = partition_playable_ids(items.iter().map(|(item, _)| item));
+        app.dispatch(IoEvent::CurrentUserSavedTracksContains { track_ids });
+        if !episode_ids.is_empty() {
+            app.dispatch(IoEvent::CurrentUserSavedEpisodesContains { episode_ids });
+        }
 
-        app.item_table.items = tracks;
+        app.item_table.set_items_added_at(items);
     }
 
     async fn set_artists_to_table(&mut self, artists: Vec<FullArtist>) {
@@ -591,34 +1356,63 @@ impl Network {
                 .await
         );
 
+        let mut app = self.app.write().await;
+        // The page from the startup cache is replaced, even when nothing is saved anymore
+        if std::mem::take(&mut app.stale.saved_shows) {
+            app.library.saved_shows = ScrollableResultPages::default();
+        }
         // not to show a blank page
         if !saved_shows.items.is_empty() {
-            let mut app = self.app.write().await;
+            for item in &saved_shows.items {
+                app.saved_show_ids_set.insert(item.show.id.clone());
+            }
             app.library.saved_shows.add_pages(saved_shows);
         }
     }
 
     async fn current_user_saved_shows_contains(&mut self, show_ids: Vec<ShowId<'_>>) {
-        let are_followed = handle_error!(
-            self,
-            self.spotify.check_users_saved_shows(show_ids.clone()).await
-        );
+        let now = Instant::now();
+        let show_ids = self
+            .app
+            .read()
+            .await
+            .checked_show_ids
+            .unchecked(show_ids.into_iter().map(ShowId::into_static), now);
+        if show_ids.is_empty() {
+            return;
+        }
+
+        let mut are_followed = Vec::with_capacity(show_ids.len());
+        for chunk in show_ids.chunks(SAVED_SHOWS_CONTAINS_LIMIT) {
+            are_followed.extend(handle_error!(
+                self,
+                self.spotify.check_users_saved_shows(chunk.to_vec()).await
+            ));
+        }
 
         let mut app = self.app.write().await;
-        show_ids
-            .into_iter()
-            .map(ShowId::into_static)
-            .enumerate()
-            .for_each(|(i, show_id)| {
-                if are_followed[i] {
-                    app.saved_show_ids_set.insert(show_id);
-                } else {
-                    app.saved_show_ids_set.remove(&show_id);
-                }
-            })
+        for (show_id, is_followed) in show_ids.iter().zip(are_followed) {
+            if is_followed {
+                app.saved_show_ids_set.insert(show_id.clone());
+            } else {
+                app.saved_show_ids_set.remove(show_id);
+            }
+        }
+        app.checked_show_ids.mark_checked(show_ids, now);
     }
 
     async fn current_user_saved_episodes_contains(&mut self, episode_ids: Vec<EpisodeId<'_>>) {
+        let now = Instant::now();
+        let episode_ids = self
+            .app
+            .read()
+            .await
+            .checked_episode_ids
+            .unchecked(episode_ids.into_iter().map(EpisodeId::into_static), now);
+        if episode_ids.is_empty() {
+            return;
+        }
+
         let ids = join_ids(episode_ids.clone());
         let mut params = std::collections::HashMap::with_capacity(1);
         params.insert("ids", &*ids);
@@ -632,47 +1426,56 @@ impl Network {
         );
 
         let mut app = self.app.write().await;
-        episode_ids
-            .into_iter()
-            .map(EpisodeId::into_static)
-            .enumerate()
-            .for_each(|(i, episode_id)| {
-                if are_followed[i] {
-                    app.liked_episode_ids_set.insert(episode_id);
-                } else {
-                    app.liked_episode_ids_set.remove(&episode_id);
-                }
-            })
+        for (episode_id, is_saved) in episode_ids.iter().zip(are_followed) {
+            if is_saved {
+                app.liked_episode_ids_set.insert(episode_id.clone());
+            } else {
+                app.liked_episode_ids_set.remove(episode_id);
+            }
+        }
+        app.checked_episode_ids.mark_checked(episode_ids, now);
     }
 
     async fn get_show_episodes(&mut self, show: Box<SimplifiedShow>) {
+        // Like searches, episodes are filtered by the user's market
+        let market = self
+            .app
+            .read()
+            .await
+            .get_user_country()
+            .map(Market::Country);
         let episodes = handle_error!(
             self,
             self.spotify
                 .get_shows_episodes_manual(
                     show.id.clone(),
-                    None,
+                    market,
                     Some(self.large_search_limit),
                     Some(0),
                 )
                 .await
         );
 
-        if !episodes.items.is_empty() {
-            let mut app = self.app.write().await;
-            app.library.show_episodes = ScrollableResultPages::default();
-            app.library.show_episodes.add_pages(episodes);
+        // Opened even without any episodes, which the table explains
+        let mut app = self.app.write().await;
+        app.library.show_episodes = ScrollableResultPages::default();
+        app.library.show_episodes.add_pages(episodes);
 
-            app.selected_show_simplified = Some(SelectedShow { show: *show });
+        app.selected_show_simplified = Some(SelectedShow { show: *show });
 
-            app.episode_table_context = EpisodeTableContext::Simplified;
+        app.episode_table_context = EpisodeTableContext::Simplified;
 
-            app.push_navigation_stack(RouteId::PodcastEpisodes, ActiveBlock::EpisodeTable);
-        }
+        app.push_navigation_stack(RouteId::PodcastEpisodes, ActiveBlock::EpisodeTable);
     }
 
     async fn get_show(&mut self, show_id: ShowId<'_>) {
-        let show = handle_error!(self, self.spotify.get_a_show(show_id, None).await);
+        let market = self
+            .app
+            .read()
+            .await
+            .get_user_country()
+            .map(Market::Country);
+        let show = handle_error!(self, self.spotify.get_a_show(show_id, market).await);
 
         let mut app = self.app.write().await;
 
@@ -683,10 +1486,16 @@ impl Network {
     }
 
     async fn get_current_show_episodes(&mut self, show_id: ShowId<'_>, offset: Option<u32>) {
+        let market = self
+            .app
+            .read()
+            .await
+            .get_user_country()
+            .map(Market::Country);
         let episodes = handle_error!(
             self,
             self.spotify
-                .get_shows_episodes_manual(show_id, None, Some(self.large_search_limit), offset)
+                .get_shows_episodes_manual(show_id, market, Some(self.large_search_limit), offset)
                 .await
         );
 
@@ -696,15 +1505,13 @@ impl Network {
         }
     }
 
-    async fn get_search_results(&mut self, search_term: String, country: Option<Country>) {
-        let search_types = [
-            SearchType::Track,
-            SearchType::Artist,
-            SearchType::Album,
-            SearchType::Playlist,
-            SearchType::Show,
-            SearchType::Episode,
-        ];
+    async fn get_search_results(
+        &mut self,
+        search_term: String,
+        types: Option<Vec<SearchType>>,
+        country: Option<Country>,
+    ) {
+        let search_types = types.unwrap_or_else(|| ALL_SEARCH_TYPES.to_vec());
         let search_queries = search_types
             .into_iter()
             .map(|search_type| {
@@ -723,56 +1530,42 @@ impl Network {
         let search_results = handle_error!(self, try_join_all(search_queries).await);
 
         let mut app = self.app.write().await;
-
+        app.search_results.search_term = search_term;
+        app.search_results.clear_selection();
         for search_result in search_results {
-            match search_result {
-                SearchResult::Tracks(track_results) => {
-                    app.search_results.tracks = Some(track_results);
-                }
-                SearchResult::Artists(artist_results) => {
-                    let artist_ids = artist_results
-                        .items
-                        .iter()
-                        .map(|item| item.id.clone())
-                        .collect();
-
-                    // Check if these artists are followed
-                    app.dispatch(IoEvent::UserArtistFollowCheck { artist_ids });
-
-                    app.search_results.artists = Some(artist_results);
-                }
-                SearchResult::Albums(album_results) => {
-                    let album_ids = album_results
-                        .items
-                        .iter()
-                        .filter_map(|album| album.id.clone())
-                        .collect();
-
-                    // Check if these albums are saved
-                    app.dispatch(IoEvent::CurrentUserSavedAlbumsContains { album_ids });
-
-                    app.search_results.albums = Some(album_results);
-                }
-                SearchResult::Playlists(playlist_results) => {
-                    app.search_results.playlists = Some(playlist_results);
-                }
-                SearchResult::Shows(show_results) => {
-                    let show_ids = show_results
-                        .items
-                        .iter()
-                        .map(|show| show.id.clone())
-                        .collect();
+            add_search_results(&mut app, search_result, true);
+        }
+    }
 
-                    // check if these shows are saved
-                    app.dispatch(IoEvent::CurrentUserSavedShowsContains { show_ids });
+    async fn get_more_search_results(
+        &mut self,
+        search_term: String,
+        search_type: SearchType,
+        offset: u32,
+        country: Option<Country>,
+    ) {
+        let search_result = handle_error!(
+            self,
+            self.spotify
+                .search(
+                    &search_term,
+                    search_type,
+                    country.map(Market::Country),
+                    None,
+                    Some(self.small_search_limit),
+                    Some(offset),
+                )
+                .await
+        );
 
-                    app.search_results.shows = Some(show_results);
-                }
-                SearchResult::Episodes(episode_results) => {
-                    app.search_results.episodes = Some(episode_results);
-                }
-            }
+        let mut app = self.app.write().await;
+        // Dropped when a new search was made in the meantime, or the page was already added
+        if app.search_results.search_term != search_term
+            || app.search_results.next_offset(search_type) != Some(offset)
+        {
+            return;
         }
+        add_search_results(&mut app, search_result, false);
     }
 
     async fn get_current_user_saved_tracks(&mut self, offset: Option<u32>) {
@@ -784,12 +1577,14 @@ impl Network {
         );
 
         let mut app = self.app.write().await;
-        app.item_table.items = saved_tracks
-            .items
-            .clone()
-            .into_iter()
-            .map(|item| PlayableItem::Track(item.track))
-            .collect::<Vec<_>>();
+        app.item_table.set_items(
+            saved_tracks
+                .items
+                .clone()
+                .into_iter()
+                .map(|item| PlayableItem::Track(item.track))
+                .collect(),
+        );
 
         saved_tracks.items.iter().for_each(|item| {
             if let Some(track_id) = &item.track.id {
@@ -802,18 +1597,73 @@ impl Network {
         app.item_table.context = Some(ItemTableContext::SavedTracks);
     }
 
+    // Walks every page of saved tracks (or until `limit` tracks have been collected), keeping
+    // the saved order and skipping tracks already seen on an earlier page
+    async fn get_all_current_user_saved_tracks(&mut self, limit: Option<u32>) {
+        let mut tracks = Vec::new();
+        let mut seen_track_ids = HashSet::new();
+        let mut offset = 0;
+
+        loop {
+            let remaining = limit.map(|limit| limit.saturating_sub(tracks.len() as u32));
+            let page_limit = remaining.map_or(SAVED_TRACKS_PAGE_LIMIT, |remaining| {
+                min(remaining, SAVED_TRACKS_PAGE_LIMIT)
+            });
+            if page_limit == 0 {
+                break;
+            }
+
+            let saved_tracks = handle_error!(
+                self,
+                self.spotify
+                    .current_user_saved_tracks_manual(None, Some(page_limit), Some(offset))
+                    .await
+            );
+            offset += saved_tracks.items.len() as u32;
+
+            for item in &saved_tracks.items {
+                let is_new = match &item.track.id {
+                    Some(track_id) => seen_track_ids.insert(track_id.clone()),
+                    None => true,
+                };
+                if is_new {
+                    tracks.push(item.track.clone());
+                }
+            }
+
+            let is_last_page = saved_tracks.next.is_none() || saved_tracks.items.is_empty();
+            self.app
+                .write()
+                .await
+                .library
+                .saved_tracks
+                .add_pages(saved_tracks);
+            if is_last_page {
+                break;
+            }
+        }
+
+        if let Some(limit) = limit {
+            tracks.truncate(limit as usize);
+        }
+
+        let mut app = self.app.write().await;
+        for track_id in tracks.iter().filter_map(|track| track.id.clone()) {
+            app.liked_song_ids_set.insert(track_id);
+        }
+        app.item_table
+            .set_items(tracks.into_iter().map(PlayableItem::Track).collect());
+        app.item_table.context = Some(ItemTableContext::SavedTracks);
+    }
+
     async fn start_context_playback(
         &mut self,
         play_context_id: PlayContextId<'_>,
-        offset: Option<u32>,
+        offset: Option<PlaybackOffset>,
     ) {
         let device_id = self.client_config.device_id.as_deref();
 
-        // Offset::Position is not a straightforward enum variant because it uses a Duration
-        // to represent an index (unclear why rspotify chose to do this) -- the methods
-        // OAuthClient::start_context_playback and OAuthClient::start_uris_playback both use
-        // the duration in Offset::Position's milliseconds as the provided position
-        let offset = offset.map(|o| Offset::Position(Duration::milliseconds(o as i64)));
+        let offset = offset.map(PlaybackOffset::into_offset);
 
         handle_error!(
             self,
@@ -830,15 +1680,11 @@ impl Network {
     async fn start_playables_playback(
         &mut self,
         playable_ids: Vec<PlayableId<'_>>,
-        offset: Option<u32>,
+        offset: Option<PlaybackOffset>,
     ) {
         let device_id = self.client_config.device_id.as_deref();
 
-        // Offset::Position is not a straightforward enum variant because it uses a Duration
-        // to represent an index (unclear why rspotify chose to do this) -- the methods
-        // OAuthClient::start_context_playback and OAuthClient::start_uris_playback both use
-        // the duration in Offset::Position's milliseconds as the provided position
-        let offset = offset.map(|o| Offset::Position(Duration::milliseconds(o as i64)));
+        let offset = offset.map(PlaybackOffset::into_offset);
 
         handle_error!(
             self,
@@ -852,43 +1698,99 @@ impl Network {
         app.dispatch(IoEvent::GetCurrentPlayback);
     }
 
-    async fn seek(&mut self, position_ms: u32) {
+    // Seeks only once the episode has started, a seek sent along with the play request could reach
+    // the device before it has loaded the episode
+    async fn resume_episode_playback(
+        &mut self,
+        playable_ids: Vec<PlayableId<'_>>,
+        offset: PlaybackOffset,
+        position_ms: u32,
+    ) {
+        let device_id = self.client_config.device_id.as_deref();
+
+        handle_error!(
+            self,
+            self.spotify
+                .start_uris_playback(playable_ids, device_id, Some(offset.into_offset()), None)
+                .await
+        );
+        handle_error!(
+            self,
+            self.spotify
+                .seek_track(Duration::milliseconds(position_ms as i64), device_id)
+                .await
+        );
+
+        let mut app = self.app.write().await;
+        app.song_progress_ms = position_ms as u128;
+        app.dispatch(IoEvent::GetCurrentPlayback);
+    }
+
+    // The device the playback controls act on, saving the active one as picked when none was.
+    // None after asking for a device to be picked.
+    async fn control_device_id(&mut self) -> Option<String> {
         if let Some(device_id) = &self.client_config.device_id {
-            handle_error!(
-                self,
-                self.spotify
-                    .seek_track(Duration::milliseconds(position_ms as i64), Some(&device_id))
-                    .await
-            );
+            return Some(device_id.clone());
+        }
 
-            // Wait between seek and status query.
-            // Without it, the Spotify API may return the old progress.
-            tokio::time::sleep(std::time::Duration::from_millis(1000)).await;
-            self.get_current_playback().await;
+        let mut app = self.app.write().await;
+        let active_device_id = app
+            .current_playback_context
+            .as_ref()
+            .and_then(|context| context.device.id.clone());
+        let Some(device_id) = active_device_id else {
+            let message = format!(
+                "No active device — press {} to select one",
+                app.user_config.keys.manage_devices
+            );
+            app.notify(Severity::Warning, message);
+            return None;
+        };
+        // Kept for the session even when the config file can't be written
+        if self.client_config.set_device_id(device_id.clone()).is_err() {
+            self.client_config.device_id = Some(device_id.clone());
         }
+        app.saved_device_id = Some(device_id.clone());
+        Some(device_id)
     }
 
-    async fn next_track(&mut self) {
+    async fn seek(&mut self, position_ms: u32) {
+        let Some(device_id) = self.control_device_id().await else {
+            return;
+        };
         handle_error!(
             self,
             self.spotify
-                .next_track(self.client_config.device_id.as_deref())
+                .seek_track(Duration::milliseconds(position_ms as i64), Some(&device_id))
                 .await
         );
+
+        // Waiting for the API to catch up here would hold up every other event, so the
+        // playback is refreshed on a later tick instead
+        let mut app = self.app.write().await;
+        app.on_seek(position_ms);
+    }
+
+    async fn next_track(&mut self) {
+        let Some(device_id) = self.control_device_id().await else {
+            return;
+        };
+        handle_error!(self, self.spotify.next_track(Some(&device_id)).await);
         self.get_current_playback().await;
     }
 
     async fn previous_track(&mut self) {
-        handle_error!(
-            self,
-            self.spotify
-                .previous_track(self.client_config.device_id.as_deref())
-                .await
-        );
+        let Some(device_id) = self.control_device_id().await else {
+            return;
+        };
+        handle_error!(self, self.spotify.previous_track(Some(&device_id)).await);
         self.get_current_playback().await;
     }
 
     async fn toggle_shuffle(&mut self) {
+        let Some(device_id) = self.control_device_id().await else {
+            return;
+        };
         let shuffle_state = {
             self.app
                 .read()
@@ -901,9 +1803,7 @@ impl Network {
 
         handle_error!(
             self,
-            self.spotify
-                .shuffle(!shuffle_state, self.client_config.device_id.as_deref())
-                .await
+            self.spotify.shuffle(!shuffle_state, Some(&device_id)).await
         );
         // Update the UI eagerly (otherwise the UI will wait until the next 5 second interval
         // due to polling playback context)
@@ -914,21 +1814,22 @@ impl Network {
     }
 
     async fn repeat(&mut self, repeat_state: RepeatState) {
-        let next_repeat_state = match repeat_state {
-            RepeatState::Off => RepeatState::Context,
-            RepeatState::Context => RepeatState::Track,
-            RepeatState::Track => RepeatState::Off,
+        let Some(device_id) = self.control_device_id().await else {
+            return;
         };
         handle_error!(
             self,
-            self.spotify
-                .repeat(next_repeat_state, self.client_config.device_id.as_deref())
-                .await
+            self.spotify.repeat(repeat_state, Some(&device_id)).await
         );
         let mut app = self.app.write().await;
         if let Some(current_playback_context) = &mut app.current_playback_context {
-            current_playback_context.repeat_state = next_repeat_state;
+            current_playback_context.repeat_state = repeat_state;
         };
+        // Which mode comes next isn't obvious from the key alone
+        app.notify(
+            Severity::Info,
+            format!("Repeat: {}", repeat_state_name(repeat_state)),
+        );
     }
 
     async fn pause_playback(&mut self) {
@@ -952,11 +1853,12 @@ impl Network {
     }
 
     async fn change_volume(&mut self, volume_percent: u8) {
+        let Some(device_id) = self.control_device_id().await else {
+            return;
+        };
         handle_error!(
             self,
-            self.spotify
-                .volume(volume_percent, self.client_config.device_id.as_deref())
-                .await
+            self.spotify.volume(volume_percent, Some(&device_id)).await
         );
         let mut app = self.app.write().await;
         if let Some(current_playback_context) = &mut app.current_playback_context {
@@ -972,7 +1874,7 @@ impl Network {
     ) {
         let market = country.map(Market::Country);
 
-        let (albums, top_tracks, related_artists, artist_name) = handle_error!(
+        let (albums, top_tracks, related_artists, full_artist) = handle_error!(
             self,
             try_join!(
                 self.spotify.artist_albums_manual(
@@ -984,20 +1886,25 @@ impl Network {
                 ),
                 self.spotify.artist_top_tracks(artist_id.clone(), market),
                 self.spotify.artist_related_artists(artist_id.clone()),
-                async {
-                    if input_artist_name.is_empty() {
-                        self.spotify
-                            .artist(artist_id)
-                            .await
-                            .map(|full_artist| full_artist.name)
-                    } else {
-                        Ok(input_artist_name)
-                    }
-                }
+                self.spotify.artist(artist_id.clone()),
             )
         );
+        let artist_name = full_artist.name;
 
         let mut app = self.app.write().await;
+        // A stale search index can list an artist under a name that isn't theirs, which is worth
+        // knowing before taking what's shown for the artist picked
+        if !input_artist_name.is_empty()
+            && artist_name.to_lowercase() != input_artist_name.to_lowercase()
+        {
+            app.notify(
+                Severity::Warning,
+                format!(
+                    "Spotify found {} instead of {}",
+                    artist_name, input_artist_name
+                ),
+            );
+        }
 
         app.dispatch(IoEvent::CurrentUserSavedAlbumsContains {
             album_ids: albums
@@ -1006,10 +1913,19 @@ impl Network {
                 .filter_map(|item| item.id.clone())
                 .collect(),
         });
+        // The artist shown along with the related ones, which are marked the same way
+        app.dispatch(IoEvent::UserArtistFollowCheck {
+            artist_ids: std::iter::once(artist_id.clone().into_static())
+                .chain(related_artists.iter().map(|artist| artist.id.clone()))
+                .collect(),
+        });
 
-        app.artist = Some(Artist {
+        let mut artist = Artist {
+            artist_id: artist_id.into_static(),
             artist_name,
-            albums,
+            albums: ScrollableResultPages::default(),
+            album_type: None,
+            pending_albums_offset: None,
             related_artists,
             top_tracks,
             selected_album_index: 0,
@@ -1017,7 +1933,47 @@ impl Network {
             selected_top_track_index: 0,
             artist_hovered_block: ArtistBlock::TopTracks,
             artist_selected_block: ArtistBlock::Empty,
+        };
+        artist.add_albums_page(albums);
+        app.artist = Some(artist);
+    }
+
+    async fn get_artist_albums(
+        &mut self,
+        artist_id: ArtistId<'_>,
+        offset: u32,
+        album_type: Option<AlbumType>,
+        country: Option<Country>,
+    ) {
+        let albums = handle_error!(
+            self,
+            self.spotify
+                .artist_albums_manual(
+                    artist_id.clone(),
+                    album_type,
+                    country.map(Market::Country),
+                    Some(self.large_search_limit),
+                    Some(offset),
+                )
+                .await
+        );
+
+        let mut app = self.app.write().await;
+        app.dispatch(IoEvent::CurrentUserSavedAlbumsContains {
+            album_ids: albums
+                .items
+                .iter()
+                .filter_map(|item| item.id.clone())
+                .collect(),
         });
+        // The artist may have been left, or their albums filtered to another group, since
+        if let Some(artist) = app
+            .artist
+            .as_mut()
+            .filter(|artist| artist.artist_id == artist_id && artist.album_type == album_type)
+        {
+            artist.add_albums_page(albums);
+        }
     }
 
     async fn get_album_tracks(&mut self, album: Box<SimplifiedAlbum>) {
@@ -1029,7 +1985,12 @@ impl Network {
         let tracks = handle_error!(
             self,
             self.spotify
-                .album_track_manual(album_id, None, Some(self.large_search_limit), Some(0),)
+                .album_track_manual(
+                    album_id.clone(),
+                    None,
+                    Some(self.large_search_limit),
+                    Some(0),
+                )
                 .await
         );
 
@@ -1049,6 +2010,10 @@ impl Network {
         app.album_table_context = AlbumTableContext::Simplified;
         app.push_navigation_stack(RouteId::AlbumTracks, ActiveBlock::AlbumTracks);
         app.dispatch(IoEvent::CurrentUserSavedTracksContains { track_ids });
+        // For the liked icon in the title
+        app.dispatch(IoEvent::CurrentUserSavedAlbumsContains {
+            album_ids: vec![album_id],
+        });
     }
 
     async fn get_recommendations_for_seed(
@@ -1105,7 +2070,7 @@ impl Network {
 
             app.dispatch(IoEvent::StartPlayablesPlayback {
                 playable_ids: track_ids.into_iter().map(PlayableId::Track).collect(),
-                offset: Some(0),
+                offset: Some(PlaybackOffset::Index(0)),
             });
         }
     }
@@ -1123,6 +2088,36 @@ impl Network {
         self.spotify.tracks(track_ids, None).await.ok()
     }
 
+    async fn get_recommendations_for_liked_songs(&mut self, country: Option<Country>) {
+        let saved_tracks = handle_error!(
+            self,
+            self.spotify
+                .current_user_saved_tracks_manual(None, Some(SAVED_TRACKS_PAGE_LIMIT), None)
+                .await
+        );
+        let track_ids = saved_tracks
+            .items
+            .into_iter()
+            .filter_map(|item| item.track.id)
+            .collect::<Vec<_>>();
+
+        if track_ids.is_empty() {
+            let mut app = self.app.write().await;
+            app.notify(
+                Severity::Info,
+                "There are no liked songs to start a radio from",
+            );
+            return;
+        }
+        self.get_recommendations_for_seed(
+            None,
+            Some(radio_seeds(&track_ids)),
+            Box::new(None),
+            country,
+        )
+        .await;
+    }
+
     async fn get_recommendations_for_track_id(
         &mut self,
         track_id: TrackId<'_>,
@@ -1138,67 +2133,104 @@ impl Network {
         .await;
     }
 
-    async fn toggle_save_episode(&mut self, _: EpisodeId<'_>) {
-        handle_error!(self, Err("cannot save episodes currently"));
-        // let saved = handle_error!(
-        //     self,
-        //     self.spotify
-        //         .current_user_saved_episodes_contains([episode_id.clone()])
-        //         .await
-        // );
-        // match saved.first().copied().unwrap_or_default() {
-        //     true => {
-        //         handle_error!(
-        //             self,
-        //             self.spotify
-        //                 .current_user_saved_episodes_delete([episode_id.clone()])
-        //                 .await
-        //         );
-        //         let mut app = self.app.write().await;
-        //         app.liked_song_ids_set.remove(&episode_id.into_static());
-        //     }
-        //     false => {
-        //         handle_error!(
-        //             self,
-        //             self.spotify
-        //                 .current_user_saved_episodes_add([episode_id.clone()])
-        //                 .await
-        //         );
-        //         // TODO: This should ideally use the same logic as `self.current_user_saved_episodes_contains`
-        //         let mut app = self.app.write().await;
-        //         app.liked_song_ids_set.insert(episode_id.into_static());
-        //     }
-        // }
+    async fn toggle_save_episode(&mut self, episode_id: EpisodeId<'_>) {
+        // Not worth the error screen, as it isn't anything that went wrong
+        let mut app = self.app.write().await;
+        app.revert_episode_save(episode_id);
+        app.notify(Severity::Warning, "Episodes can't be saved yet");
     }
 
+    // The liked state may have been changed ahead of this, so it is set from what Spotify says
+    // either way
     async fn toggle_save_track(&mut self, track_id: TrackId<'_>) {
-        let saved = handle_error!(
-            self,
+        let track_id = track_id.into_static();
+        let saved = match self
+            .spotify
+            .current_user_saved_tracks_contains([track_id.clone()])
+            .await
+        {
+            Ok(saved) => saved.first().copied().unwrap_or_default(),
+            Err(e) => {
+                self.app.write().await.revert_track_save(track_id);
+                self.handle_error(anyhow!(e)).await;
+                return;
+            }
+        };
+
+        let result = if saved {
             self.spotify
-                .current_user_saved_tracks_contains([track_id.clone()])
+                .current_user_saved_tracks_delete([track_id.clone()])
                 .await
-        );
-        match saved.first().copied().unwrap_or_default() {
-            true => {
-                handle_error!(
+        } else {
+            self.spotify
+                .current_user_saved_tracks_add([track_id.clone()])
+                .await
+        };
+        if let Err(e) = result {
+            self.app.write().await.revert_track_save(track_id);
+            self.handle_error(anyhow!(e)).await;
+            return;
+        }
+        let mut app = self.app.write().await;
+        app.confirm_track_save(track_id.clone(), !saved);
+        app.checked_track_ids
+            .mark_checked([track_id], Instant::now());
+    }
+
+    async fn get_user_top_items(&mut self, kind: TopItemKind, time_range: TimeRange, offset: u32) {
+        let limit = Some(self.large_search_limit);
+        match kind {
+            TopItemKind::Tracks => {
+                let tracks = handle_error!(
                     self,
                     self.spotify
-                        .current_user_saved_tracks_delete([track_id.clone()])
+                        .current_user_top_tracks_manual(Some(time_range), limit, Some(offset))
                         .await
                 );
                 let mut app = self.app.write().await;
-                app.liked_song_ids_set.remove(&track_id.into_static());
-            }
-            false => {
-                handle_error!(
-                    self,
-                    self.spotify
-                        .current_user_saved_tracks_add([track_id.clone()])
-                        .await
+                // Switched to another range or to the artists while this was loading
+                if (app.top_items.kind, app.top_items.time_range) != (kind, time_range) {
+                    return;
+                }
+                app.item_table.set_items(
+                    tracks
+                        .items
+                        .iter()
+                        .cloned()
+                        .map(PlayableItem::Track)
+                        .collect(),
+                );
+                app.item_table.selected_index = 0;
+                app.item_table.context = Some(ItemTableContext::TopTracks);
+                app.dispatch(IoEvent::CurrentUserSavedTracksContains {
+                    track_ids: tracks
+                        .items
+                        .iter()
+                        .filter_map(|track| track.id.clone())
+                        .collect(),
+                });
+                app.top_items.tracks = Some(tracks);
+            }
+            TopItemKind::Artists => {
+                let artists = handle_error!(
+                    self,
+                    self.spotify
+                        .current_user_top_artists_manual(Some(time_range), limit, Some(offset))
+                        .await
                 );
-                // TODO: This should ideally use the same logic as `self.current_user_saved_tracks_contains`
                 let mut app = self.app.write().await;
-                app.liked_song_ids_set.insert(track_id.into_static());
+                if (app.top_items.kind, app.top_items.time_range) != (kind, time_range) {
+                    return;
+                }
+                app.top_items.selected_artist_index = 0;
+                app.dispatch(IoEvent::UserArtistFollowCheck {
+                    artist_ids: artists
+                        .items
+                        .iter()
+                        .map(|artist| artist.id.clone())
+                        .collect(),
+                });
+                app.top_items.artists = Some(artists);
             }
         }
     }
@@ -1212,6 +2244,9 @@ impl Network {
                 .await
         );
         let mut app = self.app.write().await;
+        for artist in &saved_artists.items {
+            app.followed_artist_ids_set.insert(artist.id.clone());
+        }
         app.artists = saved_artists.items.to_owned();
         app.library.saved_artists.add_pages(saved_artists);
     }
@@ -1245,32 +2280,51 @@ impl Network {
                 .current_user_saved_albums_manual(None, Some(self.large_search_limit), offset)
                 .await
         );
+        let mut app = self.app.write().await;
+        if std::mem::take(&mut app.stale.saved_albums) {
+            app.library.saved_albums = ScrollableResultPages::default();
+        }
         // not to show a blank page
         if !saved_albums.items.is_empty() {
-            let mut app = self.app.write().await;
+            for item in &saved_albums.items {
+                app.saved_album_ids_set.insert(item.album.id.clone());
+            }
             app.library.saved_albums.add_pages(saved_albums);
+            app.clamp_album_list_index();
         }
     }
 
     async fn current_user_saved_albums_contains(&mut self, album_ids: Vec<AlbumId<'_>>) {
-        let are_followed = handle_error!(
-            self,
-            self.spotify
-                .current_user_saved_albums_contains(album_ids.clone())
-                .await
-        );
+        let now = Instant::now();
+        let album_ids = self
+            .app
+            .read()
+            .await
+            .checked_album_ids
+            .unchecked(album_ids.into_iter().map(AlbumId::into_static), now);
+        if album_ids.is_empty() {
+            return;
+        }
+
+        let mut are_followed = Vec::with_capacity(album_ids.len());
+        for chunk in album_ids.chunks(SAVED_ALBUMS_CONTAINS_LIMIT) {
+            are_followed.extend(handle_error!(
+                self,
+                self.spotify
+                    .current_user_saved_albums_contains(chunk.to_vec())
+                    .await
+            ));
+        }
+
         let mut app = self.app.write().await;
-        album_ids
-            .into_iter()
-            .map(AlbumId::into_static)
-            .enumerate()
-            .for_each(|(i, album_id)| {
-                if are_followed[i] {
-                    app.saved_album_ids_set.insert(album_id);
-                } else {
-                    app.saved_album_ids_set.remove(&album_id);
-                }
-            });
+        for (album_id, is_followed) in album_ids.iter().zip(are_followed) {
+            if is_followed {
+                app.saved_album_ids_set.insert(album_id.clone());
+            } else {
+                app.saved_album_ids_set.remove(album_id);
+            }
+        }
+        app.checked_album_ids.mark_checked(album_ids, now);
     }
 
     async fn current_user_saved_album_delete(&mut self, album_id: AlbumId<'_>) {
@@ -1294,6 +2348,7 @@ impl Network {
         );
         let mut app = self.app.write().await;
         app.saved_album_ids_set.insert(album_id.into_static());
+        app.notify(Severity::Info, "Saved the album to your library");
     }
 
     async fn current_user_saved_shows_delete(&mut self, show_id: ShowId<'_>) {
@@ -1308,18 +2363,161 @@ impl Network {
         app.saved_show_ids_set.remove(&show_id.into_static());
     }
 
+    async fn remove_saved_shows(&mut self, show_ids: Vec<ShowId<'_>>) {
+        // Those removed before a chunk fails stay removed
+        for chunk in show_ids.chunks(SAVED_SHOWS_REMOVE_LIMIT) {
+            handle_error!(
+                self,
+                self.spotify
+                    .remove_users_saved_shows(chunk.to_vec(), None)
+                    .await
+            );
+            let mut app = self.app.write().await;
+            for show_id in chunk {
+                app.saved_show_ids_set
+                    .remove(&show_id.clone().into_static());
+            }
+        }
+
+        {
+            let mut app = self.app.write().await;
+            app.library.saved_shows = ScrollableResultPages::default();
+            app.shows_list_index = 0;
+        }
+        self.get_current_user_saved_shows(None).await;
+        self.app.write().await.notify(
+            Severity::Info,
+            format!("Unfollowed {} podcasts", show_ids.len()),
+        );
+    }
+
+    async fn find_finished_shows(&mut self) {
+        let result = self.finished_shows().await;
+
+        let mut app = self.app.write().await;
+        // Cancelled while looking
+        if !std::mem::take(&mut app.is_finding_finished_shows) {
+            return;
+        }
+        match result {
+            Ok(shows) if shows.is_empty() => {
+                app.notify(Severity::Info, "None of your podcasts are finished")
+            }
+            Ok(shows) => {
+                app.finished_shows = Some(FinishedShows::new(shows));
+                app.push_navigation_stack(RouteId::FinishedShows, ActiveBlock::FinishedShows);
+            }
+            Err(e) => {
+                drop(app);
+                self.handle_error(e).await;
+            }
+        }
+    }
+
+    // The saved podcasts whose every episode has been played, in the order they were saved in.
+    // Stops early once cancelled, which the caller checks for.
+    async fn finished_shows(&self) -> anyhow::Result<Vec<SimplifiedShow>> {
+        let mut shows = Vec::new();
+        loop {
+            let page = self
+                .spotify
+                .get_saved_show_manual(Some(SHOWS_PAGE_LIMIT), Some(shows.len() as u32))
+                .await?;
+            let has_next = page.next.is_some() && !page.items.is_empty();
+            shows.extend(page.items.into_iter().map(|saved| saved.show));
+            if !has_next {
+                break;
+            }
+        }
+
+        let market = self
+            .app
+            .read()
+            .await
+            .get_user_country()
+            .map(Market::Country);
+        let total = shows.len();
+        let mut results = stream::iter(shows.into_iter().enumerate())
+            .map(|(index, show)| async move {
+                let is_finished = self.is_show_finished(show.id.as_ref(), market).await;
+                is_finished.map(|is_finished| (index, show, is_finished))
+            })
+            .buffer_unordered(FINISHED_SHOWS_CONCURRENCY);
+
+        let mut finished = Vec::new();
+        let mut looked_through = 0;
+        while let Some(result) = results.next().await {
+            let (index, show, is_finished) = result?;
+            if is_finished {
+                finished.push((index, show));
+            }
+            looked_through += 1;
+
+            let mut app = self.app.write().await;
+            if !app.is_finding_finished_shows {
+                return Ok(vec![]);
+            }
+            if looked_through % FINISHED_SHOWS_PROGRESS_EVERY == 0 && looked_through < total {
+                app.notify(
+                    Severity::Info,
+                    format!(
+                        "Looked through {} of {} podcasts for finished ones",
+                        looked_through, total
+                    ),
+                );
+            }
+        }
+
+        finished.sort_by_key(|(index, _)| *index);
+        Ok(finished.into_iter().map(|(_, show)| show).collect())
+    }
+
+    // Pages through the episodes of the podcast until one of them hasn't been played
+    async fn is_show_finished(
+        &self,
+        show_id: ShowId<'_>,
+        market: Option<Market>,
+    ) -> anyhow::Result<bool> {
+        let mut offset = 0;
+        loop {
+            let episodes = self
+                .spotify
+                .get_shows_episodes_manual(
+                    show_id.as_ref(),
+                    market,
+                    Some(SHOWS_PAGE_LIMIT),
+                    Some(offset),
+                )
+                .await?;
+            if !is_fully_played(&episodes) {
+                return Ok(false);
+            }
+            if episodes.next.is_none() {
+                return Ok(true);
+            }
+            offset += episodes.items.len() as u32;
+        }
+    }
+
     async fn current_user_saved_shows_add(&mut self, show_id: ShowId<'_>) {
         handle_error!(self, self.spotify.save_shows(vec![show_id.clone()]).await);
         self.get_current_user_saved_shows(None).await;
         let mut app = self.app.write().await;
         app.saved_show_ids_set.insert(show_id.into_static());
+        app.notify(Severity::Info, "Following the podcast");
     }
 
     async fn user_unfollow_artists(&mut self, artist_ids: Vec<ArtistId<'_>>) {
-        handle_error!(
-            self,
-            self.spotify.user_unfollow_artists(artist_ids.clone()).await
-        );
+        if let Err(e) = self.spotify.user_unfollow_artists(artist_ids.clone()).await {
+            // The artist shown is marked as unfollowed before the request
+            let mut app = self.app.write().await;
+            for artist_id in artist_ids {
+                app.followed_artist_ids_set.insert(artist_id.into_static());
+            }
+            drop(app);
+            self.handle_error(anyhow!(e)).await;
+            return;
+        }
         self.get_followed_artists(None).await;
         let mut app = self.app.write().await;
         artist_ids
@@ -1331,10 +2529,16 @@ impl Network {
     }
 
     async fn user_follow_artists(&mut self, artist_ids: Vec<ArtistId<'_>>) {
-        handle_error!(
-            self,
-            self.spotify.user_follow_artists(artist_ids.clone()).await
-        );
+        if let Err(e) = self.spotify.user_follow_artists(artist_ids.clone()).await {
+            // The artist shown is marked as followed before the request
+            let mut app = self.app.write().await;
+            for artist_id in artist_ids {
+                app.followed_artist_ids_set.remove(&artist_id.into_static());
+            }
+            drop(app);
+            self.handle_error(anyhow!(e)).await;
+            return;
+        }
         self.get_followed_artists(None).await;
         let mut app = self.app.write().await;
         artist_ids
@@ -1343,6 +2547,7 @@ impl Network {
             .for_each(|artist_id| {
                 app.followed_artist_ids_set.insert(artist_id);
             });
+        app.notify(Severity::Info, "Following the artist");
     }
 
     async fn user_follow_playlist(&mut self, playlist_id: PlaylistId<'_>, is_public: Option<bool>) {
@@ -1351,6 +2556,8 @@ impl Network {
             self.spotify.playlist_follow(playlist_id, is_public).await
         );
         self.get_current_user_playlists().await;
+        let mut app = self.app.write().await;
+        app.notify(Severity::Info, "Following the playlist");
     }
 
     async fn user_unfollow_playlist(&mut self, playlist_id: PlaylistId<'_>) {
@@ -1358,6 +2565,56 @@ impl Network {
         self.get_current_user_playlists().await;
     }
 
+    async fn change_playlist_details(
+        &mut self,
+        playlist_id: PlaylistId<'_>,
+        name: Option<String>,
+        description: Option<String>,
+    ) {
+        let result = self
+            .spotify
+            .playlist_change_detail(
+                playlist_id.clone(),
+                name.as_deref(),
+                None,
+                description.as_deref(),
+                None,
+            )
+            .await;
+
+        {
+            let mut app = self.app.write().await;
+            match (result, name) {
+                (Ok(_), Some(_)) => app.confirm_playlist_rename(playlist_id.clone()),
+                (Ok(_), None) => app.notify(Severity::Info, "Description updated"),
+                (Err(e), Some(_)) => return app.revert_playlist_rename(playlist_id, anyhow!(e)),
+                (Err(e), None) => return app.handle_error(anyhow!(e)),
+            }
+        }
+
+        self.get_current_user_playlists().await;
+        // The edited playlist stays selected, rather than the first one
+        let mut app = self.app.write().await;
+        let index = app.playlists.as_ref().and_then(|playlists| {
+            playlists
+                .items
+                .iter()
+                .position(|playlist| playlist.id == playlist_id)
+        });
+        if index.is_some() {
+            app.selected_playlist_index = index;
+        }
+    }
+
+    async fn get_playlist_description(&mut self, playlist_id: PlaylistId<'_>) {
+        let playlist = handle_error!(self, self.spotify.playlist(playlist_id, None, None).await);
+        let mut app = self.app.write().await;
+        app.start_playlist_description_input(
+            playlist.id,
+            unescape_html(&playlist.description.unwrap_or_default()),
+        );
+    }
+
     async fn made_for_you_search_and_add(
         &mut self,
         search_string: String,
@@ -1384,7 +2641,7 @@ impl Network {
             unreachable!();
         };
 
-        let mut filtered_playlists = search_playlists
+        let filtered_playlists = search_playlists
             .items
             .iter()
             .filter(|playlist| playlist.owner.id == *spotify_id && playlist.name == search_string)
@@ -1392,30 +2649,112 @@ impl Network {
             .collect::<Vec<SimplifiedPlaylist>>();
 
         let mut app = self.app.write().await;
-        if !app.library.made_for_you_playlists.pages.is_empty() {
-            app.library
-                .made_for_you_playlists
-                .get_mut_results(None)
-                .unwrap()
-                .items
-                .append(&mut filtered_playlists);
-        } else {
-            search_playlists.items = filtered_playlists;
-            app.library
-                .made_for_you_playlists
-                .add_pages(search_playlists);
+        // Spotify only makes some of them for accounts that have listened enough
+        if filtered_playlists.is_empty() {
+            app.notify(
+                Severity::Info,
+                format!("There is no '{}' playlist for your account", search_string),
+            );
+        }
+        match app.library.made_for_you_playlists.get_mut_results(None) {
+            // Searching again, e.g. after visiting the route twice before the first results came
+            // back, finds the ones already listed
+            Some(page) => {
+                for playlist in filtered_playlists {
+                    if !page.items.iter().any(|item| item.id == playlist.id) {
+                        page.items.push(playlist);
+                    }
+                }
+            }
+            None => {
+                search_playlists.items = filtered_playlists;
+                app.library
+                    .made_for_you_playlists
+                    .add_pages(search_playlists);
+            }
+        }
+    }
+
+    async fn get_audio_features(&mut self, track_ids: Vec<TrackId<'_>>) {
+        for chunk in audio_features_chunks(&track_ids) {
+            // The sort may have been cancelled while the previous chunk was being fetched
+            if self.app.read().await.item_table.pending_sort.is_none() {
+                return;
+            }
+
+            let features = match self.spotify.tracks_features(chunk.to_vec()).await {
+                Ok(features) => features,
+                Err(e) => {
+                    // Otherwise pressing the key again cancels the sort rather than retrying it
+                    self.app.write().await.item_table.pending_sort = None;
+                    self.handle_error(anyhow!(e)).await;
+                    return;
+                }
+            };
+
+            let mut app = self.app.write().await;
+            for features in features.unwrap_or_default() {
+                app.audio_features.insert(features.id.clone(), features);
+            }
+        }
+
+        let mut app = self.app.write().await;
+        if let Some(feature) = app.item_table.pending_sort.take() {
+            app.apply_item_table_sort(feature);
+        }
+    }
+
+    async fn get_track_features(&mut self, track_id: TrackId<'_>) {
+        let track_id = track_id.into_static();
+        let result = self.spotify.track_features(track_id.clone()).await;
+
+        let mut app = self.app.write().await;
+        app.loading_track_features.remove(&track_id);
+        match result {
+            Ok(features) => {
+                app.audio_features.insert(track_id, features);
+            }
+            // Asked for again the next time they are shown, rather than on every tick
+            Err(e) => app.notify(
+                Severity::Warning,
+                format!("Couldn't load the audio features: {}", e),
+            ),
         }
     }
 
     async fn get_track_analysis(&mut self, track_id: TrackId<'_>) {
-        let result = handle_error!(self, self.spotify.track_analysis(track_id).await);
+        let track_id = track_id.into_static();
+        let result = self.spotify.track_analysis(track_id.clone()).await;
+
         let mut app = self.app.write().await;
-        app.audio_analysis = Some(result);
+        // A different track may have started playing in the meantime
+        if !app
+            .audio_analysis
+            .as_ref()
+            .is_some_and(|(analysed_id, _)| *analysed_id == track_id)
+        {
+            return;
+        }
+        match result {
+            Ok(analysis) => {
+                app.audio_analysis = Some((track_id, TrackAnalysis::Loaded(Box::new(analysis))));
+            }
+            Err(e) => {
+                // Not retried for this track. A notification rather than the error screen, which
+                // would otherwise come up again on every track change while the view is open.
+                app.audio_analysis = Some((track_id, TrackAnalysis::Failed));
+                app.notify(
+                    Severity::Warning,
+                    format!("Couldn't load the audio analysis: {}", e),
+                );
+            }
+        }
     }
 
     async fn get_current_user_playlists(&mut self) {
-        let playlists = handle_error!(
+        let playlists = handle_startup_error!(
             self,
+            StartupFetch::Playlists,
             self.spotify
                 .current_user_playlists_manual(Some(self.large_search_limit), None)
                 .await
@@ -1423,18 +2762,34 @@ impl Network {
 
         let mut app = self.app.write().await;
         app.playlists = Some(playlists);
-        // Select the first playlist
-        app.selected_playlist_index = Some(0);
+        app.stale.playlists = false;
+        app.on_startup_fetch_loaded(StartupFetch::Playlists);
+        // Select the first playlist that is listed
+        app.selected_playlist_index = app.visible_playlist_indices().first().copied().or(Some(0));
     }
 
     async fn get_recently_played(&mut self) {
-        let result = handle_error!(
+        let mut result = handle_error!(
             self,
             self.spotify
                 .current_user_recently_played(Some(self.large_search_limit), None)
                 .await
         );
 
+        if self
+            .app
+            .read()
+            .await
+            .user_config
+            .behavior
+            .dedup_recently_played
+        {
+            // Tracks without an id, which are local files, are never the same as the one before
+            result.items.dedup_by(|item, previous| {
+                item.track.id.is_some() && item.track.id == previous.track.id
+            });
+        }
+
         let track_ids = result
             .items
             .iter()
@@ -1453,6 +2808,9 @@ impl Network {
 
         let mut app = self.app.write().await;
 
+        app.dispatch(IoEvent::CurrentUserSavedAlbumsContains {
+            album_ids: vec![album.id.clone()],
+        });
         app.selected_album_full = Some(SelectedFullAlbum {
             album,
             selected_index: 0,
@@ -1482,6 +2840,9 @@ impl Network {
 
         let mut app = self.app.write().await;
 
+        app.dispatch(IoEvent::CurrentUserSavedAlbumsContains {
+            album_ids: vec![selected_album.album.id.clone()],
+        });
         app.selected_album_full = Some(selected_album.clone());
         app.saved_album_tracks_index = selected_album.selected_index;
         app.album_table_context = AlbumTableContext::Full;
@@ -1495,18 +2856,2258 @@ impl Network {
         );
         self.get_current_playback().await;
 
-        handle_error!(self, self.client_config.set_device_id(device_id));
+        handle_error!(self, self.client_config.set_device_id(device_id.clone()));
         let mut app = self.app.write().await;
+        app.saved_device_id = Some(device_id);
         app.pop_navigation_stack();
     }
 
+    async fn load_library_cache(&mut self) {
+        let Some(path) = self.app.read().await.library_cache_path.clone() else {
+            return;
+        };
+        // Missing and unreadable caches are the same as none, it fills up as the library loads
+        if let Some(cache) = library_cache::load(&path) {
+            self.app.write().await.load_library_cache(cache);
+        }
+    }
+
+    async fn save_library_cache(&mut self) {
+        let (path, cache) = {
+            let app = self.app.read().await;
+            let Some(path) = app.library_cache_path.clone() else {
+                return;
+            };
+            (path, app.library_cache())
+        };
+        let result = library_cache::save(&path, &cache);
+
+        let mut app = self.app.write().await;
+        if let Err(e) = result {
+            app.notify(
+                Severity::Warning,
+                format!("Could not save the library cache: {}", e),
+            );
+        }
+        app.library_cache_saves += 1;
+    }
+
     async fn refresh_authentication(&mut self) {
-        if let Some(new_token) = crate::get_token_auto(&mut self.spotify).await {
-            let mut app = self.app.write().await;
-            app.spotify_token_expiry = new_token.expires_at.unwrap_or(Utc::now());
-        } else {
-            println!("\nFailed to refresh authentication token");
-            // TODO panic!
+        if let Some(lock_path) = &self.shared_token_lock {
+            // Only once the other instance has gone does this one refresh the token itself
+            if instance_lock::holder(lock_path).is_some() {
+                let token = self
+                    .reload_shared_token()
+                    .await
+                    .filter(|token| !token.is_expired());
+                let mut app = self.app.write().await;
+                app.spotify_token_expiry = match token {
+                    Some(token) => token.expires_at.unwrap_or(Utc::now()),
+                    // Not refreshed by the other instance yet
+                    None => Utc::now() + SHARED_TOKEN_RETRY,
+                };
+                app.is_refreshing_token = false;
+                return;
+            }
+        }
+
+        let token = match self.current_token().await {
+            // Already refreshed along with a request
+            Some(token) if !token.is_expired() => Some(token),
+            _ => match self.spotify.refresh_token().await {
+                Ok(()) => self.current_token().await,
+                // Offline, e.g. just after resuming, so the token is kept for another try
+                Err(e) if is_transient(&e) => {
+                    let mut app = self.app.write().await;
+                    if let Some(message) = transient_error_message(&e) {
+                        app.notify(Severity::Warning, message);
+                    }
+                    app.spotify_token_expiry = Utc::now() + TOKEN_REFRESH_RETRY;
+                    app.is_refreshing_token = false;
+                    return;
+                }
+                Err(e) => match refresh_error_message(e).await {
+                    Some(message) => {
+                        // Left refreshing, as signing in again is what gets past it
+                        self.app.write().await.handle_error(anyhow!(
+                            "Couldn't refresh the Spotify session ({}). Quit and start spt to \
+                             sign in.",
+                            message
+                        ));
+                        return;
+                    }
+                    None => None,
+                },
+            },
+        };
+        // The refresh token was revoked or has expired, which only signing in again gets past
+        let token = match token {
+            Some(token) => Some(token),
+            None => self.sign_in_again().await,
+        };
+
+        let mut app = self.app.write().await;
+        match token {
+            Some(token) => {
+                app.spotify_token_expiry = token.expires_at.unwrap_or(Utc::now());
+                app.is_refreshing_token = false;
+            }
+            // Left refreshing, since trying again would only prompt again
+            None => app.handle_error(anyhow!(
+                "Couldn't sign in to Spotify again. Quit and start spt to sign in."
+            )),
+        }
+    }
+
+    // The token the other instance has written to the cache, when it isn't the one in use already
+    async fn reload_shared_token(&mut self) -> Option<Token> {
+        let token = Token::from_cache(&self.spotify.config.cache_path).ok()?;
+        let mut current = self.spotify.token.lock().await.ok()?;
+        if current
+            .as_ref()
+            .is_some_and(|current| current.access_token == token.access_token)
+        {
+            return None;
+        }
+        *current = Some(token.clone());
+        Some(token)
+    }
+
+    async fn current_token(&self) -> Option<Token> {
+        self.spotify.token.lock().await.ok()?.clone()
+    }
+
+    async fn sign_in_again(&mut self) -> Option<Token> {
+        // Otherwise the token that no longer works is all there is to get
+        *self.spotify.token.lock().await.ok()? = None;
+        let Some(terminal_tx) = &self.terminal_tx else {
+            return crate::get_token_auto(&mut self.spotify).await;
+        };
+
+        let (released_tx, released_rx) = oneshot::channel();
+        let (restore_tx, restore_rx) = oneshot::channel();
+        terminal_tx
+            .send(TerminalHandover {
+                released: released_tx,
+                restore: restore_rx,
+            })
+            .ok()?;
+        released_rx.await.ok()?;
+        println!("\nYour Spotify session has expired, sign in again to carry on.");
+        let token = crate::get_token_auto(&mut self.spotify).await;
+        let _ = restore_tx.send(());
+        token
+    }
+}
+
+// The ids of the tracks and of the episodes among the items, each checked with its own endpoint
+fn partition_playable_ids<'a>(
+    items: impl IntoIterator<Item = &'a PlayableItem>,
+) -> (Vec<TrackId<'static>>, Vec<EpisodeId<'static>>) {
+    let mut track_ids = vec![];
+    let mut episode_ids = vec![];
+    for id in items.into_iter().filter_map(|item| item.id()) {
+        match id {
+            PlayableId::Track(track_id) => track_ids.push(track_id.into_static()),
+            PlayableId::Episode(episode_id) => episode_ids.push(episode_id.into_static()),
+        }
+    }
+    (track_ids, episode_ids)
+}
+
+// Spotify escapes descriptions as HTML, which the input shows and sends back as plain text
+fn unescape_html(text: &str) -> String {
+    text.replace("&quot;", "\"")
+        .replace("&#x27;", "'")
+        .replace("&#x2F;", "/")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+// Adds a page of search results, replacing those of an earlier search when `is_new_search`.
+// Whether the results are saved or followed is checked page by page.
+fn add_search_results(app: &mut App, search_result: SearchResult, is_new_search: bool) {
+    // The first page of a new search starts with nothing selected, later pages at their top
+    let selected_index = if is_new_search { None } else { Some(0) };
+    let results = &mut app.search_results;
+    match search_result {
+        SearchResult::Tracks(track_results) => {
+            if is_new_search {
+                results.tracks = ScrollableResultPages::default();
+            }
+            results.tracks.add_pages(track_results);
+            results.selected_tracks_index = selected_index;
+        }
+        SearchResult::Artists(mut artist_results) => {
+            rank_artists(&results.search_term, &mut artist_results.items);
+            let artist_ids = artist_results
+                .items
+                .iter()
+                .map(|item| item.id.clone())
+                .collect();
+
+            if is_new_search {
+                results.artists = ScrollableResultPages::default();
+            }
+            results.artists.add_pages(artist_results);
+            results.selected_artists_index = selected_index;
+
+            // Check if these artists are followed
+            app.dispatch(IoEvent::UserArtistFollowCheck { artist_ids });
+        }
+        SearchResult::Albums(album_results) => {
+            let album_ids = album_results
+                .items
+                .iter()
+                .filter_map(|album| album.id.clone())
+                .collect();
+
+            if is_new_search {
+                results.albums = ScrollableResultPages::default();
+            }
+            results.albums.add_pages(album_results);
+            results.selected_album_index = selected_index;
+
+            // Check if these albums are saved
+            app.dispatch(IoEvent::CurrentUserSavedAlbumsContains { album_ids });
+        }
+        SearchResult::Playlists(playlist_results) => {
+            if is_new_search {
+                results.playlists = ScrollableResultPages::default();
+            }
+            results.playlists.add_pages(playlist_results);
+            results.selected_playlists_index = selected_index;
+        }
+        SearchResult::Shows(show_results) => {
+            let show_ids = show_results
+                .items
+                .iter()
+                .map(|show| show.id.clone())
+                .collect();
+
+            if is_new_search {
+                results.shows = ScrollableResultPages::default();
+            }
+            results.shows.add_pages(show_results);
+            results.selected_shows_index = selected_index;
+
+            // check if these shows are saved
+            app.dispatch(IoEvent::CurrentUserSavedShowsContains { show_ids });
+        }
+        SearchResult::Episodes(episode_results) => {
+            if is_new_search {
+                results.episodes = ScrollableResultPages::default();
+            }
+            results.episodes.add_pages(episode_results);
+        }
+    }
+}
+
+// A stand-in for the Spotify Web API for tests, which answers every search with an empty page of
+// the type searched for and keeps the path of each request made
+#[cfg(test)]
+pub mod mock_api {
+    use super::*;
+    use crate::user_config::UserConfig;
+    use rspotify::{Config, Credentials, OAuth, Token};
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::Mutex;
+
+    pub type Requests = Arc<Mutex<Vec<String>>>;
+
+    pub async fn network() -> (Network, Requests) {
+        network_with(|_| None).await
+    }
+
+    // Like `network`, with the responses `respond` has for some paths
+    pub async fn network_with(
+        respond: impl Fn(&str) -> Option<String> + Send + 'static,
+    ) -> (Network, Requests) {
+        network_with_status(move |path| respond(path).map(|body| (200, body))).await
+    }
+
+    // Like `network_with`, with the status of each response too, to have requests fail
+    pub async fn network_with_status(
+        respond: impl Fn(&str) -> Option<(u16, String)> + Send + 'static,
+    ) -> (Network, Requests) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let requests = Requests::default();
+
+        let served = requests.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                // Requests dropped on the way, e.g. by a cancelled fetch, are left unanswered
+                // rather than taking the server down for the ones after them
+                let Ok(mut stream) = stream else { continue };
+                let Ok(read) = stream.try_clone() else {
+                    continue;
+                };
+                let mut reader = BufReader::new(read);
+                let mut request_line = String::new();
+                if reader.read_line(&mut request_line).is_err() {
+                    continue;
+                }
+                // The rest of the request has no body worth reading
+                let mut line = String::new();
+                while matches!(reader.read_line(&mut line), Ok(read) if read > 2) {
+                    line.clear();
+                }
+
+                let path = request_line
+                    .split(' ')
+                    .nth(1)
+                    .unwrap_or_default()
+                    .to_string();
+                let (status, body) = respond(&path).unwrap_or_else(|| (200, empty_page(&path)));
+                served.lock().unwrap().push(path);
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+            }
+        });
+
+        let spotify = AuthCodePkceSpotify::with_config(
+            Credentials::new_pkce("client"),
+            OAuth::default(),
+            Config {
+                api_base_url: format!("http://{address}/"),
+                ..Default::default()
+            },
+        );
+        *spotify.token.lock().await.unwrap() = Some(Token {
+            access_token: String::from("token"),
+            expires_at: Some(Utc::now() + Duration::hours(1)),
+            ..Default::default()
+        });
+
+        let (tx, _rx) = tokio::sync::mpsc::unbounded_channel();
+        let app = App::new(tx, UserConfig::new(), Utc::now());
+        let network = Network::new(spotify, ClientConfig::new(), Arc::new(RwLock::new(app)));
+        (network, requests)
+    }
+
+    // The order of the parameters in a query changes from one request to the next
+    fn search_type(path: &str) -> &str {
+        path.split(['?', '&'])
+            .find_map(|param| param.strip_prefix("type="))
+            .unwrap_or_default()
+    }
+
+    pub fn empty_page(path: &str) -> String {
+        let page = serde_json::json!({
+            "href": "",
+            "items": [],
+            "limit": 4,
+            "next": null,
+            "offset": 0,
+            "previous": null,
+            "total": 0,
+        });
+        serde_json::json!({ format!("{}s", search_type(path)): page }).to_string()
+    }
+
+    // The playback on a speaker, with nothing playing
+    pub fn playback() -> serde_json::Value {
+        serde_json::json!({
+            "device": {
+                "id": "active",
+                "is_active": true,
+                "is_private_session": false,
+                "is_restricted": false,
+                "name": "Speaker",
+                "type": "Speaker",
+                "volume_percent": 50,
+            },
+            "repeat_state": "off",
+            "shuffle_state": false,
+            "context": null,
+            "timestamp": 0,
+            "progress_ms": 0,
+            "is_playing": true,
+            "item": null,
+            "currently_playing_type": "unknown",
+            "actions": { "disallows": {} },
+        })
+    }
+
+    // The type of each search made, in order
+    pub fn searches(requests: &Requests) -> Vec<String> {
+        requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|path| path.starts_with("/search"))
+            .map(|path| search_type(path).to_string())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{is_item_saved, SearchResultBlock, StaleData};
+    use crate::event::Key;
+    use crate::startup_cache::StartupCache;
+    use crate::user_config::UserConfig;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    #[test]
+    fn transient_errors() {
+        assert!(is_transient_status(reqwest::StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_transient_status(
+            reqwest::StatusCode::SERVICE_UNAVAILABLE
+        ));
+        assert!(is_transient_status(reqwest::StatusCode::BAD_GATEWAY));
+        assert!(!is_transient_status(reqwest::StatusCode::UNAUTHORIZED));
+        assert!(!is_transient_status(reqwest::StatusCode::NOT_FOUND));
+
+        assert!(is_transient(&ClientError::Io(std::io::Error::from(
+            std::io::ErrorKind::ConnectionReset
+        ))));
+        assert!(!is_transient(&ClientError::InvalidToken));
+        assert!(!is_transient(&ClientError::ParseJson(
+            serde_json::from_str::<u32>("").unwrap_err()
+        )));
+    }
+
+    #[tokio::test]
+    async fn searches_every_type_unless_asked_for_some() {
+        let (mut network, requests) = mock_api::network().await;
+        network
+            .handle_network_event(IoEvent::GetSearchResults {
+                search_term: String::from("song"),
+                types: None,
+                country: None,
+            })
+            .await;
+        assert_eq!(mock_api::searches(&requests).len(), 6);
+        {
+            let app = network.app.read().await;
+            let results = &app.search_results;
+            assert!(app.api_error.is_empty(), "{}", app.api_error);
+            assert!(results.tracks.get_results(None).is_some());
+            assert!(results.artists.get_results(None).is_some());
+            assert!(results.albums.get_results(None).is_some());
+            assert!(results.playlists.get_results(None).is_some());
+            assert!(results.shows.get_results(None).is_some());
+            assert!(results.episodes.get_results(None).is_some());
+        }
+
+        let (mut network, requests) = mock_api::network().await;
+        network
+            .handle_network_event(IoEvent::GetSearchResults {
+                search_term: String::from("song"),
+                types: Some(vec![SearchType::Show]),
+                country: None,
+            })
+            .await;
+        assert_eq!(mock_api::searches(&requests), ["show"]);
+        let results = &network.app.read().await.search_results;
+        assert!(results.shows.get_results(None).is_some());
+        assert!(results.tracks.get_results(None).is_none());
+    }
+
+    #[tokio::test]
+    async fn a_failed_search_type_fails_the_whole_search() {
+        let (mut network, requests) = mock_api::network_with_status(|path| {
+            path.contains("type=album")
+                .then(|| (403, String::from("{}")))
+        })
+        .await;
+        network
+            .handle_network_event(IoEvent::GetSearchResults {
+                search_term: String::from("song"),
+                types: None,
+                country: None,
+            })
+            .await;
+        // Every type was still asked for
+        assert_eq!(mock_api::searches(&requests).len(), 6);
+        let app = network.app.read().await;
+        assert_eq!(app.get_current_route().id, RouteId::Error);
+        // None of the other results are shown for it
+        assert!(app.search_results.search_term.is_empty());
+        assert!(app.search_results.tracks.get_results(None).is_none());
+    }
+
+    #[tokio::test]
+    async fn pages_through_search_results() {
+        // Four tracks, two to a page
+        let (mut network, requests) = mock_api::network_with(|path| {
+            if !path.contains("type=track") {
+                return None;
+            }
+            let offset = path
+                .split(['?', '&'])
+                .find_map(|param| param.strip_prefix("offset="))
+                .and_then(|offset| offset.parse::<u32>().ok())
+                .unwrap_or(0);
+            let next = (offset + 2 < 4).then_some("next");
+            Some(
+                json!({ "tracks": {
+                    "href": "", "items": [], "limit": 2, "next": next, "offset": offset,
+                    "previous": null, "total": 4,
+                }})
+                .to_string(),
+            )
+        })
+        .await;
+        let more = |search_term: &str| IoEvent::GetMoreSearchResults {
+            search_term: String::from(search_term),
+            search_type: SearchType::Track,
+            offset: 2,
+            country: None,
+        };
+
+        network
+            .handle_network_event(IoEvent::GetSearchResults {
+                search_term: String::from("song"),
+                types: Some(vec![SearchType::Track]),
+                country: None,
+            })
+            .await;
+        assert_eq!(
+            network
+                .app
+                .read()
+                .await
+                .search_results
+                .next_offset(SearchType::Track),
+            Some(2)
+        );
+
+        // A page for a search made before this one is dropped
+        network.handle_network_event(more("other song")).await;
+        assert_eq!(
+            network.app.read().await.search_results.tracks.pages.len(),
+            1
+        );
+
+        network.handle_network_event(more("song")).await;
+        {
+            let app = network.app.read().await;
+            let tracks = &app.search_results.tracks;
+            assert_eq!(tracks.pages.len(), 2);
+            assert_eq!(tracks.get_results(None).unwrap().offset, 2);
+            assert_eq!(app.search_results.next_offset(SearchType::Track), None);
+        }
+
+        // Asked for twice, e.g. by pressing the key again while it loaded, it is added once
+        network.handle_network_event(more("song")).await;
+        assert_eq!(
+            network.app.read().await.search_results.tracks.pages.len(),
+            2
+        );
+        assert_eq!(
+            requests
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|path| path.contains("offset=2"))
+                .count(),
+            3
+        );
+    }
+
+    #[tokio::test]
+    async fn opens_a_show_without_episodes() {
+        let (mut network, _) = mock_api::network_with(|path| {
+            path.starts_with("/shows/4rOoJ6Egrf8K2IrywzwOMk/episodes")
+                .then(|| {
+                    json!({
+                        "href": "", "items": [], "limit": 20, "next": null, "offset": 0,
+                        "previous": null, "total": 0,
+                    })
+                    .to_string()
+                })
+        })
+        .await;
+        let show = serde_json::from_value::<SimplifiedShow>(json!({
+            "available_markets": [], "copyrights": [], "description": "", "explicit": false,
+            "external_urls": {}, "href": "", "id": "4rOoJ6Egrf8K2IrywzwOMk", "images": [],
+            "languages": [], "media_type": "audio", "name": "New show", "publisher": "",
+        }))
+        .unwrap();
+
+        network.get_show_episodes(Box::new(show)).await;
+        let app = network.app.read().await;
+        assert!(app.api_error.is_empty(), "{}", app.api_error);
+        assert_eq!(app.get_current_route().id, RouteId::PodcastEpisodes);
+        assert_eq!(
+            app.selected_show_simplified
+                .as_ref()
+                .map(|selected| selected.show.name.as_str()),
+            Some("New show")
+        );
+        assert!(app
+            .library
+            .show_episodes
+            .get_results(None)
+            .is_some_and(|page| page.items.is_empty()));
+    }
+
+    #[tokio::test]
+    async fn unfollows_the_finished_shows_found() {
+        const FINISHED: &str = "4rOoJ6Egrf8K2IrywzwOMk";
+        const STARTED: &str = "5CfCWKI5pZ28U0uOzXkDHe";
+        let page = |items: Vec<serde_json::Value>| {
+            json!({
+                "href": "", "items": items, "limit": 50, "next": null, "offset": 0,
+                "previous": null, "total": items.len(),
+            })
+            .to_string()
+        };
+        let saved_show = |id: &str| {
+            json!({
+                "added_at": "2024-01-01T00:00:00Z",
+                "show": {
+                    "available_markets": [], "copyrights": [], "description": "",
+                    "explicit": false, "external_urls": {}, "href": "", "id": id, "images": [],
+                    "languages": [], "media_type": "audio", "name": id, "publisher": "",
+                },
+            })
+        };
+        let episode = |fully_played: bool| {
+            json!({
+                "audio_preview_url": null, "description": "", "duration_ms": 1_800_000,
+                "explicit": false, "external_urls": {}, "href": "",
+                "id": "512ojhOuo1ktJprKbVcKyQ", "images": [], "is_externally_hosted": false,
+                "is_playable": true, "language": "en", "languages": [], "name": "Episode",
+                "release_date": "2020-01-01", "release_date_precision": "day",
+                "resume_point": { "fully_played": fully_played, "resume_position_ms": 0 },
+            })
+        };
+        // Once removed, the finished show isn't saved anymore
+        let removed = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let is_removed = removed.clone();
+        let (mut network, requests) = mock_api::network_with(move |path| {
+            use std::sync::atomic::Ordering;
+            if path.starts_with("/me/shows?ids=") {
+                removed.store(true, Ordering::SeqCst);
+                Some(String::new())
+            } else if path.starts_with("/me/shows") {
+                Some(if removed.load(Ordering::SeqCst) {
+                    page(vec![saved_show(STARTED)])
+                } else {
+                    page(vec![saved_show(FINISHED), saved_show(STARTED)])
+                })
+            } else if path.starts_with(&format!("/shows/{}/episodes", FINISHED)) {
+                Some(page(vec![episode(true), episode(true)]))
+            } else if path.starts_with(&format!("/shows/{}/episodes", STARTED)) {
+                Some(page(vec![episode(true), episode(false)]))
+            } else {
+                None
+            }
+        })
+        .await;
+
+        // Cancelled before it was done
+        network
+            .handle_network_event(IoEvent::FindFinishedShows)
+            .await;
+        assert!(network.app.read().await.finished_shows.is_none());
+
+        network.app.write().await.is_finding_finished_shows = true;
+        network
+            .handle_network_event(IoEvent::FindFinishedShows)
+            .await;
+        let show_ids = {
+            let app = network.app.read().await;
+            assert!(app.api_error.is_empty(), "{}", app.api_error);
+            assert!(!app.is_finding_finished_shows);
+            assert_eq!(app.get_current_route().id, RouteId::FinishedShows);
+            let finished_shows = app.finished_shows.as_ref().unwrap();
+            finished_shows.removed_ids()
+        };
+        assert_eq!(show_ids, [ShowId::from_id(FINISHED).unwrap()]);
+
+        network
+            .handle_network_event(IoEvent::RemoveSavedShows { show_ids })
+            .await;
+        assert!(is_removed.load(std::sync::atomic::Ordering::SeqCst));
+        assert!(requests
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|path| path.contains(FINISHED) && path.starts_with("/me/shows?ids=")));
+        let app = network.app.read().await;
+        assert!(!app
+            .saved_show_ids_set
+            .contains(&ShowId::from_id(FINISHED).unwrap()));
+        let saved = app.library.saved_shows.get_results(None).unwrap();
+        assert_eq!(saved.items.len(), 1);
+        assert_eq!(saved.items[0].show.id.id(), STARTED);
+    }
+
+    #[tokio::test]
+    async fn fetches_the_features_of_one_track() {
+        let (mut network, _) = mock_api::network_with(|path| {
+            path.starts_with("/audio-features/4iV5W9uYEdYUVa79Axb7Rh")
+                .then(|| {
+                    json!({
+                        "acousticness": 0.1, "analysis_url": "", "danceability": 0.65,
+                        "duration_ms": 180000, "energy": 0.8, "id": "4iV5W9uYEdYUVa79Axb7Rh",
+                        "instrumentalness": 0.0, "key": 9, "liveness": 0.1, "loudness": -5.0,
+                        "mode": 0, "speechiness": 0.05, "tempo": 119.6, "time_signature": 4,
+                        "track_href": "", "valence": 0.5,
+                    })
+                    .to_string()
+                })
+        })
+        .await;
+        let track_id = TrackId::from_id("4iV5W9uYEdYUVa79Axb7Rh").unwrap();
+        let missing_id = TrackId::from_id("6rqhFgbbKwnb9MLmUQDhG6").unwrap();
+        {
+            let mut app = network.app.write().await;
+            app.loading_track_features.insert(track_id.clone());
+            app.loading_track_features.insert(missing_id.clone());
+        }
+
+        network.get_track_features(track_id.clone()).await;
+        network.get_track_features(missing_id.clone()).await;
+        let app = network.app.read().await;
+        assert!(app.loading_track_features.is_empty());
+        assert_eq!(app.audio_features[&track_id].key, 9);
+        // A failure is a notification, not the error screen
+        assert!(!app.audio_features.contains_key(&missing_id));
+        assert_eq!(
+            app.notifications.current().unwrap().severity,
+            Severity::Warning
+        );
+        assert_ne!(app.get_current_route().id, RouteId::Error);
+    }
+
+    #[tokio::test]
+    async fn a_failed_sort_is_retried_when_asked_again() {
+        let (mut network, _) = mock_api::network_with_status(|path| {
+            path.starts_with("/audio-features?")
+                .then(|| (403, String::from("{}")))
+        })
+        .await;
+        network.app.write().await.item_table.pending_sort = Some(crate::app::TrackFeature::Tempo);
+
+        network
+            .get_audio_features(vec![TrackId::from_id("4iV5W9uYEdYUVa79Axb7Rh").unwrap()])
+            .await;
+        let app = network.app.read().await;
+        assert_eq!(app.get_current_route().id, RouteId::Error);
+        assert_eq!(app.item_table.pending_sort, None);
+    }
+
+    #[tokio::test]
+    async fn new_search_results_clear_the_selection() {
+        let (mut network, _) = mock_api::network_with(|path| {
+            if !path.contains("type=artist") {
+                return None;
+            }
+            let artist = |id: &str, name: &str| {
+                json!({
+                    "external_urls": {}, "followers": { "href": null, "total": 0 }, "genres": [],
+                    "href": "", "id": id, "images": [], "name": name, "popularity": 0,
+                })
+            };
+            Some(
+                json!({ "artists": {
+                    "href": "",
+                    "items": [
+                        artist("0OdUWJ0sBjDrqHygGUXeCF", "Band of Horses"),
+                        artist("4Z8W4fKeB5YxbusRsdQVPb", "Radiohead"),
+                    ],
+                    "limit": 2, "next": null, "offset": 0, "previous": null, "total": 2,
+                }})
+                .to_string(),
+            )
+        })
+        .await;
+        let (io_tx, mut io_rx) = tokio::sync::mpsc::unbounded_channel();
+        *network.app.write().await = App::new(io_tx, UserConfig::new(), Utc::now());
+        let search = || IoEvent::GetSearchResults {
+            search_term: String::from("horses"),
+            types: Some(vec![SearchType::Artist]),
+            country: None,
+        };
+        let drain = |io_rx: &mut tokio::sync::mpsc::UnboundedReceiver<IoEvent<'static>>| {
+            while io_rx.try_recv().is_ok() {}
+        };
+
+        network.handle_network_event(search()).await;
+        {
+            let mut app = network.app.write().await;
+            app.push_navigation_stack(RouteId::Search, ActiveBlock::SearchResultBlock);
+            app.search_results.hovered_block = SearchResultBlock::ArtistSearch;
+            crate::handlers::handle_app(Key::Enter, &mut app);
+            crate::handlers::handle_app(Key::Down, &mut app);
+            assert_eq!(app.search_results.selected_artists_index, Some(1));
+        }
+        drain(&mut io_rx);
+
+        network.handle_network_event(search()).await;
+        drain(&mut io_rx);
+        let mut app = network.app.write().await;
+        assert_eq!(app.search_results.selected_block, SearchResultBlock::Empty);
+        assert_eq!(
+            app.search_results.hovered_block,
+            SearchResultBlock::ArtistSearch
+        );
+        assert_eq!(app.search_results.selected_artists_index, None);
+
+        // Selects the block again, rather than opening an artist
+        crate::handlers::handle_app(Key::Enter, &mut app);
+        assert!(io_rx.try_recv().is_err());
+        assert_eq!(app.get_current_route().id, RouteId::Search);
+
+        crate::handlers::handle_app(Key::Enter, &mut app);
+        assert!(matches!(
+            io_rx.try_recv(),
+            Ok(IoEvent::GetArtist { input_artist_name, .. }) if input_artist_name == "Band of Horses"
+        ));
+    }
+
+    fn status_error(status: u16, retry_after: Option<&str>) -> ClientError {
+        let mut response = http::Response::builder().status(status);
+        if let Some(retry_after) = retry_after {
+            response = response.header("Retry-After", retry_after);
+        }
+        let response = reqwest::Response::from(response.body("").unwrap());
+        ClientError::Http(Box::new(HttpError::StatusCode(response)))
+    }
+
+    #[test]
+    fn transient_error_messages() {
+        assert_eq!(
+            transient_error_message(&status_error(429, Some("30"))).unwrap(),
+            "Spotify is limiting requests, try again in 30 seconds"
+        );
+        assert_eq!(
+            transient_error_message(&status_error(429, None)).unwrap(),
+            "Spotify is limiting requests, try again in a moment"
+        );
+        assert_eq!(
+            transient_error_message(&status_error(503, None)).unwrap(),
+            "Spotify is having trouble (503), try again in a moment"
+        );
+        assert_eq!(
+            transient_error_message(&ClientError::Io(std::io::Error::from(
+                std::io::ErrorKind::ConnectionReset
+            )))
+            .unwrap(),
+            "Couldn't reach Spotify, check the connection and try again"
+        );
+        assert!(transient_error_message(&status_error(401, None)).is_none());
+        assert!(transient_error_message(&status_error(403, None)).is_none());
+        assert!(transient_error_message(&ClientError::InvalidToken).is_none());
+    }
+
+    #[tokio::test]
+    async fn transient_errors_are_notified_without_leaving_the_view() {
+        let (mut network, _) = mock_api::network().await;
+        network.handle_error(anyhow!(status_error(503, None))).await;
+        {
+            let app = network.app.read().await;
+            assert_eq!(app.get_current_route().id, RouteId::Home);
+            assert_eq!(
+                app.notifications.current().unwrap().severity,
+                Severity::Warning
+            );
+        }
+
+        network.handle_error(anyhow!(status_error(403, None))).await;
+        assert_eq!(
+            network.app.read().await.get_current_route().id,
+            RouteId::Error
+        );
+    }
+
+    #[tokio::test]
+    async fn library_cache_outlives_the_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(crate::paths::LIBRARY_CACHE_FILE);
+        let track_id = TrackId::from_id("4iV5W9uYEdYUVa79Axb7Rh").unwrap();
+
+        let (mut network, _) = mock_api::network().await;
+        {
+            let mut app = network.app.write().await;
+            app.library_cache_path = Some(path.clone());
+            app.liked_song_ids_set.insert(track_id.clone());
+        }
+        network.save_library_cache().await;
+        assert_eq!(network.app.read().await.library_cache_saves, 1);
+
+        let (mut network, _) = mock_api::network().await;
+        network.app.write().await.library_cache_path = Some(path.clone());
+        network.load_library_cache().await;
+        assert!(network
+            .app
+            .read()
+            .await
+            .liked_song_ids_set
+            .is_stale(&track_id));
+
+        // A corrupted cache is the same as none
+        std::fs::write(&path, "{").unwrap();
+        let (mut network, _) = mock_api::network().await;
+        network.app.write().await.library_cache_path = Some(path);
+        network.load_library_cache().await;
+        assert!(!network
+            .app
+            .read()
+            .await
+            .liked_song_ids_set
+            .contains(&track_id));
+    }
+
+    #[tokio::test]
+    async fn fetches_replace_the_startup_cache() {
+        let page = || {
+            json!({
+                "href": "", "items": [], "limit": 20, "next": null, "offset": 0, "previous": null,
+                "total": 0
+            })
+        };
+        let (mut network, _) = mock_api::network_with(move |path| {
+            (path.starts_with("/me/playlists") || path.starts_with("/me/albums"))
+                .then(|| page().to_string())
+        })
+        .await;
+        network.app.write().await.load_startup_cache(StartupCache {
+            version: crate::startup_cache::VERSION,
+            playlists: Some(serde_json::from_value(page()).unwrap()),
+            saved_albums: Some(serde_json::from_value(page()).unwrap()),
+            saved_shows: Some(serde_json::from_value(page()).unwrap()),
+            ..StartupCache::default()
+        });
+        assert_eq!(
+            network.app.read().await.stale,
+            StaleData {
+                user: false,
+                playlists: true,
+                saved_albums: true,
+                saved_shows: true,
+            }
+        );
+
+        network.get_current_user_playlists().await;
+        network.get_current_user_saved_albums(None).await;
+        let app = network.app.read().await;
+        assert!(!app.stale.playlists && !app.stale.saved_albums);
+        // No albums are saved anymore, so the cached page is gone rather than shown again
+        assert!(app.library.saved_albums.pages.is_empty());
+        assert!(app.stale.saved_shows);
+        assert_eq!(app.library.saved_shows.pages.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn takes_the_expiry_of_a_token_refreshed_along_with_a_request() {
+        let (mut network, requests) = mock_api::network().await;
+        let expires_at = Utc::now() + Duration::hours(1);
+        *network.spotify.token.lock().await.unwrap() = Some(Token {
+            expires_at: Some(expires_at),
+            ..Token::default()
+        });
+        network.app.write().await.is_refreshing_token = true;
+
+        network.refresh_authentication().await;
+        let app = network.app.read().await;
+        assert_eq!(app.spotify_token_expiry, expires_at);
+        assert!(!app.is_refreshing_token);
+        assert!(requests.lock().unwrap().is_empty());
+    }
+
+    // A network whose token has expired, and whose refresh Spotify answers with `status`
+    async fn expired_network(status: u16, body: &'static str) -> Network {
+        let (mut network, _) = mock_api::network_with_status(move |path| {
+            path.starts_with("/api/token")
+                .then(|| (status, String::from(body)))
+        })
+        .await;
+        network.spotify.config.auth_base_url = network.spotify.config.api_base_url.clone();
+        *network.spotify.token.lock().await.unwrap() = Some(Token {
+            access_token: String::from("expired"),
+            refresh_token: Some(String::from("refresh")),
+            expires_at: Some(Utc::now() - Duration::hours(1)),
+            ..Default::default()
+        });
+        network.app.write().await.is_refreshing_token = true;
+        network
+    }
+
+    #[tokio::test]
+    async fn keeps_the_token_when_spotify_cant_be_reached() {
+        let mut network = expired_network(503, "{}").await;
+        network.refresh_authentication().await;
+        {
+            let app = network.app.read().await;
+            assert_ne!(app.get_current_route().id, RouteId::Error);
+            assert_eq!(
+                app.notifications.current().unwrap().severity,
+                Severity::Warning
+            );
+            // Tried again shortly
+            assert!(app.spotify_token_expiry > Utc::now());
+            assert!(!app.is_refreshing_token);
+        }
+        let token = network.current_token().await.unwrap();
+        assert_eq!(token.refresh_token.as_deref(), Some("refresh"));
+    }
+
+    #[tokio::test]
+    async fn shows_refresh_errors_other_than_a_rejected_token() {
+        let mut network = expired_network(400, r#"{"error":"invalid_client"}"#).await;
+        network.refresh_authentication().await;
+        let app = network.app.read().await;
+        assert_eq!(app.get_current_route().id, RouteId::Error);
+        assert!(
+            app.api_error.contains("invalid_client"),
+            "{}",
+            app.api_error
+        );
+    }
+
+    #[tokio::test]
+    async fn only_a_rejected_refresh_token_needs_signing_in_again() {
+        let rejected = |body: &str| {
+            let response = http::Response::builder()
+                .status(400)
+                .body(body.to_string())
+                .unwrap();
+            ClientError::Http(Box::new(HttpError::StatusCode(response.into())))
+        };
+        assert_eq!(
+            refresh_error_message(rejected(
+                r#"{"error":"invalid_grant","error_description":"Refresh token revoked"}"#
+            ))
+            .await,
+            None
+        );
+        assert_eq!(
+            refresh_error_message(rejected(r#"{"error":"invalid_client"}"#)).await,
+            Some(String::from(
+                r#"400 Bad Request: {"error":"invalid_client"}"#
+            ))
+        );
+        assert!(refresh_error_message(ClientError::InvalidToken)
+            .await
+            .is_some());
+    }
+
+    // A secondary instance whose token has been refreshed by the first one, which wrote the new
+    // token to the cache
+    async fn secondary_network(
+        respond: impl Fn(&str) -> Option<(u16, String)> + Send + 'static,
+    ) -> (Network, Token, tempfile::TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut network, _) = mock_api::network_with_status(respond).await;
+        network.spotify.config.cache_path = dir.path().join(crate::paths::TOKEN_CACHE_FILE);
+        // Held by the process running the tests
+        let lock_path = dir.path().join("spotify-tui.lock");
+        std::fs::write(&lock_path, std::process::id().to_string()).unwrap();
+        network = network.with_shared_token(lock_path);
+
+        let refreshed = Token {
+            access_token: String::from("refreshed"),
+            expires_at: Some(Utc::now() + Duration::hours(1)),
+            ..Default::default()
+        };
+        refreshed
+            .write_cache(&network.spotify.config.cache_path)
+            .unwrap();
+        (network, refreshed, dir)
+    }
+
+    #[tokio::test]
+    async fn secondary_instances_read_the_token_again_when_it_is_refused() {
+        let (mut network, refreshed, _dir) = secondary_network(|path| {
+            path.starts_with("/me/player/devices")
+                .then(|| (401, String::from("{}")))
+        })
+        .await;
+
+        network.get_devices(false).await;
+        {
+            let app = network.app.read().await;
+            assert_ne!(app.get_current_route().id, RouteId::Error);
+            assert_eq!(
+                app.notifications.current().unwrap().severity,
+                Severity::Warning
+            );
+            assert_eq!(app.spotify_token_expiry, refreshed.expires_at.unwrap());
+        }
+        assert_eq!(
+            network.current_token().await.unwrap().access_token,
+            "refreshed"
+        );
+
+        // The token in the cache is the one refused this time, so there's nothing else to try
+        network.get_devices(false).await;
+        assert_eq!(
+            network.app.read().await.get_current_route().id,
+            RouteId::Error
+        );
+    }
+
+    #[tokio::test]
+    async fn secondary_instances_leave_refreshing_to_the_first() {
+        let (mut network, refreshed, _dir) = secondary_network(|_| None).await;
+        network.app.write().await.is_refreshing_token = true;
+        network.refresh_authentication().await;
+        {
+            let app = network.app.read().await;
+            assert_eq!(app.spotify_token_expiry, refreshed.expires_at.unwrap());
+            assert!(!app.is_refreshing_token);
+        }
+
+        // Until the first instance refreshes the token, it's looked for again later
+        Token {
+            access_token: String::from("expired"),
+            expires_at: Some(Utc::now() - Duration::hours(1)),
+            ..Default::default()
+        }
+        .write_cache(&network.spotify.config.cache_path)
+        .unwrap();
+        network.app.write().await.is_refreshing_token = true;
+        network.refresh_authentication().await;
+        let app = network.app.read().await;
+        assert!(app.spotify_token_expiry > Utc::now());
+        assert!(!app.is_refreshing_token);
+    }
+
+    #[tokio::test]
+    async fn search_limits_are_the_same_for_the_app() {
+        let (mut network, _requests) = mock_api::network().await;
+        network
+            .handle_network_event(IoEvent::UpdateSearchLimits {
+                large_search_limit: 37,
+                small_search_limit: 10,
+            })
+            .await;
+        assert_eq!(network.large_search_limit, 37);
+        let app = network.app.read().await;
+        assert_eq!(app.large_search_limit, 37);
+        assert_eq!(app.small_search_limit, 10);
+    }
+
+    #[tokio::test]
+    async fn lists_each_made_for_you_playlist_once() {
+        let playlist = |id: &str, name: &str, owner: &str| {
+            json!({
+                "collaborative": false,
+                "external_urls": {},
+                "href": "",
+                "id": id,
+                "images": [],
+                "name": name,
+                "owner": { "external_urls": {}, "href": "", "id": owner },
+                "snapshot_id": "",
+                "tracks": { "href": "", "total": 30 },
+            })
+        };
+        let results = json!({
+            "playlists": {
+                "href": "", "limit": 20, "next": null, "offset": 0, "previous": null, "total": 3,
+                "items": [
+                    playlist("37i9dQZEVXcJZyENOWUFo7", "Discover Weekly", "spotify"),
+                    playlist("0sNOF9WDwhWunNAHPD3Baj", "Discover Weekly", "someone"),
+                    playlist("37i9dQZF1DXcBWIGoYBM5M", "Discover Weekly Mix", "spotify"),
+                ],
+            },
+        });
+        let (mut network, _requests) = mock_api::network_with(move |path| {
+            if path.contains("Discover") {
+                Some(results.to_string())
+            } else {
+                path.starts_with("/search")
+                    .then(|| mock_api::empty_page(path))
+            }
+        })
+        .await;
+
+        // Visiting the route again before the first results came back
+        for _ in 0..2 {
+            network
+                .made_for_you_search_and_add(String::from("Discover Weekly"), None)
+                .await;
+        }
+        network
+            .made_for_you_search_and_add(String::from("On Repeat"), None)
+            .await;
+
+        let app = network.app.read().await;
+        let playlists = app
+            .library
+            .made_for_you_playlists
+            .get_results(None)
+            .unwrap();
+        let ids = playlists
+            .items
+            .iter()
+            .map(|playlist| playlist.id.id())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, ["37i9dQZEVXcJZyENOWUFo7"]);
+        let notification = app.notifications.current().unwrap();
+        assert_eq!(notification.severity, Severity::Info);
+        assert!(notification.message.contains("On Repeat"));
+    }
+
+    #[tokio::test]
+    async fn refetches_playlists_after_changing_their_details() {
+        let playlist = |id: &str, name: &str| {
+            json!({
+                "collaborative": false,
+                "external_urls": {},
+                "href": "",
+                "id": id,
+                "images": [],
+                "name": name,
+                "owner": { "external_urls": {}, "href": "", "id": "owner" },
+                "snapshot_id": "",
+                "tracks": { "href": "", "total": 1 },
+            })
+        };
+        let page = |items: Vec<serde_json::Value>| {
+            json!({
+                "href": "", "items": items, "limit": 20, "next": null, "offset": 0,
+                "previous": null, "total": 2
+            })
+        };
+        let renamed = page(vec![
+            playlist("0sNOF9WDwhWunNAHPD3Baj", "Other"),
+            playlist("37i9dQZF1DXcBWIGoYBM5M", "New name"),
+        ]);
+        let (mut network, requests) = mock_api::network_with(move |path| {
+            path.starts_with("/me/playlists")
+                .then(|| renamed.to_string())
+                .or_else(|| path.starts_with("/playlists/").then(String::new))
+        })
+        .await;
+        let playlist_id = PlaylistId::from_id("37i9dQZF1DXcBWIGoYBM5M").unwrap();
+        {
+            let mut app = network.app.write().await;
+            app.playlists = Some(
+                serde_json::from_value(page(vec![
+                    playlist("37i9dQZF1DXcBWIGoYBM5M", "Old name"),
+                    playlist("0sNOF9WDwhWunNAHPD3Baj", "Other"),
+                ]))
+                .unwrap(),
+            );
+            app.selected_playlist_index = Some(0);
+        }
+
+        network
+            .change_playlist_details(playlist_id, Some(String::from("New name")), None)
+            .await;
+        assert!(requests
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|path| path.starts_with("/me/playlists")));
+        let app = network.app.read().await;
+        assert_eq!(app.playlists.as_ref().unwrap().items[1].name, "New name");
+        // Still the edited one, which the refetch moved
+        assert_eq!(app.selected_playlist_index, Some(1));
+    }
+
+    fn episode_json(id: &str) -> serde_json::Value {
+        json!({
+            "audio_preview_url": null,
+            "description": "",
+            "duration_ms": 1_800_000,
+            "explicit": false,
+            "external_urls": {},
+            "href": "",
+            "id": id,
+            "images": [],
+            "is_externally_hosted": false,
+            "is_playable": true,
+            "language": "en",
+            "languages": [],
+            "name": "Episode",
+            "release_date": "2020-01-01",
+            "release_date_precision": "day",
+            "resume_point": null,
+            "type": "episode",
+            "show": {
+                "available_markets": [], "copyrights": [], "description": "", "explicit": false,
+                "external_urls": {}, "href": "", "id": "4rOoJ6Egrf8K2IrywzwOMk", "images": [],
+                "languages": [], "media_type": "audio", "name": "Podcast", "publisher": "",
+            },
+        })
+    }
+
+    #[tokio::test]
+    async fn queues_a_playlist_in_order_up_to_the_limit() {
+        let track = |id: Option<&str>| {
+            json!({
+                "added_at": null,
+                "is_local": id.is_none(),
+                "track": {
+                    "album": { "artists": [], "external_urls": {}, "images": [], "name": "Album" },
+                    "artists": [],
+                    "disc_number": 1,
+                    "duration_ms": 180_000,
+                    "explicit": false,
+                    "external_ids": {},
+                    "external_urls": {},
+                    "id": id,
+                    "is_local": id.is_none(),
+                    "name": "Track",
+                    "popularity": 0,
+                    "track_number": 1,
+                    "type": "track",
+                },
+            })
+        };
+        let page = |items: Vec<serde_json::Value>, offset: u32, next: Option<&str>| {
+            json!({
+                "href": "",
+                "items": items,
+                "limit": 3,
+                "next": next,
+                "offset": offset,
+                "previous": null,
+                "total": 4,
+            })
+            .to_string()
+        };
+        let respond = move |path: &str| {
+            if path.starts_with("/playlists/37i9dQZF1DXcBWIGoYBM5M/tracks") {
+                let body = if path.contains("offset=0") {
+                    page(
+                        vec![
+                            track(Some("4iV5W9uYEdYUVa79Axb7Rh")),
+                            track(None),
+                            track(Some("0eGsygTp906u18L0Oimnem")),
+                        ],
+                        0,
+                        Some("https://api.spotify.com/next"),
+                    )
+                } else {
+                    page(vec![track(Some("1301WleyT98MSxVHPZCA6M"))], 3, None)
+                };
+                return Some((200, body));
+            }
+            if path.starts_with("/me/player/queue") {
+                let status = if path.contains("1301WleyT98MSxVHPZCA6M") {
+                    403
+                } else {
+                    204
+                };
+                return Some((status, String::new()));
+            }
+            None
+        };
+        let play_context_id =
+            || PlayContextId::Playlist(PlaylistId::from_id("37i9dQZF1DXcBWIGoYBM5M").unwrap());
+        let queued = |requests: &mock_api::Requests| {
+            requests
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|path| path.starts_with("/me/player/queue"))
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+
+        // Local files are left out, and a failure says how many were queued before it
+        let (mut network, requests) = mock_api::network_with_status(respond).await;
+        network.add_context_to_queue(play_context_id(), 100).await;
+        let queued_paths = queued(&requests);
+        assert_eq!(queued_paths.len(), 3);
+        assert!(queued_paths[0].contains("4iV5W9uYEdYUVa79Axb7Rh"));
+        assert!(queued_paths[1].contains("0eGsygTp906u18L0Oimnem"));
+        {
+            let app = network.app.read().await;
+            let notification = app.notifications.current().unwrap();
+            assert_eq!(notification.severity, Severity::Error);
+            assert!(notification
+                .message
+                .starts_with("Queued 2 of 3 items before failing"));
+        }
+
+        // Only as many as the limit, without fetching the pages after it
+        let (mut network, requests) = mock_api::network_with_status(respond).await;
+        network.add_context_to_queue(play_context_id(), 1).await;
+        assert_eq!(queued(&requests).len(), 1);
+        assert_eq!(requests.lock().unwrap().len(), 2);
+        assert_eq!(
+            network
+                .app
+                .read()
+                .await
+                .notifications
+                .current()
+                .unwrap()
+                .message,
+            "Added 1 item to the queue"
+        );
+    }
+
+    #[test]
+    fn partitions_tracks_and_episodes() {
+        let track = json!({
+            "album": { "artists": [], "external_urls": {}, "images": [], "name": "Album" },
+            "artists": [],
+            "disc_number": 1,
+            "duration_ms": 180_000,
+            "explicit": false,
+            "external_ids": {},
+            "external_urls": {},
+            "id": "4iV5W9uYEdYUVa79Axb7Rh",
+            "is_local": false,
+            "name": "Track",
+            "popularity": 0,
+            "track_number": 1,
+            "type": "track",
+        });
+        let local_track = json!({
+            "album": { "artists": [], "external_urls": {}, "images": [], "name": "Album" },
+            "artists": [],
+            "disc_number": 1,
+            "duration_ms": 180_000,
+            "explicit": false,
+            "external_ids": {},
+            "external_urls": {},
+            "id": null,
+            "is_local": true,
+            "name": "Local",
+            "popularity": 0,
+            "track_number": 1,
+            "type": "track",
+        });
+        let items = [
+            track.clone(),
+            episode_json("512ojhOuo1ktJprKbVcKyQ"),
+            local_track,
+            serde_json::Value::Null,
+            track,
+        ]
+        .into_iter()
+        .map(|item| json!({ "added_at": null, "is_local": false, "track": item }))
+        .collect::<Vec<_>>();
+        let page: Page<PlaylistItem> = serde_json::from_value(json!({
+            "href": "", "limit": 20, "next": null, "offset": 0, "previous": null, "total": 5,
+            "items": items,
+        }))
+        .unwrap();
+
+        let (track_ids, episode_ids) =
+            partition_playable_ids(page.items.iter().filter_map(|item| item.track.as_ref()));
+        assert_eq!(
+            track_ids,
+            vec![TrackId::from_id("4iV5W9uYEdYUVa79Axb7Rh").unwrap(); 2]
+        );
+        assert_eq!(
+            episode_ids,
+            [EpisodeId::from_id("512ojhOuo1ktJprKbVcKyQ").unwrap()]
+        );
+    }
+
+    #[tokio::test]
+    async fn checks_saved_episodes_once() {
+        let (mut network, requests) = mock_api::network_with(|path| {
+            path.starts_with("/me/episodes/contains")
+                .then(|| String::from("[true, false]"))
+        })
+        .await;
+        let saved = EpisodeId::from_id("512ojhOuo1ktJprKbVcKyQ").unwrap();
+        let unsaved = EpisodeId::from_id("0Q86acNRm6V9GYx55SXKwf").unwrap();
+        let unchecked = EpisodeId::from_id("4rOoJ6Egrf8K2IrywzwOMk").unwrap();
+
+        network
+            .current_user_saved_episodes_contains(vec![saved.clone(), unsaved.clone()])
+            .await;
+        network
+            .current_user_saved_episodes_contains(vec![saved.clone(), unsaved.clone()])
+            .await;
+        assert_eq!(requests.lock().unwrap().len(), 1);
+
+        let app = network.app.read().await;
+        assert_eq!(is_item_saved(&app, &saved.into()), Some(true));
+        assert_eq!(is_item_saved(&app, &unsaved.into()), Some(false));
+        assert_eq!(is_item_saved(&app, &unchecked.into()), None);
+    }
+
+    #[tokio::test]
+    async fn toggles_whether_a_track_is_saved() {
+        let track_id = TrackId::from_id("4iV5W9uYEdYUVa79Axb7Rh").unwrap();
+        for saved in [false, true] {
+            let (mut network, requests) = mock_api::network_with(move |path| {
+                path.starts_with("/me/tracks/contains")
+                    .then(|| format!("[{saved}]"))
+            })
+            .await;
+            network.toggle_save_track(track_id.clone()).await;
+
+            let app = network.app.read().await;
+            assert_eq!(app.liked_song_ids_set.contains(&track_id), !saved);
+            let message = if saved {
+                "Removed from Liked Songs"
+            } else {
+                "Saved to Liked Songs"
+            };
+            assert_eq!(app.notifications.current().unwrap().message, message);
+            assert_eq!(requests.lock().unwrap().len(), 2);
+        }
+    }
+
+    #[tokio::test]
+    async fn a_like_spotify_disagrees_with_is_taken_back() {
+        let track_id = TrackId::from_id("4iV5W9uYEdYUVa79Axb7Rh").unwrap();
+        // It had been liked elsewhere since it was last checked
+        let (mut network, _) = mock_api::network_with(|path| {
+            path.starts_with("/me/tracks/contains")
+                .then(|| String::from("[true]"))
+        })
+        .await;
+        {
+            let mut app = network.app.write().await;
+            app.liked_song_ids_set.insert(track_id.clone());
+            app.pending_track_saves.insert(track_id.clone(), true);
+        }
+        network.toggle_save_track(track_id.clone()).await;
+
+        let app = network.app.read().await;
+        assert!(!app.liked_song_ids_set.contains(&track_id));
+        assert!(app.pending_track_saves.is_empty());
+        assert_eq!(
+            app.notifications.current().unwrap().severity,
+            Severity::Warning
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_saves_leave_the_track_as_it_was() {
+        let track_id = TrackId::from_id("4iV5W9uYEdYUVa79Axb7Rh").unwrap();
+        let (mut network, _) = mock_api::network_with_status(|path| {
+            if path.starts_with("/me/tracks/contains") {
+                Some((200, String::from("[false]")))
+            } else {
+                path.starts_with("/me/tracks")
+                    .then(|| (403, String::from("{}")))
+            }
+        })
+        .await;
+        // Shown as liked ahead of the request
+        {
+            let mut app = network.app.write().await;
+            app.liked_song_ids_set.insert(track_id.clone());
+            app.pending_track_saves.insert(track_id.clone(), true);
+        }
+        network
+            .handle_network_event(IoEvent::ToggleSaveTrack {
+                track_id: track_id.clone(),
+            })
+            .await;
+
+        let app = network.app.read().await;
+        assert!(!app.liked_song_ids_set.contains(&track_id));
+        assert!(app.pending_track_saves.is_empty());
+        assert_eq!(app.get_current_route().id, RouteId::Error);
+    }
+
+    #[tokio::test]
+    async fn failed_requests_show_the_error_screen() {
+        let (mut network, _) = mock_api::network_with_status(|path| {
+            path.starts_with("/me/player/devices")
+                .then(|| (403, String::from("{}")))
+        })
+        .await;
+        network
+            .handle_network_event(IoEvent::GetDevices { silent: false })
+            .await;
+
+        let app = network.app.read().await;
+        assert_eq!(app.get_current_route().id, RouteId::Error);
+        assert!(app.devices.is_none());
+        assert!(!app.is_loading);
+    }
+
+    #[tokio::test]
+    async fn shows_top_items_a_page_at_a_time() {
+        let (mut network, requests) = mock_api::network_with(|path| {
+            let items = if path.starts_with("/me/top/tracks") {
+                json!([{
+                    "album": { "artists": [], "external_urls": {}, "images": [], "name": "Album" },
+                    "artists": [],
+                    "disc_number": 1,
+                    "duration_ms": 180_000,
+                    "explicit": false,
+                    "external_ids": {},
+                    "external_urls": {},
+                    "id": "4iV5W9uYEdYUVa79Axb7Rh",
+                    "is_local": false,
+                    "name": "Most played",
+                    "popularity": 0,
+                    "track_number": 1,
+                }])
+            } else if path.starts_with("/me/top/artists") {
+                json!([{
+                    "external_urls": {},
+                    "followers": { "href": null, "total": 0 },
+                    "genres": [],
+                    "href": "",
+                    "id": "0OdUWJ0sBjDrqHygGUXeCF",
+                    "images": [],
+                    "name": "Band of Horses",
+                    "popularity": 0,
+                }])
+            } else {
+                return None;
+            };
+            Some(
+                json!({
+                    "href": "", "items": items, "limit": 20, "next": null, "offset": 20,
+                    "previous": null, "total": 21,
+                })
+                .to_string(),
+            )
+        })
+        .await;
+
+        network
+            .get_user_top_items(TopItemKind::Tracks, TimeRange::ShortTerm, 20)
+            .await;
+        {
+            let app = network.app.read().await;
+            assert_eq!(app.item_table.context, Some(ItemTableContext::TopTracks));
+            assert_eq!(app.item_table.items.len(), 1);
+            assert_eq!(app.top_items.previous_offset(), Some(0));
+            assert_eq!(app.top_items.next_offset(), None);
+        }
+        let path = requests.lock().unwrap().pop().unwrap();
+        assert!(path.starts_with("/me/top/tracks?"), "{}", path);
+        assert!(path.contains("time_range=short_term"), "{}", path);
+        assert!(path.contains("offset=20"), "{}", path);
+
+        // A page for a range that isn't shown anymore is dropped
+        network
+            .get_user_top_items(TopItemKind::Artists, TimeRange::LongTerm, 0)
+            .await;
+        assert!(network.app.read().await.top_items.artists.is_none());
+
+        network.app.write().await.top_items.kind = TopItemKind::Artists;
+        network
+            .get_user_top_items(TopItemKind::Artists, TimeRange::ShortTerm, 0)
+            .await;
+        let app = network.app.read().await;
+        assert_eq!(
+            app.selected_top_artist().map(|artist| artist.name.as_str()),
+            Some("Band of Horses")
+        );
+    }
+
+    #[tokio::test]
+    async fn filters_artist_albums_by_group() {
+        let (mut network, requests) = mock_api::network_with(|path| {
+            if !path.starts_with("/artists/0OdUWJ0sBjDrqHygGUXeCF/albums") {
+                return None;
+            }
+            Some(
+                json!({
+                    "href": "",
+                    "items": [{
+                        "artists": [], "available_markets": [], "external_urls": {}, "href": null,
+                        "id": "6akEvsycLGftJxYudPjmqK", "images": [], "name": "Single",
+                        "release_date": null, "release_date_precision": null,
+                    }],
+                    "limit": 20, "next": null, "offset": 0, "previous": null, "total": 1,
+                })
+                .to_string(),
+            )
+        })
+        .await;
+        let artist_id = ArtistId::from_id("0OdUWJ0sBjDrqHygGUXeCF").unwrap();
+        network.app.write().await.artist = Some(Artist {
+            artist_id: artist_id.clone(),
+            artist_name: String::from("Band of Horses"),
+            albums: ScrollableResultPages::default(),
+            album_type: Some(AlbumType::Single),
+            pending_albums_offset: Some(0),
+            related_artists: vec![],
+            top_tracks: vec![],
+            selected_album_index: 0,
+            selected_related_artist_index: 0,
+            selected_top_track_index: 0,
+            artist_hovered_block: ArtistBlock::Albums,
+            artist_selected_block: ArtistBlock::Albums,
+        });
+
+        // A page of the group shown before the filter changed is dropped
+        network
+            .get_artist_albums(artist_id.clone(), 0, None, None)
+            .await;
+        assert!(network
+            .app
+            .read()
+            .await
+            .artist
+            .as_ref()
+            .unwrap()
+            .albums()
+            .is_empty());
+
+        network
+            .get_artist_albums(artist_id, 0, Some(AlbumType::Single), None)
+            .await;
+        let path = requests.lock().unwrap().pop().unwrap();
+        assert!(path.contains("include_groups=single"), "{}", path);
+        let app = network.app.read().await;
+        let artist = app.artist.as_ref().unwrap();
+        assert_eq!(artist.albums().len(), 1);
+        assert_eq!(artist.pending_albums_offset, None);
+    }
+
+    #[test]
+    fn unescapes_descriptions() {
+        assert_eq!(
+            unescape_html("Rock &amp; roll, &quot;live&quot; &#x2F; it&#x27;s &lt;3 &amp;amp;"),
+            "Rock & roll, \"live\" / it's <3 &amp;"
+        );
+    }
+
+    #[tokio::test]
+    async fn dedups_recently_played_when_enabled() {
+        let (mut network, _) = mock_api::network_with(|path| {
+            path.strip_prefix("/me/player/recently-played")?;
+            let items = ["track1", "track1", "track2", "track1"]
+                .iter()
+                .map(|id| {
+                    json!({
+                        "track": {
+                            "album": {
+                                "artists": [], "external_urls": {}, "images": [], "name": "Album"
+                            },
+                            "artists": [],
+                            "disc_number": 1,
+                            "duration_ms": 180_000,
+                            "explicit": false,
+                            "external_ids": {},
+                            "external_urls": {},
+                            "id": id,
+                            "is_local": false,
+                            "name": id,
+                            "popularity": 0,
+                            "track_number": 1,
+                        },
+                        "played_at": "2024-01-01T00:00:00Z",
+                        "context": null,
+                    })
+                })
+                .collect::<Vec<_>>();
+            Some(json!({ "href": "", "items": items, "limit": 50, "next": null }).to_string())
+        })
+        .await;
+        let names = |app: &App| {
+            app.recently_played
+                .result
+                .as_ref()
+                .unwrap()
+                .items
+                .iter()
+                .map(|item| item.track.name.clone())
+                .collect::<Vec<_>>()
+        };
+
+        network.get_recently_played().await;
+        assert_eq!(names(&*network.app.read().await).len(), 4);
+
+        network
+            .app
+            .write()
+            .await
+            .user_config
+            .behavior
+            .dedup_recently_played = true;
+        network.get_recently_played().await;
+        // Only plays in a row are collapsed
+        assert_eq!(
+            names(&*network.app.read().await),
+            ["track1", "track2", "track1"]
+        );
+    }
+
+    #[tokio::test]
+    async fn jumps_to_the_page_of_a_playlist_item() {
+        // Three pages of 20 tracks, named by their position
+        let (mut network, requests) = mock_api::network_with(|path| {
+            let offset = path
+                .strip_prefix("/playlists/37i9dQZF1DXcBWIGoYBM5M/tracks?")?
+                .split('&')
+                .find_map(|param| param.strip_prefix("offset="))?
+                .parse::<u32>()
+                .ok()?;
+            let items = (offset..(offset + 20).min(45))
+                .map(|position| {
+                    json!({
+                        "added_at": null,
+                        "is_local": false,
+                        "track": {
+                            "album": {
+                                "artists": [], "external_urls": {}, "images": [], "name": "Album"
+                            },
+                            "artists": [],
+                            "disc_number": 1,
+                            "duration_ms": 180_000,
+                            "explicit": false,
+                            "external_ids": {},
+                            "external_urls": {},
+                            "id": format!("track{}", position),
+                            "is_local": false,
+                            "name": format!("Track {}", position),
+                            "popularity": 0,
+                            "track_number": 1,
+                        },
+                    })
+                })
+                .collect::<Vec<_>>();
+            let next = (offset + 20 < 45).then_some("next");
+            Some(
+                json!({
+                    "href": "", "items": items, "limit": 20, "next": next, "offset": offset,
+                    "previous": null, "total": 45
+                })
+                .to_string(),
+            )
+        })
+        .await;
+        let playlist_id = PlaylistId::from_id("37i9dQZF1DXcBWIGoYBM5M").unwrap();
+        {
+            let mut app = network.app.write().await;
+            app.playlists = Some(
+                serde_json::from_value(json!({
+                    "href": "", "limit": 20, "next": null, "offset": 0, "previous": null,
+                    "total": 1,
+                    "items": [{
+                        "collaborative": false,
+                        "external_urls": {},
+                        "href": "",
+                        "id": playlist_id.id(),
+                        "images": [],
+                        "name": "Mix",
+                        "owner": { "external_urls": {}, "href": "", "id": "owner" },
+                        "snapshot_id": "",
+                        "tracks": { "href": "", "total": 45 },
+                    }],
+                }))
+                .unwrap(),
+            );
+            app.active_playlist_index = Some(0);
+            app.item_table.context = Some(ItemTableContext::MyPlaylists);
+        }
+
+        let track_id = TrackId::from_id("track23").unwrap();
+        network
+            .jump_to_playlist_item(playlist_id.clone(), track_id.into())
+            .await;
+        assert_eq!(requests.lock().unwrap().len(), 2);
+        {
+            let app = network.app.read().await;
+            assert_eq!(app.playlist_offset, 20);
+            assert_eq!(app.item_table.selected_index, 3);
+            assert_eq!(
+                app.item_table.items[app.item_table.selected_index].name(),
+                "Track 23"
+            );
+        }
+
+        // Every page is looked through before giving up
+        let track_id = TrackId::from_id("elsewhere").unwrap();
+        network
+            .jump_to_playlist_item(playlist_id, track_id.into())
+            .await;
+        assert_eq!(requests.lock().unwrap().len(), 5);
+        let app = network.app.read().await;
+        assert_eq!(app.playlist_offset, 20);
+        assert_eq!(
+            app.notifications.current().unwrap().message,
+            "What's playing isn't in this playlist"
+        );
+    }
+
+    #[tokio::test]
+    async fn controls_need_a_device() {
+        let (mut network, requests) = mock_api::network().await;
+        network.seek(1_000).await;
+        network.next_track().await;
+        assert!(requests.lock().unwrap().is_empty());
+        let app = network.app.read().await;
+        assert_eq!(
+            app.notifications.current().unwrap().message,
+            "No active device — press d to select one"
+        );
+    }
+
+    #[tokio::test]
+    async fn polls_back_off_while_nothing_plays() {
+        let playing = Arc::new(AtomicBool::new(false));
+        let is_playing = playing.clone();
+        let (mut network, _requests) = mock_api::network_with_status(move |path| {
+            if !path.starts_with("/me/player?") {
+                return None;
+            }
+            // Spotify answers without a body when there's no playback
+            Some(if is_playing.load(Ordering::SeqCst) {
+                (200, mock_api::playback().to_string())
+            } else {
+                (204, String::new())
+            })
+        })
+        .await;
+
+        network.get_current_playback().await;
+        network.get_current_playback().await;
+        {
+            let app = network.app.read().await;
+            assert_eq!(app.idle_playback_polls, 2);
+            assert_eq!(
+                app.playback_poll_interval(),
+                std::time::Duration::from_secs(20)
+            );
+        }
+
+        playing.store(true, Ordering::SeqCst);
+        network.get_current_playback().await;
+        assert_eq!(network.app.read().await.idle_playback_polls, 0);
+    }
+
+    #[tokio::test]
+    async fn controls_act_on_the_picked_device_or_else_the_active_one() {
+        let (mut network, requests) = mock_api::network().await;
+        network.client_config.device_id = Some(String::from("picked"));
+        network.change_volume(40).await;
+        assert!(requests.lock().unwrap()[0].contains("device_id=picked"));
+
+        // The active device is saved as picked the first time it's used
+        let config_dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            config_dir.path().join(crate::paths::CLIENT_CONFIG_FILE),
+            "client_id: id\nclient_secret: secret\ndevice_id: ~\nport: ~\n",
+        )
+        .unwrap();
+        std::env::set_var("SPOTIFY_TUI_CONFIG_DIR", config_dir.path());
+        let (mut network, requests) = mock_api::network().await;
+        network.app.write().await.current_playback_context =
+            Some(serde_json::from_value(mock_api::playback()).unwrap());
+        network.repeat(RepeatState::Track).await;
+        std::env::remove_var("SPOTIFY_TUI_CONFIG_DIR");
+
+        assert!(requests.lock().unwrap()[0].contains("device_id=active"));
+        assert_eq!(network.client_config.device_id.as_deref(), Some("active"));
+        assert_eq!(
+            network.app.read().await.saved_device_id.as_deref(),
+            Some("active")
+        );
+        let config =
+            std::fs::read_to_string(config_dir.path().join(crate::paths::CLIENT_CONFIG_FILE))
+                .unwrap();
+        assert!(config.contains("device_id: active"), "{}", config);
+
+        let app = network.app.read().await;
+        assert_eq!(
+            app.current_playback_context.as_ref().unwrap().repeat_state,
+            RepeatState::Track
+        );
+        assert_eq!(
+            app.notifications.current().unwrap().message,
+            "Repeat: Track"
+        );
+    }
+
+    #[test]
+    fn playback_offsets() {
+        assert_eq!(
+            PlaybackOffset::Index(3).into_offset(),
+            Offset::Position(Duration::milliseconds(3))
+        );
+        let track_id = TrackId::from_id("4iV5W9uYEdYUVa79Axb7Rh").unwrap();
+        assert_eq!(
+            PlaybackOffset::Item(PlayableId::Track(track_id)).into_offset(),
+            Offset::Uri(String::from("spotify:track:4iV5W9uYEdYUVa79Axb7Rh"))
+        );
+    }
+
+    // Offsets are made in PlaybackOffset::into_offset only, anything else goes through it
+    #[test]
+    fn rspotify_offsets_are_only_made_in_one_place() {
+        fn sources(dir: &std::path::Path, files: &mut Vec<std::path::PathBuf>) {
+            for entry in std::fs::read_dir(dir).unwrap() {
+                let path = entry.unwrap().path();
+                if path.is_dir() {
+                    sources(&path, files);
+                } else if path.extension().is_some_and(|extension| extension == "rs") {
+                    files.push(path);
+                }
+            }
+        }
+        let mut files = vec![];
+        sources(
+            &std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src"),
+            &mut files,
+        );
+
+        for variant in ["Offset::Position(", "Offset::Uri("] {
+            let uses = files
+                .iter()
+                .flat_map(|path| {
+                    let source = std::fs::read_to_string(path).unwrap();
+                    // Tests are at the end of each file, and may make Offsets to compare against
+                    let code = source.split("#[cfg(test)]").next().unwrap();
+                    vec![path; code.matches(variant).count()]
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(uses.len(), 1, "{} is made in {:?}", variant, uses);
+        }
+    }
+
+    #[test]
+    fn audio_features_are_fetched_in_chunks_of_100() {
+        let track_ids = (0..250)
+            .map(|i| TrackId::from_id(format!("{i:022}")).unwrap())
+            .collect::<Vec<_>>();
+
+        let chunk_sizes = audio_features_chunks(&track_ids)
+            .map(<[_]>::len)
+            .collect::<Vec<_>>();
+
+        assert_eq!(chunk_sizes, vec![100, 100, 50]);
+        assert_eq!(audio_features_chunks(&[]).count(), 0);
+    }
+
+    #[test]
+    fn classifies_every_event() {
+        let track_id = || TrackId::from_id("4iV5W9uYEdYUVa79Axb7Rh").unwrap();
+        let album_id = || AlbumId::from_id("4aawyAB9vmqN3uQ7FjRGTy").unwrap();
+        let artist_id = || ArtistId::from_id("0TnOYISbd1XYRBk9myaseg").unwrap();
+        let playlist_id = || PlaylistId::from_id("37i9dQZF1DXcBWIGoYBM5M").unwrap();
+        let show_id = || ShowId::from_id("4rOoJ6Egrf8K2IrywzwOMk").unwrap();
+        let episode_id = || EpisodeId::from_id("512ojhOuo1ktJprKbVcKyQ").unwrap();
+        let show: SimplifiedShow = serde_json::from_value(serde_json::json!({
+            "available_markets": [],
+            "copyrights": [],
+            "description": "",
+            "explicit": false,
+            "external_urls": {},
+            "href": "",
+            "id": "4rOoJ6Egrf8K2IrywzwOMk",
+            "images": [],
+            "languages": [],
+            "media_type": "audio",
+            "name": "Podcast",
+            "publisher": "",
+        }))
+        .unwrap();
+
+        let mutating = [
+            IoEvent::AddContextToQueue {
+                play_context_id: PlayContextId::Album(album_id()),
+                limit: 100,
+            },
+            IoEvent::AddItemToQueue {
+                playable_id: PlayableId::Track(track_id()),
+            },
+            IoEvent::ChangePlaylistDetails {
+                playlist_id: playlist_id(),
+                name: Some(String::from("Renamed")),
+                description: None,
+            },
+            IoEvent::ChangeVolume { volume: 50 },
+            IoEvent::CurrentUserSavedAlbumAdd {
+                album_id: album_id(),
+            },
+            IoEvent::CurrentUserSavedAlbumDelete {
+                album_id: album_id(),
+            },
+            IoEvent::CurrentUserSavedShowAdd { show_id: show_id() },
+            IoEvent::CurrentUserSavedShowDelete { show_id: show_id() },
+            IoEvent::NextTrack,
+            IoEvent::PausePlayback,
+            IoEvent::PreviousTrack,
+            IoEvent::RemoveSavedShows {
+                show_ids: vec![show_id()],
+            },
+            IoEvent::Repeat {
+                state: RepeatState::Off,
+            },
+            IoEvent::ResumeEpisodePlayback {
+                playable_ids: vec![PlayableId::Episode(episode_id())],
+                offset: PlaybackOffset::Index(0),
+                position_ms: 60_000,
+            },
+            IoEvent::ResumePlayback,
+            IoEvent::Seek { position_ms: 0 },
+            IoEvent::StartContextPlayback {
+                play_context_id: PlayContextId::Album(album_id()),
+                offset: None,
+            },
+            IoEvent::StartPlayablesPlayback {
+                playable_ids: vec![PlayableId::Track(track_id())],
+                offset: None,
+            },
+            IoEvent::ToggleSaveEpisode {
+                episode_id: episode_id(),
+            },
+            IoEvent::ToggleSaveTrack {
+                track_id: track_id(),
+            },
+            IoEvent::ToggleShuffle,
+            IoEvent::TransferPlaybackToDevice {
+                device_id: String::from("device"),
+            },
+            IoEvent::UserUnfollowArtists {
+                artist_ids: vec![artist_id()],
+            },
+            IoEvent::UserFollowArtists {
+                artist_ids: vec![artist_id()],
+            },
+            IoEvent::UserFollowPlaylist {
+                playlist_id: playlist_id(),
+                is_public: None,
+            },
+            IoEvent::UserUnfollowPlaylist {
+                playlist_id: playlist_id(),
+            },
+        ];
+        let read_only = [
+            IoEvent::CurrentUserSavedAlbumsContains {
+                album_ids: vec![album_id()],
+            },
+            IoEvent::CurrentUserSavedEpisodesContains {
+                episode_ids: vec![episode_id()],
+            },
+            IoEvent::CurrentUserSavedShowsContains {
+                show_ids: vec![show_id()],
+            },
+            IoEvent::CurrentUserSavedTracksContains {
+                track_ids: vec![track_id()],
+            },
+            IoEvent::FindFinishedShows,
+            IoEvent::GetAlbum {
+                album_id: album_id(),
+            },
+            IoEvent::GetAlbumForTrack {
+                track_id: track_id(),
+            },
+            IoEvent::GetAlbumTracks {
+                album: Box::default(),
+            },
+            IoEvent::GetArtist {
+                artist_id: artist_id(),
+                input_artist_name: String::from("Artist"),
+                country: None,
+            },
+            IoEvent::GetArtistAlbums {
+                artist_id: artist_id(),
+                offset: 20,
+                album_type: Some(AlbumType::Single),
+                country: None,
+            },
+            IoEvent::GetTrackAnalysis {
+                track_id: track_id(),
+            },
+            IoEvent::GetTrackFeatures {
+                track_id: track_id(),
+            },
+            IoEvent::GetCurrentPlayback,
+            IoEvent::GetCurrentShowEpisodes {
+                show_id: show_id(),
+                offset: None,
+            },
+            IoEvent::GetCurrentUserSavedAlbums { offset: None },
+            IoEvent::GetCurrentUserSavedShows { offset: None },
+            IoEvent::GetCurrentUserSavedTracks { offset: None },
+            IoEvent::GetAlbumArt {
+                url: String::from("https://i.scdn.co/image/1"),
+            },
+            IoEvent::GetAudioFeatures {
+                track_ids: vec![track_id()],
+            },
+            IoEvent::GetAllCurrentUserSavedTracks { limit: None },
+            IoEvent::GetDevices { silent: true },
+            IoEvent::GetFollowedArtists { after: None },
+            IoEvent::GetLyrics {
+                track_id: track_id(),
+                artist: String::from("Artist"),
+                title: String::from("Song"),
+            },
+            IoEvent::GetMadeForYouPlaylistItems {
+                playlist_id: playlist_id(),
+                offset: 0,
+            },
+            IoEvent::GetPlaylists,
+            IoEvent::GetPlaylistDescription {
+                playlist_id: playlist_id(),
+            },
+            IoEvent::GetPlaylistItems {
+                playlist_id: playlist_id(),
+                offset: 0,
+            },
+            IoEvent::JumpToPlaylistItem {
+                playlist_id: playlist_id(),
+                playable_id: track_id().into(),
+            },
+            IoEvent::GetRecentlyPlayed,
+            IoEvent::GetRecommendationsForSeed {
+                seed_artist_ids: None,
+                seed_track_ids: Some(vec![track_id()]),
+                first_track: Box::new(None),
+                country: None,
+            },
+            IoEvent::GetRecommendationsForLikedSongs { country: None },
+            IoEvent::GetRecommendationsForTrackId {
+                track_id: track_id(),
+                country: None,
+            },
+            IoEvent::GetSearchResults {
+                search_term: String::from("song"),
+                types: None,
+                country: None,
+            },
+            IoEvent::GetMoreSearchResults {
+                search_term: String::from("song"),
+                search_type: SearchType::Track,
+                offset: 4,
+                country: None,
+            },
+            IoEvent::GetShow { show_id: show_id() },
+            IoEvent::GetShowEpisodes {
+                show: Box::new(show),
+            },
+            IoEvent::GetUser,
+            IoEvent::GetUserTopItems {
+                kind: TopItemKind::Tracks,
+                time_range: TimeRange::ShortTerm,
+                offset: 0,
+            },
+            IoEvent::MadeForYouSearchAndAdd {
+                search_term: String::from("Discover Weekly"),
+                country: None,
+            },
+            IoEvent::LoadLibraryCache,
+            IoEvent::RefreshAuthentication,
+            IoEvent::SaveLibraryCache,
+            IoEvent::SetArtistsToTable { artists: vec![] },
+            IoEvent::SetTracksToTable { tracks: vec![] },
+            IoEvent::UpdateSearchLimits {
+                large_search_limit: 20,
+                small_search_limit: 4,
+            },
+            IoEvent::UserArtistFollowCheck {
+                artist_ids: vec![artist_id()],
+            },
+        ];
+
+        for event in &mutating {
+            assert!(event.is_mutating(), "{:?}", event);
+        }
+        for event in &read_only {
+            assert!(!event.is_mutating(), "{:?}", event);
         }
     }
 }
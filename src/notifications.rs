@@ -0,0 +1,274 @@
+use chrono::{DateTime, Local};
+use std::time::Duration;
+
+// How long the latest notification is shown in the help block
+const NOTIFICATION_DURATION: Duration = Duration::from_secs(5);
+// Repeats of the latest notification within this window are counted instead of stacked
+const DEDUP_WINDOW: Duration = Duration::from_secs(10);
+const HISTORY_CAPACITY: usize = 100;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Info => "Info",
+            Severity::Warning => "Warning",
+            Severity::Error => "Error",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Notification {
+    pub message: String,
+    pub severity: Severity,
+    // How many times the message was repeated in a row
+    pub count: usize,
+    // When it was last shown, refreshed by repeats
+    pub timestamp: DateTime<Local>,
+}
+
+impl Notification {
+    pub fn text(&self) -> String {
+        if self.count > 1 {
+            format!("{} (×{})", self.message, self.count)
+        } else {
+            self.message.clone()
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum DoNotDisturb {
+    #[default]
+    Off,
+    On,
+    // Turns itself off on the first tick after this
+    Until(DateTime<Local>),
+}
+
+// The last notifications, oldest first
+#[derive(Default)]
+pub struct Notifications {
+    history: Vec<Notification>,
+    // Whether the latest notification is still shown
+    is_shown: bool,
+    do_not_disturb: DoNotDisturb,
+}
+
+fn elapsed(since: DateTime<Local>, now: DateTime<Local>) -> Duration {
+    (now - since).to_std().unwrap_or_default()
+}
+
+impl Notifications {
+    // While do not disturb is on only errors are shown, everything else just goes to the history
+    pub fn push(&mut self, severity: Severity, message: String, now: DateTime<Local>) {
+        self.is_shown = severity == Severity::Error || !self.is_do_not_disturb();
+
+        if let Some(latest) = self.history.last_mut() {
+            if latest.message == message
+                && latest.severity == severity
+                && elapsed(latest.timestamp, now) < DEDUP_WINDOW
+            {
+                latest.count += 1;
+                latest.timestamp = now;
+                return;
+            }
+        }
+
+        self.history.push(Notification {
+            message,
+            severity,
+            count: 1,
+            timestamp: now,
+        });
+        if self.history.len() > HISTORY_CAPACITY {
+            let excess = self.history.len() - HISTORY_CAPACITY;
+            self.history.drain(..excess);
+        }
+    }
+
+    // The notification to show, if the latest one hasn't expired yet
+    pub fn current(&self) -> Option<&Notification> {
+        self.history.last().filter(|_| self.is_shown)
+    }
+
+    pub fn expire(&mut self, now: DateTime<Local>) {
+        if matches!(self.do_not_disturb, DoNotDisturb::Until(until) if until <= now) {
+            self.do_not_disturb = DoNotDisturb::Off;
+        }
+        if self
+            .history
+            .last()
+            .is_some_and(|latest| elapsed(latest.timestamp, now) >= NOTIFICATION_DURATION)
+        {
+            self.is_shown = false;
+        }
+    }
+
+    pub fn do_not_disturb(&self) -> DoNotDisturb {
+        self.do_not_disturb
+    }
+
+    pub fn is_do_not_disturb(&self) -> bool {
+        self.do_not_disturb != DoNotDisturb::Off
+    }
+
+    pub fn set_do_not_disturb(&mut self, do_not_disturb: DoNotDisturb) {
+        self.do_not_disturb = do_not_disturb;
+        // Whatever was shown when it was turned on goes away with it
+        if self.is_do_not_disturb()
+            && self
+                .history
+                .last()
+                .is_some_and(|latest| latest.severity != Severity::Error)
+        {
+            self.is_shown = false;
+        }
+    }
+
+    pub fn history(&self) -> &[Notification] {
+        &self.history
+    }
+
+    // Newest first, which is how the history is listed
+    pub fn get_newest(&self, index: usize) -> Option<&Notification> {
+        self.history.iter().rev().nth(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeDelta;
+
+    fn at(seconds: i64) -> DateTime<Local> {
+        DateTime::from_timestamp(1_700_000_000, 0)
+            .unwrap()
+            .with_timezone(&Local)
+            + TimeDelta::seconds(seconds)
+    }
+
+    #[test]
+    fn repeats_are_counted() {
+        let mut notifications = Notifications::default();
+
+        notifications.push(Severity::Error, String::from("Volume failed"), at(0));
+        notifications.push(Severity::Error, String::from("Volume failed"), at(4));
+        notifications.push(Severity::Error, String::from("Volume failed"), at(12));
+
+        assert_eq!(notifications.history().len(), 1);
+        let current = notifications.current().unwrap();
+        assert_eq!(current.count, 3);
+        assert_eq!(current.timestamp, at(12));
+        assert_eq!(current.text(), "Volume failed (×3)");
+    }
+
+    #[test]
+    fn stacks_outside_the_window_or_when_different() {
+        let mut notifications = Notifications::default();
+
+        notifications.push(Severity::Error, String::from("Volume failed"), at(0));
+        notifications.push(Severity::Error, String::from("Volume failed"), at(10));
+        notifications.push(Severity::Warning, String::from("Volume failed"), at(11));
+        notifications.push(Severity::Warning, String::from("Seek failed"), at(12));
+        // Only consecutive repeats are counted
+        notifications.push(Severity::Warning, String::from("Volume failed"), at(13));
+
+        assert_eq!(notifications.history().len(), 5);
+        assert!(notifications.history().iter().all(|n| n.count == 1));
+        assert_eq!(notifications.get_newest(1).unwrap().message, "Seek failed");
+        assert_eq!(notifications.current().unwrap().text(), "Volume failed");
+    }
+
+    #[test]
+    fn history_is_bounded() {
+        let mut notifications = Notifications::default();
+
+        for i in 0..(HISTORY_CAPACITY + 5) {
+            notifications.push(Severity::Info, i.to_string(), at(i as i64));
+        }
+
+        assert_eq!(notifications.history().len(), HISTORY_CAPACITY);
+        assert_eq!(notifications.history()[0].message, "5");
+        assert_eq!(
+            notifications.get_newest(0).unwrap().message,
+            (HISTORY_CAPACITY + 4).to_string()
+        );
+        assert!(notifications.get_newest(HISTORY_CAPACITY).is_none());
+    }
+
+    #[test]
+    fn expires_and_shows_again_on_repeat() {
+        let mut notifications = Notifications::default();
+        assert!(notifications.current().is_none());
+
+        notifications.push(Severity::Info, String::from("Copied"), at(0));
+        notifications.expire(at(4));
+        assert!(notifications.current().is_some());
+
+        notifications.expire(at(5));
+        assert!(notifications.current().is_none());
+        // Still in the history
+        assert_eq!(notifications.history().len(), 1);
+
+        notifications.push(Severity::Info, String::from("Copied"), at(6));
+        assert_eq!(notifications.current().unwrap().count, 2);
+        notifications.expire(at(10));
+        assert!(notifications.current().is_some());
+    }
+
+    #[test]
+    fn do_not_disturb_only_shows_errors() {
+        let mut notifications = Notifications::default();
+
+        notifications.set_do_not_disturb(DoNotDisturb::On);
+        notifications.push(Severity::Info, String::from("Copied"), at(0));
+        notifications.push(Severity::Warning, String::from("Skipped"), at(1));
+        assert!(notifications.current().is_none());
+        // Still in the history
+        assert_eq!(notifications.history().len(), 2);
+
+        notifications.push(Severity::Error, String::from("Volume failed"), at(2));
+        assert_eq!(notifications.current().unwrap().message, "Volume failed");
+
+        notifications.set_do_not_disturb(DoNotDisturb::Off);
+        notifications.push(Severity::Info, String::from("Copied"), at(3));
+        assert_eq!(notifications.current().unwrap().message, "Copied");
+    }
+
+    #[test]
+    fn do_not_disturb_expires_on_tick() {
+        let mut notifications = Notifications::default();
+
+        notifications.set_do_not_disturb(DoNotDisturb::Until(at(60)));
+        notifications.expire(at(59));
+        notifications.push(Severity::Info, String::from("Copied"), at(59));
+        assert!(notifications.current().is_none());
+
+        notifications.expire(at(60));
+        assert_eq!(notifications.do_not_disturb(), DoNotDisturb::Off);
+        notifications.push(Severity::Info, String::from("Saved"), at(61));
+        assert_eq!(notifications.current().unwrap().message, "Saved");
+    }
+
+    #[test]
+    fn turning_do_not_disturb_on_hides_the_current_notification() {
+        let mut notifications = Notifications::default();
+
+        notifications.push(Severity::Info, String::from("Copied"), at(0));
+        notifications.set_do_not_disturb(DoNotDisturb::On);
+        assert!(notifications.current().is_none());
+
+        notifications.set_do_not_disturb(DoNotDisturb::Off);
+        notifications.push(Severity::Error, String::from("Volume failed"), at(1));
+        notifications.set_do_not_disturb(DoNotDisturb::On);
+        assert!(notifications.current().is_some());
+    }
+}
@@ -0,0 +1,336 @@
+use anyhow::{anyhow, Result};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+const APP_DIR: &str = "spotify-tui";
+// Overrides both the config and cache directories, e.g. for portable installs
+const CONFIG_DIR_VAR: &str = "SPOTIFY_TUI_CONFIG_DIR";
+// Where everything was stored before the platform directories were used
+const LEGACY_CONFIG_DIR: &str = ".config";
+const MIGRATION_NOTE_FILE: &str = "MIGRATED.txt";
+const CONTROL_SOCKET_FILE: &str = "spotify-tui.sock";
+const INSTANCE_LOCK_FILE: &str = "spotify-tui.lock";
+
+pub const CLIENT_CONFIG_FILE: &str = "client.yml";
+pub const USER_CONFIG_FILE: &str = "config.yml";
+pub const TOKEN_CACHE_FILE: &str = ".spotify_token_cache.json";
+pub const HISTORY_FILE: &str = "history.jsonl";
+pub const LIBRARY_CACHE_FILE: &str = "library.json";
+pub const STARTUP_CACHE_FILE: &str = "startup.json";
+pub const UPDATE_CHECK_FILE: &str = "update_check.json";
+pub const DEBUG_DUMP_DIR: &str = "debug";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AppDirs {
+    pub config_dir: PathBuf,
+    pub cache_dir: PathBuf,
+}
+
+// Resolve the app's directories, creating them and copying over files from the legacy location
+// the first time round
+pub fn app_dirs() -> Result<AppDirs> {
+    let home = dirs::home_dir();
+    let app_dirs = resolve_app_dirs(|name| env::var(name).ok(), home.as_deref())?;
+
+    fs::create_dir_all(&app_dirs.config_dir)?;
+    fs::create_dir_all(&app_dirs.cache_dir)?;
+
+    if let Some(home) = home {
+        migrate_legacy_files(&home.join(LEGACY_CONFIG_DIR).join(APP_DIR), &app_dirs)?;
+    }
+
+    Ok(app_dirs)
+}
+
+// For the CLI help, resolved without creating or migrating anything
+pub fn client_config_help() -> String {
+    let home = dirs::home_dir();
+    config_help(resolve_app_dirs(|name| env::var(name).ok(), home.as_deref()).ok())
+}
+
+fn config_help(app_dirs: Option<AppDirs>) -> String {
+    let config_file = match app_dirs {
+        Some(app_dirs) => app_dirs
+            .config_dir
+            .join(CLIENT_CONFIG_FILE)
+            .display()
+            .to_string(),
+        None => format!("{} in the platform's config directory", CLIENT_CONFIG_FILE),
+    };
+    format!(
+        "Your spotify Client ID and Client Secret are stored in {}. Set ${} to keep the config \
+         and cache in another directory.",
+        config_file, CONFIG_DIR_VAR
+    )
+}
+
+pub fn control_socket_path() -> Result<PathBuf> {
+    Ok(private_runtime_dir(dirs::runtime_dir(), app_dirs)?.join(CONTROL_SOCKET_FILE))
+}
+
+pub fn instance_lock_path() -> Result<PathBuf> {
+    Ok(private_runtime_dir(dirs::runtime_dir(), app_dirs)?.join(INSTANCE_LOCK_FILE))
+}
+
+// The runtime directory, or without one, e.g. over ssh or in cron, the app's cache directory. Both
+// belong to the user, where the temp directory is shared with every other user on the machine.
+fn private_runtime_dir(
+    runtime_dir: Option<PathBuf>,
+    app_dirs: impl FnOnce() -> Result<AppDirs>,
+) -> Result<PathBuf> {
+    match runtime_dir {
+        Some(dir) => Ok(dir),
+        None => Ok(app_dirs()?.cache_dir),
+    }
+}
+
+fn resolve_app_dirs(var: impl Fn(&str) -> Option<String>, home: Option<&Path>) -> Result<AppDirs> {
+    if let Some(dir) = var(CONFIG_DIR_VAR).filter(|dir| !dir.is_empty()) {
+        return Ok(AppDirs {
+            config_dir: PathBuf::from(&dir),
+            cache_dir: PathBuf::from(dir),
+        });
+    }
+
+    let (config_base, cache_base) = platform_dirs(var, home)
+        .ok_or_else(|| anyhow!("No $HOME directory found for client config"))?;
+    Ok(AppDirs {
+        config_dir: config_base.join(APP_DIR),
+        cache_dir: cache_base.join(APP_DIR),
+    })
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn platform_dirs(
+    _var: impl Fn(&str) -> Option<String>,
+    _home: Option<&Path>,
+) -> Option<(PathBuf, PathBuf)> {
+    Some((dirs::config_dir()?, dirs::cache_dir()?))
+}
+
+// Follows the XDG base directory spec, which ignores relative paths
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn platform_dirs(
+    var: impl Fn(&str) -> Option<String>,
+    home: Option<&Path>,
+) -> Option<(PathBuf, PathBuf)> {
+    let xdg_dir = |name: &str, default: &str| {
+        var(name)
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute())
+            .or_else(|| home.map(|home| home.join(default)))
+    };
+    Some((
+        xdg_dir("XDG_CONFIG_HOME", ".config")?,
+        xdg_dir("XDG_CACHE_HOME", ".cache")?,
+    ))
+}
+
+// Copy files that are missing from their new location, leaving a note in the legacy directory
+// so that it is only done once and so that users know which old files are no longer read
+fn migrate_legacy_files(legacy_dir: &Path, app_dirs: &AppDirs) -> Result<()> {
+    if !legacy_dir.is_dir() || legacy_dir.join(MIGRATION_NOTE_FILE).exists() {
+        return Ok(());
+    }
+
+    let mut migrated = vec![];
+    for (file, dir) in [
+        (CLIENT_CONFIG_FILE, &app_dirs.config_dir),
+        (USER_CONFIG_FILE, &app_dirs.config_dir),
+        (TOKEN_CACHE_FILE, &app_dirs.cache_dir),
+    ] {
+        let from = legacy_dir.join(file);
+        let to = dir.join(file);
+        if from.is_file() && !to.exists() {
+            fs::copy(&from, &to)?;
+            migrated.push((file, to));
+        }
+    }
+
+    if !migrated.is_empty() {
+        let note = format!(
+            "spotify-tui copied these files to their new location, the copies in this directory \
+             are no longer read:\n\n{}\n",
+            migrated
+                .iter()
+                .map(|(file, to)| format!("{} -> {}", file, to.display()))
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+        fs::write(legacy_dir.join(MIGRATION_NOTE_FILE), note)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars = vars
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect::<Vec<_>>();
+        move |name| {
+            vars.iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.clone())
+        }
+    }
+
+    #[test]
+    fn override_takes_precedence() {
+        let dirs = resolve_app_dirs(
+            env(&[
+                (CONFIG_DIR_VAR, "/portable/spt"),
+                ("XDG_CONFIG_HOME", "/xdg/config"),
+            ]),
+            Some(Path::new("/home/user")),
+        )
+        .unwrap();
+
+        assert_eq!(
+            dirs,
+            AppDirs {
+                config_dir: PathBuf::from("/portable/spt"),
+                cache_dir: PathBuf::from("/portable/spt"),
+            }
+        );
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    #[test]
+    fn xdg_dirs_take_precedence_over_home() {
+        let dirs = resolve_app_dirs(
+            env(&[
+                ("XDG_CONFIG_HOME", "/xdg/config"),
+                ("XDG_CACHE_HOME", "/xdg/cache"),
+            ]),
+            Some(Path::new("/home/user")),
+        )
+        .unwrap();
+
+        assert_eq!(
+            dirs,
+            AppDirs {
+                config_dir: PathBuf::from("/xdg/config/spotify-tui"),
+                cache_dir: PathBuf::from("/xdg/cache/spotify-tui"),
+            }
+        );
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    #[test]
+    fn falls_back_to_home() {
+        // Empty and relative XDG paths are ignored
+        let dirs = resolve_app_dirs(
+            env(&[(CONFIG_DIR_VAR, ""), ("XDG_CACHE_HOME", "relative/cache")]),
+            Some(Path::new("/home/user")),
+        )
+        .unwrap();
+
+        assert_eq!(
+            dirs,
+            AppDirs {
+                config_dir: PathBuf::from("/home/user/.config/spotify-tui"),
+                cache_dir: PathBuf::from("/home/user/.cache/spotify-tui"),
+            }
+        );
+
+        assert!(resolve_app_dirs(env(&[]), None).is_err());
+    }
+
+    #[test]
+    fn help_names_the_resolved_config_file() {
+        let help = config_help(Some(AppDirs {
+            config_dir: PathBuf::from("/portable/spt"),
+            cache_dir: PathBuf::from("/portable/spt"),
+        }));
+        assert!(help.contains("/portable/spt/client.yml"), "{}", help);
+        assert!(help.contains("$SPOTIFY_TUI_CONFIG_DIR"), "{}", help);
+
+        let help = config_help(None);
+        assert!(
+            help.contains("client.yml in the platform's config directory"),
+            "{}",
+            help
+        );
+    }
+
+    #[test]
+    fn keeps_runtime_files_to_the_user() {
+        let app_dirs = || {
+            Ok(AppDirs {
+                config_dir: PathBuf::from("/home/user/.config/spotify-tui"),
+                cache_dir: PathBuf::from("/home/user/.cache/spotify-tui"),
+            })
+        };
+        assert_eq!(
+            private_runtime_dir(Some(PathBuf::from("/run/user/1000")), app_dirs).unwrap(),
+            PathBuf::from("/run/user/1000")
+        );
+        assert_eq!(
+            private_runtime_dir(None, app_dirs).unwrap(),
+            PathBuf::from("/home/user/.cache/spotify-tui")
+        );
+    }
+
+    #[test]
+    fn migrates_legacy_files_once() {
+        let legacy = TempDir::new().unwrap();
+        let config = TempDir::new().unwrap();
+        let cache = TempDir::new().unwrap();
+        let app_dirs = AppDirs {
+            config_dir: config.path().to_path_buf(),
+            cache_dir: cache.path().to_path_buf(),
+        };
+
+        fs::write(legacy.path().join(CLIENT_CONFIG_FILE), "client_id: legacy").unwrap();
+        fs::write(legacy.path().join(TOKEN_CACHE_FILE), "{}").unwrap();
+        // Files already at the new location are left alone
+        fs::write(legacy.path().join(USER_CONFIG_FILE), "legacy").unwrap();
+        fs::write(config.path().join(USER_CONFIG_FILE), "current").unwrap();
+
+        migrate_legacy_files(legacy.path(), &app_dirs).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(config.path().join(CLIENT_CONFIG_FILE)).unwrap(),
+            "client_id: legacy"
+        );
+        assert_eq!(
+            fs::read_to_string(cache.path().join(TOKEN_CACHE_FILE)).unwrap(),
+            "{}"
+        );
+        assert_eq!(
+            fs::read_to_string(config.path().join(USER_CONFIG_FILE)).unwrap(),
+            "current"
+        );
+        let note = fs::read_to_string(legacy.path().join(MIGRATION_NOTE_FILE)).unwrap();
+        assert!(note.contains(CLIENT_CONFIG_FILE));
+        assert!(!note.contains(USER_CONFIG_FILE));
+
+        // Once the note is there nothing gets copied again
+        fs::remove_file(config.path().join(CLIENT_CONFIG_FILE)).unwrap();
+        migrate_legacy_files(legacy.path(), &app_dirs).unwrap();
+        assert!(!config.path().join(CLIENT_CONFIG_FILE).exists());
+    }
+
+    #[test]
+    fn nothing_to_migrate() {
+        let legacy = TempDir::new().unwrap();
+        let config = TempDir::new().unwrap();
+        let app_dirs = AppDirs {
+            config_dir: config.path().to_path_buf(),
+            cache_dir: config.path().to_path_buf(),
+        };
+
+        migrate_legacy_files(legacy.path(), &app_dirs).unwrap();
+        assert!(!legacy.path().join(MIGRATION_NOTE_FILE).exists());
+
+        migrate_legacy_files(&legacy.path().join("missing"), &app_dirs).unwrap();
+    }
+}
@@ -0,0 +1,116 @@
+use std::time::Instant;
+
+// Where the playing item is up to. Some devices, such as certain Connect speakers, leave the
+// progress out of the playback while playing, so it carries on from the last known progress until
+// a poll has one again.
+#[derive(Clone, Debug, Default)]
+pub struct ProgressEstimate {
+    item_uri: Option<String>,
+    progress_ms: u128,
+    at: Option<Instant>,
+    is_approximate: bool,
+}
+
+impl ProgressEstimate {
+    // The progress of the item at `now`, from the progress the playback has for it when it has one
+    pub fn update(
+        &mut self,
+        item_uri: Option<String>,
+        known_ms: Option<u128>,
+        is_playing: bool,
+        duration_ms: u128,
+        now: Instant,
+    ) -> u128 {
+        let progress_ms = match known_ms {
+            Some(known_ms) => known_ms,
+            // Another item started without a progress for it
+            None if item_uri != self.item_uri => 0,
+            None if is_playing => {
+                let elapsed = self
+                    .at
+                    .map_or(0, |at| now.saturating_duration_since(at).as_millis());
+                self.progress_ms + elapsed
+            }
+            None => self.progress_ms,
+        }
+        .min(duration_ms);
+
+        self.item_uri = item_uri;
+        self.progress_ms = progress_ms;
+        self.at = Some(now);
+        self.is_approximate = known_ms.is_none();
+        progress_ms
+    }
+
+    // The progress shown is a guess, until a poll has the progress again
+    pub fn is_approximate(&self) -> bool {
+        self.is_approximate
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    const TRACK: &str = "spotify:track:4iV5W9uYEdYUVa79Axb7Rh";
+    const DURATION_MS: u128 = 200_000;
+
+    fn track() -> Option<String> {
+        Some(String::from(TRACK))
+    }
+
+    #[test]
+    fn carries_on_without_a_progress_then_resyncs() {
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let mut estimate = ProgressEstimate::default();
+
+        assert_eq!(
+            estimate.update(track(), Some(10_000), true, DURATION_MS, at(0)),
+            10_000
+        );
+        assert!(!estimate.is_approximate());
+
+        // Polls without a progress, each tick carrying on from the one before
+        assert_eq!(
+            estimate.update(track(), None, true, DURATION_MS, at(5)),
+            15_000
+        );
+        assert!(estimate.is_approximate());
+        assert_eq!(
+            estimate.update(track(), None, true, DURATION_MS, at(6)),
+            16_000
+        );
+
+        // Paused, so it stays put
+        assert_eq!(
+            estimate.update(track(), None, false, DURATION_MS, at(20)),
+            16_000
+        );
+
+        // A poll with the progress is taken as it is, however far the guess was off
+        assert_eq!(
+            estimate.update(track(), Some(12_000), true, DURATION_MS, at(21)),
+            12_000
+        );
+        assert!(!estimate.is_approximate());
+    }
+
+    #[test]
+    fn stops_at_the_end_and_starts_over_for_another_item() {
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let mut estimate = ProgressEstimate::default();
+
+        estimate.update(track(), Some(195_000), true, DURATION_MS, at(0));
+        assert_eq!(
+            estimate.update(track(), None, true, DURATION_MS, at(30)),
+            DURATION_MS
+        );
+
+        let next = Some(String::from("spotify:episode:512ojhOuo1ktJprKbVcKyQ"));
+        assert_eq!(estimate.update(next.clone(), None, true, 60_000, at(31)), 0);
+        assert_eq!(estimate.update(next, None, true, 60_000, at(33)), 2_000);
+    }
+}
@@ -0,0 +1,112 @@
+use crate::cache_file;
+use anyhow::Result;
+use rspotify::model::{Page, PrivateUser, SavedAlbum, Show, SimplifiedPlaylist};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+// Bumped whenever the file changes, including with the models of rspotify. A file of another
+// version is ignored, and the next exit writes it again.
+pub const VERSION: u32 = 1;
+
+// What the first render shows before the startup fetches return. Only what Spotify returned for
+// the user is kept here, never the credentials of the client config or the token.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct StartupCache {
+    pub version: u32,
+    pub user: Option<PrivateUser>,
+    pub playlists: Option<Page<SimplifiedPlaylist>>,
+    // The first page of each, which is what opening them fetches again
+    pub saved_albums: Option<Page<SavedAlbum>>,
+    pub saved_shows: Option<Page<Show>>,
+}
+
+// None when there is no cache yet, it can't be read, or it is of another version
+pub fn load(path: &Path) -> Option<StartupCache> {
+    cache_file::load_json::<StartupCache>(path).filter(|cache| cache.version == VERSION)
+}
+
+pub fn save(path: &Path, cache: &StartupCache) -> Result<()> {
+    cache_file::save_json(path, cache)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn playlists() -> Page<SimplifiedPlaylist> {
+        serde_json::from_value(json!({
+            "href": "https://api.spotify.com/v1/me/playlists",
+            "items": [{
+                "collaborative": false,
+                "external_urls": {},
+                "href": "https://api.spotify.com/v1/playlists/37i9dQZF1DXcBWIGoYBM5M",
+                "id": "37i9dQZF1DXcBWIGoYBM5M",
+                "images": [],
+                "name": "Today's Top Hits",
+                "owner": {
+                    "external_urls": {},
+                    "href": "https://api.spotify.com/v1/users/spotify",
+                    "id": "spotify"
+                },
+                "snapshot_id": "snapshot",
+                "tracks": { "href": "https://api.spotify.com/v1/playlists/37i9dQZF1DXcBWIGoYBM5M/tracks", "total": 50 }
+            }],
+            "limit": 20,
+            "next": null,
+            "offset": 0,
+            "previous": null,
+            "total": 1
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn saves_and_loads_the_cache_of_this_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("startup.json");
+        let cache = StartupCache {
+            version: VERSION,
+            user: Some(
+                serde_json::from_value(json!({
+                    "external_urls": {},
+                    "href": "https://api.spotify.com/v1/users/listener",
+                    "id": "listener",
+                    "display_name": "Listener"
+                }))
+                .unwrap(),
+            ),
+            playlists: Some(playlists()),
+            ..StartupCache::default()
+        };
+        save(&path, &cache).unwrap();
+        assert_eq!(load(&path), Some(cache));
+
+        for contents in ["{\"version\": 2, \"user\": null}", "{\"user\": null}"] {
+            std::fs::write(&path, contents).unwrap();
+            assert_eq!(load(&path), None, "{}", contents);
+        }
+    }
+
+    #[test]
+    fn keeps_nothing_but_what_was_fetched() {
+        let value = serde_json::to_value(StartupCache {
+            version: VERSION,
+            playlists: Some(playlists()),
+            ..StartupCache::default()
+        })
+        .unwrap();
+        let mut keys = value.as_object().unwrap().keys().collect::<Vec<_>>();
+        keys.sort();
+        assert_eq!(
+            keys,
+            [
+                "playlists",
+                "saved_albums",
+                "saved_shows",
+                "user",
+                "version"
+            ]
+        );
+    }
+}
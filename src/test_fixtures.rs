@@ -0,0 +1,65 @@
+// API responses shared by tests in different modules, built from JSON the way rspotify gets them
+use chrono::Duration;
+use rspotify::model::{
+    album::SimplifiedAlbum, show::SimplifiedEpisode, track::FullTrack, Page, TrackId,
+};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+// Named after its id
+pub fn full_track(id: &str) -> FullTrack {
+    FullTrack {
+        album: SimplifiedAlbum::default(),
+        artists: vec![],
+        available_markets: vec![],
+        disc_number: 1,
+        duration: Duration::seconds(180),
+        explicit: false,
+        external_ids: HashMap::new(),
+        external_urls: HashMap::new(),
+        href: None,
+        id: Some(TrackId::from_id(id).unwrap().into_static()),
+        is_local: false,
+        is_playable: None,
+        linked_from: None,
+        restrictions: None,
+        name: id.to_string(),
+        popularity: 0,
+        preview_url: None,
+        track_number: 1,
+    }
+}
+
+pub fn episode_json(id: &str, resume_point: Value) -> Value {
+    json!({
+        "audio_preview_url": null,
+        "description": "",
+        "duration_ms": 1_800_000,
+        "explicit": false,
+        "external_urls": {},
+        "href": "",
+        "id": id,
+        "images": [],
+        "is_externally_hosted": false,
+        "is_playable": true,
+        "language": "en",
+        "languages": [],
+        "name": "Episode",
+        "release_date": "2020-01-01",
+        "release_date_precision": "day",
+        "resume_point": resume_point,
+    })
+}
+
+pub fn episode_page(episodes: Vec<Value>) -> Page<SimplifiedEpisode> {
+    serde_json::from_value(json!({
+        "href": "",
+        "total": episodes.len(),
+        "items": episodes,
+        "limit": 50,
+        "next": null,
+        "offset": 0,
+        "previous": null,
+    }))
+    .unwrap()
+}
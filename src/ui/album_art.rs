@@ -0,0 +1,389 @@
+use anyhow::Result;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use image::{imageops::FilterType, RgbImage};
+use rspotify::model::Image;
+use std::{env, io::Write};
+use tui::layout::Rect;
+
+// Kitty graphics escape sequences can carry at most 4096 bytes of payload each
+const KITTY_CHUNK_SIZE: usize = 4096;
+// Lets us replace or delete the image we placed without touching any other images
+const KITTY_IMAGE_ID: u32 = 1;
+// Album art is drawn in a few cells of the playbar, so anything larger is wasted bandwidth
+const ALBUM_ART_MIN_SIZE: u32 = 100;
+// Used when the terminal doesn't report its size in pixels
+const DEFAULT_CELL_SIZE: (u32, u32) = (8, 16);
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum GraphicsProtocol {
+    Kitty,
+    Sixel,
+}
+
+// Figure out which graphics protocol the terminal speaks from the environment it sets, as
+// querying the terminal directly would race with the event loop reading stdin
+pub fn detect_protocol() -> Option<GraphicsProtocol> {
+    detect_protocol_from(|name| env::var(name).ok())
+}
+
+fn detect_protocol_from(var: impl Fn(&str) -> Option<String>) -> Option<GraphicsProtocol> {
+    let term = var("TERM").unwrap_or_default();
+    let term_program = var("TERM_PROGRAM").unwrap_or_default();
+
+    if var("KITTY_WINDOW_ID").is_some()
+        || term.contains("kitty")
+        || term.contains("ghostty")
+        || ["WezTerm", "ghostty"].contains(&term_program.as_str())
+    {
+        Some(GraphicsProtocol::Kitty)
+    } else if term.contains("sixel")
+        || term.starts_with("foot")
+        || term.starts_with("mlterm")
+        || term.starts_with("yaft")
+        || ["iTerm.app", "mintty"].contains(&term_program.as_str())
+    {
+        Some(GraphicsProtocol::Sixel)
+    } else {
+        None
+    }
+}
+
+// Pick the smallest image that is at least ALBUM_ART_MIN_SIZE wide, or the largest one when none
+// are. Images without a known width are only used as a last resort.
+pub fn select_album_art(images: &[Image]) -> Option<&Image> {
+    let width = |image: &&Image| image.width.unwrap_or_default();
+    images
+        .iter()
+        .filter(|image| width(image) >= ALBUM_ART_MIN_SIZE)
+        .min_by_key(width)
+        .or_else(|| images.iter().max_by_key(width))
+}
+
+// Keeps track of what has been drawn so that the image is only sent to the terminal when it
+// changes, rather than on every render
+pub struct AlbumArtRenderer {
+    protocol: GraphicsProtocol,
+    drawn: Option<(String, Rect)>,
+}
+
+impl AlbumArtRenderer {
+    pub fn new(protocol: GraphicsProtocol) -> Self {
+        AlbumArtRenderer {
+            protocol,
+            drawn: None,
+        }
+    }
+
+    // Forget what was drawn, e.g. after the terminal was resized and cleared
+    pub fn invalidate(&mut self) {
+        self.drawn = None;
+    }
+
+    // Draw the image with the given url into `area`, or remove the previous image when there is
+    // nothing to draw. Returns true when the terminal needs to be cleared, because sixel images
+    // can only be removed by redrawing the cells they cover.
+    pub fn render<W: Write>(
+        &mut self,
+        w: &mut W,
+        art: Option<(&str, &[u8], Rect)>,
+    ) -> Result<bool> {
+        let next = art.map(|(url, _, area)| (url.to_string(), area));
+        if next == self.drawn {
+            return Ok(false);
+        }
+        let needs_clear = self.drawn.is_some() && self.protocol == GraphicsProtocol::Sixel;
+        if self.drawn.is_some() && self.protocol == GraphicsProtocol::Kitty {
+            write!(w, "\x1b_Ga=d,d=I,i={},q=2\x1b\\", KITTY_IMAGE_ID)?;
+        }
+        self.drawn = None;
+
+        let Some((url, bytes, area)) = art else {
+            w.flush()?;
+            return Ok(needs_clear);
+        };
+        // After a clear the image gets drawn again on the next render
+        if needs_clear {
+            return Ok(true);
+        }
+
+        // Skip drawing when the image can't be decoded, there is nothing useful to show instead
+        let Ok(image) = image::load_from_memory(bytes) else {
+            return Ok(false);
+        };
+        let (cell_width, cell_height) = cell_size();
+        let image = image
+            .resize(
+                area.width as u32 * cell_width,
+                area.height as u32 * cell_height,
+                FilterType::Triangle,
+            )
+            .to_rgb8();
+
+        let encoded = match self.protocol {
+            GraphicsProtocol::Kitty => encode_kitty(&image, area),
+            GraphicsProtocol::Sixel => encode_sixel(&image),
+        };
+        crossterm::queue!(w, crossterm::cursor::MoveTo(area.x, area.y))?;
+        w.write_all(encoded.as_bytes())?;
+        w.flush()?;
+
+        self.drawn = Some((url.to_string(), area));
+        Ok(false)
+    }
+}
+
+fn cell_size() -> (u32, u32) {
+    match crossterm::terminal::window_size() {
+        Ok(size) if size.width > 0 && size.height > 0 && size.columns > 0 && size.rows > 0 => (
+            (size.width / size.columns) as u32,
+            (size.height / size.rows) as u32,
+        ),
+        _ => DEFAULT_CELL_SIZE,
+    }
+}
+
+// Transmit raw RGB pixels and let the terminal scale them to fit the cells of `area`
+fn encode_kitty(image: &RgbImage, area: Rect) -> String {
+    let payload = STANDARD.encode(image.as_raw());
+    let chunks = payload
+        .as_bytes()
+        .chunks(KITTY_CHUNK_SIZE)
+        .collect::<Vec<_>>();
+
+    let mut out = String::new();
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = if i + 1 < chunks.len() { 1 } else { 0 };
+        let chunk = std::str::from_utf8(chunk).unwrap_or_default();
+        if i == 0 {
+            out.push_str(&format!(
+                "\x1b_Ga=T,f=24,s={},v={},c={},r={},i={},C=1,q=2,m={};{}\x1b\\",
+                image.width(),
+                image.height(),
+                area.width,
+                area.height,
+                KITTY_IMAGE_ID,
+                more,
+                chunk
+            ));
+        } else {
+            out.push_str(&format!("\x1b_Gm={};{}\x1b\\", more, chunk));
+        }
+    }
+    out
+}
+
+// Index of a colour in a 6x6x6 colour cube, which is plenty for a thumbnail
+fn sixel_color_index(pixel: &image::Rgb<u8>) -> usize {
+    let [r, g, b] = pixel.0;
+    let level = |c: u8| (c as usize * 5 + 127) / 255;
+    level(r) * 36 + level(g) * 6 + level(b)
+}
+
+fn encode_sixel(image: &RgbImage) -> String {
+    let (width, height) = image.dimensions();
+    let mut out = format!("\x1bPq\"1;1;{};{}", width, height);
+
+    // Sixel colours are given as percentages
+    for index in 0..216 {
+        let percentage = |level: usize| level * 100 / 5;
+        out.push_str(&format!(
+            "#{};2;{};{};{}",
+            index,
+            percentage(index / 36),
+            percentage(index / 6 % 6),
+            percentage(index % 6)
+        ));
+    }
+
+    // Each band of six pixel rows is drawn once per colour in it, returning to the start of the
+    // band with `$` in between and moving to the next band with `-`
+    for band in (0..height).step_by(6) {
+        let mut colors = vec![vec![0u8; width as usize]; 216];
+        let mut used = vec![false; 216];
+        for x in 0..width {
+            for bit in 0..6 {
+                let y = band + bit;
+                if y >= height {
+                    break;
+                }
+                let index = sixel_color_index(image.get_pixel(x, y));
+                colors[index][x as usize] |= 1 << bit;
+                used[index] = true;
+            }
+        }
+
+        for (index, sixels) in colors.iter().enumerate().filter(|(index, _)| used[*index]) {
+            out.push_str(&format!("#{}", index));
+            push_sixel_runs(&mut out, sixels);
+            out.push('$');
+        }
+        out.push('-');
+    }
+
+    out.push_str("\x1b\\");
+    out
+}
+
+// Append the sixels, run length encoding repeats
+fn push_sixel_runs(out: &mut String, sixels: &[u8]) {
+    let mut iter = sixels.iter().peekable();
+    while let Some(&sixel) = iter.next() {
+        let mut count = 1;
+        while iter.peek() == Some(&&sixel) {
+            iter.next();
+            count += 1;
+        }
+        let c = (b'?' + sixel) as char;
+        if count > 3 {
+            out.push_str(&format!("!{}{}", count, c));
+        } else {
+            (0..count).for_each(|_| out.push(c));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageOutputFormat, Rgb};
+    use std::io::Cursor;
+
+    fn env(vars: &'static [(&'static str, &'static str)]) -> impl Fn(&str) -> Option<String> {
+        move |name| {
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string())
+        }
+    }
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let image = RgbImage::from_pixel(width, height, Rgb([255, 0, 0]));
+        let mut bytes = Cursor::new(vec![]);
+        image::DynamicImage::ImageRgb8(image)
+            .write_to(&mut bytes, ImageOutputFormat::Png)
+            .unwrap();
+        bytes.into_inner()
+    }
+
+    #[test]
+    fn detects_protocol_from_environment() {
+        assert_eq!(
+            detect_protocol_from(env(&[("TERM", "xterm-kitty")])),
+            Some(GraphicsProtocol::Kitty)
+        );
+        assert_eq!(
+            detect_protocol_from(env(&[
+                ("TERM", "xterm-256color"),
+                ("TERM_PROGRAM", "WezTerm")
+            ])),
+            Some(GraphicsProtocol::Kitty)
+        );
+        assert_eq!(
+            detect_protocol_from(env(&[("TERM", "foot")])),
+            Some(GraphicsProtocol::Sixel)
+        );
+        assert_eq!(
+            detect_protocol_from(env(&[("TERM", "xterm-256color")])),
+            None
+        );
+        assert_eq!(detect_protocol_from(env(&[])), None);
+    }
+
+    #[test]
+    fn selects_smallest_suitable_image() {
+        let image = |url: &str, width: Option<u32>| Image {
+            url: url.to_string(),
+            width,
+            height: width,
+        };
+
+        let images = vec![
+            image("640", Some(640)),
+            image("300", Some(300)),
+            image("64", Some(64)),
+        ];
+        assert_eq!(select_album_art(&images).unwrap().url, "300");
+
+        let images = vec![image("64", Some(64)), image("32", Some(32))];
+        assert_eq!(select_album_art(&images).unwrap().url, "64");
+
+        let images = vec![image("unknown", None)];
+        assert_eq!(select_album_art(&images).unwrap().url, "unknown");
+        assert!(select_album_art(&[]).is_none());
+    }
+
+    #[test]
+    fn encodes_sixel() {
+        let image = RgbImage::from_pixel(5, 7, Rgb([255, 0, 0]));
+        let sixel = encode_sixel(&image);
+
+        assert!(sixel.starts_with("\x1bPq\"1;1;5;7"));
+        assert!(sixel.ends_with("\x1b\\"));
+        // Red is colour 5 * 36 in the cube, a full band of five pixels then one row of the next
+        assert!(sixel.contains("#180!5~$-#180!5@$-"));
+    }
+
+    #[test]
+    fn run_length_encodes_sixels() {
+        let mut out = String::new();
+        push_sixel_runs(&mut out, &[0, 0, 63, 63, 63, 63, 1]);
+        assert_eq!(out, "??!4~@");
+    }
+
+    #[test]
+    fn chunks_kitty_payload() {
+        let image = RgbImage::from_pixel(64, 64, Rgb([0, 0, 0]));
+        let encoded = encode_kitty(&image, Rect::new(0, 0, 8, 4));
+
+        assert!(encoded.starts_with("\x1b_Ga=T,f=24,s=64,v=64,c=8,r=4,i=1,C=1,q=2,m=1;"));
+        // 64 * 64 * 3 bytes is 16384 base64 characters, so four chunks
+        assert_eq!(encoded.matches("\x1b_G").count(), 4);
+        assert!(encoded.contains("\x1b_Gm=0;"));
+    }
+
+    #[test]
+    fn renders_only_when_the_art_changes() {
+        let bytes = png(4, 4);
+        let area = Rect::new(1, 1, 8, 4);
+        let mut renderer = AlbumArtRenderer::new(GraphicsProtocol::Kitty);
+        let mut out = vec![];
+
+        assert!(!renderer
+            .render(&mut out, Some(("a", &bytes, area)))
+            .unwrap());
+        assert!(!out.is_empty());
+
+        out.clear();
+        renderer
+            .render(&mut out, Some(("a", &bytes, area)))
+            .unwrap();
+        assert!(out.is_empty());
+
+        renderer.render(&mut out, None).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\x1b_Ga=d,d=I,i=1,q=2\x1b\\"
+        );
+    }
+
+    #[test]
+    fn sixel_removal_requires_clear() {
+        let bytes = png(4, 4);
+        let area = Rect::new(1, 1, 8, 4);
+        let mut renderer = AlbumArtRenderer::new(GraphicsProtocol::Sixel);
+        let mut out = vec![];
+
+        assert!(!renderer
+            .render(&mut out, Some(("a", &bytes, area)))
+            .unwrap());
+        assert!(renderer
+            .render(&mut out, Some(("b", &bytes, area)))
+            .unwrap());
+        // Drawn again once the terminal has been cleared
+        out.clear();
+        assert!(!renderer
+            .render(&mut out, Some(("b", &bytes, area)))
+            .unwrap());
+        assert!(!out.is_empty());
+    }
+}
@@ -1,8 +1,13 @@
 use super::util;
-use crate::app::App;
+use crate::app::{App, TrackAnalysis};
+use rspotify::model::{
+    audio::{AudioAnalysis, AudioFeatures},
+    enums::Modality,
+    PlayableItem,
+};
 use tui::{
     backend::Backend,
-    layout::{Constraint, Direction, Layout},
+    layout::{Constraint, Direction, Layout, Rect},
     style::Style,
     text::{Span, Spans},
     widgets::{BarChart, Block, Borders, Paragraph},
@@ -11,6 +16,62 @@ use tui::{
 const PITCHES: [&str; 12] = [
     "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
 ];
+const SECTION_FILL: [&str; 2] = ["▒", "░"];
+const CURSOR: &str = "┃";
+// Narrower than this, the bars of the pitches are too thin to tell apart, so a summary is shown
+pub const MIN_CHART_WIDTH: u16 = 40;
+
+// The key as it's written, e.g. C# minor. Spotify gives -1 when it couldn't tell.
+pub fn key_name(features: &AudioFeatures) -> String {
+    let Some(pitch) = usize::try_from(features.key)
+        .ok()
+        .and_then(|key| PITCHES.get(key))
+    else {
+        return String::from("Unknown key");
+    };
+    match features.mode {
+        Modality::Major => format!("{} major", pitch),
+        Modality::Minor => format!("{} minor", pitch),
+        Modality::NoResult => pitch.to_string(),
+    }
+}
+
+// One line for each feature shown, labelled
+pub fn feature_lines(features: &AudioFeatures) -> Vec<String> {
+    vec![
+        format!("Tempo: {:.0} BPM", features.tempo),
+        format!("Key: {}", key_name(features)),
+        format!("Energy: {:.2}", features.energy),
+        format!("Danceability: {:.2}", features.danceability),
+    ]
+}
+
+// What the analysis says of the whole track, for terminals too narrow for the chart. The features
+// have the key in its mode, so they are used when they are loaded.
+pub fn summary_lines(analysis: &AudioAnalysis, features: Option<&AudioFeatures>) -> Vec<String> {
+    let mut lines = match features {
+        Some(features) => feature_lines(features),
+        None => vec![
+            format!("Tempo: {:.0} BPM", analysis.track.tempo),
+            format!(
+                "Key: {}",
+                PITCHES
+                    .get(analysis.track.key as usize)
+                    .unwrap_or(&"Unknown key")
+            ),
+        ],
+    };
+    lines.push(format!("Loudness: {:.1} dB", analysis.track.loudness));
+    let sections = analysis
+        .sections
+        .iter()
+        .map(|section| {
+            util::millis_to_minutes((section.time_interval.duration * 1000.0).round() as u128)
+        })
+        .collect::<Vec<_>>();
+    lines.push(format!("Sections: {}", sections.join(", ")));
+    lines
+}
 
 pub fn draw<B>(f: &mut Frame<B>, app: &App)
 where
@@ -20,7 +81,14 @@ where
 
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Min(5), Constraint::Length(95)].as_ref())
+        .constraints(
+            [
+                Constraint::Length(6),
+                Constraint::Length(3),
+                Constraint::Min(5),
+            ]
+            .as_ref(),
+        )
         .margin(margin)
         .split(f.size());
 
@@ -34,7 +102,7 @@ where
 
     let white = Style::default().fg(app.user_config.theme.text);
     let gray = Style::default().fg(app.user_config.theme.inactive);
-    let width = (chunks[1].width) as f32 / (1 + PITCHES.len()) as f32;
+    let width = (chunks[2].width) as f32 / (1 + PITCHES.len()) as f32;
     let tick_rate = app.user_config.behavior.tick_rate_milliseconds;
     let bar_chart_title = &format!("Pitches | Tick Rate {} {}FPS", tick_rate, 1000 / tick_rate);
 
@@ -44,93 +112,253 @@ where
         .title(Span::styled(bar_chart_title, gray))
         .border_style(gray);
 
-    let empty_analysis_block = || {
-        Paragraph::new("No analysis available")
-            .block(analysis_block.clone())
-            .style(Style::default().fg(app.user_config.theme.text))
+    let sections_block = |title: String| {
+        Block::default()
+            .borders(Borders::ALL)
+            .title(Span::styled(title, gray))
+            .border_style(gray)
     };
-    let empty_pitches_block = || {
-        Paragraph::new("No pitch information available")
+
+    let draw_empty = |f: &mut Frame<B>, message: &str| {
+        let p = Paragraph::new(message)
+            .block(analysis_block.clone())
+            .style(Style::default().fg(app.user_config.theme.text));
+        f.render_widget(p, chunks[0]);
+        f.render_widget(sections_block(String::from("Sections")), chunks[1]);
+        let p = Paragraph::new("No pitch information available")
             .block(bar_chart_block.clone())
-            .style(Style::default().fg(app.user_config.theme.text))
+            .style(Style::default().fg(app.user_config.theme.text));
+        f.render_widget(p, chunks[2]);
     };
 
-    if let Some(analysis) = &app.audio_analysis {
-        let progress_seconds = (app.song_progress_ms as f32) / 1000.0;
-
-        let beat = analysis
-            .beats
-            .iter()
-            .find(|beat| beat.start >= progress_seconds);
-
-        let beat_offset = beat
-            .map(|beat| beat.start - progress_seconds)
-            .unwrap_or(0.0);
-        let segment = analysis
-            .segments
-            .iter()
-            .find(|segment| segment.time_interval.start >= progress_seconds);
-        let section = analysis
-            .sections
-            .iter()
-            .find(|section| section.time_interval.start >= progress_seconds);
-
-        if let (Some(segment), Some(section)) = (segment, section) {
-            let texts = vec![
-                Spans::from(format!(
-                    "Tempo: {} (confidence {:.0}%)",
-                    section.tempo,
-                    section.tempo_confidence * 100.0
-                )),
-                Spans::from(format!(
-                    "Key: {} (confidence {:.0}%)",
-                    PITCHES.get(section.key as usize).unwrap_or(&PITCHES[0]),
-                    section.key_confidence * 100.0
-                )),
-                Spans::from(format!(
-                    "Time Signature: {}/4 (confidence {:.0}%)",
-                    section.time_signature,
-                    section.time_signature_confidence * 100.0
-                )),
-            ];
-            let p = Paragraph::new(texts)
-                .block(analysis_block)
-                .style(Style::default().fg(app.user_config.theme.text));
-            f.render_widget(p, chunks[0]);
-
-            let data: Vec<(&str, u64)> = segment
-                .clone()
-                .pitches
-                .iter()
-                .enumerate()
-                .map(|(index, pitch)| {
-                    let display_pitch = *PITCHES.get(index).unwrap_or(&PITCHES[0]);
-                    let bar_value = ((pitch * 1000.0) as u64)
-                        // Add a beat offset to make the bar animate between beats
-                        .checked_add((beat_offset * 3000.0) as u64)
-                        .unwrap_or(0);
-
-                    (display_pitch, bar_value)
-                })
-                .collect();
-
-            let analysis_bar = BarChart::default()
-                .block(bar_chart_block)
-                .data(&data)
-                .bar_width(width as u16)
-                .bar_style(Style::default().fg(app.user_config.theme.analysis_bar))
-                .value_style(
-                    Style::default()
-                        .fg(app.user_config.theme.analysis_bar_text)
-                        .bg(app.user_config.theme.analysis_bar),
-                );
-            f.render_widget(analysis_bar, chunks[1]);
-        } else {
-            f.render_widget(empty_analysis_block(), chunks[0]);
-            f.render_widget(empty_pitches_block(), chunks[1]);
-        };
+    let analysis = match app
+        .current_playback_context
+        .as_ref()
+        .and_then(|context| context.item.as_ref())
+    {
+        None => return draw_empty(f, "Nothing is playing"),
+        Some(PlayableItem::Episode(_)) => {
+            return draw_empty(f, "Podcast episodes have no audio analysis")
+        }
+        Some(PlayableItem::Track(_)) => match app.current_audio_analysis() {
+            Some(TrackAnalysis::Loaded(analysis)) => analysis,
+            Some(TrackAnalysis::Loading) => return draw_empty(f, "Loading..."),
+            _ => return draw_empty(f, "No analysis available"),
+        },
+    };
+
+    if chunks[2].width < MIN_CHART_WIDTH {
+        let features = app
+            .playing_track()
+            .and_then(|track| track.id.as_ref())
+            .and_then(|track_id| app.audio_features.get(track_id));
+        let texts = summary_lines(analysis, features)
+            .into_iter()
+            .map(Spans::from)
+            .collect::<Vec<_>>();
+        let area = Layout::default()
+            .margin(margin)
+            .constraints([Constraint::Min(0)].as_ref())
+            .split(f.size())[0];
+        let p = Paragraph::new(texts)
+            .block(analysis_block)
+            .style(Style::default().fg(app.user_config.theme.text));
+        f.render_widget(p, area);
+        return;
+    }
+
+    let progress_seconds = (app.song_progress_ms as f32) / 1000.0;
+
+    let beat = analysis
+        .beats
+        .iter()
+        .find(|beat| beat.start >= progress_seconds);
+
+    let beat_offset = beat
+        .map(|beat| beat.start - progress_seconds)
+        .unwrap_or(0.0);
+    let segment = analysis
+        .segments
+        .iter()
+        .find(|segment| segment.time_interval.start >= progress_seconds);
+    // The section being played, i.e. the last one to have started
+    let section_index = analysis
+        .sections
+        .iter()
+        .rposition(|section| section.time_interval.start <= progress_seconds);
+
+    let (Some(segment), Some(section_index)) = (segment, section_index) else {
+        return draw_empty(f, "No analysis available");
+    };
+    let section = &analysis.sections[section_index];
+
+    // The features are of the whole track, unlike the rest which follows the section
+    let features = app
+        .playing_track()
+        .and_then(|track| track.id.as_ref())
+        .and_then(|track_id| app.audio_features.get(track_id))
+        .map(|features| {
+            format!(
+                "Track: {:.0} BPM in {}, energy {:.2}, danceability {:.2}",
+                features.tempo,
+                key_name(features),
+                features.energy,
+                features.danceability
+            )
+        })
+        .unwrap_or_default();
+
+    let texts = vec![
+        Spans::from(features),
+        Spans::from(format!(
+            "Tempo: {} (confidence {:.0}%)",
+            section.tempo,
+            section.tempo_confidence * 100.0
+        )),
+        Spans::from(format!(
+            "Key: {} (confidence {:.0}%)",
+            PITCHES.get(section.key as usize).unwrap_or(&PITCHES[0]),
+            section.key_confidence * 100.0
+        )),
+        Spans::from(format!(
+            "Time Signature: {}/4 (confidence {:.0}%)",
+            section.time_signature,
+            section.time_signature_confidence * 100.0
+        )),
+    ];
+    let p = Paragraph::new(texts)
+        .block(analysis_block)
+        .style(Style::default().fg(app.user_config.theme.text));
+    f.render_widget(p, chunks[0]);
+
+    draw_sections(
+        f,
+        app,
+        analysis,
+        progress_seconds,
+        sections_block(format!(
+            "Section {}/{} | ←/→ to jump between sections",
+            section_index + 1,
+            analysis.sections.len()
+        )),
+        chunks[1],
+    );
+
+    let data: Vec<(&str, u64)> = segment
+        .clone()
+        .pitches
+        .iter()
+        .enumerate()
+        .map(|(index, pitch)| {
+            let display_pitch = *PITCHES.get(index).unwrap_or(&PITCHES[0]);
+            let bar_value = ((pitch * 1000.0) as u64)
+                // Add a beat offset to make the bar animate between beats
+                .checked_add((beat_offset * 3000.0) as u64)
+                .unwrap_or(0);
+
+            (display_pitch, bar_value)
+        })
+        .collect();
+
+    let analysis_bar = BarChart::default()
+        .block(bar_chart_block)
+        .data(&data)
+        .bar_width(width as u16)
+        .bar_style(Style::default().fg(app.user_config.theme.analysis_bar))
+        .value_style(
+            Style::default()
+                .fg(app.user_config.theme.analysis_bar_text)
+                .bg(app.user_config.theme.analysis_bar),
+        );
+    f.render_widget(analysis_bar, chunks[2]);
+}
+
+// The sections of the track side by side, shaded alternately, with a cursor at the playback
+// position
+fn draw_sections<B>(
+    f: &mut Frame<B>,
+    app: &App,
+    analysis: &AudioAnalysis,
+    progress_seconds: f32,
+    block: Block,
+    layout_chunk: Rect,
+) where
+    B: Backend,
+{
+    let width = layout_chunk.width.saturating_sub(2) as usize;
+    let section_starts = analysis
+        .sections
+        .iter()
+        .map(|section| section.time_interval.start)
+        .collect::<Vec<_>>();
+    let (before, after) = timeline(
+        width,
+        analysis.track.duration,
+        &section_starts,
+        progress_seconds,
+    );
+
+    let bar = Style::default().fg(app.user_config.theme.analysis_bar);
+    let cursor = Style::default().fg(app.user_config.theme.active);
+    let spans = if width == 0 {
+        Spans::default()
     } else {
-        f.render_widget(empty_analysis_block(), chunks[0]);
-        f.render_widget(empty_pitches_block(), chunks[1]);
+        Spans::from(vec![
+            Span::styled(before, bar),
+            Span::styled(CURSOR, cursor),
+            Span::styled(after, bar),
+        ])
+    };
+    f.render_widget(Paragraph::new(spans).block(block), layout_chunk);
+}
+
+// The columns before and after the cursor, which takes up a column of its own
+fn timeline(
+    width: usize,
+    duration_seconds: f32,
+    section_starts: &[f32],
+    progress_seconds: f32,
+) -> (String, String) {
+    if width == 0 || duration_seconds <= 0.0 {
+        return (String::new(), String::new());
+    }
+    let column_of =
+        |seconds: f32| ((seconds / duration_seconds * width as f32) as usize).min(width - 1);
+    let cursor = column_of(progress_seconds.max(0.0));
+
+    let columns = (0..width)
+        .map(|column| {
+            // The section playing halfway through the column
+            let seconds = (column as f32 + 0.5) / width as f32 * duration_seconds;
+            let section = section_starts
+                .iter()
+                .filter(|start| **start <= seconds)
+                .count();
+            SECTION_FILL[section % 2]
+        })
+        .collect::<Vec<_>>();
+
+    (columns[..cursor].concat(), columns[cursor + 1..].concat())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timeline_marks_sections_and_position() {
+        let (before, after) = timeline(10, 100.0, &[0.0, 30.0, 60.0], 45.0);
+        assert_eq!(before, "░░░▒");
+        assert_eq!(after, "▒░░░░");
+
+        // The end of the track is still on the timeline
+        let (before, after) = timeline(4, 100.0, &[0.0], 100.0);
+        assert_eq!(before, "░░░");
+        assert_eq!(after, "");
+
+        assert_eq!(
+            timeline(0, 100.0, &[0.0], 0.0),
+            (String::new(), String::new())
+        );
     }
 }
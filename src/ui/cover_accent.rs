@@ -0,0 +1,137 @@
+use image::{imageops::FilterType, RgbImage};
+use std::{collections::HashMap, env};
+use tui::style::Color;
+
+// Covers are shrunk to this many pixels a side before looking for their colors
+const SAMPLE_SIZE: u32 = 32;
+// Pixels are grouped by the top bits of each channel, so that close shades count as one color
+const BUCKET_SHIFT: u8 = 5;
+// Pixels darker than this are left out, an accent that dark wouldn't show on most backgrounds
+const MIN_BRIGHTNESS: u8 = 48;
+
+// An accent is an rgb color, which terminals only show faithfully with 24-bit color. Like the
+// graphics protocol, this is read from the environment the terminal sets.
+pub fn supports_truecolor() -> bool {
+    supports_truecolor_from(|name| env::var(name).ok())
+}
+
+fn supports_truecolor_from(var: impl Fn(&str) -> Option<String>) -> bool {
+    matches!(
+        var("COLORTERM").as_deref(),
+        Some("truecolor") | Some("24bit")
+    )
+}
+
+// The accent for a cover, or None when the image can't be decoded or has no color to use
+pub fn cover_accent(bytes: &[u8]) -> Option<Color> {
+    let image = image::load_from_memory(bytes).ok()?;
+    let sample = image
+        .resize_exact(SAMPLE_SIZE, SAMPLE_SIZE, FilterType::Triangle)
+        .to_rgb8();
+    dominant_color(&sample)
+}
+
+// The average of the most common group of similar colors, leaving out the darkest pixels. Ties go
+// to the group seen first.
+pub fn dominant_color(image: &RgbImage) -> Option<Color> {
+    let mut buckets: HashMap<[u8; 3], (usize, [u64; 3])> = HashMap::new();
+    let mut order = vec![];
+    for pixel in image.pixels() {
+        let [r, g, b] = pixel.0;
+        if r.max(g).max(b) < MIN_BRIGHTNESS {
+            continue;
+        }
+        let key = [r >> BUCKET_SHIFT, g >> BUCKET_SHIFT, b >> BUCKET_SHIFT];
+        let (count, sums) = buckets.entry(key).or_insert_with(|| {
+            order.push(key);
+            (0, [0; 3])
+        });
+        *count += 1;
+        for (sum, channel) in sums.iter_mut().zip(pixel.0) {
+            *sum += channel as u64;
+        }
+    }
+
+    let (count, sums) = order
+        .iter()
+        .map(|key| buckets[key])
+        .reduce(|most, bucket| if bucket.0 > most.0 { bucket } else { most })?;
+    let [r, g, b] = sums.map(|sum| (sum / count as u64) as u8);
+    Some(Color::Rgb(r, g, b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageOutputFormat, Rgb};
+
+    fn image(pixels: &[[u8; 3]]) -> RgbImage {
+        let mut image = RgbImage::new(pixels.len() as u32, 1);
+        for (x, pixel) in pixels.iter().enumerate() {
+            image.put_pixel(x as u32, 0, Rgb(*pixel));
+        }
+        image
+    }
+
+    #[test]
+    fn detects_truecolor() {
+        let with = |value: &'static str| {
+            move |name: &str| (name == "COLORTERM").then(|| value.to_string())
+        };
+        assert!(supports_truecolor_from(with("truecolor")));
+        assert!(supports_truecolor_from(with("24bit")));
+        assert!(!supports_truecolor_from(with("8bit")));
+        assert!(!supports_truecolor_from(|_| None));
+    }
+
+    #[test]
+    fn picks_the_most_common_color() {
+        let red = [200, 30, 30];
+        let blue = [30, 30, 200];
+        assert_eq!(
+            dominant_color(&image(&[blue, red, red, blue, red])),
+            Some(Color::Rgb(200, 30, 30))
+        );
+        // Close shades are one color, which is their average
+        assert_eq!(
+            dominant_color(&image(&[[200, 30, 30], [220, 10, 20], blue])),
+            Some(Color::Rgb(210, 20, 25))
+        );
+        // A tie goes to the color seen first
+        assert_eq!(
+            dominant_color(&image(&[blue, red])),
+            Some(Color::Rgb(30, 30, 200))
+        );
+    }
+
+    #[test]
+    fn leaves_out_dark_pixels() {
+        let black = [0, 0, 0];
+        let green = [40, 180, 60];
+        assert_eq!(
+            dominant_color(&image(&[black, black, black, green])),
+            Some(Color::Rgb(40, 180, 60))
+        );
+        assert_eq!(dominant_color(&image(&[black, [20, 20, 40]])), None);
+        assert_eq!(dominant_color(&RgbImage::new(0, 0)), None);
+    }
+
+    #[test]
+    fn accents_of_encoded_covers() {
+        let mut cover = RgbImage::from_pixel(64, 64, Rgb([240, 200, 20]));
+        for x in 0..16 {
+            for y in 0..64 {
+                cover.put_pixel(x, y, Rgb([10, 90, 220]));
+            }
+        }
+        let mut bytes = std::io::Cursor::new(vec![]);
+        image::DynamicImage::ImageRgb8(cover)
+            .write_to(&mut bytes, ImageOutputFormat::Png)
+            .unwrap();
+        assert_eq!(
+            cover_accent(bytes.get_ref()),
+            Some(Color::Rgb(240, 200, 20))
+        );
+        assert_eq!(cover_accent(b"not an image"), None);
+    }
+}
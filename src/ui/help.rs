@@ -1,6 +1,14 @@
-use crate::user_config::KeyBindings;
+use crate::app::ActiveBlock;
+use crate::user_config::UserConfig;
+
+pub fn get_help_docs(user_config: &UserConfig) -> Vec<Vec<String>> {
+    let key_bindings = &user_config.keys;
+    let audio_analysis = if user_config.behavior.enable_audio_analysis {
+        String::from("Go to audio analysis screen")
+    } else {
+        String::from("Go to audio analysis screen (disabled)")
+    };
 
-pub fn get_help_docs(key_bindings: &KeyBindings) -> Vec<Vec<String>> {
     vec![
         vec![
             String::from("Scroll down to next result page"),
@@ -22,6 +30,11 @@ pub fn get_help_docs(key_bindings: &KeyBindings) -> Vec<Vec<String>> {
             key_bindings.jump_to_end.to_string(),
             String::from("Pagination"),
         ],
+        vec![
+            String::from("Select what is playing in the open playlist or album"),
+            key_bindings.jump_to_playing.to_string(),
+            String::from("Pagination"),
+        ],
         vec![
             String::from("Jump to currently playing album"),
             key_bindings.jump_to_album.to_string(),
@@ -47,6 +60,16 @@ pub fn get_help_docs(key_bindings: &KeyBindings) -> Vec<Vec<String>> {
             key_bindings.decrease_volume.to_string(),
             String::from("General"),
         ],
+        vec![
+            String::from("Increase volume by 25%"),
+            key_bindings.increase_volume_big.to_string(),
+            String::from("General"),
+        ],
+        vec![
+            String::from("Decrease volume by 25%"),
+            key_bindings.decrease_volume_big.to_string(),
+            String::from("General"),
+        ],
         vec![
             String::from("Skip to next track"),
             key_bindings.next_track.to_string(),
@@ -138,7 +161,7 @@ pub fn get_help_docs(key_bindings: &KeyBindings) -> Vec<Vec<String>> {
             String::from("General"),
         ],
         vec![
-            String::from("Go to audio analysis screen"),
+            audio_analysis,
             key_bindings.audio_analysis.to_string(),
             String::from("General"),
         ],
@@ -147,6 +170,73 @@ pub fn get_help_docs(key_bindings: &KeyBindings) -> Vec<Vec<String>> {
             key_bindings.basic_view.to_string(),
             String::from("General"),
         ],
+        vec![
+            String::from("Show the lyrics of the current track"),
+            key_bindings.lyrics.to_string(),
+            String::from("General"),
+        ],
+        vec![
+            String::from("Show past notifications"),
+            key_bindings.notification_history.to_string(),
+            String::from("General"),
+        ],
+        vec![
+            String::from("Hide or show the playbar"),
+            key_bindings.toggle_playbar.to_string(),
+            String::from("General"),
+        ],
+        vec![
+            String::from("Like or unlike what is playing"),
+            key_bindings.like_current.to_string(),
+            String::from("General"),
+        ],
+        vec![
+            String::from(
+                "Save or remove the playing track's album, or follow or unfollow the episode's show",
+            ),
+            key_bindings.save_playing_item_parent.to_string(),
+            String::from("General"),
+        ],
+        vec![
+            String::from("Turn do not disturb on or off, which only lets errors through"),
+            key_bindings.do_not_disturb.to_string(),
+            String::from("General"),
+        ],
+        vec![
+            String::from("Copy the songs of the focused table as text"),
+            key_bindings.copy_view.to_string(),
+            String::from("General"),
+        ],
+        vec![
+            String::from("Copy the songs of the focused table as a markdown table"),
+            key_bindings.copy_view_markdown.to_string(),
+            String::from("General"),
+        ],
+        vec![
+            String::from("Copy the selected notification"),
+            String::from("<Enter>"),
+            String::from("Notifications"),
+        ],
+        vec![
+            String::from("Look for the podcasts you have finished, to unfollow them"),
+            String::from("F"),
+            String::from("Library -> Podcasts"),
+        ],
+        vec![
+            String::from("Stop looking for finished podcasts"),
+            String::from("<Esc>"),
+            String::from("Library -> Podcasts"),
+        ],
+        vec![
+            String::from("Keep the selected podcast, or unfollow it again"),
+            String::from("V"),
+            String::from("Finished podcasts"),
+        ],
+        vec![
+            String::from("Unfollow the podcasts that are checked"),
+            String::from("<Enter>"),
+            String::from("Finished podcasts"),
+        ],
         vec![
             String::from("Go back or exit when nowhere left to back to"),
             key_bindings.back.to_string(),
@@ -158,7 +248,12 @@ pub fn get_help_docs(key_bindings: &KeyBindings) -> Vec<Vec<String>> {
             String::from("General"),
         ],
         vec![
-            String::from("Enter hover mode"),
+            String::from("Type part of the name of a device to play music on"),
+            key_bindings.search.to_string(),
+            String::from("Devices"),
+        ],
+        vec![
+            String::from("Clear the selection, then focus the main view again"),
             String::from("<Esc>"),
             String::from("Selected block"),
         ],
@@ -182,6 +277,11 @@ pub fn get_help_docs(key_bindings: &KeyBindings) -> Vec<Vec<String>> {
             String::from("e"),
             String::from("Library -> Artists"),
         ],
+        vec![
+            String::from("Play the saved album"),
+            String::from("e"),
+            String::from("Library -> Albums"),
+        ],
         vec![
             String::from("Search with input text"),
             String::from("<Enter>"),
@@ -238,10 +338,35 @@ pub fn get_help_docs(key_bindings: &KeyBindings) -> Vec<Vec<String>> {
             String::from("Library -> Albums"),
         ],
         vec![
-            String::from("Delete saved playlist"),
+            String::from("Delete owned playlist"),
             String::from("D"),
             String::from("Playlist"),
         ],
+        vec![
+            String::from("Edit the description of owned playlist"),
+            String::from("E"),
+            String::from("Playlist"),
+        ],
+        vec![
+            String::from("Cycle between all, owned and followed playlists"),
+            String::from("F"),
+            String::from("Playlist"),
+        ],
+        vec![
+            String::from("Rename owned playlist"),
+            String::from("R"),
+            String::from("Playlist"),
+        ],
+        vec![
+            String::from("Retry loading playlists that failed to load"),
+            String::from("R"),
+            String::from("Playlist"),
+        ],
+        vec![
+            String::from("Look for the Made For You playlists again"),
+            String::from("R"),
+            String::from("Made For You"),
+        ],
         vec![
             String::from("Follow an artist/playlist"),
             String::from("w"),
@@ -252,20 +377,166 @@ pub fn get_help_docs(key_bindings: &KeyBindings) -> Vec<Vec<String>> {
             String::from("w"),
             String::from("Search result"),
         ],
+        vec![
+            String::from("Save (like) or remove the album shown"),
+            String::from("w/D"),
+            String::from("Album tracks"),
+        ],
+        vec![
+            String::from("Follow or unfollow the playlist shown"),
+            String::from("w/D"),
+            String::from("Selected Playlist"),
+        ],
+        vec![
+            String::from("Follow or unfollow the artist shown"),
+            String::from("F"),
+            String::from("Artist"),
+        ],
+        vec![
+            String::from("Search for playlists with the artist's name, such as concert setlists"),
+            key_bindings.search_artist_playlists.to_string(),
+            String::from("Artist"),
+        ],
+        vec![
+            String::from("Search for albums by others the artist appears on"),
+            key_bindings.search_artist_appears_on.to_string(),
+            String::from("Artist"),
+        ],
+        vec![
+            String::from("Cycle the album group: all, album, single, appears on or compilation"),
+            String::from("t"),
+            String::from("Artist -> Albums"),
+        ],
+        vec![
+            String::from("Mark or unmark a song, to queue the marked songs together"),
+            String::from("V"),
+            String::from("Selected song table"),
+        ],
+        vec![
+            String::from(
+                "Show or hide the tempo, key, energy and danceability of the selected song",
+            ),
+            String::from("i"),
+            String::from("Selected song table"),
+        ],
         vec![
             String::from("Play random song in playlist"),
             String::from("S"),
             String::from("Selected Playlist"),
         ],
+        vec![
+            String::from("Sort liked/recommended songs by tempo"),
+            String::from("T"),
+            String::from("Selected song table"),
+        ],
+        vec![
+            String::from("Sort liked/recommended songs by energy"),
+            String::from("E"),
+            String::from("Selected song table"),
+        ],
+        vec![
+            String::from("Sort songs by the next column, then back to the original order"),
+            String::from("O"),
+            String::from("Selected song table"),
+        ],
+        vec![
+            String::from("Reverse the order of the sorted column"),
+            String::from("R"),
+            String::from("Selected song table"),
+        ],
         vec![
             String::from("Toggle sort order of podcast episodes"),
             String::from("S"),
             String::from("Selected Show"),
         ],
+        vec![
+            String::from("Move between playbar controls"),
+            String::from("h | l | <Left Arrow Key> | <Right Arrow Key>"),
+            String::from("Selected playbar"),
+        ],
+        vec![
+            String::from("Activate playbar control"),
+            String::from("<Enter>"),
+            String::from("Selected playbar"),
+        ],
+        vec![
+            String::from("Jump to the previous/next section"),
+            String::from("h | l | <Left Arrow Key> | <Right Arrow Key>"),
+            String::from("Audio analysis screen"),
+        ],
+        vec![
+            String::from("Play a radio based on random liked songs"),
+            String::from("r"),
+            String::from("Library -> Liked Songs"),
+        ],
+        vec![
+            String::from("Switch between Spotify's recent plays and the local history"),
+            String::from("t"),
+            String::from("Recently Played"),
+        ],
+        vec![
+            String::from("Cycle the time range: last 4 weeks, last 6 months or all time"),
+            String::from("t"),
+            String::from("Library -> Top Items"),
+        ],
+        vec![
+            String::from("Switch between top tracks and top artists"),
+            String::from("<Tab>"),
+            String::from("Library -> Top Items"),
+        ],
+        vec![
+            String::from("Follow or unfollow the selected top artist"),
+            String::from("w/D"),
+            String::from("Library -> Top Items"),
+        ],
         vec![
             String::from("Add track to queue"),
             key_bindings.add_item_to_queue.to_string(),
             String::from("Hovered over track"),
         ],
+        vec![
+            String::from("Add all the tracks of the album or playlist to the queue"),
+            key_bindings.add_item_to_queue.to_string(),
+            String::from("Hovered over album or playlist"),
+        ],
     ]
 }
+
+// The blocks the rows with this context are about, whose rows are listed first when the help is
+// opened from one of them. Rows about the whole app are about none in particular.
+pub fn context_blocks(context: &str) -> &'static [ActiveBlock] {
+    match context {
+        "Search input" => &[ActiveBlock::Input],
+        "Selected song table" | "Library -> Liked Songs" => &[ActiveBlock::ItemTable],
+        "Library -> Top Items" => &[ActiveBlock::ItemTable, ActiveBlock::TopArtists],
+        "Playlist" | "Selected Playlist" => &[ActiveBlock::MyPlaylists],
+        "Artist" | "Artist -> Albums" => &[ActiveBlock::ArtistBlock],
+        "Library -> Albums" => &[ActiveBlock::AlbumList],
+        "Library -> Artists" => &[ActiveBlock::Artists],
+        "Search result" => &[ActiveBlock::SearchResultBlock],
+        "Selected playbar" => &[ActiveBlock::PlayBar],
+        "Notifications" => &[ActiveBlock::NotificationHistory],
+        "Devices" => &[ActiveBlock::SelectDevice],
+        "Library -> Podcasts" => &[ActiveBlock::Podcasts],
+        "Finished podcasts" => &[ActiveBlock::FinishedShows],
+        "Made For You" => &[ActiveBlock::MadeForYou],
+        "Album tracks" => &[ActiveBlock::AlbumTracks],
+        "Selected Show" => &[ActiveBlock::EpisodeTable],
+        "Audio analysis screen" => &[ActiveBlock::Analysis],
+        "Recently Played" => &[ActiveBlock::RecentlyPlayed],
+        "Hovered over track" => &[
+            ActiveBlock::ItemTable,
+            ActiveBlock::AlbumTracks,
+            ActiveBlock::RecentlyPlayed,
+            ActiveBlock::SearchResultBlock,
+            ActiveBlock::ArtistBlock,
+        ],
+        "Hovered over album or playlist" => &[
+            ActiveBlock::AlbumList,
+            ActiveBlock::MyPlaylists,
+            ActiveBlock::SearchResultBlock,
+            ActiveBlock::ArtistBlock,
+        ],
+        _ => &[],
+    }
+}
@@ -1,20 +1,36 @@
+pub mod album_art;
 pub mod audio_analysis;
+pub mod cover_accent;
 pub mod help;
 pub mod util;
+pub mod view_text;
 
 use super::{
     app::{
-        ActiveBlock, AlbumTableContext, App, ArtistBlock, EpisodeTableContext,
-        RecommendationsContext, RouteId, SearchResultBlock, LIBRARY_OPTIONS,
+        artist_name_match_count, is_artist_name_match, is_item_saved, owner_name,
+        repeat_state_name, ActiveBlock, AlbumTableContext, App, ArtistBlock, ColumnSort,
+        EpisodeTableContext, InputContext, ItemMetadata, ItemTableContext, ListArea, Lyrics,
+        PlaybarControl, PlaylistFilter, RecentlyPlayedSource, RecommendationsContext, RouteId,
+        ScrollableResultPages, SearchResultBlock, StartupFetch, StartupFetchState,
+        HELP_FILTER_HEIGHT, LIBRARY_OPTIONS,
     },
     banner::BANNER,
+    library_cache::LibraryIds,
+    notifications::{DoNotDisturb, Severity},
+    user_config::ItemTableColumn,
+};
+use chrono::Local;
+use rspotify::model::{
+    album::Restriction,
+    enums::{RepeatState, RestrictionReason},
+    show::ResumePoint,
+    Page, PlayableId, PlayableItem,
 };
-use help::get_help_docs;
-use rspotify::model::{enums::RepeatState, show::ResumePoint, PlayableId, PlayableItem};
 use spotify_tui_util::{PlayableIdExt, PlaybleItemExt};
+use std::{borrow::Borrow, hash::Hash};
 use tui::{
     backend::Backend,
-    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
     style::{Modifier, Style},
     text::{Span, Spans, Text},
     widgets::{
@@ -22,12 +38,16 @@ use tui::{
     },
     Frame,
 };
+use unicode_width::UnicodeWidthStr;
 use util::{
-    create_artist_string, display_track_progress, get_artist_highlight_state, get_color,
+    create_artist_string, create_context_string, display_followers, display_played_at,
+    display_track_progress, episode_progress, get_artist_highlight_state, get_color,
     get_percentage_width, get_search_results_highlight_state, get_track_progress_percentage,
-    millis_to_minutes, BASIC_VIEW_HEIGHT, SMALL_TERMINAL_WIDTH,
+    millis_to_minutes, PlayedRow, BASIC_VIEW_HEIGHT, SMALL_TERMINAL_WIDTH,
 };
 
+const PLAYBAR_CONTROL_SEPARATOR: &str = "|";
+
 pub enum TableId {
     Album,
     AlbumList,
@@ -37,6 +57,26 @@ pub enum TableId {
     RecentlyPlayed,
     MadeForYou,
     PodcastEpisodes,
+    NotificationHistory,
+    FinishedShows,
+}
+
+impl TableId {
+    // The block whose selection the table shows
+    fn active_block(&self) -> ActiveBlock {
+        match self {
+            TableId::Album => ActiveBlock::AlbumTracks,
+            TableId::AlbumList => ActiveBlock::AlbumList,
+            TableId::Artist => ActiveBlock::Artists,
+            TableId::Podcast => ActiveBlock::Podcasts,
+            TableId::Song => ActiveBlock::ItemTable,
+            TableId::RecentlyPlayed => ActiveBlock::RecentlyPlayed,
+            TableId::MadeForYou => ActiveBlock::MadeForYou,
+            TableId::PodcastEpisodes => ActiveBlock::EpisodeTable,
+            TableId::NotificationHistory => ActiveBlock::NotificationHistory,
+            TableId::FinishedShows => ActiveBlock::FinishedShows,
+        }
+    }
 }
 
 #[derive(PartialEq)]
@@ -70,6 +110,7 @@ pub struct TableHeaderItem<'a> {
     width: u16,
 }
 
+#[derive(Clone)]
 pub struct TableItem {
     id: String,
     format: Vec<String>,
@@ -79,9 +120,14 @@ pub fn draw_help_menu<B>(f: &mut Frame<B>, app: &App)
 where
     B: Backend,
 {
+    let filter_height = if app.is_help_filter_shown() {
+        HELP_FILTER_HEIGHT as u16
+    } else {
+        0
+    };
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Percentage(100)].as_ref())
+        .constraints([Constraint::Length(filter_height), Constraint::Min(0)].as_ref())
         .margin(2)
         .split(f.size());
 
@@ -94,16 +140,26 @@ where
     let header = ["Description", "Event", "Context"];
     let header = format_row(header.iter().map(|s| s.to_string()).collect());
 
-    let help_docs = get_help_docs(&app.user_config.keys);
-    let help_docs = help_docs
-        .into_iter()
-        .map(format_row)
-        .collect::<Vec<Vec<String>>>();
-    let help_docs = &help_docs[app.help_menu_offset as usize..];
+    if app.is_help_filter_shown() {
+        let filter_title = if app.is_filtering_help() {
+            String::from("Filter (press <Enter> to keep, <Esc> to clear)")
+        } else {
+            format!("Filter (press {} to change)", app.user_config.keys.search)
+        };
+        let filter = Paragraph::new(app.help_filter.as_str()).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .style(help_menu_style)
+                .title(Span::styled(filter_title, help_menu_style)),
+        );
+        f.render_widget(filter, chunks[0]);
+    }
 
-    let rows = help_docs
-        .iter()
-        .map(|item| Row::new(item.clone()).style(help_menu_style));
+    let rows = app
+        .help_docs()
+        .into_iter()
+        .skip(app.help_menu_offset as usize)
+        .map(|item| Row::new(format_row(item)).style(help_menu_style));
 
     let help_menu = Table::new(rows)
         .header(Row::new(header))
@@ -112,14 +168,17 @@ where
                 .borders(Borders::ALL)
                 .style(help_menu_style)
                 .title(Span::styled(
-                    "Help (press <Esc> to go back)",
+                    format!(
+                        "Help (press <Esc> to go back, {} to filter)",
+                        app.user_config.keys.search
+                    ),
                     help_menu_style,
                 ))
                 .border_style(help_menu_style),
         )
         .style(help_menu_style)
         .widths(&[Constraint::Percentage(100)]);
-    f.render_widget(help_menu, chunks[0]);
+    f.render_widget(help_menu, chunks[1]);
 }
 
 pub fn draw_input_and_help_box<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
@@ -149,11 +208,18 @@ where
 
     let input_string: String = app.input.iter().collect();
     let lines = Text::from((&input_string).as_str());
+    let input_title = match app.input_context {
+        InputContext::Search => "Search",
+        InputContext::RenamePlaylist { .. } => "Rename playlist",
+        InputContext::EditPlaylistDescription { .. } => "Edit playlist description",
+        InputContext::FilterHelp => "Filter",
+        InputContext::FindDevice => "Device",
+    };
     let input = Paragraph::new(lines).block(
         Block::default()
             .borders(Borders::ALL)
             .title(Span::styled(
-                "Search",
+                input_title,
                 get_color(highlight_state, app.user_config.theme),
             ))
             .border_style(get_color(highlight_state, app.user_config.theme)),
@@ -162,13 +228,23 @@ where
 
     let show_loading = app.is_loading && app.user_config.behavior.show_loading_indicator;
     let help_block_text = if show_loading {
-        (app.user_config.theme.hint, "Loading...")
+        (app.user_config.theme.hint, String::from("Loading..."))
+    } else if let Some(notification) = app.notifications.current() {
+        let color = match notification.severity {
+            Severity::Info | Severity::Warning => app.user_config.theme.hint,
+            Severity::Error => app.user_config.theme.error_text,
+        };
+        (color, notification.text())
     } else {
-        (app.user_config.theme.inactive, "Type ?")
+        (app.user_config.theme.inactive, String::from("Type ?"))
     };
 
+    let title = match app.notifications.do_not_disturb() {
+        DoNotDisturb::Off => "Help",
+        DoNotDisturb::On | DoNotDisturb::Until(_) => "Help DND",
+    };
     let block = Block::default()
-        .title(Span::styled("Help", Style::default().fg(help_block_text.0)))
+        .title(Span::styled(title, Style::default().fg(help_block_text.0)))
         .borders(Borders::ALL)
         .border_style(Style::default().fg(help_block_text.0));
 
@@ -179,24 +255,28 @@ where
     f.render_widget(help, chunks[1]);
 }
 
-pub fn draw_main_layout<B>(f: &mut Frame<B>, app: &App)
+// Returns the area reserved for album art, if any, so that it can be drawn after the frame
+pub fn draw_main_layout<B>(f: &mut Frame<B>, app: &App) -> Option<Rect>
 where
     B: Backend,
 {
     let margin = util::get_main_layout_margin(app);
+    // The playbar's rows go to the main block while it is hidden
+    let playbar_height = if app.is_playbar_hidden { 0 } else { 6 };
     // Responsive layout: new one kicks in at width 150 or higher
-    if app.size.width >= SMALL_TERMINAL_WIDTH && !app.user_config.behavior.enforce_wide_search_bar {
+    let playbar_area = if app.size.width >= SMALL_TERMINAL_WIDTH
+        && !app.user_config.behavior.enforce_wide_search_bar
+    {
         let parent_layout = Layout::default()
             .direction(Direction::Vertical)
-            .constraints([Constraint::Min(1), Constraint::Length(6)].as_ref())
+            .constraints([Constraint::Min(1), Constraint::Length(playbar_height)].as_ref())
             .margin(margin)
             .split(f.size());
 
         // Nested main block with potential routes
         draw_routes(f, app, parent_layout[0]);
 
-        // Currently playing
-        draw_playbar(f, app, parent_layout[1]);
+        parent_layout[1]
     } else {
         let parent_layout = Layout::default()
             .direction(Direction::Vertical)
@@ -204,7 +284,7 @@ where
                 [
                     Constraint::Length(3),
                     Constraint::Min(1),
-                    Constraint::Length(6),
+                    Constraint::Length(playbar_height),
                 ]
                 .as_ref(),
             )
@@ -217,12 +297,20 @@ where
         // Nested main block with potential routes
         draw_routes(f, app, parent_layout[1]);
 
-        // Currently playing
-        draw_playbar(f, app, parent_layout[2]);
-    }
+        parent_layout[2]
+    };
+
+    // Currently playing
+    let album_art_area = if app.is_playbar_hidden {
+        None
+    } else {
+        draw_playbar(f, app, playbar_area)
+    };
 
     // Possibly draw confirm dialog
     draw_dialog(f, app);
+
+    album_art_area
 }
 
 pub fn draw_routes<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
@@ -269,12 +357,24 @@ where
         RouteId::Artists => {
             draw_artist_table(f, app, chunks[1]);
         }
+        RouteId::TopArtists => {
+            draw_top_artist_table(f, app, chunks[1]);
+        }
         RouteId::Podcasts => {
             draw_podcast_table(f, app, chunks[1]);
         }
         RouteId::Recommendations => {
             draw_recommendations_table(f, app, chunks[1]);
         }
+        RouteId::Lyrics => {
+            draw_lyrics(f, app, chunks[1]);
+        }
+        RouteId::NotificationHistory => {
+            draw_notification_history(f, app, chunks[1]);
+        }
+        RouteId::FinishedShows => {
+            draw_finished_shows(f, app, chunks[1]);
+        }
         RouteId::Error => {} // This is handled as a "full screen" route in main.rs
         RouteId::SelectedDevice => {} // This is handled as a "full screen" route in main.rs
         RouteId::Analysis => {} // This is handled as a "full screen" route in main.rs
@@ -301,16 +401,52 @@ where
         highlight_state,
         Some(app.library.selected_index),
     );
+    record_list_area(
+        app,
+        ActiveBlock::Library,
+        layout_chunk,
+        Some(app.library.selected_index),
+        LIBRARY_OPTIONS.len(),
+    );
 }
 
 pub fn draw_playlist_block<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
 where
     B: Backend,
 {
-    let playlist_items = match &app.playlists {
-        Some(p) => p.items.iter().map(|item| item.name.to_owned()).collect(),
+    let listed = app.visible_playlist_indices();
+    let playing_index = app.playing_playlist_index();
+    let playlist_items: Vec<String> = match &app.playlists {
+        Some(p) => listed
+            .iter()
+            .map(|index| {
+                let item = &p.items[*index];
+                // Playlists the user only follows are marked with their owner
+                let name = match &app.user {
+                    Some(_) if !app.is_owned(item) => {
+                        format!("{} · {}", item.name, owner_name(item))
+                    }
+                    _ => item.name.to_owned(),
+                };
+                if playing_index == Some(*index) {
+                    format!("▶ {}", name)
+                } else {
+                    name
+                }
+            })
+            .collect(),
         None => vec![],
     };
+    let selected_position = app
+        .selected_playlist_index
+        .and_then(|index| listed.iter().position(|listed| *listed == index));
+
+    // Until they are fetched again, the playlists are the ones of the last session
+    let title = if app.stale.playlists {
+        format!("{} (cached)", app.playlist_filter.title())
+    } else {
+        app.playlist_filter.title().to_string()
+    };
 
     let current_route = app.get_current_route();
 
@@ -323,11 +459,116 @@ where
         f,
         app,
         layout_chunk,
-        "Playlists",
+        &title,
         &playlist_items,
         highlight_state,
-        app.selected_playlist_index,
+        selected_position,
     );
+    record_list_area(
+        app,
+        ActiveBlock::MyPlaylists,
+        layout_chunk,
+        selected_position,
+        playlist_items.len(),
+    );
+
+    // An empty block can still be focused, which the message shows along with the border
+    let message = match (&app.playlists, app.playlist_filter) {
+        (Some(_), _) if !playlist_items.is_empty() => return,
+        (Some(_), PlaylistFilter::All) => "No playlists yet",
+        (Some(_), _) => "No playlists here — press F to see the others",
+        (None, _) => match app.startup_fetch_state(StartupFetch::Playlists) {
+            StartupFetchState::RetryAt { .. } => "Couldn't load playlists, retrying...",
+            StartupFetchState::Failed => "Couldn't load playlists — press R to retry",
+            _ => "Loading playlists...",
+        },
+    };
+    draw_empty_state(
+        f,
+        app,
+        layout_chunk.inner(&Margin {
+            vertical: 1,
+            horizontal: 1,
+        }),
+        message,
+    );
+}
+
+// Stands in for the rows of an empty list or table, so that it reads as empty rather than as
+// broken or still loading
+fn draw_empty_state<B>(f: &mut Frame<B>, app: &App, area: Rect, message: &str)
+where
+    B: Backend,
+{
+    let message = Paragraph::new(message)
+        .wrap(Wrap { trim: true })
+        .style(Style::default().fg(app.user_config.theme.hint));
+    f.render_widget(message, area);
+}
+
+// For a view that there is nothing more to do in
+fn with_back_hint(app: &App, message: &str) -> String {
+    format!(
+        "{} — press {} to go back",
+        message, app.user_config.keys.back
+    )
+}
+
+// Episodes are fetched for the user's market, so a show may have none there even though it has
+// some elsewhere
+fn empty_show_message(app: &App, available_markets: &[String]) -> String {
+    let message = match app.get_user_country().map(<&'static str>::from) {
+        Some(country) if !app.is_available(available_markets) => {
+            format!("This show isn't available in your market ({})", country)
+        }
+        Some(country) => format!(
+            "This show has no episodes available in your market ({})",
+            country
+        ),
+        None => String::from("This show has no episodes yet"),
+    };
+    with_back_hint(app, &message)
+}
+
+fn empty_album_message(
+    app: &App,
+    available_markets: &[String],
+    restrictions: Option<&Restriction>,
+) -> String {
+    let is_restricted =
+        restrictions.is_some_and(|restriction| restriction.reason == RestrictionReason::Market);
+    let message = match app.get_user_country().map(<&'static str>::from) {
+        Some(country) if is_restricted || !app.is_available(available_markets) => {
+            format!("This album isn't available in your market ({})", country)
+        }
+        _ => String::from("This album has no tracks"),
+    };
+    with_back_hint(app, &message)
+}
+
+// A bordered list scrolls just far enough to show the selected item in its last row
+fn record_list_area(
+    app: &App,
+    block: ActiveBlock,
+    layout_chunk: Rect,
+    selected_index: Option<usize>,
+    len: usize,
+) {
+    let rows = layout_chunk.inner(&Margin {
+        vertical: 1,
+        horizontal: 1,
+    });
+    let offset = selected_index
+        .unwrap_or(0)
+        .saturating_sub((rows.height as usize).saturating_sub(1));
+    if let Ok(mut control_areas) = app.control_areas.lock() {
+        control_areas.lists.push(ListArea {
+            block,
+            rows,
+            offset,
+            len,
+        });
+    }
 }
 
 pub fn draw_user_block<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
@@ -393,7 +634,7 @@ where
             })
         });
 
-        let songs = match &app.search_results.tracks {
+        let songs = match app.search_results.tracks.get_results(None) {
             Some(tracks) => tracks
                 .items
                 .iter()
@@ -409,9 +650,7 @@ where
                             }
                             _ => {}
                         };
-                        if app.liked_song_ids_set.contains(&id) {
-                            song_name += &app.user_config.padded_liked_icon();
-                        }
+                        song_name += &library_icon(app, &app.liked_song_ids_set, &id);
                     }
 
                     song_name += &item.name;
@@ -426,25 +665,33 @@ where
             f,
             app,
             song_artist_block[0],
-            "Songs",
+            &search_block_title("Songs", &app.search_results.tracks),
             &songs,
             get_search_results_highlight_state(app, SearchResultBlock::SongSearch),
             app.search_results.selected_tracks_index,
         );
 
-        let artists = match &app.search_results.artists {
-            Some(artists) => artists
-                .items
-                .iter()
-                .map(|item| {
-                    let mut artist = String::new();
-                    if app.followed_artist_ids_set.contains(&item.id.to_owned()) {
-                        artist.push_str(&app.user_config.padded_liked_icon());
-                    }
-                    artist.push_str(&item.name.to_owned());
-                    artist
-                })
-                .collect(),
+        let artists = match app.search_results.artists.get_results(None) {
+            Some(artists) => {
+                let search_term = &app.search_results.search_term;
+                // Artists sharing the name searched for are told apart by their followers
+                let is_ambiguous = artist_name_match_count(search_term, &artists.items) > 1;
+                artists
+                    .items
+                    .iter()
+                    .map(|item| {
+                        let mut artist = library_icon(app, &app.followed_artist_ids_set, &item.id);
+                        artist.push_str(&item.name.to_owned());
+                        if is_ambiguous && is_artist_name_match(search_term, item) {
+                            artist.push_str(&format!(
+                                " ({})",
+                                display_followers(item.followers.total)
+                            ));
+                        }
+                        artist
+                    })
+                    .collect()
+            }
             None => vec![],
         };
 
@@ -452,7 +699,7 @@ where
             f,
             app,
             song_artist_block[1],
-            "Artists",
+            &search_block_title("Artists", &app.search_results.artists),
             &artists,
             get_search_results_highlight_state(app, SearchResultBlock::ArtistSearch),
             app.search_results.selected_artists_index,
@@ -465,17 +712,14 @@ where
             .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
             .split(chunks[1]);
 
-        let albums = match &app.search_results.albums {
+        let albums = match app.search_results.albums.get_results(None) {
             Some(albums) => albums
                 .items
                 .iter()
                 .map(|item| {
-                    let mut album_artist = String::new();
-                    if let Some(album_id) = &item.id {
-                        if app.saved_album_ids_set.contains(&album_id.to_owned()) {
-                            album_artist.push_str(&app.user_config.padded_liked_icon());
-                        }
-                    }
+                    let mut album_artist = item.id.as_ref().map_or_else(String::new, |album_id| {
+                        library_icon(app, &app.saved_album_ids_set, album_id)
+                    });
                     album_artist.push_str(&format!(
                         "{} - {} ({})",
                         item.name.to_owned(),
@@ -492,13 +736,13 @@ where
             f,
             app,
             albums_playlist_block[0],
-            "Albums",
+            &search_block_title("Albums", &app.search_results.albums),
             &albums,
             get_search_results_highlight_state(app, SearchResultBlock::AlbumSearch),
             app.search_results.selected_album_index,
         );
 
-        let playlists = match &app.search_results.playlists {
+        let playlists = match app.search_results.playlists.get_results(None) {
             Some(playlists) => playlists
                 .items
                 .iter()
@@ -510,7 +754,7 @@ where
             f,
             app,
             albums_playlist_block[1],
-            "Playlists",
+            &search_block_title("Playlists", &app.search_results.playlists),
             &playlists,
             get_search_results_highlight_state(app, SearchResultBlock::PlaylistSearch),
             app.search_results.selected_playlists_index,
@@ -523,37 +767,71 @@ where
             .constraints([Constraint::Percentage(100)].as_ref())
             .split(chunks[2]);
 
-        let podcasts = match &app.search_results.shows {
+        let podcasts = match app.search_results.shows.get_results(None) {
             Some(podcasts) => podcasts
                 .items
                 .iter()
                 .map(|item| {
-                    let mut show_name = String::new();
-                    if app.saved_show_ids_set.contains(&item.id.to_owned()) {
-                        show_name.push_str(&app.user_config.padded_liked_icon());
-                    }
+                    let mut show_name = library_icon(app, &app.saved_show_ids_set, &item.id);
                     show_name.push_str(&format!("{:} - {}", item.name, item.publisher));
-                    show_name
+                    // Dimmed when it can't be played in the user's market
+                    if app.is_available(&item.available_markets) {
+                        ListItem::new(show_name)
+                    } else {
+                        ListItem::new(show_name)
+                            .style(Style::default().fg(app.user_config.theme.inactive))
+                    }
                 })
                 .collect(),
             None => vec![],
         };
-        draw_selectable_list(
+        draw_list(
             f,
             app,
             podcasts_block[0],
-            "Podcasts",
-            &podcasts,
+            &search_block_title("Podcasts", &app.search_results.shows),
+            podcasts,
             get_search_results_highlight_state(app, SearchResultBlock::ShowSearch),
             app.search_results.selected_shows_index,
         );
     }
 }
 
+// The title of a block of search results, with the page shown when there are more
+fn search_block_title<T>(title: &str, pages: &ScrollableResultPages<Page<T>>) -> String {
+    match pages.page_count() {
+        Some((page, count)) if count > 1 => format!("{} (page {} of {})", title, page, count),
+        _ => String::from(title),
+    }
+}
+
+// Like the title of search results, followed by the paging keys that lead anywhere from the page
+// shown
+fn library_block_title<T>(
+    app: &App,
+    title: &str,
+    pages: &ScrollableResultPages<Page<T>>,
+) -> String {
+    let Some((page, count)) = pages.page_count().filter(|(_, count)| *count > 1) else {
+        return String::from(title);
+    };
+    let keys = &app.user_config.keys;
+    let mut parts = vec![format!("{} (page {} of {})", title, page, count)];
+    if page > 1 {
+        parts.push(format!("{} previous", keys.previous_page));
+    }
+    if page < count {
+        parts.push(format!("{} next", keys.next_page));
+    }
+    parts.join(" · ")
+}
+
 struct AlbumUi {
     selected_index: usize,
     items: Vec<TableItem>,
     title: String,
+    // Shown when the album has no tracks
+    empty_message: String,
 }
 
 pub fn draw_artist_table<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
@@ -591,6 +869,59 @@ where
         &items,
         app.artists_list_index,
         highlight_state,
+    );
+    if items.is_empty() && app.library.saved_artists.get_results(None).is_some() {
+        draw_empty_state(
+            f,
+            app,
+            table_rows_area(layout_chunk),
+            "No followed artists yet",
+        );
+    }
+}
+
+// Like the followed artists, marked when they are followed too
+pub fn draw_top_artist_table<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
+where
+    B: Backend,
+{
+    let header = TableHeader {
+        id: TableId::Artist,
+        items: vec![TableHeaderItem {
+            text: "Artist",
+            width: get_percentage_width(layout_chunk.width, 1.0),
+            ..Default::default()
+        }],
+    };
+
+    let current_route = app.get_current_route();
+    let highlight_state = (
+        current_route.active_block == ActiveBlock::TopArtists,
+        current_route.hovered_block == ActiveBlock::TopArtists,
+    );
+    let items = app
+        .top_items
+        .artists
+        .iter()
+        .flat_map(|page| &page.items)
+        .map(|item| TableItem {
+            id: item.id.to_string(),
+            format: vec![format!(
+                "{}{}",
+                library_icon(app, &app.followed_artist_ids_set, &item.id),
+                item.name
+            )],
+        })
+        .collect::<Vec<TableItem>>();
+
+    draw_table(
+        f,
+        app,
+        layout_chunk,
+        (&app.top_items.title(), &header),
+        &items,
+        app.top_items.selected_artist_index,
+        highlight_state,
     )
 }
 
@@ -638,12 +969,143 @@ where
             f,
             app,
             layout_chunk,
-            ("Podcasts", &header),
+            (
+                &library_block_title(app, "Podcasts", &app.library.saved_shows),
+                &header,
+            ),
             &items,
             app.shows_list_index,
             highlight_state,
-        )
+        );
+        if items.is_empty() {
+            draw_empty_state(
+                f,
+                app,
+                table_rows_area(layout_chunk),
+                "No saved podcasts yet",
+            );
+        }
+    };
+}
+
+pub fn draw_finished_shows<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
+where
+    B: Backend,
+{
+    let header = TableHeader {
+        id: TableId::FinishedShows,
+        items: vec![
+            TableHeaderItem {
+                text: "Unfollow",
+                width: 10,
+                ..Default::default()
+            },
+            TableHeaderItem {
+                text: "Name",
+                width: get_percentage_width(layout_chunk.width, 2.0 / 5.0),
+                ..Default::default()
+            },
+            TableHeaderItem {
+                text: "Publisher(s)",
+                width: get_percentage_width(layout_chunk.width, 2.0 / 5.0),
+                ..Default::default()
+            },
+        ],
+    };
+
+    let current_route = app.get_current_route();
+
+    let highlight_state = (
+        current_route.active_block == ActiveBlock::FinishedShows,
+        current_route.hovered_block == ActiveBlock::FinishedShows,
+    );
+
+    let Some(finished_shows) = &app.finished_shows else {
+        return;
+    };
+    let items = finished_shows
+        .shows
+        .iter()
+        .map(|(show, is_removed)| TableItem {
+            id: show.id.to_string(),
+            format: vec![
+                String::from(if *is_removed { "[x]" } else { "[ ]" }),
+                show.name.to_owned(),
+                show.publisher.to_owned(),
+            ],
+        })
+        .collect::<Vec<TableItem>>();
+
+    draw_table(
+        f,
+        app,
+        layout_chunk,
+        (
+            "Finished podcasts (V to keep one, <Enter> to unfollow the rest)",
+            &header,
+        ),
+        &items,
+        finished_shows.selected_index,
+        highlight_state,
+    )
+}
+
+pub fn draw_notification_history<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
+where
+    B: Backend,
+{
+    let header = TableHeader {
+        id: TableId::NotificationHistory,
+        items: vec![
+            TableHeaderItem {
+                text: "Time",
+                width: 10,
+                ..Default::default()
+            },
+            TableHeaderItem {
+                text: "Severity",
+                width: 10,
+                ..Default::default()
+            },
+            TableHeaderItem {
+                text: "Message",
+                width: layout_chunk.width.saturating_sub(24),
+                ..Default::default()
+            },
+        ],
     };
+
+    let current_route = app.get_current_route();
+
+    let highlight_state = (
+        current_route.active_block == ActiveBlock::NotificationHistory,
+        current_route.hovered_block == ActiveBlock::NotificationHistory,
+    );
+
+    let items = app
+        .notifications
+        .history()
+        .iter()
+        .rev()
+        .map(|notification| TableItem {
+            id: String::new(),
+            format: vec![
+                notification.timestamp.format("%H:%M:%S").to_string(),
+                notification.severity.as_str().to_string(),
+                notification.text(),
+            ],
+        })
+        .collect::<Vec<TableItem>>();
+
+    draw_table(
+        f,
+        app,
+        layout_chunk,
+        ("Notifications", &header),
+        &items,
+        app.notification_history_index,
+        highlight_state,
+    )
 }
 
 pub fn draw_album_table<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
@@ -712,11 +1174,23 @@ where
                         })
                         .collect::<Vec<TableItem>>(),
                     title: format!(
-                        "{} by {}",
+                        "{}{} by {}",
+                        selected_album_simplified
+                            .album
+                            .id
+                            .as_ref()
+                            .map_or_else(String::new, |id| {
+                                library_icon(app, &app.saved_album_ids_set, id)
+                            }),
                         selected_album_simplified.album.name,
                         create_artist_string(&selected_album_simplified.album.artists)
                     ),
                     selected_index: selected_album_simplified.selected_index,
+                    empty_message: empty_album_message(
+                        app,
+                        &selected_album_simplified.album.available_markets,
+                        selected_album_simplified.album.restrictions.as_ref(),
+                    ),
                 })
         }
         AlbumTableContext::Full => match app.selected_album_full.clone() {
@@ -742,11 +1216,21 @@ where
                     })
                     .collect::<Vec<TableItem>>(),
                 title: format!(
-                    "{} by {}",
+                    "{}{} by {}",
+                    library_icon(app, &app.saved_album_ids_set, &selected_album.album.id),
                     selected_album.album.name,
                     create_artist_string(&selected_album.album.artists)
                 ),
                 selected_index: app.saved_album_tracks_index,
+                empty_message: empty_album_message(
+                    app,
+                    selected_album
+                        .album
+                        .available_markets
+                        .as_deref()
+                        .unwrap_or_default(),
+                    None,
+                ),
             }),
             None => None,
         },
@@ -762,6 +1246,14 @@ where
             album_ui.selected_index,
             highlight_state,
         );
+        if album_ui.items.is_empty() {
+            draw_empty_state(
+                f,
+                app,
+                table_rows_area(layout_chunk),
+                &album_ui.empty_message,
+            );
+        }
     };
 }
 
@@ -769,70 +1261,6 @@ pub fn draw_recommendations_table<B>(f: &mut Frame<B>, app: &App, layout_chunk:
 where
     B: Backend,
 {
-    let header = TableHeader {
-        id: TableId::Song,
-        items: vec![
-            TableHeaderItem {
-                id: ColumnId::Liked,
-                text: "",
-                width: 2,
-            },
-            TableHeaderItem {
-                id: ColumnId::Title,
-                text: "Title",
-                width: get_percentage_width(layout_chunk.width, 0.3),
-            },
-            TableHeaderItem {
-                text: "Artist",
-                width: get_percentage_width(layout_chunk.width, 0.3),
-                ..Default::default()
-            },
-            TableHeaderItem {
-                text: "Album",
-                width: get_percentage_width(layout_chunk.width, 0.3),
-                ..Default::default()
-            },
-            TableHeaderItem {
-                text: "Length",
-                width: get_percentage_width(layout_chunk.width, 0.1),
-                ..Default::default()
-            },
-        ],
-    };
-
-    let current_route = app.get_current_route();
-    let highlight_state = (
-        current_route.active_block == ActiveBlock::ItemTable,
-        current_route.hovered_block == ActiveBlock::ItemTable,
-    );
-
-    let items = app
-        .item_table
-        .items
-        .iter()
-        .map(|item| TableItem {
-            id: item
-                .id()
-                .map(|x| x.to_string())
-                .unwrap_or_else(|| "".to_string()),
-            format: match item {
-                PlayableItem::Track(track) => vec![
-                    "".to_string(),
-                    item.name().to_owned(),
-                    create_artist_string(&track.artists),
-                    track.album.name.to_owned(),
-                    millis_to_minutes(item.duration().num_milliseconds() as u128),
-                ],
-                PlayableItem::Episode(episode) => vec![
-                    "".to_string(),
-                    item.name().to_owned(),
-                    episode.show.publisher.to_string(),
-                    episode.show.name.to_owned(),
-                    millis_to_minutes(item.duration().num_milliseconds() as u128),
-                ],
-            },
-        })
-        .collect::<Vec<TableItem>>();
     // match RecommendedContext
     let recommendations_ui = match &app.recommendations_context {
         Some(RecommendationsContext::Song) => format!(
@@ -843,53 +1271,151 @@ where
             "Recommendations based on Artist \'{}\'",
             &app.recommendations_seed
         ),
+        Some(RecommendationsContext::LikedSongs) => {
+            "Recommendations based on your Liked Songs".to_string()
+        }
         None => "Recommendations".to_string(),
     };
-    draw_table(
-        f,
-        app,
-        layout_chunk,
-        (&recommendations_ui[..], &header),
-        &items,
-        app.item_table.selected_index,
-        highlight_state,
-    )
+    draw_item_table(f, app, layout_chunk, &recommendations_ui);
 }
 
 pub fn draw_song_table<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
 where
     B: Backend,
 {
-    let header = TableHeader {
+    // Playlists are titled with their name, marked when they are followed
+    let title = match app.item_table_playlist() {
+        Some(playlist) => format!(
+            "{}{}",
+            saved_icon(app, app.is_playlist_followed(&playlist.id)),
+            playlist.name
+        ),
+        None if app.item_table.context == Some(ItemTableContext::TopTracks) => {
+            app.top_items.title()
+        }
+        None => String::from("Songs"),
+    };
+    draw_item_table(f, app, layout_chunk, &title);
+}
+
+// Marks an album or playlist title when it is in the library
+fn saved_icon(app: &App, is_saved: bool) -> String {
+    if is_saved {
+        app.user_config.padded_liked_icon()
+    } else {
+        String::new()
+    }
+}
+
+// Marks an item in the library, with the stale icon while that's only remembered from the last
+// session
+fn library_icon<T, Q>(app: &App, ids: &LibraryIds<T>, id: &Q) -> String
+where
+    T: Eq + Hash + Borrow<Q>,
+    Q: Eq + Hash + ?Sized,
+{
+    if ids.contains(id) {
+        app.user_config.padded_library_icon(ids.is_stale(id))
+    } else {
+        String::new()
+    }
+}
+
+// Marks a saved track or episode. One that hasn't been checked yet is left unmarked like one that
+// isn't saved, rather than shown as either for sure.
+fn item_icon(app: &App, id: &PlayableId<'static>) -> String {
+    match (is_item_saved(app, id), id) {
+        (Some(true), PlayableId::Track(id)) => library_icon(app, &app.liked_song_ids_set, id),
+        (Some(true), PlayableId::Episode(id)) => library_icon(app, &app.liked_episode_ids_set, id),
+        _ => String::new(),
+    }
+}
+
+// How much of the width a column takes, relative to the others
+fn column_weight(column: ItemTableColumn) -> f32 {
+    match column {
+        ItemTableColumn::Title | ItemTableColumn::Artist | ItemTableColumn::Album => 0.3,
+        ItemTableColumn::Duration | ItemTableColumn::Popularity => 0.1,
+        ItemTableColumn::AddedAt => 0.15,
+    }
+}
+
+fn column_cell(column: ItemTableColumn, item: &PlayableItem, metadata: &ItemMetadata) -> String {
+    match (column, item) {
+        (ItemTableColumn::Title, item) => item.name().to_owned(),
+        (ItemTableColumn::Artist, PlayableItem::Track(track)) => {
+            create_artist_string(&track.artists)
+        }
+        (ItemTableColumn::Artist, PlayableItem::Episode(episode)) => {
+            episode.show.publisher.to_string()
+        }
+        (ItemTableColumn::Album, PlayableItem::Track(track)) => track.album.name.to_owned(),
+        (ItemTableColumn::Album, PlayableItem::Episode(episode)) => episode.show.name.to_owned(),
+        (ItemTableColumn::Duration, item) => {
+            millis_to_minutes(item.duration().num_milliseconds() as u128)
+        }
+        (ItemTableColumn::Popularity, PlayableItem::Track(track)) => track.popularity.to_string(),
+        (ItemTableColumn::Popularity, PlayableItem::Episode(_)) => String::new(),
+        (ItemTableColumn::AddedAt, _) => metadata
+            .added_at
+            .map(|added_at| {
+                added_at
+                    .with_timezone(&Local)
+                    .format("%Y-%m-%d")
+                    .to_string()
+            })
+            .unwrap_or_default(),
+    }
+}
+
+// The configured columns, followed by the audio feature the items are sorted by
+fn draw_item_table<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect, title: &str)
+where
+    B: Backend,
+{
+    let columns = &app.user_config.behavior.item_table_columns;
+    let sort = app.item_table.sort;
+    let labels = columns
+        .iter()
+        .map(|column| match app.item_table.column_sort {
+            Some(ColumnSort {
+                column: sorted_column,
+                ascending,
+            }) if sorted_column == *column => {
+                format!("{} {}", column.label(), if ascending { "▲" } else { "▼" })
+            }
+            _ => column.label().to_string(),
+        })
+        .collect::<Vec<_>>();
+    let feature_weight = if sort.is_some() { 0.1 } else { 0.0 };
+    let total_weight = columns.iter().copied().map(column_weight).sum::<f32>() + feature_weight;
+
+    let mut header = TableHeader {
         id: TableId::Song,
-        items: vec![
-            TableHeaderItem {
-                id: ColumnId::Liked,
-                text: "",
-                width: 2,
-            },
-            TableHeaderItem {
-                id: ColumnId::Title,
-                text: "Title",
-                width: get_percentage_width(layout_chunk.width, 0.3),
-            },
-            TableHeaderItem {
-                text: "Artist",
-                width: get_percentage_width(layout_chunk.width, 0.3),
-                ..Default::default()
-            },
-            TableHeaderItem {
-                text: "Album",
-                width: get_percentage_width(layout_chunk.width, 0.3),
-                ..Default::default()
-            },
-            TableHeaderItem {
-                text: "Length",
-                width: get_percentage_width(layout_chunk.width, 0.1),
-                ..Default::default()
-            },
-        ],
+        items: vec![TableHeaderItem {
+            id: ColumnId::Liked,
+            text: "",
+            width: 2,
+        }],
     };
+    for (column, label) in columns.iter().zip(&labels) {
+        header.items.push(TableHeaderItem {
+            id: if *column == ItemTableColumn::Title {
+                ColumnId::Title
+            } else {
+                ColumnId::None
+            },
+            text: label,
+            width: get_percentage_width(layout_chunk.width, column_weight(*column) / total_weight),
+        });
+    }
+    if let Some(feature) = sort {
+        header.items.push(TableHeaderItem {
+            text: feature.label(),
+            width: get_percentage_width(layout_chunk.width, feature_weight / total_weight),
+            ..Default::default()
+        });
+    }
 
     let current_route = app.get_current_route();
     let highlight_state = (
@@ -901,42 +1427,127 @@ where
         .item_table
         .items
         .iter()
-        .map(|item| TableItem {
-            id: item
-                .id()
-                .map(|x| x.to_string())
-                .unwrap_or_else(|| "".to_string()),
-            format: match item {
-                PlayableItem::Episode(episode) => vec![
-                    "".to_string(),
-                    item.name().to_owned(),
-                    episode.show.publisher.to_string(),
-                    episode.show.name.to_owned(),
-                    millis_to_minutes(item.duration().num_milliseconds() as u128),
-                ],
-                PlayableItem::Track(track) => vec![
-                    "".to_string(),
-                    item.name().to_owned(),
-                    create_artist_string(&track.artists),
-                    track.album.name.to_owned(),
-                    millis_to_minutes(item.duration().num_milliseconds() as u128),
-                ],
-            },
+        .enumerate()
+        .map(|(index, item)| {
+            let metadata = app.item_table.metadata(index);
+            let mut format = vec!["".to_string()];
+            format.extend(
+                columns
+                    .iter()
+                    .map(|column| column_cell(*column, item, &metadata)),
+            );
+            // Marked to be queued together
+            if let Some(first_column) = format
+                .get_mut(1)
+                .filter(|_| app.item_table.is_row_selected(index))
+            {
+                first_column.insert_str(0, "● ");
+            }
+            if let Some(feature) = sort {
+                let features = match item {
+                    PlayableItem::Track(track) => track
+                        .id
+                        .as_ref()
+                        .and_then(|track_id| app.audio_features.get(track_id)),
+                    PlayableItem::Episode(_) => None,
+                };
+                format.push(
+                    features
+                        .map(|features| feature.format(features))
+                        .unwrap_or_default(),
+                );
+            }
+            TableItem {
+                id: item
+                    .id()
+                    .map(|x| x.to_string())
+                    .unwrap_or_else(|| "".to_string()),
+                format,
+            }
         })
         .collect::<Vec<TableItem>>();
 
+    let title = match app.item_table.selected_rows.len() {
+        0 => title.to_string(),
+        count => format!("{} ({} marked)", title, count),
+    };
     draw_table(
         f,
         app,
         layout_chunk,
-        ("Songs", &header),
+        (&title, &header),
         &items,
         app.item_table.selected_index,
         highlight_state,
-    )
+    );
+    if items.is_empty() {
+        let is_empty_playlist = app
+            .item_table_playlist()
+            .is_some_and(|playlist| playlist.tracks.total == 0);
+        let message = match app.item_table.context {
+            _ if is_empty_playlist => Some(with_back_hint(app, "This playlist is empty")),
+            Some(ItemTableContext::SavedTracks)
+                if app.library.saved_tracks.get_results(None).is_some() =>
+            {
+                Some(String::from("No liked songs yet"))
+            }
+            _ => None,
+        };
+        if let Some(message) = message {
+            draw_empty_state(f, app, table_rows_area(layout_chunk), &message);
+        }
+    }
+    if app.item_table.is_features_popup_open {
+        draw_features_popup(f, app, layout_chunk);
+    }
+}
+
+// The audio features of the selected row, over the middle of the table
+fn draw_features_popup<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
+where
+    B: Backend,
+{
+    let Some(item) = app.item_table.items.get(app.item_table.selected_index) else {
+        return;
+    };
+    let lines = match item {
+        PlayableItem::Episode(_) => vec![String::from("Podcast episodes have no audio features")],
+        PlayableItem::Track(track) => match &track.id {
+            None => vec![String::from("Local files have no audio features")],
+            Some(track_id) => match app.audio_features.get(track_id) {
+                Some(features) => audio_analysis::feature_lines(features),
+                None if app.loading_track_features.contains(track_id) => {
+                    vec![String::from("Loading...")]
+                }
+                None => vec![String::from("No audio features available")],
+            },
+        },
+    };
+
+    let width = layout_chunk.width.saturating_sub(2).min(44);
+    let height = (lines.len() as u16 + 2).min(layout_chunk.height);
+    let rect = Rect::new(
+        layout_chunk.x + (layout_chunk.width - width) / 2,
+        layout_chunk.y + (layout_chunk.height - height) / 2,
+        width,
+        height,
+    );
+    f.render_widget(Clear, rect);
+
+    let style = Style::default().fg(app.user_config.theme.inactive);
+    let text = lines.into_iter().map(Spans::from).collect::<Vec<_>>();
+    let popup = Paragraph::new(text)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(Span::styled(item.name(), style))
+                .border_style(style),
+        )
+        .style(Style::default().fg(app.user_config.theme.text));
+    f.render_widget(popup, rect);
 }
 
-pub fn draw_basic_view<B>(f: &mut Frame<B>, app: &App)
+pub fn draw_basic_view<B>(f: &mut Frame<B>, app: &App) -> Option<Rect>
 where
     B: Backend,
 {
@@ -955,14 +1566,38 @@ where
             )
             .split(f.size());
 
-        draw_playbar(f, app, chunks[1]);
+        draw_playbar(f, app, chunks[1])
+    } else {
+        None
     }
 }
 
-pub fn draw_playbar<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
+// Album art takes up a square-ish area on the left of the playbar, assuming terminal cells are
+// twice as tall as they are wide
+fn split_album_art(app: &App, layout_chunk: Rect) -> (Rect, Option<Rect>) {
+    let height = layout_chunk.height.saturating_sub(2);
+    let width = height * 2;
+    if height == 0 || width + 2 >= layout_chunk.width / 2 || app.current_album_art().is_none() {
+        return (layout_chunk, None);
+    }
+
+    let album_art_area = Rect::new(layout_chunk.x + 1, layout_chunk.y + 1, width, height);
+    let text_chunk = Rect {
+        x: layout_chunk.x + width + 1,
+        width: layout_chunk.width - width - 1,
+        ..layout_chunk
+    };
+    (text_chunk, Some(album_art_area))
+}
+
+pub fn draw_playbar<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect) -> Option<Rect>
 where
     B: Backend,
 {
+    let (text_chunk, album_art_area) = split_album_art(app, layout_chunk);
+    if let Ok(mut control_areas) = app.control_areas.lock() {
+        control_areas.playbar.clear();
+    }
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints(
@@ -974,36 +1609,27 @@ where
             .as_ref(),
         )
         .margin(1)
-        .split(layout_chunk);
+        .split(text_chunk);
 
     // If no track is playing, render paragraph showing which device is selected, if no selected
     // give hint to choose a device
     if let Some(current_playback_context) = &app.current_playback_context {
         if let Some(track_item) = &current_playback_context.item {
-            let play_title = if current_playback_context.is_playing {
+            // What plays next is shown as soon as the item ends, until a poll has it
+            let is_upcoming = app.upcoming_item.is_some();
+            let track_item = app.upcoming_item.as_ref().unwrap_or(track_item);
+            let play_title = if is_upcoming {
+                "Loading"
+            } else if current_playback_context.is_playing {
                 "Playing"
             } else {
                 "Paused"
             };
 
-            let shuffle_text = if current_playback_context.shuffle_state {
-                "On"
-            } else {
-                "Off"
-            };
-
-            let repeat_text = match current_playback_context.repeat_state {
-                RepeatState::Off => "Off",
-                RepeatState::Track => "Track",
-                RepeatState::Context => "All",
-            };
-
             let title = format!(
-                "{:-7} ({} | Shuffle: {:-3} | Repeat: {:-5} | Volume: {:-2}%)",
+                "{:-7} ({} | Volume: {:-2}%)",
                 play_title,
                 current_playback_context.device.name,
-                shuffle_text,
-                repeat_text,
                 current_playback_context
                     .device
                     .volume_percent
@@ -1016,13 +1642,19 @@ where
                 current_route.hovered_block == ActiveBlock::PlayBar,
             );
 
+            let title_style = get_color(highlight_state, app.user_config.theme);
+            let mut title_spans = vec![Span::styled(&title, title_style)];
+            let icons = playback_mode_icons(app);
+            if !icons.is_empty() {
+                title_spans.push(Span::styled(
+                    format!(" {} ", icons),
+                    title_style.add_modifier(Modifier::BOLD),
+                ));
+            }
             let title_block = Block::default()
                 .borders(Borders::ALL)
-                .title(Span::styled(
-                    &title,
-                    get_color(highlight_state, app.user_config.theme),
-                ))
-                .border_style(get_color(highlight_state, app.user_config.theme));
+                .title(Spans::from(title_spans))
+                .border_style(title_style);
 
             f.render_widget(title_block, layout_chunk);
 
@@ -1039,15 +1671,12 @@ where
                 ),
             };
 
-            let track_name = match &item_id {
-                Some(PlayableId::Episode(id)) if app.liked_episode_ids_set.contains(id) => {
-                    format!("{}{name}", &app.user_config.padded_liked_icon())
-                }
-                Some(PlayableId::Track(id)) if app.liked_song_ids_set.contains(id) => {
-                    format!("{}{name}", &app.user_config.padded_liked_icon())
-                }
-                _ => name,
-            };
+            let liked_icon = item_id
+                .as_ref()
+                .map(|id| item_icon(app, id))
+                .unwrap_or_default();
+            let is_liked = !liked_icon.is_empty();
+            let track_name = format!("{liked_icon}{name}");
 
             let play_bar_text = match track_item {
                 PlayableItem::Track(track) => create_artist_string(&track.artists),
@@ -1073,14 +1702,21 @@ where
                 );
             f.render_widget(artist, chunks[0]);
 
+            draw_playbar_controls(f, app, highlight_state.0, is_liked, chunks[1]);
+
             let progress_ms = match app.seek_ms {
+                _ if is_upcoming => 0,
                 Some(seek_ms) => seek_ms,
                 None => app.song_progress_ms,
             };
 
             let perc = get_track_progress_percentage(progress_ms, duration_ms as u32);
 
-            let song_progress_label = display_track_progress(progress_ms, duration_ms as u32);
+            let mut song_progress_label = display_track_progress(progress_ms, duration_ms as u32);
+            // The device left the progress out, so it's only where it should be by now
+            if app.seek_ms.is_none() && app.progress_estimate.is_approximate() && !is_upcoming {
+                song_progress_label.insert(0, '~');
+            }
             let modifier = if app.user_config.behavior.enable_text_emphasis {
                 Modifier::ITALIC | Modifier::BOLD
             } else {
@@ -1099,7 +1735,102 @@ where
                     Style::default().fg(app.user_config.theme.playbar_progress_text),
                 ));
             f.render_widget(song_progress, chunks[2]);
+            if let Ok(mut control_areas) = app.control_areas.lock() {
+                control_areas.progress = Some(chunks[2]);
+            }
+        }
+    }
+
+    album_art_area
+}
+
+// The configured icons of the shuffle and repeat modes that are on, the same ones `spt` prints
+// for its flags. Modes that are off have none.
+fn playback_mode_icons(app: &App) -> String {
+    let Some(context) = &app.current_playback_context else {
+        return String::new();
+    };
+    let behavior = &app.user_config.behavior;
+    let shuffle = context
+        .shuffle_state
+        .then_some(behavior.shuffle_icon.as_str());
+    let repeat = match context.repeat_state {
+        RepeatState::Off => None,
+        RepeatState::Track => Some(behavior.repeat_track_icon.as_str()),
+        RepeatState::Context => Some(behavior.repeat_context_icon.as_str()),
+    };
+    [shuffle, repeat]
+        .into_iter()
+        .flatten()
+        .filter(|icon| !icon.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn playbar_control_label(app: &App, control: PlaybarControl, is_liked: bool) -> String {
+    let Some(context) = &app.current_playback_context else {
+        return String::new();
+    };
+    match control {
+        PlaybarControl::Shuffle => {
+            format!(
+                "Shuffle: {}",
+                if context.shuffle_state { "On" } else { "Off" }
+            )
+        }
+        PlaybarControl::Repeat => format!("Repeat: {}", repeat_state_name(context.repeat_state)),
+        PlaybarControl::Like if is_liked => String::from("Liked"),
+        PlaybarControl::Like => String::from("Like"),
+        PlaybarControl::Previous => String::from("Previous"),
+        PlaybarControl::PlayPause if context.is_playing => String::from("Pause"),
+        PlaybarControl::PlayPause => String::from("Play"),
+        PlaybarControl::Next => String::from("Next"),
+    }
+}
+
+// Draw each playbar control as its own segment, recording where it ended up
+fn draw_playbar_controls<B>(
+    f: &mut Frame<B>,
+    app: &App,
+    is_active: bool,
+    is_liked: bool,
+    layout_chunk: Rect,
+) where
+    B: Backend,
+{
+    let text_style = Style::default().fg(app.user_config.theme.playbar_text);
+    let mut spans = vec![];
+    let mut areas = vec![];
+    let mut x = layout_chunk.x;
+    for (i, control) in PlaybarControl::ALL.into_iter().enumerate() {
+        if i > 0 {
+            spans.push(Span::styled(PLAYBAR_CONTROL_SEPARATOR, text_style));
+            x += PLAYBAR_CONTROL_SEPARATOR.width() as u16;
         }
+
+        let label = format!(" {} ", playbar_control_label(app, control, is_liked));
+        let width = label.width() as u16;
+        let style = if is_active && app.playbar_control == control {
+            Style::default()
+                .fg(app.user_config.theme.selected)
+                .add_modifier(Modifier::REVERSED)
+        } else {
+            text_style
+        };
+        spans.push(Span::styled(label, style));
+
+        // Controls that don't fit are cut off and can't be clicked
+        let right = layout_chunk.x + layout_chunk.width;
+        if x < right {
+            let area = Rect::new(x, layout_chunk.y, width.min(right - x), 1);
+            areas.push((control, area));
+        }
+        x += width;
+    }
+
+    f.render_widget(Paragraph::new(Spans::from(spans)), layout_chunk);
+    if let Ok(mut control_areas) = app.control_areas.lock() {
+        control_areas.playbar = areas;
     }
 }
 
@@ -1221,6 +1952,51 @@ where
     f.render_widget(bottom_text, chunks[1]);
 }
 
+fn draw_lyrics<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
+where
+    B: Backend,
+{
+    let current_route = app.get_current_route();
+    let highlight_state = (
+        current_route.active_block == ActiveBlock::Lyrics,
+        current_route.hovered_block == ActiveBlock::Lyrics,
+    );
+
+    let playing_item = app
+        .current_playback_context
+        .as_ref()
+        .and_then(|context| context.item.as_ref());
+    let title = match playing_item {
+        Some(PlayableItem::Track(track)) => format!(
+            "Lyrics - {} by {}",
+            track.name,
+            create_artist_string(&track.artists)
+        ),
+        _ => String::from("Lyrics"),
+    };
+
+    let lyrics = match app.current_lyrics() {
+        Some(Lyrics::Found(lyrics)) => lyrics.as_str(),
+        Some(Lyrics::Loading) => "Loading...",
+        Some(Lyrics::NotFound) | None => "No lyrics found",
+    };
+
+    let paragraph = Paragraph::new(Text::from(lyrics))
+        .style(Style::default().fg(app.user_config.theme.text))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(Span::styled(
+                    title,
+                    get_color(highlight_state, app.user_config.theme),
+                ))
+                .border_style(get_color(highlight_state, app.user_config.theme)),
+        )
+        .wrap(Wrap { trim: false })
+        .scroll((app.lyrics_scroll, 0));
+    f.render_widget(paragraph, layout_chunk);
+}
+
 fn draw_artist_albums<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
 where
     B: Backend,
@@ -1262,23 +2038,23 @@ where
             f,
             app,
             chunks[0],
-            &format!("{} - Top Tracks", &artist.artist_name),
+            &format!(
+                "{}{} - Top Tracks",
+                library_icon(app, &app.followed_artist_ids_set, &artist.artist_id),
+                &artist.artist_name
+            ),
             &top_tracks,
             get_artist_highlight_state(app, ArtistBlock::TopTracks),
             Some(artist.selected_top_track_index),
         );
 
         let albums = &artist
-            .albums
-            .items
-            .iter()
+            .albums()
+            .into_iter()
             .map(|item| {
-                let mut album_artist = String::new();
-                if let Some(album_id) = &item.id {
-                    if app.saved_album_ids_set.contains(&album_id.to_owned()) {
-                        album_artist.push_str(&app.user_config.padded_liked_icon());
-                    }
-                }
+                let mut album_artist = item.id.as_ref().map_or_else(String::new, |album_id| {
+                    library_icon(app, &app.saved_album_ids_set, album_id)
+                });
                 album_artist.push_str(&format!(
                     "{} - {} ({})",
                     item.name.to_owned(),
@@ -1293,7 +2069,7 @@ where
             f,
             app,
             chunks[1],
-            "Albums",
+            &artist.albums_title(),
             albums,
             get_artist_highlight_state(app, ArtistBlock::Albums),
             Some(artist.selected_album_index),
@@ -1303,10 +2079,7 @@ where
             .related_artists
             .iter()
             .map(|item| {
-                let mut artist = String::new();
-                if app.followed_artist_ids_set.contains(&item.id.to_owned()) {
-                    artist.push_str(&app.user_config.padded_liked_icon());
-                }
+                let mut artist = library_icon(app, &app.followed_artist_ids_set, &item.id);
                 artist.push_str(&item.name.to_owned());
                 artist
             })
@@ -1338,7 +2111,9 @@ where
         "To play tracks, please select a device. ",
         "Use `j/k` or up/down arrow keys to move up and down and <Enter> to select. ",
         "Your choice here will be cached so you can jump straight back in when you next open `spotify-tui`. ",
-        "You can change the playback device at any time by pressing `d`.",
+        "You can change the playback device at any time by pressing `d`. ",
+        "The list refreshes while it is open, so devices started meanwhile show up. ",
+        "Press `/` to type part of the name of a device instead.",
     ].into_iter().map(|instruction| Spans::from(Span::raw(instruction))).collect();
 
     let instructions = Paragraph::new(device_instructions)
@@ -1364,20 +2139,42 @@ where
                 items
                     .devices
                     .iter()
-                    .map(|device| ListItem::new(Span::raw(&device.name)))
+                    .map(|device| {
+                        // The saved device is where playback goes on startup
+                        let style = if device.id.is_some() && device.id == app.saved_device_id {
+                            Style::default().fg(app.user_config.theme.selected)
+                        } else {
+                            Style::default()
+                        };
+                        let mut spans = vec![Span::styled(&device.name, style)];
+                        if device.is_active {
+                            spans.push(Span::styled(
+                                " (active)",
+                                Style::default().fg(app.user_config.theme.hint),
+                            ));
+                        }
+                        ListItem::new(Spans::from(spans))
+                    })
                     .collect()
             }
         }
         None => vec![ListItem::new(no_device_message)],
     };
 
+    // The name being typed shows in place of the title
+    let title = if app.is_finding_device() {
+        format!("Device: {}", app.input.iter().collect::<String>())
+    } else {
+        String::from("Devices")
+    };
+
     let mut state = ListState::default();
     state.select(app.selected_device_index);
     let list = List::new(items)
         .block(
             Block::default()
                 .title(Span::styled(
-                    "Devices",
+                    title,
                     Style::default().fg(app.user_config.theme.active),
                 ))
                 .borders(Borders::ALL)
@@ -1448,11 +2245,17 @@ where
             f,
             app,
             layout_chunk,
-            ("Saved Albums", &header),
+            (
+                &library_block_title(app, "Saved Albums", &app.library.saved_albums),
+                &header,
+            ),
             &items,
             selected_song_index,
             highlight_state,
-        )
+        );
+        if items.is_empty() {
+            draw_empty_state(f, app, table_rows_area(layout_chunk), "No saved albums yet");
+        }
     };
 }
 
@@ -1464,14 +2267,13 @@ where
         id: TableId::PodcastEpisodes,
         items: vec![
             TableHeaderItem {
-                // Column to mark an episode as fully played
-                text: "",
-                width: 2,
+                text: "Played",
+                width: 6,
                 ..Default::default()
             },
             TableHeaderItem {
                 text: "Date",
-                width: get_percentage_width(layout_chunk.width, 0.5 / 5.0) - 2,
+                width: get_percentage_width(layout_chunk.width, 0.5 / 5.0).saturating_sub(6),
                 ..Default::default()
             },
             TableHeaderItem {
@@ -1499,26 +2301,17 @@ where
             .items
             .iter()
             .map(|episode| {
-                let (played_str, time_str) = match episode.resume_point {
+                let played_str = episode_progress(episode.resume_point.as_ref(), episode.duration);
+                let time_str = match &episode.resume_point {
                     Some(ResumePoint {
-                        fully_played,
+                        fully_played: false,
                         resume_position,
-                    }) => (
-                        if fully_played {
-                            " ✔".to_owned()
-                        } else {
-                            "".to_owned()
-                        },
-                        format!(
-                            "{} / {}",
-                            millis_to_minutes(resume_position.num_milliseconds() as u128),
-                            millis_to_minutes(episode.duration.num_milliseconds() as u128)
-                        ),
-                    ),
-                    None => (
-                        "".to_owned(),
-                        millis_to_minutes(episode.duration.num_milliseconds() as u128),
+                    }) if *resume_position > chrono::Duration::zero() => format!(
+                        "{} / {}",
+                        millis_to_minutes(resume_position.num_milliseconds() as u128),
+                        millis_to_minutes(episode.duration.num_milliseconds() as u128)
                     ),
+                    _ => millis_to_minutes(episode.duration.num_milliseconds() as u128),
                 };
                 TableItem {
                     id: episode.id.to_string(),
@@ -1532,26 +2325,28 @@ where
             })
             .collect::<Vec<TableItem>>();
 
-        let title = match &app.episode_table_context {
+        let (title, available_markets) = match &app.episode_table_context {
             EpisodeTableContext::Simplified => match &app.selected_show_simplified {
-                Some(selected_show) => {
+                Some(selected_show) => (
                     format!(
                         "{} by {}",
                         selected_show.show.name.to_owned(),
                         selected_show.show.publisher
-                    )
-                }
-                None => "Episodes".to_owned(),
+                    ),
+                    &selected_show.show.available_markets[..],
+                ),
+                None => ("Episodes".to_owned(), &[][..]),
             },
             EpisodeTableContext::Full => match &app.selected_show_full {
-                Some(selected_show) => {
+                Some(selected_show) => (
                     format!(
                         "{} by {}",
                         selected_show.show.name.to_owned(),
                         selected_show.show.publisher
-                    )
-                }
-                None => "Episodes".to_owned(),
+                    ),
+                    &selected_show.show.available_markets[..],
+                ),
+                None => ("Episodes".to_owned(), &[][..]),
             },
         };
 
@@ -1564,6 +2359,14 @@ where
             app.episode_list_index,
             highlight_state,
         );
+        if items.is_empty() {
+            draw_empty_state(
+                f,
+                app,
+                table_rows_area(layout_chunk),
+                &empty_show_message(app, available_markets),
+            );
+        }
     };
 }
 
@@ -1620,11 +2423,16 @@ where
                 text: "",
                 width: 2,
             },
+            TableHeaderItem {
+                text: "Played at",
+                width: 10,
+                ..Default::default()
+            },
             TableHeaderItem {
                 id: ColumnId::Title,
                 text: "Title",
-                // We need to subtract the fixed value of the previous column
-                width: get_percentage_width(layout_chunk.width, 2.0 / 5.0) - 2,
+                // We need to subtract the fixed value of the previous columns
+                width: get_percentage_width(layout_chunk.width, 2.0 / 5.0).saturating_sub(12),
             },
             TableHeaderItem {
                 text: "Artist",
@@ -1632,52 +2440,104 @@ where
                 ..Default::default()
             },
             TableHeaderItem {
-                text: "Length",
+                text: "Context",
                 width: get_percentage_width(layout_chunk.width, 1.0 / 5.0),
                 ..Default::default()
             },
         ],
     };
 
-    if let Some(recently_played) = &app.recently_played.result {
-        let current_route = app.get_current_route();
-
-        let highlight_state = (
-            current_route.active_block == ActiveBlock::RecentlyPlayed,
-            current_route.hovered_block == ActiveBlock::RecentlyPlayed,
-        );
+    let current_route = app.get_current_route();
+    let highlight_state = (
+        current_route.active_block == ActiveBlock::RecentlyPlayed,
+        current_route.hovered_block == ActiveBlock::RecentlyPlayed,
+    );
 
-        let selected_song_index = app.recently_played.index;
+    let (title, items, selected_index) = match app.recently_played_source {
+        RecentlyPlayedSource::Spotify => {
+            let Some(recently_played) = &app.recently_played.result else {
+                return;
+            };
+            let items = recently_played
+                .items
+                .iter()
+                .map(|item| TableItem {
+                    id: item
+                        .track
+                        .id
+                        .as_ref()
+                        .map(|x| x.to_string())
+                        .unwrap_or_else(|| "".to_string()),
+                    format: vec![
+                        "".to_string(),
+                        display_played_at(item.played_at),
+                        item.track.name.to_owned(),
+                        create_artist_string(&item.track.artists),
+                        create_context_string(
+                            app,
+                            item.context.as_ref().map(|context| context.uri.as_str()),
+                        ),
+                    ],
+                })
+                .collect::<Vec<TableItem>>();
+            ("Recently Played Tracks", items, app.recently_played.index)
+        }
+        RecentlyPlayedSource::History => {
+            let items = app
+                .local_history
+                .entries
+                .iter()
+                .map(|entry| TableItem {
+                    id: entry.uri.clone(),
+                    format: vec![
+                        "".to_string(),
+                        display_played_at(entry.played_at),
+                        entry.title.clone(),
+                        entry.artist.clone(),
+                        create_context_string(app, entry.context_uri.as_deref()),
+                    ],
+                })
+                .collect::<Vec<TableItem>>();
+            (
+                "Recently Played Tracks (history)",
+                items,
+                app.local_history.index,
+            )
+        }
+    };
 
-        let items = recently_played
-            .items
-            .iter()
-            .map(|item| TableItem {
-                id: item
-                    .track
-                    .id
-                    .as_ref()
-                    .map(|x| x.to_string())
-                    .unwrap_or_else(|| "".to_string()),
+    // The plays under a header for each day
+    let rows = app.recently_played_rows();
+    let selected_row = rows
+        .iter()
+        .position(|row| *row == PlayedRow::Play(selected_index))
+        .unwrap_or_default();
+    let items = rows
+        .into_iter()
+        .filter_map(|row| match row {
+            PlayedRow::Day(day) => Some(TableItem {
+                id: String::new(),
                 format: vec![
-                    "".to_string(),
-                    item.track.name.to_owned(),
-                    create_artist_string(&item.track.artists),
-                    millis_to_minutes(item.track.duration.num_milliseconds() as u128),
+                    String::new(),
+                    day,
+                    String::new(),
+                    String::new(),
+                    String::new(),
                 ],
-            })
-            .collect::<Vec<TableItem>>();
+            }),
+            PlayedRow::Play(index) => items.get(index).cloned(),
+        })
+        .collect::<Vec<TableItem>>();
 
-        draw_table(
-            f,
-            app,
-            layout_chunk,
-            ("Recently Played Tracks", &header),
-            &items,
-            selected_song_index,
-            highlight_state,
-        )
-    };
+    draw_table(
+        f,
+        app,
+        layout_chunk,
+        (title, &header),
+        &items,
+        selected_row,
+        highlight_state,
+    )
 }
 
 fn draw_selectable_list<B, S>(
@@ -1692,16 +2552,39 @@ fn draw_selectable_list<B, S>(
     B: Backend,
     S: std::convert::AsRef<str>,
 {
-    let mut state = ListState::default();
-    state.select(selected_index);
-
     let lst_items: Vec<ListItem> = items
         .iter()
         .map(|i| ListItem::new(Span::raw(i.as_ref())))
         .collect();
 
+    draw_list(
+        f,
+        app,
+        layout_chunk,
+        title,
+        lst_items,
+        highlight_state,
+        selected_index,
+    );
+}
+
+// Like draw_selectable_list, for items that are styled individually
+fn draw_list<B>(
+    f: &mut Frame<B>,
+    app: &App,
+    layout_chunk: Rect,
+    title: &str,
+    items: Vec<ListItem>,
+    highlight_state: (bool, bool),
+    selected_index: Option<usize>,
+) where
+    B: Backend,
+{
+    let mut state = ListState::default();
+    state.select(selected_index);
+
     //TODO
-    let list = List::new(lst_items)
+    let list = List::new(items)
         .block(
             Block::default()
                 .title(Span::styled(
@@ -1827,6 +2710,17 @@ fn draw_table<B>(
 
     let (title, header) = table_layout;
 
+    // A playlist's table takes the color of its cover in place of the active and hovered colors,
+    // and is dimmed like any other block when neither
+    let accent = match header.id {
+        TableId::Song => app.item_table_accent(),
+        _ => None,
+    };
+    let border_style = match accent {
+        Some(color) if highlight_state.0 || highlight_state.1 => Style::default().fg(color),
+        _ => get_color(highlight_state, app.user_config.theme),
+    };
+
     // Make sure that the selected item is visible on the page. Need to add some rows of padding
     // to chunk height for header and header space to get a true table height
     let padding = 5;
@@ -1859,8 +2753,9 @@ fn draw_table<B>(
 
                 // Show this the liked icon if the song is liked
                 if let Some(liked_idx) = header.get_index(ColumnId::Liked) {
-                    if app.liked_song_ids_set.contains(item.id.as_str()) {
-                        formatted_row[liked_idx] = app.user_config.padded_liked_icon();
+                    let liked_icon = library_icon(app, &app.liked_song_ids_set, item.id.as_str());
+                    if !liked_icon.is_empty() {
+                        formatted_row[liked_idx] = liked_icon;
                     }
                 }
             }
@@ -1905,13 +2800,846 @@ fn draw_table<B>(
             Block::default()
                 .borders(Borders::ALL)
                 .style(Style::default().fg(app.user_config.theme.text))
-                .title(Span::styled(
-                    title,
-                    get_color(highlight_state, app.user_config.theme),
-                ))
-                .border_style(get_color(highlight_state, app.user_config.theme)),
+                .title(Span::styled(title, border_style))
+                .border_style(border_style),
         )
         .style(Style::default().fg(app.user_config.theme.text))
         .widths(&widths);
     f.render_widget(table, layout_chunk);
+
+    let rows = table_rows_area(layout_chunk);
+    if let Ok(mut control_areas) = app.control_areas.lock() {
+        control_areas.lists.push(ListArea {
+            block: header.id.active_block(),
+            rows,
+            offset,
+            len: items.len(),
+        });
+    }
+}
+
+// The rows of a table start below the border and the header
+fn table_rows_area(layout_chunk: Rect) -> Rect {
+    Rect {
+        x: layout_chunk.x + 1,
+        y: layout_chunk.y + 2,
+        width: layout_chunk.width.saturating_sub(2),
+        height: layout_chunk.height.saturating_sub(3),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        app::{SelectedAlbum, SelectedShow, TrackAnalysis},
+        event::Key,
+        handlers::{handle_app, handle_mouse},
+    };
+    use chrono::{Duration, Utc};
+    use crossterm::event::{KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+    use rspotify::model::{
+        album::SimplifiedAlbum,
+        audio::{AudioAnalysis, AudioFeatures},
+        context::{Actions, CurrentPlaybackContext},
+        device::Device,
+        enums::{CurrentlyPlayingType, DeviceType, Modality},
+        show::FullEpisode,
+        track::FullTrack,
+        TrackId,
+    };
+    use std::collections::HashMap;
+    use tui::{backend::TestBackend, buffer::Buffer, Terminal};
+
+    fn playing_app() -> App {
+        let mut app = App::default();
+        app.current_playback_context = Some(CurrentPlaybackContext {
+            device: Device {
+                id: None,
+                is_active: true,
+                is_private_session: false,
+                is_restricted: false,
+                name: String::from("Speaker"),
+                _type: DeviceType::Speaker,
+                volume_percent: Some(50),
+            },
+            repeat_state: RepeatState::Context,
+            shuffle_state: false,
+            context: None,
+            timestamp: Utc::now(),
+            progress: Some(Duration::seconds(30)),
+            is_playing: true,
+            item: Some(PlayableItem::Track(FullTrack {
+                album: SimplifiedAlbum::default(),
+                artists: vec![],
+                available_markets: vec![],
+                disc_number: 1,
+                duration: Duration::seconds(180),
+                explicit: false,
+                external_ids: HashMap::new(),
+                external_urls: HashMap::new(),
+                href: None,
+                id: Some(TrackId::from_id("4iV5W9uYEdYUVa79Axb7Rh").unwrap()),
+                is_local: false,
+                is_playable: None,
+                linked_from: None,
+                restrictions: None,
+                name: String::from("Song"),
+                popularity: 0,
+                preview_url: None,
+                track_number: 1,
+            })),
+            currently_playing_type: CurrentlyPlayingType::Track,
+            actions: Actions::default(),
+        });
+        app.set_current_route_state(Some(ActiveBlock::PlayBar), Some(ActiveBlock::PlayBar));
+        app
+    }
+
+    fn render_playbar(app: &App) -> Buffer {
+        let mut terminal = Terminal::new(TestBackend::new(90, 6)).unwrap();
+        terminal
+            .draw(|f| {
+                draw_playbar(f, app, f.size());
+            })
+            .unwrap();
+        terminal.backend().buffer().clone()
+    }
+
+    fn row(buffer: &Buffer, y: u16) -> String {
+        (0..buffer.area.width)
+            .map(|x| buffer.get(x, y).symbol.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn lyrics_view() {
+        let mut app = playing_app();
+        let track_id = TrackId::from_id("4iV5W9uYEdYUVa79Axb7Rh").unwrap();
+        let render_lyrics = |app: &App| {
+            let mut terminal = Terminal::new(TestBackend::new(20, 5)).unwrap();
+            terminal.draw(|f| draw_lyrics(f, app, f.size())).unwrap();
+            let buffer = terminal.backend().buffer().clone();
+            (1..4)
+                .map(|y| row(&buffer, y).trim_matches('│').trim_end().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        app.show_lyrics();
+        assert_eq!(app.get_current_route().id, RouteId::Lyrics);
+        assert!(app.is_loading);
+        assert_eq!(render_lyrics(&app)[0], "Loading...");
+
+        app.lyrics.insert(track_id.clone(), Lyrics::NotFound);
+        assert_eq!(render_lyrics(&app)[0], "No lyrics found");
+
+        app.lyrics.insert(
+            track_id,
+            Lyrics::Found(String::from("one two three four five six\nseven")),
+        );
+        assert_eq!(
+            render_lyrics(&app),
+            vec!["one two three four", "five six", "seven"]
+        );
+
+        // Coming back to the lyrics uses the cached ones
+        app.is_loading = false;
+        app.lyrics_scroll = 3;
+        app.pop_navigation_stack();
+        app.show_lyrics();
+        assert!(!app.is_loading);
+        assert_eq!(app.lyrics_scroll, 0);
+    }
+
+    fn audio_analysis(section_starts: &[f32]) -> AudioAnalysis {
+        let sections = section_starts
+            .iter()
+            .map(|start| {
+                serde_json::json!({
+                    "start": start, "duration": 10.0, "confidence": 1.0, "loudness": -5.0,
+                    "tempo": 120.0, "tempo_confidence": 1.0, "key": 0, "key_confidence": 1.0,
+                    "mode": 1, "mode_confidence": 1.0, "time_signature": 4,
+                    "time_signature_confidence": 1.0,
+                })
+            })
+            .collect::<Vec<_>>();
+        serde_json::from_value(serde_json::json!({
+            "bars": [],
+            "beats": [],
+            "meta": {
+                "analyzer_version": "", "platform": "", "detailed_status": "", "status_code": 0,
+                "timestamp": 0, "analysis_time": 0.0, "input_process": "",
+            },
+            "sections": sections,
+            "segments": [{
+                "start": 179.0, "duration": 1.0, "confidence": 1.0, "loudness_start": -5.0,
+                "loudness_max_time": 0.0, "loudness_max": -5.0, "pitches": vec![0.5; 12],
+                "timbre": [],
+            }],
+            "tatums": [],
+            "track": {
+                "num_samples": 0, "duration": 180.0, "sample_md5": "", "offset_seconds": 0,
+                "window_seconds": 0, "analysis_sample_rate": 0, "analysis_channels": 0,
+                "end_of_fade_in": 0.0, "start_of_fade_out": 0.0, "loudness": 0.0,
+                "tempo": 120.0, "tempo_confidence": 1.0, "time_signature": 4,
+                "time_signature_confidence": 1.0, "key": 0, "key_confidence": 1.0, "mode": 1,
+                "mode_confidence": 1.0, "codestring": "", "code_version": 0.0,
+                "echoprintstring": "", "echoprint_version": 0.0, "synchstring": "",
+                "synch_version": 0.0, "rhythmstring": "", "rhythm_version": 0.0,
+            },
+        }))
+        .unwrap()
+    }
+
+    fn render_audio_analysis(app: &App) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(60, 20)).unwrap();
+        terminal.draw(|f| audio_analysis::draw(f, app)).unwrap();
+        let buffer = terminal.backend().buffer().clone();
+        (0..buffer.area.height)
+            .map(|y| row(&buffer, y))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn audio_analysis_view() {
+        let mut app = playing_app();
+        let track_id = TrackId::from_id("4iV5W9uYEdYUVa79Axb7Rh").unwrap();
+        app.song_progress_ms = 30_000;
+
+        app.get_audio_analysis();
+        assert_eq!(app.get_current_route().id, RouteId::Analysis);
+        assert!(app.is_loading);
+        assert!(render_audio_analysis(&app).contains("Loading..."));
+
+        app.audio_analysis = Some((
+            track_id.clone(),
+            TrackAnalysis::Loaded(Box::new(audio_analysis(&[0.0, 20.0, 60.0]))),
+        ));
+        let rendered = render_audio_analysis(&app);
+        assert!(rendered.contains("Section 2/3"));
+        assert!(rendered.contains("Tempo: 120"));
+        // Fetched along with the analysis
+        assert!(app.loading_track_features.contains(&track_id));
+        app.audio_features
+            .insert(track_id.clone(), audio_features(track_id.clone()));
+        assert!(render_audio_analysis(&app)
+            .contains("Track: 120 BPM in A minor, energy 0.80, danceability 0.65"));
+
+        // Jumping between section boundaries
+        handle_app(Key::Right, &mut app);
+        assert_eq!(app.seek_ms, Some(60_000));
+        handle_app(Key::Right, &mut app);
+        assert_eq!(app.seek_ms, Some(60_000));
+        handle_app(Key::Left, &mut app);
+        assert_eq!(app.seek_ms, Some(20_000));
+        handle_app(Key::Left, &mut app);
+        assert_eq!(app.seek_ms, Some(0));
+
+        // The analysis of the previous track isn't shown once another one plays
+        if let Some(PlayableItem::Track(track)) = app
+            .current_playback_context
+            .as_mut()
+            .and_then(|context| context.item.as_mut())
+        {
+            track.id = Some(TrackId::from_id("6rqhFgbbKwnb9MLmUQDhG6").unwrap());
+        }
+        assert!(app.current_audio_analysis().is_none());
+        assert!(render_audio_analysis(&app).contains("No analysis available"));
+        app.fetch_audio_analysis();
+        assert!(matches!(
+            app.current_audio_analysis(),
+            Some(TrackAnalysis::Loading)
+        ));
+
+        let episode: FullEpisode = serde_json::from_value(serde_json::json!({
+            "audio_preview_url": null,
+            "description": "",
+            "duration_ms": 1_800_000,
+            "explicit": false,
+            "external_urls": {},
+            "href": "",
+            "id": "512ojhOuo1ktJprKbVcKyQ",
+            "images": [],
+            "is_externally_hosted": false,
+            "is_playable": true,
+            "language": "en",
+            "languages": [],
+            "name": "Episode",
+            "release_date": "2020-01-01",
+            "release_date_precision": "day",
+            "resume_point": null,
+            "show": {
+                "available_markets": [],
+                "copyrights": [],
+                "description": "",
+                "explicit": false,
+                "external_urls": {},
+                "href": "",
+                "id": "4rOoJ6Egrf8K2IrywzwOMk",
+                "images": [],
+                "languages": [],
+                "media_type": "audio",
+                "name": "Podcast",
+                "publisher": "",
+            },
+        }))
+        .unwrap();
+        app.current_playback_context.as_mut().unwrap().item = Some(PlayableItem::Episode(episode));
+        assert!(render_audio_analysis(&app).contains("Podcast episodes have no audio analysis"));
+    }
+
+    #[test]
+    fn audio_analysis_can_be_disabled() {
+        let mut app = playing_app();
+        app.user_config.behavior.enable_audio_analysis = false;
+        app.get_audio_analysis();
+        assert_ne!(app.get_current_route().id, RouteId::Analysis);
+        assert!(!app.is_loading);
+        assert_eq!(
+            app.notifications.current().unwrap().message,
+            "The audio analysis is disabled in the config"
+        );
+        app.fetch_audio_analysis();
+        assert!(app.audio_analysis.is_none());
+
+        let docs = help::get_help_docs(&app.user_config);
+        assert!(docs
+            .iter()
+            .any(|row| row[0] == "Go to audio analysis screen (disabled)"));
+    }
+
+    #[test]
+    fn narrow_audio_analysis_is_summarised() {
+        let analysis = audio_analysis(&[0.0, 20.0, 60.0]);
+        assert_eq!(
+            audio_analysis::summary_lines(&analysis, None),
+            [
+                "Tempo: 120 BPM",
+                "Key: C",
+                "Loudness: 0.0 dB",
+                "Sections: 0:10, 0:10, 0:10"
+            ]
+        );
+        let track_id = TrackId::from_id("4iV5W9uYEdYUVa79Axb7Rh").unwrap();
+        let features = audio_features(track_id.clone());
+        assert_eq!(
+            audio_analysis::summary_lines(&analysis, Some(&features))[..2],
+            ["Tempo: 120 BPM", "Key: A minor"]
+        );
+
+        let mut app = playing_app();
+        app.song_progress_ms = 30_000;
+        app.audio_analysis = Some((track_id, TrackAnalysis::Loaded(Box::new(analysis))));
+        let mut terminal =
+            Terminal::new(TestBackend::new(audio_analysis::MIN_CHART_WIDTH - 10, 20)).unwrap();
+        terminal.draw(|f| audio_analysis::draw(f, &app)).unwrap();
+        let buffer = terminal.backend().buffer().clone();
+        let rendered = (0..buffer.area.height)
+            .map(|y| row(&buffer, y))
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert!(rendered.contains("Loudness: 0.0 dB"));
+        assert!(!rendered.contains("Pitches"));
+    }
+
+    fn click(app: &mut App, kind: MouseEventKind, column: u16, row: u16) {
+        handle_mouse(
+            MouseEvent {
+                kind,
+                column,
+                row,
+                modifiers: KeyModifiers::NONE,
+            },
+            app,
+        );
+    }
+
+    #[test]
+    fn mouse_on_the_playbar() {
+        let mut app = playing_app();
+        render_playbar(&app);
+        let progress = app.control_areas.lock().unwrap().progress.unwrap();
+        let left = MouseEventKind::Down(MouseButton::Left);
+
+        click(&mut app, left, progress.x + progress.width / 2, progress.y);
+        assert_eq!(
+            app.seek_ms,
+            Some((progress.width / 2) as u128 * 180_000 / progress.width as u128)
+        );
+        click(&mut app, left, progress.x, progress.y);
+        assert_eq!(app.seek_ms, Some(0));
+
+        let (_, next) = app.control_areas.lock().unwrap().playbar[5];
+        assert_eq!(app.playbar_control, PlaybarControl::PlayPause);
+        app.is_loading = false;
+        click(&mut app, left, next.x, next.y);
+        assert!(app.is_loading);
+
+        // Turned off, the mouse does nothing
+        app.user_config.behavior.enable_mouse = false;
+        click(&mut app, left, progress.x + 1, progress.y);
+        assert_eq!(app.seek_ms, Some(0));
+    }
+
+    #[test]
+    fn item_table_columns() {
+        let mut app = App::default();
+        app.user_config.behavior.item_table_columns =
+            vec![ItemTableColumn::Title, ItemTableColumn::Popularity];
+        let mut track = match playing_app().current_playback_context.unwrap().item {
+            Some(PlayableItem::Track(track)) => track,
+            _ => unreachable!(),
+        };
+        track.popularity = 87;
+        app.item_table.set_items(vec![PlayableItem::Track(track)]);
+        app.cycle_item_table_sort();
+        app.cycle_item_table_sort();
+        app.reverse_item_table_sort();
+
+        let mut terminal = Terminal::new(TestBackend::new(80, 6)).unwrap();
+        terminal
+            .draw(|f| draw_song_table(f, &app, f.size()))
+            .unwrap();
+        let buffer = terminal.backend().buffer().clone();
+        let split = |y: u16| {
+            row(&buffer, y)
+                .trim_matches('│')
+                .split_whitespace()
+                .map(String::from)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(split(1), ["Title", "Popularity", "▼"]);
+        assert_eq!(split(2), ["Song", "87"]);
+    }
+
+    fn audio_features(track_id: TrackId<'static>) -> AudioFeatures {
+        AudioFeatures {
+            acousticness: 0.0,
+            analysis_url: String::new(),
+            danceability: 0.65,
+            duration: Duration::seconds(180),
+            energy: 0.8,
+            id: track_id,
+            instrumentalness: 0.0,
+            key: 9,
+            liveness: 0.0,
+            loudness: 0.0,
+            mode: Modality::Minor,
+            speechiness: 0.0,
+            tempo: 119.6,
+            time_signature: 4,
+            track_href: String::new(),
+            valence: 0.0,
+        }
+    }
+
+    #[test]
+    fn names_keys() {
+        let mut features = audio_features(TrackId::from_id("4iV5W9uYEdYUVa79Axb7Rh").unwrap());
+        assert_eq!(audio_analysis::key_name(&features), "A minor");
+        features.key = 1;
+        features.mode = Modality::Major;
+        assert_eq!(audio_analysis::key_name(&features), "C# major");
+        features.mode = Modality::NoResult;
+        assert_eq!(audio_analysis::key_name(&features), "C#");
+        features.key = -1;
+        assert_eq!(audio_analysis::key_name(&features), "Unknown key");
+    }
+
+    #[test]
+    fn features_popup_follows_the_selection() {
+        let mut app = App::default();
+        app.push_navigation_stack(RouteId::ItemTable, ActiveBlock::ItemTable);
+        let track = match playing_app().current_playback_context.unwrap().item {
+            Some(PlayableItem::Track(track)) => track,
+            _ => unreachable!(),
+        };
+        let track_id = track.id.clone().unwrap();
+        let mut local_track = track.clone();
+        local_track.id = None;
+        app.item_table.set_items(vec![
+            PlayableItem::Track(track),
+            PlayableItem::Track(local_track),
+        ]);
+        let render = |app: &App| {
+            let mut terminal = Terminal::new(TestBackend::new(80, 12)).unwrap();
+            terminal
+                .draw(|f| draw_song_table(f, app, f.size()))
+                .unwrap();
+            let buffer = terminal.backend().buffer().clone();
+            (0..buffer.area.height)
+                .map(|y| row(&buffer, y))
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+
+        handle_app(Key::Char('i'), &mut app);
+        assert!(app.is_loading);
+        assert!(app.loading_track_features.contains(&track_id));
+        assert!(render(&app).contains("Loading..."));
+
+        app.loading_track_features.clear();
+        app.audio_features
+            .insert(track_id.clone(), audio_features(track_id));
+        let rendered = render(&app);
+        assert!(rendered.contains("Tempo: 120 BPM"));
+        assert!(rendered.contains("Key: A minor"));
+        assert!(rendered.contains("Danceability: 0.65"));
+
+        handle_app(Key::Down, &mut app);
+        assert!(render(&app).contains("Local files have no audio features"));
+
+        handle_app(Key::Esc, &mut app);
+        assert!(!app.item_table.is_features_popup_open);
+        assert!(!render(&app).contains("Local files have no audio features"));
+    }
+
+    #[test]
+    fn playlists_that_failed_to_load() {
+        let mut app = App::default();
+        let render_playlists = |app: &App| {
+            let mut terminal = Terminal::new(TestBackend::new(24, 6)).unwrap();
+            terminal
+                .draw(|f| draw_playlist_block(f, app, f.size()))
+                .unwrap();
+            let buffer = terminal.backend().buffer().clone();
+            (1..5)
+                .map(|y| row(&buffer, y).trim_matches('│').trim_end().to_string())
+                .collect::<Vec<_>>()
+        };
+        app.dispatch_startup_events();
+        assert_eq!(render_playlists(&app)[0], "Loading playlists...");
+
+        let now = std::time::Instant::now();
+        app.on_startup_fetch_failed(StartupFetch::Playlists, true, now);
+        assert_eq!(
+            render_playlists(&app)[..2],
+            ["Couldn't load", "playlists, retrying..."]
+        );
+
+        app.retry_startup_fetches();
+        app.on_startup_fetch_failed(StartupFetch::Playlists, false, now);
+        assert_eq!(
+            render_playlists(&app)[..3],
+            ["Couldn't load", "playlists — press R to", "retry"]
+        );
+    }
+
+    #[test]
+    fn empty_playlists_say_so() {
+        let mut app = App::default();
+        app.set_current_route_state(Some(ActiveBlock::Empty), Some(ActiveBlock::MyPlaylists));
+        app.playlists = Some(
+            serde_json::from_value(serde_json::json!({
+                "href": "",
+                "items": [],
+                "limit": 20,
+                "next": null,
+                "offset": 0,
+                "previous": null,
+                "total": 0,
+            }))
+            .unwrap(),
+        );
+        let mut terminal = Terminal::new(TestBackend::new(24, 6)).unwrap();
+        terminal
+            .draw(|f| draw_playlist_block(f, &app, f.size()))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        assert_eq!(
+            row(buffer, 1).trim_matches('│').trim_end(),
+            "No playlists yet"
+        );
+        // The border still shows where focus is
+        assert_eq!(buffer.get(0, 0).fg, app.user_config.theme.hovered);
+    }
+
+    fn empty_page<T: serde::de::DeserializeOwned>() -> Page<T> {
+        serde_json::from_value(serde_json::json!({
+            "href": "", "items": [], "limit": 20, "next": null, "offset": 0, "previous": null,
+            "total": 0,
+        }))
+        .unwrap()
+    }
+
+    fn set_country(app: &mut App, country: &str) {
+        app.user = serde_json::from_value(serde_json::json!({
+            "country": country, "external_urls": {}, "href": "", "id": "listener",
+        }))
+        .ok();
+    }
+
+    // The first row below the header of a table, without its borders
+    fn first_table_row(app: &App, draw: fn(&mut Frame<TestBackend>, &App, Rect)) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(90, 6)).unwrap();
+        terminal.draw(|f| draw(f, app, f.size())).unwrap();
+        row(terminal.backend().buffer(), 2)
+            .trim_matches('│')
+            .trim_end()
+            .to_string()
+    }
+
+    #[test]
+    fn empty_show_episodes_say_why() {
+        let mut app = App::default();
+        app.library.show_episodes.add_pages(empty_page());
+        app.episode_table_context = EpisodeTableContext::Simplified;
+        let show = |available_markets: &[&str]| {
+            Some(SelectedShow {
+                show: serde_json::from_value(serde_json::json!({
+                    "available_markets": available_markets, "copyrights": [], "description": "",
+                    "explicit": false, "external_urls": {}, "href": "",
+                    "id": "4rOoJ6Egrf8K2IrywzwOMk", "images": [], "languages": [],
+                    "media_type": "audio", "name": "Podcast", "publisher": "Publisher",
+                }))
+                .unwrap(),
+            })
+        };
+        app.selected_show_simplified = show(&[]);
+        assert_eq!(
+            first_table_row(&app, draw_show_episodes),
+            "This show has no episodes yet — press q to go back"
+        );
+
+        set_country(&mut app, "DE");
+        assert_eq!(
+            first_table_row(&app, draw_show_episodes),
+            "This show has no episodes available in your market (DE) — press q to go back"
+        );
+
+        app.selected_show_simplified = show(&["GB"]);
+        assert_eq!(
+            first_table_row(&app, draw_show_episodes),
+            "This show isn't available in your market (DE) — press q to go back"
+        );
+    }
+
+    #[test]
+    fn empty_albums_say_why() {
+        let mut app = App::default();
+        app.album_table_context = AlbumTableContext::Simplified;
+        let album = |restrictions: serde_json::Value| {
+            Some(SelectedAlbum {
+                album: serde_json::from_value(serde_json::json!({
+                    "artists": [], "available_markets": [], "external_urls": {}, "href": null,
+                    "id": "6akEvsycLGftJxYudPjmqK", "images": [], "name": "Album",
+                    "release_date": null, "release_date_precision": null,
+                    "restrictions": restrictions,
+                }))
+                .unwrap(),
+                tracks: empty_page(),
+                selected_index: 0,
+            })
+        };
+        app.selected_album_simplified = album(serde_json::Value::Null);
+        assert_eq!(
+            first_table_row(&app, draw_album_table),
+            "This album has no tracks — press q to go back"
+        );
+
+        set_country(&mut app, "DE");
+        app.selected_album_simplified = album(serde_json::json!({ "reason": "market" }));
+        assert_eq!(
+            first_table_row(&app, draw_album_table),
+            "This album isn't available in your market (DE) — press q to go back"
+        );
+    }
+
+    #[test]
+    fn empty_library_rows_say_so() {
+        let mut app = App::default();
+        app.library.saved_albums.add_pages(empty_page());
+        app.library.saved_shows.add_pages(empty_page());
+        assert_eq!(
+            first_table_row(&app, draw_album_list),
+            "No saved albums yet"
+        );
+        assert_eq!(
+            first_table_row(&app, draw_podcast_table),
+            "No saved podcasts yet"
+        );
+
+        // Not until they have loaded
+        assert_eq!(first_table_row(&app, draw_artist_table), "");
+        app.library.saved_artists.add_pages(
+            serde_json::from_value(serde_json::json!({
+                "href": "", "items": [], "limit": 20, "next": null, "cursors": { "after": null },
+                "total": 0,
+            }))
+            .unwrap(),
+        );
+        assert_eq!(
+            first_table_row(&app, draw_artist_table),
+            "No followed artists yet"
+        );
+    }
+
+    #[test]
+    fn mouse_on_lists() {
+        let mut app = App::default();
+        let mut terminal = Terminal::new(TestBackend::new(20, 10)).unwrap();
+        terminal
+            .draw(|f| draw_library_block(f, &app, f.size()))
+            .unwrap();
+        let left = MouseEventKind::Down(MouseButton::Left);
+
+        click(&mut app, left, 3, 3);
+        assert_eq!(app.library.selected_index, 2);
+        assert_eq!(app.get_current_route().active_block, ActiveBlock::Library);
+
+        click(&mut app, MouseEventKind::ScrollDown, 3, 3);
+        assert_eq!(app.library.selected_index, 3);
+        click(&mut app, MouseEventKind::ScrollUp, 3, 3);
+        click(&mut app, MouseEventKind::ScrollUp, 3, 3);
+        assert_eq!(app.library.selected_index, 1);
+
+        // Below the last item, and on the border
+        click(&mut app, left, 3, 8);
+        click(&mut app, left, 0, 3);
+        assert_eq!(app.library.selected_index, 1);
+
+        // Areas of views that are no longer drawn can't be clicked
+        app.clear_control_areas();
+        click(&mut app, left, 3, 4);
+        assert_eq!(app.library.selected_index, 1);
+    }
+
+    #[test]
+    fn playbar_controls_snapshot() {
+        let mut app = playing_app();
+
+        for control in PlaybarControl::ALL {
+            app.playbar_control = control;
+            let buffer = render_playbar(&app);
+
+            assert_eq!(
+                row(&buffer, 3).trim_end_matches('│').trim_end(),
+                "│ Shuffle: Off | Repeat: All | Like | Previous | Pause | Next",
+                "{:?}",
+                control
+            );
+
+            let areas = app.control_areas.lock().unwrap().playbar.clone();
+            assert_eq!(areas.len(), PlaybarControl::ALL.len());
+            for (area_control, area) in areas {
+                let is_focused = (area.x..area.x + area.width)
+                    .all(|x| buffer.get(x, area.y).modifier.contains(Modifier::REVERSED));
+                assert_eq!(is_focused, area_control == control, "{:?}", control);
+            }
+        }
+    }
+
+    #[test]
+    fn playbar_shows_the_playback_modes() {
+        let mut app = playing_app();
+        app.user_config.behavior.shuffle_icon = String::from("<S>");
+        app.user_config.behavior.repeat_track_icon = String::from("<R1>");
+        app.user_config.behavior.repeat_context_icon = String::from("<R>");
+        assert!(row(&render_playbar(&app), 0).contains("Volume: 50%) <R> "));
+
+        let context = app.current_playback_context.as_mut().unwrap();
+        context.shuffle_state = true;
+        context.repeat_state = RepeatState::Track;
+        assert!(row(&render_playbar(&app), 0).contains("Volume: 50%) <S> <R1> "));
+
+        // Modes that are off have no icon
+        let context = app.current_playback_context.as_mut().unwrap();
+        context.shuffle_state = false;
+        context.repeat_state = RepeatState::Off;
+        assert_eq!(playback_mode_icons(&app), "");
+        assert!(!row(&render_playbar(&app), 0).contains('<'));
+
+        app.current_playback_context = None;
+        assert_eq!(playback_mode_icons(&app), "");
+    }
+
+    #[test]
+    fn playbar_control_areas() {
+        let app = playing_app();
+        render_playbar(&app);
+
+        let areas = app.control_areas.lock().unwrap().playbar.clone();
+        assert_eq!(
+            areas,
+            vec![
+                (PlaybarControl::Shuffle, Rect::new(1, 3, 14, 1)),
+                (PlaybarControl::Repeat, Rect::new(16, 3, 13, 1)),
+                (PlaybarControl::Like, Rect::new(30, 3, 6, 1)),
+                (PlaybarControl::Previous, Rect::new(37, 3, 10, 1)),
+                (PlaybarControl::PlayPause, Rect::new(48, 3, 7, 1)),
+                (PlaybarControl::Next, Rect::new(56, 3, 6, 1)),
+            ]
+        );
+    }
+
+    #[test]
+    fn playbar_controls_unfocused_outside_playbar() {
+        let mut app = playing_app();
+        app.set_current_route_state(Some(ActiveBlock::Empty), Some(ActiveBlock::Library));
+        let buffer = render_playbar(&app);
+
+        assert!(
+            (0..buffer.area.width).all(|x| !buffer.get(x, 3).modifier.contains(Modifier::REVERSED))
+        );
+    }
+
+    #[test]
+    fn hidden_playbar() {
+        let mut app = playing_app();
+        app.set_current_route_state(Some(ActiveBlock::Empty), Some(ActiveBlock::Library));
+
+        // Narrow and short, then wide and tall, which have the input box in different places
+        for (width, height) in [(100, 30), (160, 50)] {
+            for is_playbar_hidden in [false, true] {
+                app.is_playbar_hidden = is_playbar_hidden;
+                app.size = Rect::new(0, 0, width, height);
+                let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+                terminal
+                    .draw(|f| {
+                        draw_main_layout(f, &app);
+                    })
+                    .unwrap();
+                let buffer = terminal.backend().buffer().clone();
+                let case = format!("{}x{} hidden: {}", width, height, is_playbar_hidden);
+
+                // The cursor is just inside the input box's border
+                let (x, y) = util::get_input_cursor_position(&app);
+                assert_eq!(buffer.get(x - 1, y - 1).symbol, "┌", "{}", case);
+                assert!(row(&buffer, y - 1).contains("Search"), "{}", case);
+
+                // The playlists block reaches down to the playbar, or to the bottom without it
+                let margin = util::get_main_layout_margin(&app);
+                let playlists_bottom = if is_playbar_hidden {
+                    height - margin - 1
+                } else {
+                    height - margin - 7
+                };
+                assert!(
+                    row(&buffer, playlists_bottom)
+                        .starts_with(&format!("{}└", " ".repeat(margin as usize))),
+                    "{}",
+                    case
+                );
+                let shows_playbar = (0..height).any(|y| row(&buffer, y).contains("Speaker"));
+                assert_eq!(shows_playbar, !is_playbar_hidden, "{}", case);
+            }
+        }
+    }
+
+    #[test]
+    fn window_title_while_the_playbar_is_hidden() {
+        let mut app = playing_app();
+        assert_eq!(util::get_window_title(&app), "Spotify");
+
+        app.toggle_playbar();
+        assert_eq!(util::get_window_title(&app), "Spotify · Song");
+        // The playbar can't stay selected once it's gone
+        let route = app.get_current_route();
+        assert_eq!(route.active_block, ActiveBlock::Empty);
+        assert_eq!(route.hovered_block, ActiveBlock::MyPlaylists);
+
+        app.current_playback_context = None;
+        assert_eq!(util::get_window_title(&app), "Spotify");
+    }
 }
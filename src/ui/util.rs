@@ -1,6 +1,10 @@
 use super::super::app::{ActiveBlock, App, ArtistBlock, SearchResultBlock};
 use crate::user_config::Theme;
-use rspotify::model::artist::SimplifiedArtist;
+use chrono::{DateTime, Local, NaiveDate, TimeZone, Utc};
+use rspotify::{
+    model::{artist::SimplifiedArtist, show::ResumePoint, PlayableItem},
+    prelude::Id,
+};
 use tui::style::Style;
 
 pub const BASIC_VIEW_HEIGHT: u16 = 6;
@@ -47,6 +51,107 @@ pub fn create_artist_string(artists: &[SimplifiedArtist]) -> String {
         .join(", ")
 }
 
+// Playlists in the library are shown by name, other contexts by their kind
+pub fn create_context_string(app: &App, context_uri: Option<&str>) -> String {
+    let Some(uri) = context_uri else {
+        return String::new();
+    };
+    if let Some(playlist) = app
+        .playlists
+        .iter()
+        .flat_map(|page| &page.items)
+        .find(|playlist| playlist.id.uri() == uri)
+    {
+        return playlist.name.clone();
+    }
+    if uri.ends_with(":collection") {
+        return String::from("Liked Songs");
+    }
+    match uri.split(':').nth(1) {
+        Some("album") => "Album",
+        Some("artist") => "Artist",
+        Some("playlist") => "Playlist",
+        Some("show") => "Podcast",
+        _ => "",
+    }
+    .to_string()
+}
+
+// Only the time, as the plays are under a header for their day
+pub fn display_played_at(played_at: DateTime<Utc>) -> String {
+    played_at.with_timezone(&Local).format("%H:%M").to_string()
+}
+
+// A row of the recently played table
+#[derive(Clone, PartialEq, Debug)]
+pub enum PlayedRow {
+    // A header, which can't be selected
+    Day(String),
+    // The index of the play
+    Play(usize),
+}
+
+// The day of a play, by name for the past week and by date before that
+pub fn display_played_day(day: NaiveDate, today: NaiveDate) -> String {
+    match (today - day).num_days() {
+        // A clock ahead of this one may have it played "tomorrow"
+        ..=0 => String::from("Today"),
+        1 => String::from("Yesterday"),
+        2..=6 => day.format("%A").to_string(),
+        _ => day.format("%Y-%m-%d").to_string(),
+    }
+}
+
+// The plays, newest first, with a header above the first play of each day. The days are those of
+// the timezone `now` is in.
+pub fn group_plays_by_day<Tz: TimeZone>(
+    played_at: impl IntoIterator<Item = DateTime<Utc>>,
+    now: &DateTime<Tz>,
+) -> Vec<PlayedRow> {
+    let today = now.date_naive();
+    let mut rows = vec![];
+    let mut day = None;
+    for (index, played_at) in played_at.into_iter().enumerate() {
+        let played_on = played_at.with_timezone(&now.timezone()).date_naive();
+        if day != Some(played_on) {
+            rows.push(PlayedRow::Day(display_played_day(played_on, today)));
+            day = Some(played_on);
+        }
+        rows.push(PlayedRow::Play(index));
+    }
+    rows
+}
+
+// How much of an episode has been listened to, as a percentage, or a tick once it has been played
+// to the end
+pub fn episode_progress(resume_point: Option<&ResumePoint>, duration: chrono::Duration) -> String {
+    let Some(resume_point) = resume_point else {
+        return String::new();
+    };
+    if resume_point.fully_played {
+        return String::from("✓");
+    }
+    let position_ms = resume_point.resume_position.num_milliseconds();
+    let duration_ms = duration.num_milliseconds();
+    if position_ms <= 0 || duration_ms <= 0 {
+        return String::new();
+    }
+    // Only a fully played episode shows as done
+    let percent = (position_ms * 100 / duration_ms).clamp(1, 99);
+    format!("{}%", percent)
+}
+
+// A follower count rounded to thousands or millions past those
+pub fn display_followers(total: u32) -> String {
+    let count = match total {
+        0..=999 => total.to_string(),
+        1_000..=999_999 => format!("{:.1}K", f64::from(total) / 1_000.0),
+        _ => format!("{:.1}M", f64::from(total) / 1_000_000.0),
+    };
+    let noun = if total == 1 { "follower" } else { "followers" };
+    format!("{} {}", count.replace(".0", ""), noun)
+}
+
 pub fn millis_to_minutes(millis: u128) -> String {
     let minutes = millis / 60000;
     let seconds = (millis % 60000) / 1000;
@@ -95,10 +200,160 @@ pub fn get_main_layout_margin(app: &App) -> u16 {
     }
 }
 
+// Where the terminal cursor goes while typing. The input box is the first row of the main layout
+// at either width, inside its border, so hiding the playbar at the bottom doesn't move it.
+pub fn get_input_cursor_position(app: &App) -> (u16, u16) {
+    // The filter is the first row of the help instead, which has a margin of its own
+    let offset = if app.is_filtering_help() {
+        2 + 1
+    } else {
+        get_main_layout_margin(app) + 1
+    };
+    (offset + app.input_cursor_position, offset)
+}
+
+// The playing item takes the playbar's place in the window title while the playbar is hidden
+pub fn get_window_title(app: &App) -> String {
+    let playing = app
+        .current_playback_context
+        .as_ref()
+        .and_then(|context| context.item.as_ref())
+        .filter(|_| app.is_playbar_hidden);
+    let (name, by) = match playing {
+        Some(PlayableItem::Track(track)) => (&track.name, create_artist_string(&track.artists)),
+        Some(PlayableItem::Episode(episode)) => (&episode.name, episode.show.name.clone()),
+        None => return String::from("Spotify"),
+    };
+    if by.is_empty() {
+        format!("Spotify · {}", name)
+    } else {
+        format!("Spotify · {} - {}", name, by)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn displays_follower_counts() {
+        assert_eq!(display_followers(0), "0 followers");
+        assert_eq!(display_followers(1), "1 follower");
+        assert_eq!(display_followers(950), "950 followers");
+        assert_eq!(display_followers(1_000), "1K followers");
+        assert_eq!(display_followers(12_345), "12.3K followers");
+        assert_eq!(display_followers(3_000_000), "3M followers");
+        assert_eq!(display_followers(8_460_000), "8.5M followers");
+    }
+
+    #[test]
+    fn episode_progress_of_resume_points() {
+        let duration = chrono::Duration::minutes(40);
+        let resume_point = |fully_played, minutes| ResumePoint {
+            fully_played,
+            resume_position: chrono::Duration::minutes(minutes),
+        };
+        assert_eq!(episode_progress(None, duration), "");
+        assert_eq!(
+            episode_progress(Some(&resume_point(false, 0)), duration),
+            ""
+        );
+        assert_eq!(
+            episode_progress(Some(&resume_point(false, 10)), duration),
+            "25%"
+        );
+        assert_eq!(
+            episode_progress(Some(&resume_point(false, 40)), duration),
+            "99%"
+        );
+        assert_eq!(
+            episode_progress(Some(&resume_point(true, 0)), duration),
+            "✓"
+        );
+        assert_eq!(
+            episode_progress(Some(&resume_point(true, 40)), duration),
+            "✓"
+        );
+    }
+
+    #[test]
+    fn groups_plays_by_the_day_they_were_on_there() {
+        use PlayedRow::{Day, Play};
+        let at = |time: &str| time.parse::<DateTime<Utc>>().unwrap();
+        let day = |name: &str| Day(String::from(name));
+
+        // Two hours ahead of UTC, on a Friday morning
+        let now = chrono::FixedOffset::east_opt(2 * 3600)
+            .unwrap()
+            .with_ymd_and_hms(2024, 3, 15, 9, 0, 0)
+            .unwrap();
+        let rows = group_plays_by_day(
+            [
+                at("2024-03-15T06:00:00Z"),
+                // Just after midnight there, though not in UTC
+                at("2024-03-14T22:30:00Z"),
+                at("2024-03-14T21:59:00Z"),
+                at("2024-03-13T12:00:00Z"),
+                at("2024-03-09T12:00:00Z"),
+                at("2024-03-08T12:00:00Z"),
+            ],
+            &now,
+        );
+        assert_eq!(
+            rows,
+            [
+                day("Today"),
+                Play(0),
+                Play(1),
+                day("Yesterday"),
+                Play(2),
+                day("Wednesday"),
+                Play(3),
+                day("Saturday"),
+                Play(4),
+                // A week ago
+                day("2024-03-08"),
+                Play(5),
+            ]
+        );
+
+        // Five hours behind, just after midnight
+        let now = chrono::FixedOffset::west_opt(5 * 3600)
+            .unwrap()
+            .with_ymd_and_hms(2024, 3, 15, 0, 10, 0)
+            .unwrap();
+        let rows = group_plays_by_day(
+            [
+                // From a clock a little ahead
+                at("2024-03-15T05:20:00Z"),
+                at("2024-03-15T05:05:00Z"),
+                at("2024-03-15T04:50:00Z"),
+            ],
+            &now,
+        );
+        assert_eq!(
+            rows,
+            [day("Today"), Play(0), Play(1), day("Yesterday"), Play(2)]
+        );
+        assert!(group_plays_by_day(Vec::new(), &now).is_empty());
+    }
+
+    #[test]
+    fn context_string() {
+        let app = App::default();
+
+        assert_eq!(create_context_string(&app, None), "");
+        assert_eq!(
+            create_context_string(&app, Some("spotify:album:4aawyAB9vmqN3uQ7FjRGTy")),
+            "Album"
+        );
+        assert_eq!(
+            create_context_string(&app, Some("spotify:user:spotify:collection")),
+            "Liked Songs"
+        );
+        assert_eq!(create_context_string(&app, Some("nonsense")), "");
+    }
+
     #[test]
     fn millis_to_minutes_test() {
         assert_eq!(millis_to_minutes(0), "0:00");
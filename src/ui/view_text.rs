@@ -0,0 +1,324 @@
+use super::util::{create_artist_string, millis_to_minutes};
+use crate::app::{ActiveBlock, AlbumTableContext, App, RecentlyPlayedSource, SearchResultBlock};
+use chrono::TimeDelta;
+use rspotify::model::{track::FullTrack, PlayableItem};
+use unicode_width::UnicodeWidthChar;
+
+// Generous enough for most titles and artists, whatever the width of the terminal
+const TITLE_WIDTH: usize = 48;
+const ARTISTS_WIDTH: usize = 32;
+const UNAVAILABLE: &str = " (unavailable)";
+
+// A row of a table as it is copied, whichever table it comes from
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextRow {
+    pub title: String,
+    // The publisher or show of an episode
+    pub artists: String,
+    // Not known for entries of the local history
+    pub duration: Option<TimeDelta>,
+    pub is_unavailable: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TextFormat {
+    Plain,
+    Markdown,
+}
+
+impl TextRow {
+    fn track(app: &App, track: &FullTrack) -> TextRow {
+        TextRow {
+            title: track.name.clone(),
+            artists: create_artist_string(&track.artists),
+            duration: Some(track.duration),
+            is_unavailable: track.is_playable == Some(false)
+                || !app.is_available(&track.available_markets),
+        }
+    }
+
+    fn title(&self) -> String {
+        if self.is_unavailable {
+            format!("{}{}", self.title, UNAVAILABLE)
+        } else {
+            self.title.clone()
+        }
+    }
+
+    fn duration(&self) -> String {
+        self.duration
+            .map(|duration| millis_to_minutes(duration.num_milliseconds().max(0) as u128))
+            .unwrap_or_default()
+    }
+}
+
+// The loaded rows of the focused table, None when it isn't one that can be copied
+pub fn focused_rows(app: &App) -> Option<Vec<TextRow>> {
+    let route = app.get_current_route();
+    let rows = match route.active_block {
+        ActiveBlock::ItemTable => app
+            .item_table
+            .items
+            .iter()
+            .map(|item| match item {
+                PlayableItem::Track(track) => TextRow::track(app, track),
+                PlayableItem::Episode(episode) => TextRow {
+                    title: episode.name.clone(),
+                    artists: episode.show.publisher.clone(),
+                    duration: Some(episode.duration),
+                    is_unavailable: !episode.is_playable,
+                },
+            })
+            .collect(),
+        ActiveBlock::AlbumTracks => match app.album_table_context {
+            AlbumTableContext::Simplified => app
+                .selected_album_simplified
+                .as_ref()?
+                .tracks
+                .items
+                .iter()
+                .map(|track| TextRow {
+                    title: track.name.clone(),
+                    artists: create_artist_string(&track.artists),
+                    duration: Some(track.duration),
+                    is_unavailable: track.is_playable == Some(false),
+                })
+                .collect(),
+            AlbumTableContext::Full => app
+                .selected_album_full
+                .as_ref()?
+                .album
+                .tracks
+                .items
+                .iter()
+                .map(|track| TextRow {
+                    title: track.name.clone(),
+                    artists: create_artist_string(&track.artists),
+                    duration: Some(track.duration),
+                    is_unavailable: track.is_playable == Some(false),
+                })
+                .collect(),
+        },
+        ActiveBlock::SearchResultBlock
+            if app.search_results.selected_block == SearchResultBlock::SongSearch
+                || app.search_results.hovered_block == SearchResultBlock::SongSearch =>
+        {
+            app.search_results
+                .tracks
+                .get_results(None)?
+                .items
+                .iter()
+                .map(|track| TextRow::track(app, track))
+                .collect()
+        }
+        ActiveBlock::EpisodeTable => {
+            let show = app
+                .selected_show_simplified
+                .as_ref()
+                .map(|selected| selected.show.name.clone())
+                .unwrap_or_default();
+            app.library
+                .show_episodes
+                .get_results(None)?
+                .items
+                .iter()
+                .map(|episode| TextRow {
+                    title: episode.name.clone(),
+                    artists: show.clone(),
+                    duration: Some(episode.duration),
+                    is_unavailable: !episode.is_playable,
+                })
+                .collect()
+        }
+        ActiveBlock::RecentlyPlayed => match app.recently_played_source {
+            RecentlyPlayedSource::Spotify => app
+                .recently_played
+                .result
+                .as_ref()?
+                .items
+                .iter()
+                .map(|item| TextRow::track(app, &item.track))
+                .collect(),
+            RecentlyPlayedSource::History => app
+                .local_history
+                .entries
+                .iter()
+                .map(|entry| TextRow {
+                    title: entry.title.clone(),
+                    artists: entry.artist.clone(),
+                    duration: None,
+                    is_unavailable: false,
+                })
+                .collect(),
+        },
+        _ => return None,
+    };
+    Some(rows)
+}
+
+// Cut to at most `width` columns, marking the cut with an ellipsis, and padded to it
+fn fit(text: &str, width: usize) -> String {
+    let mut fitted = String::new();
+    let mut used = 0;
+    let text_width = text.chars().map(|c| c.width().unwrap_or(0)).sum::<usize>();
+    let limit = if text_width > width { width - 1 } else { width };
+    for c in text.chars() {
+        let char_width = c.width().unwrap_or(0);
+        if used + char_width > limit {
+            break;
+        }
+        fitted.push(c);
+        used += char_width;
+    }
+    if text_width > width {
+        fitted.push('…');
+        used += 1;
+    }
+    fitted.push_str(&" ".repeat(width - used));
+    fitted
+}
+
+fn escape_markdown(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
+// One line for each row, numbered from 1, in aligned columns or as a markdown table
+pub fn format_rows(rows: &[TextRow], format: TextFormat) -> String {
+    match format {
+        TextFormat::Plain => {
+            let index_width = rows.len().to_string().len();
+            rows.iter()
+                .enumerate()
+                .map(|(index, row)| {
+                    format!(
+                        "{:>index_width$}  {}  {}  {}",
+                        index + 1,
+                        fit(&row.title(), TITLE_WIDTH),
+                        fit(&row.artists, ARTISTS_WIDTH),
+                        row.duration()
+                    )
+                    .trim_end()
+                    .to_string()
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+        TextFormat::Markdown => {
+            let mut lines = vec![
+                String::from("| # | Title | Artists | Duration |"),
+                String::from("|--:|---|---|--:|"),
+            ];
+            lines.extend(rows.iter().enumerate().map(|(index, row)| {
+                format!(
+                    "| {} | {} | {} | {} |",
+                    index + 1,
+                    escape_markdown(&row.title()),
+                    escape_markdown(&row.artists),
+                    row.duration()
+                )
+            }));
+            lines.join("\n")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows() -> Vec<TextRow> {
+        vec![
+            TextRow {
+                title: String::from("So What"),
+                artists: String::from("Miles Davis"),
+                duration: Some(TimeDelta::seconds(562)),
+                is_unavailable: false,
+            },
+            TextRow {
+                title: String::from("A title far too long to fit in the title column of the copy"),
+                artists: String::from("Björk | Guests"),
+                duration: Some(TimeDelta::seconds(65)),
+                is_unavailable: true,
+            },
+            TextRow {
+                title: String::from("The episode"),
+                artists: String::from("The show"),
+                duration: None,
+                is_unavailable: false,
+            },
+        ]
+    }
+
+    #[test]
+    fn copies_aligned_plain_text() {
+        let text = format_rows(&rows(), TextFormat::Plain);
+        let lines = text.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[0],
+            format!(
+                "1  So What{}  Miles Davis{}  9:22",
+                " ".repeat(TITLE_WIDTH - 7),
+                " ".repeat(ARTISTS_WIDTH - 11)
+            )
+        );
+        assert!(lines[1].starts_with("2  A title far too long to fit in the title column…  Björk"));
+        assert!(lines[1].ends_with("1:05"));
+        // The duration column lines up
+        let column = |line: &str, text: &str| line[..line.find(text).unwrap()].chars().count();
+        assert_eq!(column(lines[0], "9:22"), column(lines[1], "1:05"));
+        // Unknown durations are left out
+        assert!(lines[2].ends_with("The show"));
+    }
+
+    #[test]
+    fn marks_unavailable_rows() {
+        let mut rows = rows();
+        rows[1].title = String::from("Short");
+        let text = format_rows(&rows, TextFormat::Plain);
+        assert!(text.lines().nth(1).unwrap().contains("Short (unavailable)"));
+    }
+
+    #[test]
+    fn copies_a_markdown_table() {
+        assert_eq!(
+            format_rows(&rows(), TextFormat::Markdown),
+            "| # | Title | Artists | Duration |\n\
+|--:|---|---|--:|\n\
+| 1 | So What | Miles Davis | 9:22 |\n\
+| 2 | A title far too long to fit in the title column of the copy (unavailable) | Björk \\| Guests | 1:05 |\n\
+| 3 | The episode | The show |  |"
+        );
+        assert_eq!(
+            format_rows(&[], TextFormat::Markdown),
+            "| # | Title | Artists | Duration |\n|--:|---|---|--:|"
+        );
+    }
+
+    #[test]
+    fn copies_the_focused_table() {
+        let mut app = App::default();
+        assert_eq!(focused_rows(&app), None);
+
+        app.recently_played_source = RecentlyPlayedSource::History;
+        app.local_history
+            .entries
+            .push(crate::history::HistoryEntry {
+                played_at: chrono::Utc::now(),
+                uri: String::from("spotify:track:4iV5W9uYEdYUVa79Axb7Rh"),
+                title: String::from("So What"),
+                artist: String::from("Miles Davis"),
+                context_uri: None,
+            });
+        app.set_current_route_state(
+            Some(ActiveBlock::RecentlyPlayed),
+            Some(ActiveBlock::RecentlyPlayed),
+        );
+        assert_eq!(
+            focused_rows(&app).map(|rows| format_rows(&rows, TextFormat::Markdown)),
+            Some(String::from(
+                "| # | Title | Artists | Duration |\n|--:|---|---|--:|\n| 1 | So What | Miles Davis |  |"
+            ))
+        );
+    }
+}
@@ -0,0 +1,264 @@
+use crate::cache_file;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use std::{cmp::Ordering, path::Path, time::Duration};
+
+const LATEST_RELEASE_URL: &str =
+    "https://api.github.com/repos/Rigellute/spotify-tui/releases/latest";
+// Long enough for a slow connection, short enough that a hanging one doesn't linger
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+// How long a check is trusted before the next launch asks again
+const CHECK_INTERVAL: TimeDelta = TimeDelta::hours(24);
+
+pub const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+// A part of a pre-release, numbers come before words
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Identifier {
+    Numeric(u64),
+    Alphanumeric(String),
+}
+
+// A semantic version, without its build metadata which doesn't count towards the order
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Version {
+    major: u64,
+    minor: u64,
+    patch: u64,
+    pre: Vec<Identifier>,
+}
+
+impl Version {
+    // Release tags are written with or without a leading v
+    pub fn parse(text: &str) -> Option<Version> {
+        let text = text.trim();
+        let text = text.strip_prefix('v').unwrap_or(text);
+        let text = text
+            .split_once('+')
+            .map_or(text, |(version, _build)| version);
+        let (core, pre) = match text.split_once('-') {
+            Some((core, pre)) => (core, Some(pre)),
+            None => (text, None),
+        };
+
+        let mut numbers = core.split('.').map(|number| number.parse::<u64>().ok());
+        let (Some(Some(major)), Some(Some(minor)), Some(Some(patch)), None) = (
+            numbers.next(),
+            numbers.next(),
+            numbers.next(),
+            numbers.next(),
+        ) else {
+            return None;
+        };
+
+        let pre = match pre {
+            Some(pre) => pre
+                .split('.')
+                .map(|identifier| {
+                    if identifier.is_empty() {
+                        None
+                    } else if let Ok(number) = identifier.parse() {
+                        Some(Identifier::Numeric(number))
+                    } else {
+                        Some(Identifier::Alphanumeric(identifier.to_string()))
+                    }
+                })
+                .collect::<Option<Vec<_>>>()?,
+            None => vec![],
+        };
+
+        Some(Version {
+            major,
+            minor,
+            patch,
+            pre,
+        })
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            // A pre-release comes before the release it leads up to
+            .then_with(|| match (self.pre.is_empty(), other.pre.is_empty()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => self.pre.cmp(&other.pre),
+            })
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// False when either version can't be read, an odd tag is never worth a notification
+pub fn is_newer(latest: &str, current: &str) -> bool {
+    match (Version::parse(latest), Version::parse(current)) {
+        (Some(latest), Some(current)) => latest > current,
+        _ => false,
+    }
+}
+
+// The cache file, with the tag of the latest release when it was last fetched
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct UpdateCheck {
+    pub checked_at: DateTime<Utc>,
+    pub latest: String,
+}
+
+// A check from the future, after the clock was turned back, is fetched again
+pub fn is_fresh(checked_at: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+    let age = now - checked_at;
+    age >= TimeDelta::zero() && age < CHECK_INTERVAL
+}
+
+#[derive(Deserialize)]
+struct Release {
+    tag_name: String,
+}
+
+async fn fetch_latest_release() -> Result<String> {
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        // GitHub turns away requests without one
+        .user_agent(concat!("spotify-tui/", env!("CARGO_PKG_VERSION")))
+        .build()?;
+    let response = client
+        .get(LATEST_RELEASE_URL)
+        .send()
+        .await?
+        .error_for_status()?;
+    let release = serde_json::from_str::<Release>(&response.text().await?)?;
+    if release.tag_name.is_empty() {
+        return Err(anyhow!("The latest release has no tag"));
+    }
+    Ok(release.tag_name)
+}
+
+// The latest release when it is newer than this one, fetched at most once a day. Nothing is
+// ever sent but the request itself, and any failure is the same as there being no newer release.
+pub async fn newer_release(cache_path: &Path, now: DateTime<Utc>) -> Option<String> {
+    let latest = match cache_file::load_json::<UpdateCheck>(cache_path)
+        .filter(|check| is_fresh(check.checked_at, now))
+    {
+        Some(check) => check.latest,
+        None => {
+            let latest = fetch_latest_release().await.ok()?;
+            let _ = cache_file::save_json(
+                cache_path,
+                &UpdateCheck {
+                    checked_at: now,
+                    latest: latest.clone(),
+                },
+            );
+            latest
+        }
+    };
+    is_newer(&latest, CURRENT_VERSION).then(|| latest.trim().trim_start_matches('v').to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compares_versions() {
+        assert!(is_newer("0.26.0", "0.25.0"));
+        assert!(is_newer("v1.0.0", "0.25.0"));
+        assert!(is_newer("0.25.1", "0.25.0"));
+        // Numbers, not text
+        assert!(is_newer("0.25.10", "0.25.9"));
+        assert!(!is_newer("0.25.0", "0.25.0"));
+        assert!(!is_newer("v0.24.3", "0.25.0"));
+        // Tags that aren't versions
+        assert!(!is_newer("nightly", "0.25.0"));
+        assert!(!is_newer("1.0", "0.25.0"));
+        assert!(!is_newer("1.0.0.0", "0.25.0"));
+        assert!(!is_newer("0.26.0", "unknown"));
+    }
+
+    #[test]
+    fn orders_pre_releases_before_their_release() {
+        assert!(is_newer("0.26.0", "0.26.0-beta.2"));
+        assert!(!is_newer("0.26.0-rc.1", "0.26.0"));
+        // Still newer than the release before
+        assert!(is_newer("0.26.0-alpha", "0.25.0"));
+
+        // The order from the semver spec
+        let versions = [
+            "1.0.0-alpha",
+            "1.0.0-alpha.1",
+            "1.0.0-alpha.beta",
+            "1.0.0-beta",
+            "1.0.0-beta.2",
+            "1.0.0-beta.11",
+            "1.0.0-rc.1",
+            "1.0.0",
+        ];
+        for pair in versions.windows(2) {
+            assert!(is_newer(pair[1], pair[0]), "{} > {}", pair[1], pair[0]);
+            assert!(!is_newer(pair[0], pair[1]), "{} < {}", pair[0], pair[1]);
+        }
+        assert_eq!(Version::parse("1.0.0-"), None);
+        assert_eq!(Version::parse("1.0.0-beta..1"), None);
+    }
+
+    #[test]
+    fn ignores_build_metadata() {
+        assert_eq!(Version::parse("v1.0.0+20240101"), Version::parse("1.0.0"));
+        assert_eq!(
+            Version::parse("1.0.0-rc.1+build.5"),
+            Version::parse("1.0.0-rc.1")
+        );
+        assert!(!is_newer("0.25.0+build.2", "0.25.0"));
+        assert!(!is_newer("0.25.0", "0.25.0+build.2"));
+    }
+
+    #[test]
+    fn trusts_a_check_for_a_day() {
+        let checked_at = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        assert!(is_fresh(checked_at, checked_at));
+        assert!(is_fresh(checked_at, checked_at + TimeDelta::hours(23)));
+        assert!(!is_fresh(checked_at, checked_at + TimeDelta::hours(24)));
+        assert!(!is_fresh(checked_at, checked_at + TimeDelta::days(3)));
+        // The clock was turned back
+        assert!(!is_fresh(checked_at, checked_at - TimeDelta::minutes(1)));
+    }
+
+    #[test]
+    fn saves_and_loads_the_check() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("update_check.json");
+        let check = UpdateCheck {
+            checked_at: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+            latest: String::from("v0.26.0"),
+        };
+        cache_file::save_json(&path, &check).unwrap();
+        assert_eq!(cache_file::load_json(&path), Some(check));
+    }
+
+    #[tokio::test]
+    async fn uses_a_fresh_check_without_fetching() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("update_check.json");
+        let now = Utc::now();
+        let check = |latest: &str| UpdateCheck {
+            checked_at: now - TimeDelta::hours(1),
+            latest: latest.to_string(),
+        };
+
+        cache_file::save_json(&path, &check("v999.0.0")).unwrap();
+        assert_eq!(
+            newer_release(&path, now).await,
+            Some(String::from("999.0.0"))
+        );
+        cache_file::save_json(&path, &check(CURRENT_VERSION)).unwrap();
+        assert_eq!(newer_release(&path, now).await, None);
+    }
+}
@@ -1,15 +1,17 @@
 use crate::event::Key;
+use crate::lyrics::DEFAULT_LYRICS_API_URL;
+use crate::paths::{self, USER_CONFIG_FILE};
 use anyhow::{anyhow, Result};
+use rspotify::model::Country;
 use serde::{Deserialize, Serialize};
-use std::{
-    fs,
-    path::{Path, PathBuf},
-};
+use std::{fs, path::PathBuf};
 use tui::style::Color;
 
-const FILE_NAME: &str = "config.yml";
-const CONFIG_DIR: &str = ".config";
-const APP_CONFIG_DIR: &str = "spotify-tui";
+// Ticking faster than this spins the UI, and can starve the network task, for no smoother a
+// picture
+pub const MIN_TICK_RATE_MILLISECONDS: u64 = 16;
+// Exclusive
+pub const MAX_TICK_RATE_MILLISECONDS: u64 = 1000;
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct UserTheme {
@@ -73,42 +75,68 @@ impl Default for Theme {
 }
 
 fn parse_key(key: String) -> Result<Key> {
-    fn get_single_char(string: &str) -> char {
-        match string.chars().next() {
-            Some(c) => c,
-            None => panic!(),
+    fn get_single_char(string: &str) -> Option<char> {
+        let mut chars = string.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Some(c),
+            _ => None,
         }
     }
 
-    match key.len() {
-        1 => Ok(Key::Char(get_single_char(key.as_str()))),
-        _ => {
-            let sections: Vec<&str> = key.split('-').collect();
-
-            if sections.len() > 2 {
-                return Err(anyhow!(
-                    "Shortcut can only have 2 keys, \"{}\" has {}",
-                    key,
-                    sections.len()
-                ));
+    fn get_named_key(name: &str) -> Option<Key> {
+        let key = match name.to_lowercase().as_str() {
+            "left" => Key::Left,
+            "right" => Key::Right,
+            "up" => Key::Up,
+            "down" => Key::Down,
+            "home" => Key::Home,
+            "end" => Key::End,
+            "pageup" => Key::PageUp,
+            "pagedown" => Key::PageDown,
+            "tab" => Key::Tab,
+            "backtab" => Key::BackTab,
+            "enter" => Key::Enter,
+            "ins" | "insert" => Key::Ins,
+            "backspace" | "delete" => Key::Backspace,
+            "del" => Key::Delete,
+            "esc" | "escape" => Key::Esc,
+            "space" => Key::Char(' '),
+            function => {
+                let n = function.strip_prefix('f')?.parse::<u8>().ok()?;
+                if !(1..=12).contains(&n) {
+                    return None;
+                }
+                Key::from_f(n)
             }
+        };
+        Some(key)
+    }
 
-            match sections[0].to_lowercase().as_str() {
-                "ctrl" => Ok(Key::Ctrl(get_single_char(sections[1]))),
-                "alt" => Ok(Key::Alt(get_single_char(sections[1]))),
-                "left" => Ok(Key::Left),
-                "right" => Ok(Key::Right),
-                "up" => Ok(Key::Up),
-                "down" => Ok(Key::Down),
-                "backspace" | "delete" => Ok(Key::Backspace),
-                "del" => Ok(Key::Delete),
-                "esc" | "escape" => Ok(Key::Esc),
-                "pageup" => Ok(Key::PageUp),
-                "pagedown" => Ok(Key::PageDown),
-                "space" => Ok(Key::Char(' ')),
-                _ => Err(anyhow!("The key \"{}\" is unknown.", sections[0])),
-            }
+    if let Some(c) = get_single_char(&key) {
+        return Ok(Key::Char(c));
+    }
+
+    let sections: Vec<&str> = key.split('-').collect();
+    match sections[..] {
+        [name] => get_named_key(name).ok_or_else(|| anyhow!("The key \"{}\" is unknown.", name)),
+        [modifier, name] => {
+            let base = get_single_char(name)
+                .map(Key::Char)
+                .or_else(|| get_named_key(name))
+                .ok_or_else(|| anyhow!("The key \"{}\" is unknown.", name))?;
+            let modified = match modifier.to_lowercase().as_str() {
+                "ctrl" => base.with_ctrl(),
+                "alt" => base.with_alt(),
+                "shift" if base == Key::Tab => Some(Key::BackTab),
+                _ => return Err(anyhow!("The modifier \"{}\" is unknown.", modifier)),
+            };
+            modified.ok_or_else(|| anyhow!("The shortcut \"{}\" can't be typed.", key))
         }
+        _ => Err(anyhow!(
+            "Shortcut can only have 2 keys, \"{}\" has {}",
+            key,
+            sections.len()
+        )),
     }
 }
 
@@ -158,6 +186,8 @@ pub struct KeyBindingsString {
     manage_devices: Option<String>,
     decrease_volume: Option<String>,
     increase_volume: Option<String>,
+    decrease_volume_big: Option<String>,
+    increase_volume_big: Option<String>,
     toggle_playback: Option<String>,
     seek_backwards: Option<String>,
     seek_forwards: Option<String>,
@@ -173,6 +203,18 @@ pub struct KeyBindingsString {
     audio_analysis: Option<String>,
     basic_view: Option<String>,
     add_item_to_queue: Option<String>,
+    lyrics: Option<String>,
+    notification_history: Option<String>,
+    toggle_playbar: Option<String>,
+    jump_to_playing: Option<String>,
+    like_current: Option<String>,
+    do_not_disturb: Option<String>,
+    copy_view: Option<String>,
+    copy_view_markdown: Option<String>,
+    search_artist_playlists: Option<String>,
+    search_artist_appears_on: Option<String>,
+    save_playing_item_parent: Option<String>,
+    dump_selected_item: Option<String>,
 }
 
 #[derive(Clone)]
@@ -188,6 +230,8 @@ pub struct KeyBindings {
     pub manage_devices: Key,
     pub decrease_volume: Key,
     pub increase_volume: Key,
+    pub decrease_volume_big: Key,
+    pub increase_volume_big: Key,
     pub toggle_playback: Key,
     pub seek_backwards: Key,
     pub seek_forwards: Key,
@@ -203,40 +247,150 @@ pub struct KeyBindings {
     pub audio_analysis: Key,
     pub basic_view: Key,
     pub add_item_to_queue: Key,
+    pub lyrics: Key,
+    pub notification_history: Key,
+    pub toggle_playbar: Key,
+    // Selects the row of what is playing in the open table
+    pub jump_to_playing: Key,
+    // Likes or unlikes what is playing, whichever view is open
+    pub like_current: Key,
+    pub do_not_disturb: Key,
+    pub copy_view: Key,
+    pub copy_view_markdown: Key,
+    // Search for playlists with the name of the open artist, or albums by others they're on
+    pub search_artist_playlists: Key,
+    pub search_artist_appears_on: Key,
+    // Saves the album of the playing track or follows the show of the playing episode
+    pub save_playing_item_parent: Key,
+    // Only with `--debug`, and left out of the help
+    pub dump_selected_item: Key,
+}
+
+// The columns the item table can show, each of which it can be sorted by
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ItemTableColumn {
+    Title,
+    Artist,
+    Album,
+    Duration,
+    Popularity,
+    AddedAt,
+}
+
+impl ItemTableColumn {
+    fn parse(column: &str) -> Result<ItemTableColumn> {
+        Ok(match column {
+            "title" => ItemTableColumn::Title,
+            "artist" => ItemTableColumn::Artist,
+            "album" => ItemTableColumn::Album,
+            "duration" => ItemTableColumn::Duration,
+            "popularity" => ItemTableColumn::Popularity,
+            "added_at" => ItemTableColumn::AddedAt,
+            _ => return Err(anyhow!("The item table column \"{}\" is unknown", column)),
+        })
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ItemTableColumn::Title => "Title",
+            ItemTableColumn::Artist => "Artist",
+            ItemTableColumn::Album => "Album",
+            ItemTableColumn::Duration => "Length",
+            ItemTableColumn::Popularity => "Popularity",
+            ItemTableColumn::AddedAt => "Added",
+        }
+    }
 }
 
 #[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct BehaviorConfigString {
     pub seek_milliseconds: Option<u32>,
     pub volume_increment: Option<u8>,
+    pub volume_big_increment: Option<u8>,
     pub tick_rate_milliseconds: Option<u64>,
     pub enable_text_emphasis: Option<bool>,
     pub show_loading_indicator: Option<bool>,
     pub enforce_wide_search_bar: Option<bool>,
     pub liked_icon: Option<String>,
+    pub stale_liked_icon: Option<String>,
     pub shuffle_icon: Option<String>,
     pub repeat_track_icon: Option<String>,
     pub repeat_context_icon: Option<String>,
     pub playing_icon: Option<String>,
     pub paused_icon: Option<String>,
     pub set_window_title: Option<bool>,
+    pub clipboard_command: Option<String>,
+    pub show_album_art: Option<bool>,
+    pub auto_select_device: Option<bool>,
+    pub control_socket: Option<bool>,
+    pub lyrics_api_url: Option<String>,
+    pub enable_mouse: Option<bool>,
+    pub record_history: Option<bool>,
+    pub item_table_columns: Option<Vec<String>>,
+    pub hide_playbar: Option<bool>,
+    pub playlist_cover_accent: Option<bool>,
+    pub enable_cache: Option<bool>,
+    pub dedup_recently_played: Option<bool>,
+    pub check_for_updates: Option<bool>,
+    pub market: Option<String>,
+    pub enable_audio_analysis: Option<bool>,
+    pub playback_poll_interval_seconds: Option<u64>,
+    pub playback_poll_max_interval_seconds: Option<u64>,
+    pub queue_context_limit: Option<u32>,
+    pub large_search_limit: Option<u32>,
+    pub small_search_limit: Option<u32>,
 }
 
 #[derive(Clone)]
 pub struct BehaviorConfig {
     pub seek_milliseconds: u32,
     pub volume_increment: u8,
+    pub volume_big_increment: u8,
     pub tick_rate_milliseconds: u64,
     pub enable_text_emphasis: bool,
     pub show_loading_indicator: bool,
     pub enforce_wide_search_bar: bool,
     pub liked_icon: String,
+    pub stale_liked_icon: String,
     pub shuffle_icon: String,
     pub repeat_track_icon: String,
     pub repeat_context_icon: String,
     pub playing_icon: String,
     pub paused_icon: String,
     pub set_window_title: bool,
+    // Command that receives copied text on stdin when the system clipboard is unavailable
+    pub clipboard_command: Option<String>,
+    pub show_album_art: bool,
+    pub auto_select_device: bool,
+    pub control_socket: bool,
+    pub lyrics_api_url: String,
+    pub enable_mouse: bool,
+    pub record_history: bool,
+    pub item_table_columns: Vec<ItemTableColumn>,
+    // Start without the playbar, which can still be shown again with its key
+    pub hide_playbar: bool,
+    // Draw the table of a playlist in the main color of its cover
+    pub playlist_cover_accent: bool,
+    // Keep the playlists, saved albums and shows and the profile between sessions, shown until
+    // they are fetched again
+    pub enable_cache: bool,
+    // Show a track played several times in a row once in Spotify's recently played
+    pub dedup_recently_played: bool,
+    // Look for a newer release on startup, at most once a day
+    pub check_for_updates: bool,
+    // Searches and availability go by this market rather than the country of the account
+    pub market: Option<Country>,
+    // The audio analysis screen, which redraws on every tick, can be turned off on slow machines
+    pub enable_audio_analysis: bool,
+    // How often the playback is fetched, and how far that backs off to while nothing is playing
+    pub playback_poll_interval_seconds: u64,
+    pub playback_poll_max_interval_seconds: u64,
+    // The most tracks queued for a whole album or playlist, as each is a request of its own
+    pub queue_context_limit: u32,
+    // How many results a page fetches for the big tables and for the small ones, e.g. those of the
+    // search, when set rather than fitted to the height of the terminal
+    pub large_search_limit: Option<u32>,
+    pub small_search_limit: Option<u32>,
 }
 
 #[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -270,6 +424,8 @@ impl UserConfig {
                 manage_devices: Key::Char('d'),
                 decrease_volume: Key::Char('-'),
                 increase_volume: Key::Char('+'),
+                decrease_volume_big: Key::Char('_'),
+                increase_volume_big: Key::Char('='),
                 toggle_playback: Key::Char(' '),
                 seek_backwards: Key::Char('<'),
                 seek_forwards: Key::Char('>'),
@@ -285,51 +441,71 @@ impl UserConfig {
                 audio_analysis: Key::Char('v'),
                 basic_view: Key::Char('B'),
                 add_item_to_queue: Key::Char('z'),
+                lyrics: Key::Char('y'),
+                notification_history: Key::Char('N'),
+                toggle_playbar: Key::Char('b'),
+                jump_to_playing: Key::Char('g'),
+                like_current: Key::Char('f'),
+                do_not_disturb: Key::Char('Z'),
+                copy_view: Key::Char('Y'),
+                copy_view_markdown: Key::Alt('y'),
+                search_artist_playlists: Key::Char('P'),
+                search_artist_appears_on: Key::Char('I'),
+                save_playing_item_parent: Key::Alt('f'),
+                dump_selected_item: Key::Alt('j'),
             },
             behavior: BehaviorConfig {
                 seek_milliseconds: 5 * 1000,
                 volume_increment: 10,
+                volume_big_increment: 25,
                 tick_rate_milliseconds: 250,
                 enable_text_emphasis: true,
                 show_loading_indicator: true,
                 enforce_wide_search_bar: false,
                 liked_icon: "♥".to_string(),
+                stale_liked_icon: "♡".to_string(),
                 shuffle_icon: "🔀".to_string(),
                 repeat_track_icon: "🔂".to_string(),
                 repeat_context_icon: "🔁".to_string(),
                 playing_icon: "▶".to_string(),
                 paused_icon: "⏸".to_string(),
                 set_window_title: true,
+                clipboard_command: None,
+                show_album_art: false,
+                auto_select_device: true,
+                control_socket: false,
+                lyrics_api_url: String::from(DEFAULT_LYRICS_API_URL),
+                enable_mouse: true,
+                record_history: false,
+                item_table_columns: vec![
+                    ItemTableColumn::Title,
+                    ItemTableColumn::Artist,
+                    ItemTableColumn::Album,
+                    ItemTableColumn::Duration,
+                ],
+                hide_playbar: false,
+                playlist_cover_accent: false,
+                enable_cache: false,
+                dedup_recently_played: false,
+                check_for_updates: false,
+                market: None,
+                enable_audio_analysis: true,
+                playback_poll_interval_seconds: 5,
+                playback_poll_max_interval_seconds: 60,
+                queue_context_limit: 100,
+                large_search_limit: None,
+                small_search_limit: None,
             },
             path_to_config: None,
         }
     }
 
     pub fn get_or_build_paths(&mut self) -> Result<()> {
-        match dirs::home_dir() {
-            Some(home) => {
-                let path = Path::new(&home);
-                let home_config_dir = path.join(CONFIG_DIR);
-                let app_config_dir = home_config_dir.join(APP_CONFIG_DIR);
-
-                if !home_config_dir.exists() {
-                    fs::create_dir(&home_config_dir)?;
-                }
-
-                if !app_config_dir.exists() {
-                    fs::create_dir(&app_config_dir)?;
-                }
-
-                let config_file_path = &app_config_dir.join(FILE_NAME);
-
-                let paths = UserConfigPaths {
-                    config_file_path: config_file_path.to_path_buf(),
-                };
-                self.path_to_config = Some(paths);
-                Ok(())
-            }
-            None => Err(anyhow!("No $HOME directory found for client config")),
-        }
+        let app_dirs = paths::app_dirs()?;
+        self.path_to_config = Some(UserConfigPaths {
+            config_file_path: app_dirs.config_dir.join(USER_CONFIG_FILE),
+        });
+        Ok(())
     }
 
     pub fn load_keybindings(&mut self, keybindings: KeyBindingsString) -> Result<()> {
@@ -353,6 +529,8 @@ impl UserConfig {
         to_keys!(manage_devices);
         to_keys!(decrease_volume);
         to_keys!(increase_volume);
+        to_keys!(decrease_volume_big);
+        to_keys!(increase_volume_big);
         to_keys!(toggle_playback);
         to_keys!(seek_backwards);
         to_keys!(seek_forwards);
@@ -368,6 +546,18 @@ impl UserConfig {
         to_keys!(audio_analysis);
         to_keys!(basic_view);
         to_keys!(add_item_to_queue);
+        to_keys!(lyrics);
+        to_keys!(notification_history);
+        to_keys!(toggle_playbar);
+        to_keys!(jump_to_playing);
+        to_keys!(like_current);
+        to_keys!(do_not_disturb);
+        to_keys!(copy_view);
+        to_keys!(copy_view_markdown);
+        to_keys!(search_artist_playlists);
+        to_keys!(search_artist_appears_on);
+        to_keys!(save_playing_item_parent);
+        to_keys!(dump_selected_item);
 
         Ok(())
     }
@@ -413,12 +603,26 @@ impl UserConfig {
             self.behavior.volume_increment = behavior_string;
         }
 
+        if let Some(behavior_string) = behavior_config.volume_big_increment {
+            if behavior_string > 100 {
+                return Err(anyhow!(
+                    "Volume big increment must be between 0 and 100, is {}",
+                    behavior_string,
+                ));
+            }
+            self.behavior.volume_big_increment = behavior_string;
+        }
+
         if let Some(tick_rate) = behavior_config.tick_rate_milliseconds {
-            if tick_rate >= 1000 {
-                return Err(anyhow!("Tick rate must be below 1000"));
-            } else {
-                self.behavior.tick_rate_milliseconds = tick_rate;
+            if !(MIN_TICK_RATE_MILLISECONDS..MAX_TICK_RATE_MILLISECONDS).contains(&tick_rate) {
+                return Err(anyhow!(
+                    "Tick rate must be between {} and {} milliseconds, is {}",
+                    MIN_TICK_RATE_MILLISECONDS,
+                    MAX_TICK_RATE_MILLISECONDS - 1,
+                    tick_rate,
+                ));
             }
+            self.behavior.tick_rate_milliseconds = tick_rate;
         }
 
         if let Some(text_emphasis) = behavior_config.enable_text_emphasis {
@@ -437,6 +641,10 @@ impl UserConfig {
             self.behavior.liked_icon = liked_icon;
         }
 
+        if let Some(stale_liked_icon) = behavior_config.stale_liked_icon {
+            self.behavior.stale_liked_icon = stale_liked_icon;
+        }
+
         if let Some(paused_icon) = behavior_config.paused_icon {
             self.behavior.paused_icon = paused_icon;
         }
@@ -461,6 +669,108 @@ impl UserConfig {
             self.behavior.set_window_title = set_window_title;
         }
 
+        if let Some(clipboard_command) = behavior_config.clipboard_command {
+            self.behavior.clipboard_command = Some(clipboard_command);
+        }
+
+        if let Some(show_album_art) = behavior_config.show_album_art {
+            self.behavior.show_album_art = show_album_art;
+        }
+
+        if let Some(auto_select_device) = behavior_config.auto_select_device {
+            self.behavior.auto_select_device = auto_select_device;
+        }
+
+        if let Some(control_socket) = behavior_config.control_socket {
+            self.behavior.control_socket = control_socket;
+        }
+
+        if let Some(lyrics_api_url) = behavior_config.lyrics_api_url {
+            self.behavior.lyrics_api_url = lyrics_api_url;
+        }
+
+        if let Some(enable_mouse) = behavior_config.enable_mouse {
+            self.behavior.enable_mouse = enable_mouse;
+        }
+
+        if let Some(record_history) = behavior_config.record_history {
+            self.behavior.record_history = record_history;
+        }
+
+        if let Some(columns) = behavior_config.item_table_columns {
+            let columns = columns
+                .iter()
+                .map(|column| ItemTableColumn::parse(column))
+                .collect::<Result<Vec<_>>>()?;
+            if columns.is_empty() {
+                return Err(anyhow!("The item table needs at least one column"));
+            }
+            self.behavior.item_table_columns = columns;
+        }
+
+        if let Some(hide_playbar) = behavior_config.hide_playbar {
+            self.behavior.hide_playbar = hide_playbar;
+        }
+
+        if let Some(playlist_cover_accent) = behavior_config.playlist_cover_accent {
+            self.behavior.playlist_cover_accent = playlist_cover_accent;
+        }
+
+        if let Some(enable_cache) = behavior_config.enable_cache {
+            self.behavior.enable_cache = enable_cache;
+        }
+
+        if let Some(dedup_recently_played) = behavior_config.dedup_recently_played {
+            self.behavior.dedup_recently_played = dedup_recently_played;
+        }
+
+        if let Some(check_for_updates) = behavior_config.check_for_updates {
+            self.behavior.check_for_updates = check_for_updates;
+        }
+
+        if let Some(market) = behavior_config.market {
+            self.behavior.market = Some(parse_market(&market)?);
+        }
+
+        if let Some(enable_audio_analysis) = behavior_config.enable_audio_analysis {
+            self.behavior.enable_audio_analysis = enable_audio_analysis;
+        }
+
+        if let Some(interval) = behavior_config.playback_poll_interval_seconds {
+            if interval == 0 {
+                return Err(anyhow!("Playback poll interval must be at least 1 second"));
+            }
+            self.behavior.playback_poll_interval_seconds = interval;
+        }
+
+        if let Some(max_interval) = behavior_config.playback_poll_max_interval_seconds {
+            self.behavior.playback_poll_max_interval_seconds = max_interval;
+        }
+
+        if let Some(queue_context_limit) = behavior_config.queue_context_limit {
+            self.behavior.queue_context_limit = queue_context_limit;
+        }
+
+        // 50 is the most the API returns at once
+        for (name, limit) in [
+            ("Large search limit", behavior_config.large_search_limit),
+            ("Small search limit", behavior_config.small_search_limit),
+        ] {
+            if limit.is_some_and(|limit| !(1..=50).contains(&limit)) {
+                return Err(anyhow!(
+                    "{} must be between 1 and 50, is {}",
+                    name,
+                    limit.unwrap()
+                ));
+            }
+        }
+        if let Some(large_search_limit) = behavior_config.large_search_limit {
+            self.behavior.large_search_limit = Some(large_search_limit);
+        }
+        if let Some(small_search_limit) = behavior_config.small_search_limit {
+            self.behavior.small_search_limit = Some(small_search_limit);
+        }
+
         Ok(())
     }
 
@@ -501,6 +811,16 @@ impl UserConfig {
     pub fn padded_liked_icon(&self) -> String {
         format!("{} ", &self.behavior.liked_icon)
     }
+
+    // The liked icon, or while the item is only remembered from the last session to be in the
+    // library, the stale one
+    pub fn padded_library_icon(&self, is_stale: bool) -> String {
+        if is_stale {
+            format!("{} ", &self.behavior.stale_liked_icon)
+        } else {
+            self.padded_liked_icon()
+        }
+    }
 }
 
 fn parse_theme_item(theme_item: &str) -> Result<Color> {
@@ -540,6 +860,12 @@ fn parse_theme_item(theme_item: &str) -> Result<Color> {
     Ok(color)
 }
 
+// An ISO 3166-1 alpha-2 code such as GB, in either case
+fn parse_market(code: &str) -> Result<Country> {
+    serde_json::from_value(serde_json::Value::String(code.trim().to_uppercase()))
+        .map_err(|_| anyhow!("\"{}\" is not a two letter country code, like GB", code))
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -553,6 +879,32 @@ mod tests {
         assert_eq!(parse_key(String::from("-")).unwrap(), Key::Char('-'));
         assert_eq!(parse_key(String::from("esc")).unwrap(), Key::Esc);
         assert_eq!(parse_key(String::from("del")).unwrap(), Key::Delete);
+        assert_eq!(parse_key(String::from("é")).unwrap(), Key::Char('é'));
+        assert_eq!(parse_key(String::from("space")).unwrap(), Key::Char(' '));
+        assert_eq!(parse_key(String::from("Home")).unwrap(), Key::Home);
+        assert_eq!(parse_key(String::from("tab")).unwrap(), Key::Tab);
+        assert_eq!(parse_key(String::from("backtab")).unwrap(), Key::BackTab);
+        assert_eq!(parse_key(String::from("shift-tab")).unwrap(), Key::BackTab);
+        assert_eq!(parse_key(String::from("f5")).unwrap(), Key::F5);
+        assert_eq!(parse_key(String::from("F12")).unwrap(), Key::F12);
+        assert_eq!(
+            parse_key(String::from("ctrl-right")).unwrap(),
+            Key::CtrlRight
+        );
+        assert_eq!(
+            parse_key(String::from("ctrl-pagedown")).unwrap(),
+            Key::CtrlPageDown
+        );
+        assert_eq!(parse_key(String::from("ctrl-home")).unwrap(), Key::CtrlHome);
+        assert_eq!(parse_key(String::from("alt-x")).unwrap(), Key::Alt('x'));
+        assert_eq!(parse_key(String::from("alt-space")).unwrap(), Key::Alt(' '));
+
+        for key in [
+            "", "f0", "f13", "ctrl-", "ctrl-f5", "alt-left", "shift-a", "meta-x", "ctrl-a-b",
+            "nope",
+        ] {
+            assert!(parse_key(String::from(key)).is_err(), "{:?}", key);
+        }
     }
 
     #[test]
@@ -595,4 +947,90 @@ mod tests {
             "Enter key should be reserved"
         );
     }
+
+    #[test]
+    fn item_table_columns() {
+        use super::{BehaviorConfigString, ItemTableColumn, UserConfig};
+        let load = |columns: &[&str]| {
+            let mut config = UserConfig::new();
+            config
+                .load_behaviorconfig(BehaviorConfigString {
+                    item_table_columns: Some(columns.iter().map(|c| c.to_string()).collect()),
+                    ..Default::default()
+                })
+                .map(|_| config.behavior.item_table_columns)
+        };
+
+        assert_eq!(
+            load(&["added_at", "title", "popularity"]).unwrap(),
+            [
+                ItemTableColumn::AddedAt,
+                ItemTableColumn::Title,
+                ItemTableColumn::Popularity
+            ]
+        );
+        assert!(load(&["title", "bpm"]).is_err());
+        assert!(load(&[]).is_err());
+    }
+
+    #[test]
+    fn tick_rate() {
+        use super::{BehaviorConfigString, UserConfig};
+        let load = |tick_rate: u64| {
+            let mut config = UserConfig::new();
+            config
+                .load_behaviorconfig(BehaviorConfigString {
+                    tick_rate_milliseconds: Some(tick_rate),
+                    ..Default::default()
+                })
+                .map(|_| config.behavior.tick_rate_milliseconds)
+        };
+
+        assert_eq!(load(16).unwrap(), 16);
+        assert_eq!(load(999).unwrap(), 999);
+        assert!(load(1).is_err());
+        assert!(load(0).is_err());
+        assert!(load(1000).is_err());
+    }
+
+    #[test]
+    fn search_limits() {
+        use super::{BehaviorConfigString, UserConfig};
+        let load = |large_search_limit: u32| {
+            let mut config = UserConfig::new();
+            config
+                .load_behaviorconfig(BehaviorConfigString {
+                    large_search_limit: Some(large_search_limit),
+                    ..Default::default()
+                })
+                .map(|_| config.behavior.large_search_limit)
+        };
+
+        assert_eq!(load(1).unwrap(), Some(1));
+        assert_eq!(load(50).unwrap(), Some(50));
+        assert!(load(0).is_err());
+        assert!(load(51).is_err());
+        assert_eq!(UserConfig::new().behavior.small_search_limit, None);
+    }
+
+    #[test]
+    fn market() {
+        use super::{BehaviorConfigString, UserConfig};
+        use rspotify::model::Country;
+        let load = |market: &str| {
+            let mut config = UserConfig::new();
+            config
+                .load_behaviorconfig(BehaviorConfigString {
+                    market: Some(market.to_string()),
+                    ..Default::default()
+                })
+                .map(|_| config.behavior.market)
+        };
+
+        assert_eq!(load("GB").unwrap(), Some(Country::UnitedKingdom));
+        assert_eq!(load("de").unwrap(), Some(Country::Germany));
+        assert!(load("Germany").is_err());
+        assert!(load("XX").is_err());
+        assert_eq!(UserConfig::new().behavior.market, None);
+    }
 }
@@ -104,28 +104,30 @@ pub fn derive_to_static(tokens: TokenStream) -> Result<TokenStream, Error> {
         quote!(#data_ident)
     };
 
-    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
-
-    let mut owned_generics = ast.generics.clone();
-    owned_generics.params = owned_generics
-        .params
-        .into_iter()
-        .map(|param| match param {
-            ::syn::GenericParam::Lifetime(lt_param) => {
-                ::syn::GenericParam::Lifetime(::syn::LifetimeParam {
-                    lifetime: parse2(quote!('static)).unwrap(),
-                    ..lt_param
-                })
-            }
-            _ => param,
-        })
-        .collect();
-
-    let (_, owned_ty_generics, _) = owned_generics.split_for_impl();
+    let (impl_generics, ty_generics, _) = ast.generics.split_for_impl();
+    let where_clause = where_clause(&ast.generics);
+
+    // Lifetimes become 'static and each type parameter its own static type
+    let owned_params = ast.generics.params.iter().map(|param| match param {
+        ::syn::GenericParam::Lifetime(_) => quote!('static),
+        ::syn::GenericParam::Type(ty_param) => {
+            let ident = &ty_param.ident;
+            quote!(<#ident as ::spotify_tui_util::ToStatic>::Static)
+        }
+        ::syn::GenericParam::Const(const_param) => {
+            let ident = &const_param.ident;
+            quote!(#ident)
+        }
+    });
+    let owned_ty = if ast.generics.params.is_empty() {
+        quote!(#data_ident)
+    } else {
+        quote!(#data_ident<#(#owned_params),*>)
+    };
 
     let tokens = quote!(
         impl #impl_generics ::spotify_tui_util::ToStatic for #data_ident #ty_generics #where_clause {
-            type Static = #data_ident #owned_ty_generics;
+            type Static = #owned_ty;
             fn to_static(self) -> Self::Static {
                 let #receiver_ident = self;
                 #body
@@ -136,6 +138,46 @@ pub fn derive_to_static(tokens: TokenStream) -> Result<TokenStream, Error> {
     Ok(tokens)
 }
 
+// The where clause of the data type, with each type parameter `T` also bound by `T: ToStatic`,
+// and `T::Static` bound the way `T` is so that the static type is well-formed
+fn where_clause(generics: &::syn::Generics) -> TokenStream {
+    let mut predicates = vec![];
+    for ty_param in generics.type_params() {
+        let ident = &ty_param.ident;
+        let bounds = &ty_param.bounds;
+        predicates.push(quote!(#ident: ::spotify_tui_util::ToStatic));
+        if !bounds.is_empty() {
+            predicates.push(quote!(<#ident as ::spotify_tui_util::ToStatic>::Static: #bounds));
+        }
+    }
+    let ty_param_idents = generics
+        .type_params()
+        .map(|ty_param| &ty_param.ident)
+        .collect::<Vec<_>>();
+    for predicate in generics.where_clause.iter().flat_map(|w| &w.predicates) {
+        predicates.push(quote!(#predicate));
+        if let ::syn::WherePredicate::Type(::syn::PredicateType {
+            bounded_ty: ::syn::Type::Path(ty_path),
+            bounds,
+            ..
+        }) = predicate
+        {
+            if let Some(ident) = ty_path.path.get_ident() {
+                if ty_param_idents.contains(&ident) {
+                    predicates
+                        .push(quote!(<#ident as ::spotify_tui_util::ToStatic>::Static: #bounds));
+                }
+            }
+        }
+    }
+
+    if predicates.is_empty() {
+        quote!()
+    } else {
+        quote!(where #(#predicates),*)
+    }
+}
+
 fn map_fields(
     scope_path: ::syn::Path,
     fields: Fields<ToStaticField>,
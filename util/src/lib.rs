@@ -1,7 +1,59 @@
 use rspotify_model::enums::types::Type;
 use rspotify_model::{idtypes::*, PlayableItem, *};
 pub use spotify_tui_util_proc_macros::*;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::Hash;
+use std::marker::PhantomData;
 
+/// Converts a value into one that borrows nothing, e.g. so that it can be sent to another task.
+///
+/// The derive does it field by field, and turns each lifetime parameter into `'static` and each
+/// type parameter `T` into `T::Static`:
+///
+/// ```
+/// use spotify_tui_util::ToStatic;
+/// use rspotify_model::TrackId;
+///
+/// #[derive(ToStatic)]
+/// struct Tracks<'a, T>(Vec<T>, TrackId<'a>);
+///
+/// let id = String::from("4iV5W9uYEdYUVa79Axb7Rh");
+/// let tracks = Tracks(vec![1u32], TrackId::from_id(id.as_str()).unwrap());
+/// let tracks: Tracks<'static, u32> = tracks.to_static();
+/// ```
+///
+/// Every field needs to be `ToStatic` itself, or mapped with `#[to_static(map = ...)]`:
+///
+/// ```compile_fail
+/// use spotify_tui_util::ToStatic;
+///
+/// #[derive(ToStatic)]
+/// struct Shared(std::rc::Rc<String>);
+/// ```
+///
+/// And so does every type a type parameter stands for:
+///
+/// ```compile_fail
+/// use spotify_tui_util::ToStatic;
+///
+/// #[derive(ToStatic)]
+/// struct Items<T>(Vec<T>);
+///
+/// let _ = Items(vec![std::rc::Rc::new(1)]).to_static();
+/// ```
+///
+/// Unions can't be derived:
+///
+/// ```compile_fail
+/// use spotify_tui_util::ToStatic;
+///
+/// #[derive(ToStatic)]
+/// union Number {
+///     int: u32,
+///     float: f32,
+/// }
+/// ```
 pub trait ToStatic {
     type Static: 'static;
     fn to_static(self) -> Self::Static;
@@ -35,7 +87,9 @@ to_static!(
     f64,
     String,
     bool,
+    AlbumType,
     Country,
+    SearchType,
     FullAlbum,
     FullArtist,
     FullEpisode,
@@ -50,6 +104,7 @@ to_static!(
     SimplifiedPlaylist,
     SimplifiedShow,
     SimplifiedTrack,
+    TimeRange,
 );
 
 impl<T: ToStatic> ToStatic for Option<T> {
@@ -80,6 +135,72 @@ impl<const N: usize, T: ToStatic> ToStatic for [T; N] {
     }
 }
 
+impl<K: ToStatic, V: ToStatic> ToStatic for HashMap<K, V>
+where
+    K::Static: Eq + Hash,
+{
+    type Static = HashMap<K::Static, V::Static>;
+    fn to_static(self) -> Self::Static {
+        self.into_iter()
+            .map(|(key, value)| (key.to_static(), value.to_static()))
+            .collect()
+    }
+}
+
+impl<T: ToStatic> ToStatic for HashSet<T>
+where
+    T::Static: Eq + Hash,
+{
+    type Static = HashSet<T::Static>;
+    fn to_static(self) -> Self::Static {
+        self.into_iter().map(T::to_static).collect()
+    }
+}
+
+impl<K: ToStatic, V: ToStatic> ToStatic for BTreeMap<K, V>
+where
+    K::Static: Ord,
+{
+    type Static = BTreeMap<K::Static, V::Static>;
+    fn to_static(self) -> Self::Static {
+        self.into_iter()
+            .map(|(key, value)| (key.to_static(), value.to_static()))
+            .collect()
+    }
+}
+
+impl<T: ToStatic> ToStatic for PhantomData<T> {
+    type Static = PhantomData<T::Static>;
+    fn to_static(self) -> Self::Static {
+        PhantomData
+    }
+}
+
+impl ToStatic for Cow<'_, str> {
+    type Static = Cow<'static, str>;
+    fn to_static(self) -> Self::Static {
+        Cow::Owned(self.into_owned())
+    }
+}
+
+macro_rules! tuple_to_static {
+    ($($ty:ident),*) => {
+        impl<$($ty: ToStatic),*> ToStatic for ($($ty,)*) {
+            type Static = ($($ty::Static,)*);
+            #[allow(non_snake_case)]
+            fn to_static(self) -> Self::Static {
+                let ($($ty,)*) = self;
+                ($($ty.to_static(),)*)
+            }
+        }
+    };
+}
+
+tuple_to_static!(A);
+tuple_to_static!(A, B);
+tuple_to_static!(A, B, C);
+tuple_to_static!(A, B, C, D);
+
 impl<'a> ToStatic for ArtistId<'a> {
     type Static = ArtistId<'static>;
     fn to_static(self) -> Self::Static {
@@ -169,10 +290,67 @@ pub fn fmt_opt_ids<T: Id>(
     }
 }
 
+/// Parses the ids of the enums over several types of id, e.g. `PlayableId`, from the ways
+/// Spotify writes them: uris, open.spotify.com links, and bare ids whose type is known.
 pub trait ParseFromUri<'a> {
+    /// A uri such as `spotify:track:4iV5W9uYEdYUVa79Axb7Rh`.
     fn from_uri(uri: &'a str) -> Result<Self, IdError>
     where
         Self: Sized + 'a;
+
+    /// A link such as `https://open.spotify.com/track/4iV5W9uYEdYUVa79Axb7Rh?si=...`, as copied
+    /// from the share menu. See [`parse_url`].
+    fn from_url(url: &'a str) -> Result<Self, IdError>
+    where
+        Self: Sized + 'a;
+
+    /// A bare id, taken to be of type `ty`, or a uri or link of any type.
+    fn from_id_or_uri(ty: Type, id_or_uri: &'a str) -> Result<Self, IdError>
+    where
+        Self: Sized + 'a;
+
+    /// A uri or a link, whichever it is.
+    fn from_uri_or_url(uri_or_url: &'a str) -> Result<Self, IdError>
+    where
+        Self: Sized + 'a,
+    {
+        if is_url(uri_or_url) {
+            Self::from_url(uri_or_url)
+        } else {
+            Self::from_uri(uri_or_url)
+        }
+    }
+}
+
+const URL_HOST: &str = "open.spotify.com/";
+
+fn is_url(text: &str) -> bool {
+    text.contains(URL_HOST)
+}
+
+/// The type and id of an open.spotify.com link, which may leave out the scheme. Whatever follows
+/// the id, such as the `?si=` of a shared link, is ignored, and so is the language some shared
+/// links have before the type, as in `https://open.spotify.com/intl-de/track/<id>`.
+pub fn parse_url(url: &str) -> Result<(Type, &str), IdError> {
+    let host_and_path = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .unwrap_or(url);
+    let path = host_and_path
+        .strip_prefix(URL_HOST)
+        .ok_or(IdError::InvalidPrefix)?;
+    let path = path.split(['?', '#']).next().unwrap_or_default();
+
+    let mut segments = path.split('/');
+    let mut ty = segments.next().unwrap_or_default();
+    if ty.starts_with("intl-") {
+        ty = segments.next().ok_or(IdError::InvalidFormat)?;
+    }
+    let (Some(id), None) = (segments.next(), segments.next()) else {
+        return Err(IdError::InvalidFormat);
+    };
+    let ty = ty.parse::<Type>().map_err(|_| IdError::InvalidType)?;
+    Ok((ty, id))
 }
 
 pub trait PlaybleItemExt {
@@ -190,7 +368,10 @@ pub trait PlayableIdExt {
 }
 
 impl PlaybleItemExt for PlayableItem {
-    type Id<'a> = Option<PlayableId<'a>> where Self: 'a;
+    type Id<'a>
+        = Option<PlayableId<'a>>
+    where
+        Self: 'a;
     fn id(&self) -> Self::Id<'_> {
         match self {
             PlayableItem::Episode(episode) => Some(PlayableId::Episode(episode.id.clone())),
@@ -229,15 +410,40 @@ impl PlayableIdExt for PlayableId<'_> {
 
 macro_rules! id_enum {
     ($name:ident { $($ty:ident),*$(,)? }) => { ::paste::paste! {
+        fn [<$name:snake _from_typed_id>](ty: Type, id: &str) -> Result<$name<'_>, IdError> {
+            match ty {
+                $(Type::$ty => Ok([<$ty Id>]::from_id(id)?.into()),)*
+                _ => Err(IdError::InvalidType),
+            }
+        }
+
         impl<'a> ParseFromUri<'a> for $name<'a> {
             fn from_uri(uri: &'a str) -> Result<Self, IdError>
             where
                 Self: Sized + 'a,
             {
-                let (ty, id) = parse_uri(&uri)?;
-                match ty {
-                    $(Type::$ty => Ok([<$ty Id>]::from_id(id)?.into()),)*
-                    _ => Err(IdError::InvalidType),
+                let (ty, id) = parse_uri(uri)?;
+                [<$name:snake _from_typed_id>](ty, id)
+            }
+
+            fn from_url(url: &'a str) -> Result<Self, IdError>
+            where
+                Self: Sized + 'a,
+            {
+                let (ty, id) = parse_url(url)?;
+                [<$name:snake _from_typed_id>](ty, id)
+            }
+
+            fn from_id_or_uri(ty: Type, id_or_uri: &'a str) -> Result<Self, IdError>
+            where
+                Self: Sized + 'a,
+            {
+                if is_url(id_or_uri) {
+                    Self::from_url(id_or_uri)
+                } else if id_or_uri.starts_with("spotify:") || id_or_uri.starts_with("spotify/") {
+                    Self::from_uri(id_or_uri)
+                } else {
+                    [<$name:snake _from_typed_id>](ty, id_or_uri)
                 }
             }
         }
@@ -251,3 +457,231 @@ id_enum!(PlayContextId {
     Show,
 });
 id_enum!(PlayableId { Episode, Track });
+
+// The derive names the trait by its path from outside the crate
+#[cfg(test)]
+extern crate self as spotify_tui_util;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Compiles only when `T::Static` is `S`
+    fn static_type<T: ToStatic<Static = S>, S>(value: T) -> S {
+        value.to_static()
+    }
+
+    #[derive(Debug, PartialEq, ToStatic)]
+    struct Tracks<'a, T>(Vec<T>, TrackId<'a>);
+
+    #[derive(Debug, PartialEq, ToStatic)]
+    struct Library<'a, T: Clone, const N: usize>
+    where
+        T: Default,
+    {
+        counts: HashMap<AlbumId<'a>, [T; N]>,
+        names: HashSet<Cow<'a, str>>,
+        sorted: BTreeMap<u32, (T, Option<ShowId<'a>>)>,
+    }
+
+    #[derive(Debug, PartialEq, ToStatic)]
+    enum Event<'a, T> {
+        Play(PlayableId<'a>, T),
+        Marker(PhantomData<T>),
+        Stop,
+    }
+
+    const ID: &str = "4iV5W9uYEdYUVa79Axb7Rh";
+
+    const TYPES: [Type; 9] = [
+        Type::Artist,
+        Type::Album,
+        Type::Track,
+        Type::Playlist,
+        Type::User,
+        Type::Show,
+        Type::Episode,
+        Type::Collection,
+        Type::Collectionyourepisodes,
+    ];
+
+    // Whether the type is a context and whether it is playable, which fails to compile once there
+    // is a type that `TYPES` doesn't list
+    fn kind(ty: Type) -> (bool, bool) {
+        match ty {
+            Type::Album | Type::Artist | Type::Playlist | Type::Show => (true, false),
+            Type::Track | Type::Episode => (false, true),
+            Type::User | Type::Collection | Type::Collectionyourepisodes => (false, false),
+        }
+    }
+
+    // The uri of what was parsed, or the error
+    fn parsed<T: Id>(result: Result<T, IdError>) -> Result<String, IdError> {
+        result.map(|id| id.uri())
+    }
+
+    #[test]
+    fn every_type_parses_to_its_id_or_fails() {
+        for ty in TYPES {
+            let (is_context, is_playable) = kind(ty);
+            let uri = format!("spotify:{ty}:{ID}");
+            let url = format!("https://open.spotify.com/{ty}/{ID}?si=0123456789abcdef");
+            let context = if is_context {
+                Ok(uri.clone())
+            } else {
+                Err(IdError::InvalidType)
+            };
+            let playable = if is_playable {
+                Ok(uri.clone())
+            } else {
+                Err(IdError::InvalidType)
+            };
+
+            assert_eq!(parsed(PlayContextId::from_uri(&uri)), context, "{uri}");
+            assert_eq!(parsed(PlayContextId::from_url(&url)), context, "{url}");
+            assert_eq!(parsed(PlayContextId::from_id_or_uri(ty, ID)), context);
+            assert_eq!(parsed(PlayableId::from_uri(&uri)), playable, "{uri}");
+            assert_eq!(parsed(PlayableId::from_url(&url)), playable, "{url}");
+            assert_eq!(parsed(PlayableId::from_id_or_uri(ty, ID)), playable);
+        }
+    }
+
+    #[test]
+    fn parses_uris() {
+        let uri = format!("spotify:track:{ID}");
+        assert_eq!(parsed(PlayableId::from_uri(&uri)), Ok(uri.clone()));
+        assert_eq!(parsed(PlayableId::from_uri_or_url(&uri)), Ok(uri.clone()));
+        // The type given is only for bare ids
+        assert_eq!(
+            parsed(PlayableId::from_id_or_uri(Type::Episode, &uri)),
+            Ok(uri)
+        );
+
+        for (uri, error) in [
+            (format!("track:{ID}"), IdError::InvalidPrefix),
+            (format!("spotify-track:{ID}"), IdError::InvalidPrefix),
+            (String::from("spotify:track"), IdError::InvalidFormat),
+            (format!("spotify:podcast:{ID}"), IdError::InvalidType),
+            // Playlists of old had their owner in the uri
+            (
+                format!("spotify:user:spotify:playlist:{ID}"),
+                IdError::InvalidType,
+            ),
+            (String::from("spotify:track:not-an-id"), IdError::InvalidId),
+        ] {
+            assert_eq!(parsed(PlayableId::from_uri(&uri)), Err(error), "{uri}");
+        }
+    }
+
+    #[test]
+    fn parses_links() {
+        let uri = format!("spotify:playlist:{ID}");
+        for url in [
+            format!("https://open.spotify.com/playlist/{ID}"),
+            format!("http://open.spotify.com/playlist/{ID}"),
+            format!("open.spotify.com/playlist/{ID}"),
+            format!("https://open.spotify.com/playlist/{ID}?si=0123456789abcdef&pt=1"),
+            format!("https://open.spotify.com/playlist/{ID}#top"),
+            format!("https://open.spotify.com/intl-de/playlist/{ID}?si=0123456789abcdef"),
+        ] {
+            assert_eq!(
+                parsed(PlayContextId::from_url(&url)),
+                Ok(uri.clone()),
+                "{url}"
+            );
+            assert_eq!(
+                parsed(PlayContextId::from_uri_or_url(&url)),
+                Ok(uri.clone())
+            );
+            assert_eq!(
+                parsed(PlayContextId::from_id_or_uri(Type::Album, &url)),
+                Ok(uri.clone())
+            );
+        }
+
+        for (url, error) in [
+            (
+                format!("https://example.com/playlist/{ID}"),
+                IdError::InvalidPrefix,
+            ),
+            (format!("spotify:playlist:{ID}"), IdError::InvalidPrefix),
+            (
+                String::from("https://open.spotify.com/playlist"),
+                IdError::InvalidFormat,
+            ),
+            (
+                format!("https://open.spotify.com/playlist/{ID}/tracks"),
+                IdError::InvalidFormat,
+            ),
+            (
+                String::from("https://open.spotify.com/intl-de"),
+                IdError::InvalidFormat,
+            ),
+            (
+                format!("https://open.spotify.com/podcast/{ID}"),
+                IdError::InvalidType,
+            ),
+            (
+                String::from("https://open.spotify.com/playlist/not-an-id?si=0123456789abcdef"),
+                IdError::InvalidId,
+            ),
+        ] {
+            assert_eq!(parsed(PlayContextId::from_url(&url)), Err(error), "{url}");
+        }
+    }
+
+    #[test]
+    fn containers_become_static() {
+        let id = String::from("0sNOF9WDwhWunNAHPD3Baj");
+        let album_id = AlbumId::from_id(id.as_str()).unwrap();
+        let map = HashMap::from([(album_id.clone(), vec![1u8])]);
+        let map: HashMap<AlbumId<'static>, Vec<u8>> = static_type(map);
+        assert_eq!(map[&album_id.clone().into_static()], [1]);
+
+        let set: HashSet<AlbumId<'static>> = static_type(HashSet::from([album_id.clone()]));
+        assert_eq!(set.len(), 1);
+
+        let tree: BTreeMap<u32, AlbumId<'static>> =
+            static_type(BTreeMap::from([(1u32, album_id.clone())]));
+        assert_eq!(tree[&1].id(), id);
+
+        let name: Cow<'static, str> = static_type(Cow::Borrowed(id.as_str()));
+        assert!(matches!(name, Cow::Owned(_)));
+
+        let tuple: (AlbumId<'static>, u8, String, Option<bool>) =
+            static_type((album_id, 1u8, String::from("a"), Some(true)));
+        assert_eq!(tuple.1, 1);
+        let single: (u8,) = static_type((1u8,));
+        assert_eq!(single, (1,));
+    }
+
+    #[test]
+    fn derives_with_type_parameters() {
+        let id = String::from("4iV5W9uYEdYUVa79Axb7Rh");
+        let track_id = TrackId::from_id(id.as_str()).unwrap();
+
+        let tracks: Tracks<'static, u32> = static_type(Tracks(vec![1u32, 2], track_id.clone()));
+        assert_eq!(tracks, Tracks(vec![1, 2], track_id.clone().into_static()));
+        // A type parameter standing for a borrowed type becomes its static type too
+        let nested: Tracks<'static, TrackId<'static>> =
+            static_type(Tracks(vec![track_id.clone()], track_id.clone()));
+        assert_eq!(nested.0[0].id(), id);
+
+        let show_id = ShowId::from_id(id.as_str()).unwrap();
+        let library: Library<'static, u8, 2> = static_type(Library {
+            counts: HashMap::new(),
+            names: HashSet::from([Cow::Borrowed(id.as_str())]),
+            sorted: BTreeMap::from([(1, (3u8, Some(show_id)))]),
+        });
+        assert!(library.names.contains(id.as_str()));
+        assert_eq!(library.sorted[&1].0, 3);
+
+        let event: Event<'static, u8> = static_type(Event::Play(PlayableId::Track(track_id), 1u8));
+        assert!(matches!(event, Event::Play(PlayableId::Track(_), 1)));
+        let marker: Event<'static, Cow<'static, str>> =
+            static_type(Event::Marker::<Cow<str>>(PhantomData));
+        assert_eq!(marker, Event::Marker(PhantomData));
+        let stop: Event<'static, u8> = static_type(Event::<u8>::Stop);
+        assert_eq!(stop, Event::Stop);
+    }
+}